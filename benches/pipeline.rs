@@ -0,0 +1,352 @@
+// File: benches/pipeline.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 20:42:22
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+#![expect(clippy::unwrap_used)]
+
+use std::{fs::remove_dir_all, hint::black_box, thread::available_parallelism};
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use tokio::runtime::Runtime;
+
+use bifrost::{
+    account::{AccountMeta, TransactionAccount, TransactionContext, Wallet, Writable},
+    crypto::{Keypair, Pubkey},
+    io::{set_vault_path, AccountsHash, Vault},
+    program::{dispatcher::dispatch, memo, system, Context},
+    transaction::{Instruction, Placeholder, Transaction, TransactionTemplate},
+};
+
+const SIGNER_COUNTS: [usize; 4] = [1, 2, 4, 8];
+const BATCH_SIZES: [usize; 3] = [1, 16, 64];
+const AMOUNT: u64 = 1_000;
+const LARGE_ACCOUNT_SIZE: usize = 4 * 1024 * 1024;
+
+fn transfers(signers: &[Keypair], receiver: Pubkey) -> Vec<Instruction> {
+    signers
+        .iter()
+        .map(|signer| system::instruction::transfer(signer.pubkey(), receiver, AMOUNT).unwrap())
+        .collect()
+}
+
+fn signed_transaction(signers: &[Keypair], receiver: Pubkey) -> Transaction {
+    let mut trx = Transaction::new(0);
+    trx.add(&transfers(signers, receiver)).unwrap();
+    signers.iter().for_each(|signer| trx.sign(signer).unwrap());
+    trx
+}
+
+fn signing_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Transaction signing");
+    let receiver = Keypair::generate().pubkey();
+    for signers in SIGNER_COUNTS {
+        let keys = (0..signers)
+            .map(|_| Keypair::generate())
+            .collect::<Vec<_>>();
+        let instructions = transfers(&keys, receiver);
+        group.throughput(Throughput::Elements(1));
+        group.bench_with_input(BenchmarkId::new("build", signers), &signers, |b, _| {
+            b.iter(|| {
+                let mut trx = Transaction::new(0);
+                trx.add(black_box(&instructions)).unwrap();
+                trx
+            });
+        });
+        group.bench_with_input(
+            BenchmarkId::new("build and sign", signers),
+            &signers,
+            |b, _| {
+                b.iter(|| signed_transaction(black_box(&keys), receiver));
+            },
+        );
+    }
+    group.finish();
+}
+
+fn payment(payer: Pubkey, recipient: Pubkey, amount: u64) -> Vec<Instruction> {
+    vec![
+        system::instruction::transfer(payer, recipient, amount).unwrap(),
+        memo::instruction::memo("invoice", &[payer]).unwrap(),
+    ]
+}
+
+fn template_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Transaction templates");
+    let payer = Keypair::generate().pubkey();
+    let recipient = Keypair::generate().pubkey();
+    let placeholder = Placeholder::<Pubkey>::new("recipient");
+    let amount = Placeholder::<u64>::new("amount");
+    let template = TransactionTemplate::new(
+        &payment(payer, placeholder.value(), amount.value()),
+        &[placeholder.into(), amount.into()],
+    )
+    .unwrap();
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("build", |b| {
+        b.iter(|| {
+            let mut trx = Transaction::new(0);
+            trx.add(&payment(payer, black_box(recipient), black_box(AMOUNT)))
+                .unwrap();
+            trx
+        });
+    });
+    group.bench_function("instantiate", |b| {
+        b.iter(|| {
+            template
+                .instantiate(
+                    &[
+                        ("recipient", black_box(recipient).into()),
+                        ("amount", black_box(AMOUNT).into()),
+                    ],
+                    0,
+                )
+                .unwrap()
+        });
+    });
+    group.finish();
+}
+
+fn verification_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Signature verification");
+    let keypair = Keypair::generate();
+    let message = b"a message of reasonable size to sign and verify";
+    let signature = keypair.sign(message);
+    group.bench_function("single signature", |b| {
+        b.iter(|| {
+            signature
+                .verify(&keypair.pubkey(), black_box(message))
+                .unwrap();
+        });
+    });
+
+    let receiver = Keypair::generate().pubkey();
+    for signers in SIGNER_COUNTS {
+        let keys = (0..signers)
+            .map(|_| Keypair::generate())
+            .collect::<Vec<_>>();
+        let trx = signed_transaction(&keys, receiver);
+        group.throughput(Throughput::Elements(signers as u64));
+        group.bench_with_input(BenchmarkId::new("transaction", signers), &trx, |b, trx| {
+            b.iter(|| black_box(trx).is_valid());
+        });
+    }
+    group.finish();
+}
+
+struct Transfer {
+    from: (AccountMeta, Wallet),
+    to: (AccountMeta, Wallet),
+    instruction: Instruction,
+}
+
+fn non_conflicting_transfers(batch: usize) -> Vec<Transfer> {
+    (0..batch)
+        .map(|_| {
+            let from = Keypair::generate().pubkey();
+            let to = Keypair::generate().pubkey();
+            Transfer {
+                from: (
+                    AccountMeta::signing(from, Writable::Yes).unwrap(),
+                    Wallet::new(AMOUNT),
+                ),
+                to: (
+                    AccountMeta::wallet(to, Writable::Yes).unwrap(),
+                    Wallet::default(),
+                ),
+                instruction: system::instruction::transfer(from, to, AMOUNT).unwrap(),
+            }
+        })
+        .collect()
+}
+
+fn execute_transfers(context: &Context, transfers: &mut [Transfer]) {
+    for transfer in transfers {
+        let trx_context = TransactionContext::new(vec![
+            TransactionAccount::new(&transfer.from.0, &mut transfer.from.1),
+            TransactionAccount::new(&transfer.to.0, &mut transfer.to.1),
+        ]);
+        dispatch(
+            transfer.instruction.program(),
+            context,
+            trx_context.accounts(),
+            transfer.instruction.data(),
+        )
+        .unwrap();
+        trx_context.commit();
+    }
+}
+
+fn execution_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Transfer execution");
+    let context = Context::default();
+    for batch in BATCH_SIZES {
+        group.throughput(Throughput::Elements(batch as u64));
+        group.bench_with_input(BenchmarkId::new("serial", batch), &batch, |b, &batch| {
+            b.iter_batched(
+                || non_conflicting_transfers(batch),
+                |mut transfers| {
+                    execute_transfers(&context, &mut transfers);
+                    transfers
+                },
+                BatchSize::SmallInput,
+            );
+        });
+        group.bench_with_input(BenchmarkId::new("parallel", batch), &batch, |b, &batch| {
+            let threads = available_parallelism().map_or(1, usize::from);
+            b.iter_batched(
+                || non_conflicting_transfers(batch),
+                |mut transfers| {
+                    let chunk = batch.div_ceil(threads);
+                    std::thread::scope(|scope| {
+                        for chunk in transfers.chunks_mut(chunk) {
+                            scope.spawn(move || execute_transfers(&Context::default(), chunk));
+                        }
+                    });
+                    transfers
+                },
+                BatchSize::SmallInput,
+            );
+        });
+    }
+    group.finish();
+}
+
+fn vault_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Vault writes");
+    let runtime = Runtime::new().unwrap();
+    let folder = std::env::temp_dir().join("bifrost-bench-vault");
+    if folder.exists() {
+        remove_dir_all(&folder).unwrap();
+    }
+    set_vault_path(&folder).unwrap();
+    let mut vault = runtime.block_on(Vault::load_or_create()).unwrap();
+    let mut slot = 0_u64;
+    for batch in BATCH_SIZES {
+        let accounts = (0..batch)
+            .map(|_| (Keypair::generate().pubkey(), Wallet::new(AMOUNT)))
+            .collect::<Vec<_>>();
+        group.throughput(Throughput::Elements(batch as u64));
+        group.bench_with_input(
+            BenchmarkId::new("save accounts", batch),
+            &accounts,
+            |b, accounts| {
+                b.iter(|| {
+                    // a new slot each time, so the accounts of the previous one are flushed
+                    slot += 1;
+                    runtime.block_on(async {
+                        for (key, wallet) in accounts {
+                            vault.save_account(*key, wallet, slot).await.unwrap();
+                        }
+                    });
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+fn accounts_hash_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Accounts hash");
+    for batch in BATCH_SIZES {
+        let accounts = (0..batch)
+            .map(|_| (Keypair::generate().pubkey(), Wallet::new(AMOUNT)))
+            .collect::<Vec<_>>();
+        let updated = Wallet::new(AMOUNT * 2);
+        group.throughput(Throughput::Elements(batch as u64));
+        group.bench_with_input(
+            BenchmarkId::new("update", batch),
+            &accounts,
+            |b, accounts| {
+                b.iter_batched(
+                    || {
+                        AccountsHash::from_accounts(
+                            accounts.iter().map(|(key, wallet)| (key, wallet)),
+                        )
+                    },
+                    |mut hash| {
+                        accounts
+                            .iter()
+                            .for_each(|(key, wallet)| hash.update(key, wallet, &updated));
+                        hash.root()
+                    },
+                    BatchSize::SmallInput,
+                );
+            },
+        );
+    }
+    group.finish();
+}
+
+fn large_account_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Large account data");
+    let meta = AccountMeta::wallet(Keypair::generate().pubkey(), Writable::Yes).unwrap();
+    let mut committed = Wallet::new(AMOUNT);
+    committed.data = vec![1; LARGE_ACCOUNT_SIZE].into();
+    group.bench_function("read (shared)", |b| {
+        b.iter(|| {
+            let mut wallet = committed.clone();
+            let account = TransactionAccount::new(&meta, &mut wallet);
+            let byte = account.data()[LARGE_ACCOUNT_SIZE - 1];
+            black_box(byte)
+        });
+    });
+    group.bench_function("read (owned copy)", |b| {
+        b.iter(|| {
+            let data = committed.data.to_vec();
+            black_box(data[LARGE_ACCOUNT_SIZE - 1])
+        });
+    });
+    group.bench_function("write", |b| {
+        b.iter(|| {
+            let mut wallet = committed.clone();
+            let account = TransactionAccount::new(&meta, &mut wallet);
+            account.write_data(0, black_box(&[2; 32])).unwrap();
+        });
+    });
+    group.bench_function("write (64 times)", |b| {
+        b.iter(|| {
+            let mut wallet = committed.clone();
+            let account = TransactionAccount::new(&meta, &mut wallet);
+            for offset in (0..64).map(|i| i * 32) {
+                account.write_data(offset, black_box(&[2; 32])).unwrap();
+            }
+        });
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    signing_benchmark,
+    template_benchmark,
+    verification_benchmark,
+    execution_benchmark,
+    vault_benchmark,
+    accounts_hash_benchmark,
+    large_account_benchmark
+);
+criterion_main!(benches);
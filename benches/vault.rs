@@ -0,0 +1,69 @@
+// File: benches/vault.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 20:48:52
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+#![expect(clippy::unwrap_used)]
+
+use std::fs::create_dir_all;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use tokio::runtime::Runtime;
+
+use bifrost::io::{FileWrite, WritePool, DEFAULT_WRITE_WORKERS};
+
+const SHARD_COUNTS: [usize; 3] = [1, 16, 64];
+const SHARD_SIZE: usize = 16 * 1024;
+
+fn commit_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Vault commit");
+    let runtime = Runtime::new().unwrap();
+    let folder = std::env::temp_dir().join("bifrost-bench-commit");
+    create_dir_all(&folder).unwrap();
+    for shards in SHARD_COUNTS {
+        let writes = (0..shards)
+            .map(|shard| FileWrite::new(folder.join(format!("shard-{shard}")), &[1_u8; SHARD_SIZE]))
+            .collect::<Vec<_>>();
+        group.throughput(Throughput::Elements(shards as u64));
+        for workers in [1, DEFAULT_WRITE_WORKERS] {
+            let pool = {
+                let _runtime = runtime.enter();
+                WritePool::new(workers, folder.join("commit"))
+            };
+            group.bench_with_input(
+                BenchmarkId::new(format!("{workers} workers"), shards),
+                &writes,
+                |b, writes| {
+                    b.iter(|| runtime.block_on(pool.commit(writes.clone())).unwrap());
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, commit_benchmark);
+criterion_main!(benches);
@@ -3,7 +3,7 @@
 // Creation date: Saturday 08 February 2025
 // Author: Vincent Berthier <vincent.berthier@posteo.org>
 // -----
-// Last modified: Sunday 09 February 2025 @ 16:52:47
+// Last modified: Thursday 15 October 2026 @ 20:31:20
 // Modified by: Vincent Berthier
 // -----
 // Copyright (c) 2025 <Vincent Berthier>
@@ -36,9 +36,68 @@ use super::types::AccountType;
 #[derive(Debug, Display, From)]
 #[display("during an account operation: {_variant}")]
 pub enum Error {
+    /// A closed account was used as writable by a later instruction.
+    #[display("account '{key}' was closed earlier in the transaction")]
+    #[from(ignore)]
+    AccountClosed {
+        /// Public key of the account
+        key: Pubkey,
+    },
+    /// A delegate tried to spend more than its remaining allowance.
+    #[display("tried to spend {requested} prisms but the allowance is only {remaining}")]
+    AllowanceExceeded {
+        /// The amount the delegate tried to spend.
+        requested: u64,
+        /// The remaining allowance of the delegate.
+        remaining: u64,
+    },
+    /// Tried to write past the end of an account's data.
+    #[display("can't write up to byte {end} of '{key}', its data is {len} bytes long")]
+    DataOutOfBounds {
+        /// Public key of the account
+        key: Pubkey,
+        /// The end of the write.
+        end: usize,
+        /// The length of the data.
+        len: usize,
+    },
+    /// Tried to stop an account from signing when its type doesn't allow it.
+    #[display("account '{key}' of type {kind:?} can't be demoted from signing")]
+    #[from(ignore)]
+    DemoteIncompatibleAccountType {
+        /// Public key of the account
+        key: Pubkey,
+        /// The type of the account.
+        kind: AccountType,
+    },
     /// An operation would have caused an overflow.
     #[display("arithmetic overflow")]
     ArithmeticOverflow,
+    /// A program account was used as writable.
+    #[display("program account '{key}' can't be writable")]
+    #[from(ignore)]
+    ProgramAccountWritable {
+        /// Public key of the program
+        key: Pubkey,
+    },
+    /// Tried to withdraw more prisms than deactivated.
+    #[display("tried to withdraw {requested} prisms but only {deactivated} are deactivated")]
+    #[from(ignore)]
+    InsufficientDeactivatedStake {
+        /// The amount that was requested.
+        requested: u64,
+        /// The amount deactivated.
+        deactivated: u64,
+    },
+    /// Tried to deactivate more prisms than staked.
+    #[display("tried to deactivate {requested} prisms but only {staked} are staked")]
+    #[from(ignore)]
+    InsufficientStake {
+        /// The amount that was requested.
+        requested: u64,
+        /// The amount still staked.
+        staked: u64,
+    },
     /// Invalid key used to create account metadata
     #[display("invalid key use: {} (error: {:?})", key, kind)]
     MetaAccountCreation {
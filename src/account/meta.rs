@@ -3,7 +3,7 @@
 // Creation date: Saturday 08 February 2025
 // Author: Vincent Berthier <vincent.berthier@posteo.org>
 // -----
-// Last modified: Thursday 13 February 2025 @ 09:59:26
+// Last modified: Thursday 15 October 2026 @ 20:18:26
 // Modified by: Vincent Berthier
 // -----
 // Copyright (c) 2025 <Vincent Berthier>
@@ -27,13 +27,17 @@
 // SOFTWARE.
 
 use borsh::{BorshDeserialize, BorshSerialize};
-use tracing::{debug, instrument, warn};
+use tracing::{debug, instrument, trace, warn};
 
-use crate::crypto::Pubkey;
+use crate::{
+    crypto::Pubkey,
+    layout::{DescribeLayout, Extent, Field, Size},
+};
 
 use super::{
     error::ErrorType,
-    types::{AccountType, Writable},
+    privileges::AccountPrivileges,
+    types::{AccountType, Privilege, Writable},
     Error, Result,
 };
 
@@ -42,10 +46,9 @@ use super::{
 pub struct AccountMeta {
     /// The public key of the account.
     key: Pubkey,
-    /// The type of account (important when there's a need to create it)
-    kind: AccountType,
-    /// Whether the account is read-only or writable.
-    writable: Writable,
+    /// The type of the account (important when there's a need to create it)
+    /// and whether it's read-only or writable.
+    privileges: AccountPrivileges,
 }
 
 impl AccountMeta {
@@ -78,8 +81,7 @@ impl AccountMeta {
         Self::check_on_curve(&key)?;
         Ok(Self {
             key,
-            kind: AccountType::Signing,
-            writable,
+            privileges: AccountPrivileges::from_legacy(AccountType::Signing, writable),
         })
     }
 
@@ -112,8 +114,7 @@ impl AccountMeta {
         Self::check_on_curve(&key)?;
         Ok(Self {
             key,
-            kind: AccountType::Wallet,
-            writable,
+            privileges: AccountPrivileges::from_legacy(AccountType::Wallet, writable),
         })
     }
 
@@ -163,8 +164,46 @@ impl AccountMeta {
         }
         Ok(Self {
             key,
-            kind: AccountType::Program,
-            writable: Writable::No,
+            privileges: AccountPrivileges::from_legacy(AccountType::Program, Writable::No),
+        })
+    }
+
+    /// Create metadata for an account derived from a program's seeds.
+    ///
+    /// # Parameters
+    /// * `key` - The public key of the account,
+    /// * `writable` - Whether the account is read-only or writable.
+    ///
+    /// # Returns
+    /// Metadata for a derived account
+    ///
+    /// # Errors
+    /// If the `key` was on the curve.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use bifrost::Error;
+    /// # use bifrost::crypto::Seeds;
+    /// # use bifrost::account::{Writable, AccountMeta};
+    /// let seeds = Seeds::new(&[&b"key1"])?;
+    /// let offcurve = seeds.generate_offcurve()?.0;
+    /// let meta = AccountMeta::derived(offcurve, Writable::Yes)?;
+    /// assert!(meta.is_writable());
+    ///
+    /// # Ok::<(), Error>(())
+    /// ```
+    #[instrument]
+    pub fn derived(key: Pubkey, writable: Writable) -> Result<Self> {
+        debug!("creating new derived meta account");
+        if key.is_oncurve() {
+            return Err(super::Error::MetaAccountCreation {
+                key,
+                kind: ErrorType::NonWalletOnCurve,
+            });
+        }
+        Ok(Self {
+            key,
+            privileges: AccountPrivileges::from_legacy(AccountType::Derived, writable),
         })
     }
 
@@ -195,32 +234,146 @@ impl AccountMeta {
     #[instrument]
     pub fn merge(&mut self, other: &Self) -> Result<()> {
         debug!("merging meta accounts");
-        if !self.kind.is_compatible(other.kind) {
-            warn!("attempted to merge non-compatible accounts");
-            return Err(Error::MergeIncompatibleAccountTypes(self.kind, other.kind));
+        let is_program = self.is_program() || other.is_program();
+        if is_program && (self.is_writable() || other.is_writable()) {
+            warn!("attempted to make a program account writable");
+            return Err(Error::ProgramAccountWritable { key: self.key });
         }
+        let kind = self.kind().merge_result(other.kind()).inspect_err(|_| {
+            warn!("attempted to merge non-compatible accounts");
+        })?;
+        let writable = if self.is_writable() || other.is_writable() {
+            Writable::Yes
+        } else {
+            Writable::No
+        };
+        let fee_payer = self.privileges.is_fee_payer();
+        self.privileges = AccountPrivileges::from_legacy(kind, writable);
+        self.set_fee_payer(fee_payer);
+
+        Ok(())
+    }
 
-        if other.is_writable() {
-            self.writable = Writable::Yes;
+    /// Get a read-only copy of the metadata.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use bifrost::{Error, crypto::Keypair, account::{AccountMeta, Writable}};
+    /// let meta = AccountMeta::wallet(Keypair::generate().pubkey(), Writable::Yes)?;
+    /// assert!(!meta.demote_writable().is_writable());
+    /// # Ok::<(), Error>(())
+    /// ```
+    #[must_use]
+    pub const fn demote_writable(&self) -> Self {
+        Self {
+            privileges: self.privileges.without(AccountPrivileges::WRITABLE),
+            ..*self
         }
+    }
 
-        if other.is_signing() {
-            self.kind = AccountType::Signing;
+    /// Get a non-signing copy of the metadata: a signing account becomes a plain wallet.
+    ///
+    /// # Errors
+    /// If the account is neither a signing account nor a wallet.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use bifrost::{Error, crypto::Keypair, account::{AccountMeta, Writable}};
+    /// let meta = AccountMeta::signing(Keypair::generate().pubkey(), Writable::Yes)?;
+    /// let demoted = meta.demote_signing()?;
+    /// assert!(!demoted.is_signing() && demoted.is_writable());
+    /// # Ok::<(), Error>(())
+    /// ```
+    #[instrument]
+    pub fn demote_signing(&self) -> Result<Self> {
+        debug!("demoting meta account from signing");
+        match self.kind() {
+            AccountType::Signing | AccountType::Wallet => Ok(Self {
+                privileges: self
+                    .privileges
+                    .without(AccountPrivileges::SIGNER.with(AccountPrivileges::FEE_PAYER)),
+                ..*self
+            }),
+            kind @ (AccountType::Program | AccountType::Derived) => {
+                warn!("attempted to demote a {kind:?} account from signing");
+                Err(Error::DemoteIncompatibleAccountType {
+                    key: self.key,
+                    kind,
+                })
+            }
         }
+    }
 
-        Ok(())
+    /// Get a copy of the metadata without a privilege.
+    ///
+    /// # Parameters
+    /// * `privilege` - The privilege removed.
+    ///
+    /// # Errors
+    /// If the privilege is signing and the account is neither a signing account nor a wallet.
+    pub fn demote(&self, privilege: Privilege) -> Result<Self> {
+        match privilege {
+            Privilege::Writable => Ok(self.demote_writable()),
+            Privilege::Signing => self.demote_signing(),
+        }
     }
 
     /// Checks whether the account is a signing one or not.
     #[must_use]
     pub const fn is_signing(&self) -> bool {
-        matches!(self.kind, AccountType::Signing)
+        self.privileges.is_signer()
+    }
+
+    /// Checks whether the account is a program.
+    #[must_use]
+    pub const fn is_program(&self) -> bool {
+        self.privileges.is_executable()
     }
 
     /// Checks whether the account is read-only or writable
     #[must_use]
     pub const fn is_writable(&self) -> bool {
-        matches!(self.writable, Writable::Yes)
+        self.privileges.is_writable()
+    }
+
+    /// Get the class of the account in the canonical order of a message's accounts:
+    /// writable signers, then read-only signers, then the other writable accounts,
+    /// then the other read-only ones, then programs.
+    pub(crate) const fn rank(&self) -> u8 {
+        if self.is_signing() {
+            if self.is_writable() {
+                0
+            } else {
+                1
+            }
+        } else if self.is_program() {
+            4
+        } else if self.is_writable() {
+            2
+        } else {
+            3
+        }
+    }
+
+    /// Get the type of the account.
+    #[must_use]
+    pub const fn kind(&self) -> AccountType {
+        self.privileges.kind()
+    }
+
+    /// Get the privileges of the account.
+    #[must_use]
+    pub const fn privileges(&self) -> AccountPrivileges {
+        self.privileges
+    }
+
+    /// Marks the account as the one paying the fees of its message, or not.
+    pub(crate) const fn set_fee_payer(&mut self, fee_payer: bool) {
+        self.privileges = if fee_payer {
+            self.privileges.with(AccountPrivileges::FEE_PAYER)
+        } else {
+            self.privileges.without(AccountPrivileges::FEE_PAYER)
+        };
     }
 
     /// Get the account's public key
@@ -228,6 +381,102 @@ impl AccountMeta {
     pub const fn key(&self) -> &Pubkey {
         &self.key
     }
+
+    /// Get the same metadata for another account.
+    ///
+    /// # Errors
+    /// If the key isn't on the curve for a wallet or a signer, or if it is for a program
+    /// or a derived account.
+    pub(crate) fn with_key(&self, key: Pubkey) -> Result<Self> {
+        match self.kind() {
+            AccountType::Signing | AccountType::Wallet => Self::check_on_curve(&key)?,
+            AccountType::Program | AccountType::Derived if key.is_oncurve() => {
+                return Err(super::Error::MetaAccountCreation {
+                    key,
+                    kind: ErrorType::NonWalletOnCurve,
+                });
+            }
+            AccountType::Program | AccountType::Derived => {}
+        }
+
+        Ok(Self {
+            key,
+            privileges: self.privileges,
+        })
+    }
+}
+
+/// Deduplicates account metas the way a message does when it compiles instructions.
+///
+/// The later occurrences of an account are merged into its first one: it becomes writable
+/// (or signing) if any of them is. The accounts are then in the canonical order of a message:
+/// the writable signers, the read-only signers, the other writable accounts, the read-only
+/// ones and the programs, each in the order they first appear. The payer of a message built
+/// from the same metas is thus the first account of the normalized list.
+///
+/// # Parameters
+/// * `metas` - The metas, in the order the instructions reference them.
+///
+/// # Errors
+/// If two occurrences of an account can't be merged.
+///
+/// # Example
+/// ```rust
+/// # use bifrost::Error;
+/// # use bifrost::crypto::Keypair;
+/// # use bifrost::account::{normalize, Writable, AccountMeta};
+/// let key = Keypair::generate().pubkey();
+/// let other = Keypair::generate().pubkey();
+/// let metas = [
+///     AccountMeta::wallet(key, Writable::No)?,
+///     AccountMeta::wallet(other, Writable::No)?,
+///     AccountMeta::signing(key, Writable::Yes)?,
+/// ];
+/// let normalized = normalize(&metas)?;
+/// assert_eq!(normalized.len(), 2);
+/// assert!(normalized[0].is_signing() && normalized[0].is_writable());
+///
+/// # Ok::<(), Error>(())
+/// ```
+pub fn normalize(metas: &[AccountMeta]) -> Result<Vec<AccountMeta>> {
+    let mut normalized = Vec::new();
+    for meta in metas {
+        find_or_add(&mut normalized, meta)?;
+    }
+    normalized.sort_by_key(AccountMeta::rank);
+
+    Ok(normalized)
+}
+
+/// Get the position of an account in a list, merging it with the existing entry
+/// or adding it at the end.
+#[instrument(skip_all, fields(key = %meta.key))]
+pub(crate) fn find_or_add(accounts: &mut Vec<AccountMeta>, meta: &AccountMeta) -> Result<usize> {
+    if let Some(idx) = accounts.iter().position(|acc| acc.key == meta.key) {
+        trace!("account was found in position {idx} of the accounts");
+        accounts[idx].merge(meta)?;
+        return Ok(idx);
+    }
+
+    trace!("account wasn’t found in the accounts");
+    accounts.push(*meta);
+    Ok(accounts.len() - 1)
+}
+
+impl DescribeLayout for AccountMeta {
+    const NAME: &'static str = "AccountMeta";
+    const DESCRIPTION: &'static str = "An account referenced by a message.";
+
+    fn fields() -> Vec<Field> {
+        vec![
+            Field::new("key", "Pubkey", Size::Fixed(32)),
+            Field::new("privileges", "AccountPrivileges", Size::Fixed(1)),
+        ]
+    }
+
+    fn extents(&self) -> Vec<Extent> {
+        vec![Extent::Fixed, Extent::Fixed]
+    }
 }
 
 #[cfg(test)]
@@ -283,6 +532,27 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn derived_accounts_must_be_off_curve() -> TestResult {
+        // Given
+        let seeds = Seeds::new(&[&b"key1"])?;
+        let offcurve = seeds.generate_offcurve()?.0;
+        let oncurve = Keypair::generate().pubkey();
+
+        // When
+        let res1 = AccountMeta::derived(offcurve, Writable::Yes)?;
+        let res2 = AccountMeta::derived(oncurve, Writable::Yes);
+
+        // Then
+        assert!(res1.is_writable());
+        assert!(!res1.is_signing());
+        assert_matches!(
+            res2,
+            Err(Error::MetaAccountCreation { kind, .. }) if matches!(kind, ErrorType::NonWalletOnCurve),
+        );
+        Ok(())
+    }
+
     #[test]
     fn accounts_must_be_compatible() -> TestResult {
         // Given
@@ -299,4 +569,95 @@ mod tests {
         assert_matches!(res, Err(Error::MergeIncompatibleAccountTypes(_, _)));
         Ok(())
     }
+
+    #[test]
+    fn program_accounts_cannot_become_writable() -> TestResult {
+        // Given
+        let seeds = Seeds::new(&[&b"key1"])?;
+        let offcurve = seeds.generate_offcurve()?.0;
+        let mut program = AccountMeta::program(offcurve)?;
+        let mut derived = AccountMeta::derived(offcurve, Writable::Yes)?;
+
+        // When
+        let upgrade = program.merge(&derived);
+        let downgrade = derived.merge(&AccountMeta::program(offcurve)?);
+
+        // Then
+        assert_matches!(upgrade, Err(Error::ProgramAccountWritable { key }) if key == offcurve);
+        assert_matches!(downgrade, Err(Error::ProgramAccountWritable { key }) if key == offcurve);
+        assert!(!program.is_writable());
+
+        Ok(())
+    }
+
+    #[test]
+    fn normalize_merges_duplicates_in_canonical_order() -> TestResult {
+        // Given
+        let key1 = Keypair::generate().pubkey();
+        let key2 = Keypair::generate().pubkey();
+        let metas = [
+            AccountMeta::wallet(key1, Writable::No)?,
+            AccountMeta::signing(key2, Writable::No)?,
+            AccountMeta::wallet(key2, Writable::Yes)?,
+            AccountMeta::signing(key1, Writable::No)?,
+        ];
+
+        // When
+        let normalized = normalize(&metas)?;
+
+        // Then
+        assert_eq!(normalized.len(), 2);
+        // the writable signer comes before the read-only one, although it appears later
+        assert_eq!(normalized[0].key(), &key2);
+        assert!(normalized[0].is_signing() && normalized[0].is_writable());
+        assert_eq!(normalized[1].key(), &key1);
+        assert!(normalized[1].is_signing() && !normalized[1].is_writable());
+
+        Ok(())
+    }
+
+    #[test]
+    fn normalize_rejects_incompatible_duplicates() -> TestResult {
+        // Given
+        let offcurve = Seeds::new(&[&b"normalize"])?.generate_offcurve()?.0;
+        let metas = [
+            AccountMeta::program(offcurve)?,
+            AccountMeta::derived(offcurve, Writable::Yes)?,
+        ];
+
+        // When
+        let res = normalize(&metas);
+
+        // Then
+        assert_matches!(res, Err(Error::ProgramAccountWritable { key }) if key == offcurve);
+
+        Ok(())
+    }
+
+    #[test]
+    fn demotions_remove_privileges() -> TestResult {
+        // Given
+        let key = Keypair::generate().pubkey();
+        let signing = AccountMeta::signing(key, Writable::Yes)?;
+        let offcurve = Seeds::new(&[&b"demote"])?.generate_offcurve()?.0;
+        let derived = AccountMeta::derived(offcurve, Writable::Yes)?;
+
+        // When
+        let read_only = signing.demote(Privilege::Writable)?;
+        let wallet = signing.demote(Privilege::Signing)?;
+        let derived_read_only = derived.demote_writable();
+        let res = derived.demote_signing();
+
+        // Then
+        assert!(read_only.is_signing() && !read_only.is_writable());
+        assert!(!wallet.is_signing() && wallet.is_writable());
+        assert!(!wallet.demote_signing()?.is_signing());
+        assert!(!derived_read_only.is_writable());
+        assert_matches!(
+            res,
+            Err(Error::DemoteIncompatibleAccountType { key: demoted_key, kind: AccountType::Derived }) if demoted_key == offcurve
+        );
+
+        Ok(())
+    }
 }
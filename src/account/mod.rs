@@ -3,7 +3,7 @@
 // Creation date: Saturday 08 February 2025
 // Author: Vincent Berthier <vincent.berthier@posteo.org>
 // -----
-// Last modified: Wednesday 12 February 2025 @ 22:16:56
+// Last modified: Thursday 15 October 2026 @ 20:31:20
 // Modified by: Vincent Berthier
 // -----
 // Copyright (c) 2025 <Vincent Berthier>
@@ -29,14 +29,24 @@
 mod error;
 mod meta;
 mod onchain;
+mod privileges;
 mod transaction;
+mod transaction_context;
 mod types;
 
 pub use error::Error;
-pub use meta::AccountMeta;
-pub use onchain::wallet::Wallet;
+pub(crate) use meta::find_or_add;
+pub use meta::{normalize, AccountMeta};
+pub use onchain::{
+    delegation::Delegation,
+    escrow::Escrow,
+    stake::{Stake, DELEGATED_STAKE_SIZE},
+    wallet::Wallet,
+};
+pub use privileges::AccountPrivileges;
 pub use transaction::{next_account, TransactionAccount};
-pub use types::Writable;
+pub use transaction_context::{Checkpoint, TransactionContext};
+pub use types::{AccountType, Privilege, Writable};
 
 /// The result for the accounts module.
 pub type Result<T> = core::result::Result<T, Error>;
@@ -0,0 +1,172 @@
+// File: src/account/onchain/delegation.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 14:48:44
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use tracing::{debug, instrument, warn};
+
+use crate::{
+    account::{Error, Result},
+    crypto::Pubkey,
+    program::Epoch,
+};
+
+/// Limited spending rights over an account granted to another key.
+#[derive(Clone, Copy, Debug, BorshSerialize, BorshDeserialize, PartialEq, Eq)]
+pub struct Delegation {
+    /// The key allowed to spend from the account.
+    pub delegate: Pubkey,
+    /// The maximum amount the delegate can spend (per epoch if the delegation is recurring).
+    pub limit: u64,
+    /// The amount the delegate can still spend.
+    pub remaining: u64,
+    /// Whether the allowance is restored at each new epoch.
+    pub recurring: bool,
+    /// The epoch during which the allowance was last restored.
+    pub epoch: u64,
+}
+
+impl Delegation {
+    /// Creates a new delegation with its full allowance available.
+    ///
+    /// # Parameters
+    /// * `delegate` - The key allowed to spend from the account,
+    /// * `limit` - The maximum amount the delegate can spend,
+    /// * `recurring` - Whether the allowance is restored at each new epoch,
+    /// * `epoch` - The current epoch.
+    #[must_use]
+    pub fn new<E>(delegate: Pubkey, limit: u64, recurring: bool, epoch: E) -> Self
+    where
+        E: Into<Epoch>,
+    {
+        Self {
+            delegate,
+            limit,
+            remaining: limit,
+            recurring,
+            epoch: epoch.into().get(),
+        }
+    }
+
+    /// Restores the allowance if the delegation is recurring and a new epoch started.
+    ///
+    /// # Parameters
+    /// * `epoch` - The current epoch.
+    #[instrument(skip(self, epoch))]
+    pub fn refresh<E>(&mut self, epoch: E)
+    where
+        E: Into<Epoch>,
+    {
+        let epoch = epoch.into().get();
+        if self.recurring && epoch > self.epoch {
+            debug!(from = self.epoch, "new epoch: restoring the allowance");
+            self.remaining = self.limit;
+            self.epoch = epoch;
+        }
+    }
+
+    /// Spends part of the allowance.
+    ///
+    /// # Parameters
+    /// * `amount` - The amount the delegate wants to spend.
+    ///
+    /// # Errors
+    /// If the amount is above the remaining allowance.
+    #[instrument(skip(self))]
+    pub fn spend(&mut self, amount: u64) -> Result<()> {
+        debug!(remaining = self.remaining, "spending allowance");
+        self.remaining = self.remaining.checked_sub(amount).ok_or_else(|| {
+            warn!("the delegate tried to spend more than its allowance");
+            Error::AllowanceExceeded {
+                requested: amount,
+                remaining: self.remaining,
+            }
+        })?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+
+    use std::assert_matches::assert_matches;
+
+    use test_log::test;
+
+    use crate::crypto::Keypair;
+
+    use super::*;
+    type TestResult = core::result::Result<(), Box<dyn core::error::Error>>;
+
+    #[test]
+    fn cannot_spend_above_allowance() -> TestResult {
+        // Given
+        let mut delegation = Delegation::new(Keypair::generate().pubkey(), 1_000, false, 0);
+        delegation.spend(600)?;
+
+        // When
+        let res = delegation.spend(500);
+
+        // Then
+        assert_matches!(
+            res,
+            Err(Error::AllowanceExceeded {
+                requested: 500,
+                remaining: 400
+            })
+        );
+        assert_eq!(delegation.remaining, 400);
+
+        Ok(())
+    }
+
+    #[test]
+    fn only_recurring_allowances_are_restored() -> TestResult {
+        // Given
+        let delegate = Keypair::generate().pubkey();
+        let mut recurring = Delegation::new(delegate, 1_000, true, 3);
+        let mut one_shot = Delegation::new(delegate, 1_000, false, 3);
+        recurring.spend(1_000)?;
+        one_shot.spend(1_000)?;
+
+        // When
+        recurring.refresh(3);
+        one_shot.refresh(4);
+        let same_epoch = recurring.remaining;
+        recurring.refresh(4);
+
+        // Then
+        assert_eq!(same_epoch, 0);
+        assert_eq!(recurring.remaining, 1_000);
+        assert_eq!(recurring.epoch, 4);
+        assert_eq!(one_shot.remaining, 0);
+
+        Ok(())
+    }
+}
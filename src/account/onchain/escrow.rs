@@ -0,0 +1,66 @@
+// File: src/account/onchain/escrow.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 11:48:21
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::crypto::Pubkey;
+
+/// Prisms locked until a given slot for a recipient.
+#[derive(Clone, Copy, Debug, BorshSerialize, BorshDeserialize, PartialEq, Eq)]
+pub struct Escrow {
+    /// The account that locked the prisms.
+    pub sender: Pubkey,
+    /// The account allowed to claim the prisms.
+    pub recipient: Pubkey,
+    /// The first slot at which the prisms can be claimed.
+    pub unlock_slot: u64,
+    /// The first slot at which the sender can no longer cancel the escrow.
+    pub cancel_deadline: u64,
+    /// The amount of prisms locked.
+    pub amount: u64,
+}
+
+impl Escrow {
+    /// Checks whether the escrow can be claimed during the given slot.
+    ///
+    /// # Parameters
+    /// * `slot` - The current slot.
+    #[must_use]
+    pub const fn is_unlocked(&self, slot: u64) -> bool {
+        slot >= self.unlock_slot
+    }
+
+    /// Checks whether the escrow can still be cancelled during the given slot.
+    ///
+    /// # Parameters
+    /// * `slot` - The current slot.
+    #[must_use]
+    pub const fn is_cancellable(&self, slot: u64) -> bool {
+        slot < self.cancel_deadline
+    }
+}
@@ -1 +1,4 @@
+pub mod delegation;
+pub mod escrow;
+pub mod stake;
 pub mod wallet;
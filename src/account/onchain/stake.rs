@@ -0,0 +1,293 @@
+// File: src/account/onchain/stake.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 20:31:20
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use tracing::{debug, instrument, warn};
+
+use crate::{
+    account::{Error, Result},
+    crypto::Pubkey,
+    program::Epoch,
+};
+
+/// The size of the data of a stake account once delegated.
+pub const DELEGATED_STAKE_SIZE: usize = 113;
+
+/// Prisms locked in an account and delegated to a validator.
+///
+/// The account's address is derived from its staker and index, so only the stake
+/// program can move its prisms. Changes to the delegation only take effect at the
+/// start of the next epoch.
+#[derive(Clone, Copy, Debug, Default, BorshSerialize, BorshDeserialize, PartialEq, Eq)]
+pub struct Stake {
+    /// The account allowed to delegate, deactivate and withdraw the stake.
+    pub staker: Pubkey,
+    /// The index of the stake among those of its staker.
+    pub index: u64,
+    /// The amount of prisms staked.
+    pub amount: u64,
+    /// The identity of the validator the stake is delegated to.
+    pub validator: Option<Pubkey>,
+    /// The epoch during which the stake was delegated.
+    pub activation_epoch: u64,
+    /// The amount of prisms whose deactivation already took effect.
+    pub deactivated: u64,
+    /// The amount of prisms being deactivated.
+    pub deactivating: u64,
+    /// The epoch during which the last deactivation was requested.
+    pub deactivation_epoch: u64,
+}
+
+impl Stake {
+    /// Creates a new undelegated stake.
+    ///
+    /// # Parameters
+    /// * `staker` - The account allowed to manage the stake,
+    /// * `index` - The index of the stake among those of its staker,
+    /// * `amount` - The amount of prisms staked.
+    #[must_use]
+    pub const fn new(staker: Pubkey, index: u64, amount: u64) -> Self {
+        Self {
+            staker,
+            index,
+            amount,
+            validator: None,
+            activation_epoch: 0,
+            deactivated: 0,
+            deactivating: 0,
+            deactivation_epoch: 0,
+        }
+    }
+
+    /// Delegates the stake to a validator, starting with the next epoch.
+    ///
+    /// # Parameters
+    /// * `validator` - The identity of the validator,
+    /// * `epoch` - The current epoch.
+    #[instrument(skip(self, epoch))]
+    pub fn delegate<E>(&mut self, validator: Pubkey, epoch: E)
+    where
+        E: Into<Epoch>,
+    {
+        debug!("delegating stake");
+        self.validator = Some(validator);
+        self.activation_epoch = epoch.into().get();
+    }
+
+    /// Deactivates part of the stake, starting with the next epoch.
+    ///
+    /// # Parameters
+    /// * `amount` - The amount of prisms to deactivate,
+    /// * `epoch` - The current epoch.
+    ///
+    /// # Errors
+    /// If the amount is above what is still staked.
+    #[instrument(skip(self, epoch))]
+    pub fn deactivate<E>(&mut self, amount: u64, epoch: E) -> Result<()>
+    where
+        E: Into<Epoch>,
+    {
+        debug!("deactivating stake");
+        let epoch = epoch.into().get();
+        self.settle(epoch);
+        let staked = self.amount - self.deactivated - self.deactivating;
+        if amount > staked {
+            warn!(staked, "tried to deactivate more than the staked amount");
+            return Err(Error::InsufficientStake {
+                requested: amount,
+                staked,
+            });
+        }
+        self.deactivating += amount;
+        self.deactivation_epoch = epoch;
+
+        Ok(())
+    }
+
+    /// Withdraws prisms whose deactivation already took effect.
+    ///
+    /// # Parameters
+    /// * `amount` - The amount of prisms to withdraw,
+    /// * `epoch` - The current epoch.
+    ///
+    /// # Errors
+    /// If the amount is above what is deactivated.
+    #[instrument(skip(self, epoch))]
+    pub fn withdraw<E>(&mut self, amount: u64, epoch: E) -> Result<()>
+    where
+        E: Into<Epoch>,
+    {
+        debug!("withdrawing stake");
+        self.settle(epoch.into().get());
+        if amount > self.deactivated {
+            warn!(
+                deactivated = self.deactivated,
+                "tried to withdraw more than the deactivated amount"
+            );
+            return Err(Error::InsufficientDeactivatedStake {
+                requested: amount,
+                deactivated: self.deactivated,
+            });
+        }
+        self.deactivated -= amount;
+        self.amount -= amount;
+
+        Ok(())
+    }
+
+    /// Counts the prisms being deactivated as deactivated once their epoch is over.
+    const fn settle(&mut self, epoch: u64) {
+        if epoch > self.deactivation_epoch {
+            self.deactivated += self.deactivating;
+            self.deactivating = 0;
+        }
+    }
+
+    /// Get the amount of prisms actively staked during an epoch.
+    ///
+    /// # Parameters
+    /// * `epoch` - The epoch to consider.
+    #[must_use]
+    pub fn active<E>(&self, epoch: E) -> u64
+    where
+        E: Into<Epoch>,
+    {
+        let epoch = epoch.into().get();
+        if self.validator.is_none() || epoch <= self.activation_epoch {
+            return 0;
+        }
+        if epoch > self.deactivation_epoch {
+            self.amount - self.deactivated - self.deactivating
+        } else {
+            self.amount - self.deactivated
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+
+    use std::assert_matches::assert_matches;
+
+    use test_log::test;
+
+    use crate::crypto::Keypair;
+
+    use super::*;
+    type TestResult = core::result::Result<(), Box<dyn core::error::Error>>;
+
+    #[test]
+    fn delegated_stake_size() -> TestResult {
+        // Given
+        let mut stake = Stake::new(Keypair::generate().pubkey(), 0, 1_000);
+
+        // When
+        stake.delegate(Keypair::generate().pubkey(), 0);
+
+        // Then
+        assert_eq!(borsh::to_vec(&stake)?.len(), DELEGATED_STAKE_SIZE);
+
+        Ok(())
+    }
+
+    #[test]
+    fn stake_activates_at_next_epoch() {
+        // Given
+        let mut stake = Stake::new(Keypair::generate().pubkey(), 0, 1_000);
+
+        // When
+        stake.delegate(Keypair::generate().pubkey(), 3);
+
+        // Then
+        assert_eq!(stake.active(3), 0);
+        assert_eq!(stake.active(4), 1_000);
+    }
+
+    #[test]
+    fn partial_deactivation() -> TestResult {
+        // Given
+        let mut stake = Stake::new(Keypair::generate().pubkey(), 0, 1_000);
+        stake.delegate(Keypair::generate().pubkey(), 0);
+
+        // When
+        stake.deactivate(400, 2)?;
+        let (during, after) = (stake.active(2), stake.active(3));
+        let res = stake.deactivate(700, 3);
+        stake.deactivate(100, 3)?;
+
+        // Then
+        assert_eq!(during, 1_000);
+        assert_eq!(after, 600);
+        assert_matches!(
+            res,
+            Err(Error::InsufficientStake {
+                requested: 700,
+                staked: 600
+            })
+        );
+        assert_eq!(stake.active(3), 600);
+        assert_eq!(stake.active(4), 500);
+
+        Ok(())
+    }
+
+    #[test]
+    fn only_deactivated_stake_is_withdrawn() -> TestResult {
+        // Given
+        let mut stake = Stake::new(Keypair::generate().pubkey(), 0, 1_000);
+        stake.delegate(Keypair::generate().pubkey(), 0);
+        stake.deactivate(400, 2)?;
+
+        // When
+        let early = stake.withdraw(400, 2);
+        let too_much = stake.withdraw(401, 3);
+        stake.withdraw(300, 3)?;
+
+        // Then
+        assert_matches!(
+            early,
+            Err(Error::InsufficientDeactivatedStake {
+                requested: 400,
+                deactivated: 0
+            })
+        );
+        assert_matches!(
+            too_much,
+            Err(Error::InsufficientDeactivatedStake {
+                requested: 401,
+                deactivated: 400
+            })
+        );
+        assert_eq!(stake.amount, 700);
+        assert_eq!(stake.deactivated, 100);
+        assert_eq!(stake.active(3), 600);
+
+        Ok(())
+    }
+}
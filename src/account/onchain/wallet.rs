@@ -1,8 +1,46 @@
+use std::sync::Arc;
+
 use borsh::{BorshDeserialize, BorshSerialize};
 
+use crate::layout::{DescribeLayout, Extent, Field, Size};
+
 /// A wallet as saved on the chain
-#[derive(Copy, Clone, Debug, Default, BorshSerialize, BorshDeserialize, PartialEq, Eq)]
+#[derive(Clone, Debug, Default, BorshSerialize, BorshDeserialize, PartialEq, Eq)]
 pub struct Wallet {
     /// Number of prisms on the wallet.
     pub prisms: u64,
+    /// Data stored on the account (interpreted by the program managing it).
+    ///
+    /// The data is shared between the copies of the wallet, cloning it is cheap.
+    pub data: Arc<[u8]>,
+}
+
+impl Wallet {
+    /// Creates a wallet holding the given amount of prisms and no data.
+    ///
+    /// # Parameters
+    /// * `prisms` - The number of prisms on the wallet.
+    #[must_use]
+    pub fn new(prisms: u64) -> Self {
+        Self {
+            prisms,
+            data: Arc::default(),
+        }
+    }
+}
+
+impl DescribeLayout for Wallet {
+    const NAME: &'static str = "Wallet";
+    const DESCRIPTION: &'static str = "An account, as appended to the account files of the vault.";
+
+    fn fields() -> Vec<Field> {
+        vec![
+            Field::new("prisms", "u64", Size::Fixed(8)),
+            Field::new("data", "Arc<[u8]>", Size::items(1)),
+        ]
+    }
+
+    fn extents(&self) -> Vec<Extent> {
+        vec![Extent::Fixed, Extent::Count(self.data.len())]
+    }
 }
@@ -0,0 +1,300 @@
+// File: src/account/privileges.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 14:59:52
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::fmt::{Debug, Formatter};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use super::types::{AccountType, Writable};
+
+/// The privileges of an account in a compiled message.
+///
+/// They're encoded as a single byte, each privilege being a bit.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, BorshSerialize, BorshDeserialize)]
+pub struct AccountPrivileges(u8);
+
+impl AccountPrivileges {
+    /// The account signs the transaction.
+    pub const SIGNER: Self = Self(1);
+    /// The account can be modified.
+    pub const WRITABLE: Self = Self(1 << 1);
+    /// The account pays the fees of the transaction.
+    pub const FEE_PAYER: Self = Self(1 << 2);
+    /// The account holds a program.
+    pub const EXECUTABLE: Self = Self(1 << 3);
+    /// The address of the account is derived from a program's seeds.
+    pub const DERIVED: Self = Self(1 << 4);
+    /// Every known privilege.
+    const ALL: u8 = 0b1_1111;
+    /// The privileges giving the type of the account, at most one of them can be set.
+    const KINDS: [Self; 3] = [Self::SIGNER, Self::EXECUTABLE, Self::DERIVED];
+
+    /// Get the privileges of an account built from its legacy type and writability.
+    ///
+    /// # Parameters
+    /// * `kind` - The type of the account,
+    /// * `writable` - Whether the account is read-only or writable.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use bifrost::account::{AccountPrivileges, AccountType, Writable};
+    /// let privileges = AccountPrivileges::from_legacy(AccountType::Signing, Writable::Yes);
+    /// assert!(privileges.is_signer() && privileges.is_writable());
+    /// assert_eq!(privileges.kind(), AccountType::Signing);
+    /// ```
+    #[must_use]
+    pub const fn from_legacy(kind: AccountType, writable: Writable) -> Self {
+        let kind = match kind {
+            AccountType::Program => Self::EXECUTABLE,
+            AccountType::Signing => Self::SIGNER,
+            AccountType::Wallet => Self(0),
+            AccountType::Derived => Self::DERIVED,
+        };
+        match writable {
+            Writable::Yes => kind.with(Self::WRITABLE),
+            Writable::No => kind,
+        }
+    }
+
+    /// Get the privileges from their encoding.
+    ///
+    /// # Returns
+    /// The privileges, or `None` if an unknown bit is set.
+    #[must_use]
+    pub const fn from_bits(bits: u8) -> Option<Self> {
+        if bits & !Self::ALL != 0 {
+            return None;
+        }
+        Some(Self(bits))
+    }
+
+    /// Get the encoding of the privileges.
+    #[must_use]
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// Checks whether all the given privileges are granted.
+    ///
+    /// # Parameters
+    /// * `other` - The privileges to look for.
+    #[must_use]
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Get the privileges with others granted.
+    ///
+    /// # Parameters
+    /// * `other` - The privileges to grant.
+    #[must_use]
+    pub const fn with(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    /// Get the privileges with others removed.
+    ///
+    /// # Parameters
+    /// * `other` - The privileges to remove.
+    #[must_use]
+    pub const fn without(self, other: Self) -> Self {
+        Self(self.0 & !other.0)
+    }
+
+    /// Checks whether the account signs the transaction.
+    #[must_use]
+    pub const fn is_signer(self) -> bool {
+        self.contains(Self::SIGNER)
+    }
+
+    /// Checks whether the account can be modified.
+    #[must_use]
+    pub const fn is_writable(self) -> bool {
+        self.contains(Self::WRITABLE)
+    }
+
+    /// Checks whether the account pays the fees of the transaction.
+    #[must_use]
+    pub const fn is_fee_payer(self) -> bool {
+        self.contains(Self::FEE_PAYER)
+    }
+
+    /// Checks whether the account holds a program.
+    #[must_use]
+    pub const fn is_executable(self) -> bool {
+        self.contains(Self::EXECUTABLE)
+    }
+
+    /// Checks whether the address of the account is derived from a program's seeds.
+    #[must_use]
+    pub const fn is_derived(self) -> bool {
+        self.contains(Self::DERIVED)
+    }
+
+    /// Get the legacy type of the account.
+    #[must_use]
+    pub const fn kind(self) -> AccountType {
+        if self.is_executable() {
+            AccountType::Program
+        } else if self.is_signer() {
+            AccountType::Signing
+        } else if self.is_derived() {
+            AccountType::Derived
+        } else {
+            AccountType::Wallet
+        }
+    }
+
+    /// Checks that the privileges make sense together: no unknown bit,
+    /// a single type of account, and only a signer paying the fees.
+    #[must_use]
+    pub fn is_consistent(self) -> bool {
+        let kinds = Self::KINDS
+            .iter()
+            .filter(|&&kind| self.contains(kind))
+            .count();
+        self.0 & !Self::ALL == 0 && kinds <= 1 && (!self.is_fee_payer() || self.is_signer())
+    }
+}
+
+impl Debug for AccountPrivileges {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let names = [
+            (Self::SIGNER, "SIGNER"),
+            (Self::WRITABLE, "WRITABLE"),
+            (Self::FEE_PAYER, "FEE_PAYER"),
+            (Self::EXECUTABLE, "EXECUTABLE"),
+            (Self::DERIVED, "DERIVED"),
+        ]
+        .into_iter()
+        .filter(|(flag, _)| self.contains(*flag))
+        .map(|(_, name)| name)
+        .collect::<Vec<_>>();
+        write!(f, "AccountPrivileges({})", names.join(" | "))
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+    type TestResult = core::result::Result<(), Box<dyn core::error::Error>>;
+
+    #[test]
+    fn every_legacy_combination_maps_to_its_privileges() -> TestResult {
+        // Given
+        let cases = [
+            (
+                AccountType::Program,
+                Writable::No,
+                AccountPrivileges::EXECUTABLE,
+            ),
+            (
+                AccountType::Program,
+                Writable::Yes,
+                AccountPrivileges::EXECUTABLE.with(AccountPrivileges::WRITABLE),
+            ),
+            (
+                AccountType::Signing,
+                Writable::No,
+                AccountPrivileges::SIGNER,
+            ),
+            (
+                AccountType::Signing,
+                Writable::Yes,
+                AccountPrivileges::SIGNER.with(AccountPrivileges::WRITABLE),
+            ),
+            (
+                AccountType::Wallet,
+                Writable::No,
+                AccountPrivileges::default(),
+            ),
+            (
+                AccountType::Wallet,
+                Writable::Yes,
+                AccountPrivileges::WRITABLE,
+            ),
+            (
+                AccountType::Derived,
+                Writable::No,
+                AccountPrivileges::DERIVED,
+            ),
+            (
+                AccountType::Derived,
+                Writable::Yes,
+                AccountPrivileges::DERIVED.with(AccountPrivileges::WRITABLE),
+            ),
+        ];
+
+        for (kind, writable, expected) in cases {
+            // When
+            let privileges = AccountPrivileges::from_legacy(kind, writable);
+
+            // Then
+            assert_eq!(privileges, expected, "{kind:?} / {writable:?}");
+            assert_eq!(privileges.kind(), kind);
+            assert_eq!(privileges.is_writable(), matches!(writable, Writable::Yes));
+            assert_eq!(privileges.is_signer(), kind == AccountType::Signing);
+            assert_eq!(privileges.is_executable(), kind == AccountType::Program);
+            assert!(!privileges.is_fee_payer());
+            assert!(privileges.is_consistent());
+            assert_eq!(borsh::to_vec(&privileges)?, vec![expected.bits()]);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn inconsistent_privileges_are_detected() {
+        // Given
+        let payer = AccountPrivileges::SIGNER
+            .with(AccountPrivileges::WRITABLE)
+            .with(AccountPrivileges::FEE_PAYER);
+        let signing_program = AccountPrivileges::SIGNER.with(AccountPrivileges::EXECUTABLE);
+        let derived_program = AccountPrivileges::DERIVED.with(AccountPrivileges::EXECUTABLE);
+        let wallet_payer = AccountPrivileges::FEE_PAYER;
+
+        // When
+        let unknown = AccountPrivileges::from_bits(1 << 5);
+        let decoded = AccountPrivileges::from_bits(payer.bits());
+
+        // Then
+        assert!(payer.is_consistent());
+        assert!(!signing_program.is_consistent());
+        assert!(!derived_program.is_consistent());
+        assert!(!wallet_payer.is_consistent());
+        assert_eq!(unknown, None);
+        assert_eq!(decoded, Some(payer));
+        assert_eq!(
+            format!("{payer:?}"),
+            "AccountPrivileges(SIGNER | WRITABLE | FEE_PAYER)"
+        );
+    }
+}
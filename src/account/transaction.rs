@@ -3,7 +3,7 @@
 // Creation date: Thursday 13 February 2025
 // Author: Vincent Berthier <vincent.berthier@posteo.org>
 // -----
-// Last modified: Thursday 13 February 2025 @ 09:45:33
+// Last modified: Thursday 15 October 2026 @ 20:24:13
 // Modified by: Vincent Berthier
 // -----
 // Copyright (c) 2025 <Vincent Berthier>
@@ -26,13 +26,24 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use std::{cell::RefCell, rc::Rc};
+use std::{
+    cell::{Cell, Ref, RefCell},
+    rc::Rc,
+    sync::Arc,
+};
 
-use tracing::{debug, instrument};
+use tracing::{debug, instrument, trace};
 
 use crate::crypto::Pubkey;
 
-use super::{AccountMeta, Error, Result, Wallet};
+use super::{AccountMeta, AccountPrivileges, Error, Result, Wallet};
+
+/// A modification of an account, with the value it replaced.
+enum Undo {
+    Prisms(u64),
+    Data(Arc<[u8]>),
+    Closed,
+}
 
 /// Stores all data regarding an account needed by an instruction
 /// to allow it to access or modify its data.
@@ -40,11 +51,14 @@ use super::{AccountMeta, Error, Result, Wallet};
 pub struct TransactionAccount<'a> {
     /// The public key of the account
     pub key: Pubkey,
-    /// Is the account writable or read only.
-    pub readonly: bool,
-    /// Is the account signing the transaction or not.
-    pub is_signer: bool,
-    prisms: Rc<RefCell<&'a mut u64>>,
+    /// The privileges of the account: whether it signs the transaction, is writable…
+    pub privileges: AccountPrivileges,
+    account: Rc<RefCell<&'a mut Wallet>>,
+    closed: Rc<Cell<bool>>,
+    journal: Rc<RefCell<Vec<Undo>>>,
+    /// The length of the journal when its last entry is a copy of the data taken by
+    /// [`TransactionAccount::write_data`], which the next writes don't need to copy again.
+    snapshot: Rc<Cell<Option<usize>>>,
 }
 
 impl<'a> TransactionAccount<'a> {
@@ -57,7 +71,7 @@ impl<'a> TransactionAccount<'a> {
     /// # Example
     /// ```rust
     /// # use bifrost::{account::{AccountMeta, Wallet, Writable, TransactionAccount}, crypto::Keypair, Error};
-    /// let mut wallet = Wallet { prisms: 1_000 };
+    /// let mut wallet = Wallet::new(1_000);
     /// let key = Keypair::generate().pubkey();
     /// let meta = AccountMeta::wallet(key, Writable::Yes)?;
     /// let info = TransactionAccount::new(&meta, &mut wallet);
@@ -69,26 +83,123 @@ impl<'a> TransactionAccount<'a> {
         debug!("creating new TransactionAccount for {}", meta.key());
         Self {
             key: *meta.key(),
-            readonly: !meta.is_writable(),
-            is_signer: meta.is_signing(),
-            prisms: Rc::new(RefCell::new(&mut account.prisms)),
+            privileges: meta.privileges(),
+            account: Rc::new(RefCell::new(account)),
+            closed: Rc::new(Cell::new(false)),
+            journal: Rc::new(RefCell::new(Vec::new())),
+            snapshot: Rc::new(Cell::new(None)),
         }
     }
 
+    /// Get the number of prisms currently on the account.
+    #[must_use]
+    pub fn prisms(&self) -> u64 {
+        self.account.borrow().prisms
+    }
+
     #[instrument(skip(self))]
     fn set_prisms(&self, amount: u64) -> Result<()> {
-        debug!(
-            "setting prisms to {amount} (from {})",
-            *self.prisms.borrow()
-        );
-        if self.readonly {
+        debug!("setting prisms to {amount} (from {})", self.prisms());
+        if !self.privileges.is_writable() {
+            return Err(Error::ModificationOfReadOnlyAccount { key: self.key });
+        }
+        let previous = core::mem::replace(&mut self.account.borrow_mut().prisms, amount);
+        self.journal.borrow_mut().push(Undo::Prisms(previous));
+
+        Ok(())
+    }
+
+    /// Get the data stored on the account.
+    #[must_use]
+    pub fn data(&self) -> Ref<'_, [u8]> {
+        Ref::map(self.account.borrow(), |account| &*account.data)
+    }
+
+    /// Replaces the data stored on the account.
+    ///
+    /// # Parameters
+    /// * `data` - the new data of the account,
+    ///
+    /// # Errors
+    /// If the account is read only.
+    #[instrument(skip_all, fields(key = %self.key, len = data.len()))]
+    pub fn set_data(&self, data: Vec<u8>) -> Result<()> {
+        debug!("setting account data");
+        if !self.privileges.is_writable() {
             return Err(Error::ModificationOfReadOnlyAccount { key: self.key });
         }
-        **self.prisms.borrow_mut() = amount;
+        let previous = core::mem::replace(&mut self.account.borrow_mut().data, data.into());
+        self.journal.borrow_mut().push(Undo::Data(previous));
 
         Ok(())
     }
 
+    /// Overwrites part of the data stored on the account.
+    ///
+    /// The data is only copied if it's shared with another copy of the account
+    /// (such as the one kept to roll the modification back): consecutive writes
+    /// since the last checkpoint share a single copy to roll them back.
+    ///
+    /// # Parameters
+    /// * `offset` - Where the bytes are written in the data,
+    /// * `bytes` - The bytes to write.
+    ///
+    /// # Errors
+    /// If the account is read only or if the bytes don't fit in the data.
+    #[instrument(skip_all, fields(key = %self.key, offset, len = bytes.len()))]
+    pub fn write_data(&self, offset: usize, bytes: &[u8]) -> Result<()> {
+        debug!("writing account data");
+        if !self.privileges.is_writable() {
+            return Err(Error::ModificationOfReadOnlyAccount { key: self.key });
+        }
+        let mut account = self.account.borrow_mut();
+        let len = account.data.len();
+        let range = offset
+            .checked_add(bytes.len())
+            .filter(|end| *end <= len)
+            .map(|end| offset..end)
+            .ok_or_else(|| Error::DataOutOfBounds {
+                key: self.key,
+                end: offset.saturating_add(bytes.len()),
+                len,
+            })?;
+        let mut journal = self.journal.borrow_mut();
+        if self.snapshot.get() == Some(journal.len()) {
+            trace!("the data was already saved since the last checkpoint");
+        } else {
+            journal.push(Undo::Data(Arc::clone(&account.data)));
+            self.snapshot.set(Some(journal.len()));
+        }
+        Arc::make_mut(&mut account.data)[range].copy_from_slice(bytes);
+
+        Ok(())
+    }
+
+    /// Marks the account as closed.
+    ///
+    /// A closed account can't be used as a writable account by the
+    /// following instructions of the transaction.
+    ///
+    /// # Errors
+    /// If the account is read only.
+    #[instrument(skip(self), fields(key = %self.key))]
+    pub fn close(&self) -> Result<()> {
+        debug!("closing account");
+        if !self.privileges.is_writable() {
+            return Err(Error::ModificationOfReadOnlyAccount { key: self.key });
+        }
+        self.closed.set(true);
+        self.journal.borrow_mut().push(Undo::Closed);
+
+        Ok(())
+    }
+
+    /// Checks if the account was closed during the transaction.
+    #[must_use]
+    pub fn is_closed(&self) -> bool {
+        self.closed.get()
+    }
+
     /// Adds a given amount of prisms to the account.
     ///
     /// # Parameters
@@ -99,10 +210,9 @@ impl<'a> TransactionAccount<'a> {
     /// is read only.
     #[instrument(skip(self))]
     pub fn add_prisms(&self, amount: u64) -> Result<()> {
-        debug!(current = *self.prisms.borrow(), "adding {amount} prisms");
+        debug!(current = self.prisms(), "adding {amount} prisms");
         let res = self
-            .prisms
-            .borrow()
+            .prisms()
             .checked_add(amount)
             .ok_or(Error::ArithmeticOverflow)?;
 
@@ -119,17 +229,51 @@ impl<'a> TransactionAccount<'a> {
     /// is read only.
     #[instrument(skip(self))]
     pub fn sub_prisms(&self, amount: u64) -> Result<()> {
-        debug!(
-            current = *self.prisms.borrow(),
-            "subtracting {amount} prisms"
-        );
+        debug!(current = self.prisms(), "subtracting {amount} prisms");
         let res = self
-            .prisms
-            .borrow()
+            .prisms()
             .checked_sub(amount)
             .ok_or(Error::ArithmeticOverflow)?;
         self.set_prisms(res)
     }
+
+    /// Get the number of modifications made to the account so far.
+    pub(super) fn changes(&self) -> usize {
+        self.journal.borrow().len()
+    }
+
+    /// Get the number of modifications made to the account so far, to roll back to them
+    /// later: the next write saves the data again.
+    pub(super) fn checkpoint(&self) -> usize {
+        self.snapshot.set(None);
+        self.changes()
+    }
+
+    /// Undoes the modifications made to the account, down to the given number of changes.
+    ///
+    /// # Parameters
+    /// * `changes` - The number of changes to keep.
+    #[instrument(skip(self), fields(key = %self.key))]
+    pub(super) fn undo_to(&self, changes: usize) {
+        debug!(from = self.changes(), "undoing account modifications");
+        self.snapshot.set(None);
+        let mut journal = self.journal.borrow_mut();
+        let mut account = self.account.borrow_mut();
+        while journal.len() > changes {
+            match journal.pop() {
+                Some(Undo::Prisms(prisms)) => account.prisms = prisms,
+                Some(Undo::Data(data)) => account.data = data,
+                Some(Undo::Closed) => self.closed.set(false),
+                None => break,
+            }
+        }
+    }
+
+    /// Forgets the modifications made to the account, they can't be undone anymore.
+    pub(super) fn forget_changes(&self) {
+        self.snapshot.set(None);
+        self.journal.borrow_mut().clear();
+    }
 }
 
 /// Accesses the next account in the list.
@@ -165,7 +309,7 @@ mod tests {
     fn modify_account_through_info() -> Result<()> {
         // Given
         const AMOUNT: u64 = 983_983;
-        let mut wallet = Wallet { prisms: AMOUNT };
+        let mut wallet = Wallet::new(AMOUNT);
         let key = Keypair::generate().pubkey();
         let meta = AccountMeta::wallet(key, Writable::Yes)?;
         let info = TransactionAccount::new(&meta, &mut wallet);
@@ -183,7 +327,7 @@ mod tests {
     fn sub_prisms() -> TestResult {
         // Given
         const AMOUNT: u64 = 983_983;
-        let mut wallet = Wallet { prisms: AMOUNT };
+        let mut wallet = Wallet::new(AMOUNT);
         let key = Keypair::generate().pubkey();
         let meta = AccountMeta::wallet(key, Writable::Yes)?;
         let info = TransactionAccount::new(&meta, &mut wallet);
@@ -201,11 +345,11 @@ mod tests {
     fn prevent_arithmetic_overflow() -> TestResult {
         // Given
         const AMOUNT: u64 = u64::MAX - 100;
-        let mut wallet1 = Wallet { prisms: AMOUNT };
+        let mut wallet1 = Wallet::new(AMOUNT);
         let key1 = Keypair::generate().pubkey();
         let meta1 = AccountMeta::wallet(key1, Writable::Yes)?;
         let info1 = TransactionAccount::new(&meta1, &mut wallet1);
-        let mut wallet2 = Wallet { prisms: 100 };
+        let mut wallet2 = Wallet::new(100);
         let key2 = Keypair::generate().pubkey();
         let meta2 = AccountMeta::wallet(key2, Writable::Yes)?;
         let info2 = TransactionAccount::new(&meta2, &mut wallet2);
@@ -225,7 +369,7 @@ mod tests {
     fn cannot_modify_read_only_account() -> TestResult {
         // Given
         const AMOUNT: u64 = 983_983;
-        let mut wallet = Wallet { prisms: AMOUNT };
+        let mut wallet = Wallet::new(AMOUNT);
         let key = Keypair::generate().pubkey();
         let meta = AccountMeta::wallet(key, Writable::No)?;
         let info = TransactionAccount::new(&meta, &mut wallet);
@@ -240,4 +384,59 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn writing_data_copies_shared_data_only() -> TestResult {
+        // Given
+        let mut wallet = Wallet::new(0);
+        wallet.data = vec![0; 8].into();
+        let committed = wallet.clone();
+        let key = Keypair::generate().pubkey();
+        let meta = AccountMeta::wallet(key, Writable::Yes)?;
+        let info = TransactionAccount::new(&meta, &mut wallet);
+
+        // When
+        let shared_before = Arc::ptr_eq(&info.account.borrow().data, &committed.data);
+        info.write_data(2, &[1, 2])?;
+        let res = info.write_data(7, &[1, 2]);
+        info.undo_to(0);
+        drop(info);
+
+        // Then
+        assert!(shared_before);
+        assert_matches!(res, Err(Error::DataOutOfBounds { end: 9, len: 8, .. }));
+        assert_eq!(*committed.data, [0; 8]);
+        assert!(Arc::ptr_eq(&wallet.data, &committed.data));
+
+        Ok(())
+    }
+
+    #[test]
+    fn consecutive_writes_save_the_data_once() -> TestResult {
+        // Given
+        let mut wallet = Wallet::new(0);
+        wallet.data = vec![0; 8].into();
+        let key = Keypair::generate().pubkey();
+        let meta = AccountMeta::wallet(key, Writable::Yes)?;
+        let info = TransactionAccount::new(&meta, &mut wallet);
+
+        // When
+        for offset in 0..4 {
+            info.write_data(offset, &[1])?;
+        }
+        let changes = info.changes();
+        info.add_prisms(10)?;
+        info.write_data(4, &[1])?;
+        let after_prisms = info.changes();
+        info.undo_to(0);
+        drop(info);
+
+        // Then
+        assert_eq!(changes, 1);
+        assert_eq!(after_prisms, 3);
+        assert_eq!(wallet.prisms, 0);
+        assert_eq!(*wallet.data, [0; 8]);
+
+        Ok(())
+    }
 }
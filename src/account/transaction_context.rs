@@ -0,0 +1,259 @@
+// File: src/account/transaction_context.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 20:24:13
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use tracing::{debug, instrument};
+
+use super::{Error, Result, TransactionAccount};
+
+/// A point in the modifications of a transaction's accounts that can be rolled back to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Checkpoint(Vec<usize>);
+
+/// The accounts of a transaction, whose modifications can be rolled back.
+///
+/// Every modification made through the accounts (including directly through
+/// a [`TransactionAccount`]) is recorded, so that the accounts can be restored
+/// if an instruction or the transaction fails.
+pub struct TransactionContext<'a> {
+    accounts: Vec<TransactionAccount<'a>>,
+}
+
+impl<'a> TransactionContext<'a> {
+    /// Creates the context of a transaction.
+    ///
+    /// # Parameters
+    /// * `accounts` - The accounts of the transaction.
+    #[must_use]
+    pub const fn new(accounts: Vec<TransactionAccount<'a>>) -> Self {
+        Self { accounts }
+    }
+
+    /// Get the accounts of the transaction.
+    #[must_use]
+    #[expect(clippy::missing_const_for_fn, reason = "false positive")]
+    pub fn accounts(&self) -> &[TransactionAccount<'a>] {
+        &self.accounts
+    }
+
+    /// Removes prisms from an account.
+    ///
+    /// # Parameters
+    /// * `index` - The index of the account in the transaction,
+    /// * `amount` - The amount of prisms to remove.
+    ///
+    /// # Errors
+    /// If the account doesn't exist, is read-only, or doesn't hold enough prisms.
+    pub fn checked_debit(&self, index: usize, amount: u64) -> Result<()> {
+        self.get(index)?.sub_prisms(amount)
+    }
+
+    /// Adds prisms to an account.
+    ///
+    /// # Parameters
+    /// * `index` - The index of the account in the transaction,
+    /// * `amount` - The amount of prisms to add.
+    ///
+    /// # Errors
+    /// If the account doesn't exist, is read-only, or in case of overflow.
+    pub fn checked_credit(&self, index: usize, amount: u64) -> Result<()> {
+        self.get(index)?.add_prisms(amount)
+    }
+
+    /// Replaces the data of an account.
+    ///
+    /// # Parameters
+    /// * `index` - The index of the account in the transaction,
+    /// * `data` - The new data of the account.
+    ///
+    /// # Errors
+    /// If the account doesn't exist or is read-only.
+    pub fn set_data(&self, index: usize, data: Vec<u8>) -> Result<()> {
+        self.get(index)?.set_data(data)
+    }
+
+    /// Get the current state of the modifications, to roll back to it later.
+    #[must_use]
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint(
+            self.accounts
+                .iter()
+                .map(TransactionAccount::checkpoint)
+                .collect(),
+        )
+    }
+
+    /// Undoes the modifications made since a checkpoint.
+    ///
+    /// # Parameters
+    /// * `checkpoint` - The checkpoint to go back to.
+    #[instrument(skip_all)]
+    pub fn rollback_to(&self, checkpoint: &Checkpoint) {
+        debug!("rolling back the transaction’s accounts");
+        for (account, changes) in self.accounts.iter().zip(checkpoint.0.iter()) {
+            account.undo_to(*changes);
+        }
+    }
+
+    /// Undoes all the modifications that weren't committed.
+    #[instrument(skip_all)]
+    pub fn rollback(&self) {
+        debug!("rolling back the transaction’s accounts");
+        for account in &self.accounts {
+            account.undo_to(0);
+        }
+    }
+
+    /// Keeps the modifications made so far: they can't be rolled back anymore.
+    #[instrument(skip_all)]
+    pub fn commit(&self) {
+        debug!("committing the transaction’s accounts");
+        for account in &self.accounts {
+            account.forget_changes();
+        }
+    }
+
+    fn get(&self, index: usize) -> Result<&TransactionAccount<'a>> {
+        self.accounts.get(index).ok_or(Error::MissingAccounts)
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+
+    use std::assert_matches::assert_matches;
+
+    use test_log::test;
+
+    use crate::account::{AccountMeta, Wallet, Writable};
+    use crate::crypto::Keypair;
+
+    use super::*;
+    type TestResult = core::result::Result<(), Box<dyn core::error::Error>>;
+
+    #[test]
+    fn rollback_after_partial_mutations() -> TestResult {
+        // Given
+        let meta1 = AccountMeta::wallet(Keypair::generate().pubkey(), Writable::Yes)?;
+        let meta2 = AccountMeta::wallet(Keypair::generate().pubkey(), Writable::Yes)?;
+        let mut wallet1 = Wallet::new(1_000);
+        wallet1.data = vec![1, 2, 3].into();
+        let mut wallet2 = Wallet::new(10);
+        let (before1, before2) = (wallet1.clone(), wallet2.clone());
+
+        // When
+        let res = {
+            let context = TransactionContext::new(vec![
+                TransactionAccount::new(&meta1, &mut wallet1),
+                TransactionAccount::new(&meta2, &mut wallet2),
+            ]);
+            context.checked_debit(0, 600)?;
+            context.checked_credit(1, 600)?;
+            context.set_data(0, vec![4])?;
+            context.accounts()[1].set_data(vec![5, 6])?;
+            let res = context.checked_debit(0, 600);
+            context.rollback();
+            res
+        };
+
+        // Then
+        assert_matches!(res, Err(Error::ArithmeticOverflow));
+        assert_eq!(wallet1, before1);
+        assert_eq!(wallet2, before2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn rollback_to_checkpoint() -> TestResult {
+        // Given
+        let meta = AccountMeta::wallet(Keypair::generate().pubkey(), Writable::Yes)?;
+        let mut wallet = Wallet::new(1_000);
+
+        // When
+        {
+            let context =
+                TransactionContext::new(vec![TransactionAccount::new(&meta, &mut wallet)]);
+            context.checked_debit(0, 100)?;
+            let checkpoint = context.checkpoint();
+            context.checked_debit(0, 200)?;
+            context.set_data(0, vec![1])?;
+            context.rollback_to(&checkpoint);
+        }
+
+        // Then
+        assert_eq!(wallet, Wallet::new(900));
+
+        Ok(())
+    }
+
+    #[test]
+    fn rollback_to_checkpoint_between_writes() -> TestResult {
+        // Given
+        let meta = AccountMeta::wallet(Keypair::generate().pubkey(), Writable::Yes)?;
+        let mut wallet = Wallet::new(1_000);
+        wallet.data = vec![0; 4].into();
+
+        // When
+        {
+            let context =
+                TransactionContext::new(vec![TransactionAccount::new(&meta, &mut wallet)]);
+            context.accounts()[0].write_data(0, &[1])?;
+            let checkpoint = context.checkpoint();
+            context.accounts()[0].write_data(1, &[2])?;
+            context.rollback_to(&checkpoint);
+        }
+
+        // Then
+        assert_eq!(*wallet.data, [1, 0, 0, 0]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn committed_changes_are_kept() -> TestResult {
+        // Given
+        let meta = AccountMeta::wallet(Keypair::generate().pubkey(), Writable::Yes)?;
+        let mut wallet = Wallet::new(1_000);
+
+        // When
+        {
+            let context =
+                TransactionContext::new(vec![TransactionAccount::new(&meta, &mut wallet)]);
+            context.checked_credit(0, 100)?;
+            context.commit();
+            context.checked_credit(0, 100)?;
+            context.rollback();
+        }
+
+        // Then
+        assert_eq!(wallet.prisms, 1_100);
+
+        Ok(())
+    }
+}
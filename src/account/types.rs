@@ -3,7 +3,7 @@
 // Creation date: Saturday 08 February 2025
 // Author: Vincent Berthier <vincent.berthier@posteo.org>
 // -----
-// Last modified: Saturday 08 February 2025 @ 16:17:11
+// Last modified: Thursday 15 October 2026 @ 13:31:58
 // Modified by: Vincent Berthier
 // -----
 // Copyright (c) 2025 <Vincent Berthier>
@@ -26,10 +26,10 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use std::mem::discriminant;
-
 use borsh::{BorshDeserialize, BorshSerialize};
 
+use super::{Error, Result};
+
 /// Determines if an account is read-only or writable
 #[derive(Clone, Copy, Debug, Default, BorshSerialize, BorshDeserialize)]
 pub enum Writable {
@@ -40,8 +40,17 @@ pub enum Writable {
     No,
 }
 
+/// A privilege an account can be given by the instructions referencing it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Privilege {
+    /// The account can be modified.
+    Writable,
+    /// The account signs the transaction.
+    Signing,
+}
+
 /// The type of account.
-#[derive(Clone, Copy, Debug, BorshDeserialize, BorshSerialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, BorshDeserialize, BorshSerialize)]
 pub enum AccountType {
     /// An account containing a program
     Program,
@@ -49,14 +58,121 @@ pub enum AccountType {
     Signing,
     /// A user's wallet (used only as identification)
     Wallet,
+    /// An off-curve account whose address is derived from a program's seeds.
+    Derived,
 }
 
 impl AccountType {
-    pub const fn is_wallet(self) -> bool {
-        matches!(self, Self::Wallet | Self::Signing)
+    /// Checks whether two metas of the same account can be merged.
+    ///
+    /// # Parameters
+    /// * `other` - The type of the other meta.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use bifrost::account::AccountType;
+    /// assert!(AccountType::Wallet.compatible_with(AccountType::Signing));
+    /// assert!(!AccountType::Program.compatible_with(AccountType::Wallet));
+    /// ```
+    #[must_use]
+    pub const fn compatible_with(self, other: Self) -> bool {
+        self.merged(other).is_some()
+    }
+
+    /// Get the type of an account referenced with both types.
+    ///
+    /// A signing account stays signing when merged with a wallet.
+    ///
+    /// # Parameters
+    /// * `other` - The type of the other meta.
+    ///
+    /// # Errors
+    /// If the types are not compatible.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use bifrost::account::AccountType;
+    /// assert_eq!(AccountType::Wallet.merge_result(AccountType::Signing)?, AccountType::Signing);
+    /// # Ok::<(), bifrost::account::Error>(())
+    /// ```
+    pub fn merge_result(self, other: Self) -> Result<Self> {
+        self.merged(other)
+            .ok_or(Error::MergeIncompatibleAccountTypes(self, other))
+    }
+
+    /// The compatibility matrix: every pair is listed, so that adding a type
+    /// requires deciding how it merges with the others.
+    const fn merged(self, other: Self) -> Option<Self> {
+        match (self, other) {
+            (Self::Program, Self::Program) => Some(Self::Program),
+            (Self::Derived, Self::Derived) => Some(Self::Derived),
+            (Self::Wallet, Self::Wallet) => Some(Self::Wallet),
+            (Self::Signing, Self::Signing | Self::Wallet) | (Self::Wallet, Self::Signing) => {
+                Some(Self::Signing)
+            }
+            (Self::Program, Self::Signing | Self::Wallet | Self::Derived)
+            | (Self::Signing | Self::Wallet | Self::Derived, Self::Program)
+            | (Self::Derived, Self::Signing | Self::Wallet)
+            | (Self::Signing | Self::Wallet, Self::Derived) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+
+    const ALL: [AccountType; 4] = [
+        AccountType::Program,
+        AccountType::Signing,
+        AccountType::Wallet,
+        AccountType::Derived,
+    ];
+
+    #[test]
+    fn compatibility_is_symmetric() {
+        for first in ALL {
+            for second in ALL {
+                assert_eq!(
+                    first.compatible_with(second),
+                    second.compatible_with(first),
+                    "{first:?} / {second:?}"
+                );
+                assert_eq!(
+                    first.merge_result(second).ok(),
+                    second.merge_result(first).ok(),
+                    "{first:?} / {second:?}"
+                );
+            }
+        }
     }
 
-    pub fn is_compatible(self, other: Self) -> bool {
-        (self.is_wallet() && other.is_wallet()) || (discriminant(&self) == discriminant(&other))
+    #[test]
+    fn compatibility_matrix() {
+        for first in ALL {
+            // every type merges with itself
+            assert_eq!(first.merge_result(first).ok(), Some(first), "{first:?}");
+            for second in ALL {
+                let expected = match (first, second) {
+                    _ if first == second => true,
+                    (AccountType::Signing | AccountType::Wallet, other) => {
+                        matches!(other, AccountType::Signing | AccountType::Wallet)
+                    }
+                    _ => false,
+                };
+                assert_eq!(
+                    first.compatible_with(second),
+                    expected,
+                    "{first:?} / {second:?}"
+                );
+            }
+        }
+        assert_eq!(
+            AccountType::Wallet.merge_result(AccountType::Signing).ok(),
+            Some(AccountType::Signing)
+        );
     }
 }
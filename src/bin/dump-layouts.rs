@@ -0,0 +1,47 @@
+// File: src/bin/dump-layouts.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 18:18:00
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Writes the byte layouts of the types of the blockchain, as JSON and as markdown.
+//!
+//! Usage: `cargo run --bin dump-layouts [directory]`
+
+use std::{env, fs, path::PathBuf};
+
+use bifrost::layout;
+
+fn main() -> Result<(), Box<dyn core::error::Error>> {
+    let dir = env::args()
+        .nth(1)
+        .map_or_else(|| PathBuf::from("layouts"), PathBuf::from);
+    fs::create_dir_all(&dir)?;
+    let layouts = layout::all();
+    fs::write(dir.join("layouts.json"), layout::to_json(&layouts))?;
+    fs::write(dir.join("layouts.md"), layout::to_markdown(&layouts))?;
+
+    Ok(())
+}
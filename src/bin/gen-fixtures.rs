@@ -0,0 +1,50 @@
+// File: src/bin/gen-fixtures.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 12:38:06
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Writes the deterministic test vectors of the blockchain as JSON files.
+//!
+//! Usage: `cargo run --features test-utils --bin gen-fixtures [directory]`
+
+use std::{env, fs, path::PathBuf};
+
+use bifrost::fixtures;
+
+fn main() -> Result<(), Box<dyn core::error::Error>> {
+    let dir = env::args()
+        .nth(1)
+        .map_or_else(|| PathBuf::from("fixtures"), PathBuf::from);
+    fs::create_dir_all(&dir)?;
+    for fixture in fixtures::all()? {
+        fs::write(
+            dir.join(format!("{}.json", fixture.name)),
+            fixture.to_json(),
+        )?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,181 @@
+// File: src/crypto/conformance.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 13:45:13
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Conformance of the signatures with the Ed25519 test vectors of RFC 8032 (section 7.1).
+
+use std::assert_matches::assert_matches;
+
+use test_log::test;
+
+use super::{Error, Keypair, Pubkey, Signature};
+
+type TestResult = core::result::Result<(), Box<dyn core::error::Error>>;
+
+/// A test vector, its fields being hex encoded.
+struct Vector {
+    name: &'static str,
+    secret: &'static str,
+    public: &'static str,
+    message: &'static str,
+    signature: &'static str,
+}
+
+const VECTORS: [Vector; 4] = [
+    Vector {
+        name: "TEST 1",
+        secret: "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60",
+        public: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        message: "",
+        signature: "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+    },
+    Vector {
+        name: "TEST 2",
+        secret: "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb",
+        public: "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+        message: "72",
+        signature: "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00",
+    },
+    Vector {
+        name: "TEST 3",
+        secret: "c5aa8df43f9f837bedb7442f31dcb7b166d38535076f094b85ce3a2e0b4458f7",
+        public: "fc51cd8e6218a1a38da47ed00230f0580816ed13ba3303ac5deb911548908025",
+        message: "af82",
+        signature: "6291d657deec24024827e69c3abe01a30ce548a284743a445e3680d7db5ac3ac18ff9b538d16f290ae67f760984dc6594a7c15e9716ed28dc027beceea1ec40a",
+    },
+    Vector {
+        name: "TEST SHA(abc)",
+        secret: "833fe62409237b9d62ec77587520911e9a759cec1d19755b7da901b96dca3d42",
+        public: "ec172b93ad5e563bf4932c70e1245034c35467ef2efd4d64ebf819683467e2bf",
+        message: "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f",
+        signature: "dc2a4459e7369633a52b1bf277839a00201009a3efbf3ecb69bea2186c26b58909351fc9ac90b3ecfdfbc7c66431e0303dca179c138ac17ad9bef1177331a704",
+    },
+];
+
+fn decode(hex: &str) -> Result<Vec<u8>, Box<dyn core::error::Error>> {
+    hex.as_bytes()
+        .chunks(2)
+        .map(|pair| Ok(u8::from_str_radix(core::str::from_utf8(pair)?, 16)?))
+        .collect()
+}
+
+/// The decoded public key, message and signature of a vector.
+fn parts(vector: &Vector) -> Result<(Pubkey, Vec<u8>, Signature), Box<dyn core::error::Error>> {
+    let public = Pubkey::from_bytes(&decode(vector.public)?.as_slice().try_into()?);
+    let signature =
+        ed25519_dalek::Signature::from_bytes(&decode(vector.signature)?.as_slice().try_into()?);
+
+    Ok((public, decode(vector.message)?, signature.into()))
+}
+
+/// Flips a bit of some bytes.
+fn flip(bytes: &[u8], bit: usize) -> Vec<u8> {
+    let mut flipped = bytes.to_vec();
+    flipped[bit >> 3_usize] ^= 1_u8 << (bit & 7);
+    flipped
+}
+
+#[test]
+fn vectors_sign_and_verify() -> TestResult {
+    for vector in &VECTORS {
+        // Given
+        let (public, message, expected) = parts(vector)?;
+        let secret = decode(vector.secret)?;
+        let keypair = Keypair::from_secret_bytes(&secret)?;
+        let full = Keypair::from_secret_bytes(&[secret, decode(vector.public)?].concat())?;
+
+        // When
+        let signature = keypair.sign(&message);
+
+        // Then
+        assert_eq!(keypair.pubkey(), public, "{}", vector.name);
+        assert_eq!(full.pubkey(), public, "{}", vector.name);
+        assert_eq!(signature, expected, "{}", vector.name);
+        signature.verify(&public, &message)?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn flipped_bits_are_rejected() -> TestResult {
+    for vector in &VECTORS {
+        // Given
+        let (public, message, signature) = parts(vector)?;
+
+        for bit in [0_usize, 7, 100, 255] {
+            // When
+            let key = Pubkey::from_bytes(&flip(public.as_ref(), bit).as_slice().try_into()?);
+            let tampered_message = if message.is_empty() {
+                vec![1_u8]
+            } else {
+                flip(&message, bit % (message.len() * 8))
+            };
+            let tampered_signature: Signature = ed25519_dalek::Signature::from_bytes(
+                &flip(signature.as_ref(), bit).as_slice().try_into()?,
+            )
+            .into();
+
+            // Then
+            let name = vector.name;
+            assert_matches!(
+                signature.verify(&key, &message),
+                Err(_),
+                "{name} key bit {bit}"
+            );
+            assert_matches!(
+                signature.verify(&public, &tampered_message),
+                Err(_),
+                "{name} message bit {bit}"
+            );
+            assert_matches!(
+                tampered_signature.verify(&public, &message),
+                Err(_),
+                "{name} signature bit {bit}"
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn mismatched_keypair_bytes_are_rejected() -> TestResult {
+    // Given
+    let secret = decode(VECTORS[0].secret)?;
+    let other_public = decode(VECTORS[1].public)?;
+
+    // When
+    let mismatched = Keypair::from_secret_bytes(&[secret.clone(), other_public].concat());
+    let truncated = Keypair::from_secret_bytes(&secret[..31]);
+
+    // Then
+    assert_matches!(mismatched, Err(Error::Signature(_)));
+    assert_matches!(truncated, Err(Error::WrongSecretKeyLength { length: 31 }));
+
+    Ok(())
+}
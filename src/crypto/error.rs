@@ -3,7 +3,7 @@
 // Creation date: Friday 07 February 2025
 // Author: Vincent Berthier <vincent.berthier@posteo.org>
 // -----
-// Last modified: Sunday 16 February 2025 @ 00:41:21
+// Last modified: Thursday 15 October 2026 @ 14:25:48
 // Modified by: Vincent Berthier
 // -----
 // Copyright (c) 2025 <Vincent Berthier>
@@ -27,7 +27,7 @@
 // SOFTWARE.
 
 use derive_more::derive::{Display, From};
-use ed25519_dalek::SignatureError;
+use ed25519_dalek::{SignatureError, KEYPAIR_LENGTH, PUBLIC_KEY_LENGTH, SECRET_KEY_LENGTH};
 
 /// Errors of the cryptography module.
 #[derive(Debug, Display, From)]
@@ -39,6 +39,18 @@ pub enum Error {
     RandomEnginePoisonedLock,
     /// Tried to used too many seeds to derive a public key.
     TooManySeeds,
+    /// The secret key bytes are neither a seed nor a full keypair.
+    #[display("a secret key is {SECRET_KEY_LENGTH} or {KEYPAIR_LENGTH} bytes long, got {length}")]
+    WrongSecretKeyLength {
+        /// The number of bytes given.
+        length: usize,
+    },
+    /// The decoded bytes aren't the length of a public key.
+    #[display("a public key is {PUBLIC_KEY_LENGTH} bytes long, got {length}")]
+    WrongPublicKeyLength {
+        /// The number of bytes decoded.
+        length: usize,
+    },
     /// When byte array doesn't have the right size for a block hash
     #[display("the given hash is not compatible with a block hash")]
     WrongHashLength,
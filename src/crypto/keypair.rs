@@ -3,7 +3,7 @@
 // Creation date: Friday 07 February 2025
 // Author: Vincent Berthier <vincent.berthier@posteo.org>
 // -----
-// Last modified: Sunday 09 February 2025 @ 16:15:34
+// Last modified: Thursday 15 October 2026 @ 13:45:13
 // Modified by: Vincent Berthier
 // -----
 // Copyright (c) 2025 <Vincent Berthier>
@@ -31,12 +31,16 @@ use std::{
     sync::{Mutex, OnceLock},
 };
 
-use ed25519_dalek::{ed25519::signature::Signer, SigningKey, KEYPAIR_LENGTH};
+use ed25519_dalek::{ed25519::signature::Signer, SigningKey, KEYPAIR_LENGTH, SECRET_KEY_LENGTH};
 use rand::SeedableRng as _;
 use rand_chacha::ChaCha20Rng;
-use tracing::{debug, info, instrument};
+use tracing::{debug, info, instrument, warn};
 
-use super::{pubkey::Pubkey, Signature};
+use super::{
+    pubkey::Pubkey,
+    signature::{digest_payload, DIGEST_LENGTH},
+    Error, Result, Signature,
+};
 
 static RNG: OnceLock<Mutex<ChaCha20Rng>> = OnceLock::new();
 
@@ -74,6 +78,70 @@ impl Keypair {
         }
     }
 
+    /// Builds the private key derived from a secret seed.
+    ///
+    /// The same seed always gives the same key, which is mostly useful
+    /// to produce reproducible test vectors.
+    ///
+    /// # Parameters
+    /// * `seed` - The secret seed of the key.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use bifrost::crypto::{Keypair, Error};
+    /// let key = Keypair::from_seed(&[7; 32]);
+    /// assert_eq!(key.pubkey(), Keypair::from_seed(&[7; 32]).pubkey());
+    ///
+    /// # Ok::<(), Error>(())
+    /// ```
+    #[must_use]
+    pub fn from_seed(seed: &[u8; SECRET_KEY_LENGTH]) -> Self {
+        Self {
+            key: SigningKey::from_bytes(seed).to_keypair_bytes(),
+        }
+    }
+
+    /// Builds a private key from its secret bytes.
+    ///
+    /// The bytes are either the 32 bytes secret seed, or the 64 bytes of the seed followed
+    /// by the public key, in which case the public key must match the seed.
+    ///
+    /// # Parameters
+    /// * `bytes` - The secret bytes of the key.
+    ///
+    /// # Errors
+    /// If the bytes have the wrong length, or if the public key doesn't match the seed.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use bifrost::crypto::{Keypair, Error};
+    /// let key = Keypair::from_secret_bytes(&[7; 32])?;
+    /// assert_eq!(key.pubkey(), Keypair::from_seed(&[7; 32]).pubkey());
+    /// assert!(Keypair::from_secret_bytes(&[7; 64]).is_err());
+    ///
+    /// # Ok::<(), Error>(())
+    /// ```
+    #[instrument(skip_all, fields(length = bytes.len()))]
+    pub fn from_secret_bytes(bytes: &[u8]) -> Result<Self> {
+        debug!("loading keypair from its secret bytes");
+        if let Ok(seed) = <&[u8; SECRET_KEY_LENGTH]>::try_from(bytes) {
+            return Ok(Self::from_seed(seed));
+        }
+        let Ok(keypair) = <&[u8; KEYPAIR_LENGTH]>::try_from(bytes) else {
+            warn!("wrong secret key length");
+            return Err(Error::WrongSecretKeyLength {
+                length: bytes.len(),
+            });
+        };
+        let key = SigningKey::from_keypair_bytes(keypair).inspect_err(|err| {
+            warn!("the public key doesn't match the secret: {err}");
+        })?;
+
+        Ok(Self {
+            key: key.to_keypair_bytes(),
+        })
+    }
+
     /// Get the public key associated with the private key.
     ///
     /// # Returns
@@ -125,6 +193,34 @@ impl Keypair {
     }
 }
 
+impl Keypair {
+    /// Sign the digest of a message rather than the message itself.
+    ///
+    /// This is meant for devices that can't process a full message.
+    /// The digest is prefixed with [`DIGEST_DOMAIN`](super::DIGEST_DOMAIN) before being signed,
+    /// so the signature can't be mistaken for the signature of a message.
+    ///
+    /// # Parameters
+    /// * `digest` - The digest to sign.
+    ///
+    /// # Returns
+    /// The signature of the digest
+    ///
+    /// # Example
+    /// ```rust
+    /// # use bifrost::crypto::{Keypair, Error};
+    /// let key = Keypair::generate();
+    /// let signature = key.sign_digest(&[3; 32]);
+    /// assert!(signature.verify_digest(&key.pubkey(), &[3; 32]).is_ok());
+    ///
+    /// # Ok::<(), Error>(())
+    /// ```
+    #[must_use]
+    pub fn sign_digest(&self, digest: &[u8; DIGEST_LENGTH]) -> Signature {
+        self.sign(digest_payload(digest))
+    }
+}
+
 impl fmt::Debug for Keypair {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "Keypair (pubkey: {})", self.pubkey())
@@ -3,7 +3,7 @@
 // Creation date: Friday 07 February 2025
 // Author: Vincent Berthier <vincent.berthier@posteo.org>
 // -----
-// Last modified: Friday 07 February 2025 @ 17:07:36
+// Last modified: Thursday 15 October 2026 @ 13:45:13
 // Modified by: Vincent Berthier
 // -----
 // Copyright (c) 2025 <Vincent Berthier>
@@ -26,6 +26,8 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
+#[cfg(test)]
+mod conformance;
 mod error;
 mod keypair;
 mod pubkey;
@@ -35,7 +37,7 @@ mod signature;
 pub use keypair::Keypair;
 pub use pubkey::Pubkey;
 pub use seeds::Seeds;
-pub use signature::Signature;
+pub use signature::{Signature, DIGEST_DOMAIN, DIGEST_LENGTH};
 
 pub use error::Error;
 type Result<T> = core::result::Result<T, Error>;
@@ -3,7 +3,7 @@
 // Creation date: Friday 07 February 2025
 // Author: Vincent Berthier <vincent.berthier@posteo.org>
 // -----
-// Last modified: Sunday 09 February 2025 @ 16:15:10
+// Last modified: Thursday 15 October 2026 @ 18:22:40
 // Modified by: Vincent Berthier
 // -----
 // Copyright (c) 2025 <Vincent Berthier>
@@ -33,11 +33,34 @@ use std::{
 
 use borsh::{BorshDeserialize, BorshSerialize};
 use curve25519_dalek::edwards::CompressedEdwardsY;
-use ed25519_dalek::{VerifyingKey, PUBLIC_KEY_LENGTH};
+use ed25519_dalek::{SignatureError, VerifyingKey, PUBLIC_KEY_LENGTH};
 use tracing::{debug, instrument};
 
 use super::error::Error;
 
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Creates a [`Pubkey`] from its `bs58` representation at compile time.
+///
+/// # Example
+/// ```rust
+/// # use bifrost::{crypto::Pubkey, pubkey};
+/// const KEY: Pubkey = pubkey!("H1LS9EF2cPrmmM828buVJSvvbztLc9buJPHMpqTmgEpa");
+/// assert_eq!(KEY.to_string(), "H1LS9EF2cPrmmM828buVJSvvbztLc9buJPHMpqTmgEpa");
+/// ```
+///
+/// Invalid keys are rejected during compilation:
+/// ```rust,compile_fail
+/// # use bifrost::{crypto::Pubkey, pubkey};
+/// const KEY: Pubkey = pubkey!("H1LS9EF2cPrmmM828buVJSvvbztLc9buJPHMpqTmgEp0");
+/// ```
+#[macro_export]
+macro_rules! pubkey {
+    ($key:literal) => {
+        const { $crate::crypto::Pubkey::from_base58_unwrap($key) }
+    };
+}
+
 /// A public key
 #[derive(Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize, Hash, PartialOrd, Ord)]
 pub struct Pubkey {
@@ -65,6 +88,70 @@ impl Pubkey {
         Self { key: *bytes }
     }
 
+    /// Creates a public key from its `bs58` representation.
+    ///
+    /// This is meant to be used in const contexts, usually through the [`pubkey!`](crate::pubkey) macro.
+    ///
+    /// # Parameters
+    /// * `key` - The `bs58` encoded public key.
+    ///
+    /// # Panics
+    /// If the string contains characters outside of the `bs58` alphabet,
+    /// or if it doesn't decode to exactly 32 bytes.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use bifrost::crypto::Pubkey;
+    /// const KEY: Pubkey = Pubkey::from_base58_unwrap("H1LS9EF2cPrmmM828buVJSvvbztLc9buJPHMpqTmgEpa");
+    /// ```
+    #[must_use]
+    pub const fn from_base58_unwrap(key: &str) -> Self {
+        let chars = key.as_bytes();
+        let mut bytes = [0_u8; PUBLIC_KEY_LENGTH];
+        let mut leading_zeros = 0;
+        let mut counting_zeros = true;
+        let mut i = 0;
+        while i < chars.len() {
+            let mut value = 0_u32;
+            while (value as usize) < BASE58_ALPHABET.len()
+                && BASE58_ALPHABET[value as usize] != chars[i]
+            {
+                value += 1;
+            }
+            assert!(
+                (value as usize) < BASE58_ALPHABET.len(),
+                "invalid character in bs58 public key"
+            );
+            if counting_zeros && value == 0 {
+                leading_zeros += 1;
+            } else {
+                counting_zeros = false;
+            }
+
+            let mut carry = value;
+            let mut j = PUBLIC_KEY_LENGTH;
+            while j > 0 {
+                j -= 1;
+                carry += bytes[j] as u32 * 58_u32;
+                bytes[j] = (carry & 0xFF) as u8;
+                carry >>= 8_u32;
+            }
+            assert!(carry == 0, "bs58 public key is too long");
+            i += 1;
+        }
+
+        let mut significant = PUBLIC_KEY_LENGTH;
+        while significant > 0 && bytes[PUBLIC_KEY_LENGTH - significant] == 0 {
+            significant -= 1;
+        }
+        assert!(
+            leading_zeros + significant == PUBLIC_KEY_LENGTH,
+            "bs58 public key must decode to 32 bytes"
+        );
+
+        Self { key: bytes }
+    }
+
     /// Check if the public key is on or off the `ed25519` curve
     ///
     /// # Returns
@@ -83,6 +170,25 @@ impl Pubkey {
         debug!("checking if key is on curve");
         matches!(CompressedEdwardsY::from_slice(&self.key), Ok(key) if key.decompress().is_some())
     }
+
+    /// A short form of the public key, to display it to humans.
+    ///
+    /// # Returns
+    /// The first and last 4 characters of the `bs58` representation of the key.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use bifrost::crypto::Pubkey;
+    /// let key = Pubkey::from_base58_unwrap("H1LS9EF2cPrmmM828buVJSvvbztLc9buJPHMpqTmgEpa");
+    /// assert_eq!(key.short(), "H1LS…gEpa");
+    /// ```
+    #[must_use]
+    pub fn short(&self) -> String {
+        let encoded = self.to_string();
+        let start = encoded.chars().take(4).collect::<String>();
+        let end = encoded.chars().skip(encoded.len() - 4).collect::<String>();
+        format!("{start}…{end}")
+    }
 }
 
 impl From<VerifyingKey> for Pubkey {
@@ -93,24 +199,26 @@ impl From<VerifyingKey> for Pubkey {
     }
 }
 
-#[expect(clippy::unwrap_used, clippy::fallible_impl_from)]
-impl From<&Pubkey> for VerifyingKey {
-    fn from(value: &Pubkey) -> Self {
-        Self::from_bytes(&value.key).unwrap()
+impl TryFrom<&Pubkey> for VerifyingKey {
+    type Error = SignatureError;
+
+    fn try_from(value: &Pubkey) -> core::result::Result<Self, Self::Error> {
+        Self::from_bytes(&value.key)
     }
 }
 
 impl FromStr for Pubkey {
     type Err = Error;
 
-    #[expect(
-        clippy::unwrap_used,
-        clippy::unwrap_in_result,
-        reason = "right len by definition"
-    )]
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
         let decoded = bs58::decode(s).into_vec()?;
-        let bytes: [u8; PUBLIC_KEY_LENGTH] = decoded.as_slice().try_into().unwrap();
+        let bytes: [u8; PUBLIC_KEY_LENGTH] =
+            decoded
+                .as_slice()
+                .try_into()
+                .map_err(|_err| Error::WrongPublicKeyLength {
+                    length: decoded.len(),
+                })?;
         Ok(Self { key: bytes })
     }
 }
@@ -164,6 +272,26 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn const_decoding_matches_parsing() -> TestResult {
+        // Given
+        const KEYS: [&str; 3] = [
+            "H1LS9EF2cPrmmM828buVJSvvbztLc9buJPHMpqTmgEpa",
+            "HvkkZN4pSTTo9wkCKhpTQ5pQ79fzjEVu2WJwu1mYH3Wk",
+            "11111111111111111111111111111111",
+        ];
+
+        for key in KEYS {
+            // When
+            let decoded = Pubkey::from_base58_unwrap(key);
+
+            // Then
+            assert_eq!(decoded, key.parse()?);
+        }
+
+        Ok(())
+    }
+
     #[test]
     fn check_offcurve() -> TestResult {
         // Given
@@ -3,7 +3,7 @@
 // Creation date: Friday 07 February 2025
 // Author: Vincent Berthier <vincent.berthier@posteo.org>
 // -----
-// Last modified: Sunday 16 February 2025 @ 00:45:28
+// Last modified: Thursday 15 October 2026 @ 13:45:13
 // Modified by: Vincent Berthier
 // -----
 // Copyright (c) 2025 <Vincent Berthier>
@@ -34,6 +34,17 @@ use tracing::{debug, instrument};
 
 use super::{Error, Pubkey, Result};
 
+/// The length of a message digest.
+pub const DIGEST_LENGTH: usize = 32;
+
+/// The prefix of the payload signed for a digest, so it can't be confused with a message.
+pub const DIGEST_DOMAIN: &[u8] = b"bifrost digest signature\0";
+
+/// The payload actually signed when signing a digest.
+pub(super) fn digest_payload(digest: &[u8; DIGEST_LENGTH]) -> Vec<u8> {
+    [DIGEST_DOMAIN, digest].concat()
+}
+
 /// The signature of a transaction.
 #[derive(Copy, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize, Hash)]
 pub struct Signature {
@@ -48,7 +59,7 @@ impl Signature {
     /// * `message` - the message that was signed.
     ///
     /// # Errors
-    /// If the signature does *not* match.
+    /// If the signature does *not* match, or if the public key is not a valid point.
     ///
     /// # Example
     /// ```rust
@@ -67,10 +78,22 @@ impl Signature {
         B: AsRef<[u8]>,
     {
         debug!("verifying signature");
-        let key: VerifyingKey = pubkey.into();
+        let key = VerifyingKey::try_from(pubkey)?;
         let signature = ed25519_dalek::Signature::from_bytes(&self.data);
         Ok(key.verify_strict(message.as_ref(), &signature)?)
     }
+
+    /// Verify that the signature was made over a digest with [`Keypair::sign_digest`](super::Keypair::sign_digest).
+    ///
+    /// # Parameters
+    /// * `pubkey` - the public key who supposedly signed the digest,
+    /// * `digest` - the digest that was signed.
+    ///
+    /// # Errors
+    /// If the signature does *not* match.
+    pub fn verify_digest(&self, pubkey: &Pubkey, digest: &[u8; DIGEST_LENGTH]) -> Result<()> {
+        self.verify(pubkey, digest_payload(digest))
+    }
 }
 
 impl From<ed25519_dalek::Signature> for Signature {
@@ -116,6 +139,7 @@ mod tests {
     use crate::crypto::{Keypair, Signature};
 
     use super::super::Error;
+    use super::DIGEST_LENGTH;
     type Result<T> = core::result::Result<T, Error>;
     type TestResult = core::result::Result<(), Box<dyn core::error::Error>>;
 
@@ -145,4 +169,29 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn digest_and_message_signatures_cannot_be_confused() -> TestResult {
+        // Given
+        let key = Keypair::generate();
+        let digest = [7; DIGEST_LENGTH];
+
+        // When
+        let digest_signature = key.sign_digest(&digest);
+        let message_signature = key.sign(digest);
+
+        // Then
+        digest_signature.verify_digest(&key.pubkey(), &digest)?;
+        message_signature.verify(&key.pubkey(), digest)?;
+        assert_matches!(
+            digest_signature.verify(&key.pubkey(), digest),
+            Err(Error::Signature(_))
+        );
+        assert_matches!(
+            message_signature.verify_digest(&key.pubkey(), &digest),
+            Err(Error::Signature(_))
+        );
+
+        Ok(())
+    }
 }
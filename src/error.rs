@@ -3,7 +3,7 @@
 // Creation date: Saturday 08 February 2025
 // Author: Vincent Berthier <vincent.berthier@posteo.org>
 // -----
-// Last modified: Sunday 09 February 2025 @ 16:49:32
+// Last modified: Thursday 15 October 2026 @ 14:25:48
 // Modified by: Vincent Berthier
 // -----
 // Copyright (c) 2025 <Vincent Berthier>
@@ -41,9 +41,18 @@ pub enum Error {
     /// An error occurred during an I/O operation.
     #[from]
     Io(crate::io::Error),
+    /// An error occurred while running a program.
+    #[from]
+    Program(crate::program::Error),
     /// An error occurring in the transactions module.
     #[from]
     Transaction(crate::transaction::Error),
+    /// An error occurred in the validator.
+    #[from]
+    Validator(crate::validator::Error),
+    /// The command line arguments are invalid.
+    #[display("invalid arguments, usage: {_0}")]
+    Usage(&'static str),
     /// Error while configuring the tracing.
     #[display("while configuring the tracing: {_0}")]
     TracingConfiguration(tracing_subscriber::filter::FromEnvError),
@@ -0,0 +1,245 @@
+// File: src/fixtures.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 12:38:06
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use borsh::BorshDeserialize as _;
+
+use crate::{
+    crypto::{Keypair, Pubkey},
+    program::{escrow, memo, system},
+    transaction::{Instruction, Transaction},
+    Error,
+};
+
+type Result<T> = core::result::Result<T, Error>;
+
+/// The slot at which every fixture transaction is created.
+pub const SLOT: u64 = 42;
+
+/// A signed transaction along with its serialized parts.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Fixture {
+    /// The name of the fixture.
+    pub name: &'static str,
+    /// The seeds of the keypairs signing the transaction, payer first.
+    pub seeds: Vec<[u8; 32]>,
+    /// The serialized message, as it is signed.
+    pub message: Vec<u8>,
+    /// The signatures of the transaction, payer first.
+    pub signatures: Vec<Vec<u8>>,
+    /// The serialized transaction.
+    pub transaction: Vec<u8>,
+}
+
+impl Fixture {
+    #[expect(
+        clippy::unwrap_used,
+        clippy::unwrap_in_result,
+        reason = "serializing in memory can't fail"
+    )]
+    fn new(name: &'static str, seeds: &[[u8; 32]], instructions: &[Instruction]) -> Result<Self> {
+        let mut trx = Transaction::new(SLOT);
+        trx.add(instructions)?;
+        for seed in seeds {
+            trx.sign(&Keypair::from_seed(seed))?;
+        }
+
+        Ok(Self {
+            name,
+            seeds: seeds.to_vec(),
+            message: trx.message().to_vec(),
+            signatures: trx
+                .signatures()
+                .iter()
+                .map(|signature| signature.as_ref().to_vec())
+                .collect(),
+            transaction: borsh::to_vec(&trx).unwrap(),
+        })
+    }
+
+    /// Decodes the transaction of the fixture.
+    ///
+    /// # Errors
+    /// If the transaction bytes are not a valid transaction.
+    pub fn decode(&self) -> std::io::Result<Transaction> {
+        Transaction::try_from_slice(&self.transaction)
+    }
+
+    /// Formats the fixture as a JSON object, the binary payloads being encoded in base64.
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        let list = |items: &[Vec<u8>]| {
+            items
+                .iter()
+                .map(|item| format!("\"{}\"", STANDARD.encode(item)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let seeds = self
+            .seeds
+            .iter()
+            .map(|seed| seed.to_vec())
+            .collect::<Vec<_>>();
+        let signers = self
+            .seeds
+            .iter()
+            .map(|seed| format!("\"{}\"", Keypair::from_seed(seed).pubkey()))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        format!(
+            "{{\n  \"name\": \"{}\",\n  \"slot\": {SLOT},\n  \"seeds\": [{}],\n  \"signers\": [{signers}],\n  \"message\": \"{}\",\n  \"signatures\": [{}],\n  \"transaction\": \"{}\"\n}}\n",
+            self.name,
+            list(&seeds),
+            STANDARD.encode(&self.message),
+            list(&self.signatures),
+            STANDARD.encode(&self.transaction),
+        )
+    }
+}
+
+/// The seed of the n-th fixture keypair.
+#[must_use]
+pub const fn seed(n: u8) -> [u8; 32] {
+    [n; 32]
+}
+
+/// The public key of the n-th fixture keypair.
+#[must_use]
+pub fn pubkey(n: u8) -> Pubkey {
+    Keypair::from_seed(&seed(n)).pubkey()
+}
+
+/// A transfer of prisms between two accounts.
+///
+/// # Errors
+/// If the transaction can't be built.
+pub fn transfer() -> Result<Fixture> {
+    Fixture::new(
+        "transfer",
+        &[seed(1)],
+        &[system::instruction::transfer(pubkey(1), pubkey(2), 1_000)?],
+    )
+}
+
+/// A transaction signed by several accounts.
+///
+/// # Errors
+/// If the transaction can't be built.
+pub fn multi_signer() -> Result<Fixture> {
+    Fixture::new(
+        "multi_signer",
+        &[seed(1), seed(3)],
+        &[
+            system::instruction::transfer(pubkey(1), pubkey(2), 1_000)?,
+            system::instruction::transfer(pubkey(3), pubkey(2), 2_000)?,
+            memo::instruction::memo("fixture", &[pubkey(1), pubkey(3)])?,
+        ],
+    )
+}
+
+/// Prisms locked in an escrow, whose address is derived from the program.
+///
+/// # Errors
+/// If the transaction can't be built.
+pub fn derived_address() -> Result<Fixture> {
+    Fixture::new(
+        "derived_address",
+        &[seed(1)],
+        &[escrow::instruction::lock(
+            pubkey(1),
+            pubkey(2),
+            SLOT + 10,
+            SLOT + 5,
+            1_000,
+        )?],
+    )
+}
+
+/// Every fixture.
+///
+/// # Errors
+/// If a transaction can't be built.
+pub fn all() -> Result<Vec<Fixture>> {
+    Ok(vec![transfer()?, multi_signer()?, derived_address()?])
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+    type TestResult = core::result::Result<(), Box<dyn core::error::Error>>;
+
+    #[test]
+    fn fixtures_are_deterministic() -> TestResult {
+        // Given
+        let first = all()?;
+
+        // When
+        let second = all()?;
+
+        // Then
+        assert_eq!(first, second);
+
+        Ok(())
+    }
+
+    #[test]
+    fn fixtures_are_valid_transactions() -> TestResult {
+        // Given
+        let fixtures = all()?;
+
+        // When
+        let transactions = fixtures
+            .iter()
+            .map(Fixture::decode)
+            .collect::<std::io::Result<Vec<_>>>()?;
+
+        // Then
+        for (fixture, trx) in fixtures.iter().zip(&transactions) {
+            assert!(trx.is_valid(), "{}", fixture.name);
+            assert_eq!(trx.message().to_vec(), fixture.message, "{}", fixture.name);
+            assert_eq!(
+                trx.signature().map(|signature| signature.as_ref().to_vec()),
+                fixture.signatures.first().cloned(),
+                "{}",
+                fixture.name
+            );
+            assert_eq!(
+                fixture.signatures.len(),
+                fixture.seeds.len(),
+                "{}",
+                fixture.name
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,210 @@
+// File: src/io/account_cache.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 17:15:59
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2025 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{account::Wallet, crypto::Pubkey};
+
+/// Default size of the account cache, in bytes.
+pub const DEFAULT_CACHE_CAPACITY: usize = 64 * 1024 * 1024;
+
+/// The hits and misses of the account cache.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Number of accounts found in the cache.
+    pub hits: u64,
+    /// Number of accounts that had to be read from the disk.
+    pub misses: u64,
+}
+
+impl CacheStats {
+    /// The proportion of reads served by the cache (0 if nothing was read).
+    #[must_use]
+    #[expect(
+        clippy::cast_precision_loss,
+        reason = "the rate doesn't need to be exact"
+    )]
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            return 0.;
+        }
+        self.hits as f64 / total as f64
+    }
+}
+
+/// Least recently used cache of accounts, bounded by the memory they use.
+#[derive(Debug)]
+pub(super) struct AccountCache {
+    /// Maximum number of bytes used by the cached accounts.
+    capacity: usize,
+    /// Number of bytes used by the cached accounts.
+    size: usize,
+    /// Counter used to order the accesses.
+    tick: u64,
+    /// The cached accounts, with their last access.
+    entries: HashMap<Pubkey, (Wallet, u64)>,
+    /// The cached accounts by last access.
+    recency: BTreeMap<u64, Pubkey>,
+    /// The hits and misses of the cache.
+    stats: CacheStats,
+}
+
+impl AccountCache {
+    pub(super) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            size: 0,
+            tick: 0,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            stats: CacheStats::default(),
+        }
+    }
+
+    pub(super) const fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    /// The number of bytes used by the cached accounts.
+    pub(super) const fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Changes the capacity of the cache, evicting accounts if needed.
+    pub(super) fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.evict();
+    }
+
+    /// Get an account from the cache, marking it as the most recently used.
+    pub(super) fn get(&mut self, key: &Pubkey) -> Option<Wallet> {
+        self.tick += 1;
+        let Some((account, last_access)) = self.entries.get_mut(key) else {
+            self.stats.misses += 1;
+            return None;
+        };
+        self.recency.remove(last_access);
+        self.recency.insert(self.tick, *key);
+        *last_access = self.tick;
+        self.stats.hits += 1;
+
+        Some(account.clone())
+    }
+
+    /// Inserts or replaces an account in the cache.
+    pub(super) fn insert(&mut self, key: Pubkey, account: Wallet) {
+        self.remove(&key);
+        let size = footprint(&account);
+        if size > self.capacity {
+            return;
+        }
+        self.tick += 1;
+        self.size += size;
+        self.recency.insert(self.tick, key);
+        self.entries.insert(key, (account, self.tick));
+        self.evict();
+    }
+
+    /// Removes an account from the cache.
+    pub(super) fn remove(&mut self, key: &Pubkey) {
+        if let Some((account, last_access)) = self.entries.remove(key) {
+            self.recency.remove(&last_access);
+            self.size -= footprint(&account);
+        }
+    }
+
+    fn evict(&mut self) {
+        while self.size > self.capacity {
+            let Some((_, key)) = self.recency.pop_first() else {
+                return;
+            };
+            if let Some((account, _)) = self.entries.remove(&key) {
+                self.size -= footprint(&account);
+            }
+        }
+    }
+}
+
+/// The number of bytes used by a cached account.
+#[expect(clippy::missing_const_for_fn, reason = "false positive")]
+fn footprint(account: &Wallet) -> usize {
+    size_of::<Pubkey>() + size_of::<(Wallet, u64)>() + account.data.len()
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use test_log::test;
+
+    use crate::crypto::Keypair;
+
+    use super::*;
+
+    #[test]
+    fn least_recently_used_account_is_evicted() {
+        // Given
+        let keys = (0..3_u8)
+            .map(|_| Keypair::generate().pubkey())
+            .collect::<Vec<_>>();
+        let mut cache = AccountCache::new(2 * footprint(&Wallet::new(0)));
+        cache.insert(keys[0], Wallet::new(1));
+        cache.insert(keys[1], Wallet::new(2));
+
+        // When
+        let first = cache.get(&keys[0]);
+        cache.insert(keys[2], Wallet::new(3));
+
+        // Then
+        assert_eq!(first, Some(Wallet::new(1)));
+        assert_eq!(cache.get(&keys[1]), None);
+        assert_eq!(cache.get(&keys[0]), Some(Wallet::new(1)));
+        assert_eq!(cache.get(&keys[2]), Some(Wallet::new(3)));
+        assert_eq!(cache.stats(), CacheStats { hits: 3, misses: 1 });
+    }
+
+    #[test]
+    fn oversized_account_is_not_cached() {
+        // Given
+        let key = Keypair::generate().pubkey();
+        let mut cache = AccountCache::new(footprint(&Wallet::new(0)));
+
+        // When
+        cache.insert(
+            key,
+            Wallet {
+                prisms: 0,
+                data: vec![0; 16].into(),
+            },
+        );
+
+        // Then
+        assert_eq!(cache.get(&key), None);
+        assert_eq!(cache.size, 0);
+    }
+}
@@ -0,0 +1,197 @@
+// File: src/io/accounts_hash.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 12:04:56
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use sha2::{Digest as _, Sha512};
+
+use crate::{account::Wallet, crypto::Pubkey};
+
+const LANES: usize = 8;
+
+/// A hash of all the accounts in the vault that can be updated incrementally.
+///
+/// Each account is hashed into a leaf, and the leaves are combined by adding them
+/// lane by lane. The result doesn't depend on the order in which the accounts
+/// were added, and an account can be updated by removing its old leaf and adding
+/// the new one. Empty accounts (no prisms and no data) aren't part of the hash.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AccountsHash {
+    lanes: [u64; LANES],
+}
+
+impl AccountsHash {
+    /// Computes the hash of a set of accounts from scratch.
+    ///
+    /// # Parameters
+    /// * `accounts` - The accounts to hash.
+    pub fn from_accounts<'a, I>(accounts: I) -> Self
+    where
+        I: IntoIterator<Item = (&'a Pubkey, &'a Wallet)>,
+    {
+        let mut hash = Self::default();
+        for (key, account) in accounts {
+            hash.insert(key, account);
+        }
+        hash
+    }
+
+    /// Adds an account to the hash.
+    ///
+    /// # Parameters
+    /// * `key` - The key of the account,
+    /// * `account` - The account to add.
+    pub fn insert(&mut self, key: &Pubkey, account: &Wallet) {
+        if let Some(leaf) = leaf(key, account) {
+            for (lane, value) in self.lanes.iter_mut().zip(leaf) {
+                *lane = lane.wrapping_add(value);
+            }
+        }
+    }
+
+    /// Removes an account from the hash.
+    ///
+    /// # Parameters
+    /// * `key` - The key of the account,
+    /// * `account` - The account as it was when added.
+    pub fn remove(&mut self, key: &Pubkey, account: &Wallet) {
+        if let Some(leaf) = leaf(key, account) {
+            for (lane, value) in self.lanes.iter_mut().zip(leaf) {
+                *lane = lane.wrapping_sub(value);
+            }
+        }
+    }
+
+    /// Replaces an account in the hash.
+    ///
+    /// # Parameters
+    /// * `key` - The key of the account,
+    /// * `old` - The account as it was when added,
+    /// * `new` - The new state of the account.
+    pub fn update(&mut self, key: &Pubkey, old: &Wallet, new: &Wallet) {
+        self.remove(key, old);
+        self.insert(key, new);
+    }
+
+    /// Get the root of the accounts' state.
+    #[must_use]
+    pub fn root(&self) -> [u8; 64] {
+        let mut hasher = Sha512::new();
+        self.lanes
+            .iter()
+            .for_each(|lane| hasher.update(lane.to_le_bytes()));
+        hasher.finalize().into()
+    }
+}
+
+#[expect(clippy::little_endian_bytes)]
+fn leaf(key: &Pubkey, account: &Wallet) -> Option<[u64; LANES]> {
+    if account.prisms == 0 && account.data.is_empty() {
+        return None;
+    }
+
+    let mut hasher = Sha512::new();
+    hasher.update(key);
+    hasher.update(account.prisms.to_le_bytes());
+    hasher.update(Sha512::digest(&account.data));
+    let digest = hasher.finalize();
+
+    let mut leaf = [0; LANES];
+    for (value, chunk) in leaf.iter_mut().zip(digest.chunks_exact(8)) {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(chunk);
+        *value = u64::from_le_bytes(bytes);
+    }
+    Some(leaf)
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+
+    use std::collections::BTreeMap;
+
+    use rand::{Rng as _, SeedableRng as _};
+    use rand_chacha::ChaCha20Rng;
+    use test_log::test;
+
+    use crate::crypto::Keypair;
+
+    use super::*;
+
+    #[test]
+    fn incremental_hash_matches_recomputation() {
+        // Given
+        let mut rng = ChaCha20Rng::seed_from_u64(651);
+        let keys = (0..20_u8)
+            .map(|_| Keypair::generate().pubkey())
+            .collect::<Vec<_>>();
+        let mut accounts = BTreeMap::new();
+        let mut hash = AccountsHash::default();
+
+        for _ in 0..500_u16 {
+            // When
+            let key = keys[rng.gen_range(0..keys.len())];
+            let mut new = Wallet::new(rng.gen_range(0..3_u64) * 1_000);
+            if rng.gen_bool(0.5) {
+                new.data = vec![rng.gen(); rng.gen_range(0..10)].into();
+            }
+            let old = accounts.insert(key, new.clone()).unwrap_or_default();
+            hash.update(&key, &old, &new);
+
+            // Then
+            assert_eq!(hash, AccountsHash::from_accounts(&accounts));
+        }
+    }
+
+    #[test]
+    fn empty_accounts_do_not_change_the_hash() {
+        // Given
+        let key = Keypair::generate().pubkey();
+        let mut hash = AccountsHash::default();
+
+        // When
+        hash.insert(&key, &Wallet::default());
+
+        // Then
+        assert_eq!(hash.root(), AccountsHash::default().root());
+    }
+
+    #[test]
+    fn data_changes_the_hash() {
+        // Given
+        let key = Keypair::generate().pubkey();
+        let mut account = Wallet::new(10);
+        let before = AccountsHash::from_accounts([(&key, &account)]);
+
+        // When
+        account.data = vec![1].into();
+        let after = AccountsHash::from_accounts([(&key, &account)]);
+
+        // Then
+        assert_ne!(before.root(), after.root());
+    }
+}
@@ -0,0 +1,521 @@
+// File: src/io/backup.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 20:25:10
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::path::{Component, Path, PathBuf};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use sha2::{Digest as _, Sha256};
+use tokio::fs;
+use tracing::{debug, instrument, trace, warn};
+
+use super::{
+    support::{create_folder, read_from_file, write_to_file},
+    vault::{get_vault_path, lock_vault, LOCK_FILE, VAULT_FOLDERS},
+    Error, Result, Vault,
+};
+
+/// The name of the manifest file in a backup.
+pub const MANIFEST_FILE: &str = "manifest";
+
+/// A file of a backup.
+#[derive(Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct BackupFile {
+    /// The path of the file, relative to the vault, its components separated by `/`
+    /// whatever the platform.
+    pub path: String,
+    /// The SHA-256 of the file's content.
+    pub checksum: [u8; 32],
+}
+
+/// The description of a backup of the vault.
+#[derive(Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct BackupManifest {
+    /// The slot the vault was at when it was backed up.
+    pub slot: u64,
+    /// The state root of the vault when it was backed up.
+    pub state_root: [u8; 64],
+    /// The files of the backup.
+    pub files: Vec<BackupFile>,
+}
+
+impl BackupManifest {
+    /// Loads the manifest of a backup.
+    ///
+    /// # Parameters
+    /// * `dir` - The folder holding the backup.
+    ///
+    /// # Errors
+    /// If the manifest can't be read.
+    pub async fn load<P>(dir: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        read_from_file(dir.as_ref().join(MANIFEST_FILE)).await
+    }
+
+    /// Checks that every file of the backup is present and unchanged.
+    ///
+    /// # Parameters
+    /// * `dir` - The folder holding the backup.
+    ///
+    /// # Errors
+    /// If a path leads outside of the backup, or if a file is missing or its content
+    /// doesn't match its checksum.
+    #[instrument(skip(self))]
+    pub async fn verify(&self, dir: &Path) -> Result<()> {
+        debug!("verifying backup");
+        for file in &self.files {
+            let path = dir.join(native_path(&file.path)?);
+            let Ok(content) = fs::read(&path).await else {
+                warn!(?path, "backup file is missing");
+                return Err(Error::BackupCorrupted { path });
+            };
+            if checksum(&content) != file.checksum {
+                warn!(?path, "backup file doesn't match its checksum");
+                return Err(Error::BackupCorrupted { path });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Vault {
+    /// Copies the vault in a folder.
+    ///
+    /// The vault is saved first. Since it's borrowed mutably, no account can be
+    /// written while it's copied, so the backup is a consistent point-in-time copy.
+    ///
+    /// # Parameters
+    /// * `dest` - The folder the backup is written to.
+    ///
+    /// # Errors
+    /// On I/O issues.
+    #[instrument(skip(self))]
+    pub async fn backup<P>(&mut self, dest: P) -> Result<BackupManifest>
+    where
+        P: Into<PathBuf> + core::fmt::Debug,
+    {
+        debug!("backing up the vault");
+        let dest = dest.into();
+        self.save().await?;
+        let root = get_vault_path()?;
+        let mut files = Vec::new();
+        for path in list_files(root).await? {
+            #[expect(clippy::unwrap_used, reason = "the files were listed from the root")]
+            let relative = path.strip_prefix(root).unwrap();
+            if relative == Path::new(LOCK_FILE) {
+                trace!("skipping the lock file");
+                continue;
+            }
+            trace!(?relative, "backing up file");
+            let content = fs::read(&path).await?;
+            let target = dest.join(relative);
+            if let Some(parent) = target.parent() {
+                create_folder(parent).await?;
+            }
+            fs::write(&target, &content).await?;
+            files.push(BackupFile {
+                path: portable_path(relative),
+                checksum: checksum(&content),
+            });
+        }
+
+        let manifest = BackupManifest {
+            slot: self.slot(),
+            state_root: self.state_root(),
+            files,
+        };
+        write_to_file(dest.join(MANIFEST_FILE), &manifest).await?;
+
+        Ok(manifest)
+    }
+
+    /// Replaces the vault with a backup.
+    ///
+    /// The backup is verified and copied next to the vault before anything is replaced,
+    /// the copy then taking the place of the vault. Any vault loaded before must be
+    /// dropped (the vault can't be replaced while it's locked), the restored one being
+    /// returned.
+    ///
+    /// # Parameters
+    /// * `backup` - The folder holding the backup, outside of the vault.
+    ///
+    /// # Errors
+    /// If the backup is corrupted or inside the vault, if the vault is still opened,
+    /// or on I/O issues.
+    #[instrument]
+    pub async fn restore<P>(backup: P) -> Result<Self>
+    where
+        P: Into<PathBuf> + core::fmt::Debug,
+    {
+        debug!("restoring the vault");
+        let backup = backup.into();
+        let manifest = BackupManifest::load(&backup).await?;
+        manifest.verify(&backup).await?;
+
+        let root = get_vault_path()?;
+        if root.exists()
+            && fs::canonicalize(&backup)
+                .await?
+                .starts_with(fs::canonicalize(root).await?)
+        {
+            warn!(?backup, "the backup is inside the vault");
+            return Err(Error::BackupInsideVault { path: backup });
+        }
+        let lock = root.exists().then(lock_vault).transpose()?;
+        let staging = sibling(root, "restoring");
+        if let Err(err) = stage(&backup, &manifest, &staging).await {
+            warn!("could not copy the backup next to the vault: {err}");
+            if staging.exists() {
+                fs::remove_dir_all(&staging).await?;
+            }
+            return Err(err);
+        }
+
+        trace!("replacing the vault");
+        if let Some(lock) = lock {
+            let previous = sibling(root, "previous");
+            if previous.exists() {
+                fs::remove_dir_all(&previous).await?;
+            }
+            fs::rename(root, &previous).await?;
+            if let Err(err) = fs::rename(&staging, root).await {
+                warn!("could not move the restored vault in place: {err}");
+                fs::rename(&previous, root).await?;
+                return Err(err.into());
+            }
+            drop(lock);
+            fs::remove_dir_all(&previous).await?;
+        } else {
+            fs::rename(&staging, root).await?;
+        }
+
+        let vault = Self::load_or_create().await?;
+        if vault.state_root() != manifest.state_root {
+            warn!("the restored vault doesn't match the state root of the backup");
+            return Err(Error::BackupStateRootMismatch);
+        }
+
+        Ok(vault)
+    }
+}
+
+/// Copies the files of a backup in a new folder, laid out as a vault.
+///
+/// # Parameters
+/// * `backup` - The folder holding the backup,
+/// * `manifest` - The manifest of the backup,
+/// * `staging` - The folder the files are copied to, replaced if it exists.
+async fn stage(backup: &Path, manifest: &BackupManifest, staging: &Path) -> Result<()> {
+    trace!(?staging, "copying the backup");
+    if staging.exists() {
+        fs::remove_dir_all(staging).await?;
+    }
+    for folder in VAULT_FOLDERS {
+        create_folder(staging.join(folder)).await?;
+    }
+    for file in &manifest.files {
+        trace!(path = file.path, "restoring file");
+        let relative = native_path(&file.path)?;
+        let target = staging.join(&relative);
+        if let Some(parent) = target.parent() {
+            create_folder(parent).await?;
+        }
+        fs::copy(backup.join(relative), target).await?;
+    }
+
+    Ok(())
+}
+
+/// A folder next to another one, with the same name and an extension.
+fn sibling(folder: &Path, extension: &str) -> PathBuf {
+    let mut name = folder.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(extension);
+    folder.with_file_name(name)
+}
+
+/// Lists the files in a folder and its sub-folders.
+async fn list_files(root: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut folders = vec![root.to_path_buf()];
+    while let Some(folder) = folders.pop() {
+        let mut entries = fs::read_dir(folder).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            if entry.file_type().await?.is_dir() {
+                folders.push(entry.path());
+            } else {
+                files.push(entry.path());
+            }
+        }
+    }
+    files.sort();
+
+    Ok(files)
+}
+
+/// Writes a relative path with `/` separators, so manifests can be read on any platform.
+fn portable_path(relative: &Path) -> String {
+    relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Reads a relative path of a manifest with the separators of the platform.
+///
+/// # Errors
+/// If the path is empty, absolute, or has components other than plain names
+/// (such as `..`), which could lead outside of the vault.
+fn native_path(portable: &str) -> Result<PathBuf> {
+    let path = portable.split('/').collect::<PathBuf>();
+    let plain = path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)));
+    if portable.is_empty() || portable.starts_with('/') || !plain {
+        warn!(path = portable, "invalid path in the backup manifest");
+        return Err(Error::BackupInvalidPath {
+            path: portable.to_owned(),
+        });
+    }
+
+    Ok(path)
+}
+
+fn checksum(content: &[u8]) -> [u8; 32] {
+    Sha256::digest(content).into()
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use std::assert_matches::assert_matches;
+    use std::sync::Arc;
+
+    use test_log::test;
+    use tokio::sync::RwLock;
+
+    use crate::account::Wallet;
+    use crate::crypto::Keypair;
+    use crate::io::set_vault_path;
+
+    use super::*;
+    type TestResult = core::result::Result<(), Box<dyn core::error::Error>>;
+
+    async fn reset(vault: &str, backup: &str) -> Result<()> {
+        set_vault_path(vault)?;
+        for path in [vault, backup] {
+            if Path::new(path).exists() {
+                fs::remove_dir_all(path).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test(tokio::test(flavor = "multi_thread", worker_threads = 2))]
+    async fn backup_under_load_restores_the_cut() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/backup-1";
+        const BACKUP: &str = "/tmp/bifrost/backup-1-copy";
+        reset(VAULT, BACKUP).await?;
+        let vault = Arc::new(RwLock::new(Vault::load_or_create().await?));
+        let writer = {
+            let vault = Arc::clone(&vault);
+            tokio::spawn(async move {
+                for slot in 0..50_u64 {
+                    let key = Keypair::generate().pubkey();
+                    let mut vault = vault.write().await;
+                    vault
+                        .save_account(key, &Wallet::new(slot + 1), slot)
+                        .await?;
+                    drop(vault);
+                    tokio::task::yield_now().await;
+                }
+                Result::Ok(())
+            })
+        };
+
+        // When
+        tokio::task::yield_now().await;
+        let manifest = vault.write().await.backup(BACKUP).await?;
+        writer.await??;
+        drop(vault);
+        let restored = Vault::restore(BACKUP).await?;
+
+        // Then
+        assert_eq!(restored.state_root(), manifest.state_root);
+        assert_eq!(BackupManifest::load(BACKUP).await?, manifest);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn corrupted_backup_is_not_restored() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/backup-2";
+        const BACKUP: &str = "/tmp/bifrost/backup-2-copy";
+        reset(VAULT, BACKUP).await?;
+        let mut vault = Vault::load_or_create().await?;
+        let key = Keypair::generate().pubkey();
+        vault.save_account(key, &Wallet::new(1_000), 1).await?;
+        let manifest = vault.backup(BACKUP).await?;
+        let root = vault.state_root();
+        drop(vault);
+        let file = manifest
+            .files
+            .iter()
+            .find(|file| file.path == "index")
+            .ok_or("index not backed up")?;
+        let tampered = Path::new(BACKUP).join(&file.path);
+        let mut content = fs::read(&tampered).await?;
+        content[0] ^= 0xff;
+        fs::write(&tampered, content).await?;
+
+        // When
+        let res = Vault::restore(BACKUP).await;
+
+        // Then
+        assert_matches!(res.err(), Some(Error::BackupCorrupted { path }) if path == tampered);
+        let untouched = Vault::load_or_create().await?;
+        assert_eq!(untouched.state_root(), root);
+        assert_eq!(untouched.get(&key).await?.prisms, 1_000);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn opened_vault_is_not_replaced() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/backup-4";
+        const BACKUP: &str = "/tmp/bifrost/backup-4-copy";
+        reset(VAULT, BACKUP).await?;
+        let mut vault = Vault::load_or_create().await?;
+        let key = Keypair::generate().pubkey();
+        vault.save_account(key, &Wallet::new(1_000), 1).await?;
+        vault.backup(BACKUP).await?;
+        vault.save_account(key, &Wallet::new(2_000), 2).await?;
+        vault.save().await?;
+
+        // When
+        let res = Vault::restore(BACKUP).await;
+
+        // Then
+        assert_matches!(res.err(), Some(Error::VaultLocked { path }) if path == Path::new(VAULT));
+        assert_eq!(vault.get(&key).await?.prisms, 2_000);
+        drop(vault);
+        let untouched = Vault::load_or_create().await?;
+        assert_eq!(untouched.get(&key).await?.prisms, 2_000);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn backup_inside_the_vault_is_refused() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/backup-5";
+        const BACKUP: &str = "/tmp/bifrost/backup-5/copy";
+        reset(VAULT, BACKUP).await?;
+        let mut vault = Vault::load_or_create().await?;
+        let key = Keypair::generate().pubkey();
+        vault.save_account(key, &Wallet::new(1_000), 1).await?;
+        vault.backup(BACKUP).await?;
+        drop(vault);
+
+        // When
+        let res = Vault::restore(BACKUP).await;
+
+        // Then
+        assert_matches!(res.err(), Some(Error::BackupInsideVault { path }) if path == Path::new(BACKUP));
+        let untouched = Vault::load_or_create().await?;
+        assert_eq!(untouched.get(&key).await?.prisms, 1_000);
+
+        Ok(())
+    }
+
+    #[test]
+    fn manifest_paths_are_portable() {
+        // Given
+        let relative = Path::new("accounts").join("3.1");
+
+        // When
+        let portable = portable_path(&relative);
+
+        // Then
+        assert_eq!(portable, "accounts/3.1");
+        assert_eq!(native_path(&portable).ok(), Some(relative));
+    }
+
+    #[test]
+    fn manifest_paths_stay_inside_the_vault() {
+        for path in [
+            "",
+            "/etc/passwd",
+            "../vault",
+            "accounts/../../vault",
+            "./index",
+            "C:/x",
+        ] {
+            assert_matches!(
+                native_path(path),
+                Err(Error::BackupInvalidPath { path: invalid }) if invalid == path,
+                "{path} should be rejected"
+            );
+        }
+    }
+
+    #[test(tokio::test)]
+    async fn backup_to_long_non_ascii_path() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/backup-3";
+        const BACKUP: &str = "/tmp/bifrost/backup-3-copy";
+        reset(VAULT, BACKUP).await?;
+        let dest = (0..20_u8).fold(
+            Path::new(BACKUP).join("sauvegarde-été-バックアップ"),
+            |path, depth| path.join(format!("niveau-{depth}-{}", "ø".repeat(10))),
+        );
+        let mut vault = Vault::load_or_create().await?;
+        let key = Keypair::generate().pubkey();
+        vault.save_account(key, &Wallet::new(1_000), 1).await?;
+
+        // When
+        let manifest = vault.backup(&dest).await?;
+        drop(vault);
+        let restored = Vault::restore(&dest).await?;
+
+        // Then
+        assert!(dest.as_os_str().len() > 260);
+        assert!(manifest.files.iter().all(|file| file.path != LOCK_FILE));
+        assert_eq!(restored.state_root(), manifest.state_root);
+        assert_eq!(restored.get(&key).await?.prisms, 1_000);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,292 @@
+// File: src/io/balance_journal.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 15:47:08
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::{collections::HashMap, path::PathBuf};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use tracing::{debug, instrument, trace, warn};
+
+use crate::crypto::{Pubkey, Signature};
+
+use super::{
+    support::{read_from_file, write_to_file},
+    vault::get_vault_path,
+    write_pool::FileWrite,
+    Error, Result,
+};
+
+/// A change of an account's balance made by a transaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct BalanceChange {
+    /// The slot during which the transaction was executed.
+    pub slot: u64,
+    /// The signature of the transaction.
+    pub signature: Signature,
+    /// The change of the balance.
+    pub delta: i128,
+    /// The balance after the transaction.
+    pub balance: u64,
+}
+
+/// The balance changes of every account, in the order they were executed.
+#[derive(Clone, Debug, Default, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct BalanceJournal {
+    changes: HashMap<Pubkey, Vec<BalanceChange>>,
+}
+
+impl BalanceJournal {
+    #[instrument]
+    pub async fn load_or_create() -> Self {
+        debug!("initializing balance journal");
+        match Self::load_from_disk().await {
+            Ok(journal) => {
+                trace!("balance journal could be reloaded from the disk");
+                return journal;
+            }
+            Err(err) => warn!(
+                "balance journal could not be reloaded from the disk ({err}): starting from scratch"
+            ),
+        }
+
+        Self::default()
+    }
+
+    #[instrument]
+    async fn load_from_disk() -> Result<Self> {
+        let path = Self::get_path()?;
+        if !path.exists() {
+            return Err(Error::BalanceJournalNotFound);
+        }
+        read_from_file(path).await
+    }
+
+    #[instrument(skip_all)]
+    pub async fn save(&self) -> Result<()> {
+        debug!("saving balance journal to file");
+        write_to_file(Self::get_path()?, self).await
+    }
+
+    /// The balance journal file, to write along with the rest of the vault.
+    pub fn file(&self) -> Result<FileWrite> {
+        Ok(FileWrite::new(Self::get_path()?, self))
+    }
+
+    #[instrument(skip(self, signature))]
+    pub fn record(
+        &mut self,
+        key: Pubkey,
+        slot: u64,
+        signature: Signature,
+        before: u64,
+        after: u64,
+    ) {
+        if before == after {
+            return;
+        }
+        debug!("recording balance change");
+        self.changes.entry(key).or_default().push(BalanceChange {
+            slot,
+            signature,
+            delta: i128::from(after) - i128::from(before),
+            balance: after,
+        });
+    }
+
+    /// Forgets the balance changes made by transactions that were undone.
+    pub fn discard(&mut self, signatures: &[Signature]) {
+        self.changes.retain(|_, changes| {
+            changes.retain(|change| !signatures.contains(&change.signature));
+            !changes.is_empty()
+        });
+    }
+
+    pub fn balance_at_slot(&self, key: &Pubkey, slot: u64) -> Option<u64> {
+        let changes = self.changes.get(key)?;
+        let end = changes.partition_point(|change| change.slot <= slot);
+        end.checked_sub(1).map(|last| changes[last].balance)
+    }
+
+    /// The balance at the end of a slot, undoing the changes made after it.
+    ///
+    /// Returns `None` if the balance didn't change after the slot.
+    pub fn rewind(&self, key: &Pubkey, slot: u64) -> Option<u64> {
+        let changes = self.changes.get(key)?;
+        let next = changes.get(changes.partition_point(|change| change.slot <= slot))?;
+        u64::try_from(i128::from(next.balance) - next.delta).ok()
+    }
+
+    pub fn history(
+        &self,
+        key: &Pubkey,
+        from_slot: u64,
+        to_slot: u64,
+        page: usize,
+        page_size: usize,
+    ) -> Vec<BalanceChange> {
+        let Some(changes) = self.changes.get(key) else {
+            return Vec::new();
+        };
+        let start = changes.partition_point(|change| change.slot < from_slot);
+        let end = changes.partition_point(|change| change.slot <= to_slot);
+        changes[start..end]
+            .iter()
+            .skip(page.saturating_mul(page_size))
+            .take(page_size)
+            .copied()
+            .collect()
+    }
+
+    #[instrument(skip(self))]
+    pub fn prune(&mut self, boundary: u64) {
+        debug!("pruning balance journal");
+        self.changes.values_mut().for_each(|changes| {
+            let before_boundary = changes.partition_point(|change| change.slot < boundary);
+            // the last change before the boundary still gives the balance at the boundary
+            changes.drain(..before_boundary.saturating_sub(1));
+        });
+    }
+
+    fn get_path() -> Result<PathBuf> {
+        Ok(get_vault_path()?.join("balances"))
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+
+    use std::collections::HashMap;
+
+    use test_log::test;
+
+    use crate::crypto::Keypair;
+
+    use super::*;
+
+    fn signature() -> Signature {
+        Keypair::generate().sign(b"transaction")
+    }
+
+    #[test]
+    fn journal_matches_replay() {
+        // Given
+        let keys = (0..4_u8)
+            .map(|_| Keypair::generate().pubkey())
+            .collect::<Vec<_>>();
+        let mut journal = BalanceJournal::default();
+        let mut balances = HashMap::new();
+        let mut replays = Vec::new();
+
+        // When
+        for slot in 0..50_u64 {
+            for (i, key) in keys.iter().enumerate() {
+                if (slot + i as u64) % 3 == 0 {
+                    continue;
+                }
+                let before = balances.get(key).copied().unwrap_or_default();
+                let after = (slot * 7 + i as u64 * 13) % 100;
+                journal.record(*key, slot, signature(), before, after);
+                balances.insert(*key, after);
+            }
+            replays.push(balances.clone());
+        }
+
+        // Then
+        for (slot, replay) in replays.iter().enumerate() {
+            for key in &keys {
+                assert_eq!(
+                    journal
+                        .balance_at_slot(key, slot as u64)
+                        .unwrap_or_default(),
+                    replay.get(key).copied().unwrap_or_default(),
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn unknown_history_is_empty() {
+        // Given
+        let key = Keypair::generate().pubkey();
+        let mut journal = BalanceJournal::default();
+        journal.record(key, 10, signature(), 0, 100);
+
+        // When
+        let before = journal.balance_at_slot(&key, 9);
+        let other = journal.balance_at_slot(&Keypair::generate().pubkey(), 10);
+
+        // Then
+        assert_eq!(before, None);
+        assert_eq!(other, None);
+    }
+
+    #[test]
+    fn paginate_history() {
+        // Given
+        let key = Keypair::generate().pubkey();
+        let mut journal = BalanceJournal::default();
+        for slot in 0..10_u64 {
+            journal.record(key, slot, signature(), slot, slot + 1);
+        }
+
+        // When
+        let first = journal.history(&key, 2, 7, 0, 4);
+        let second = journal.history(&key, 2, 7, 1, 4);
+        let third = journal.history(&key, 2, 7, 2, 4);
+
+        // Then
+        assert_eq!(
+            first.iter().map(|change| change.slot).collect::<Vec<_>>(),
+            vec![2, 3, 4, 5]
+        );
+        assert_eq!(
+            second.iter().map(|change| change.slot).collect::<Vec<_>>(),
+            vec![6, 7]
+        );
+        assert!(third.is_empty());
+        assert!(first.iter().all(|change| change.delta == 1));
+    }
+
+    #[test]
+    fn pruning_keeps_balance_at_boundary() {
+        // Given
+        let key = Keypair::generate().pubkey();
+        let mut journal = BalanceJournal::default();
+        for slot in [1, 3, 5, 8] {
+            journal.record(key, slot, signature(), slot - 1, slot);
+        }
+
+        // When
+        journal.prune(6);
+
+        // Then
+        assert_eq!(journal.balance_at_slot(&key, 6), Some(5));
+        assert_eq!(journal.balance_at_slot(&key, 8), Some(8));
+        assert_eq!(journal.history(&key, 0, 10, 0, 10).len(), 2);
+    }
+}
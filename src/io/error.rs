@@ -3,7 +3,7 @@
 // Creation date: Sunday 09 February 2025
 // Author: Vincent Berthier <vincent.berthier@posteo.org>
 // -----
-// Last modified: Monday 10 February 2025 @ 20:49:48
+// Last modified: Thursday 15 October 2026 @ 20:25:10
 // Modified by: Vincent Berthier
 // -----
 // Copyright (c) 2025 <Vincent Berthier>
@@ -26,14 +26,67 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
+use std::path::PathBuf;
+
 use derive_more::derive::{Display, From};
 
-use super::location::AccountDiskLocation;
+use crate::crypto::Pubkey;
+
+use super::{filter::AccountFilter, location::AccountDiskLocation};
 
 /// Errors of the I/O module.
 #[derive(Debug, Display, From)]
 #[display("during an I/O operation: {_variant}")]
 pub enum Error {
+    /// Several known accounts start with the prefix.
+    #[display("{matches} accounts start with '{prefix}', such as {candidates:?}")]
+    AmbiguousAccountPrefix {
+        /// The prefix.
+        prefix: String,
+        /// The number of accounts starting with the prefix.
+        matches: usize,
+        /// The first of those accounts, in order.
+        candidates: Vec<Pubkey>,
+    },
+    /// A file of a backup is missing or was modified.
+    #[display("the backup file {path:?} is missing or corrupted")]
+    BackupCorrupted {
+        /// The path of the file.
+        path: PathBuf,
+    },
+    /// A backup can't be restored from a folder inside the vault it replaces.
+    #[display("the backup in {path:?} is inside the vault")]
+    BackupInsideVault {
+        /// The folder of the backup.
+        path: PathBuf,
+    },
+    /// The manifest of a backup holds a path leading outside of the vault.
+    #[display("the backup file {path} isn't a relative path inside the vault")]
+    BackupInvalidPath {
+        /// The path, as written in the manifest.
+        path: String,
+    },
+    /// The vault restored from a backup doesn't match the state root it recorded.
+    #[display("the restored vault doesn't match the state root of the backup")]
+    BackupStateRootMismatch,
+    /// A past balance was requested while the balance history is disabled.
+    #[display("the balance history is needed to read past balances")]
+    BalanceHistoryDisabled,
+    /// The balance journal file wasn't found.
+    #[display("the balance journal file wasn’t found")]
+    BalanceJournalNotFound,
+    /// Data read from a file couldn't be decoded.
+    #[display("could not decode a '{kind}' at byte {offset} of {path:?}: {reason}")]
+    Deserialization {
+        /// The path of the file
+        path: PathBuf,
+        /// The type that was expected
+        kind: &'static str,
+        /// Where the data starts in the file
+        offset: u64,
+        /// Why the data couldn't be decoded
+        reason: std::io::Error,
+    },
     /// Tried to put the same location twice in the trash
     #[display("attempted to put {loc:?} in the trash twice")]
     DuplicateLocationInTrash {
@@ -43,6 +96,14 @@ pub enum Error {
     /// The index file wasn't found.
     #[display("the index file wasn’t found")]
     IndexFileNotFound,
+    /// A filter of a query could never match an account.
+    #[display("invalid account filter {filter:?}: {reason}")]
+    InvalidAccountFilter {
+        /// The filter.
+        filter: AccountFilter,
+        /// Why it's invalid.
+        reason: &'static str,
+    },
     /// Attempted to read beyond file size
     #[display("attempted to read from {from} to {to} but file only has {size} bytes")]
     OutOfBounds {
@@ -53,9 +114,67 @@ pub enum Error {
         /// Actual size of the file
         size: u64,
     },
+    /// More accounts were requested at once than allowed.
+    #[display("{requested} accounts were requested at once, the maximum is {max}")]
+    TooManyAccounts {
+        /// The number of accounts requested.
+        requested: usize,
+        /// The maximum number of accounts.
+        max: usize,
+    },
+    /// More filters were given to a query than allowed.
+    #[display("{count} account filters were given, the maximum is {max}")]
+    TooManyAccountFilters {
+        /// The number of filters.
+        count: usize,
+        /// The maximum number of filters.
+        max: usize,
+    },
     /// The trash file wasn't found.
     #[display("the trash file wasn’t found")]
     TrashFileNotFound,
+    /// No known account starts with the prefix.
+    #[display("no known account starts with '{prefix}'")]
+    UnknownAccountPrefix {
+        /// The prefix.
+        prefix: String,
+    },
+    /// The vault was written by a newer version of the crate.
+    #[display("the vault has version {found}, but only versions up to {supported} are supported")]
+    UnsupportedVaultVersion {
+        /// The version of the vault.
+        found: u32,
+        /// The latest version supported.
+        supported: u32,
+    },
+    /// The vault is already opened, by this process or another one.
+    #[display("the vault at {path:?} is already in use")]
+    VaultLocked {
+        /// The path of the vault.
+        path: PathBuf,
+    },
+    /// The vault has an older layout and must be migrated before being opened.
+    #[display("the vault has version {found} and must be migrated")]
+    VaultNeedsMigration {
+        /// The version of the vault.
+        found: u32,
+    },
+    /// The vault path was already set to another location.
+    #[display(
+        "the vault path is already set to {current:?} (attempted to set it to {requested:?})"
+    )]
+    VaultPathAlreadySet {
+        /// The path of the vault
+        current: PathBuf,
+        /// The path that was rejected
+        requested: PathBuf,
+    },
+    /// The vault path was used before being set.
+    #[display("the vault path is not set")]
+    VaultNotInitialized,
+    /// A worker of the write pool stopped before writing a file.
+    #[display("a write worker stopped")]
+    WriteWorkerStopped,
     /// An operation on the file system couldn't be completed.
     #[from]
     #[display("filesystem error '{_0}'")]
@@ -67,3 +186,27 @@ pub enum Error {
 }
 
 impl core::error::Error for Error {}
+
+/// Why an account of a batch couldn't be fetched, the other ones being fetched anyway.
+#[derive(Debug, Display)]
+pub enum AccountFetchError {
+    /// The file holding the account is missing.
+    #[display("the file holding the account is missing")]
+    MissingFile,
+    /// The account couldn't be read from its file.
+    #[display("the account couldn't be read: {_0}")]
+    Unreadable(Box<Error>),
+}
+
+impl From<Error> for AccountFetchError {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::FileSystem(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                Self::MissingFile
+            }
+            err => Self::Unreadable(Box::new(err)),
+        }
+    }
+}
+
+impl core::error::Error for AccountFetchError {}
@@ -0,0 +1,224 @@
+// File: src/io/filter.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 20:05:13
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use tracing::warn;
+
+use crate::{account::Wallet, program::rent::MAX_ACCOUNT_DATA_SIZE};
+
+use super::{Error, Result};
+
+/// Maximum number of filters applied by a single query.
+pub const MAX_ACCOUNT_FILTERS: usize = 4;
+
+/// Maximum number of bytes compared by a single [`AccountFilter::Memcmp`].
+pub const MAX_MEMCMP_BYTES: usize = 128;
+
+/// A condition on the data of the accounts returned by a query.
+///
+/// The filters of a query are combined: an account must match all of them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AccountFilter {
+    /// The data of the account is exactly this many bytes long.
+    DataSize(usize),
+    /// The data of the account holds these bytes at this offset.
+    Memcmp {
+        /// Where the bytes start in the data.
+        offset: usize,
+        /// The expected bytes.
+        bytes: Vec<u8>,
+    },
+}
+
+impl AccountFilter {
+    /// Checks that the filter could match an account.
+    ///
+    /// # Errors
+    /// If the size or the compared bytes go beyond the maximum data size of an account,
+    /// or if more than [`MAX_MEMCMP_BYTES`] bytes are compared.
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            Self::DataSize(size) if *size > MAX_ACCOUNT_DATA_SIZE => {
+                warn!(size, "data size filter above the maximum data size");
+                Err(Error::InvalidAccountFilter {
+                    filter: self.clone(),
+                    reason: "the size is above the maximum data size of an account",
+                })
+            }
+            Self::Memcmp { bytes, .. } if bytes.is_empty() || bytes.len() > MAX_MEMCMP_BYTES => {
+                warn!(len = bytes.len(), "invalid number of bytes to compare");
+                Err(Error::InvalidAccountFilter {
+                    filter: self.clone(),
+                    reason: "too few or too many bytes to compare",
+                })
+            }
+            Self::Memcmp { offset, bytes }
+                if offset.saturating_add(bytes.len()) > MAX_ACCOUNT_DATA_SIZE =>
+            {
+                warn!(offset, "bytes compared beyond the maximum data size");
+                Err(Error::InvalidAccountFilter {
+                    filter: self.clone(),
+                    reason: "the bytes end beyond the maximum data size of an account",
+                })
+            }
+            Self::DataSize(_) | Self::Memcmp { .. } => Ok(()),
+        }
+    }
+
+    /// Checks that a set of filters can be applied together.
+    ///
+    /// # Parameters
+    /// * `filters` - The filters of a query.
+    ///
+    /// # Errors
+    /// If there are more than [`MAX_ACCOUNT_FILTERS`] filters, or if one of them is invalid.
+    pub fn validate_all(filters: &[Self]) -> Result<()> {
+        if filters.len() > MAX_ACCOUNT_FILTERS {
+            warn!(count = filters.len(), "too many account filters");
+            return Err(Error::TooManyAccountFilters {
+                count: filters.len(),
+                max: MAX_ACCOUNT_FILTERS,
+            });
+        }
+        filters.iter().try_for_each(Self::validate)
+    }
+
+    /// Whether an account with this much data could match the filter,
+    /// to exclude accounts before reading them.
+    ///
+    /// # Parameters
+    /// * `len` - The size of the data of the account.
+    #[must_use]
+    pub const fn accepts_data_len(&self, len: usize) -> bool {
+        match self {
+            Self::DataSize(size) => len == *size,
+            Self::Memcmp { offset, bytes } => offset.saturating_add(bytes.len()) <= len,
+        }
+    }
+
+    /// Whether an account matches the filter.
+    ///
+    /// # Parameters
+    /// * `account` - The account to check.
+    #[must_use]
+    pub fn matches(&self, account: &Wallet) -> bool {
+        match self {
+            Self::DataSize(size) => account.data.len() == *size,
+            Self::Memcmp { offset, bytes } => account
+                .data
+                .get(*offset..offset.saturating_add(bytes.len()))
+                .is_some_and(|data| data == bytes.as_slice()),
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use std::{assert_matches::assert_matches, sync::Arc};
+
+    use test_log::test;
+
+    use super::*;
+
+    fn wallet(data: &[u8]) -> Wallet {
+        Wallet {
+            prisms: 1,
+            data: Arc::from(data),
+        }
+    }
+
+    fn memcmp(offset: usize, bytes: &[u8]) -> AccountFilter {
+        AccountFilter::Memcmp {
+            offset,
+            bytes: bytes.to_vec(),
+        }
+    }
+
+    #[test]
+    fn filters_are_validated() {
+        // Given
+        let valid = [
+            AccountFilter::DataSize(0),
+            AccountFilter::DataSize(MAX_ACCOUNT_DATA_SIZE),
+            memcmp(0, &[1; MAX_MEMCMP_BYTES]),
+            memcmp(MAX_ACCOUNT_DATA_SIZE - 1, &[1]),
+        ];
+        let invalid = [
+            AccountFilter::DataSize(MAX_ACCOUNT_DATA_SIZE + 1),
+            memcmp(0, &[1; MAX_MEMCMP_BYTES + 1]),
+            memcmp(0, &[]),
+            memcmp(MAX_ACCOUNT_DATA_SIZE, &[1]),
+            memcmp(usize::MAX, &[1]),
+        ];
+        let too_many = vec![AccountFilter::DataSize(1); MAX_ACCOUNT_FILTERS + 1];
+
+        // When
+        let all_valid = AccountFilter::validate_all(&valid);
+        let rejected = invalid
+            .iter()
+            .map(AccountFilter::validate)
+            .collect::<Vec<_>>();
+        let too_many = AccountFilter::validate_all(&too_many);
+
+        // Then
+        assert_matches!(all_valid, Ok(()));
+        for res in rejected {
+            assert_matches!(res, Err(Error::InvalidAccountFilter { .. }));
+        }
+        assert_matches!(
+            too_many,
+            Err(Error::TooManyAccountFilters { count, max: MAX_ACCOUNT_FILTERS })
+                if count == MAX_ACCOUNT_FILTERS + 1
+        );
+    }
+
+    #[test]
+    fn accounts_are_matched_against_their_data() {
+        // Given
+        let account = wallet(&[1, 2, 3]);
+
+        // When
+        let matching = [AccountFilter::DataSize(3), memcmp(1, &[2, 3])];
+        let other = [
+            AccountFilter::DataSize(2),
+            memcmp(1, &[2, 4]),
+            memcmp(2, &[3, 4]),
+        ];
+
+        // Then
+        for filter in &matching {
+            assert!(filter.matches(&account), "{filter:?}");
+            assert!(filter.accepts_data_len(account.data.len()), "{filter:?}");
+        }
+        for filter in &other {
+            assert!(!filter.matches(&account), "{filter:?}");
+        }
+        assert!(!other[0].accepts_data_len(account.data.len()));
+        assert!(!other[2].accepts_data_len(account.data.len()));
+    }
+}
@@ -3,7 +3,7 @@
 // Creation date: Sunday 09 February 2025
 // Author: Vincent Berthier <vincent.berthier@posteo.org>
 // -----
-// Last modified: Saturday 15 February 2025 @ 17:13:47
+// Last modified: Thursday 15 October 2026 @ 18:22:40
 // Modified by: Vincent Berthier
 // -----
 // Copyright (c) 2025 <Vincent Berthier>
@@ -31,10 +31,16 @@ use std::{collections::HashMap, path::PathBuf};
 use borsh::{BorshDeserialize, BorshSerialize};
 use tracing::{debug, instrument, trace, warn};
 
-use crate::{account::Wallet, crypto::Pubkey, io::support::write_to_file};
+use crate::{
+    account::Wallet,
+    crypto::Pubkey,
+    io::support::write_to_file,
+    layout::{DescribeLayout, Extent, Field, Size},
+};
 
 use super::{
-    location::AccountDiskLocation, support::read_from_file, vault::get_vault_path, Error, Result,
+    location::AccountDiskLocation, support::read_from_file, vault::get_vault_path,
+    write_pool::FileWrite, Error, Result,
 };
 
 #[derive(BorshSerialize, BorshDeserialize)]
@@ -46,12 +52,16 @@ impl Index {
     #[instrument]
     pub async fn load_or_create() -> Self {
         debug!("initializing index");
-        if let Ok(index) = Self::load_from_disk().await {
-            trace!("index could be reloaded from the disk");
-            return index;
+        match Self::load_from_disk().await {
+            Ok(index) => {
+                trace!("index could be reloaded from the disk");
+                return index;
+            }
+            Err(err) => {
+                warn!("index could not be reloaded from the disk ({err}): starting from scratch");
+            }
         }
 
-        warn!("index could not be reloaded from the disk: starting from scratch");
         Self {
             accounts: HashMap::new(),
         }
@@ -59,7 +69,7 @@ impl Index {
 
     #[instrument]
     async fn load_from_disk() -> Result<Self> {
-        let index_path = Self::get_path();
+        let index_path = Self::get_path()?;
         if !index_path.exists() {
             return Err(Error::IndexFileNotFound);
         }
@@ -87,6 +97,30 @@ impl Index {
         self.accounts.insert(key, loc);
     }
 
+    #[instrument(skip_all, fields(%key))]
+    pub fn remove_account(&mut self, key: &Pubkey) -> Option<AccountDiskLocation> {
+        debug!("removing account from the index");
+        self.accounts.remove(key)
+    }
+
+    pub fn keys(&self) -> Vec<Pubkey> {
+        self.accounts.keys().copied().collect()
+    }
+
+    /// The known accounts, with where they're stored.
+    pub fn locations(&self) -> impl Iterator<Item = (&Pubkey, &AccountDiskLocation)> {
+        self.accounts.iter()
+    }
+
+    /// The accounts whose `bs58` representation starts with a prefix.
+    pub fn keys_with_prefix(&self, prefix: &str) -> Vec<Pubkey> {
+        self.accounts
+            .keys()
+            .filter(|key| key.to_string().starts_with(prefix))
+            .copied()
+            .collect()
+    }
+
     #[instrument(skip(self))]
     pub fn accounts_on_file(&self, slot: u64, id: u8) -> Vec<Pubkey> {
         self.accounts
@@ -100,11 +134,34 @@ impl Index {
     #[instrument(skip_all)]
     pub async fn save(&self) -> Result<()> {
         debug!("saving index to file");
-        write_to_file(Self::get_path(), self).await
+        write_to_file(Self::get_path()?, self).await
+    }
+
+    /// The index file, to write along with the rest of the vault.
+    pub fn file(&self) -> Result<FileWrite> {
+        Ok(FileWrite::new(Self::get_path()?, self))
+    }
+
+    fn get_path() -> Result<PathBuf> {
+        Ok(get_vault_path()?.join("index"))
+    }
+}
+
+impl DescribeLayout for Index {
+    const NAME: &'static str = "Index";
+    const DESCRIPTION: &'static str =
+        "The index file of the vault, locating every account (in no particular order).";
+
+    fn fields() -> Vec<Field> {
+        vec![Field::new(
+            "accounts",
+            "HashMap<Pubkey, AccountDiskLocation>",
+            Size::items(32 + 25),
+        )]
     }
 
-    fn get_path() -> PathBuf {
-        get_vault_path().join("index")
+    fn extents(&self) -> Vec<Extent> {
+        vec![Extent::Count(self.accounts.len())]
     }
 }
 
@@ -143,7 +200,7 @@ mod tests {
         P: Into<PathBuf>,
     {
         let path = path.into();
-        set_vault_path(&path);
+        set_vault_path(&path)?;
         if path.exists() {
             remove_dir_all(path)?;
         }
@@ -154,7 +211,7 @@ mod tests {
     async fn generate_dummy_index(vault_path: &str) -> TestResult {
         reset_vault(vault_path)?;
         Vault::init_vault().await?;
-        let index_path = get_vault_path().join("index");
+        let index_path = get_vault_path()?.join("index");
 
         let key = Keypair::generate().pubkey();
         let mut accounts = HashMap::new();
@@ -278,8 +335,8 @@ mod tests {
         const ID: u8 = 5;
         reset_vault(VAULT)?;
         Vault::init_vault().await?;
-        let account = Wallet { prisms: 398_399 };
-        let mut writer = SlotWriter::new(SLOT);
+        let account = Wallet::new(398_399);
+        let mut writer = SlotWriter::new(SLOT)?;
         writer.append(&account).await?;
         writer.append(&account).await?;
         writer.append(&account).await?;
@@ -315,12 +372,10 @@ mod tests {
 
         for i in 0..100 {
             if i % 2 == 0 {
-                vault
-                    .save_account(key, &Wallet { prisms: 983_373 }, SLOT)
-                    .await?;
+                vault.save_account(key, &Wallet::new(983_373), SLOT).await?;
             } else {
                 vault
-                    .save_account(Keypair::generate().pubkey(), &Wallet { prisms: 99 }, SLOT)
+                    .save_account(Keypair::generate().pubkey(), &Wallet::new(99), SLOT)
                     .await?;
             }
         }
@@ -331,10 +386,54 @@ mod tests {
         let accounts_on_file = index.accounts_on_file(SLOT, 0);
 
         // Then
-        let expected =
-            MAX_ACCOUNT_FILE_SIZE / borsh::to_vec(&Wallet { prisms: 0 })?.len() as u64 / 2 + 1;
+        let expected = MAX_ACCOUNT_FILE_SIZE / borsh::to_vec(&Wallet::new(0))?.len() as u64 / 2 + 1;
         assert_eq!(accounts_on_file.len() as u64, expected);
 
         Ok(())
     }
+
+    #[test(tokio::test)]
+    async fn corrupted_index_names_file_and_type() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/index-8";
+        reset_vault(VAULT)?;
+        Vault::init_vault().await?;
+        std::fs::write(Index::get_path()?, [0xFF; 3])?;
+
+        // When
+        let res = Index::load_from_disk().await;
+
+        // Then
+        assert_matches!(
+            res.err(),
+            Some(Error::Deserialization { path, kind, offset: 0, .. })
+                if path.ends_with("index") && kind.contains("Index")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn described_index_matches_its_serialization() -> TestResult {
+        // Given
+        let mut index = Index {
+            accounts: HashMap::new(),
+        };
+
+        // When
+        for slot in 0..3_u64 {
+            index.set_account(
+                Keypair::generate().pubkey(),
+                AccountDiskLocation {
+                    slot,
+                    ..Default::default()
+                },
+            );
+        }
+
+        // Then
+        assert_eq!(index.described_len(), Some(borsh::to_vec(&index)?.len()));
+
+        Ok(())
+    }
 }
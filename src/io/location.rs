@@ -3,7 +3,7 @@
 // Creation date: Sunday 09 February 2025
 // Author: Vincent Berthier <vincent.berthier@posteo.org>
 // -----
-// Last modified: Saturday 15 February 2025 @ 18:41:11
+// Last modified: Thursday 15 October 2026 @ 20:47:26
 // Modified by: Vincent Berthier
 // -----
 // Copyright (c) 2025 <Vincent Berthier>
@@ -26,12 +26,19 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use std::path::{Path, PathBuf};
+use std::{
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
 
 use borsh::{BorshDeserialize, BorshSerialize};
 use tracing::{debug, instrument, warn};
 
-use crate::{account::Wallet, io::MAX_ACCOUNT_FILE_SIZE};
+use crate::{
+    account::Wallet,
+    io::MAX_ACCOUNT_FILE_SIZE,
+    layout::{DescribeLayout, Extent, Field, Size},
+};
 
 use super::{
     support::{append_to_file, read_from_file_map},
@@ -47,26 +54,53 @@ pub struct AccountDiskLocation {
     pub size: u64,
 }
 
+/// The bytes written before the data of a wallet: its prisms and the length of its data.
+const WALLET_HEADER_SIZE: u64 = 12;
+
 impl AccountDiskLocation {
+    /// The size of the data of the wallet, known without reading it.
+    #[expect(clippy::cast_possible_truncation)]
+    pub const fn data_len(&self) -> usize {
+        self.size.saturating_sub(WALLET_HEADER_SIZE) as usize
+    }
+
     pub async fn read(&self) -> Result<Wallet> {
-        let path = get_account_path(self.slot, self.id);
+        let path = get_account_path(self.slot, self.id)?;
         read_from_file_map(path, self.offset, self.size).await
     }
 }
 
-#[expect(clippy::unwrap_used)]
+/// Finds the id of the last file holding the accounts of a slot.
+///
+/// # Errors
+/// If the folder of the accounts can't be read, or if it holds a file of the slot
+/// whose name isn't an id.
 #[instrument]
-fn get_id_from_files(slot: u64) -> u8 {
+fn get_id_from_files(slot: u64) -> Result<u8> {
     debug!("retrieving the slot id from the files");
-    let path = get_vault_path().join("accounts");
-    let filter = format!("{slot}.");
-    std::fs::read_dir(path)
-        .unwrap()
-        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
-        .filter(|name| name.starts_with(&filter))
-        .map(|name| name.split('.').next_back().unwrap().parse().unwrap())
-        .max()
-        .unwrap_or_default()
+    let path = get_vault_path()?.join("accounts");
+    let prefix = format!("{slot}.");
+    let mut id = 0;
+    for entry in std::fs::read_dir(path)? {
+        let name = entry?.file_name().into_string().map_err(|name| {
+            std::io::Error::new(
+                ErrorKind::InvalidData,
+                format!("invalid account file name {name:?}"),
+            )
+        })?;
+        let Some(file_id) = name.strip_prefix(&prefix) else {
+            continue;
+        };
+        let file_id = file_id.parse::<u8>().map_err(|err| {
+            std::io::Error::new(
+                ErrorKind::InvalidData,
+                format!("invalid account file name '{name}': {err}"),
+            )
+        })?;
+        id = id.max(file_id);
+    }
+
+    Ok(id)
 }
 
 #[derive(Default)]
@@ -80,35 +114,34 @@ pub struct SlotWriter {
 
 impl SlotWriter {
     #[instrument]
-    pub fn new(slot: u64) -> Self {
+    pub fn new(slot: u64) -> Result<Self> {
         debug!("creating new slot writer");
-        let id = get_id_from_files(slot);
-        let offset = Path::new(&get_account_path(slot, id))
+        let id = get_id_from_files(slot)?;
+        let offset = Path::new(&get_account_path(slot, id)?)
             .metadata()
             .map_or(0, |metadata| metadata.len());
         #[expect(clippy::cast_possible_truncation)]
         let buffer = Vec::with_capacity(MAX_ACCOUNT_FILE_SIZE as usize * 2);
 
-        Self {
+        Ok(Self {
             slot,
             id,
             offset,
             buffer,
             dropped: false,
-        }
+        })
     }
 
     pub const fn slot(&self) -> u64 {
         self.slot
     }
 
-    #[expect(clippy::unwrap_used)]
     #[instrument(skip_all)]
     pub async fn append<A>(&mut self, account: A) -> Result<AccountDiskLocation>
     where
         A: BorshSerialize + Send + Sync,
     {
-        let data = borsh::to_vec(&account).unwrap();
+        let data = borsh::to_vec(&account)?;
         let size = data.len() as u64;
 
         let res = self.get_account_loc(size);
@@ -138,7 +171,7 @@ impl SlotWriter {
         let slot = self.slot;
         let id = self.id;
         // tokio::spawn(async move {
-        let path = get_account_path(slot, id);
+        let path = get_account_path(slot, id)?;
         match append_to_file(path, &data).await {
             Ok(()) => (),
             Err(err) => warn!("could not write account data to file: {err}"),
@@ -161,7 +194,7 @@ impl SlotWriter {
 impl Drop for SlotWriter {
     #[instrument(skip(self))]
     fn drop(&mut self) {
-        if !self.dropped {
+        if !self.dropped && !self.buffer.is_empty() {
             debug!(slot = self.slot, "dropping SlotWriter");
             let mut this = std::mem::take(self);
             this.dropped = true;
@@ -170,16 +203,36 @@ impl Drop for SlotWriter {
     }
 }
 
-pub fn get_account_path(slot: u64, id: u8) -> PathBuf {
-    get_vault_path()
+pub fn get_account_path(slot: u64, id: u8) -> Result<PathBuf> {
+    Ok(get_vault_path()?
         .join("accounts")
-        .join(format!("{slot}.{id}"))
+        .join(format!("{slot}.{id}")))
+}
+
+impl DescribeLayout for AccountDiskLocation {
+    const NAME: &'static str = "AccountDiskLocation";
+    const DESCRIPTION: &'static str =
+        "Where the last version of an account is, in the account files of the vault.";
+
+    fn fields() -> Vec<Field> {
+        vec![
+            Field::new("slot", "u64", Size::Fixed(8)),
+            Field::new("id", "u8", Size::Fixed(1)),
+            Field::new("offset", "u64", Size::Fixed(8)),
+            Field::new("size", "u64", Size::Fixed(8)),
+        ]
+    }
+
+    fn extents(&self) -> Vec<Extent> {
+        vec![Extent::Fixed; 4]
+    }
 }
 
 #[cfg(test)]
 #[cfg_attr(coverage_nightly, coverage(off))]
 mod tests {
 
+    use std::assert_matches::assert_matches;
     use std::fs::remove_dir_all;
     use std::path::Path;
 
@@ -188,6 +241,7 @@ mod tests {
     use crate::io::support::write_to_file;
     use crate::io::vault::{set_vault_path, Vault};
 
+    use super::super::Error;
     use super::*;
     type TestResult = core::result::Result<(), Box<dyn core::error::Error>>;
 
@@ -199,19 +253,69 @@ mod tests {
         if Path::new(VAULT).exists() {
             remove_dir_all(Path::new(VAULT))?;
         }
-        set_vault_path(VAULT);
+        set_vault_path(VAULT)?;
         Vault::init_vault().await?;
-        write_to_file(get_vault_path().join("accounts").join("0.0"), &[1, 2, 3]).await?;
-        write_to_file(get_vault_path().join("accounts").join("0.1"), &[1, 2, 3]).await?;
-        write_to_file(get_vault_path().join("accounts").join("0.2"), &[1, 2, 3]).await?;
-        write_to_file(get_vault_path().join("accounts").join("0.4"), &[1, 2, 3]).await?;
+        write_to_file(get_vault_path()?.join("accounts").join("0.0"), &[1, 2, 3]).await?;
+        write_to_file(get_vault_path()?.join("accounts").join("0.1"), &[1, 2, 3]).await?;
+        write_to_file(get_vault_path()?.join("accounts").join("0.2"), &[1, 2, 3]).await?;
+        write_to_file(get_vault_path()?.join("accounts").join("0.4"), &[1, 2, 3]).await?;
 
         // When
-        let id = get_id_from_files(0);
+        let id = get_id_from_files(0)?;
 
         // Then
         assert_eq!(id, 4);
 
         Ok(())
     }
+
+    #[test(tokio::test)]
+    async fn unexpected_account_file_is_an_error() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/location-4";
+        if Path::new(VAULT).exists() {
+            remove_dir_all(Path::new(VAULT))?;
+        }
+        set_vault_path(VAULT)?;
+        Vault::init_vault().await?;
+        std::fs::write(get_vault_path()?.join("accounts").join("0.backup"), [1_u8])?;
+
+        // When
+        let res = get_id_from_files(0);
+
+        // Then
+        assert_matches!(res, Err(Error::FileSystem(err)) if err.kind() == ErrorKind::InvalidData);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn corrupted_account_names_file_type_and_offset() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/location-3";
+        if Path::new(VAULT).exists() {
+            remove_dir_all(Path::new(VAULT))?;
+        }
+        set_vault_path(VAULT)?;
+        Vault::init_vault().await?;
+        std::fs::write(get_account_path(0, 0)?, [0xFF; 5])?;
+        let loc = AccountDiskLocation {
+            slot: 0,
+            id: 0,
+            offset: 3,
+            size: 2,
+        };
+
+        // When
+        let res = loc.read().await;
+
+        // Then
+        assert_matches!(
+            res,
+            Err(Error::Deserialization { path, kind, offset: 3, .. })
+                if path.ends_with("0.0") && kind.contains("Wallet")
+        );
+
+        Ok(())
+    }
 }
@@ -0,0 +1,218 @@
+// File: src/io/migration.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 13:20:08
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::path::PathBuf;
+
+use tracing::{debug, info, instrument, warn};
+
+use super::{
+    support::{read_from_file, write_to_file},
+    vault::get_vault_path,
+    Error, Result, Vault,
+};
+
+/// The version of the vault layout written by this version of the crate.
+pub const VAULT_VERSION: u32 = 1;
+
+/// The version of the vaults created before the layout was versioned.
+const UNVERSIONED: u32 = 0;
+
+impl Vault {
+    /// Checks whether the vault on the disk has to be migrated before being opened.
+    ///
+    /// # Errors
+    /// If the vault was written by a newer version of the crate, or on I/O issues.
+    pub async fn needs_migration() -> Result<bool> {
+        let Some(version) = stored_version().await? else {
+            return Ok(false);
+        };
+        check_supported(version)?;
+
+        Ok(version < VAULT_VERSION)
+    }
+
+    /// Migrates the vault on the disk to the current layout.
+    ///
+    /// The steps are applied in order, the version being saved after each of them.
+    /// Each step can be run again, so an interrupted migration can be resumed.
+    ///
+    /// # Errors
+    /// If the vault was written by a newer version of the crate, or on I/O issues.
+    #[instrument]
+    pub async fn migrate() -> Result<()> {
+        let Some(mut version) = stored_version().await? else {
+            debug!("no vault to migrate");
+            return Ok(());
+        };
+        check_supported(version)?;
+        while version < VAULT_VERSION {
+            info!(from = version, "migrating the vault");
+            migrate_from(version)?;
+            version += 1;
+            write_version(version).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Refuses to open a vault whose layout isn't the current one.
+    pub(super) async fn check_version() -> Result<()> {
+        let Some(version) = stored_version().await? else {
+            return Ok(());
+        };
+        check_supported(version)?;
+        if version < VAULT_VERSION {
+            warn!(version, "the vault needs to be migrated");
+            return Err(Error::VaultNeedsMigration { found: version });
+        }
+
+        Ok(())
+    }
+}
+
+/// Runs the step migrating the vault from a version to the next one.
+fn migrate_from(version: u32) -> Result<()> {
+    match version {
+        UNVERSIONED => {
+            debug!("the layout didn't change when it was versioned, recording the version");
+            Ok(())
+        }
+        _ => Err(Error::UnsupportedVaultVersion {
+            found: version,
+            supported: VAULT_VERSION,
+        }),
+    }
+}
+
+const fn check_supported(version: u32) -> Result<()> {
+    if version > VAULT_VERSION {
+        return Err(Error::UnsupportedVaultVersion {
+            found: version,
+            supported: VAULT_VERSION,
+        });
+    }
+
+    Ok(())
+}
+
+/// Get the version of the vault on the disk, if there's one (it has an accounts folder).
+async fn stored_version() -> Result<Option<u32>> {
+    if !get_vault_path()?.join("accounts").is_dir() {
+        return Ok(None);
+    }
+    let file = version_path()?;
+    if !file.exists() {
+        return Ok(Some(UNVERSIONED));
+    }
+
+    read_from_file(file).await.map(Some)
+}
+
+/// Records the version of the vault's layout.
+pub(super) async fn write_version(version: u32) -> Result<()> {
+    write_to_file(version_path()?, &version).await
+}
+
+fn version_path() -> Result<PathBuf> {
+    Ok(get_vault_path()?.join("vault_version"))
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use std::assert_matches::assert_matches;
+    use std::fs::{remove_dir_all, remove_file};
+
+    use test_log::test;
+
+    use crate::account::Wallet;
+    use crate::crypto::Keypair;
+    use crate::io::set_vault_path;
+
+    use super::*;
+    type TestResult = core::result::Result<(), Box<dyn core::error::Error>>;
+
+    #[test(tokio::test)]
+    async fn unversioned_vault_is_migrated() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/migration-1";
+        set_vault_path(VAULT)?;
+        if PathBuf::from(VAULT).exists() {
+            remove_dir_all(VAULT)?;
+        }
+        let key = Keypair::generate().pubkey();
+        let mut vault = Vault::load_or_create().await?;
+        vault.save_account(key, &Wallet::new(1_000), 1).await?;
+        vault.save().await?;
+        drop(vault);
+        remove_file(version_path()?)?;
+
+        // When
+        let needed = Vault::needs_migration().await?;
+        let refused = Vault::load_or_create().await.err();
+        Vault::migrate().await?;
+        Vault::migrate().await?;
+
+        // Then
+        assert!(needed);
+        assert_matches!(refused, Some(Error::VaultNeedsMigration { found: 0 }));
+        assert!(!Vault::needs_migration().await?);
+        let migrated = Vault::load_or_create().await?;
+        assert_eq!(migrated.get(&key).await?.prisms, 1_000);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn newer_vault_is_refused() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/migration-2";
+        set_vault_path(VAULT)?;
+        if PathBuf::from(VAULT).exists() {
+            remove_dir_all(VAULT)?;
+        }
+        Vault::init_vault().await?;
+        write_version(VAULT_VERSION + 1).await?;
+
+        // When
+        let res = Vault::load_or_create().await.err();
+
+        // Then
+        assert_matches!(
+            res,
+            Some(Error::UnsupportedVaultVersion { found, supported: VAULT_VERSION })
+                if found == VAULT_VERSION + 1
+        );
+        assert_matches!(
+            Vault::migrate().await,
+            Err(Error::UnsupportedVaultVersion { .. })
+        );
+
+        Ok(())
+    }
+}
@@ -3,7 +3,7 @@
 // Creation date: Sunday 09 February 2025
 // Author: Vincent Berthier <vincent.berthier@posteo.org>
 // -----
-// Last modified: Monday 10 February 2025 @ 20:49:48
+// Last modified: Thursday 15 October 2026 @ 20:53:45
 // Modified by: Vincent Berthier
 // -----
 // Copyright (c) 2025 <Vincent Berthier>
@@ -26,22 +26,43 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
+mod account_cache;
+mod accounts_hash;
+mod backup;
+mod balance_journal;
+mod commitment;
 mod error;
+mod filter;
 mod index;
 mod location;
+mod migration;
 mod support;
 mod trash;
 mod vault;
+mod watch_wallet;
+mod write_pool;
 
-pub use error::Error;
+pub use error::{AccountFetchError, Error};
+pub(crate) use index::Index;
+pub(crate) use location::AccountDiskLocation;
+pub(crate) use vault::get_vault_path;
 type Result<T> = core::result::Result<T, Error>;
 
-pub use vault::{set_vault_path, Vault};
+pub use account_cache::{CacheStats, DEFAULT_CACHE_CAPACITY};
+pub use accounts_hash::AccountsHash;
+pub use backup::{BackupFile, BackupManifest, MANIFEST_FILE};
+pub use balance_journal::BalanceChange;
+pub use commitment::{Commitment, CommitmentSlots};
+pub use filter::{AccountFilter, MAX_ACCOUNT_FILTERS, MAX_MEMCMP_BYTES};
+pub use migration::VAULT_VERSION;
+pub use vault::{set_vault_path, Checkpoint, Vault, MAX_MULTIPLE_ACCOUNTS, MAX_PREFIX_CANDIDATES};
+pub use watch_wallet::{WatchEvent, WatchWallet, MAX_WATCHED_CHANGES};
+pub use write_pool::{FileWrite, WritePool, DEFAULT_WRITE_WORKERS};
 
-/// Maximum size for an account file.
+/// Maximum size for an account file (holds 32 wallets without data in tests).
 #[cfg(test)]
 #[cfg_attr(test, mutants::skip)]
-pub const MAX_ACCOUNT_FILE_SIZE: u64 = 250;
+pub const MAX_ACCOUNT_FILE_SIZE: u64 = 375;
 
 /// Maximum size for an account file.
 #[cfg(not(test))]
@@ -3,7 +3,7 @@
 // Creation date: Sunday 09 February 2025
 // Author: Vincent Berthier <vincent.berthier@posteo.org>
 // -----
-// Last modified: Saturday 15 February 2025 @ 17:01:55
+// Last modified: Thursday 15 October 2026 @ 20:47:26
 // Modified by: Vincent Berthier
 // -----
 // Copyright (c) 2025 <Vincent Berthier>
@@ -47,10 +47,9 @@ where
     T: BorshDeserialize,
 {
     debug!("reading data from file");
-    let data = fs::read(path.into()).await?;
-    trace!(kind = type_name::<T>(), "casting data");
-    let res: T = borsh::from_slice(&data)?;
-    Ok(res)
+    let path = path.into();
+    let data = fs::read(&path).await?;
+    deserialize(&data, path, 0)
 }
 
 #[instrument]
@@ -60,7 +59,8 @@ where
     T: BorshDeserialize,
 {
     debug!("reading data from file memmap");
-    let file = File::open(path.into()).await?;
+    let path = path.into();
+    let file = File::open(&path).await?;
     let file_len = file.metadata().await?.len();
     if offset + size > file_len {
         return Err(Error::OutOfBounds {
@@ -81,11 +81,22 @@ where
             .map(&file)?
     };
 
-    let res: T = borsh::from_slice(&mmap)?;
-    Ok(res)
+    deserialize(&mmap, path, offset)
+}
+
+fn deserialize<T>(data: &[u8], path: PathBuf, offset: u64) -> Result<T>
+where
+    T: BorshDeserialize,
+{
+    trace!(kind = type_name::<T>(), "casting data");
+    borsh::from_slice(data).map_err(|reason| Error::Deserialization {
+        path,
+        kind: type_name::<T>(),
+        offset,
+        reason,
+    })
 }
 
-#[expect(clippy::unwrap_used)]
 #[instrument(skip(data))]
 pub async fn write_to_file<P, B>(path: P, data: &B) -> Result<()>
 where
@@ -93,7 +104,7 @@ where
     B: BorshSerialize + Send + Sync,
 {
     debug!(kind = type_name::<B>(), "writing data to file");
-    let data = borsh::to_vec(data).unwrap();
+    let data = borsh::to_vec(data)?;
     let mut file = OpenOptions::new()
         .create(true)
         .write(true)
@@ -164,6 +175,7 @@ mod tests {
         assert_matches!(res, Err(Error::FileSystem(err)) if matches!(err.kind(), std::io::ErrorKind::NotFound));
     }
 
+    #[cfg(unix)]
     #[test(tokio::test)]
     async fn folder_creation_fails_when_no_permission() {
         // Given
@@ -187,7 +199,7 @@ mod tests {
         if path.exists() {
             remove_file(&path).await?;
         }
-        let wallet = Wallet { prisms: 989_237 };
+        let wallet = Wallet::new(989_237);
         let data = borsh::to_vec(&wallet).unwrap();
         append_to_file(&path, &data).await?;
         append_to_file(&path, &data).await?;
@@ -210,7 +222,7 @@ mod tests {
         if path.exists() {
             remove_file(&path).await?;
         }
-        let wallet = Wallet { prisms: 989_237 };
+        let wallet = Wallet::new(989_237);
         let data = borsh::to_vec(&wallet).unwrap();
         let write_size = data.len() as u64;
         append_to_file(&path, &data).await?;
@@ -219,7 +231,7 @@ mod tests {
         let reloaded: Result<Wallet> = read_from_file_map(path, write_size, write_size).await;
 
         // Then
-        assert_matches!(reloaded, Err(Error::OutOfBounds { from, to, size }) if from == 8 && to == 16 && size == 8);
+        assert_matches!(reloaded, Err(Error::OutOfBounds { from, to, size }) if from == write_size && to == 2 * write_size && size == write_size);
 
         Ok(())
     }
@@ -3,7 +3,7 @@
 // Creation date: Monday 10 February 2025
 // Author: Vincent Berthier <vincent.berthier@posteo.org>
 // -----
-// Last modified: Thursday 13 February 2025 @ 09:51:51
+// Last modified: Thursday 15 October 2026 @ 15:47:08
 // Modified by: Vincent Berthier
 // -----
 // Copyright (c) 2025 <Vincent Berthier>
@@ -34,8 +34,8 @@ use tracing::{debug, instrument, trace, warn};
 use crate::io::support::write_to_file;
 
 use super::{
-    location::AccountDiskLocation, support::read_from_file, vault::get_vault_path, Error, Result,
-    MAX_ACCOUNT_FILE_SIZE,
+    location::AccountDiskLocation, support::read_from_file, vault::get_vault_path,
+    write_pool::FileWrite, Error, Result, MAX_ACCOUNT_FILE_SIZE,
 };
 
 #[derive(
@@ -101,12 +101,16 @@ impl Trash {
     #[instrument]
     pub async fn load_or_create() -> Self {
         debug!("initializing trash");
-        if let Ok(trash) = Self::load_from_disk().await {
-            trace!("trash could be reloaded from the disk");
-            return trash;
+        match Self::load_from_disk().await {
+            Ok(trash) => {
+                trace!("trash could be reloaded from the disk");
+                return trash;
+            }
+            Err(err) => {
+                warn!("trash could not be reloaded from the disk ({err}): starting from scratch");
+            }
         }
 
-        warn!("trash could not be reloaded from the disk: starting from scratch");
         Self {
             trash: HashMap::new(),
         }
@@ -114,7 +118,7 @@ impl Trash {
 
     #[instrument]
     async fn load_from_disk() -> Result<Self> {
-        let trash_path = Self::get_path();
+        let trash_path = Self::get_path()?;
         if !trash_path.exists() {
             return Err(Error::TrashFileNotFound);
         }
@@ -144,7 +148,12 @@ impl Trash {
     #[instrument(skip_all)]
     pub async fn save(&self) -> Result<()> {
         debug!("saving trash to file");
-        write_to_file(Self::get_path(), self).await
+        write_to_file(Self::get_path()?, self).await
+    }
+
+    /// The trash file, to write along with the rest of the vault.
+    pub fn file(&self) -> Result<FileWrite> {
+        Ok(FileWrite::new(Self::get_path()?, self))
     }
 
     #[expect(clippy::integer_division)]
@@ -163,8 +172,8 @@ impl Trash {
         self.trash.len()
     }
 
-    fn get_path() -> PathBuf {
-        get_vault_path().join("trash")
+    fn get_path() -> Result<PathBuf> {
+        Ok(get_vault_path()?.join("trash"))
     }
 }
 
@@ -191,7 +200,7 @@ mod tests {
         P: Into<PathBuf>,
     {
         let path = path.into();
-        set_vault_path(&path);
+        set_vault_path(&path)?;
         if path.exists() {
             remove_dir_all(path)?;
         }
@@ -236,12 +245,10 @@ mod tests {
         for slot in 0..4 {
             for i in 0..100 {
                 if i % 2 == 0 {
-                    vault
-                        .save_account(key, &Wallet { prisms: 983_373 }, slot)
-                        .await?;
+                    vault.save_account(key, &Wallet::new(983_373), slot).await?;
                 } else {
                     vault
-                        .save_account(Keypair::generate().pubkey(), &Wallet { prisms: 99 }, slot)
+                        .save_account(Keypair::generate().pubkey(), &Wallet::new(99), slot)
                         .await?;
                 }
             }
@@ -257,4 +264,25 @@ mod tests {
 
         Ok(())
     }
+
+    #[test(tokio::test)]
+    async fn corrupted_trash_names_file_and_type() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/trash-2";
+        reset_vault(VAULT)?;
+        Vault::init_vault().await?;
+        std::fs::write(Trash::get_path()?, [0xFF; 3])?;
+
+        // When
+        let res = Trash::load_from_disk().await;
+
+        // Then
+        assert_matches!(
+            res,
+            Err(Error::Deserialization { path, kind, offset: 0, .. })
+                if path.ends_with("trash") && kind.contains("Trash")
+        );
+
+        Ok(())
+    }
 }
@@ -3,7 +3,7 @@
 // Creation date: Sunday 09 February 2025
 // Author: Vincent Berthier <vincent.berthier@posteo.org>
 // -----
-// Last modified: Sunday 16 February 2025 @ 00:18:49
+// Last modified: Thursday 15 October 2026 @ 20:51:54
 // Modified by: Vincent Berthier
 // -----
 // Copyright (c) 2025 <Vincent Berthier>
@@ -26,42 +26,118 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use std::{collections::HashMap, path::PathBuf, sync::OnceLock};
-
-use tokio::fs::remove_file;
-use tracing::{debug, instrument, trace};
+use std::{
+    collections::HashMap,
+    fs::File,
+    path::PathBuf,
+    sync::{Mutex, OnceLock},
+};
 
-use crate::{account::Wallet, crypto::Pubkey, io::location::get_account_path};
+use borsh::BorshDeserialize;
+use tokio::{fs::remove_file, task::JoinSet};
+use tracing::{debug, instrument, trace, warn};
+
+use crate::{
+    account::{Stake, Wallet, DELEGATED_STAKE_SIZE},
+    crypto::{Pubkey, Signature},
+    io::location::get_account_path,
+    program::{stake::stake_address, Epoch, Slot},
+    validator::{
+        AuditCheckpoint, ClusterClock, EpochActivity, EpochRewards, IdentityHistory, LeaderSchedule,
+    },
+};
 
 use super::{
+    account_cache::{AccountCache, CacheStats, DEFAULT_CACHE_CAPACITY},
+    accounts_hash::AccountsHash,
+    balance_journal::{BalanceChange, BalanceJournal},
+    commitment::{Commitment, CommitmentSlots},
+    filter::AccountFilter,
     index::Index,
     location::SlotWriter,
-    support::create_folder,
+    migration::{write_version, VAULT_VERSION},
+    support::{create_folder, read_from_file},
     trash::{AccountFile, Trash},
-    Result,
+    write_pool::{FileWrite, WritePool, DEFAULT_WRITE_WORKERS},
+    AccountFetchError, Error, Result,
 };
 
+/// Maximum number of accounts fetched by a single [`Vault::get_multiple_accounts`].
+pub const MAX_MULTIPLE_ACCOUNTS: usize = 256;
+
+/// Maximum number of candidates reported when a prefix matches several accounts.
+pub const MAX_PREFIX_CANDIDATES: usize = 8;
+
+/// The name of the file locked while a vault is opened.
+pub const LOCK_FILE: &str = "lock";
+
+/// The name of the file recording the files of a save while they're moved in place.
+const COMMIT_FILE: &str = "commit";
+
+/// The folders of a vault, created along with it.
+pub(super) const VAULT_FOLDERS: [&str; 3] = ["accounts", "transactions", "blocks"];
+
 pub static VAULT_PATH: OnceLock<PathBuf> = OnceLock::new();
 
 /// Sets the path where the vault will be stored on disk.
 ///
+/// Setting the same path more than once has no effect.
+///
 /// # Parameters
 /// * `path` - Path to the on-disk vault.
 ///
 /// # Errors
-/// if the vault can not be created on the given path.
+/// If the path was already set to a different location.
 #[mutants::skip]
-#[expect(clippy::unwrap_used)]
-pub fn set_vault_path<P>(path: P)
+pub fn set_vault_path<P>(path: P) -> Result<()>
 where
     P: Into<PathBuf>,
 {
-    VAULT_PATH.set(path.into()).unwrap();
+    let path = path.into();
+    let current = VAULT_PATH.get_or_init(|| path.clone());
+    if *current != path {
+        warn!(?current, requested = ?path, "attempted to change the vault path");
+        return Err(Error::VaultPathAlreadySet {
+            current: current.clone(),
+            requested: path,
+        });
+    }
+
+    Ok(())
+}
+
+pub fn get_vault_path() -> Result<&'static PathBuf> {
+    VAULT_PATH.get().ok_or(Error::VaultNotInitialized)
+}
+
+/// Takes the lock of the vault's folder.
+///
+/// The lock is held by the operating system (advisory on Unix, mandatory on Windows),
+/// so it's released even if the process crashes.
+pub(super) fn lock_vault() -> Result<File> {
+    let path = get_vault_path()?;
+    let lock = File::options()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path.join(LOCK_FILE))?;
+    if !lock.try_lock()? {
+        warn!(?path, "the vault is already in use");
+        return Err(Error::VaultLocked { path: path.clone() });
+    }
+
+    Ok(lock)
 }
 
-#[expect(clippy::expect_used)]
-pub fn get_vault_path() -> &'static PathBuf {
-    VAULT_PATH.get().expect("vault path is not set")
+/// The state of some accounts, to undo the changes made to them afterwards.
+#[derive(Clone, Debug)]
+pub struct Checkpoint {
+    /// The accounts, as they were (empty if they didn't exist).
+    accounts: Vec<(Pubkey, Wallet)>,
+    /// The sequence number of the last transaction of the accounts, as payers.
+    sequences: Vec<(Pubkey, Option<u64>)>,
+    /// The prisms burned.
+    burned: u64,
 }
 
 /// Storage for all accounts on the blockchain.
@@ -72,48 +148,113 @@ pub struct Vault {
     trash: Trash,
     /// The account writer
     writer: SlotWriter,
-    /// Account cache
+    /// Accounts written during the current slot
     cache: HashMap<Pubkey, Wallet>,
+    /// Recently used accounts, to avoid reading them from the disk
+    accounts: Mutex<AccountCache>,
+    /// The hash of all the accounts in the vault.
+    hash: AccountsHash,
+    /// The total of prisms held by the accounts.
+    supply: u64,
+    /// The balance changes of the accounts, if they're recorded.
+    journal: Option<BalanceJournal>,
+    /// The last confirmed and finalized slots.
+    commitment: CommitmentSlots,
+    /// The prisms removed from the supply by the fees.
+    burned: u64,
+    /// The sequence number of the last transaction executed for each payer.
+    sequences: HashMap<Pubkey, u64>,
+    /// The successive identities of the validator.
+    identities: IdentityHistory,
+    /// The number of transactions of a bulk import already applied.
+    bulk_progress: u64,
+    /// The signed checkpoints of the ledger produced for the auditors.
+    audit_trail: Vec<AuditCheckpoint>,
+    /// What the validators did during the current epoch.
+    activity: EpochActivity,
+    /// The rewards paid at the end of each epoch, from the first one.
+    epoch_rewards: Vec<EpochRewards>,
+    /// The stake of each validator, snapshotted at the start of the current epoch.
+    leader_schedule: Option<LeaderSchedule>,
+    /// The estimated time of the recent blocks.
+    cluster_time: ClusterClock,
+    /// The workers writing the files of the vault when it's saved.
+    writes: WritePool,
+    /// The lock file keeping other vaults from opening the same folder, released on drop.
+    _lock: File,
 }
 
 impl Vault {
     /// Load or creates the vault.
     ///
+    /// The vault stays locked until it's dropped: it can't be opened twice.
+    ///
     /// # Errors
-    /// Only if the vault could not be initialized,
+    /// If the vault is already opened, or if it could not be initialized,
     /// which would only happen because of a file system error
     /// such as a permission issue.
     #[instrument]
     pub async fn load_or_create() -> Result<Self> {
         debug!("initializing vault");
         Self::init_vault().await?;
+        let lock = lock_vault()?;
+        let writes = WritePool::new(DEFAULT_WRITE_WORKERS, get_vault_path()?.join(COMMIT_FILE));
+        writes.recover().await?;
+        Self::check_version().await?;
+        let index = Index::load_or_create().await;
+        let mut hash = AccountsHash::default();
+        let mut supply = 0_u64;
+        for key in index.keys() {
+            if let Some(account) = index.load(&key).await? {
+                hash.insert(&key, &account);
+                supply = supply.saturating_add(account.prisms);
+            }
+        }
+
         Ok(Self {
-            index: Index::load_or_create().await,
+            index,
             trash: Trash::load_or_create().await,
-            writer: SlotWriter::new(0),
+            writer: SlotWriter::new(0)?,
             cache: HashMap::new(),
+            accounts: Mutex::new(AccountCache::new(DEFAULT_CACHE_CAPACITY)),
+            hash,
+            supply,
+            journal: None,
+            commitment: CommitmentSlots::default(),
+            burned: Self::load_state("burned").await?,
+            sequences: Self::load_state("sequences").await?,
+            identities: Self::load_state("identities").await?,
+            bulk_progress: Self::load_state("bulk_progress").await?,
+            audit_trail: Self::load_state("audit_trail").await?,
+            activity: Self::load_state("epoch_activity").await?,
+            epoch_rewards: Self::load_state("epoch_rewards").await?,
+            leader_schedule: Self::load_state("leader_schedule").await?,
+            cluster_time: Self::load_state("block_times").await?,
+            writes,
+            _lock: lock,
         })
     }
 
     /// Initializes the vault.
     ///
-    /// This mostly just creates the folder architecture if it's needed.
+    /// This mostly just creates the folder architecture if it's needed,
+    /// and records the version of its layout.
     ///
     /// # Errors
-    /// Can only happen in case of file system errors.
+    /// If the vault path wasn't set, or in case of file system errors.
     #[mutants::skip]
     #[instrument]
     pub async fn init_vault() -> Result<()> {
         debug!("initializing vault");
-        let path = get_vault_path();
+        let path = get_vault_path()?;
         if path.exists() {
             return Ok(());
         }
-        for folder in ["accounts", "transactions", "blocks"] {
+        for folder in VAULT_FOLDERS {
             create_folder(path.join(folder)).await?;
         }
 
-        Ok(())
+        write_version(VAULT_VERSION).await
     }
 
     /// Creates or loads an account from the disk.
@@ -126,14 +267,221 @@ impl Vault {
     #[instrument(skip(self))]
     pub async fn get(&self, key: &Pubkey) -> Result<Wallet> {
         debug!("getting account");
-        let res = match self.cache.get(key) {
-            Some(&account) => {
-                trace!("account found in the cache");
-                account
+        if let Some(account) = self.cache.get(key) {
+            trace!("account found in the slot cache");
+            return Ok(account.clone());
+        }
+        let cached = self.lock_accounts().get(key);
+        if let Some(account) = cached {
+            trace!("account found in the account cache");
+            return Ok(account);
+        }
+        let account = self.index.load(key).await?;
+        if let Some(account) = &account {
+            self.lock_accounts().insert(*key, account.clone());
+        }
+
+        Ok(account.unwrap_or_default())
+    }
+
+    /// Fetches several accounts at once, reading the account files in parallel.
+    ///
+    /// The results are in the order of the keys: an account that doesn't exist is `None`,
+    /// and an account that can't be read doesn't keep the other ones from being fetched.
+    ///
+    /// # Parameters
+    /// * `keys` - The public keys of the accounts to fetch.
+    ///
+    /// # Errors
+    /// If more than [`MAX_MULTIPLE_ACCOUNTS`] keys are given.
+    #[instrument(skip_all, fields(n = keys.len()))]
+    pub async fn get_multiple_accounts(
+        &self,
+        keys: &[Pubkey],
+    ) -> Result<Vec<core::result::Result<Option<Wallet>, AccountFetchError>>> {
+        debug!("getting multiple accounts");
+        if keys.len() > MAX_MULTIPLE_ACCOUNTS {
+            warn!("too many accounts requested at once");
+            return Err(Error::TooManyAccounts {
+                requested: keys.len(),
+                max: MAX_MULTIPLE_ACCOUNTS,
+            });
+        }
+        let mut results = keys.iter().map(|_| Ok(None)).collect::<Vec<_>>();
+        let mut files = HashMap::<_, Vec<_>>::new();
+        for (position, key) in keys.iter().enumerate() {
+            if let Some(account) = self.cache.get(key) {
+                results[position] = Ok(Some(account.clone()));
+                continue;
             }
-            None => (self.index.load(key).await?).unwrap_or_default(),
-        };
-        Ok(res)
+            let cached = self.lock_accounts().get(key);
+            if let Some(account) = cached {
+                results[position] = Ok(Some(account));
+                continue;
+            }
+            if let Some(&loc) = self.index.find(key) {
+                files
+                    .entry((loc.slot, loc.id))
+                    .or_default()
+                    .push((position, loc));
+            }
+        }
+
+        trace!(files = files.len(), "reading the account files");
+        let mut reads = JoinSet::new();
+        for locations in files.into_values() {
+            reads.spawn(async move {
+                let mut read = Vec::with_capacity(locations.len());
+                for (position, loc) in locations {
+                    read.push((position, loc.read().await));
+                }
+                read
+            });
+        }
+        for (position, read) in reads.join_all().await.into_iter().flatten() {
+            results[position] = match read {
+                Ok(account) => {
+                    self.lock_accounts().insert(keys[position], account.clone());
+                    Ok(Some(account))
+                }
+                Err(err) => {
+                    warn!(key = %keys[position], "could not read the account: {err}");
+                    Err(err.into())
+                }
+            };
+        }
+
+        Ok(results)
+    }
+
+    /// Finds the account whose public key starts with a prefix.
+    ///
+    /// Only the index is scanned, no account is read from the disk.
+    /// A prefix that is the full key of a known account resolves to it,
+    /// even if other keys start with it.
+    ///
+    /// # Parameters
+    /// * `prefix` - The first characters of the `bs58` representation of the key.
+    ///
+    /// # Errors
+    /// If no known account starts with the prefix, or if several do
+    /// (the first [`MAX_PREFIX_CANDIDATES`] of them are reported).
+    #[instrument(skip(self))]
+    pub fn resolve_prefix(&self, prefix: &str) -> Result<Pubkey> {
+        debug!("resolving account prefix");
+        if let Ok(key) = prefix.parse::<Pubkey>() {
+            if self.index.find(&key).is_some() {
+                trace!("the prefix is a known key");
+                return Ok(key);
+            }
+        }
+
+        let mut candidates = self.index.keys_with_prefix(prefix);
+        match candidates.as_slice() {
+            [] => Err(Error::UnknownAccountPrefix {
+                prefix: prefix.to_owned(),
+            }),
+            [key] => Ok(*key),
+            _ => {
+                let matches = candidates.len();
+                candidates.sort_unstable_by_key(ToString::to_string);
+                candidates.truncate(MAX_PREFIX_CANDIDATES);
+                Err(Error::AmbiguousAccountPrefix {
+                    prefix: prefix.to_owned(),
+                    matches,
+                    candidates,
+                })
+            }
+        }
+    }
+
+    /// Loads accounts in the account cache, so that the first transactions
+    /// using them don't have to read them from the disk.
+    ///
+    /// # Parameters
+    /// * `keys` - The public keys of the accounts to load.
+    ///
+    /// # Errors
+    /// If the index failed to load an existing account.
+    #[instrument(skip_all)]
+    pub async fn preload(&self, keys: &[Pubkey]) -> Result<()> {
+        debug!(count = keys.len(), "preloading accounts");
+        for key in keys {
+            if let Some(account) = self.index.load(key).await? {
+                self.lock_accounts().insert(*key, account);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Changes the maximum memory used by the account cache.
+    ///
+    /// # Parameters
+    /// * `capacity` - The capacity of the cache, in bytes.
+    pub fn set_cache_capacity(&mut self, capacity: usize) {
+        self.lock_accounts().set_capacity(capacity);
+    }
+
+    /// Get the hits and misses of the account cache.
+    #[must_use]
+    pub fn cache_stats(&self) -> CacheStats {
+        self.lock_accounts().stats()
+    }
+
+    /// Get the memory used by the account cache, in bytes.
+    #[must_use]
+    pub fn cache_size(&self) -> usize {
+        self.lock_accounts().size()
+    }
+
+    fn lock_accounts(&self) -> std::sync::MutexGuard<'_, AccountCache> {
+        #[expect(clippy::unwrap_used, reason = "the cache never panics while locked")]
+        self.accounts.lock().unwrap()
+    }
+
+    /// Finds the accounts matching all the filters, ordered by public key.
+    ///
+    /// The size of the data of each account is known from the index: accounts
+    /// whose size can't match a filter are excluded without being read.
+    ///
+    /// # Parameters
+    /// * `filters` - The conditions on the data of the accounts.
+    ///
+    /// # Errors
+    /// If the filters are invalid, or if a candidate account couldn't be read.
+    #[instrument(skip_all, fields(n = filters.len()))]
+    pub async fn get_filtered_accounts(
+        &self,
+        filters: &[AccountFilter],
+    ) -> Result<Vec<(Pubkey, Wallet)>> {
+        debug!("getting filtered accounts");
+        AccountFilter::validate_all(filters)?;
+        let mut candidates = self
+            .index
+            .locations()
+            .filter(|(_key, loc)| {
+                filters
+                    .iter()
+                    .all(|filter| filter.accepts_data_len(loc.data_len()))
+            })
+            .map(|(key, _loc)| *key)
+            .collect::<Vec<_>>();
+        candidates.sort_unstable();
+        trace!(
+            candidates = candidates.len(),
+            "reading the candidate accounts"
+        );
+
+        let mut accounts = Vec::new();
+        for key in candidates {
+            let account = self.get(&key).await?;
+            if filters.iter().all(|filter| filter.matches(&account)) {
+                accounts.push((key, account));
+            }
+        }
+
+        Ok(accounts)
     }
 
     // TODO: will need to handle saving the same account multiple times for the same slot
@@ -150,6 +498,12 @@ impl Vault {
     #[instrument(skip(self, account))]
     pub async fn save_account(&mut self, key: Pubkey, account: &Wallet, slot: u64) -> Result<()> {
         debug!("saving account");
+        let old = self.get(&key).await?;
+        self.hash.update(&key, &old, account);
+        self.supply = self
+            .supply
+            .saturating_sub(old.prisms)
+            .saturating_add(account.prisms);
         if let Some(&old_loc) = self.index.find(&key) {
             trace!(
                 ?old_loc,
@@ -157,19 +511,486 @@ impl Vault {
             );
             self.trash.insert(old_loc)?;
         }
-
-        if self.writer.slot() != slot {
-            self.writer = SlotWriter::new(slot);
-            self.cache.clear();
+
+        if self.writer.slot() != slot {
+            // flushed now rather than when dropped, so the file is complete once saved
+            self.writer.flush().await?;
+            self.writer = SlotWriter::new(slot)?;
+            self.cache.clear();
+        }
+        self.cache.insert(key, account.clone());
+        self.lock_accounts().insert(key, account.clone());
+        let loc = self.writer.append(account).await?;
+        self.index.set_account(key, loc);
+
+        Ok(())
+    }
+
+    /// Removes an account from the vault.
+    ///
+    /// Getting the account afterwards returns an empty account.
+    ///
+    /// # Parameters
+    /// * `key` - The public key of the account to remove.
+    ///
+    /// # Errors
+    /// If the account couldn't be read from the disk.
+    #[instrument(skip(self))]
+    pub async fn remove_account(&mut self, key: &Pubkey) -> Result<()> {
+        debug!("removing account");
+        let old = self.get(key).await?;
+        self.hash.remove(key, &old);
+        self.supply = self.supply.saturating_sub(old.prisms);
+        self.cache.remove(key);
+        self.lock_accounts().remove(key);
+        if let Some(old_loc) = self.index.remove_account(key) {
+            trace!(
+                ?old_loc,
+                "placing the account's old location into the trash"
+            );
+            self.trash.insert(old_loc)?;
+        }
+
+        Ok(())
+    }
+
+    /// Starts recording the balance changes made by transactions.
+    ///
+    /// The changes recorded previously are reloaded from the disk.
+    #[instrument(skip(self))]
+    pub async fn enable_balance_history(&mut self) {
+        debug!("enabling balance history");
+        if self.journal.is_none() {
+            self.journal = Some(BalanceJournal::load_or_create().await);
+        }
+    }
+
+    /// Records the change of an account's balance made by a transaction.
+    ///
+    /// Does nothing unless the balance history is enabled.
+    ///
+    /// # Parameters
+    /// * `key` - The public key of the account,
+    /// * `slot` - The slot during which the transaction was executed,
+    /// * `signature` - The signature of the transaction,
+    /// * `before` - The balance before the transaction,
+    /// * `after` - The balance after the transaction.
+    pub fn record_balance(
+        &mut self,
+        key: Pubkey,
+        slot: u64,
+        signature: Signature,
+        before: u64,
+        after: u64,
+    ) {
+        if let Some(journal) = self.journal.as_mut() {
+            journal.record(key, slot, signature, before, after);
+        }
+    }
+
+    /// Get the balance of an account at the end of a slot.
+    ///
+    /// # Parameters
+    /// * `key` - The public key of the account,
+    /// * `slot` - The slot.
+    ///
+    /// # Returns
+    /// The balance, or `None` if the history is disabled or has no change
+    /// for the account up to that slot.
+    #[must_use]
+    pub fn get_balance_at_slot(&self, key: &Pubkey, slot: u64) -> Option<u64> {
+        self.journal.as_ref()?.balance_at_slot(key, slot)
+    }
+
+    /// Get the balance of an account as seen at a commitment level.
+    ///
+    /// The processed balance is the current one. The confirmed and finalized ones
+    /// undo the changes made after the last confirmed or finalized slot, which
+    /// requires the balance history (pruning it past the finalized slot keeps
+    /// both available).
+    ///
+    /// # Parameters
+    /// * `key` - The public key of the account,
+    /// * `commitment` - The view to read the balance from.
+    ///
+    /// # Errors
+    /// If the account can't be read, or if a past balance is requested
+    /// while the balance history is disabled.
+    #[instrument(skip(self))]
+    pub async fn get_balance(&self, key: &Pubkey, commitment: Commitment) -> Result<u64> {
+        let current = self.get(key).await?.prisms;
+        let slot = match commitment {
+            Commitment::Processed => return Ok(current),
+            Commitment::Confirmed => self.commitment.confirmed,
+            Commitment::Finalized => self.commitment.finalized,
+        };
+        let journal = self.journal.as_ref().ok_or(Error::BalanceHistoryDisabled)?;
+
+        Ok(journal.rewind(key, slot).unwrap_or(current))
+    }
+
+    /// The last confirmed and finalized slots.
+    #[must_use]
+    pub const fn commitment(&self) -> CommitmentSlots {
+        self.commitment
+    }
+
+    /// Marks a slot as confirmed.
+    ///
+    /// # Parameters
+    /// * `slot` - The slot that reached the vote threshold.
+    pub fn confirm(&mut self, slot: u64) {
+        self.commitment.confirmed = self.commitment.confirmed.max(slot);
+    }
+
+    /// Marks a slot as finalized, which also confirms it.
+    ///
+    /// # Parameters
+    /// * `slot` - The slot that can't be reverted anymore.
+    pub fn finalize(&mut self, slot: u64) {
+        self.confirm(slot);
+        self.commitment.finalized = self.commitment.finalized.max(slot);
+    }
+
+    /// Get a page of the balance changes of an account between two slots (inclusive).
+    ///
+    /// # Parameters
+    /// * `key` - The public key of the account,
+    /// * `from_slot` - The first slot of the range,
+    /// * `to_slot` - The last slot of the range,
+    /// * `page` - The index of the page,
+    /// * `page_size` - The maximum number of changes in a page.
+    #[must_use]
+    pub fn get_balance_history(
+        &self,
+        key: &Pubkey,
+        from_slot: u64,
+        to_slot: u64,
+        page: usize,
+        page_size: usize,
+    ) -> Vec<BalanceChange> {
+        self.journal.as_ref().map_or_else(Vec::new, |journal| {
+            journal.history(key, from_slot, to_slot, page, page_size)
+        })
+    }
+
+    /// Forgets the balance changes older than a slot.
+    ///
+    /// The last change before the boundary is kept, so that the balances
+    /// at the boundary can still be queried.
+    ///
+    /// # Parameters
+    /// * `boundary` - The oldest slot whose balances must be kept.
+    pub fn prune_balance_history(&mut self, boundary: u64) {
+        if let Some(journal) = self.journal.as_mut() {
+            journal.prune(boundary);
+        }
+    }
+
+    /// Removes prisms from the supply.
+    ///
+    /// # Parameters
+    /// * `amount` - The number of prisms burned.
+    pub const fn burn(&mut self, amount: u64) {
+        self.burned = self.burned.saturating_add(amount);
+    }
+
+    /// Get the total number of prisms burned.
+    #[must_use]
+    pub const fn burned(&self) -> u64 {
+        self.burned
+    }
+
+    /// Get the number of transactions of a bulk import already applied.
+    #[must_use]
+    pub const fn bulk_progress(&self) -> u64 {
+        self.bulk_progress
+    }
+
+    /// Records how many transactions of a bulk import were applied.
+    ///
+    /// # Parameters
+    /// * `applied` - The number of transactions applied, from the start of the import.
+    pub const fn set_bulk_progress(&mut self, applied: u64) {
+        self.bulk_progress = applied;
+    }
+
+    /// Get the total of prisms held by the accounts.
+    #[must_use]
+    pub const fn supply(&self) -> u64 {
+        self.supply
+    }
+
+    /// Get the signed checkpoints of the ledger, from the first one.
+    #[expect(clippy::missing_const_for_fn, reason = "false positive")]
+    #[must_use]
+    pub fn audit_trail(&self) -> &[AuditCheckpoint] {
+        &self.audit_trail
+    }
+
+    /// Records a checkpoint of the ledger at the end of the audit trail.
+    ///
+    /// # Parameters
+    /// * `checkpoint` - The checkpoint, following the last one recorded.
+    pub fn record_checkpoint(&mut self, checkpoint: AuditCheckpoint) {
+        self.audit_trail.push(checkpoint);
+    }
+
+    /// Get what the validators did during the current epoch.
+    #[must_use]
+    pub const fn epoch_activity(&self) -> &EpochActivity {
+        &self.activity
+    }
+
+    /// Get what the validators did during the current epoch, to record more of it.
+    pub const fn epoch_activity_mut(&mut self) -> &mut EpochActivity {
+        &mut self.activity
+    }
+
+    /// Records the rewards of the epoch ending, and starts counting the activity
+    /// of the next one.
+    ///
+    /// # Parameters
+    /// * `rewards` - The rewards paid for the epoch.
+    pub fn record_epoch_rewards(&mut self, rewards: EpochRewards) {
+        self.activity = EpochActivity::default();
+        self.epoch_rewards.push(rewards);
+    }
+
+    /// Get the rewards paid at the end of an epoch.
+    ///
+    /// # Parameters
+    /// * `epoch` - The epoch.
+    ///
+    /// # Returns
+    /// The rewards, or `None` if the epoch wasn't rewarded (yet).
+    #[must_use]
+    pub fn get_epoch_rewards<E>(&self, epoch: E) -> Option<&EpochRewards>
+    where
+        E: Into<Epoch>,
+    {
+        let epoch = epoch.into();
+        self.epoch_rewards
+            .iter()
+            .find(|rewards| rewards.epoch == epoch)
+    }
+
+    /// Get the leader schedule of the current epoch, if its stake was snapshotted.
+    #[must_use]
+    pub const fn leader_schedule(&self) -> Option<&LeaderSchedule> {
+        self.leader_schedule.as_ref()
+    }
+
+    /// Get the estimated unix timestamp of a recent block.
+    ///
+    /// # Parameters
+    /// * `slot` - The slot of the block.
+    #[must_use]
+    pub fn get_block_time(&self, slot: u64) -> Option<i64> {
+        self.cluster_time.block_time(slot)
+    }
+
+    /// Records the estimated time of a block, from the timestamps reported by the validators.
+    ///
+    /// # Parameters
+    /// * `slot` - The slot of the block,
+    /// * `timestamps` - The unix timestamps reported by the validators for the slot,
+    /// * `max_step` - The maximum number of seconds the estimate may move forward in a single slot.
+    ///
+    /// # Returns
+    /// The estimated time of the block, if one could be made.
+    pub(crate) fn record_block_time(
+        &mut self,
+        slot: u64,
+        timestamps: &[i64],
+        max_step: i64,
+    ) -> Option<i64> {
+        self.cluster_time.record_slot(slot, timestamps, max_step)
+    }
+
+    /// Snapshots the stake delegated to each validator, as the leader schedule of an epoch.
+    ///
+    /// Only the delegated stake accounts held at the address derived from their staker
+    /// are counted: the others weren't written by the stake program.
+    ///
+    /// # Parameters
+    /// * `epoch` - The epoch starting.
+    ///
+    /// # Errors
+    /// If a stake account couldn't be read.
+    #[instrument(skip(self, epoch))]
+    pub async fn snapshot_stakes<E>(&mut self, epoch: E) -> Result<&LeaderSchedule>
+    where
+        E: Into<Epoch>,
+    {
+        debug!("snapshotting the stakes");
+        let stakes = self
+            .get_filtered_accounts(&[AccountFilter::DataSize(DELEGATED_STAKE_SIZE)])
+            .await?
+            .into_iter()
+            .filter_map(|(key, account)| {
+                let stake = borsh::from_slice::<Stake>(&account.data).ok()?;
+                let derived = stake_address(&stake.staker, stake.index).ok()?;
+                (derived == key).then_some(stake)
+            })
+            .collect::<Vec<_>>();
+        trace!(stakes = stakes.len(), "stake accounts found");
+
+        Ok(self
+            .leader_schedule
+            .insert(LeaderSchedule::new(epoch, &stakes)))
+    }
+
+    /// Get the sequence number of the last transaction executed for a payer.
+    ///
+    /// # Parameters
+    /// * `payer` - The public key of the payer.
+    #[must_use]
+    pub fn last_sequence(&self, payer: &Pubkey) -> Option<u64> {
+        self.sequences.get(payer).copied()
+    }
+
+    /// Records the sequence number of the last transaction executed for a payer.
+    ///
+    /// # Parameters
+    /// * `payer` - The public key of the payer,
+    /// * `sequence` - The sequence number of the transaction.
+    pub fn set_sequence(&mut self, payer: Pubkey, sequence: u64) {
+        self.sequences.insert(payer, sequence);
+    }
+
+    /// Get the successive identities of the validator.
+    #[must_use]
+    pub const fn identities(&self) -> &IdentityHistory {
+        &self.identities
+    }
+
+    /// Schedules a new identity for the validator, from the next epoch.
+    ///
+    /// The first identity recorded is valid from the start of the chain.
+    ///
+    /// # Parameters
+    /// * `identity` - The new identity of the validator,
+    /// * `current_slot` - The slot the validator is in.
+    ///
+    /// # Returns
+    /// The first slot of the new identity.
+    pub fn rotate_identity<S>(&mut self, identity: Pubkey, current_slot: S) -> Slot
+    where
+        S: Into<Slot>,
+    {
+        if self.identities == IdentityHistory::default() {
+            self.identities = IdentityHistory::new(identity);
+            return Slot::default();
+        }
+        self.identities.rotate(identity, current_slot)
+    }
+
+    /// Loads a part of the vault's state saved in its own file, or its default value
+    /// if it was never saved.
+    ///
+    /// # Errors
+    /// If the file exists but can't be read or decoded: starting from scratch would
+    /// forget the state (the sequences of the payers, the progress of an import…).
+    #[instrument]
+    async fn load_state<T>(name: &str) -> Result<T>
+    where
+        T: BorshDeserialize + Default,
+    {
+        let path = get_vault_path()?.join(name);
+        if !path.exists() {
+            return Ok(T::default());
+        }
+        read_from_file(path)
+            .await
+            .inspect_err(|err| warn!("{name} could not be reloaded from the disk: {err}"))
+    }
+
+    /// Get the slot the vault is writing accounts for.
+    pub(super) const fn slot(&self) -> u64 {
+        self.writer.slot()
+    }
+
+    /// Get the root of the hash of all the accounts in the vault.
+    #[must_use]
+    pub fn state_root(&self) -> [u8; 64] {
+        self.hash.root()
+    }
+
+    /// Records the state of some accounts, to restore it if the changes made to them
+    /// must be undone.
+    ///
+    /// # Parameters
+    /// * `keys` - The public keys of the accounts that may change.
+    ///
+    /// # Errors
+    /// If an account couldn't be read from the disk.
+    #[instrument(skip_all, fields(n = keys.len()))]
+    pub async fn checkpoint(&self, keys: &[Pubkey]) -> Result<Checkpoint> {
+        debug!("recording a checkpoint");
+        let mut accounts = Vec::with_capacity(keys.len());
+        for key in keys {
+            accounts.push((*key, self.get(key).await?));
+        }
+        let sequences = keys
+            .iter()
+            .map(|key| (*key, self.last_sequence(key)))
+            .collect();
+
+        Ok(Checkpoint {
+            accounts,
+            sequences,
+            burned: self.burned,
+        })
+    }
+
+    /// Undoes the changes made to the accounts of a checkpoint since it was recorded.
+    ///
+    /// # Parameters
+    /// * `checkpoint` - The state to restore,
+    /// * `slot` - The current slot,
+    /// * `undone` - The signatures of the transactions undone, whose balance changes are forgotten.
+    ///
+    /// # Errors
+    /// If an account couldn't be written on the disk.
+    #[instrument(skip_all)]
+    pub async fn rollback(
+        &mut self,
+        checkpoint: Checkpoint,
+        slot: u64,
+        undone: &[Signature],
+    ) -> Result<()> {
+        debug!("rolling back to a checkpoint");
+        for (key, account) in checkpoint.accounts {
+            if self.get(&key).await? == account {
+                continue;
+            }
+            if account == Wallet::default() {
+                self.remove_account(&key).await?;
+            } else {
+                self.save_account(key, &account, slot).await?;
+            }
+        }
+        for (payer, sequence) in checkpoint.sequences {
+            match sequence {
+                Some(sequence) => self.sequences.insert(payer, sequence),
+                None => self.sequences.remove(&payer),
+            };
+        }
+        self.burned = checkpoint.burned;
+        if let Some(journal) = self.journal.as_mut() {
+            journal.discard(undone);
         }
-        self.cache.insert(key, *account);
-        let loc = self.writer.append(account).await?;
-        self.index.set_account(key, loc);
 
         Ok(())
     }
 
-    /// Saves the vault on the disk (index and trash).
+    /// Saves the vault on the disk (index, trash and state).
+    ///
+    /// The accounts written since the last save are flushed first, then the files
+    /// referencing them are written concurrently: either all of them are replaced, or none.
+    /// If they couldn't all be moved in place, the remaining ones are moved by the next
+    /// save, or when the vault is opened again.
     ///
     /// # Errors
     /// Only if there was a problem saving the vault on the disk.
@@ -177,8 +998,25 @@ impl Vault {
     pub async fn save(&mut self) -> Result<()> {
         debug!("saving vault");
         self.writer.flush().await?;
-        self.index.save().await?;
-        self.trash.save().await
+        let path = get_vault_path()?;
+        let mut files = vec![
+            self.index.file()?,
+            self.trash.file()?,
+            FileWrite::new(path.join("burned"), &self.burned),
+            FileWrite::new(path.join("sequences"), &self.sequences),
+            FileWrite::new(path.join("identities"), &self.identities),
+            FileWrite::new(path.join("bulk_progress"), &self.bulk_progress),
+            FileWrite::new(path.join("audit_trail"), &self.audit_trail),
+            FileWrite::new(path.join("epoch_activity"), &self.activity),
+            FileWrite::new(path.join("epoch_rewards"), &self.epoch_rewards),
+            FileWrite::new(path.join("leader_schedule"), &self.leader_schedule),
+            FileWrite::new(path.join("block_times"), &self.cluster_time),
+        ];
+        if let Some(journal) = &self.journal {
+            files.push(journal.file()?);
+        }
+
+        self.writes.commit(files).await
     }
 
     /// Trims the accounts on the disk.
@@ -197,7 +1035,7 @@ impl Vault {
         debug!("cleaning up the vault");
         let mut to_clean = self.trash.get_files_to_clean().await;
         to_clean.sort();
-        let mut writer = SlotWriter::new(0);
+        let mut writer = SlotWriter::new(0)?;
         for file in to_clean {
             trace!(?file, "cleaning up the file");
             let AccountFile { slot, id } = file;
@@ -206,11 +1044,11 @@ impl Vault {
                 continue;
             }
             if slot != writer.slot() {
-                writer = SlotWriter::new(slot);
+                writer = SlotWriter::new(slot)?;
             }
             self.relocate_accounts(&mut writer, slot, id).await?;
             trace!(?file, "removing file from the disk");
-            remove_file(get_account_path(slot, id)).await?;
+            remove_file(get_account_path(slot, id)?).await?;
             trace!(?file, "removing file from the trash");
             self.trash.remove(&file);
         }
@@ -244,6 +1082,7 @@ mod tests {
 
     use std::assert_matches::assert_matches;
     use std::fs::{read_dir, remove_dir_all};
+    use std::path::Path;
     use std::time::Duration;
 
     use test_log::test;
@@ -252,7 +1091,6 @@ mod tests {
     use crate::account::Wallet;
     use crate::crypto::{Keypair, Pubkey};
     use crate::io::index::Index;
-    use crate::io::support::read_from_file;
     use crate::io::MAX_ACCOUNT_FILE_SIZE;
 
     // use super::super::Error;
@@ -268,7 +1106,7 @@ mod tests {
         P: Into<PathBuf>,
     {
         let path = path.into();
-        set_vault_path(&path);
+        set_vault_path(&path)?;
         if path.exists() {
             remove_dir_all(path)?;
         }
@@ -286,12 +1124,12 @@ mod tests {
         let key2 = Keypair::generate().pubkey();
         let key3 = Keypair::generate().pubkey();
 
-        let wallet1 = Wallet { prisms: AMOUNT1 };
-        let wallet2 = Wallet { prisms: AMOUNT2 };
-        let wallet3 = Wallet { prisms: AMOUNT3 };
+        let wallet1 = Wallet::new(AMOUNT1);
+        let wallet2 = Wallet::new(AMOUNT2);
+        let wallet3 = Wallet::new(AMOUNT3);
 
         let mut index = Index::load_or_create().await;
-        let mut writer = SlotWriter::new(82);
+        let mut writer = SlotWriter::new(82)?;
         let loc1 = writer.append(&wallet1).await?;
         let loc2 = writer.append(&wallet2).await?;
         let loc3 = writer.append(&wallet3).await?;
@@ -355,7 +1193,7 @@ mod tests {
 
         // Then
         let from_disk: Wallet =
-            read_from_file(get_vault_path().join("accounts").join("0.0")).await?;
+            read_from_file(get_vault_path()?.join("accounts").join("0.0")).await?;
         assert_eq!(from_disk, account);
 
         Ok(())
@@ -367,9 +1205,7 @@ mod tests {
         const VAULT: &str = "/tmp/bifrost/vault-4";
         reset_vault(VAULT)?;
         let mut vault = Vault::load_or_create().await?;
-        let account = Wallet {
-            prisms: 938_983_237,
-        };
+        let account = Wallet::new(938_983_237);
         let data_len = borsh::to_vec(&account)?.len() as u64;
         #[expect(clippy::integer_division)]
         let account_per_file = MAX_ACCOUNT_FILE_SIZE / data_len;
@@ -388,7 +1224,7 @@ mod tests {
         sleep(Duration::from_millis(5)).await;
 
         // Then
-        let path = get_vault_path().join("accounts").join("0.1");
+        let path = get_vault_path()?.join("accounts").join("0.1");
         assert!(path.exists());
         assert_eq!(path.metadata()?.len(), data_len);
 
@@ -452,12 +1288,10 @@ mod tests {
         for slot in 0..4 {
             for i in 0..100 {
                 if i % 2 == 0 {
-                    vault
-                        .save_account(key, &Wallet { prisms: 983_373 }, slot)
-                        .await?;
+                    vault.save_account(key, &Wallet::new(983_373), slot).await?;
                 } else {
                     vault
-                        .save_account(Keypair::generate().pubkey(), &Wallet { prisms: 99 }, slot)
+                        .save_account(Keypair::generate().pubkey(), &Wallet::new(99), slot)
                         .await?;
                 }
             }
@@ -468,7 +1302,7 @@ mod tests {
         sleep(Duration::from_millis(2)).await;
 
         // Then
-        assert_eq!(read_dir(get_vault_path().join("accounts"))?.count(), 8);
+        assert_eq!(read_dir(get_vault_path()?.join("accounts"))?.count(), 8);
 
         Ok(())
     }
@@ -485,12 +1319,10 @@ mod tests {
         for slot in 0..4 {
             for i in 0..100 {
                 if i % 2 == 0 {
-                    vault
-                        .save_account(key, &Wallet { prisms: 983_373 }, slot)
-                        .await?;
+                    vault.save_account(key, &Wallet::new(983_373), slot).await?;
                 } else {
                     vault
-                        .save_account(Keypair::generate().pubkey(), &Wallet { prisms: 99 }, slot)
+                        .save_account(Keypair::generate().pubkey(), &Wallet::new(99), slot)
                         .await?;
                 }
             }
@@ -502,7 +1334,7 @@ mod tests {
         sleep(Duration::from_millis(2)).await;
 
         // Then
-        assert_eq!(read_dir(get_vault_path().join("accounts"))?.count(), 8);
+        assert_eq!(read_dir(get_vault_path()?.join("accounts"))?.count(), 8);
 
         Ok(())
     }
@@ -519,12 +1351,10 @@ mod tests {
         for slot in 0..4 {
             for i in 0..100 {
                 if i % 2 == 0 {
-                    vault
-                        .save_account(key, &Wallet { prisms: 983_373 }, slot)
-                        .await?;
+                    vault.save_account(key, &Wallet::new(983_373), slot).await?;
                 } else {
                     vault
-                        .save_account(Keypair::generate().pubkey(), &Wallet { prisms: 99 }, slot)
+                        .save_account(Keypair::generate().pubkey(), &Wallet::new(99), slot)
                         .await?;
                 }
             }
@@ -536,7 +1366,525 @@ mod tests {
         sleep(Duration::from_millis(2)).await;
 
         // Then
-        assert_eq!(read_dir(get_vault_path().join("accounts"))?.count(), 10);
+        assert_eq!(read_dir(get_vault_path()?.join("accounts"))?.count(), 10);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn uninitialized_vault_path_is_an_error() {
+        // Given
+        // No vault path was set
+
+        // When
+        let res = Vault::load_or_create().await;
+
+        // Then
+        assert!(matches!(res, Err(Error::VaultNotInitialized)));
+    }
+
+    #[test]
+    fn vault_path_cannot_be_changed() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/vault-10";
+        set_vault_path(VAULT)?;
+
+        // When
+        let same = set_vault_path(VAULT);
+        let other = set_vault_path("/tmp/bifrost/vault-11");
+
+        // Then
+        assert_matches!(same, Ok(()));
+        assert_matches!(other, Err(Error::VaultPathAlreadySet { .. }));
+        assert_eq!(get_vault_path()?, &PathBuf::from(VAULT));
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn state_root_survives_reload() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/vault-12";
+        reset_vault(VAULT)?;
+        let mut vault = Vault::load_or_create().await?;
+        let key1 = Keypair::generate().pubkey();
+        let key2 = Keypair::generate().pubkey();
+        let mut wallet1 = Wallet::new(1_000);
+        let wallet2 = Wallet::new(2_000);
+
+        // When
+        vault.save_account(key1, &wallet1, 0).await?;
+        vault.save_account(key2, &wallet2, 0).await?;
+        wallet1.prisms = 500;
+        vault.save_account(key1, &wallet1, 0).await?;
+        vault.save().await?;
+        let root = vault.state_root();
+        drop(vault);
+        sleep(Duration::from_millis(5)).await;
+        let reloaded = Vault::load_or_create().await?;
+
+        // Then
+        let expected = AccountsHash::from_accounts([(&key1, &wallet1), (&key2, &wallet2)]);
+        assert_eq!(root, expected.root());
+        assert_eq!(reloaded.state_root(), root);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn removed_account_is_empty() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/vault-13";
+        reset_vault(VAULT)?;
+        let mut vault = Vault::load_or_create().await?;
+        let key = Keypair::generate().pubkey();
+        let mut wallet = Wallet::new(1_000);
+        wallet.data = vec![1, 2, 3].into();
+        vault.save_account(key, &wallet, 0).await?;
+        vault.save().await?;
+
+        // When
+        vault.remove_account(&key).await?;
+        vault.save().await?;
+        drop(vault);
+        sleep(Duration::from_millis(5)).await;
+        let reloaded = Vault::load_or_create().await?;
+
+        // Then
+        assert_eq!(reloaded.get(&key).await?, Wallet::default());
+        assert_eq!(reloaded.state_root(), AccountsHash::default().root());
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn balance_history_is_persisted() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/vault-14";
+        reset_vault(VAULT)?;
+        let mut vault = Vault::load_or_create().await?;
+        let key = Keypair::generate().pubkey();
+        let signature = Keypair::generate().sign(b"transaction");
+        vault.record_balance(key, 1, signature, 0, 1_000);
+        vault.enable_balance_history().await;
+
+        // When
+        vault.record_balance(key, 2, signature, 0, 1_000);
+        vault.record_balance(key, 4, signature, 1_000, 400);
+        vault.save().await?;
+        drop(vault);
+        sleep(Duration::from_millis(5)).await;
+        let mut reloaded = Vault::load_or_create().await?;
+        let disabled = reloaded.get_balance_at_slot(&key, 4);
+        reloaded.enable_balance_history().await;
+
+        // Then
+        assert_eq!(disabled, None);
+        assert_eq!(reloaded.get_balance_at_slot(&key, 1), None);
+        assert_eq!(reloaded.get_balance_at_slot(&key, 3), Some(1_000));
+        assert_eq!(reloaded.get_balance_at_slot(&key, 4), Some(400));
+        let history = reloaded.get_balance_history(&key, 0, 10, 0, 10);
+        assert_eq!(
+            history
+                .iter()
+                .map(|change| change.delta)
+                .collect::<Vec<_>>(),
+            vec![1_000, -600]
+        );
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn cached_accounts_are_not_read_from_disk() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/vault-15";
+        let keys = setup_vault(VAULT).await?;
+        let vault = Vault::load_or_create().await?;
+        vault.preload(&keys[..2]).await?;
+        std::fs::remove_file(get_account_path(82, 0)?)?;
+
+        // When
+        let mut accounts = Vec::new();
+        for _ in 0..10_u8 {
+            accounts.push(vault.get(&keys[0]).await?.prisms);
+            accounts.push(vault.get(&keys[1]).await?.prisms);
+        }
+        let uncached = vault.get(&keys[2]).await;
+
+        // Then
+        assert!(accounts.chunks(2).all(|pair| pair == [AMOUNT1, AMOUNT2]));
+        assert_matches!(uncached, Err(_));
+        let stats = vault.cache_stats();
+        assert_eq!(
+            stats,
+            CacheStats {
+                hits: 20,
+                misses: 1
+            }
+        );
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn multiple_accounts_are_fetched_in_order_despite_failures() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/vault-23";
+        reset_vault(VAULT)?;
+        Vault::init_vault().await?;
+        let keys = (0..4_u8)
+            .map(|_| Keypair::generate().pubkey())
+            .collect::<Vec<_>>();
+        let mut index = Index::load_or_create().await;
+        let mut locations = Vec::new();
+        for (slot, amounts) in [
+            (82, &[AMOUNT1, AMOUNT2][..]),
+            (83, &[AMOUNT3]),
+            (84, &[AMOUNT2]),
+        ] {
+            let mut writer = SlotWriter::new(slot)?;
+            for &amount in amounts {
+                locations.push(writer.append(&Wallet::new(amount)).await?);
+            }
+            writer.flush().await?;
+        }
+        for (key, loc) in keys.iter().zip(&locations) {
+            index.set_account(*key, *loc);
+        }
+        index.save().await?;
+        let vault = Vault::load_or_create().await?;
+        // the second account of slot 82 is cut, the file of slot 83 is lost
+        File::options()
+            .write(true)
+            .open(get_account_path(82, 0)?)?
+            .set_len(locations[1].offset + 1)?;
+        std::fs::remove_file(get_account_path(83, 0)?)?;
+        let unknown = Keypair::generate().pubkey();
+
+        // When
+        let fetched = vault
+            .get_multiple_accounts(&[keys[3], unknown, keys[1], keys[0], keys[2], keys[3]])
+            .await?;
+        let too_many = vault
+            .get_multiple_accounts(&vec![unknown; MAX_MULTIPLE_ACCOUNTS + 1])
+            .await;
+
+        // Then
+        assert_eq!(fetched.len(), 6);
+        assert_matches!(&fetched[0], Ok(Some(account)) if account.prisms == AMOUNT2);
+        assert_matches!(&fetched[1], Ok(None));
+        assert_matches!(&fetched[2], Err(AccountFetchError::Unreadable(_)));
+        assert_matches!(&fetched[3], Ok(Some(account)) if account.prisms == AMOUNT1);
+        assert_matches!(&fetched[4], Err(AccountFetchError::MissingFile));
+        assert_matches!(&fetched[5], Ok(Some(account)) if account.prisms == AMOUNT2);
+        assert_matches!(
+            too_many,
+            Err(Error::TooManyAccounts {
+                requested,
+                max: MAX_MULTIPLE_ACCOUNTS
+            }) if requested == MAX_MULTIPLE_ACCOUNTS + 1
+        );
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn removed_account_is_evicted_from_cache() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/vault-16";
+        let keys = setup_vault(VAULT).await?;
+        let mut vault = Vault::load_or_create().await?;
+        vault.preload(&keys).await?;
+
+        // When
+        vault.remove_account(&keys[0]).await?;
+
+        // Then
+        assert_eq!(vault.get(&keys[0]).await?, Wallet::default());
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn sequences_survive_reload() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/vault-17";
+        reset_vault(VAULT)?;
+        let payer = Keypair::generate().pubkey();
+        let mut vault = Vault::load_or_create().await?;
+        vault.set_sequence(payer, 3);
+        vault.burn(42);
+        let old = Keypair::generate().pubkey();
+        let new = Keypair::generate().pubkey();
+        vault.rotate_identity(old, 0);
+        let boundary = vault.rotate_identity(new, 7);
+
+        // When
+        vault.save().await?;
+        drop(vault);
+        sleep(Duration::from_millis(5)).await;
+        let reloaded = Vault::load_or_create().await?;
+
+        // Then
+        assert_eq!(reloaded.last_sequence(&payer), Some(3));
+        assert_eq!(reloaded.last_sequence(&Keypair::generate().pubkey()), None);
+        assert_eq!(reloaded.burned(), 42);
+        assert_eq!(
+            reloaded
+                .identities()
+                .identity_at(boundary.saturating_sub(1)),
+            Some(&old)
+        );
+        assert_eq!(reloaded.identities().identity_at(boundary), Some(&new));
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn undecodable_state_is_not_forgotten() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/vault-26";
+        reset_vault(VAULT)?;
+        let mut vault = Vault::load_or_create().await?;
+        vault.set_sequence(Keypair::generate().pubkey(), 3);
+        vault.save().await?;
+        drop(vault);
+        sleep(Duration::from_millis(5)).await;
+        std::fs::write(Path::new(VAULT).join("sequences"), [1, 2, 3])?;
+
+        // When
+        let res = Vault::load_or_create().await;
+
+        // Then
+        assert_matches!(res.err(), Some(Error::Deserialization { .. }));
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn block_times_are_saved() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/vault-27";
+        const TIME: i64 = 1_700_000_000;
+        reset_vault(VAULT)?;
+        let mut vault = Vault::load_or_create().await?;
+        vault.record_block_time(1, &[TIME], 10);
+        vault.record_block_time(2, &[TIME + 100], 10);
+
+        // When
+        vault.save().await?;
+        drop(vault);
+        sleep(Duration::from_millis(5)).await;
+        let vault = Vault::load_or_create().await?;
+
+        // Then
+        assert_eq!(vault.get_block_time(1), Some(TIME));
+        assert_eq!(vault.get_block_time(2), Some(TIME + 10));
+        assert_eq!(vault.get_block_time(3), None);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn vault_cannot_be_opened_twice() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/vault-18";
+        reset_vault(VAULT)?;
+        let vault = Vault::load_or_create().await?;
+
+        // When
+        let concurrent = tokio::spawn(Vault::load_or_create()).await?;
+        drop(vault);
+        let reopened = Vault::load_or_create().await.map(|_reopened| ());
+
+        // Then
+        assert_matches!(concurrent.err(), Some(Error::VaultLocked { path }) if path == Path::new(VAULT));
+        assert_matches!(reopened, Ok(()));
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn vault_in_non_ascii_folder() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/vault-19-coffre-fort-金庫";
+        reset_vault(VAULT)?;
+        let key = Keypair::generate().pubkey();
+        let mut vault = Vault::load_or_create().await?;
+        vault.save_account(key, &Wallet::new(42), 0).await?;
+
+        // When
+        vault.save().await?;
+        drop(vault);
+        let reloaded = Vault::load_or_create().await?;
+
+        // Then
+        assert_eq!(reloaded.get(&key).await?.prisms, 42);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn balance_depends_on_commitment() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/vault-20";
+        reset_vault(VAULT)?;
+        let key = Keypair::generate().pubkey();
+        let signature = Keypair::generate().sign(b"transfer");
+        let mut vault = Vault::load_or_create().await?;
+        vault.enable_balance_history().await;
+        vault.save_account(key, &Wallet::new(100), 1).await?;
+        vault.record_balance(key, 1, signature, 0, 100);
+        vault.finalize(1);
+
+        // When
+        vault.save_account(key, &Wallet::new(40), 2).await?;
+        vault.record_balance(key, 2, signature, 100, 40);
+        let before_votes = [
+            vault.get_balance(&key, Commitment::Processed).await?,
+            vault.get_balance(&key, Commitment::default()).await?,
+            vault.get_balance(&key, Commitment::Finalized).await?,
+        ];
+        vault.confirm(2);
+        let confirmed = [
+            vault.get_balance(&key, Commitment::Processed).await?,
+            vault.get_balance(&key, Commitment::Confirmed).await?,
+            vault.get_balance(&key, Commitment::Finalized).await?,
+        ];
+
+        // Then
+        assert_eq!(before_votes, [40, 100, 100]);
+        assert_eq!(confirmed, [40, 40, 100]);
+        assert_eq!(
+            vault.commitment(),
+            CommitmentSlots {
+                confirmed: 2,
+                finalized: 1
+            }
+        );
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn past_balances_need_history() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/vault-21";
+        reset_vault(VAULT)?;
+        let key = Keypair::generate().pubkey();
+        let mut vault = Vault::load_or_create().await?;
+        vault.save_account(key, &Wallet::new(100), 1).await?;
+
+        // When
+        let processed = vault.get_balance(&key, Commitment::Processed).await?;
+        let confirmed = vault.get_balance(&key, Commitment::Confirmed).await;
+
+        // Then
+        assert_eq!(processed, 100);
+        assert_matches!(confirmed, Err(Error::BalanceHistoryDisabled));
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn accounts_are_resolved_from_their_prefix() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/vault-24";
+        reset_vault(VAULT)?;
+        let mut vault = Vault::load_or_create().await?;
+        // the keys of `longer` and `other` extend the key of `short`
+        let short = Pubkey::from_bytes(&[1; 32]);
+        let longer: Pubkey = format!("{short}2").parse()?;
+        let other: Pubkey = format!("{short}3").parse()?;
+        let unique = Keypair::generate().pubkey();
+        for key in [short, longer, other, unique] {
+            vault.save_account(key, &Wallet::new(AMOUNT1), 1).await?;
+        }
+        let shared = short.to_string().chars().take(5).collect::<String>();
+
+        // When
+        let ambiguous = vault.resolve_prefix(&shared);
+        let full = vault.resolve_prefix(&short.to_string());
+        let extended = vault.resolve_prefix(&format!("{short}2"));
+        let found = vault.resolve_prefix(&unique.to_string().chars().take(12).collect::<String>());
+        let unknown = vault.resolve_prefix("zzzzzzzzzzzz");
+
+        // Then
+        assert_matches!(
+            ambiguous,
+            Err(Error::AmbiguousAccountPrefix { matches: 3, candidates, .. })
+                if candidates.contains(&short) && candidates.contains(&longer) && candidates.contains(&other)
+        );
+        assert_eq!(full?, short);
+        assert_eq!(extended?, longer);
+        assert_eq!(found?, unique);
+        assert_matches!(unknown, Err(Error::UnknownAccountPrefix { prefix }) if prefix == "zzzzzzzzzzzz");
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn accounts_are_filtered_on_their_data() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/vault-25";
+        reset_vault(VAULT)?;
+        let mut vault = Vault::load_or_create().await?;
+        // token holdings: the mint followed by the amount held
+        let mints = (0..3_u8)
+            .map(|_| Keypair::generate().pubkey())
+            .collect::<Vec<_>>();
+        let mut holdings = Vec::new();
+        for (slot, mint) in (1..).zip(&mints) {
+            for amount in 0..3_u64 {
+                let key = Keypair::generate().pubkey();
+                let data = [mint.as_ref(), &amount.to_le_bytes()[..]].concat();
+                let wallet = Wallet {
+                    prisms: AMOUNT2,
+                    data: data.into(),
+                };
+                vault.save_account(key, &wallet, slot).await?;
+                holdings.push((key, wallet));
+            }
+        }
+        // the mint at the start of a longer account, and a wallet without data
+        let longer = Wallet {
+            prisms: AMOUNT2,
+            data: [mints[1].as_ref(), &[0; 16][..]].concat().into(),
+        };
+        vault
+            .save_account(Keypair::generate().pubkey(), &longer, 4)
+            .await?;
+        vault
+            .save_account(Keypair::generate().pubkey(), &Wallet::new(AMOUNT1), 4)
+            .await?;
+        let mint_filter = AccountFilter::Memcmp {
+            offset: 0,
+            bytes: mints[1].as_ref().to_vec(),
+        };
+
+        // When
+        let filtered = vault
+            .get_filtered_accounts(&[AccountFilter::DataSize(40), mint_filter.clone()])
+            .await?;
+        let mint_only = vault.get_filtered_accounts(&[mint_filter]).await?;
+        let sized = vault
+            .get_filtered_accounts(&[AccountFilter::DataSize(40)])
+            .await?;
+        let invalid = vault
+            .get_filtered_accounts(&[AccountFilter::Memcmp {
+                offset: 0,
+                bytes: Vec::new(),
+            }])
+            .await;
+
+        // Then
+        let mut expected = holdings[3..6].to_vec();
+        expected.sort_unstable_by_key(|(key, _wallet)| *key);
+        assert_eq!(filtered, expected);
+        assert_eq!(mint_only.len(), 4);
+        assert_eq!(sized.len(), holdings.len());
+        assert_matches!(invalid, Err(Error::InvalidAccountFilter { .. }));
 
         Ok(())
     }
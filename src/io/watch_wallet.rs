@@ -0,0 +1,371 @@
+// File: src/io/watch_wallet.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 15:21:25
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fmt::Debug,
+    path::PathBuf,
+};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use tracing::{debug, instrument, trace, warn};
+
+use crate::crypto::{Pubkey, Signature};
+
+use super::{
+    balance_journal::BalanceChange,
+    support::{read_from_file, write_to_file},
+    Result, Vault,
+};
+
+/// Maximum number of balance changes kept for each watched account.
+pub const MAX_WATCHED_CHANGES: usize = 64;
+
+/// The number of balance changes read from the vault at once.
+const PAGE_SIZE: usize = 128;
+
+/// What happened to a watched account since the last synchronization.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WatchEvent {
+    /// The account received prisms.
+    Credited {
+        /// The watched account.
+        key: Pubkey,
+        /// The number of prisms received.
+        amount: u64,
+        /// The slot of the transaction.
+        slot: u64,
+        /// The signature of the transaction.
+        signature: Signature,
+    },
+    /// Prisms were taken from the account.
+    Debited {
+        /// The watched account.
+        key: Pubkey,
+        /// The number of prisms taken.
+        amount: u64,
+        /// The slot of the transaction.
+        slot: u64,
+        /// The signature of the transaction.
+        signature: Signature,
+    },
+    /// The balance changed without any recorded change (e.g. the history was disabled
+    /// or pruned meanwhile), it was corrected.
+    Reconciled {
+        /// The watched account.
+        key: Pubkey,
+        /// The balance held before.
+        before: u64,
+        /// The actual balance.
+        balance: u64,
+    },
+}
+
+/// What's known of a watched account.
+#[derive(Clone, Debug, Default, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+struct Watched {
+    balance: u64,
+    /// The last balance changes, oldest first.
+    recent: VecDeque<BalanceChange>,
+}
+
+impl Watched {
+    /// Whether a change was already seen.
+    fn has_seen(&self, change: &BalanceChange) -> bool {
+        self.recent.back().is_some_and(|last| {
+            change.slot < last.slot
+                || self
+                    .recent
+                    .iter()
+                    .any(|seen| seen.slot == change.slot && seen.signature == change.signature)
+        })
+    }
+}
+
+/// Follows the balances of accounts whose keys aren't held, such as a custodian's deposits.
+///
+/// The balances are kept up to date from the balance history of the vault, which must be
+/// enabled, and checked against the actual balances so missed changes are caught.
+#[derive(Clone, Debug, Default, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct WatchWallet {
+    accounts: BTreeMap<Pubkey, Watched>,
+}
+
+impl WatchWallet {
+    /// Starts watching accounts.
+    ///
+    /// # Parameters
+    /// * `keys` - The public keys of the accounts.
+    #[must_use]
+    pub fn new(keys: &[Pubkey]) -> Self {
+        let mut wallet = Self::default();
+        for key in keys {
+            wallet.watch(*key);
+        }
+
+        wallet
+    }
+
+    /// Starts watching an account, its balance is known after the next synchronization.
+    pub fn watch(&mut self, key: Pubkey) {
+        self.accounts.entry(key).or_default();
+    }
+
+    /// Stops watching an account.
+    pub fn unwatch(&mut self, key: &Pubkey) {
+        self.accounts.remove(key);
+    }
+
+    /// Get the keys of the watched accounts.
+    pub fn keys(&self) -> impl Iterator<Item = &Pubkey> {
+        self.accounts.keys()
+    }
+
+    /// Get the balance of a watched account, as of the last synchronization.
+    #[must_use]
+    pub fn balance(&self, key: &Pubkey) -> Option<u64> {
+        self.accounts.get(key).map(|watched| watched.balance)
+    }
+
+    /// Get the last balance changes of a watched account, oldest first.
+    #[must_use]
+    pub fn history(&self, key: &Pubkey) -> Vec<BalanceChange> {
+        self.accounts
+            .get(key)
+            .map(|watched| watched.recent.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Catches up with the changes of the watched accounts.
+    ///
+    /// # Parameters
+    /// * `vault` - The vault holding the accounts.
+    ///
+    /// # Returns
+    /// What happened to the accounts, in the order it happened for each of them.
+    ///
+    /// # Errors
+    /// If an account couldn't be read from the disk.
+    #[instrument(skip_all, fields(n = self.accounts.len()))]
+    pub async fn sync(&mut self, vault: &Vault) -> Result<Vec<WatchEvent>> {
+        debug!("synchronizing the watched accounts");
+        let mut events = Vec::new();
+        for (key, watched) in &mut self.accounts {
+            let from = watched.recent.back().map_or(0, |last| last.slot);
+            for change in Self::changes_since(vault, key, from) {
+                if watched.has_seen(&change) {
+                    continue;
+                }
+                trace!(
+                    slot = change.slot,
+                    delta = change.delta,
+                    "new balance change"
+                );
+                events.push(Self::event(*key, &change));
+                watched.balance = change.balance;
+                if watched.recent.len() == MAX_WATCHED_CHANGES {
+                    watched.recent.pop_front();
+                }
+                watched.recent.push_back(change);
+            }
+
+            let balance = vault.get(key).await?.prisms;
+            if balance != watched.balance {
+                warn!(%key, before = watched.balance, balance, "missed a balance change");
+                events.push(WatchEvent::Reconciled {
+                    key: *key,
+                    before: watched.balance,
+                    balance,
+                });
+                watched.balance = balance;
+            }
+        }
+
+        Ok(events)
+    }
+
+    /// Get all the balance changes of an account from a slot.
+    fn changes_since(vault: &Vault, key: &Pubkey, from: u64) -> Vec<BalanceChange> {
+        let mut changes = Vec::new();
+        for page in 0.. {
+            let batch = vault.get_balance_history(key, from, u64::MAX, page, PAGE_SIZE);
+            let last = batch.len() < PAGE_SIZE;
+            changes.extend(batch);
+            if last {
+                break;
+            }
+        }
+
+        changes
+    }
+
+    fn event(key: Pubkey, change: &BalanceChange) -> WatchEvent {
+        let amount = u64::try_from(change.delta.unsigned_abs()).unwrap_or(u64::MAX);
+        if change.delta >= 0 {
+            WatchEvent::Credited {
+                key,
+                amount,
+                slot: change.slot,
+                signature: change.signature,
+            }
+        } else {
+            WatchEvent::Debited {
+                key,
+                amount,
+                slot: change.slot,
+                signature: change.signature,
+            }
+        }
+    }
+
+    /// Loads a watch wallet saved previously.
+    ///
+    /// # Errors
+    /// If the file can't be read, or doesn't hold a watch wallet.
+    #[instrument]
+    pub async fn load<P>(path: P) -> Result<Self>
+    where
+        P: Into<PathBuf> + Debug,
+    {
+        debug!("loading the watch wallet");
+        read_from_file(path).await
+    }
+
+    /// Saves the watch wallet to a file.
+    ///
+    /// # Errors
+    /// If the file can't be written.
+    #[instrument(skip(self))]
+    pub async fn save<P>(&self, path: P) -> Result<()>
+    where
+        P: Into<PathBuf> + Debug,
+    {
+        debug!("saving the watch wallet");
+        write_to_file(path, self).await
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use std::fs::remove_dir_all;
+
+    use test_log::test;
+
+    use crate::account::Wallet;
+    use crate::crypto::Keypair;
+    use crate::io::set_vault_path;
+
+    use super::*;
+    type TestResult = core::result::Result<(), Box<dyn core::error::Error>>;
+
+    /// Moves prisms between two accounts, recording the changes like the processor does.
+    async fn transfer(
+        vault: &mut Vault,
+        from: Pubkey,
+        to: Pubkey,
+        amount: u64,
+        slot: u64,
+    ) -> TestResult {
+        let signature = Keypair::generate().sign(b"transfer");
+        for (key, after) in [
+            (from, vault.get(&from).await?.prisms - amount),
+            (to, vault.get(&to).await?.prisms + amount),
+        ] {
+            let before = vault.get(&key).await?.prisms;
+            vault.save_account(key, &Wallet::new(after), slot).await?;
+            vault.record_balance(key, slot, signature, before, after);
+        }
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn watched_balances_follow_the_vault() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/vault-22";
+        let path = PathBuf::from(VAULT);
+        set_vault_path(&path)?;
+        if path.exists() {
+            remove_dir_all(&path)?;
+        }
+        let mut vault = Vault::load_or_create().await?;
+        vault.enable_balance_history().await;
+        let treasury = Keypair::generate().pubkey();
+        let deposit = Keypair::generate().pubkey();
+        vault.save_account(treasury, &Wallet::new(1_000), 1).await?;
+        let mut wallet = WatchWallet::new(&[deposit]);
+
+        // When
+        transfer(&mut vault, treasury, deposit, 300, 2).await?;
+        transfer(&mut vault, deposit, treasury, 100, 3).await?;
+        let first = wallet.sync(&vault).await?;
+        let again = wallet.sync(&vault).await?;
+        transfer(&mut vault, treasury, deposit, 50, 3).await?;
+        // a change the history missed
+        vault.save_account(deposit, &Wallet::new(1), 4).await?;
+        let second = wallet.sync(&vault).await?;
+        wallet.save(path.join("watch")).await?;
+        let reloaded = WatchWallet::load(path.join("watch")).await?;
+
+        // Then
+        let amounts = first
+            .iter()
+            .map(|event| match *event {
+                WatchEvent::Credited { amount, slot, .. } => (i128::from(amount), slot),
+                WatchEvent::Debited { amount, slot, .. } => (-i128::from(amount), slot),
+                WatchEvent::Reconciled { .. } => (0, 0),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(amounts, vec![(300, 2), (-100, 3)]);
+        assert!(again.is_empty());
+        assert_eq!(second.len(), 2);
+        assert!(matches!(
+            second[0],
+            WatchEvent::Credited {
+                amount: 50,
+                slot: 3,
+                ..
+            }
+        ));
+        assert_eq!(
+            second[1],
+            WatchEvent::Reconciled {
+                key: deposit,
+                before: 250,
+                balance: 1
+            }
+        );
+        assert_eq!(wallet.balance(&deposit), Some(1));
+        assert_eq!(wallet.history(&deposit).len(), 3);
+        assert_eq!(reloaded, wallet);
+        assert_eq!(wallet.balance(&treasury), None);
+
+        Ok(())
+    }
+}
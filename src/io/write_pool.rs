@@ -0,0 +1,462 @@
+// File: src/io/write_pool.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 20:48:52
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Writes the files of a commit in parallel.
+//!
+//! The files are spread over a few workers by path, so the writes to the same file
+//! are always made by the same worker, in the order they were submitted. The workers
+//! write next to the final files, which are only moved in place once every worker
+//! acknowledged its writes: if a single write fails, no file of the commit is replaced.
+//!
+//! Before moving them, the commit records the files in a manifest, which is removed
+//! once they're all in place. If they can't all be moved (a crash, a full disk…), the
+//! manifest is left behind and the next commit, or the recovery of the pool when the
+//! vault is opened, moves the remaining ones: a commit is either lost or complete.
+
+use std::{
+    collections::{BTreeSet, HashSet},
+    fmt::Debug,
+    hash::{DefaultHasher, Hash as _, Hasher as _},
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use borsh::BorshSerialize;
+use tokio::{
+    fs::{read_dir, remove_file, rename, File},
+    io::AsyncWriteExt as _,
+    sync::{
+        mpsc::{channel, Receiver as TReceiver, Sender as TSender},
+        oneshot::{channel as oneshot, Sender as OSender},
+    },
+};
+use tracing::{debug, instrument, trace, warn};
+
+use super::{support::read_from_file, Error, Result};
+
+/// The number of workers writing the files of the vault.
+pub const DEFAULT_WRITE_WORKERS: usize = 4;
+
+/// The number of writes waiting for each worker before the commit waits for them.
+const WRITE_QUEUE_DEPTH: usize = 64;
+
+/// A file to write as part of a commit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileWrite {
+    /// Where the file is written.
+    path: PathBuf,
+    /// The content of the file.
+    data: Vec<u8>,
+}
+
+impl FileWrite {
+    /// Serializes a value to write.
+    ///
+    /// # Parameters
+    /// * `path` - Where the value is written,
+    /// * `value` - The value to write.
+    #[expect(clippy::unwrap_used)]
+    pub fn new<P, B>(path: P, value: &B) -> Self
+    where
+        P: Into<PathBuf>,
+        B: BorshSerialize,
+    {
+        Self {
+            path: path.into(),
+            data: borsh::to_vec(value).unwrap(),
+        }
+    }
+
+    /// Where the file is written.
+    #[expect(clippy::missing_const_for_fn, reason = "false positive")]
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// A write handed to a worker.
+struct Job {
+    /// Where the worker writes the data, until the commit completes.
+    pending: PathBuf,
+    /// The content of the file.
+    data: Vec<u8>,
+    /// Told once the data was written.
+    ack: OSender<Result<()>>,
+}
+
+/// Workers writing the files of a commit concurrently.
+#[derive(Debug)]
+pub struct WritePool {
+    /// The queues of the workers.
+    workers: Vec<TSender<Job>>,
+    /// The files of the commit being moved in place.
+    manifest: PathBuf,
+}
+
+impl WritePool {
+    /// Starts the workers, which stop once the pool is dropped.
+    ///
+    /// # Parameters
+    /// * `workers` - The number of workers (at least one is started),
+    /// * `manifest` - Where the files of a commit are recorded while they're moved in place.
+    #[must_use]
+    pub fn new<P>(workers: usize, manifest: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        debug!(workers, "starting the write workers");
+        let workers = (0..workers.max(1))
+            .map(|_| {
+                let (tx, rx) = channel(WRITE_QUEUE_DEPTH);
+                tokio::spawn(work(rx));
+                tx
+            })
+            .collect();
+
+        Self {
+            workers,
+            manifest: manifest.into(),
+        }
+    }
+
+    /// The number of workers.
+    #[must_use]
+    pub fn workers(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Writes files, replacing them all or none of them.
+    ///
+    /// When a file is written more than once, its last content is kept.
+    ///
+    /// # Parameters
+    /// * `writes` - The files to write.
+    ///
+    /// # Errors
+    /// If a file couldn't be written (none of them are replaced), or if the files
+    /// couldn't all be moved in place once they were written: the remaining ones
+    /// are moved by the next commit, or when the pool is recovered.
+    #[instrument(skip_all, fields(n = writes.len()))]
+    pub async fn commit(&self, writes: Vec<FileWrite>) -> Result<()> {
+        debug!("committing files");
+        self.complete().await?;
+        let mut acks = Vec::with_capacity(writes.len());
+        let mut targets = Vec::new();
+        let mut known = HashSet::new();
+        for FileWrite { path, data } in writes {
+            let pending = pending_path(&path);
+            let (ack, rx) = oneshot();
+            let job = Job {
+                pending: pending.clone(),
+                data,
+                ack,
+            };
+            match self.workers[self.shard(&path)].send(job).await {
+                Ok(()) => acks.push(Some(rx)),
+                Err(_err) => acks.push(None),
+            }
+            if known.insert(path.clone()) {
+                targets.push((pending, path));
+            }
+        }
+
+        let mut failure = None;
+        for ack in acks {
+            let res = match ack {
+                Some(rx) => rx.await.unwrap_or(Err(Error::WriteWorkerStopped)),
+                None => Err(Error::WriteWorkerStopped),
+            };
+            if let Err(err) = res {
+                failure.get_or_insert(err);
+            }
+        }
+        if let Some(err) = failure {
+            warn!("a write failed, aborting the commit: {err}");
+            for (pending, _path) in &targets {
+                if remove_file(pending).await.is_err() {
+                    trace!(?pending, "nothing was written");
+                }
+            }
+            return Err(err);
+        }
+
+        trace!("every write was acknowledged, recording the commit");
+        let files = targets
+            .into_iter()
+            .map(|(_pending, path)| {
+                path.into_os_string().into_string().map_err(|path| {
+                    Error::FileSystem(std::io::Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("invalid file name {path:?}"),
+                    ))
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let pending = pending_path(&self.manifest);
+        write(&pending, &borsh::to_vec(&files)?).await?;
+        rename(&pending, &self.manifest).await?;
+        sync_folder(folder_of(&self.manifest)).await?;
+
+        self.move_in_place(files).await
+    }
+
+    /// Completes the commit interrupted while its files were moved in place,
+    /// and removes the files written by the commits that were aborted.
+    ///
+    /// # Errors
+    /// If the manifest of the interrupted commit couldn't be read, or if its files
+    /// couldn't be moved in place.
+    #[instrument(skip(self))]
+    pub async fn recover(&self) -> Result<()> {
+        debug!("recovering the interrupted commits");
+        self.complete().await?;
+        let mut entries = read_dir(folder_of(&self.manifest)).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "pending") {
+                warn!(?path, "removing the file of an aborted commit");
+                remove_file(path).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Moves in place the files of the commit recorded in the manifest, if any.
+    async fn complete(&self) -> Result<()> {
+        if !self.manifest.exists() {
+            return Ok(());
+        }
+        warn!("a commit was interrupted, moving its remaining files in place");
+        let files = read_from_file(&self.manifest).await?;
+
+        self.move_in_place(files).await
+    }
+
+    /// Moves in place the files of the commit recorded in the manifest,
+    /// then removes the manifest.
+    async fn move_in_place(&self, files: Vec<String>) -> Result<()> {
+        let mut folders = BTreeSet::new();
+        for path in files.into_iter().map(PathBuf::from) {
+            let pending = pending_path(&path);
+            if pending.exists() {
+                rename(&pending, &path).await?;
+            }
+            folders.insert(folder_of(&path).to_path_buf());
+        }
+        for folder in &folders {
+            sync_folder(folder).await?;
+        }
+        remove_file(&self.manifest).await?;
+
+        sync_folder(folder_of(&self.manifest)).await
+    }
+
+    /// The worker writing a file.
+    #[expect(
+        clippy::cast_possible_truncation,
+        reason = "below the number of workers"
+    )]
+    fn shard(&self, path: &Path) -> usize {
+        let mut hasher = DefaultHasher::new();
+        path.hash(&mut hasher);
+
+        (hasher.finish() % self.workers.len() as u64) as usize
+    }
+}
+
+/// Where a file is written until its commit completes.
+fn pending_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".pending");
+
+    path.with_file_name(name)
+}
+
+/// The folder holding a file.
+fn folder_of(path: &Path) -> &Path {
+    match path.parent() {
+        Some(folder) if !folder.as_os_str().is_empty() => folder,
+        _ => Path::new("."),
+    }
+}
+
+/// Waits for the entries of a folder (the files moved in it) to reach the disk.
+async fn sync_folder(folder: &Path) -> Result<()> {
+    File::open(folder).await?.sync_all().await?;
+
+    Ok(())
+}
+
+/// Writes the files of a worker's queue, until the pool is dropped.
+async fn work(mut jobs: TReceiver<Job>) {
+    while let Some(Job { pending, data, ack }) = jobs.recv().await {
+        let res = write(&pending, &data).await;
+        if ack.send(res).is_err() {
+            trace!(?pending, "the commit was dropped");
+        }
+    }
+}
+
+/// Writes a file and waits for it to reach the disk.
+async fn write(path: &Path, data: &[u8]) -> Result<()> {
+    trace!(?path, "writing file");
+    let mut file = File::create(path).await?;
+    file.write_all(data).await?;
+    file.sync_data().await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use std::assert_matches::assert_matches;
+    use std::fs::{create_dir_all, read, read_dir, remove_dir_all};
+
+    use test_log::test;
+
+    use super::*;
+    type TestResult = core::result::Result<(), Box<dyn core::error::Error>>;
+
+    fn reset_folder(path: &str) -> std::io::Result<PathBuf> {
+        let path = PathBuf::from(path);
+        if path.exists() {
+            remove_dir_all(&path)?;
+        }
+        create_dir_all(&path)?;
+
+        Ok(path)
+    }
+
+    fn shard_files(folder: &Path, count: u8) -> Vec<FileWrite> {
+        (0..count)
+            .map(|shard| FileWrite::new(folder.join(format!("shard-{shard}")), &shard))
+            .collect()
+    }
+
+    #[test(tokio::test)]
+    async fn commit_writes_every_file() -> TestResult {
+        // Given
+        let folder = reset_folder("/tmp/bifrost/write-pool-1")?;
+        let pool = WritePool::new(3, folder.join("commit"));
+        let writes = shard_files(&folder, 10);
+
+        // When
+        pool.commit(writes.clone()).await?;
+
+        // Then
+        for write in &writes {
+            assert_eq!(read(write.path())?, write.data);
+        }
+        assert_eq!(read_dir(&folder)?.count(), writes.len());
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn failed_write_aborts_the_commit() -> TestResult {
+        // Given
+        let folder = reset_folder("/tmp/bifrost/write-pool-2")?;
+        let pool = WritePool::new(DEFAULT_WRITE_WORKERS, folder.join("commit"));
+        pool.commit(shard_files(&folder, 8)).await?;
+        let before = shard_files(&folder, 8);
+        let mut writes = before
+            .iter()
+            .map(|write| FileWrite::new(write.path(), &u64::MAX))
+            .collect::<Vec<_>>();
+        writes.insert(
+            3,
+            FileWrite::new(folder.join("missing").join("file"), &1_u8),
+        );
+
+        // When
+        let res = pool.commit(writes).await;
+
+        // Then
+        assert_matches!(res, Err(Error::FileSystem(_)));
+        for write in &before {
+            assert_eq!(read(write.path())?, write.data);
+        }
+        assert_eq!(read_dir(&folder)?.count(), before.len());
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn writes_to_the_same_file_keep_their_order() -> TestResult {
+        // Given
+        let folder = reset_folder("/tmp/bifrost/write-pool-3")?;
+        let pool = WritePool::new(DEFAULT_WRITE_WORKERS, folder.join("commit"));
+        let path = folder.join("stream");
+        let writes = (0..100_u32)
+            .map(|version| FileWrite::new(&path, &version))
+            .collect();
+
+        // When
+        pool.commit(writes).await?;
+
+        // Then
+        assert_eq!(read(&path)?, borsh::to_vec(&99_u32)?);
+        assert_eq!(read_dir(&folder)?.count(), 1);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn interrupted_commit_is_completed_on_recovery() -> TestResult {
+        // Given
+        let folder = reset_folder("/tmp/bifrost/write-pool-4")?;
+        let manifest = folder.join("commit");
+        let pool = WritePool::new(DEFAULT_WRITE_WORKERS, &manifest);
+        pool.commit(shard_files(&folder, 4)).await?;
+        let moved = FileWrite::new(folder.join("shard-0"), &10_u8);
+        let remaining = FileWrite::new(folder.join("shard-1"), &11_u8);
+        let aborted = folder.join("shard-2.pending");
+        std::fs::write(moved.path(), &moved.data)?;
+        std::fs::write(pending_path(remaining.path()), &remaining.data)?;
+        std::fs::write(&aborted, borsh::to_vec(&12_u8)?)?;
+        let files = [moved.path(), remaining.path()]
+            .map(|path| path.to_string_lossy().into_owned())
+            .to_vec();
+        std::fs::write(&manifest, borsh::to_vec(&files)?)?;
+
+        // When
+        pool.recover().await?;
+
+        // Then
+        assert_eq!(read(moved.path())?, moved.data);
+        assert_eq!(read(remaining.path())?, remaining.data);
+        assert_eq!(read(folder.join("shard-2"))?, borsh::to_vec(&2_u8)?);
+        assert!(!aborted.exists());
+        assert!(!manifest.exists());
+        assert_eq!(read_dir(&folder)?.count(), 4);
+
+        Ok(())
+    }
+}
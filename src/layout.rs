@@ -0,0 +1,471 @@
+// File: src/layout.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 18:18:00
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use core::fmt::{self, Display, Formatter, Write as _};
+
+use crate::{
+    account::{AccountMeta, Wallet},
+    io::{AccountDiskLocation, Index},
+    transaction::{CompiledInstruction, Message, Transaction},
+    validator::{AuditCheckpoint, Block},
+};
+
+/// The length of the prefix holding the number of items of a serialized sequence.
+pub const LENGTH_PREFIX: usize = 4;
+
+/// The number of bytes a field takes once serialized.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Size {
+    /// Always the same number of bytes.
+    Fixed(usize),
+    /// A one byte tag, followed by a value of the given size when it's present.
+    Optional(usize),
+    /// A prefix holding the number of items, followed by the items.
+    Sequence {
+        /// The length of the prefix (zero when the number of items isn't written).
+        prefix: usize,
+        /// The size of an item, if they all have the same.
+        item: Option<usize>,
+    },
+    /// A nested type, whose size depends on its content.
+    Variable,
+}
+
+impl Size {
+    /// A sequence with a length prefix, whose items all have the same size.
+    #[must_use]
+    pub const fn items(item: usize) -> Self {
+        Self::Sequence {
+            prefix: LENGTH_PREFIX,
+            item: Some(item),
+        }
+    }
+
+    /// A sequence with a length prefix, whose items have different sizes.
+    #[must_use]
+    pub const fn variable_items() -> Self {
+        Self::Sequence {
+            prefix: LENGTH_PREFIX,
+            item: None,
+        }
+    }
+
+    /// The number of bytes taken by a field.
+    ///
+    /// # Parameters
+    /// * `extent` - What the value of the field holds.
+    ///
+    /// # Returns
+    /// The size of the field, or `None` if the extent doesn't match the size.
+    #[must_use]
+    pub fn len(&self, extent: &Extent) -> Option<usize> {
+        match (*self, extent) {
+            (Self::Fixed(size), Extent::Fixed) => Some(size),
+            (Self::Optional(size), Extent::Present(present)) => {
+                Some(1 + if *present { size } else { 0 })
+            }
+            (
+                Self::Sequence {
+                    prefix,
+                    item: Some(item),
+                },
+                Extent::Count(count),
+            ) => Some(prefix + item * count),
+            (Self::Sequence { prefix, item: None }, Extent::Items(items)) => items
+                .iter()
+                .copied()
+                .sum::<Option<usize>>()
+                .map(|size| prefix + size),
+            (Self::Variable, Extent::Len(len)) => *len,
+            _ => None,
+        }
+    }
+}
+
+impl Display for Size {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Fixed(size) => write!(f, "{size}"),
+            Self::Optional(size) => write!(f, "1 + {size} if present"),
+            Self::Sequence {
+                prefix,
+                item: Some(item),
+            } => write!(f, "{prefix} + {item} × n"),
+            Self::Sequence { prefix, item: None } => write!(f, "{prefix} + Σ items"),
+            Self::Variable => write!(f, "variable"),
+        }
+    }
+}
+
+/// What the value of a field holds, to compute its size from its [`Size`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Extent {
+    /// The field always has the same size.
+    Fixed,
+    /// Whether an optional field holds a value.
+    Present(bool),
+    /// The number of items of a sequence whose items have the same size.
+    Count(usize),
+    /// The described size of each item of a sequence.
+    Items(Vec<Option<usize>>),
+    /// The described size of a nested type.
+    Len(Option<usize>),
+}
+
+/// A field of a serialized type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Field {
+    /// The name of the field.
+    pub name: &'static str,
+    /// The Rust type of the field.
+    pub ty: &'static str,
+    /// The number of bytes the field takes.
+    pub size: Size,
+}
+
+impl Field {
+    /// Describes a field.
+    ///
+    /// # Parameters
+    /// * `name` - The name of the field,
+    /// * `ty` - Its Rust type,
+    /// * `size` - The number of bytes it takes.
+    #[must_use]
+    pub const fn new(name: &'static str, ty: &'static str, size: Size) -> Self {
+        Self { name, ty, size }
+    }
+}
+
+/// The byte layout of a serialized type: its fields, in the order they are written.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TypeLayout {
+    /// The name of the type.
+    pub name: &'static str,
+    /// What the bytes are used for.
+    pub description: &'static str,
+    /// The fields of the type.
+    pub fields: Vec<Field>,
+}
+
+impl TypeLayout {
+    /// The number of bytes taken by the type, if all its fields have a fixed size.
+    #[must_use]
+    pub fn fixed_size(&self) -> Option<usize> {
+        self.fields
+            .iter()
+            .map(|field| match field.size {
+                Size::Fixed(size) => Some(size),
+                Size::Optional(_) | Size::Sequence { .. } | Size::Variable => None,
+            })
+            .sum()
+    }
+}
+
+/// A type written on the disk or sent to other nodes, whose byte layout can be described.
+pub trait DescribeLayout {
+    /// The name of the type.
+    const NAME: &'static str;
+    /// What the bytes are used for.
+    const DESCRIPTION: &'static str;
+
+    /// The fields of the type, in the order they are serialized.
+    fn fields() -> Vec<Field>;
+
+    /// What each field of the value holds, in the same order as [`DescribeLayout::fields`].
+    fn extents(&self) -> Vec<Extent>;
+
+    /// The layout of the type.
+    #[must_use]
+    fn layout() -> TypeLayout {
+        TypeLayout {
+            name: Self::NAME,
+            description: Self::DESCRIPTION,
+            fields: Self::fields(),
+        }
+    }
+
+    /// The number of bytes the value takes once serialized, according to its layout.
+    ///
+    /// # Returns
+    /// The size of the value, or `None` if its extents don't match the layout.
+    #[must_use]
+    fn described_len(&self) -> Option<usize> {
+        let fields = Self::fields();
+        let extents = self.extents();
+        if fields.len() != extents.len() {
+            return None;
+        }
+
+        fields
+            .iter()
+            .zip(&extents)
+            .map(|(field, extent)| field.size.len(extent))
+            .sum()
+    }
+}
+
+/// The layout of every type written on the disk or sent to other nodes.
+#[must_use]
+pub fn all() -> Vec<TypeLayout> {
+    vec![
+        Transaction::layout(),
+        Message::layout(),
+        CompiledInstruction::layout(),
+        AccountMeta::layout(),
+        Block::layout(),
+        Wallet::layout(),
+        AccountDiskLocation::layout(),
+        Index::layout(),
+        AuditCheckpoint::layout(),
+    ]
+}
+
+/// Renders layouts as a JSON array.
+///
+/// # Parameters
+/// * `layouts` - The layouts to render.
+#[must_use]
+pub fn to_json(layouts: &[TypeLayout]) -> String {
+    let optional =
+        |value: Option<usize>| value.map_or_else(|| "null".to_owned(), |v| v.to_string());
+    let types = layouts
+        .iter()
+        .map(|layout| {
+            let fields = layout
+                .fields
+                .iter()
+                .map(|field| {
+                    let size = match field.size {
+                        Size::Fixed(size) => format!("{{ \"kind\": \"fixed\", \"bytes\": {size} }}"),
+                        Size::Optional(size) => {
+                            format!("{{ \"kind\": \"optional\", \"tag\": 1, \"bytes\": {size} }}")
+                        }
+                        Size::Sequence { prefix, item } => format!(
+                            "{{ \"kind\": \"sequence\", \"prefix\": {prefix}, \"item\": {} }}",
+                            optional(item)
+                        ),
+                        Size::Variable => "{ \"kind\": \"variable\" }".to_owned(),
+                    };
+                    format!(
+                        "      {{ \"name\": \"{}\", \"type\": \"{}\", \"size\": {size} }}",
+                        field.name, field.ty
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(",\n");
+            format!(
+                "  {{\n    \"name\": \"{}\",\n    \"description\": \"{}\",\n    \"fixed_size\": {},\n    \"fields\": [\n{fields}\n    ]\n  }}",
+                layout.name,
+                layout.description,
+                optional(layout.fixed_size()),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",\n");
+
+    format!("[\n{types}\n]\n")
+}
+
+/// Renders layouts as markdown, a table per type.
+///
+/// # Parameters
+/// * `layouts` - The layouts to render.
+#[must_use]
+pub fn to_markdown(layouts: &[TypeLayout]) -> String {
+    let header = String::from(
+        "# On-disk and wire formats\n\nIntegers are little-endian, sequences are prefixed by their number of items as a `u32`.\n",
+    );
+    layouts.iter().fold(header, |mut doc, layout| {
+        write!(doc, "\n## `{}`\n\n{}", layout.name, layout.description).unwrap();
+        if let Some(size) = layout.fixed_size() {
+            write!(doc, " Always {size} bytes long.").unwrap();
+        }
+        doc.push_str("\n\n| Field | Type | Size (bytes) |\n|---|---|---|\n");
+        for field in &layout.fields {
+            writeln!(
+                doc,
+                "| `{}` | `{}` | {} |",
+                field.name, field.ty, field.size
+            )
+            .unwrap();
+        }
+        doc
+    })
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use std::sync::Arc;
+
+    use test_log::test;
+
+    use super::*;
+    use crate::{
+        crypto::Keypair,
+        fixtures::{self, Fixture},
+        program::system,
+        validator::BlockHash,
+    };
+    type TestResult = core::result::Result<(), Box<dyn core::error::Error>>;
+
+    #[test]
+    fn described_transactions_match_the_fixtures() -> TestResult {
+        // Given
+        let fixtures = fixtures::all()?;
+
+        // When
+        let transactions = fixtures
+            .iter()
+            .map(Fixture::decode)
+            .collect::<std::io::Result<Vec<_>>>()?;
+
+        // Then
+        for (fixture, trx) in fixtures.iter().zip(&transactions) {
+            assert_eq!(
+                trx.described_len(),
+                Some(fixture.transaction.len()),
+                "{}",
+                fixture.name
+            );
+            assert_eq!(
+                trx.message().described_len(),
+                Some(fixture.message.len()),
+                "{}",
+                fixture.name
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn described_optional_fields_match_their_serialization() -> TestResult {
+        // Given
+        let payer = Keypair::generate();
+        let mut trx = Transaction::new(fixtures::SLOT)
+            .with_sequence(3)?
+            .with_deadline(fixtures::SLOT + 10)?
+            .with_chain(BlockHash::default())?;
+        trx.add(&[system::instruction::transfer(
+            payer.pubkey(),
+            Keypair::generate().pubkey(),
+            1_000,
+        )?])?;
+
+        // When
+        trx.sign(&payer)?;
+
+        // Then
+        assert_eq!(trx.described_len(), Some(borsh::to_vec(&trx)?.len()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn described_vault_envelopes_match_their_serialization() -> TestResult {
+        // Given
+        let wallet = Wallet {
+            prisms: 1_000,
+            data: Arc::from([1_u8, 2, 3].as_slice()),
+        };
+        let location = AccountDiskLocation {
+            slot: 12,
+            id: 3,
+            offset: 1_024,
+            size: 15,
+        };
+
+        // When
+        let sizes = (
+            borsh::to_vec(&wallet)?.len(),
+            borsh::to_vec(&location)?.len(),
+        );
+
+        // Then
+        assert_eq!(wallet.described_len(), Some(sizes.0));
+        assert_eq!(location.described_len(), Some(sizes.1));
+        assert_eq!(AccountDiskLocation::layout().fixed_size(), Some(sizes.1));
+
+        Ok(())
+    }
+
+    #[test]
+    fn described_blocks_and_checkpoints_match_their_bytes() -> TestResult {
+        // Given
+        let signer = Keypair::generate();
+        let mut block = Block::genesis();
+        block.transactions.push(signer.sign(b"first"));
+        block.transactions.push(signer.sign(b"second"));
+        let first = AuditCheckpoint::new(
+            1,
+            block.get_hash(),
+            BlockHash::default(),
+            1_000,
+            None,
+            &signer,
+        );
+        let checkpoint = AuditCheckpoint::new(
+            2,
+            block.get_hash(),
+            BlockHash::default(),
+            1_000,
+            Some(&first),
+            &signer,
+        );
+
+        // When
+        let sizes = (block.preimage().len(), borsh::to_vec(&checkpoint)?.len());
+
+        // Then
+        assert_eq!(block.described_len(), Some(sizes.0));
+        assert_eq!(checkpoint.described_len(), Some(sizes.1));
+        assert_eq!(AuditCheckpoint::layout().fixed_size(), Some(sizes.1));
+
+        Ok(())
+    }
+
+    #[test]
+    fn every_layout_is_rendered() {
+        // Given
+        let layouts = all();
+
+        // When
+        let json = to_json(&layouts);
+        let markdown = to_markdown(&layouts);
+
+        // Then
+        for layout in &layouts {
+            assert!(json.contains(&format!("\"name\": \"{}\"", layout.name)));
+            assert!(markdown.contains(&format!("## `{}`", layout.name)));
+            for field in &layout.fields {
+                assert!(markdown.contains(&format!("| `{}` | `{}` |", field.name, field.ty)));
+            }
+        }
+    }
+}
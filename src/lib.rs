@@ -3,7 +3,7 @@
 // Creation date: Friday 07 February 2025
 // Author: Vincent Berthier <vincent.berthier@posteo.org>
 // -----
-// Last modified: Wednesday 12 February 2025 @ 22:12:18
+// Last modified: Thursday 15 October 2026 @ 20:42:22
 // Modified by: Vincent Berthier
 // -----
 // Copyright (c) 2025 <Vincent Berthier>
@@ -31,6 +31,8 @@
 #![feature(never_type)]
 #![feature(assert_matches)]
 #![feature(coverage_attribute)]
+#![feature(file_lock)]
+#![cfg_attr(test, feature(test))]
 #![cfg_attr(not(feature = "test"), allow(dead_code, clippy::allow_attributes))]
 #![warn(missing_docs)]
 
@@ -41,10 +43,18 @@ mod error;
 pub mod account;
 /// Definition of all cryptography related operations
 pub mod crypto;
+/// Deterministic test vectors for the clients of the blockchain.
+#[cfg(any(test, feature = "test-utils"))]
+pub mod fixtures;
 /// I/O operations
 pub mod io;
+/// Byte layouts of the types written on the disk or sent to other nodes.
+pub mod layout;
 /// Programs embedded in the blockchain.
 pub mod program;
+/// Harness running programs against in-memory accounts.
+#[cfg(any(test, feature = "test-utils"))]
+pub mod program_test;
 /// Definition of transaction and base instructions.
 pub mod transaction;
 /// The validator producing blocks from instructions.
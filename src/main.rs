@@ -3,7 +3,7 @@
 // Creation date: Friday 07 February 2025
 // Author: Vincent Berthier <vincent.berthier@posteo.org>
 // -----
-// Last modified: Sunday 09 February 2025 @ 16:51:46
+// Last modified: Thursday 15 October 2026 @ 18:22:40
 // Modified by: Vincent Berthier
 // -----
 // Copyright (c) 2025 <Vincent Berthier>
@@ -29,13 +29,76 @@
 use tracing::{info, level_filters::LevelFilter};
 use tracing_subscriber::{fmt, prelude::*, EnvFilter};
 
-use bifrost::Error;
+use bifrost::{
+    io::{set_vault_path, Vault},
+    validator::{parse_prisms, DuplicatePolicy, Genesis, GenesisConfig},
+    Error,
+};
 type Result<T> = core::result::Result<T, Error>;
 
+const USAGE: &str = "bifrost genesis verify <file> [--max-supply <prisms>] [--sum-duplicates]
+       bifrost account show <vault> <key or prefix>";
+
 #[tokio::main]
 async fn main() -> Result<()> {
     setup_tracing()?;
-    info!("Hello World");
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    match args.as_slice() {
+        [] => info!("Hello World"),
+        [genesis, verify, file, options @ ..] if genesis == "genesis" && verify == "verify" => {
+            verify_genesis(file, options).await?;
+        }
+        [account, show, vault, prefix] if account == "account" && show == "show" => {
+            show_account(vault, prefix).await?;
+        }
+        _ => return Err(Error::Usage(USAGE)),
+    }
+
+    Ok(())
+}
+
+/// Validates an allocation file and logs its summary.
+async fn verify_genesis(file: &str, options: &[String]) -> Result<()> {
+    let mut config = GenesisConfig::default();
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        match option.as_str() {
+            "--sum-duplicates" => config.duplicates = DuplicatePolicy::Sum,
+            "--max-supply" => {
+                let max = options.next().ok_or(Error::Usage(USAGE))?;
+                config.max_supply = parse_prisms(max)?;
+            }
+            _ => return Err(Error::Usage(USAGE)),
+        }
+    }
+
+    let genesis = Genesis::from_allocation_file(file, &config).await?;
+    info!(
+        accounts = genesis.allocations().len(),
+        total = genesis.total(),
+        chain_id = ?genesis.hash(),
+        "the allocation file is valid"
+    );
+
+    Ok(())
+}
+
+/// Logs the account of an existing vault whose key starts with a prefix.
+async fn show_account(path: &str, prefix: &str) -> Result<()> {
+    if !std::path::Path::new(path).exists() {
+        return Err(Error::Usage(USAGE));
+    }
+    set_vault_path(path)?;
+    let vault = Vault::load_or_create().await?;
+    let key = vault.resolve_prefix(prefix)?;
+    let account = vault.get(&key).await?;
+    info!(
+        %key,
+        short = key.short(),
+        prisms = account.prisms,
+        data = account.data.len(),
+        "account found"
+    );
 
     Ok(())
 }
@@ -0,0 +1,266 @@
+// File: src/program/clock.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 14:48:44
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::fmt::{Display, Formatter};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// A slot of the blockchain: the period during which a block is produced.
+///
+/// It's encoded like the `u64` it wraps.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    BorshSerialize,
+    BorshDeserialize,
+)]
+pub struct Slot(u64);
+
+/// An epoch of the blockchain: a fixed number of consecutive slots.
+///
+/// It's encoded like the `u64` it wraps.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    BorshSerialize,
+    BorshDeserialize,
+)]
+pub struct Epoch(u64);
+
+impl Slot {
+    /// Creates a slot from its number.
+    #[must_use]
+    pub const fn new(slot: u64) -> Self {
+        Self(slot)
+    }
+
+    /// Get the number of the slot.
+    #[must_use]
+    pub const fn get(self) -> u64 {
+        self.0
+    }
+
+    /// Get the slot a number of slots later, stopping at the last one.
+    ///
+    /// # Parameters
+    /// * `slots` - The number of slots to move forward.
+    #[must_use]
+    pub const fn saturating_add(self, slots: u64) -> Self {
+        Self(self.0.saturating_add(slots))
+    }
+
+    /// Get the slot a number of slots earlier, stopping at the first one.
+    ///
+    /// # Parameters
+    /// * `slots` - The number of slots to move back.
+    #[must_use]
+    pub const fn saturating_sub(self, slots: u64) -> Self {
+        Self(self.0.saturating_sub(slots))
+    }
+
+    /// Get the epoch the slot belongs to.
+    ///
+    /// # Parameters
+    /// * `epoch_len` - The number of slots in an epoch (must not be zero).
+    ///
+    /// # Example
+    /// ```rust
+    /// # use bifrost::program::{Epoch, Slot, SLOTS_PER_EPOCH};
+    /// assert_eq!(Slot::new(SLOTS_PER_EPOCH - 1).epoch(SLOTS_PER_EPOCH), Epoch::new(0));
+    /// assert_eq!(Slot::new(SLOTS_PER_EPOCH).epoch(SLOTS_PER_EPOCH), Epoch::new(1));
+    /// ```
+    #[expect(clippy::integer_division)]
+    #[must_use]
+    pub const fn epoch(self, epoch_len: u64) -> Epoch {
+        Epoch(self.0 / epoch_len)
+    }
+
+    /// Get the position of the slot within its epoch.
+    ///
+    /// # Parameters
+    /// * `epoch_len` - The number of slots in an epoch (must not be zero).
+    #[expect(clippy::integer_division_remainder_used)]
+    #[must_use]
+    pub const fn index_in_epoch(self, epoch_len: u64) -> u64 {
+        self.0 % epoch_len
+    }
+}
+
+impl Epoch {
+    /// Creates an epoch from its number.
+    #[must_use]
+    pub const fn new(epoch: u64) -> Self {
+        Self(epoch)
+    }
+
+    /// Get the number of the epoch.
+    #[must_use]
+    pub const fn get(self) -> u64 {
+        self.0
+    }
+
+    /// Get the epoch following this one.
+    #[must_use]
+    pub const fn next(self) -> Self {
+        Self(self.0.saturating_add(1))
+    }
+
+    /// Get the first slot of the epoch.
+    ///
+    /// # Parameters
+    /// * `epoch_len` - The number of slots in an epoch.
+    #[must_use]
+    pub const fn first_slot(self, epoch_len: u64) -> Slot {
+        Slot(self.0.saturating_mul(epoch_len))
+    }
+
+    /// Get the last slot of the epoch.
+    ///
+    /// # Parameters
+    /// * `epoch_len` - The number of slots in an epoch (must not be zero).
+    #[must_use]
+    pub const fn last_slot(self, epoch_len: u64) -> Slot {
+        self.next().first_slot(epoch_len).saturating_sub(1)
+    }
+}
+
+impl From<u64> for Slot {
+    fn from(slot: u64) -> Self {
+        Self(slot)
+    }
+}
+
+impl From<Slot> for u64 {
+    fn from(slot: Slot) -> Self {
+        slot.0
+    }
+}
+
+impl From<u64> for Epoch {
+    fn from(epoch: u64) -> Self {
+        Self(epoch)
+    }
+}
+
+impl From<Epoch> for u64 {
+    fn from(epoch: Epoch) -> Self {
+        epoch.0
+    }
+}
+
+impl Display for Slot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "slot {}", self.0)
+    }
+}
+
+impl Display for Epoch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "epoch {}", self.0)
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use test_log::test;
+
+    use super::super::SLOTS_PER_EPOCH;
+    use super::*;
+    type TestResult = core::result::Result<(), Box<dyn core::error::Error>>;
+
+    #[test]
+    fn epoch_boundaries() {
+        // Given
+        const LEN: u64 = 10;
+        let epoch = Epoch::new(3);
+
+        // When
+        let first = epoch.first_slot(LEN);
+        let last = epoch.last_slot(LEN);
+
+        // Then
+        assert_eq!((first, last), (Slot::new(30), Slot::new(39)));
+        assert_eq!(first.epoch(LEN), epoch);
+        assert_eq!(last.epoch(LEN), epoch);
+        assert_eq!(last.saturating_add(1).epoch(LEN), epoch.next());
+        assert_eq!(first.saturating_sub(1).epoch(LEN), Epoch::new(2));
+        assert_eq!(
+            (first.index_in_epoch(LEN), last.index_in_epoch(LEN)),
+            (0, 9)
+        );
+    }
+
+    #[test]
+    fn arithmetic_saturates() {
+        // Given
+        let last_epoch = Epoch::new(u64::MAX);
+
+        // When
+        let after = Slot::new(u64::MAX).saturating_add(1);
+        let before = Slot::new(0).saturating_sub(1);
+
+        // Then
+        assert_eq!(after, Slot::new(u64::MAX));
+        assert_eq!(before, Slot::new(0));
+        assert_eq!(last_epoch.next(), last_epoch);
+        assert_eq!(last_epoch.first_slot(SLOTS_PER_EPOCH), Slot::new(u64::MAX));
+    }
+
+    #[test]
+    fn encoded_like_integers() -> TestResult {
+        // Given
+        let slot = Slot::new(1_234);
+        let epoch = Epoch::new(5);
+
+        // When
+        let encoded = borsh::to_vec(&(slot, epoch))?;
+
+        // Then
+        assert_eq!(encoded, borsh::to_vec(&(1_234_u64, 5_u64))?);
+        assert_eq!(borsh::from_slice::<(Slot, Epoch)>(&encoded)?, (slot, epoch));
+        assert_eq!(slot.to_string(), "slot 1234");
+        assert_eq!(epoch.to_string(), "epoch 5");
+
+        Ok(())
+    }
+}
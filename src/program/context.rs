@@ -0,0 +1,171 @@
+// File: src/program/context.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 17:07:05
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use ed25519_dalek::PUBLIC_KEY_LENGTH;
+use tracing::{instrument, warn};
+
+use super::{Epoch, Error, Result, Slot};
+use crate::crypto::{Pubkey, Seeds};
+
+/// Number of slots in an epoch.
+pub const SLOTS_PER_EPOCH: u64 = 432_000;
+
+/// The environment in which an instruction is executed.
+///
+/// The slot (hence the epoch) is the one the processor pinned when it started the block:
+/// every instruction of every transaction in the block sees the same values, on whichever
+/// validator replays it, without passing an account for them. No wall-clock time is given,
+/// since it would differ between the validators.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Context {
+    /// The slot during which the instruction is executed.
+    slot: Slot,
+    /// The account that paid the transaction's fee.
+    fee_payer: Pubkey,
+    /// The fee debited from the payer.
+    fee_paid: u64,
+}
+
+impl Context {
+    /// Creates the context of an instruction executed during the given slot.
+    ///
+    /// # Parameters
+    /// * `slot` - The slot during which the instruction is executed.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use bifrost::program::{Context, Epoch, SLOTS_PER_EPOCH};
+    /// let context = Context::new(SLOTS_PER_EPOCH + 1);
+    /// assert_eq!(context.epoch(), Epoch::new(1));
+    /// ```
+    #[must_use]
+    pub fn new<S>(slot: S) -> Self
+    where
+        S: Into<Slot>,
+    {
+        Self {
+            slot: slot.into(),
+            fee_payer: Pubkey::from_bytes(&[0; PUBLIC_KEY_LENGTH]),
+            fee_paid: 0,
+        }
+    }
+
+    /// Sets the fee paid for the transaction the instruction belongs to.
+    ///
+    /// # Parameters
+    /// * `payer` - The account the fee was debited from,
+    /// * `fee` - The amount of prisms debited.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use bifrost::{crypto::Keypair, program::Context};
+    /// let payer = Keypair::generate().pubkey();
+    /// let context = Context::new(1).with_fee(payer, 5_000);
+    /// assert_eq!(context.fee_payer(), payer);
+    /// assert_eq!(context.fee_paid(), 5_000);
+    /// ```
+    #[must_use]
+    pub const fn with_fee(self, payer: Pubkey, fee: u64) -> Self {
+        Self {
+            fee_payer: payer,
+            fee_paid: fee,
+            ..self
+        }
+    }
+
+    /// Get the slot during which the instruction is executed.
+    #[must_use]
+    pub const fn slot(&self) -> Slot {
+        self.slot
+    }
+
+    /// Get the account that paid the transaction's fee.
+    #[must_use]
+    pub const fn fee_payer(&self) -> Pubkey {
+        self.fee_payer
+    }
+
+    /// Get the fee debited from the payer for the transaction.
+    #[must_use]
+    pub const fn fee_paid(&self) -> u64 {
+        self.fee_paid
+    }
+
+    /// Get the epoch during which the instruction is executed.
+    #[must_use]
+    pub const fn epoch(&self) -> Epoch {
+        self.slot.epoch(SLOTS_PER_EPOCH)
+    }
+
+    /// Checks that an account is the one derived from the given seeds.
+    ///
+    /// Programs requiring a derived account declare it in the metas of their
+    /// instructions, and check here that the client passed the right one instead
+    /// of looking it up in the vault.
+    ///
+    /// # Parameters
+    /// * `key` - The key of the account passed to the instruction,
+    /// * `seeds` - The seeds the account is derived from.
+    ///
+    /// # Errors
+    /// If no off-curve key can be derived from the seeds, or if the account isn't
+    /// the derived one (the error then holds the expected address).
+    ///
+    /// # Example
+    /// ```rust
+    /// # use bifrost::{crypto::{Keypair, Seeds}, program::{Context, Error}};
+    /// let seeds: [&[u8]; 2] = [b"vault", b"owner"];
+    /// let derived = Seeds::new(&seeds)?.generate_offcurve()?.0;
+    /// let context = Context::new(1);
+    /// context.verify_derivation(&derived, &seeds)?;
+    /// assert!(context.verify_derivation(&Keypair::generate().pubkey(), &seeds).is_err());
+    /// # Ok::<(), Error>(())
+    /// ```
+    #[instrument(skip_all, fields(%key))]
+    pub fn verify_derivation<S>(&self, key: &Pubkey, seeds: &[S]) -> Result<()>
+    where
+        S: AsRef<[u8]>,
+    {
+        let expected = Seeds::new(seeds)?.generate_offcurve()?.0;
+        if *key != expected {
+            warn!(%expected, "the account doesn't match its seeds");
+            return Err(Error::InvalidDerivedAddress {
+                key: *key,
+                expected,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for Context {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
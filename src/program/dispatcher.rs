@@ -3,32 +3,60 @@ use tracing::{debug, instrument};
 use crate::{account::TransactionAccount, crypto::Pubkey};
 
 use super::{
+    escrow::{self, ESCROW_PROGRAM},
+    memo::{self, MEMO_PROGRAM},
+    stake::{self, STAKE_PROGRAM},
     system::{self, SYSTEM_PROGRAM},
     testing_dummy::{self, TESTING_PROGRAM},
-    Error, Result,
+    Context, Error, Result,
 };
 
 /// Dispatches an instruction to the program handling it.
 ///
 /// # Parameters
-/// * `instruction` - The instruction to execute,
-/// * `accounts` - The accounts referenced by the instruction.
+/// * `program` - The program executing the instruction,
+/// * `context` - The context of the execution,
+/// * `accounts` - The accounts referenced by the instruction,
+/// * `payload` - The instruction's payload.
 ///
 /// # Errors
 /// If the program is unknown or failed to run.
 #[instrument(skip_all)]
-pub fn dispatch(program: &Pubkey, accounts: &[TransactionAccount], payload: &[u8]) -> Result<()> {
+pub fn dispatch(
+    program: &Pubkey,
+    context: &Context,
+    accounts: &[TransactionAccount],
+    payload: &[u8],
+) -> Result<()> {
     debug!(
         %program,
         "received new instruction to handle"
     );
     match *program {
-        SYSTEM_PROGRAM => system::execute_instruction(accounts, payload),
-        TESTING_PROGRAM => testing_dummy::execute_instruction(accounts, payload),
+        ESCROW_PROGRAM => escrow::execute_instruction(context, accounts, payload),
+        STAKE_PROGRAM => stake::execute_instruction(context, accounts, payload),
+        SYSTEM_PROGRAM => system::execute_instruction(context, accounts, payload),
+        MEMO_PROGRAM => memo::execute_instruction(accounts, payload),
+        TESTING_PROGRAM => testing_dummy::execute_instruction(context, accounts, payload),
         key => Err(Error::UnknownProgram { key }),
     }
 }
 
+/// Get the maximum number of times a program can be invoked by a single transaction.
+///
+/// # Parameters
+/// * `program` - The program to check.
+///
+/// # Returns
+/// The limit if the program has one, `None` otherwise.
+#[must_use]
+pub const fn max_invocations(program: &Pubkey) -> Option<usize> {
+    match *program {
+        MEMO_PROGRAM => Some(memo::MAX_INVOCATIONS),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 #[cfg_attr(coverage_nightly, coverage(off))]
 mod tests {
@@ -54,8 +82,8 @@ mod tests {
         let key2 = Keypair::generate().pubkey();
         let meta1 = AccountMeta::signing(key1, Writable::Yes)?;
         let meta2 = AccountMeta::wallet(key2, Writable::Yes)?;
-        let mut wallet1 = Wallet { prisms: AMOUNT };
-        let mut wallet2 = Wallet { prisms: 0 };
+        let mut wallet1 = Wallet::new(AMOUNT);
+        let mut wallet2 = Wallet::new(0);
 
         let accounts_vec = vec![
             TransactionAccount::new(&meta1, &mut wallet1),
@@ -65,7 +93,12 @@ mod tests {
         let instruction = system::instruction::transfer(key1, key2, AMOUNT)?;
 
         // When
-        dispatch(&SYSTEM_PROGRAM, &accounts_vec, instruction.data())?;
+        dispatch(
+            &SYSTEM_PROGRAM,
+            &Context::default(),
+            &accounts_vec,
+            instruction.data(),
+        )?;
 
         // Then
         assert_eq!(wallet1.prisms, 0);
@@ -74,6 +107,19 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn program_ids_are_off_curve() {
+        for program in [
+            ESCROW_PROGRAM,
+            MEMO_PROGRAM,
+            STAKE_PROGRAM,
+            SYSTEM_PROGRAM,
+            TESTING_PROGRAM,
+        ] {
+            assert!(!program.is_oncurve(), "{program} is on the curve");
+        }
+    }
+
     #[test]
     fn unknow_program() -> TestResult {
         // Given
@@ -83,8 +129,8 @@ mod tests {
         let program = Keypair::generate().pubkey();
         let meta1 = AccountMeta::signing(key1, Writable::Yes)?;
         let meta2 = AccountMeta::wallet(key2, Writable::Yes)?;
-        let mut wallet1 = Wallet { prisms: AMOUNT };
-        let mut wallet2 = Wallet { prisms: 0 };
+        let mut wallet1 = Wallet::new(AMOUNT);
+        let mut wallet2 = Wallet::new(0);
 
         let accounts_vec = vec![
             TransactionAccount::new(&meta1, &mut wallet1),
@@ -94,7 +140,12 @@ mod tests {
         let instruction = Instruction::new(program, [meta1, meta2], &Vec::<u8>::new());
 
         // When
-        let res = dispatch(&program, &accounts_vec, instruction.data());
+        let res = dispatch(
+            &program,
+            &Context::default(),
+            &accounts_vec,
+            instruction.data(),
+        );
 
         // Then
         assert_matches!(res, Err(err) if matches!(err, Error::UnknownProgram { key } if key == program));
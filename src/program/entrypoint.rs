@@ -0,0 +1,300 @@
+// File: src/program/entrypoint.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 16:49:53
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use core::{
+    fmt::{Debug, Formatter},
+    ops::Deref,
+};
+
+use crate::account::{Privilege, TransactionAccount};
+
+use super::{Error, Result};
+
+/// An account of an instruction, checked to hold the privileges its type requires.
+///
+/// The handlers of [`declare_program!`](crate::declare_program) take their accounts
+/// as implementors of this trait, so they don't have to check them.
+pub trait InstructionAccount<'r, 'a>: Sized {
+    /// Checks the privileges of an account, and wraps it.
+    ///
+    /// # Parameters
+    /// * `account` - The account given to the instruction.
+    ///
+    /// # Errors
+    /// If the account lacks a privilege the type requires.
+    fn from_account(account: &'r TransactionAccount<'a>) -> Result<Self>;
+}
+
+macro_rules! instruction_account {
+    ($(#[$meta:meta])* $name:ident, $($privilege:ident => $check:ident),*) => {
+        $(#[$meta])*
+        pub struct $name<'r, 'a>(&'r TransactionAccount<'a>);
+
+        impl<'r, 'a> InstructionAccount<'r, 'a> for $name<'r, 'a> {
+            fn from_account(account: &'r TransactionAccount<'a>) -> Result<Self> {
+                $(
+                    if !account.privileges.$check() {
+                        return Err(Error::MissingPrivilege {
+                            key: account.key,
+                            privilege: Privilege::$privilege,
+                        });
+                    }
+                )*
+                Ok(Self(account))
+            }
+        }
+
+        impl Debug for $name<'_, '_> {
+            fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+                f.debug_tuple(stringify!($name)).field(&self.0.key).finish()
+            }
+        }
+
+        impl<'a> Deref for $name<'_, 'a> {
+            type Target = TransactionAccount<'a>;
+
+            fn deref(&self) -> &Self::Target {
+                self.0
+            }
+        }
+    };
+}
+
+instruction_account!(
+    /// Any account, read-only or not.
+    Account,
+);
+instruction_account!(
+    /// An account signing the transaction, which the instruction can't modify.
+    Signer,
+    Signing => is_signer
+);
+instruction_account!(
+    /// An account the instruction can modify.
+    Mutable,
+    Writable => is_writable
+);
+instruction_account!(
+    /// An account signing the transaction, which the instruction can modify.
+    MutableSigner,
+    Signing => is_signer,
+    Writable => is_writable
+);
+
+/// Declares the entrypoint of a program: the `execute_instruction` function
+/// decoding an instruction and calling its handler.
+///
+/// Each variant of the instruction enum (a unit or a struct variant) is mapped to a handler
+/// taking the context, then its accounts, then the fields of the variant. The accounts are
+/// typed by their [`InstructionAccount`]: the entrypoint fails if there are fewer accounts
+/// than the handler takes, or if one lacks a privilege, before the handler is called.
+/// The attributes (and doc comments) before the enum are given to the entrypoint.
+///
+/// # Example
+/// ```rust
+/// # use borsh::{BorshDeserialize, BorshSerialize};
+/// # use bifrost::{
+/// #     account::{AccountMeta, TransactionAccount, Wallet, Writable},
+/// #     crypto::Keypair,
+/// #     declare_program,
+/// #     program::{entrypoint::{Mutable, MutableSigner}, Context, Error},
+/// # };
+/// #[derive(BorshSerialize, BorshDeserialize)]
+/// enum CounterInstruction {
+///     Pay { amount: u64 },
+///     Reset,
+/// }
+///
+/// fn pay(_: &Context, from: MutableSigner, to: Mutable, amount: u64) -> Result<(), Error> {
+///     from.sub_prisms(amount)?;
+///     to.add_prisms(amount)?;
+///     Ok(())
+/// }
+///
+/// fn reset(_: &Context, account: Mutable) -> Result<(), Error> {
+///     Ok(account.set_data(Vec::new())?)
+/// }
+///
+/// declare_program! {
+///     CounterInstruction;
+///     Pay { amount } => pay(from: MutableSigner, to: Mutable);
+///     Reset => reset(account: Mutable);
+/// }
+///
+/// let (from, to) = (Keypair::generate().pubkey(), Keypair::generate().pubkey());
+/// let (mut from_wallet, mut to_wallet) = (Wallet::new(10), Wallet::new(0));
+/// let accounts = [
+///     TransactionAccount::new(&AccountMeta::signing(from, Writable::Yes)?, &mut from_wallet),
+///     TransactionAccount::new(&AccountMeta::wallet(to, Writable::No)?, &mut to_wallet),
+/// ];
+/// let payload = borsh::to_vec(&CounterInstruction::Pay { amount: 4 })?;
+/// let res = execute_instruction(&Context::default(), &accounts, &payload);
+/// assert!(matches!(res, Err(Error::MissingPrivilege { key, .. }) if key == to));
+/// # Ok::<(), Box<dyn core::error::Error>>(())
+/// ```
+///
+/// The handlers must take the accounts the entrypoint declares:
+/// ```rust,compile_fail
+/// # use borsh::{BorshDeserialize, BorshSerialize};
+/// # use bifrost::{declare_program, program::{entrypoint::{Mutable, Signer}, Context, Error}};
+/// #[derive(BorshSerialize, BorshDeserialize)]
+/// enum Instruction {
+///     Close,
+/// }
+///
+/// fn close(_: &Context, account: Mutable) -> Result<(), Error> {
+///     Ok(account.close()?)
+/// }
+///
+/// declare_program! {
+///     Instruction;
+///     Close => close(account: Signer);
+/// }
+/// ```
+///
+/// as many as it declares:
+/// ```rust,compile_fail
+/// # use borsh::{BorshDeserialize, BorshSerialize};
+/// # use bifrost::{declare_program, program::{entrypoint::Mutable, Context, Error}};
+/// #[derive(BorshSerialize, BorshDeserialize)]
+/// enum Instruction {
+///     Close,
+/// }
+///
+/// fn close(_: &Context, account: Mutable) -> Result<(), Error> {
+///     Ok(account.close()?)
+/// }
+///
+/// declare_program! {
+///     Instruction;
+///     Close => close(account: Mutable, other: Mutable);
+/// }
+/// ```
+///
+/// and every field of their variant:
+/// ```rust,compile_fail
+/// # use borsh::{BorshDeserialize, BorshSerialize};
+/// # use bifrost::{declare_program, program::{entrypoint::Mutable, Context, Error}};
+/// #[derive(BorshSerialize, BorshDeserialize)]
+/// enum Instruction {
+///     Burn { amount: u64 },
+/// }
+///
+/// fn burn(_: &Context, account: Mutable) -> Result<(), Error> {
+///     Ok(account.sub_prisms(1)?)
+/// }
+///
+/// declare_program! {
+///     Instruction;
+///     Burn { amount } => burn(account: Mutable);
+/// }
+/// ```
+#[macro_export]
+macro_rules! declare_program {
+    (
+        $(#[$meta:meta])*
+        $instruction:ident;
+        $(
+            $variant:ident $({ $($field:ident),* $(,)? })?
+                => $handler:ident($($account:ident: $kind:ident),* $(,)?);
+        )*
+    ) => {
+        $(#[$meta])*
+        pub fn execute_instruction(
+            context: &$crate::program::Context,
+            accounts: &[$crate::account::TransactionAccount],
+            payload: &[u8],
+        ) -> ::core::result::Result<(), $crate::program::Error> {
+            match ::borsh::from_slice::<$instruction>(payload)? {
+                $(
+                    $instruction::$variant $({ $($field),* })? => {
+                        let [$($account,)* ..] = accounts else {
+                            return Err($crate::account::Error::MissingAccounts.into());
+                        };
+                        $handler(
+                            context,
+                            $(
+                                <$crate::program::entrypoint::$kind as
+                                    $crate::program::entrypoint::InstructionAccount>::from_account(
+                                    $account,
+                                )?,
+                            )*
+                            $($($field,)*)?
+                        )
+                    }
+                )*
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use std::assert_matches::assert_matches;
+
+    use test_log::test;
+
+    use crate::account::{AccountMeta, Wallet, Writable};
+    use crate::crypto::Keypair;
+
+    use super::*;
+    type TestResult = core::result::Result<(), Box<dyn core::error::Error>>;
+
+    #[test]
+    fn accounts_are_checked_against_their_type() -> TestResult {
+        // Given
+        let signer = AccountMeta::signing(Keypair::generate().pubkey(), Writable::No)?;
+        let wallet = AccountMeta::wallet(Keypair::generate().pubkey(), Writable::Yes)?;
+        let (mut signed, mut plain) = (Wallet::new(0), Wallet::new(0));
+        let signer = TransactionAccount::new(&signer, &mut signed);
+        let wallet = TransactionAccount::new(&wallet, &mut plain);
+
+        // When
+        let any = Account::from_account(&signer);
+        let signing = Signer::from_account(&signer);
+        let read_only = Mutable::from_account(&signer);
+        let mutable = Mutable::from_account(&wallet);
+        let unsigned = MutableSigner::from_account(&wallet);
+
+        // Then
+        assert_matches!(any, Ok(account) if account.key == signer.key);
+        assert_matches!(signing, Ok(_));
+        assert_matches!(
+            read_only,
+            Err(Error::MissingPrivilege { key, privilege: Privilege::Writable }) if key == signer.key
+        );
+        assert_matches!(mutable, Ok(_));
+        assert_matches!(
+            unsigned,
+            Err(Error::MissingPrivilege { key, privilege: Privilege::Signing }) if key == wallet.key
+        );
+
+        Ok(())
+    }
+}
@@ -3,7 +3,7 @@
 // Creation date: Wednesday 12 February 2025
 // Author: Vincent Berthier <vincent.berthier@posteo.org>
 // -----
-// Last modified: Wednesday 12 February 2025 @ 22:35:14
+// Last modified: Thursday 15 October 2026 @ 16:49:53
 // Modified by: Vincent Berthier
 // -----
 // Copyright (c) 2025 <Vincent Berthier>
@@ -28,29 +28,170 @@
 
 use derive_more::derive::{Display, From};
 
-use crate::crypto::Pubkey;
+use crate::{account::Privilege, crypto::Pubkey};
 
 /// Errors of the programs module.
 #[derive(Debug, Display, From)]
 #[display("while executing a program: {_variant}")]
 pub enum Error {
+    /// Tried to initialize an account that already holds data.
+    #[display("account '{key}' is already initialized")]
+    AccountAlreadyInitialized {
+        /// The key of the account
+        key: Pubkey,
+    },
+    /// The data of an account would grow above the maximum size.
+    #[display("account data can't be {size} bytes long")]
+    AccountDataTooLarge {
+        /// The requested size
+        size: usize,
+    },
+    /// Tried to cancel an escrow after its deadline.
+    #[display("the escrow can't be cancelled since slot {deadline}")]
+    CancelDeadlinePassed {
+        /// The cancellation deadline of the escrow
+        deadline: u64,
+    },
+    /// Tried to claim an escrow before its unlock slot.
+    #[display("the escrow is locked until slot {unlock_slot}")]
+    EscrowLocked {
+        /// The first slot at which the escrow can be claimed
+        unlock_slot: u64,
+    },
+    /// An account's address doesn't match the one derived from its seeds.
+    #[display("'{key}' is not the expected derived address '{expected}'")]
+    InvalidDerivedAddress {
+        /// The key of the account
+        key: Pubkey,
+        /// The address derived from the seeds.
+        expected: Pubkey,
+    },
     /// The instruction's payload is invalid
     #[display("payload is invalid for the program: {_0}")]
     #[from]
     InvalidPayload(std::io::Error),
+    /// The data stored on an account could not be decoded.
+    #[display("the data of account '{key}' is invalid")]
+    InvalidAccountData {
+        /// The key of the account
+        key: Pubkey,
+    },
+    /// An account can't pay for the rent exemption of its data.
+    #[display("'{key}' can't pay the {required} prisms needed for rent exemption")]
+    InsufficientFundsForRent {
+        /// The key of the account paying
+        key: Pubkey,
+        /// The minimum balance needed
+        required: u64,
+    },
+    /// The memo isn't valid UTF-8.
+    #[display("the memo is not valid UTF-8")]
+    InvalidMemoEncoding,
+    /// The memo is longer than allowed.
+    #[display("the memo is {length} bytes long, more than allowed")]
+    MemoTooLong {
+        /// The length of the memo
+        length: usize,
+    },
+    /// An account given to the memo program didn't sign the transaction.
+    #[display("'{key}' must sign the memo")]
+    MissingMemoSigner {
+        /// The key of the account
+        key: Pubkey,
+    },
+    /// An account given to an instruction lacks a privilege the instruction requires.
+    #[display("'{key}' lacks the {privilege:?} privilege")]
+    MissingPrivilege {
+        /// The key of the account
+        key: Pubkey,
+        /// The privilege the account lacks
+        privilege: Privilege,
+    },
+    /// A delegated operation was attempted on an account without delegation.
+    #[display("account '{key}' has no delegate")]
+    NoDelegation {
+        /// The key of the account
+        key: Pubkey,
+    },
+    /// A delegated operation was signed by a key that isn't the account's delegate.
+    #[display("'{key}' is not the delegate of the account")]
+    UnauthorizedDelegate {
+        /// The key of the signer
+        key: Pubkey,
+    },
     /// Tried to execute an instruction on an unknown program.
     #[display("'{key}' is not a known program")]
     UnknownProgram {
         /// The key of the unknown program
         key: Pubkey,
     },
+    /// An error happened while deriving a key.
+    #[display("error while deriving a key: {_0}")]
+    #[from]
+    Crypto(crate::crypto::Error),
     /// An error happened while trying to access or modify an account.
     #[display("error while operating on an account: {_0}")]
     #[from]
     Account(crate::account::Error),
-    /// Custom error form programs.
+    /// An error of a program that isn't built in, with the program's own code.
     #[display("custom program error: {_0}")]
-    Custom(String),
+    Custom(u32),
+    /// A program failed for the given reason.
+    #[display("the program failed: {_0}")]
+    Failed(String),
 }
 
 impl core::error::Error for Error {}
+
+/// The first error code of the range left to programs that aren't built in,
+/// the built-in errors have codes below it.
+pub const CUSTOM_ERROR_CODES: u32 = 10_000;
+
+impl Error {
+    /// Get the code identifying the error for the clients.
+    ///
+    /// Custom errors are offset into the range starting at [`CUSTOM_ERROR_CODES`]
+    /// (saturating at its end), the built-in ones have fixed codes below it.
+    ///
+    /// # Example
+    /// ```
+    /// # use bifrost::program::{Error, CUSTOM_ERROR_CODES};
+    /// let error = Error::Custom(42);
+    /// assert_eq!(error.code(), CUSTOM_ERROR_CODES + 42);
+    /// assert_eq!(error.custom_code(), Some(42));
+    /// assert!(Error::InvalidMemoEncoding.code() < CUSTOM_ERROR_CODES);
+    /// ```
+    #[must_use]
+    pub const fn code(&self) -> u32 {
+        match self {
+            Self::AccountAlreadyInitialized { .. } => 1,
+            Self::AccountDataTooLarge { .. } => 2,
+            Self::CancelDeadlinePassed { .. } => 3,
+            Self::EscrowLocked { .. } => 4,
+            Self::InvalidDerivedAddress { .. } => 5,
+            Self::InvalidPayload(_) => 6,
+            Self::InvalidAccountData { .. } => 7,
+            Self::InsufficientFundsForRent { .. } => 8,
+            Self::InvalidMemoEncoding => 9,
+            Self::MemoTooLong { .. } => 10,
+            Self::MissingMemoSigner { .. } => 11,
+            Self::NoDelegation { .. } => 12,
+            Self::UnauthorizedDelegate { .. } => 13,
+            Self::UnknownProgram { .. } => 14,
+            Self::Crypto(_) => 15,
+            Self::Account(_) => 16,
+            Self::Failed(_) => 17,
+            Self::MissingPrivilege { .. } => 18,
+            Self::Custom(code) => CUSTOM_ERROR_CODES.saturating_add(*code),
+        }
+    }
+
+    /// Get the program's own code of a custom error.
+    #[must_use]
+    pub const fn custom_code(&self) -> Option<u32> {
+        match self {
+            Self::Custom(code) => Some(*code),
+            _ => None,
+        }
+    }
+}
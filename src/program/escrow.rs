@@ -0,0 +1,604 @@
+// File: src/program/escrow.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 16:49:53
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use tracing::{debug, instrument, warn};
+
+use crate::{
+    account::{Escrow, TransactionAccount},
+    crypto::{Pubkey, Seeds},
+    declare_program, pubkey,
+};
+
+use super::{
+    entrypoint::{Mutable, MutableSigner},
+    Context, Error, Result,
+};
+
+/// The Escrow's program id (`BifrostEscrowProgram11111111111111111111111`)
+pub const ESCROW_PROGRAM: Pubkey = pubkey!("BifrostEscrowProgram11111111111111111111111");
+
+const ESCROW_SEED: &[u8] = b"escrow";
+
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+enum EscrowInstruction {
+    Lock {
+        recipient: Pubkey,
+        unlock_slot: u64,
+        cancel_deadline: u64,
+        amount: u64,
+    },
+    Claim,
+    Cancel,
+}
+
+/// Describes an instruction's payload in a human readable form.
+pub(crate) fn decode(payload: &[u8]) -> Option<String> {
+    let instruction: EscrowInstruction = borsh::from_slice(payload).ok()?;
+    Some(format!("{instruction:?}"))
+}
+
+/// Derives the address of the escrow account locking prisms between two accounts.
+///
+/// # Parameters
+/// * `sender` - The account locking the prisms,
+/// * `recipient` - The account allowed to claim them,
+/// * `unlock_slot` - The first slot at which they can be claimed.
+///
+/// # Errors
+/// If no off-curve key could be derived from those seeds.
+///
+/// # Example
+/// ```rust
+/// # use bifrost::crypto::Keypair;
+/// # use bifrost::program::{escrow::escrow_address, Error};
+/// let sender = Keypair::generate().pubkey();
+/// let recipient = Keypair::generate().pubkey();
+/// let escrow = escrow_address(&sender, &recipient, 100)?;
+/// assert!(!escrow.is_oncurve());
+///
+/// # Ok::<(), Error>(())
+/// ```
+pub fn escrow_address(sender: &Pubkey, recipient: &Pubkey, unlock_slot: u64) -> Result<Pubkey> {
+    let seeds = escrow_seeds(sender, recipient, unlock_slot);
+    Ok(Seeds::new(&seeds)?.generate_offcurve()?.0)
+}
+
+/// The seeds of the escrow account locking prisms between two accounts.
+#[expect(clippy::little_endian_bytes)]
+fn escrow_seeds(sender: &Pubkey, recipient: &Pubkey, unlock_slot: u64) -> [Vec<u8>; 5] {
+    [
+        ESCROW_SEED.to_vec(),
+        ESCROW_PROGRAM.as_ref().to_vec(),
+        sender.as_ref().to_vec(),
+        recipient.as_ref().to_vec(),
+        unlock_slot.to_le_bytes().to_vec(),
+    ]
+}
+
+declare_program! {
+    /// Executes an escrow program's instruction.
+    ///
+    /// # Parameters
+    /// * `context` - The context of the execution,
+    /// * `accounts` - The accounts needed by the instruction,
+    /// * `payload` - The data payload for the instruction.
+    ///
+    /// # Errors
+    /// if the instruction fails to complete (missing accounts, locked escrow, *etc.*).
+    #[instrument(skip_all)]
+    EscrowInstruction;
+    Lock {
+        recipient,
+        unlock_slot,
+        cancel_deadline,
+        amount,
+    } => lock(sender: MutableSigner, escrow_account: Mutable);
+    Claim => claim(escrow_account: Mutable, recipient: MutableSigner);
+    Cancel => cancel(escrow_account: Mutable, sender: MutableSigner);
+}
+
+#[instrument(skip(context, sender, escrow_account))]
+fn lock(
+    context: &Context,
+    sender: MutableSigner,
+    escrow_account: Mutable,
+    recipient: Pubkey,
+    unlock_slot: u64,
+    cancel_deadline: u64,
+    amount: u64,
+) -> Result<()> {
+    debug!("locking prisms in escrow");
+    context.verify_derivation(
+        &escrow_account.key,
+        &escrow_seeds(&sender.key, &recipient, unlock_slot),
+    )?;
+    if !escrow_account.data().is_empty() {
+        return Err(Error::AccountAlreadyInitialized {
+            key: escrow_account.key,
+        });
+    }
+
+    let escrow = Escrow {
+        sender: sender.key,
+        recipient,
+        unlock_slot,
+        cancel_deadline,
+        amount,
+    };
+    sender.sub_prisms(amount)?;
+    escrow_account.add_prisms(amount)?;
+    escrow_account.set_data(borsh::to_vec(&escrow)?)?;
+    Ok(())
+}
+
+#[instrument(skip_all)]
+fn claim(context: &Context, escrow_account: Mutable, recipient: MutableSigner) -> Result<()> {
+    debug!("claiming escrow");
+    let escrow = get_escrow(&escrow_account)?;
+    verify_escrow(context, &escrow_account, &escrow)?;
+    if recipient.key != escrow.recipient {
+        return Err(Error::Failed(format!(
+            "{} is not the recipient of the escrow",
+            recipient.key
+        )));
+    }
+    if !escrow.is_unlocked(context.slot().get()) {
+        return Err(Error::EscrowLocked {
+            unlock_slot: escrow.unlock_slot,
+        });
+    }
+
+    release(&escrow_account, &recipient)
+}
+
+#[instrument(skip_all)]
+fn cancel(context: &Context, escrow_account: Mutable, sender: MutableSigner) -> Result<()> {
+    debug!("cancelling escrow");
+    let escrow = get_escrow(&escrow_account)?;
+    verify_escrow(context, &escrow_account, &escrow)?;
+    if sender.key != escrow.sender {
+        return Err(Error::Failed(format!(
+            "{} is not the sender of the escrow",
+            sender.key
+        )));
+    }
+    if !escrow.is_cancellable(context.slot().get()) {
+        return Err(Error::CancelDeadlinePassed {
+            deadline: escrow.cancel_deadline,
+        });
+    }
+
+    release(&escrow_account, &sender)
+}
+
+fn release(escrow_account: &TransactionAccount, to: &TransactionAccount) -> Result<()> {
+    let prisms = escrow_account.prisms();
+    escrow_account.sub_prisms(prisms)?;
+    to.add_prisms(prisms)?;
+    escrow_account.set_data(Vec::new())?;
+    escrow_account.close()?;
+    Ok(())
+}
+
+/// Checks that an escrow is held by the account derived from its own fields,
+/// so a look-alike account can't be passed instead.
+fn verify_escrow(context: &Context, account: &TransactionAccount, escrow: &Escrow) -> Result<()> {
+    context.verify_derivation(
+        &account.key,
+        &escrow_seeds(&escrow.sender, &escrow.recipient, escrow.unlock_slot),
+    )
+}
+
+fn get_escrow(account: &TransactionAccount) -> Result<Escrow> {
+    borsh::from_slice(&account.data())
+        .map_err(|_err| Error::InvalidAccountData { key: account.key })
+}
+
+/// Get the instructions for the escrow program.
+pub mod instruction {
+    use crate::{
+        account::{AccountMeta, Writable},
+        crypto::Pubkey,
+        transaction::Instruction,
+    };
+
+    use super::{escrow_address, EscrowInstruction, Result, ESCROW_PROGRAM};
+
+    /// Locks prisms until a given slot for a recipient.
+    ///
+    /// # Parameters
+    /// * `sender` - The account the prisms are taken from,
+    /// * `recipient` - The account allowed to claim the prisms,
+    /// * `unlock_slot` - The first slot at which the prisms can be claimed,
+    /// * `cancel_deadline` - The first slot at which the sender can no longer cancel,
+    /// * `amount` - The amount of prisms to lock.
+    ///
+    /// # Errors
+    /// If the sender is not on the `ed25519` curve or the escrow address can't be derived.
+    pub fn lock(
+        sender: Pubkey,
+        recipient: Pubkey,
+        unlock_slot: u64,
+        cancel_deadline: u64,
+        amount: u64,
+    ) -> Result<Instruction> {
+        let escrow = escrow_address(&sender, &recipient, unlock_slot)?;
+        let accounts = vec![
+            AccountMeta::signing(sender, Writable::Yes)?,
+            AccountMeta::derived(escrow, Writable::Yes)?,
+        ];
+        Ok(Instruction::new(
+            ESCROW_PROGRAM,
+            accounts,
+            &EscrowInstruction::Lock {
+                recipient,
+                unlock_slot,
+                cancel_deadline,
+                amount,
+            },
+        ))
+    }
+
+    /// Claims the prisms of an escrow once it's unlocked.
+    ///
+    /// # Parameters
+    /// * `escrow` - The escrow account,
+    /// * `recipient` - The recipient of the escrow.
+    ///
+    /// # Errors
+    /// If the escrow is on the `ed25519` curve or the recipient isn't.
+    pub fn claim(escrow: Pubkey, recipient: Pubkey) -> Result<Instruction> {
+        let accounts = vec![
+            AccountMeta::derived(escrow, Writable::Yes)?,
+            AccountMeta::signing(recipient, Writable::Yes)?,
+        ];
+        Ok(Instruction::new(
+            ESCROW_PROGRAM,
+            accounts,
+            &EscrowInstruction::Claim,
+        ))
+    }
+
+    /// Gives the prisms of an escrow back to its sender before the deadline.
+    ///
+    /// # Parameters
+    /// * `escrow` - The escrow account,
+    /// * `sender` - The sender of the escrow.
+    ///
+    /// # Errors
+    /// If the escrow is on the `ed25519` curve or the sender isn't.
+    pub fn cancel(escrow: Pubkey, sender: Pubkey) -> Result<Instruction> {
+        let accounts = vec![
+            AccountMeta::derived(escrow, Writable::Yes)?,
+            AccountMeta::signing(sender, Writable::Yes)?,
+        ];
+        Ok(Instruction::new(
+            ESCROW_PROGRAM,
+            accounts,
+            &EscrowInstruction::Cancel,
+        ))
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+
+    use std::assert_matches::assert_matches;
+
+    use test_log::test;
+
+    use crate::account::{AccountMeta, Wallet, Writable};
+    use crate::crypto::Keypair;
+
+    use super::*;
+    type TestResult = core::result::Result<(), Box<dyn core::error::Error>>;
+
+    const AMOUNT: u64 = 5_000;
+    const UNLOCK_SLOT: u64 = 100;
+    const DEADLINE: u64 = 50;
+
+    struct Setup {
+        sender: Pubkey,
+        recipient: Pubkey,
+        escrow: Pubkey,
+        sender_wallet: Wallet,
+        recipient_wallet: Wallet,
+        escrow_wallet: Wallet,
+    }
+
+    fn locked_escrow() -> core::result::Result<Setup, Box<dyn core::error::Error>> {
+        let sender = Keypair::generate().pubkey();
+        let recipient = Keypair::generate().pubkey();
+        let escrow = escrow_address(&sender, &recipient, UNLOCK_SLOT)?;
+        let mut sender_wallet = Wallet::new(AMOUNT * 2);
+        let mut escrow_wallet = Wallet::default();
+        {
+            let accounts = vec![
+                TransactionAccount::new(
+                    &AccountMeta::signing(sender, Writable::Yes)?,
+                    &mut sender_wallet,
+                ),
+                TransactionAccount::new(
+                    &AccountMeta::derived(escrow, Writable::Yes)?,
+                    &mut escrow_wallet,
+                ),
+            ];
+            execute_instruction(
+                &Context::default(),
+                &accounts,
+                instruction::lock(sender, recipient, UNLOCK_SLOT, DEADLINE, AMOUNT)?.data(),
+            )?;
+        }
+
+        Ok(Setup {
+            sender,
+            recipient,
+            escrow,
+            sender_wallet,
+            recipient_wallet: Wallet::default(),
+            escrow_wallet,
+        })
+    }
+
+    fn run_claim(setup: &mut Setup, slot: u64) -> Result<()> {
+        let escrow_meta = AccountMeta::derived(setup.escrow, Writable::Yes)?;
+        let recipient_meta = AccountMeta::signing(setup.recipient, Writable::Yes)?;
+        let accounts = vec![
+            TransactionAccount::new(&escrow_meta, &mut setup.escrow_wallet),
+            TransactionAccount::new(&recipient_meta, &mut setup.recipient_wallet),
+        ];
+        execute_instruction(
+            &Context::new(slot),
+            &accounts,
+            instruction::claim(setup.escrow, setup.recipient)?.data(),
+        )
+    }
+
+    fn run_cancel(setup: &mut Setup, slot: u64) -> Result<()> {
+        let escrow_meta = AccountMeta::derived(setup.escrow, Writable::Yes)?;
+        let sender_meta = AccountMeta::signing(setup.sender, Writable::Yes)?;
+        let accounts = vec![
+            TransactionAccount::new(&escrow_meta, &mut setup.escrow_wallet),
+            TransactionAccount::new(&sender_meta, &mut setup.sender_wallet),
+        ];
+        execute_instruction(
+            &Context::new(slot),
+            &accounts,
+            instruction::cancel(setup.escrow, setup.sender)?.data(),
+        )
+    }
+
+    #[test]
+    fn escrow_program_id() {
+        assert_eq!(
+            ESCROW_PROGRAM.to_string(),
+            "BifrostEscrowProgram11111111111111111111111"
+        );
+    }
+
+    #[test]
+    fn escrow_address_is_deterministic() -> TestResult {
+        // Given
+        let sender = Keypair::generate().pubkey();
+        let recipient = Keypair::generate().pubkey();
+
+        // When
+        let address = escrow_address(&sender, &recipient, UNLOCK_SLOT)?;
+
+        // Then
+        assert!(!address.is_oncurve());
+        assert_eq!(address, escrow_address(&sender, &recipient, UNLOCK_SLOT)?);
+        assert_ne!(
+            address,
+            escrow_address(&sender, &recipient, UNLOCK_SLOT + 1)?
+        );
+        assert_ne!(address, escrow_address(&recipient, &sender, UNLOCK_SLOT)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn lock_moves_prisms_into_escrow() -> TestResult {
+        // When
+        let setup = locked_escrow()?;
+
+        // Then
+        let escrow: Escrow = borsh::from_slice(&setup.escrow_wallet.data)?;
+        assert_eq!(setup.sender_wallet.prisms, AMOUNT);
+        assert_eq!(setup.escrow_wallet.prisms, AMOUNT);
+        assert_eq!(escrow.sender, setup.sender);
+        assert_eq!(escrow.recipient, setup.recipient);
+        assert_eq!(escrow.unlock_slot, UNLOCK_SLOT);
+        assert_eq!(escrow.cancel_deadline, DEADLINE);
+
+        Ok(())
+    }
+
+    #[test]
+    fn cannot_lock_into_an_underived_account() -> TestResult {
+        // Given
+        let sender = Keypair::generate().pubkey();
+        let recipient = Keypair::generate().pubkey();
+        let wrong = escrow_address(&sender, &recipient, UNLOCK_SLOT + 1)?;
+        let mut sender_wallet = Wallet::new(AMOUNT);
+        let mut escrow_wallet = Wallet::default();
+        let sender_meta = AccountMeta::signing(sender, Writable::Yes)?;
+        let escrow_meta = AccountMeta::derived(wrong, Writable::Yes)?;
+        let accounts = vec![
+            TransactionAccount::new(&sender_meta, &mut sender_wallet),
+            TransactionAccount::new(&escrow_meta, &mut escrow_wallet),
+        ];
+        let payload = borsh::to_vec(&EscrowInstruction::Lock {
+            recipient,
+            unlock_slot: UNLOCK_SLOT,
+            cancel_deadline: DEADLINE,
+            amount: AMOUNT,
+        })?;
+
+        // When
+        let res = execute_instruction(&Context::default(), &accounts, &payload);
+
+        // Then
+        let expected = escrow_address(&sender, &recipient, UNLOCK_SLOT)?;
+        assert_matches!(
+            res,
+            Err(Error::InvalidDerivedAddress { key, expected: derived })
+                if key == wrong && derived == expected
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn cannot_claim_from_a_look_alike_account() -> TestResult {
+        // Given
+        let mut setup = locked_escrow()?;
+        let expected = setup.escrow;
+        // same escrow data, but not held by the derived account
+        setup.escrow = escrow_address(&setup.sender, &setup.recipient, UNLOCK_SLOT + 1)?;
+
+        // When
+        let res = run_claim(&mut setup, UNLOCK_SLOT);
+
+        // Then
+        assert_matches!(
+            res,
+            Err(Error::InvalidDerivedAddress { key, expected: derived })
+                if key == setup.escrow && derived == expected
+        );
+        assert_eq!(setup.escrow_wallet.prisms, AMOUNT);
+        assert_eq!(setup.recipient_wallet.prisms, 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn cannot_claim_before_unlock_slot() -> TestResult {
+        // Given
+        let mut setup = locked_escrow()?;
+
+        // When
+        let res = run_claim(&mut setup, UNLOCK_SLOT - 1);
+
+        // Then
+        assert_matches!(
+            res,
+            Err(Error::EscrowLocked {
+                unlock_slot: UNLOCK_SLOT
+            })
+        );
+        assert_eq!(setup.escrow_wallet.prisms, AMOUNT);
+        assert_eq!(setup.recipient_wallet.prisms, 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn claim_exactly_at_unlock_slot() -> TestResult {
+        // Given
+        let mut setup = locked_escrow()?;
+
+        // When
+        run_claim(&mut setup, UNLOCK_SLOT)?;
+
+        // Then
+        assert_eq!(setup.escrow_wallet, Wallet::default());
+        assert_eq!(setup.recipient_wallet.prisms, AMOUNT);
+
+        Ok(())
+    }
+
+    #[test]
+    fn cannot_claim_twice() -> TestResult {
+        // Given
+        let mut setup = locked_escrow()?;
+        run_claim(&mut setup, UNLOCK_SLOT + 1)?;
+
+        // When
+        let res = run_claim(&mut setup, UNLOCK_SLOT + 2);
+
+        // Then
+        assert_matches!(res, Err(Error::InvalidAccountData { key }) if key == setup.escrow);
+        assert_eq!(setup.recipient_wallet.prisms, AMOUNT);
+
+        Ok(())
+    }
+
+    #[test]
+    fn only_the_recipient_can_claim() -> TestResult {
+        // Given
+        let mut setup = locked_escrow()?;
+        setup.recipient = Keypair::generate().pubkey();
+
+        // When
+        let res = run_claim(&mut setup, UNLOCK_SLOT);
+
+        // Then
+        assert_matches!(res, Err(Error::Failed(_)));
+        assert_eq!(setup.escrow_wallet.prisms, AMOUNT);
+
+        Ok(())
+    }
+
+    #[test]
+    fn cancel_before_deadline_refunds_sender() -> TestResult {
+        // Given
+        let mut setup = locked_escrow()?;
+
+        // When
+        run_cancel(&mut setup, DEADLINE - 1)?;
+
+        // Then
+        assert_eq!(setup.escrow_wallet, Wallet::default());
+        assert_eq!(setup.sender_wallet.prisms, AMOUNT * 2);
+        assert_matches!(
+            run_claim(&mut setup, UNLOCK_SLOT),
+            Err(Error::InvalidAccountData { .. })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn cannot_cancel_at_deadline() -> TestResult {
+        // Given
+        let mut setup = locked_escrow()?;
+
+        // When
+        let res = run_cancel(&mut setup, DEADLINE);
+
+        // Then
+        assert_matches!(res, Err(Error::CancelDeadlinePassed { deadline: DEADLINE }));
+        assert_eq!(setup.escrow_wallet.prisms, AMOUNT);
+        assert_eq!(setup.sender_wallet.prisms, AMOUNT);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,199 @@
+// File: src/program/memo.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 14:59:52
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use tracing::{debug, info, instrument};
+
+use crate::{account::TransactionAccount, crypto::Pubkey, pubkey};
+
+use super::{Error, Result};
+
+/// The Memo's program id (`BifrostMemoProgram11111111111111111111111115`)
+pub const MEMO_PROGRAM: Pubkey = pubkey!("BifrostMemoProgram11111111111111111111111115");
+
+/// Maximum length of a memo, in bytes.
+pub const MAX_MEMO_LENGTH: usize = 566;
+
+/// Maximum number of memos in a single transaction.
+pub const MAX_INVOCATIONS: usize = 4;
+
+/// Describes an instruction's payload in a human readable form.
+pub(crate) fn decode(payload: &[u8]) -> Option<String> {
+    let bytes: Vec<u8> = borsh::from_slice(payload).ok()?;
+    let memo = core::str::from_utf8(&bytes).ok()?;
+    Some(format!("Memo({memo:?})"))
+}
+
+/// Executes a memo program's instruction.
+///
+/// Every account given to the instruction must have signed the transaction.
+///
+/// # Parameters
+/// * `accounts` - The accounts vouching for the memo,
+/// * `payload` - The data payload for the instruction.
+///
+/// # Errors
+/// If the memo is too long, isn't valid UTF-8, or if one of the accounts didn't sign.
+#[instrument(skip_all)]
+pub fn execute_instruction(accounts: &[TransactionAccount], payload: &[u8]) -> Result<()> {
+    debug!("received memo instruction");
+    let bytes: Vec<u8> = borsh::from_slice(payload)?;
+    if bytes.len() > MAX_MEMO_LENGTH {
+        return Err(Error::MemoTooLong {
+            length: bytes.len(),
+        });
+    }
+    let memo = core::str::from_utf8(&bytes).map_err(|_err| Error::InvalidMemoEncoding)?;
+
+    if let Some(account) = accounts
+        .iter()
+        .find(|account| !account.privileges.is_signer())
+    {
+        return Err(Error::MissingMemoSigner { key: account.key });
+    }
+
+    info!(memo, "memo recorded");
+    Ok(())
+}
+
+/// Get the instructions for the memo program.
+pub mod instruction {
+    use crate::{
+        account::{AccountMeta, Writable},
+        crypto::Pubkey,
+        transaction::Instruction,
+    };
+
+    use super::{Result, MEMO_PROGRAM};
+
+    /// Attaches a memo to a transaction.
+    ///
+    /// # Parameters
+    /// * `memo` - The text of the memo,
+    /// * `signers` - The accounts that must sign the memo.
+    ///
+    /// # Errors
+    /// If any signer is not on the `ed25519` curve.
+    pub fn memo(memo: &str, signers: &[Pubkey]) -> Result<Instruction> {
+        let accounts = signers
+            .iter()
+            .map(|signer| AccountMeta::signing(*signer, Writable::No))
+            .collect::<core::result::Result<Vec<_>, _>>()?;
+        Ok(Instruction::new(
+            MEMO_PROGRAM,
+            accounts,
+            &memo.as_bytes().to_vec(),
+        ))
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+
+    use std::assert_matches::assert_matches;
+
+    use test_log::test;
+
+    use crate::account::{AccountMeta, Wallet, Writable};
+    use crate::crypto::Keypair;
+
+    use super::*;
+    type TestResult = core::result::Result<(), Box<dyn core::error::Error>>;
+
+    #[test]
+    fn memo_program_id() {
+        assert_eq!(
+            MEMO_PROGRAM.to_string(),
+            "BifrostMemoProgram11111111111111111111111115"
+        );
+    }
+
+    #[test]
+    fn signed_memo() -> TestResult {
+        // Given
+        let key = Keypair::generate().pubkey();
+        let meta = AccountMeta::signing(key, Writable::No)?;
+        let mut wallet = Wallet::new(0);
+        let accounts = vec![TransactionAccount::new(&meta, &mut wallet)];
+        let instruction = instruction::memo("invoice 1234", &[key])?;
+
+        // When
+        let res = execute_instruction(&accounts, instruction.data());
+
+        // Then
+        assert_matches!(res, Ok(()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn reject_invalid_utf8() -> TestResult {
+        // Given
+        let payload = borsh::to_vec(&vec![0xF0_u8, 0x28, 0x8C, 0x28])?;
+
+        // When
+        let res = execute_instruction(&[], &payload);
+
+        // Then
+        assert_matches!(res, Err(Error::InvalidMemoEncoding));
+
+        Ok(())
+    }
+
+    #[test]
+    fn reject_long_memo() -> TestResult {
+        // Given
+        let instruction = instruction::memo(&"a".repeat(MAX_MEMO_LENGTH + 1), &[])?;
+
+        // When
+        let res = execute_instruction(&[], instruction.data());
+
+        // Then
+        assert_matches!(res, Err(Error::MemoTooLong { length }) if length == MAX_MEMO_LENGTH + 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn reject_unsigned_memo() -> TestResult {
+        // Given
+        let key = Keypair::generate().pubkey();
+        let meta = AccountMeta::wallet(key, Writable::No)?;
+        let mut wallet = Wallet::new(0);
+        let accounts = vec![TransactionAccount::new(&meta, &mut wallet)];
+        let instruction = instruction::memo("invoice 1234", &[])?;
+
+        // When
+        let res = execute_instruction(&accounts, instruction.data());
+
+        // Then
+        assert_matches!(res, Err(Error::MissingMemoSigner { key: missing }) if missing == key);
+
+        Ok(())
+    }
+}
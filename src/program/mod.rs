@@ -3,7 +3,7 @@
 // Creation date: Wednesday 12 February 2025
 // Author: Vincent Berthier <vincent.berthier@posteo.org>
 // -----
-// Last modified: Wednesday 12 February 2025 @ 22:13:39
+// Last modified: Thursday 15 October 2026 @ 16:49:53
 // Modified by: Vincent Berthier
 // -----
 // Copyright (c) 2025 <Vincent Berthier>
@@ -28,12 +28,28 @@
 
 /// The instruction dispatcher
 pub mod dispatcher;
+/// The entrypoint of the native programs
+pub mod entrypoint;
+/// The escrow program
+pub mod escrow;
+/// The memo program
+pub mod memo;
+/// Rent exemption of the accounts
+pub mod rent;
+/// Human readable descriptions of instructions
+pub mod schema;
+/// The stake program
+pub mod stake;
 /// The system program
 pub mod system;
 /// A dummy program for testing only
 pub mod testing_dummy;
 
+mod clock;
+mod context;
 mod error;
 
-pub use error::Error;
+pub use clock::{Epoch, Slot};
+pub use context::{Context, SLOTS_PER_EPOCH};
+pub use error::{Error, CUSTOM_ERROR_CODES};
 type Result<T> = core::result::Result<T, Error>;
@@ -0,0 +1,51 @@
+// File: src/program/rent.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 11:41:13
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+/// Prisms needed per byte stored on an account to be rent exempt.
+pub const PRISMS_PER_BYTE: u64 = 10;
+
+/// Bytes accounted for every account on top of its data.
+pub const ACCOUNT_STORAGE_OVERHEAD: u64 = 128;
+
+/// Maximum size of the data of an account.
+pub const MAX_ACCOUNT_DATA_SIZE: usize = 10 * 1024;
+
+/// Get the minimum balance for an account to be rent exempt.
+///
+/// # Parameters
+/// * `data_len` - The size of the data stored on the account.
+///
+/// # Example
+/// ```rust
+/// # use bifrost::program::rent::{minimum_balance, ACCOUNT_STORAGE_OVERHEAD, PRISMS_PER_BYTE};
+/// assert_eq!(minimum_balance(0), ACCOUNT_STORAGE_OVERHEAD * PRISMS_PER_BYTE);
+/// ```
+#[must_use]
+pub const fn minimum_balance(data_len: usize) -> u64 {
+    (ACCOUNT_STORAGE_OVERHEAD + data_len as u64) * PRISMS_PER_BYTE
+}
@@ -0,0 +1,222 @@
+// File: src/program/schema.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 20:31:20
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::{collections::HashMap, fmt::Write as _};
+
+use crate::crypto::Pubkey;
+
+use super::{
+    escrow::{self, ESCROW_PROGRAM},
+    memo::{self, MEMO_PROGRAM},
+    stake::{self, STAKE_PROGRAM},
+    system::{self, SYSTEM_PROGRAM},
+};
+
+/// Describes an instruction's payload in a human readable form,
+/// or returns `None` if the payload can't be decoded.
+pub type Decoder = fn(&[u8]) -> Option<String>;
+
+/// Names a program's custom error code, or returns `None` if the code is unknown.
+pub type ErrorNames = fn(u32) -> Option<&'static str>;
+
+/// The decoders of the instructions of each known program, and the names of their custom errors.
+#[derive(Clone, Debug)]
+pub struct SchemaRegistry {
+    decoders: HashMap<Pubkey, Decoder>,
+    errors: HashMap<Pubkey, ErrorNames>,
+}
+
+impl Default for SchemaRegistry {
+    /// A registry knowing the built-in programs.
+    fn default() -> Self {
+        let mut registry = Self {
+            decoders: HashMap::new(),
+            errors: HashMap::new(),
+        };
+        registry.register(ESCROW_PROGRAM, escrow::decode);
+        registry.register(MEMO_PROGRAM, memo::decode);
+        registry.register(STAKE_PROGRAM, stake::decode);
+        registry.register(SYSTEM_PROGRAM, system::decode);
+
+        registry
+    }
+}
+
+impl SchemaRegistry {
+    /// Registers the decoder of a program's instructions, replacing any previous one.
+    ///
+    /// # Parameters
+    /// * `program` - The program whose instructions are decoded,
+    /// * `decoder` - The decoder of the instructions.
+    pub fn register(&mut self, program: Pubkey, decoder: Decoder) {
+        self.decoders.insert(program, decoder);
+    }
+
+    /// Describes an instruction's payload.
+    ///
+    /// Payloads of unknown programs, or that their decoder rejects, are given in hexadecimal.
+    ///
+    /// # Parameters
+    /// * `program` - The program executing the instruction,
+    /// * `payload` - The payload of the instruction.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use bifrost::{crypto::Pubkey, program::schema::SchemaRegistry};
+    /// let registry = SchemaRegistry::default();
+    /// let description = registry.decode(&Pubkey::from_bytes(&[2; 32]), &[0xca, 0xfe]);
+    /// assert_eq!(description, "0xcafe");
+    /// ```
+    #[must_use]
+    pub fn decode(&self, program: &Pubkey, payload: &[u8]) -> String {
+        self.decoders
+            .get(program)
+            .and_then(|decoder| decoder(payload))
+            .unwrap_or_else(|| to_hex(payload))
+    }
+
+    /// Registers the names of a program's custom error codes, replacing any previous ones.
+    ///
+    /// # Parameters
+    /// * `program` - The program returning the errors,
+    /// * `names` - The names of the codes.
+    pub fn register_errors(&mut self, program: Pubkey, names: ErrorNames) {
+        self.errors.insert(program, names);
+    }
+
+    /// Describes a custom error returned by a program.
+    ///
+    /// # Parameters
+    /// * `program` - The program that failed,
+    /// * `code` - The program's own error code.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use bifrost::{crypto::Pubkey, program::schema::SchemaRegistry};
+    /// let program = Pubkey::from_bytes(&[2; 32]);
+    /// let mut registry = SchemaRegistry::default();
+    /// assert_eq!(registry.explain_error(&program, 42), "custom error 42");
+    /// registry.register_errors(program, |code| (code == 42).then_some("NotEnoughCollateral"));
+    /// assert_eq!(registry.explain_error(&program, 42), "custom error 42 (NotEnoughCollateral)");
+    /// ```
+    #[must_use]
+    pub fn explain_error(&self, program: &Pubkey, code: u32) -> String {
+        self.errors
+            .get(program)
+            .and_then(|names| names(code))
+            .map_or_else(
+                || format!("custom error {code}"),
+                |name| format!("custom error {code} ({name})"),
+            )
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::from("0x"), |mut res, byte| {
+        write!(res, "{byte:02x}").unwrap();
+        res
+    })
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use test_log::test;
+
+    use crate::crypto::Keypair;
+    use crate::transaction::Transaction;
+
+    use super::*;
+    type TestResult = core::result::Result<(), Box<dyn core::error::Error>>;
+
+    const PROGRAM: Pubkey = Pubkey::from_bytes(&[2; 32]);
+
+    fn explain(trx: &Transaction, registry: &SchemaRegistry) -> Vec<String> {
+        trx.explain(registry)
+            .into_iter()
+            .map(|(_program, description)| description)
+            .collect()
+    }
+
+    #[test]
+    fn decode_built_in_programs() -> TestResult {
+        // Given
+        let payer = Keypair::from_seed(&[1; 32]).pubkey();
+        let receiver = Keypair::from_seed(&[2; 32]).pubkey();
+        let mut trx = Transaction::new(0);
+        trx.add(&[
+            system::instruction::transfer(payer, receiver, 1_000)?,
+            memo::instruction::memo("thanks", &[payer])?,
+            stake::instruction::deactivate(stake::stake_address(&payer, 0)?, payer, 50)?,
+            escrow::instruction::claim(escrow::escrow_address(&payer, &receiver, 10)?, receiver)?,
+        ])?;
+
+        // When
+        let descriptions = explain(&trx, &SchemaRegistry::default());
+
+        // Then
+        assert_eq!(
+            descriptions,
+            vec![
+                "Transfer(1000)".to_owned(),
+                "Memo(\"thanks\")".to_owned(),
+                "Deactivate { amount: 50 }".to_owned(),
+                "Claim".to_owned(),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_programs_fall_back_to_hex() -> TestResult {
+        // Given
+        let payer = Keypair::generate().pubkey();
+        let mut trx = Transaction::new(0);
+        trx.add(&[crate::transaction::Instruction::new(
+            PROGRAM,
+            vec![crate::account::AccountMeta::signing(
+                payer,
+                crate::account::Writable::Yes,
+            )?],
+            &[0xde_u8, 0xad],
+        )])?;
+        let mut registry = SchemaRegistry::default();
+
+        // When
+        let unknown = explain(&trx, &registry);
+        registry.register(PROGRAM, |payload| Some(format!("{} bytes", payload.len())));
+        let registered = explain(&trx, &registry);
+
+        // Then
+        assert_eq!(unknown, vec!["0xdead".to_owned()]);
+        assert_eq!(registered, vec!["2 bytes".to_owned()]);
+
+        Ok(())
+    }
+}
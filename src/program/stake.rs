@@ -0,0 +1,560 @@
+// File: src/program/stake.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 20:31:20
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use tracing::{debug, instrument, warn};
+
+use crate::{
+    account::{Stake, TransactionAccount},
+    crypto::{Pubkey, Seeds},
+    declare_program, pubkey,
+};
+
+use super::{
+    entrypoint::{Mutable, MutableSigner, Signer},
+    Context, Error, Result,
+};
+
+/// The Stake's program id (`BifrostStakeProgram111111111111111111111111`)
+pub const STAKE_PROGRAM: Pubkey = pubkey!("BifrostStakeProgram111111111111111111111111");
+
+const STAKE_SEED: &[u8] = b"stake";
+
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+enum StakeInstruction {
+    CreateStake { index: u64, amount: u64 },
+    Delegate { validator_identity: Pubkey },
+    Deactivate { amount: u64 },
+    Withdraw { amount: u64 },
+}
+
+/// Describes an instruction's payload in a human readable form.
+pub(crate) fn decode(payload: &[u8]) -> Option<String> {
+    let instruction: StakeInstruction = borsh::from_slice(payload).ok()?;
+    Some(format!("{instruction:?}"))
+}
+
+/// Derives the address of a stake account.
+///
+/// The account is off-curve: it can't sign, so its prisms can only be moved by the
+/// stake program.
+///
+/// # Parameters
+/// * `staker` - The account managing the stake,
+/// * `index` - The index of the stake among those of the staker.
+///
+/// # Errors
+/// If no off-curve key could be derived from those seeds.
+///
+/// # Example
+/// ```rust
+/// # use bifrost::crypto::Keypair;
+/// # use bifrost::program::{stake::stake_address, Error};
+/// let staker = Keypair::generate().pubkey();
+/// let stake = stake_address(&staker, 0)?;
+/// assert!(!stake.is_oncurve());
+/// assert_ne!(stake, stake_address(&staker, 1)?);
+///
+/// # Ok::<(), Error>(())
+/// ```
+pub fn stake_address(staker: &Pubkey, index: u64) -> Result<Pubkey> {
+    Ok(Seeds::new(&stake_seeds(staker, index))?
+        .generate_offcurve()?
+        .0)
+}
+
+/// The seeds of a stake account.
+#[expect(clippy::little_endian_bytes)]
+fn stake_seeds(staker: &Pubkey, index: u64) -> [Vec<u8>; 4] {
+    [
+        STAKE_SEED.to_vec(),
+        STAKE_PROGRAM.as_ref().to_vec(),
+        staker.as_ref().to_vec(),
+        index.to_le_bytes().to_vec(),
+    ]
+}
+
+declare_program! {
+    /// Executes a stake program's instruction.
+    ///
+    /// # Parameters
+    /// * `context` - The context of the execution,
+    /// * `accounts` - The accounts needed by the instruction,
+    /// * `payload` - The data payload for the instruction.
+    ///
+    /// # Errors
+    /// if the instruction fails to complete (missing accounts, arithmetic overflows, *etc.*).
+    #[instrument(skip_all)]
+    StakeInstruction;
+    CreateStake { index, amount } => create_stake(staker: MutableSigner, stake_account: Mutable);
+    Delegate { validator_identity } => delegate(stake_account: Mutable, staker: Signer);
+    Deactivate { amount } => deactivate(stake_account: Mutable, staker: Signer);
+    Withdraw { amount } => withdraw(stake_account: Mutable, staker: MutableSigner);
+}
+
+#[instrument(skip(context, staker, stake_account))]
+fn create_stake(
+    context: &Context,
+    staker: MutableSigner,
+    stake_account: Mutable,
+    index: u64,
+    amount: u64,
+) -> Result<()> {
+    debug!("creating stake account");
+    context.verify_derivation(&stake_account.key, &stake_seeds(&staker.key, index))?;
+    if !stake_account.data().is_empty() {
+        return Err(Error::AccountAlreadyInitialized {
+            key: stake_account.key,
+        });
+    }
+
+    staker.sub_prisms(amount)?;
+    stake_account.add_prisms(amount)?;
+    stake_account.set_data(borsh::to_vec(&Stake::new(staker.key, index, amount))?)?;
+    Ok(())
+}
+
+#[instrument(skip(context, stake_account, staker))]
+fn delegate(
+    context: &Context,
+    stake_account: Mutable,
+    staker: Signer,
+    validator_identity: Pubkey,
+) -> Result<()> {
+    debug!("delegating stake");
+    let mut stake = get_stake(context, &stake_account, &staker)?;
+    stake.delegate(validator_identity, context.epoch());
+    stake_account.set_data(borsh::to_vec(&stake)?)?;
+    Ok(())
+}
+
+#[instrument(skip(context, stake_account, staker))]
+fn deactivate(
+    context: &Context,
+    stake_account: Mutable,
+    staker: Signer,
+    amount: u64,
+) -> Result<()> {
+    debug!("deactivating stake");
+    let mut stake = get_stake(context, &stake_account, &staker)?;
+    stake.deactivate(amount, context.epoch())?;
+    stake_account.set_data(borsh::to_vec(&stake)?)?;
+    Ok(())
+}
+
+#[instrument(skip(context, stake_account, staker))]
+fn withdraw(
+    context: &Context,
+    stake_account: Mutable,
+    staker: MutableSigner,
+    amount: u64,
+) -> Result<()> {
+    debug!("withdrawing stake");
+    let mut stake = get_stake(context, &stake_account, &staker)?;
+    stake.withdraw(amount, context.epoch())?;
+    stake_account.sub_prisms(amount)?;
+    staker.add_prisms(amount)?;
+    if stake.amount == 0 {
+        debug!("the whole stake was withdrawn, closing the account");
+        stake_account.set_data(Vec::new())?;
+        return Ok(stake_account.close()?);
+    }
+    stake_account.set_data(borsh::to_vec(&stake)?)?;
+    Ok(())
+}
+
+/// Reads the stake held by an account, checking it's managed by the given staker
+/// and held at the address derived from its own fields (so a look-alike account
+/// can't be passed instead).
+fn get_stake(
+    context: &Context,
+    account: &TransactionAccount,
+    staker: &TransactionAccount,
+) -> Result<Stake> {
+    let stake: Stake = borsh::from_slice(&account.data())
+        .map_err(|_err| Error::InvalidAccountData { key: account.key })?;
+    context.verify_derivation(&account.key, &stake_seeds(&stake.staker, stake.index))?;
+    if staker.key != stake.staker {
+        warn!(staker = %stake.staker, "the stake is managed by another account");
+        return Err(Error::Failed(format!(
+            "{} is not the staker of the stake",
+            staker.key
+        )));
+    }
+
+    Ok(stake)
+}
+
+/// Get the instructions for the stake program.
+pub mod instruction {
+    use crate::{
+        account::{AccountMeta, Writable},
+        crypto::Pubkey,
+        transaction::Instruction,
+    };
+
+    use super::{stake_address, Result, StakeInstruction, STAKE_PROGRAM};
+
+    /// Creates a stake account funded by its staker.
+    ///
+    /// # Parameters
+    /// * `staker` - The account the staked prisms are taken from, which manages the stake,
+    /// * `index` - The index of the stake among those of the staker,
+    /// * `amount` - The amount of prisms to stake.
+    ///
+    /// # Errors
+    /// If the staker is not on the `ed25519` curve or the stake address can't be derived.
+    pub fn create_stake(staker: Pubkey, index: u64, amount: u64) -> Result<Instruction> {
+        let stake = stake_address(&staker, index)?;
+        let accounts = vec![
+            AccountMeta::signing(staker, Writable::Yes)?,
+            AccountMeta::derived(stake, Writable::Yes)?,
+        ];
+        Ok(Instruction::new(
+            STAKE_PROGRAM,
+            accounts,
+            &StakeInstruction::CreateStake { index, amount },
+        ))
+    }
+
+    /// Delegates a stake to a validator.
+    ///
+    /// # Parameters
+    /// * `stake` - The stake account,
+    /// * `staker` - The staker managing it,
+    /// * `validator_identity` - The identity of the validator.
+    ///
+    /// # Errors
+    /// If the stake account is on the `ed25519` curve or the staker isn't.
+    pub fn delegate(
+        stake: Pubkey,
+        staker: Pubkey,
+        validator_identity: Pubkey,
+    ) -> Result<Instruction> {
+        let accounts = vec![
+            AccountMeta::derived(stake, Writable::Yes)?,
+            AccountMeta::signing(staker, Writable::No)?,
+        ];
+        Ok(Instruction::new(
+            STAKE_PROGRAM,
+            accounts,
+            &StakeInstruction::Delegate { validator_identity },
+        ))
+    }
+
+    /// Deactivates part of a stake.
+    ///
+    /// # Parameters
+    /// * `stake` - The stake account,
+    /// * `staker` - The staker managing it,
+    /// * `amount` - The amount of prisms to deactivate.
+    ///
+    /// # Errors
+    /// If the stake account is on the `ed25519` curve or the staker isn't.
+    pub fn deactivate(stake: Pubkey, staker: Pubkey, amount: u64) -> Result<Instruction> {
+        let accounts = vec![
+            AccountMeta::derived(stake, Writable::Yes)?,
+            AccountMeta::signing(staker, Writable::No)?,
+        ];
+        Ok(Instruction::new(
+            STAKE_PROGRAM,
+            accounts,
+            &StakeInstruction::Deactivate { amount },
+        ))
+    }
+
+    /// Pays deactivated stake back to its staker.
+    ///
+    /// Only the prisms whose deactivation took effect (in a previous epoch) can be
+    /// withdrawn. The stake account is closed once all its prisms are withdrawn.
+    ///
+    /// # Parameters
+    /// * `stake` - The stake account,
+    /// * `staker` - The staker managing it, receiving the prisms,
+    /// * `amount` - The amount of prisms to withdraw.
+    ///
+    /// # Errors
+    /// If the stake account is on the `ed25519` curve or the staker isn't.
+    pub fn withdraw(stake: Pubkey, staker: Pubkey, amount: u64) -> Result<Instruction> {
+        let accounts = vec![
+            AccountMeta::derived(stake, Writable::Yes)?,
+            AccountMeta::signing(staker, Writable::Yes)?,
+        ];
+        Ok(Instruction::new(
+            STAKE_PROGRAM,
+            accounts,
+            &StakeInstruction::Withdraw { amount },
+        ))
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+
+    use std::assert_matches::assert_matches;
+
+    use test_log::test;
+
+    use crate::account::{AccountMeta, Error as AccountError, Wallet, Writable};
+    use crate::crypto::Keypair;
+    use crate::program::SLOTS_PER_EPOCH;
+
+    use super::*;
+    type TestResult = core::result::Result<(), Box<dyn core::error::Error>>;
+
+    const AMOUNT: u64 = 10_000;
+
+    struct Setup {
+        staker: Pubkey,
+        stake: Pubkey,
+        staker_meta: AccountMeta,
+        stake_meta: AccountMeta,
+        staker_wallet: Wallet,
+        stake_wallet: Wallet,
+    }
+
+    impl Setup {
+        /// A staker holding `AMOUNT` prisms, and an empty stake account.
+        fn new() -> core::result::Result<Self, Box<dyn core::error::Error>> {
+            let staker = Keypair::generate().pubkey();
+            let stake = stake_address(&staker, 0)?;
+            Ok(Self {
+                staker,
+                stake,
+                staker_meta: AccountMeta::signing(staker, Writable::Yes)?,
+                stake_meta: AccountMeta::derived(stake, Writable::Yes)?,
+                staker_wallet: Wallet::new(AMOUNT),
+                stake_wallet: Wallet::new(0),
+            })
+        }
+
+        /// A staker, and a stake of `amount` prisms delegated at the first epoch.
+        fn delegated(amount: u64) -> core::result::Result<Self, Box<dyn core::error::Error>> {
+            let mut setup = Self::new()?;
+            let mut stake = Stake::new(setup.staker, 0, amount);
+            stake.delegate(Keypair::generate().pubkey(), 0);
+            setup.stake_wallet = Wallet::new(amount);
+            setup.stake_wallet.data = borsh::to_vec(&stake)?.into();
+            Ok(setup)
+        }
+
+        fn execute(&mut self, slot: u64, payload: &[u8]) -> Result<()> {
+            let accounts = [
+                TransactionAccount::new(&self.stake_meta, &mut self.stake_wallet),
+                TransactionAccount::new(&self.staker_meta, &mut self.staker_wallet),
+            ];
+            execute_instruction(&Context::new(slot), &accounts, payload)
+        }
+    }
+
+    #[test]
+    fn stake_program_id() {
+        assert_eq!(
+            STAKE_PROGRAM.to_string(),
+            "BifrostStakeProgram111111111111111111111111"
+        );
+    }
+
+    #[test]
+    fn delegated_stake_activates_over_epoch_boundary() -> TestResult {
+        // Given
+        let mut setup = Setup::new()?;
+        let validator = Keypair::generate().pubkey();
+        let context = Context::new(SLOTS_PER_EPOCH * 2 + 10);
+
+        // When
+        {
+            let accounts = [
+                TransactionAccount::new(&setup.staker_meta, &mut setup.staker_wallet),
+                TransactionAccount::new(&setup.stake_meta, &mut setup.stake_wallet),
+            ];
+            execute_instruction(
+                &context,
+                &accounts,
+                instruction::create_stake(setup.staker, 0, 6_000)?.data(),
+            )?;
+            execute_instruction(
+                &context,
+                &[accounts[1].clone(), accounts[0].clone()],
+                instruction::delegate(setup.stake, setup.staker, validator)?.data(),
+            )?;
+        }
+
+        // Then
+        let stake: Stake = borsh::from_slice(&setup.stake_wallet.data)?;
+        assert_eq!(setup.staker_wallet.prisms, AMOUNT - 6_000);
+        assert_eq!(setup.stake_wallet.prisms, 6_000);
+        assert_eq!(stake.staker, setup.staker);
+        assert_eq!(stake.validator, Some(validator));
+        assert_eq!(stake.active(2), 0);
+        assert_eq!(stake.active(3), 6_000);
+
+        Ok(())
+    }
+
+    #[test]
+    fn stake_is_created_at_its_derived_address() -> TestResult {
+        // Given
+        let mut setup = Setup::new()?;
+        let other = stake_address(&setup.staker, 1)?;
+        let other_meta = AccountMeta::derived(other, Writable::Yes)?;
+        let mut other_wallet = Wallet::new(0);
+        let instruction = instruction::create_stake(setup.staker, 0, 1_000)?;
+
+        // When
+        let res = {
+            let accounts = [
+                TransactionAccount::new(&setup.staker_meta, &mut setup.staker_wallet),
+                TransactionAccount::new(&other_meta, &mut other_wallet),
+            ];
+            execute_instruction(&Context::default(), &accounts, instruction.data())
+        };
+
+        // Then
+        assert_matches!(res, Err(Error::InvalidDerivedAddress { key, .. }) if key == other);
+        assert_eq!(instruction.accounts()[1].key(), &setup.stake);
+        assert_eq!(setup.staker_wallet.prisms, AMOUNT);
+
+        Ok(())
+    }
+
+    #[test]
+    fn cannot_deactivate_more_than_staked() -> TestResult {
+        // Given
+        let mut setup = Setup::delegated(1_000)?;
+
+        // When
+        let res = setup.execute(
+            0,
+            instruction::deactivate(setup.stake, setup.staker, 1_001)?.data(),
+        );
+
+        // Then
+        assert_matches!(
+            res,
+            Err(Error::Account(AccountError::InsufficientStake {
+                requested: 1_001,
+                staked: 1_000
+            }))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn only_the_staker_manages_the_stake() -> TestResult {
+        // Given
+        let mut setup = Setup::delegated(1_000)?;
+        let thief = Keypair::generate().pubkey();
+        setup.staker_meta = AccountMeta::signing(thief, Writable::Yes)?;
+
+        // When
+        let deactivated = setup.execute(
+            0,
+            instruction::deactivate(setup.stake, thief, 1_000)?.data(),
+        );
+        let withdrawn = setup.execute(
+            SLOTS_PER_EPOCH,
+            instruction::withdraw(setup.stake, thief, 1_000)?.data(),
+        );
+
+        // Then
+        assert_matches!(deactivated, Err(Error::Failed(_)));
+        assert_matches!(withdrawn, Err(Error::Failed(_)));
+        assert_eq!(setup.stake_wallet.prisms, 1_000);
+
+        Ok(())
+    }
+
+    #[test]
+    fn look_alike_stake_is_refused() -> TestResult {
+        // Given
+        let mut setup = Setup::delegated(1_000)?;
+        let fake = Keypair::generate().pubkey();
+        setup.stake_meta = AccountMeta::wallet(fake, Writable::Yes)?;
+
+        // When
+        let res = setup.execute(
+            SLOTS_PER_EPOCH,
+            instruction::withdraw(setup.stake, setup.staker, 1_000)?.data(),
+        );
+
+        // Then
+        assert_matches!(res, Err(Error::InvalidDerivedAddress { key, .. }) if key == fake);
+        assert_eq!(setup.staker_wallet.prisms, AMOUNT);
+
+        Ok(())
+    }
+
+    #[test]
+    fn only_deactivated_stake_is_withdrawn() -> TestResult {
+        // Given
+        let mut setup = Setup::delegated(1_000)?;
+        let deactivate = instruction::deactivate(setup.stake, setup.staker, 400)?;
+        let withdraw = instruction::withdraw(setup.stake, setup.staker, 400)?;
+
+        // When
+        setup.execute(SLOTS_PER_EPOCH * 2, deactivate.data())?;
+        let early = setup.execute(SLOTS_PER_EPOCH * 2 + 1, withdraw.data());
+        setup.execute(SLOTS_PER_EPOCH * 3, withdraw.data())?;
+
+        // Then
+        assert_matches!(
+            early,
+            Err(Error::Account(AccountError::InsufficientDeactivatedStake {
+                requested: 400,
+                deactivated: 0
+            }))
+        );
+        let stake: Stake = borsh::from_slice(&setup.stake_wallet.data)?;
+        assert_eq!(stake.amount, 600);
+        assert_eq!(setup.stake_wallet.prisms, 600);
+        assert_eq!(setup.staker_wallet.prisms, AMOUNT + 400);
+
+        Ok(())
+    }
+
+    #[test]
+    fn withdrawing_the_whole_stake_closes_the_account() -> TestResult {
+        // Given
+        let mut setup = Setup::delegated(1_000)?;
+        let deactivate = instruction::deactivate(setup.stake, setup.staker, 1_000)?;
+        let withdraw = instruction::withdraw(setup.stake, setup.staker, 1_000)?;
+
+        // When
+        setup.execute(0, deactivate.data())?;
+        setup.execute(SLOTS_PER_EPOCH, withdraw.data())?;
+
+        // Then
+        assert_eq!(setup.stake_wallet, Wallet::default());
+        assert_eq!(setup.staker_wallet.prisms, AMOUNT + 1_000);
+
+        Ok(())
+    }
+}
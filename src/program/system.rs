@@ -3,7 +3,7 @@
 // Creation date: Wednesday 12 February 2025
 // Author: Vincent Berthier <vincent.berthier@posteo.org>
 // -----
-// Last modified: Thursday 13 February 2025 @ 09:48:37
+// Last modified: Thursday 15 October 2026 @ 20:18:16
 // Modified by: Vincent Berthier
 // -----
 // Copyright (c) 2025 <Vincent Berthier>
@@ -27,39 +27,86 @@
 // SOFTWARE.
 
 use borsh::{BorshDeserialize, BorshSerialize};
-use tracing::{debug, instrument};
+use tracing::{debug, instrument, warn};
 
 use crate::{
-    account::{next_account, TransactionAccount},
+    account::{next_account, Delegation, TransactionAccount},
     crypto::Pubkey,
+    pubkey,
 };
 
-use super::{Error, Result};
+use super::{
+    rent::{minimum_balance, MAX_ACCOUNT_DATA_SIZE},
+    Context, Error, Result,
+};
 
-/// The System's program id (`BifrostSystemProgram111111111111111111111111`)
-pub const SYSTEM_PROGRAM: Pubkey = Pubkey::from_bytes(&[
-    2, 190, 236, 171, 26, 147, 23, 185, 158, 168, 176, 152, 117, 167, 48, 232, 60, 78, 120, 154,
-    96, 248, 193, 153, 0, 203, 246, 209, 37, 0, 0, 0,
-]);
+/// The System's program id (`BifrostSystemProgram11111111111111111111111`)
+pub const SYSTEM_PROGRAM: Pubkey = pubkey!("BifrostSystemProgram11111111111111111111111");
 
 #[derive(Debug, BorshSerialize, BorshDeserialize)]
 enum SystemInstruction {
     Transfer(u64),
+    Approve {
+        delegate: Pubkey,
+        limit: u64,
+        recurring: bool,
+    },
+    Revoke,
+    DelegatedTransfer(u64),
+    Realloc {
+        new_size: u32,
+    },
+    CloseAccount {
+        beneficiary: Pubkey,
+    },
+}
+
+/// Describes an instruction's payload in a human readable form.
+pub(crate) fn decode(payload: &[u8]) -> Option<String> {
+    let instruction: SystemInstruction = borsh::from_slice(payload).ok()?;
+    Some(format!("{instruction:?}"))
 }
 
 /// Executes a system program's instruction.
 ///
 /// # Parameters
+/// * `context` - The context of the execution,
 /// * `accounts` - The accounts needed by the instruction,
 /// * `payload` - The data payload for the instruction.
 ///
 /// # Errors
 /// if the instruction fails to complete (missing accounts, arithmetic overflows, *etc.*).
 #[instrument(skip_all)]
-pub fn execute_instruction(accounts: &[TransactionAccount], payload: &[u8]) -> Result<()> {
+pub fn execute_instruction(
+    context: &Context,
+    accounts: &[TransactionAccount],
+    payload: &[u8],
+) -> Result<()> {
     debug!("received system insruction");
     match borsh::from_slice(payload)? {
         SystemInstruction::Transfer(amount) => transfer(accounts, amount),
+        SystemInstruction::Approve {
+            delegate,
+            limit,
+            recurring,
+        } => approve(context, accounts, delegate, limit, recurring),
+        SystemInstruction::Revoke => revoke(accounts),
+        SystemInstruction::DelegatedTransfer(amount) => {
+            delegated_transfer(context, accounts, amount)
+        }
+        SystemInstruction::Realloc { new_size } => realloc(accounts, new_size as usize),
+        SystemInstruction::CloseAccount { beneficiary } => close_account(accounts, beneficiary),
+    }
+}
+
+/// Get the amount of a plain transfer instruction's payload.
+///
+/// # Returns
+/// `None` if the payload is not a transfer.
+pub(crate) fn transfer_amount(payload: &[u8]) -> Option<u64> {
+    match borsh::from_slice(payload).ok()? {
+        SystemInstruction::Transfer(amount) => Some(amount),
+        _ => None,
     }
 }
 
@@ -69,18 +116,157 @@ fn transfer(accounts: &[TransactionAccount], amount: u64) -> Result<()> {
     let mut accounts_iter = accounts.iter();
     let payer = next_account(&mut accounts_iter)?;
     let receiver = next_account(&mut accounts_iter)?;
-    if !payer.is_signer {
-        return Err(Error::Custom(format!(
-            "{} must be a signing account",
-            payer.key
-        )));
-    }
+    check_signer(payer)?;
     debug!("from {} to {}", payer.key, receiver.key);
     payer.sub_prisms(amount)?;
     receiver.add_prisms(amount)?;
     Ok(())
 }
 
+#[instrument(skip(context, accounts))]
+fn approve(
+    context: &Context,
+    accounts: &[TransactionAccount],
+    delegate: Pubkey,
+    limit: u64,
+    recurring: bool,
+) -> Result<()> {
+    debug!("approving delegate");
+    let mut accounts_iter = accounts.iter();
+    let owner = next_account(&mut accounts_iter)?;
+    check_signer(owner)?;
+    current_delegation(owner)?;
+    let delegation = Delegation::new(delegate, limit, recurring, context.epoch());
+    owner.set_data(borsh::to_vec(&delegation)?)?;
+    Ok(())
+}
+
+#[instrument(skip_all)]
+fn revoke(accounts: &[TransactionAccount]) -> Result<()> {
+    debug!("revoking delegate");
+    let mut accounts_iter = accounts.iter();
+    let owner = next_account(&mut accounts_iter)?;
+    check_signer(owner)?;
+    current_delegation(owner)?;
+    owner.set_data(Vec::new())?;
+    Ok(())
+}
+
+#[instrument(skip(context, accounts))]
+fn delegated_transfer(
+    context: &Context,
+    accounts: &[TransactionAccount],
+    amount: u64,
+) -> Result<()> {
+    debug!("transferring prisms on behalf of the owner");
+    let mut accounts_iter = accounts.iter();
+    let owner = next_account(&mut accounts_iter)?;
+    let delegate = next_account(&mut accounts_iter)?;
+    let receiver = next_account(&mut accounts_iter)?;
+    check_signer(delegate)?;
+
+    let Some(mut delegation) = current_delegation(owner)? else {
+        return Err(Error::NoDelegation { key: owner.key });
+    };
+    if delegation.delegate != delegate.key {
+        return Err(Error::UnauthorizedDelegate { key: delegate.key });
+    }
+    delegation.refresh(context.epoch());
+    delegation.spend(amount)?;
+
+    debug!("from {} to {}", owner.key, receiver.key);
+    owner.set_data(borsh::to_vec(&delegation)?)?;
+    owner.sub_prisms(amount)?;
+    receiver.add_prisms(amount)?;
+    Ok(())
+}
+
+/// Get the delegation stored in an account's data, if it has one.
+///
+/// # Errors
+/// If the account holds other data, which approving or revoking a delegate would destroy.
+fn current_delegation(owner: &TransactionAccount) -> Result<Option<Delegation>> {
+    let data = owner.data();
+    if data.is_empty() {
+        return Ok(None);
+    }
+    borsh::from_slice(&data).map(Some).map_err(|_err| {
+        warn!(key = %owner.key, "the account holds data that is not a delegation");
+        Error::InvalidAccountData { key: owner.key }
+    })
+}
+
+#[instrument(skip(accounts))]
+fn realloc(accounts: &[TransactionAccount], new_size: usize) -> Result<()> {
+    debug!("reallocating account data");
+    let mut accounts_iter = accounts.iter();
+    let account = next_account(&mut accounts_iter)?;
+    let payer = next_account(&mut accounts_iter)?;
+    check_signer(account)?;
+    check_signer(payer)?;
+    if new_size > MAX_ACCOUNT_DATA_SIZE {
+        return Err(Error::AccountDataTooLarge { size: new_size });
+    }
+
+    let old_size = account.data().len();
+    let required = minimum_balance(new_size);
+    if new_size > old_size {
+        let missing = required.saturating_sub(account.prisms());
+        if payer.prisms() < missing {
+            return Err(Error::InsufficientFundsForRent {
+                key: payer.key,
+                required,
+            });
+        }
+        payer.sub_prisms(missing)?;
+        account.add_prisms(missing)?;
+    } else {
+        let refund = minimum_balance(old_size)
+            .saturating_sub(required)
+            .min(account.prisms());
+        account.sub_prisms(refund)?;
+        payer.add_prisms(refund)?;
+    }
+
+    let mut data = account.data().to_vec();
+    data.resize(new_size, 0);
+    account.set_data(data)?;
+    Ok(())
+}
+
+#[instrument(skip(accounts))]
+fn close_account(accounts: &[TransactionAccount], beneficiary: Pubkey) -> Result<()> {
+    debug!("closing account");
+    let mut accounts_iter = accounts.iter();
+    let account = next_account(&mut accounts_iter)?;
+    let receiver = next_account(&mut accounts_iter)?;
+    check_signer(account)?;
+    if receiver.key != beneficiary || receiver.key == account.key {
+        return Err(Error::Failed(format!(
+            "{} can't be the beneficiary of the account",
+            receiver.key
+        )));
+    }
+
+    let amount = account.prisms();
+    debug!("from {} to {}", account.key, receiver.key);
+    account.sub_prisms(amount)?;
+    receiver.add_prisms(amount)?;
+    account.set_data(Vec::new())?;
+    account.close()?;
+    Ok(())
+}
+
+fn check_signer(account: &TransactionAccount) -> Result<()> {
+    if !account.privileges.is_signer() {
+        return Err(Error::Failed(format!(
+            "{} must be a signing account",
+            account.key
+        )));
+    }
+    Ok(())
+}
+
 /// Get the instructions for the system program.
 pub mod instruction {
     use crate::{
@@ -111,6 +297,127 @@ pub mod instruction {
             &SystemInstruction::Transfer(amount),
         ))
     }
+
+    /// Allows a delegate to spend a limited amount of prisms from an account.
+    ///
+    /// Approving a new delegate replaces the previous one.
+    ///
+    /// # Parameters
+    /// * `owner` - The account the delegate will be allowed to spend from,
+    /// * `delegate` - The key allowed to spend from the account,
+    /// * `limit` - The maximum amount the delegate can spend,
+    /// * `recurring` - Whether the allowance is restored at each new epoch.
+    ///
+    /// # Errors
+    /// If the owner is not on the `ed25519` curve.
+    pub fn approve(
+        owner: Pubkey,
+        delegate: Pubkey,
+        limit: u64,
+        recurring: bool,
+    ) -> Result<Instruction> {
+        let accounts = vec![AccountMeta::signing(owner, Writable::Yes)?];
+        Ok(Instruction::new(
+            SYSTEM_PROGRAM,
+            accounts,
+            &SystemInstruction::Approve {
+                delegate,
+                limit,
+                recurring,
+            },
+        ))
+    }
+
+    /// Revokes the delegate of an account.
+    ///
+    /// # Parameters
+    /// * `owner` - The account whose delegate is revoked.
+    ///
+    /// # Errors
+    /// If the owner is not on the `ed25519` curve.
+    pub fn revoke(owner: Pubkey) -> Result<Instruction> {
+        let accounts = vec![AccountMeta::signing(owner, Writable::Yes)?];
+        Ok(Instruction::new(
+            SYSTEM_PROGRAM,
+            accounts,
+            &SystemInstruction::Revoke,
+        ))
+    }
+
+    /// Resizes the data of an account.
+    ///
+    /// When the data grows, the payer tops up the account to its new rent exempt
+    /// minimum balance, and the new bytes are zeroed. When it shrinks, the
+    /// difference in rent is refunded to the payer.
+    ///
+    /// # Parameters
+    /// * `account` - The account to resize,
+    /// * `payer` - The account paying for, or refunded of, the rent difference,
+    /// * `new_size` - The new size of the account's data.
+    ///
+    /// # Errors
+    /// If either account is not on the `ed25519` curve.
+    pub fn realloc(account: Pubkey, payer: Pubkey, new_size: u32) -> Result<Instruction> {
+        let accounts = vec![
+            AccountMeta::signing(account, Writable::Yes)?,
+            AccountMeta::signing(payer, Writable::Yes)?,
+        ];
+        Ok(Instruction::new(
+            SYSTEM_PROGRAM,
+            accounts,
+            &SystemInstruction::Realloc { new_size },
+        ))
+    }
+
+    /// Closes an account, sending its remaining prisms to a beneficiary.
+    ///
+    /// The account's data is cleared and it's removed from the vault.
+    ///
+    /// # Parameters
+    /// * `account` - The account to close,
+    /// * `beneficiary` - The account receiving the remaining prisms.
+    ///
+    /// # Errors
+    /// If either account is not on the `ed25519` curve.
+    pub fn close_account(account: Pubkey, beneficiary: Pubkey) -> Result<Instruction> {
+        let accounts = vec![
+            AccountMeta::signing(account, Writable::Yes)?,
+            AccountMeta::wallet(beneficiary, Writable::Yes)?,
+        ];
+        Ok(Instruction::new(
+            SYSTEM_PROGRAM,
+            accounts,
+            &SystemInstruction::CloseAccount { beneficiary },
+        ))
+    }
+
+    /// Prisms transfer instruction signed by the delegate of the source account.
+    ///
+    /// # Parameters
+    /// * `owner` - The account the prisms are taken from,
+    /// * `delegate` - The delegate of the owner's account,
+    /// * `to` - The account receiving the prisms,
+    /// * `amount` - The amount of prisms to receive.
+    ///
+    /// # Errors
+    /// If any account is not on the `ed25519` curve.
+    pub fn delegated_transfer(
+        owner: Pubkey,
+        delegate: Pubkey,
+        to: Pubkey,
+        amount: u64,
+    ) -> Result<Instruction> {
+        let accounts = vec![
+            AccountMeta::wallet(owner, Writable::Yes)?,
+            AccountMeta::signing(delegate, Writable::No)?,
+            AccountMeta::wallet(to, Writable::Yes)?,
+        ];
+        Ok(Instruction::new(
+            SYSTEM_PROGRAM,
+            accounts,
+            &SystemInstruction::DelegatedTransfer(amount),
+        ))
+    }
 }
 
 #[cfg(test)]
@@ -121,38 +428,34 @@ mod tests {
 
     use test_log::test;
 
-    use crate::account::{AccountMeta, TransactionAccount, Wallet, Writable};
+    use crate::account::{
+        AccountMeta, Error as AccountError, TransactionAccount, Wallet, Writable,
+    };
     use crate::crypto::Keypair;
+    use crate::program::rent::PRISMS_PER_BYTE;
+    use crate::program::SLOTS_PER_EPOCH;
+    use crate::program_test::ProgramTest;
+    use crate::validator::Error as ValidatorError;
 
     use super::super::Error;
     use super::*;
     type TestResult = core::result::Result<(), Box<dyn core::error::Error>>;
 
-    #[expect(clippy::unwrap_used)]
     #[test]
     fn execute_transfer_instruction() -> TestResult {
         // Given
         const AMOUNT: u64 = 1_000;
         let key1 = Keypair::generate().pubkey();
         let key2 = Keypair::generate().pubkey();
-        let meta1 = AccountMeta::signing(key1, Writable::Yes)?;
-        let meta2 = AccountMeta::wallet(key2, Writable::Yes)?;
-        let mut wallet1 = Wallet { prisms: AMOUNT };
-        let mut wallet2 = Wallet { prisms: 0 };
-
-        let accounts_vec = vec![
-            TransactionAccount::new(&meta1, &mut wallet1),
-            TransactionAccount::new(&meta2, &mut wallet2),
-        ];
-
-        let payload = borsh::to_vec(&SystemInstruction::Transfer(100)).unwrap();
+        let mut test = ProgramTest::default();
+        test.add_account(key1, Wallet::new(AMOUNT));
 
         // When
-        execute_instruction(&accounts_vec, &payload)?;
+        test.process(&[instruction::transfer(key1, key2, 100)?])?;
 
         // Then
-        assert_eq!(wallet1.prisms, AMOUNT - 100);
-        assert_eq!(wallet2.prisms, 100);
+        assert_eq!(test.account(&key1).prisms, AMOUNT - 100);
+        assert_eq!(test.account(&key2).prisms, 100);
 
         Ok(())
     }
@@ -163,7 +466,7 @@ mod tests {
         const AMOUNT: u64 = 1_000;
         let key1 = Keypair::generate().pubkey();
         let meta1 = AccountMeta::signing(key1, Writable::Yes)?;
-        let mut wallet1 = Wallet { prisms: AMOUNT };
+        let mut wallet1 = Wallet::new(AMOUNT);
 
         let accounts_vec = vec![TransactionAccount::new(&meta1, &mut wallet1)];
 
@@ -171,7 +474,7 @@ mod tests {
         let payload = borsh::to_vec(&SystemInstruction::Transfer(100)).unwrap();
 
         // When
-        let res = execute_instruction(&accounts_vec, &payload);
+        let res = execute_instruction(&Context::default(), &accounts_vec, &payload);
 
         // Then
         assert_matches!(res, Err(error) if matches!(error, Error::Account(_)));
@@ -187,8 +490,8 @@ mod tests {
         let key2 = Keypair::generate().pubkey();
         let meta1 = AccountMeta::wallet(key1, Writable::Yes)?;
         let meta2 = AccountMeta::wallet(key2, Writable::Yes)?;
-        let mut wallet1 = Wallet { prisms: AMOUNT };
-        let mut wallet2 = Wallet { prisms: 0 };
+        let mut wallet1 = Wallet::new(AMOUNT);
+        let mut wallet2 = Wallet::new(0);
 
         let accounts_vec = vec![
             TransactionAccount::new(&meta1, &mut wallet1),
@@ -199,10 +502,312 @@ mod tests {
         let payload = borsh::to_vec(&SystemInstruction::Transfer(100)).unwrap();
 
         // When
-        let res = execute_instruction(&accounts_vec, &payload);
+        let res = execute_instruction(&Context::default(), &accounts_vec, &payload);
+
+        // Then
+        assert_matches!(res, Err(error) if matches!(error, Error::Failed { .. }));
+
+        Ok(())
+    }
+
+    #[expect(clippy::unwrap_used)]
+    fn approve_payload(delegate: Pubkey, limit: u64, recurring: bool) -> Vec<u8> {
+        borsh::to_vec(&SystemInstruction::Approve {
+            delegate,
+            limit,
+            recurring,
+        })
+        .unwrap()
+    }
+
+    #[expect(clippy::unwrap_used)]
+    fn delegated_transfer_payload(amount: u64) -> Vec<u8> {
+        borsh::to_vec(&SystemInstruction::DelegatedTransfer(amount)).unwrap()
+    }
+
+    #[test]
+    fn delegate_spends_within_allowance() -> TestResult {
+        // Given
+        const AMOUNT: u64 = 10_000;
+        let owner = Keypair::generate().pubkey();
+        let delegate = Keypair::generate().pubkey();
+        let receiver = Keypair::generate().pubkey();
+        let mut test = ProgramTest::default();
+        test.add_account(owner, Wallet::new(AMOUNT));
+        test.process(&[instruction::approve(owner, delegate, 1_000, false)?])?;
+
+        // When
+        test.process(&[instruction::delegated_transfer(
+            owner, delegate, receiver, 600,
+        )?])?;
+        let res = test.process(&[instruction::delegated_transfer(
+            owner, delegate, receiver, 600,
+        )?]);
+
+        // Then
+        assert_matches!(
+            res,
+            Err(crate::Error::Validator(ValidatorError::Program(
+                Error::Account(AccountError::AllowanceExceeded {
+                    requested: 600,
+                    remaining: 400
+                })
+            )))
+        );
+        assert_eq!(test.account(&owner).prisms, AMOUNT - 600);
+        assert_eq!(test.account(&receiver).prisms, 600);
+        let delegation: Delegation = borsh::from_slice(&test.account(&owner).data)?;
+        assert_eq!(delegation.remaining, 400);
+
+        Ok(())
+    }
+
+    #[test]
+    fn recurring_allowance_restored_at_new_epoch() -> TestResult {
+        // Given
+        let owner = Keypair::generate().pubkey();
+        let delegate = Keypair::generate().pubkey();
+        let receiver = Keypair::generate().pubkey();
+        let owner_meta = AccountMeta::signing(owner, Writable::Yes)?;
+        let delegate_meta = AccountMeta::signing(delegate, Writable::No)?;
+        let receiver_meta = AccountMeta::wallet(receiver, Writable::Yes)?;
+        let mut owner_wallet = Wallet::new(10_000);
+        let mut delegate_wallet = Wallet::new(0);
+        let mut receiver_wallet = Wallet::new(0);
+        execute_instruction(
+            &Context::new(0),
+            &[TransactionAccount::new(&owner_meta, &mut owner_wallet)],
+            &approve_payload(delegate, 1_000, true),
+        )?;
+        let accounts_vec = vec![
+            TransactionAccount::new(&owner_meta, &mut owner_wallet),
+            TransactionAccount::new(&delegate_meta, &mut delegate_wallet),
+            TransactionAccount::new(&receiver_meta, &mut receiver_wallet),
+        ];
+        execute_instruction(
+            &Context::new(0),
+            &accounts_vec,
+            &delegated_transfer_payload(1_000),
+        )?;
+
+        // When
+        let same_epoch = execute_instruction(
+            &Context::new(SLOTS_PER_EPOCH - 1),
+            &accounts_vec,
+            &delegated_transfer_payload(1_000),
+        );
+        let next_epoch = execute_instruction(
+            &Context::new(SLOTS_PER_EPOCH),
+            &accounts_vec,
+            &delegated_transfer_payload(1_000),
+        );
+        drop(accounts_vec);
+
+        // Then
+        assert_matches!(
+            same_epoch,
+            Err(Error::Account(AccountError::AllowanceExceeded { .. }))
+        );
+        assert_matches!(next_epoch, Ok(()));
+        assert_eq!(receiver_wallet.prisms, 2_000);
+
+        Ok(())
+    }
+
+    #[test]
+    fn only_the_delegate_can_spend() -> TestResult {
+        // Given
+        let owner = Keypair::generate().pubkey();
+        let delegate = Keypair::generate().pubkey();
+        let thief = Keypair::generate().pubkey();
+        let owner_meta = AccountMeta::signing(owner, Writable::Yes)?;
+        let thief_meta = AccountMeta::signing(thief, Writable::No)?;
+        let receiver_meta = AccountMeta::wallet(Keypair::generate().pubkey(), Writable::Yes)?;
+        let mut owner_wallet = Wallet::new(10_000);
+        let mut thief_wallet = Wallet::new(0);
+        let mut receiver_wallet = Wallet::new(0);
+        execute_instruction(
+            &Context::default(),
+            &[TransactionAccount::new(&owner_meta, &mut owner_wallet)],
+            &approve_payload(delegate, 1_000, false),
+        )?;
+
+        // When
+        let accounts_vec = vec![
+            TransactionAccount::new(&owner_meta, &mut owner_wallet),
+            TransactionAccount::new(&thief_meta, &mut thief_wallet),
+            TransactionAccount::new(&receiver_meta, &mut receiver_wallet),
+        ];
+        let res = execute_instruction(
+            &Context::default(),
+            &accounts_vec,
+            &delegated_transfer_payload(100),
+        );
+        drop(accounts_vec);
+
+        // Then
+        assert_matches!(res, Err(Error::UnauthorizedDelegate { key }) if key == thief);
+        assert_eq!(owner_wallet.prisms, 10_000);
+
+        Ok(())
+    }
+
+    #[test]
+    fn delegates_never_replace_other_data() -> TestResult {
+        // Given
+        let owner = Keypair::generate().pubkey();
+        let owner_meta = AccountMeta::signing(owner, Writable::Yes)?;
+        let mut owner_wallet = Wallet::new(10_000);
+        owner_wallet.data = vec![7; 12].into();
+        let accounts_vec = vec![TransactionAccount::new(&owner_meta, &mut owner_wallet)];
+
+        // When
+        let approved = execute_instruction(
+            &Context::default(),
+            &accounts_vec,
+            &approve_payload(Keypair::generate().pubkey(), 1_000, false),
+        );
+        let revoked = execute_instruction(
+            &Context::default(),
+            &accounts_vec,
+            instruction::revoke(owner)?.data(),
+        );
+        drop(accounts_vec);
+
+        // Then
+        assert_matches!(approved, Err(Error::InvalidAccountData { key }) if key == owner);
+        assert_matches!(revoked, Err(Error::InvalidAccountData { key }) if key == owner);
+        assert_eq!(&*owner_wallet.data, &[7; 12]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn realloc_grows_and_shrinks_data() -> TestResult {
+        // Given
+        let key = Keypair::generate().pubkey();
+        let payer = Keypair::generate().pubkey();
+        let meta = AccountMeta::signing(key, Writable::Yes)?;
+        let payer_meta = AccountMeta::signing(payer, Writable::Yes)?;
+        let mut wallet = Wallet::new(minimum_balance(2));
+        wallet.data = vec![7, 7].into();
+        let mut payer_wallet = Wallet::new(10_000);
+        let accounts_vec = vec![
+            TransactionAccount::new(&meta, &mut wallet),
+            TransactionAccount::new(&payer_meta, &mut payer_wallet),
+        ];
+
+        // When
+        execute_instruction(
+            &Context::default(),
+            &accounts_vec,
+            instruction::realloc(key, payer, 100)?.data(),
+        )?;
+        let grown = (accounts_vec[0].data().to_vec(), accounts_vec[0].prisms());
+        execute_instruction(
+            &Context::default(),
+            &accounts_vec,
+            instruction::realloc(key, payer, 1)?.data(),
+        )?;
+        drop(accounts_vec);
+
+        // Then
+        let mut expected = vec![0; 100];
+        expected[..2].copy_from_slice(&[7, 7]);
+        assert_eq!(grown, (expected, minimum_balance(100)));
+        assert_eq!(*wallet.data, [7]);
+        assert_eq!(wallet.prisms, minimum_balance(1));
+        assert_eq!(
+            payer_wallet.prisms,
+            10_000 - minimum_balance(1) + minimum_balance(2)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn realloc_is_capped() -> TestResult {
+        // Given
+        let key = Keypair::generate().pubkey();
+        let meta = AccountMeta::signing(key, Writable::Yes)?;
+        let mut wallet = Wallet::new(1_000_000_000);
+        let mut payer_wallet = Wallet::new(0);
+        let accounts_vec = vec![
+            TransactionAccount::new(&meta, &mut wallet),
+            TransactionAccount::new(&meta, &mut payer_wallet),
+        ];
+        let size = u32::try_from(MAX_ACCOUNT_DATA_SIZE)? + 1;
+
+        // When
+        let res = execute_instruction(
+            &Context::default(),
+            &accounts_vec,
+            instruction::realloc(key, key, size)?.data(),
+        );
+
+        // Then
+        assert_matches!(res, Err(Error::AccountDataTooLarge { size: too_large }) if too_large == MAX_ACCOUNT_DATA_SIZE + 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn underfunded_realloc_fails() -> TestResult {
+        // Given
+        let key = Keypair::generate().pubkey();
+        let payer = Keypair::generate().pubkey();
+        let meta = AccountMeta::signing(key, Writable::Yes)?;
+        let payer_meta = AccountMeta::signing(payer, Writable::Yes)?;
+        let mut wallet = Wallet::new(minimum_balance(0));
+        let mut payer_wallet = Wallet::new(100 * PRISMS_PER_BYTE - 1);
+        let accounts_vec = vec![
+            TransactionAccount::new(&meta, &mut wallet),
+            TransactionAccount::new(&payer_meta, &mut payer_wallet),
+        ];
+
+        // When
+        let res = execute_instruction(
+            &Context::default(),
+            &accounts_vec,
+            instruction::realloc(key, payer, 100)?.data(),
+        );
+        drop(accounts_vec);
+
+        // Then
+        assert_matches!(res, Err(Error::InsufficientFundsForRent { key: poor, .. }) if poor == payer);
+        assert!(wallet.data.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn close_account_sends_everything_to_beneficiary() -> TestResult {
+        // Given
+        let key = Keypair::generate().pubkey();
+        let beneficiary = Keypair::generate().pubkey();
+        let meta = AccountMeta::signing(key, Writable::Yes)?;
+        let beneficiary_meta = AccountMeta::wallet(beneficiary, Writable::Yes)?;
+        let mut wallet = Wallet::new(1_000);
+        wallet.data = vec![1, 2, 3].into();
+        let mut beneficiary_wallet = Wallet::new(10);
+        let accounts_vec = vec![
+            TransactionAccount::new(&meta, &mut wallet),
+            TransactionAccount::new(&beneficiary_meta, &mut beneficiary_wallet),
+        ];
+
+        // When
+        execute_instruction(
+            &Context::default(),
+            &accounts_vec,
+            instruction::close_account(key, beneficiary)?.data(),
+        )?;
+        let closed = accounts_vec[0].is_closed();
+        drop(accounts_vec);
 
         // Then
-        assert_matches!(res, Err(error) if matches!(error, Error::Custom { .. }));
+        assert!(closed);
+        assert_eq!(wallet, Wallet::default());
+        assert_eq!(beneficiary_wallet.prisms, 1_010);
 
         Ok(())
     }
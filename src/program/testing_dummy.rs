@@ -3,7 +3,7 @@
 // Creation date: Friday 14 February 2025
 // Author: Vincent Berthier <vincent.berthier@posteo.org>
 // -----
-// Last modified: Friday 14 February 2025 @ 16:41:49
+// Last modified: Thursday 15 October 2026 @ 17:07:05
 // Modified by: Vincent Berthier
 // -----
 // Copyright (c) 2025 <Vincent Berthier>
@@ -32,34 +32,47 @@ use tracing::{debug, instrument};
 use crate::{
     account::{next_account, TransactionAccount},
     crypto::Pubkey,
+    pubkey,
 };
 
-use super::Result;
+use super::{Context, Error, Result};
 
 /// The System's program id (`BifrostTestingSystemProgram11111111111111111`)
-pub const TESTING_PROGRAM: Pubkey = Pubkey::from_bytes(&[
-    159, 65, 158, 196, 5, 88, 89, 176, 224, 101, 212, 80, 151, 14, 225, 182, 96, 196, 131, 59, 87,
-    252, 174, 1, 124, 135, 56, 32, 33, 180, 0, 0,
-]);
+pub const TESTING_PROGRAM: Pubkey = pubkey!("BifrostTestingSystemProgram11111111111111111");
 
 #[derive(Debug, BorshSerialize, BorshDeserialize)]
 enum SystemInstruction {
     BurnPrisms(u64),
+    MintPrisms(u64),
+    CheckFee { payer: Pubkey, fee: u64 },
+    Fail(u32),
+    Tamper,
+    RecordClock,
 }
 
 /// Executes a testing program's instruction.
 ///
 /// # Parameters
+/// * `context` - The context of the execution,
 /// * `accounts` - The accounts needed by the instruction,
 /// * `payload` - The data payload for the instruction.
 ///
 /// # Errors
 /// if the instruction fails to complete (missing accounts, arithmetic overflows, *etc.*).
 #[instrument(skip_all)]
-pub fn execute_instruction(accounts: &[TransactionAccount], payload: &[u8]) -> Result<()> {
+pub fn execute_instruction(
+    context: &Context,
+    accounts: &[TransactionAccount],
+    payload: &[u8],
+) -> Result<()> {
     debug!("received system insruction");
     match borsh::from_slice(payload)? {
         SystemInstruction::BurnPrisms(amount) => burn_prisms(accounts, amount),
+        SystemInstruction::MintPrisms(amount) => mint_prisms(accounts, amount),
+        SystemInstruction::CheckFee { payer, fee } => check_fee(context, payer, fee),
+        SystemInstruction::Fail(code) => Err(Error::Custom(code)),
+        SystemInstruction::Tamper => tamper(accounts),
+        SystemInstruction::RecordClock => record_clock(context, accounts),
     }
 }
 
@@ -74,6 +87,47 @@ fn burn_prisms(accounts: &[TransactionAccount], amount: u64) -> Result<()> {
     Ok(())
 }
 
+#[instrument(skip(accounts))]
+fn mint_prisms(accounts: &[TransactionAccount], amount: u64) -> Result<()> {
+    debug!("minting prisms out of thin air");
+    let mut accounts_iter = accounts.iter();
+    let receiver = next_account(&mut accounts_iter)?;
+    receiver.add_prisms(amount)?;
+    Ok(())
+}
+
+#[instrument(skip_all)]
+fn tamper(accounts: &[TransactionAccount]) -> Result<()> {
+    debug!("tampering with every account, whatever its privileges");
+    for account in accounts {
+        account.set_data(vec![0xFF; 8])?;
+        account.add_prisms(1)?;
+    }
+    Ok(())
+}
+
+#[instrument(skip_all)]
+fn record_clock(context: &Context, accounts: &[TransactionAccount]) -> Result<()> {
+    debug!("recording the slot and epoch seen by the program");
+    let mut accounts_iter = accounts.iter();
+    let account = next_account(&mut accounts_iter)?;
+    account.set_data(borsh::to_vec(&(context.slot(), context.epoch()))?)?;
+    Ok(())
+}
+
+#[instrument(skip(context))]
+fn check_fee(context: &Context, payer: Pubkey, fee: u64) -> Result<()> {
+    debug!("checking the fee seen by the program");
+    if context.fee_payer() != payer || context.fee_paid() != fee {
+        return Err(Error::Failed(format!(
+            "expected a fee of {fee} paid by {payer}, got {} paid by {}",
+            context.fee_paid(),
+            context.fee_payer()
+        )));
+    }
+    Ok(())
+}
+
 /// Get the instructions for the system program.
 pub mod instruction {
     use crate::{
@@ -104,4 +158,77 @@ pub mod instruction {
             &SystemInstruction::BurnPrisms(amount),
         ))
     }
+
+    /// Instruction creating prisms from nothing.
+    ///
+    /// # Parameters
+    /// * `to` - The account receiving the prisms,
+    /// * `amount` - The amount of prisms created.
+    ///
+    /// # Errors
+    /// If the account is not on the `ed25519` curve.
+    pub fn mint_prisms(to: Pubkey, amount: u64) -> Result<Instruction> {
+        let accounts = vec![AccountMeta::signing(to, Writable::Yes)?];
+        Ok(Instruction::new(
+            TESTING_PROGRAM,
+            accounts,
+            &SystemInstruction::MintPrisms(amount),
+        ))
+    }
+
+    /// Instruction failing unless the program sees the given fee.
+    ///
+    /// # Parameters
+    /// * `payer` - The expected fee payer,
+    /// * `fee` - The expected fee.
+    ///
+    /// # Errors
+    /// If the payer is not on the `ed25519` curve.
+    pub fn check_fee(payer: Pubkey, fee: u64) -> Result<Instruction> {
+        Ok(Instruction::new(
+            TESTING_PROGRAM,
+            vec![AccountMeta::signing(payer, Writable::Yes)?],
+            &SystemInstruction::CheckFee { payer, fee },
+        ))
+    }
+
+    /// Instruction storing the slot and epoch the program sees in an account's data.
+    ///
+    /// # Parameters
+    /// * `account` - The account the slot and epoch are written to.
+    ///
+    /// # Errors
+    /// If the account is not on the `ed25519` curve.
+    pub fn record_clock(account: Pubkey) -> Result<Instruction> {
+        Ok(Instruction::new(
+            TESTING_PROGRAM,
+            vec![AccountMeta::signing(account, Writable::Yes)?],
+            &SystemInstruction::RecordClock,
+        ))
+    }
+
+    /// Instruction modifying all its accounts, as a buggy program would.
+    ///
+    /// # Parameters
+    /// * `accounts` - The accounts the program tries to modify.
+    #[must_use]
+    pub fn tamper(accounts: Vec<AccountMeta>) -> Instruction {
+        Instruction::new(TESTING_PROGRAM, accounts, &SystemInstruction::Tamper)
+    }
+
+    /// Instruction failing with a custom error code, as a third-party program would.
+    ///
+    /// # Parameters
+    /// * `payer` - The account paying for the transaction,
+    /// * `code` - The custom error code.
+    ///
+    /// # Errors
+    /// If the payer is not on the `ed25519` curve.
+    pub fn fail(payer: Pubkey, code: u32) -> Result<Instruction> {
+        Ok(Instruction::new(
+            TESTING_PROGRAM,
+            vec![AccountMeta::signing(payer, Writable::Yes)?],
+            &SystemInstruction::Fail(code),
+        ))
+    }
 }
@@ -0,0 +1,341 @@
+// File: src/program_test.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 20:23:36
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::collections::HashMap;
+
+use tracing::{debug, instrument, trace, warn};
+
+use crate::{
+    account::{TransactionAccount, TransactionContext, Wallet},
+    crypto::Pubkey,
+    program::{self, Context},
+    transaction::{Instruction, Transaction},
+    validator::{
+        self, check_balance, check_invocations, execute_instruction, total_prisms, ValidatorConfig,
+    },
+    Error,
+};
+
+type Result<T> = core::result::Result<T, Error>;
+
+/// The entry point of a program registered in a [`ProgramTest`].
+pub type Processor =
+    fn(&Context, &[TransactionAccount], &[u8]) -> core::result::Result<(), program::Error>;
+
+/// Runs instructions against in-memory accounts, without a validator or a vault.
+///
+/// The instructions go through the same account resolution, dispatch and checks as in the
+/// validator (the invocation limits of the default configuration, and a total of prisms
+/// left unchanged), and all the instructions given to [`ProgramTest::process`] are rolled
+/// back if one of them fails. No fee is charged.
+///
+/// # Example
+/// ```rust
+/// # use bifrost::{Error, account::Wallet, crypto::Keypair, program::system, program_test::ProgramTest};
+/// let from = Keypair::generate().pubkey();
+/// let to = Keypair::generate().pubkey();
+/// let mut test = ProgramTest::default();
+/// test.add_account(from, Wallet::new(1_000));
+///
+/// test.process(&[system::instruction::transfer(from, to, 400)?])?;
+/// assert_eq!(test.account(&from).prisms, 600);
+/// assert_eq!(test.account(&to).prisms, 400);
+/// # Ok::<(), Error>(())
+/// ```
+#[derive(Debug, Default)]
+pub struct ProgramTest {
+    /// The environment the instructions are executed in.
+    context: Context,
+    /// The accounts known to the test.
+    accounts: HashMap<Pubkey, Wallet>,
+    /// The programs registered by the test, on top of the built-in ones.
+    programs: HashMap<Pubkey, Processor>,
+}
+
+impl ProgramTest {
+    /// Sets the environment the instructions are executed in.
+    ///
+    /// # Parameters
+    /// * `context` - The context given to the programs.
+    #[must_use]
+    pub const fn with_context(mut self, context: Context) -> Self {
+        self.context = context;
+        self
+    }
+
+    /// Adds or replaces an account.
+    ///
+    /// # Parameters
+    /// * `key` - The public key of the account,
+    /// * `account` - The account's content.
+    pub fn add_account(&mut self, key: Pubkey, account: Wallet) -> &mut Self {
+        self.accounts.insert(key, account);
+        self
+    }
+
+    /// Registers a program, which takes precedence over a built-in one with the same key.
+    ///
+    /// # Parameters
+    /// * `key` - The public key of the program,
+    /// * `processor` - The entry point of the program.
+    pub fn add_program(&mut self, key: Pubkey, processor: Processor) -> &mut Self {
+        self.programs.insert(key, processor);
+        self
+    }
+
+    /// Get an account, which is empty if it's unknown.
+    ///
+    /// # Parameters
+    /// * `key` - The public key of the account.
+    #[must_use]
+    pub fn account(&self, key: &Pubkey) -> Wallet {
+        self.accounts.get(key).cloned().unwrap_or_default()
+    }
+
+    /// Executes instructions as a single transaction.
+    ///
+    /// Only the writable accounts are updated, and only if every instruction succeeds.
+    ///
+    /// # Parameters
+    /// * `instructions` - The instructions to execute, in order.
+    ///
+    /// # Errors
+    /// If the instructions can't be assembled in a transaction, if they invoke programs
+    /// more than allowed, if one of them fails, or if they change the total of prisms.
+    #[instrument(skip_all)]
+    pub fn process(&mut self, instructions: &[Instruction]) -> Result<()> {
+        debug!(n = instructions.len(), "processing instructions");
+        let mut trx = Transaction::new(self.context.slot());
+        trx.add(instructions)?;
+        check_invocations(&ValidatorConfig::default(), &trx)?;
+        let message = trx.message();
+        let metas = message.accounts();
+        let mut wallets = metas
+            .iter()
+            .map(|meta| self.account(meta.key()))
+            .collect::<Vec<_>>();
+
+        {
+            let trx_context = TransactionContext::new(
+                metas
+                    .iter()
+                    .zip(wallets.iter_mut())
+                    .map(|(meta, wallet)| TransactionAccount::new(meta, wallet))
+                    .collect(),
+            );
+            let total_before = total_prisms(trx_context.accounts());
+            for (index, instruction) in message.instructions.iter().enumerate() {
+                let res = match self.programs.get(message.instruction_program_id(index)?) {
+                    Some(processor) => {
+                        trace!("executing a registered program");
+                        let accounts = message
+                            .instruction_accounts(index)?
+                            .iter()
+                            .map(|meta| trx_context.accounts()[meta.index].clone())
+                            .collect::<Vec<_>>();
+                        processor(&self.context, &accounts, &instruction.data)
+                            .map_err(validator::Error::from)
+                    }
+                    None => execute_instruction(
+                        &trx,
+                        index,
+                        &self.context,
+                        &instruction.data,
+                        trx_context.accounts(),
+                    ),
+                };
+                if let Err(err) = res {
+                    warn!("instruction {index} failed: {err}");
+                    trx_context.rollback();
+                    return Err(err.into());
+                }
+            }
+            if let Err(err) = check_balance(trx_context.accounts(), total_before, 0) {
+                trx_context.rollback();
+                return Err(err.into());
+            }
+            trx_context.commit();
+        }
+
+        for (meta, wallet) in metas.iter().zip(wallets) {
+            if !meta.is_writable() {
+                continue;
+            }
+            if wallet == Wallet::default() {
+                self.accounts.remove(meta.key());
+            } else {
+                self.accounts.insert(*meta.key(), wallet);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use std::assert_matches::assert_matches;
+
+    use test_log::test;
+
+    use crate::account::{next_account, AccountMeta, Writable};
+    use crate::crypto::Keypair;
+    use crate::program::{memo, system};
+
+    use super::*;
+    type TestResult = core::result::Result<(), Box<dyn core::error::Error>>;
+
+    const PROGRAM: Pubkey = Pubkey::from_bytes(&[7; 32]);
+
+    /// Burns the prisms of its only account.
+    fn burn(
+        _context: &Context,
+        accounts: &[TransactionAccount],
+        payload: &[u8],
+    ) -> core::result::Result<(), program::Error> {
+        let account = next_account(&mut accounts.iter())?;
+        account.sub_prisms(u64::from(payload[0]))?;
+        Ok(())
+    }
+
+    /// Moves prisms from its first account to its second one.
+    fn shift(
+        _context: &Context,
+        accounts: &[TransactionAccount],
+        payload: &[u8],
+    ) -> core::result::Result<(), program::Error> {
+        let accounts_iter = &mut accounts.iter();
+        let from = next_account(accounts_iter)?;
+        let to = next_account(accounts_iter)?;
+        from.sub_prisms(u64::from(payload[0]))?;
+        to.add_prisms(u64::from(payload[0]))?;
+        Ok(())
+    }
+
+    #[test]
+    fn registered_programs_are_invoked() -> TestResult {
+        // Given
+        let from = Keypair::generate().pubkey();
+        let to = Keypair::generate().pubkey();
+        let mut test = ProgramTest::default();
+        test.add_account(from, Wallet::new(100))
+            .add_program(PROGRAM, shift);
+        let instruction = Instruction::new(
+            PROGRAM,
+            vec![
+                AccountMeta::signing(from, Writable::Yes)?,
+                AccountMeta::wallet(to, Writable::Yes)?,
+            ],
+            &10_u8,
+        );
+
+        // When
+        test.process(&[instruction])?;
+
+        // Then
+        assert_eq!(test.account(&from).prisms, 90);
+        assert_eq!(test.account(&to).prisms, 10);
+
+        Ok(())
+    }
+
+    #[test]
+    fn burning_prisms_is_rejected() -> TestResult {
+        // Given
+        let key = Keypair::generate().pubkey();
+        let mut test = ProgramTest::default();
+        test.add_account(key, Wallet::new(100))
+            .add_program(PROGRAM, burn);
+        let instruction = Instruction::new(
+            PROGRAM,
+            vec![AccountMeta::signing(key, Writable::Yes)?],
+            &10_u8,
+        );
+
+        // When
+        let res = test.process(&[instruction]);
+
+        // Then
+        assert_matches!(
+            res,
+            Err(Error::Validator(
+                validator::Error::BalanceInvariantViolation { delta: -10 }
+            ))
+        );
+        assert_eq!(test.account(&key).prisms, 100);
+
+        Ok(())
+    }
+
+    #[test]
+    fn invocation_limits_are_enforced() -> TestResult {
+        // Given
+        let key = Keypair::generate().pubkey();
+        let mut test = ProgramTest::default();
+        let instructions = (0..=memo::MAX_INVOCATIONS)
+            .map(|_| memo::instruction::memo("memo", &[key]))
+            .collect::<core::result::Result<Vec<_>, _>>()?;
+
+        // When
+        let res = test.process(&instructions);
+
+        // Then
+        assert_matches!(
+            res,
+            Err(Error::Validator(validator::Error::TooManyInvocations {
+                max: memo::MAX_INVOCATIONS,
+                ..
+            }))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn failed_instruction_rolls_back_the_others() -> TestResult {
+        // Given
+        let from = Keypair::generate().pubkey();
+        let to = Keypair::generate().pubkey();
+        let mut test = ProgramTest::default();
+        test.add_account(from, Wallet::new(100));
+
+        // When
+        let res = test.process(&[
+            system::instruction::transfer(from, to, 60)?,
+            system::instruction::transfer(from, to, 60)?,
+        ]);
+
+        // Then
+        assert_matches!(res, Err(Error::Validator(_)));
+        assert_eq!(test.account(&from).prisms, 100);
+        assert_eq!(test.account(&to), Wallet::default());
+
+        Ok(())
+    }
+}
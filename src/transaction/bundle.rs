@@ -0,0 +1,174 @@
+// File: src/transaction/bundle.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 15:13:27
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::collections::HashSet;
+
+use tracing::{instrument, warn};
+
+use super::{estimate_size, Error, Result, Transaction};
+
+/// Transactions executed together: all of them in order within the same block, or none.
+#[derive(Clone, Debug)]
+pub struct Bundle(Vec<Transaction>);
+
+impl Bundle {
+    /// Groups signed transactions into a bundle.
+    ///
+    /// # Parameters
+    /// * `transactions` - The transactions, in the order they must be executed.
+    ///
+    /// # Errors
+    /// If there are no transactions, if one isn't signed, or if the same one is given twice.
+    ///
+    /// # Example
+    /// ```
+    /// # use bifrost::{
+    /// #     crypto::Keypair,
+    /// #     program::system,
+    /// #     transaction::{Bundle, Error, Transaction},
+    /// # };
+    /// let payer = Keypair::generate();
+    /// let mut transactions = Vec::new();
+    /// for amount in [10, 20] {
+    ///     let mut trx = Transaction::new(0);
+    ///     trx.add(&[system::instruction::transfer(
+    ///         payer.pubkey(),
+    ///         Keypair::generate().pubkey(),
+    ///         amount,
+    ///     )?])?;
+    ///     trx.sign(&payer)?;
+    ///     transactions.push(trx);
+    /// }
+    ///
+    /// let bundle = Bundle::new(transactions)?;
+    /// assert_eq!(bundle.len(), 2);
+    /// # Ok::<(), Box<dyn core::error::Error>>(())
+    /// ```
+    #[instrument(skip_all, fields(n = transactions.len()))]
+    pub fn new(transactions: Vec<Transaction>) -> Result<Self> {
+        if transactions.is_empty() {
+            warn!("cannot bundle no transaction");
+            return Err(Error::EmptyBundle);
+        }
+        let mut signatures = HashSet::new();
+        for (index, transaction) in transactions.iter().enumerate() {
+            let signature = transaction
+                .signature()
+                .ok_or(Error::NoSignersOnTransaction)?;
+            if !signatures.insert(*signature) {
+                warn!(index, "the transaction is already in the bundle");
+                return Err(Error::DuplicateBundleMember { index });
+            }
+        }
+
+        Ok(Self(transactions))
+    }
+
+    /// Get the transactions of the bundle, in their order of execution.
+    #[expect(clippy::missing_const_for_fn, reason = "false positive")]
+    #[must_use]
+    pub fn transactions(&self) -> &[Transaction] {
+        &self.0
+    }
+
+    /// Get the transactions of the bundle, in their order of execution.
+    #[must_use]
+    pub fn into_transactions(self) -> Vec<Transaction> {
+        self.0
+    }
+
+    /// Get the number of transactions in the bundle.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Whether the bundle holds no transaction (never true once built).
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Estimates the serialized size of the transactions of the bundle.
+    #[must_use]
+    pub fn size(&self) -> usize {
+        self.0
+            .iter()
+            .map(|transaction| estimate_size(transaction.message()))
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::assert_matches::assert_matches;
+
+    use test_log::test;
+
+    use super::*;
+    use crate::{crypto::Keypair, program::system};
+
+    type TestResult = core::result::Result<(), Box<dyn core::error::Error>>;
+
+    fn transfer(
+        payer: &Keypair,
+        amount: u64,
+    ) -> core::result::Result<Transaction, Box<dyn core::error::Error>> {
+        let mut trx = Transaction::new(0);
+        trx.add(&[system::instruction::transfer(
+            payer.pubkey(),
+            Keypair::generate().pubkey(),
+            amount,
+        )?])?;
+        trx.sign(payer)?;
+
+        Ok(trx)
+    }
+
+    #[test]
+    fn bundles_hold_distinct_transactions() -> TestResult {
+        // Given
+        let payer = Keypair::generate();
+        let first = transfer(&payer, 10)?;
+        let second = transfer(&payer, 20)?;
+        let size = estimate_size(first.message()) + estimate_size(second.message());
+
+        // When
+        let empty = Bundle::new(Vec::new());
+        let duplicated = Bundle::new(vec![first.clone(), second.clone(), first.clone()]);
+        let bundle = Bundle::new(vec![first, second])?;
+
+        // Then
+        assert_matches!(empty, Err(Error::EmptyBundle));
+        assert_matches!(duplicated, Err(Error::DuplicateBundleMember { index: 2 }));
+        assert_eq!(bundle.len(), 2);
+        assert_eq!(bundle.size(), size);
+
+        Ok(())
+    }
+}
@@ -3,7 +3,7 @@
 // Creation date: Saturday 08 February 2025
 // Author: Vincent Berthier <vincent.berthier@posteo.org>
 // -----
-// Last modified: Sunday 09 February 2025 @ 16:42:31
+// Last modified: Thursday 15 October 2026 @ 17:23:26
 // Modified by: Vincent Berthier
 // -----
 // Copyright (c) 2025 <Vincent Berthier>
@@ -34,9 +34,64 @@ use crate::crypto::Pubkey;
 #[derive(Debug, Display, From)]
 #[display("while handling a transaction: {_variant}")]
 pub enum Error {
+    /// An instruction references an account the message doesn't hold.
+    #[display("account {index} is out of bounds (the message holds {count} accounts)")]
+    AccountOutOfBounds {
+        /// The position of the account.
+        index: usize,
+        /// The number of accounts in the message.
+        count: usize,
+    },
+    /// The same transaction was added twice to a bundle.
+    #[display("transaction {index} is already in the bundle")]
+    DuplicateBundleMember {
+        /// The position of the duplicate.
+        index: usize,
+    },
+    /// The same signature was given twice.
+    #[display("the transaction holds the same signature twice")]
+    DuplicateSignature,
+    /// A bundle must hold at least one transaction.
+    #[display("the bundle holds no transaction")]
+    EmptyBundle,
+    /// There's no instruction at the requested position.
+    #[display("instruction {index} is out of bounds (the message holds {count} instructions)")]
+    InstructionOutOfBounds {
+        /// The position of the instruction.
+        index: usize,
+        /// The number of instructions in the message.
+        count: usize,
+    },
+    /// The message doesn't pass the sanity checks.
+    #[display("the message is not valid")]
+    InvalidMessage,
+    /// The bytes aren't the canonical encoding of a message.
+    #[display("the message bytes are malformed")]
+    MalformedMessage,
+    /// A placeholder of a template is declared or bound more than once.
+    #[display("the placeholder '{name}' is given twice")]
+    DuplicatePlaceholder {
+        /// The name of the placeholder.
+        name: String,
+    },
+    /// The message was signed, changing it would invalidate the signatures.
+    #[display("the message is frozen, it can't be changed once signed")]
+    MessageFrozen,
     /// The transaction is not signed at all.
     #[display("the transaction has no signer")]
     NoSignersOnTransaction,
+    /// Tried to remove a privilege the payer needs to pay for the transaction.
+    #[display("the payer '{key}' must stay signing and writable")]
+    RestrictedPayer {
+        /// The public key of the payer.
+        key: Pubkey,
+    },
+    /// The transaction holds more instructions than allowed.
+    #[display("a transaction can't hold more than {max} instructions")]
+    TooManyInstructions {
+        /// The maximum number of instructions.
+        max: usize,
+    },
     /// The transaction has the wrong number of signatures
     #[display("wrong number of signatures: expected '{expected}', but got '{actual}'")]
     WrongNumberOfSignatures {
@@ -48,6 +103,30 @@ pub enum Error {
     /// At least one signature doesn't match a signer (or vice-versa)
     #[display("mismatch between signers and signatures")]
     SignaturesMismatch,
+    /// A placeholder of a template wasn't given a value.
+    #[display("the placeholder '{name}' isn't bound")]
+    UnboundPlaceholder {
+        /// The name of the placeholder.
+        name: String,
+    },
+    /// A value was given for a placeholder the template doesn't have.
+    #[display("the template has no placeholder '{name}'")]
+    UnknownPlaceholder {
+        /// The name given.
+        name: String,
+    },
+    /// A placeholder isn't used by the instructions of its template.
+    #[display("the placeholder '{name}' isn't used by the instructions")]
+    UnusedPlaceholder {
+        /// The name of the placeholder.
+        name: String,
+    },
+    /// The value given for a placeholder doesn't have its type.
+    #[display("the value given for the placeholder '{name}' has the wrong type")]
+    WrongPlaceholderType {
+        /// The name of the placeholder.
+        name: String,
+    },
     /// There was an attempt to sign from an account that's not a signer.
     #[display("'{key}' is not a signing account on this transaction")]
     UnexpectedSigner {
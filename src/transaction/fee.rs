@@ -0,0 +1,361 @@
+// File: src/transaction/fee.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 16:45:14
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use core::fmt::Debug;
+
+use ed25519_dalek::SIGNATURE_LENGTH;
+
+use super::message::Message;
+
+/// The default fee paid for each signature of a transaction.
+pub const FEE_PER_SIGNATURE: u64 = 5_000;
+
+/// The parameters of the fee paid by a transaction.
+///
+/// The same structure is used by the validator to charge transactions
+/// and by clients to estimate the fee before sending them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeeStructure {
+    /// The fee paid by every transaction.
+    pub base_fee: u64,
+    /// The fee paid for each signature of the transaction.
+    pub per_signature: u64,
+    /// The fee paid for each byte of the serialized transaction.
+    pub per_byte: u64,
+    /// The percentage of the fee removed from the supply, the rest being paid
+    /// to the block producer (values above 100 burn the whole fee).
+    pub burn_percent: u8,
+}
+
+/// How a fee is shared between the supply and the block producer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FeeSplit {
+    /// The prisms removed from the supply.
+    pub burned: u64,
+    /// The prisms paid to the block producer.
+    pub producer: u64,
+}
+
+impl FeeStructure {
+    /// Splits a fee between the burned part and the block producer's part.
+    ///
+    /// The burned part is rounded down, so that the producer gets the remainder.
+    ///
+    /// # Parameters
+    /// * `fee` - The fee paid by a transaction.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use bifrost::transaction::{FeeSplit, FeeStructure};
+    /// let fees = FeeStructure { burn_percent: 50, ..FeeStructure::default() };
+    /// assert_eq!(fees.split(5), FeeSplit { burned: 2, producer: 3 });
+    /// ```
+    #[must_use]
+    #[expect(
+        clippy::cast_possible_truncation,
+        reason = "the burned part is at most the fee"
+    )]
+    #[expect(clippy::integer_division, reason = "rounding down is intended")]
+    pub fn split(&self, fee: u64) -> FeeSplit {
+        let percent = u128::from(self.burn_percent.min(100));
+        let burned = (u128::from(fee) * percent / 100) as u64;
+
+        FeeSplit {
+            burned,
+            producer: fee - burned,
+        }
+    }
+}
+
+impl Default for FeeStructure {
+    fn default() -> Self {
+        Self {
+            base_fee: 0,
+            per_signature: FEE_PER_SIGNATURE,
+            per_byte: 0,
+            burn_percent: 100,
+        }
+    }
+}
+
+/// Computes the fee charged to transactions.
+///
+/// The validator charges the fee given by its model, and clients can estimate
+/// the fee of a transaction by calling the same model.
+pub trait FeeModel: Debug + Send + Sync {
+    /// Computes the fee paid by the transaction of a message.
+    ///
+    /// # Parameters
+    /// * `message` - The message of the transaction,
+    /// * `queue_pressure` - How loaded the validator is: the number of transactions
+    ///   waiting to be executed, in batches (0 when idle).
+    fn fee(&self, message: &Message, queue_pressure: f64) -> u64;
+}
+
+impl FeeModel for FeeStructure {
+    /// The fee of [`estimate_fee`], regardless of the load.
+    fn fee(&self, message: &Message, _queue_pressure: f64) -> u64 {
+        estimate_fee(message, self)
+    }
+}
+
+/// A fee growing with the load of the validator.
+///
+/// The fee of the base structure is multiplied by a factor growing linearly from 1
+/// (no pending transaction) to `max_multiplier` (`full_at` batches pending or more).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CongestionFees {
+    /// The fee charged when the validator is idle.
+    pub base: FeeStructure,
+    /// The multiplier of the fee when the validator is fully loaded.
+    pub max_multiplier: u64,
+    /// The queue pressure from which the validator is considered fully loaded.
+    pub full_at: f64,
+}
+
+impl Default for CongestionFees {
+    fn default() -> Self {
+        Self {
+            base: FeeStructure::default(),
+            max_multiplier: 10,
+            full_at: 4.0,
+        }
+    }
+}
+
+impl FeeModel for CongestionFees {
+    #[expect(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        reason = "the load is clamped between 0 and 1000"
+    )]
+    #[expect(clippy::integer_division, reason = "rounding down is intended")]
+    fn fee(&self, message: &Message, queue_pressure: f64) -> u64 {
+        let base = estimate_fee(message, &self.base);
+        let load = if self.full_at > 0.0_f64 {
+            (queue_pressure / self.full_at).clamp(0.0_f64, 1.0_f64)
+        } else {
+            1.0_f64
+        };
+        let permille = (load * 1_000.0).round() as u128;
+        let extra =
+            u128::from(base) * u128::from(self.max_multiplier.saturating_sub(1)) * permille / 1_000;
+
+        u64::try_from(u128::from(base) + extra).unwrap_or(u64::MAX)
+    }
+}
+
+/// Computes the fee paid by the transaction of a message.
+///
+/// # Parameters
+/// * `message` - The message of the transaction,
+/// * `fees` - The fee parameters.
+///
+/// # Example
+/// ```rust
+/// # use bifrost::{
+/// #     Error,
+/// #     crypto::Keypair,
+/// #     program::system,
+/// #     transaction::{estimate_fee, FeeStructure, Transaction, FEE_PER_SIGNATURE},
+/// # };
+/// let payer = Keypair::generate();
+/// let mut trx = Transaction::new(0);
+/// trx.add(&[system::instruction::transfer(payer.pubkey(), Keypair::generate().pubkey(), 10)?])?;
+/// assert_eq!(estimate_fee(trx.message(), &FeeStructure::default()), FEE_PER_SIGNATURE);
+/// # Ok::<(), Error>(())
+/// ```
+#[must_use]
+pub fn estimate_fee(message: &Message, fees: &FeeStructure) -> u64 {
+    let signatures = message
+        .accounts()
+        .iter()
+        .filter(|meta| meta.is_signing())
+        .count() as u64;
+    let size = estimate_size(message) as u64;
+
+    fees.base_fee
+        .saturating_add(fees.per_signature.saturating_mul(signatures))
+        .saturating_add(fees.per_byte.saturating_mul(size))
+}
+
+/// Computes the size of the serialized transaction of a message, once signed.
+///
+/// # Parameters
+/// * `message` - The message of the transaction.
+#[must_use]
+pub fn estimate_size(message: &Message) -> usize {
+    let signatures = message
+        .accounts()
+        .iter()
+        .filter(|meta| meta.is_signing())
+        .count();
+
+    // the signatures are prefixed by their number, as a u32
+    size_of::<u32>() + signatures * SIGNATURE_LENGTH + message.to_vec().len()
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use rand::{Rng as _, SeedableRng as _};
+    use rand_chacha::ChaCha20Rng;
+    use test_log::test;
+
+    use crate::crypto::Keypair;
+    use crate::program::{memo, system};
+    use crate::transaction::Transaction;
+
+    use super::*;
+    type TestResult = core::result::Result<(), Box<dyn core::error::Error>>;
+
+    #[test]
+    fn size_matches_signed_transaction() -> TestResult {
+        // Given
+        let payer = Keypair::generate();
+        let signer = Keypair::generate();
+        let mut trx = Transaction::new(0);
+        trx.add(&[
+            system::instruction::transfer(payer.pubkey(), signer.pubkey(), 10)?,
+            memo::instruction::memo("hello", &[payer.pubkey(), signer.pubkey()])?,
+        ])?;
+        let estimate = estimate_size(trx.message());
+
+        // When
+        trx.sign(&payer)?;
+        trx.sign(&signer)?;
+
+        // Then
+        assert_eq!(estimate, borsh::to_vec(&trx)?.len());
+
+        Ok(())
+    }
+
+    #[test]
+    fn fee_adds_every_component() -> TestResult {
+        // Given
+        let payer = Keypair::generate();
+        let signer = Keypair::generate();
+        let mut trx = Transaction::new(0);
+        trx.add(&[memo::instruction::memo(
+            "hello",
+            &[payer.pubkey(), signer.pubkey()],
+        )?])?;
+        let fees = FeeStructure {
+            base_fee: 100,
+            per_signature: 10,
+            per_byte: 1,
+            burn_percent: 0,
+        };
+
+        // When
+        let fee = estimate_fee(trx.message(), &fees);
+
+        // Then
+        assert_eq!(fee, 100 + 2 * 10 + estimate_size(trx.message()) as u64);
+
+        Ok(())
+    }
+
+    #[test]
+    fn read_only_signers_pay_for_their_signature() -> TestResult {
+        // Given
+        let payer = Keypair::generate();
+        let witness = Keypair::generate();
+        let mut trx = Transaction::new(0);
+        trx.add(&[
+            system::instruction::transfer(payer.pubkey(), Keypair::generate().pubkey(), 10)?,
+            memo::instruction::memo("witnessed", &[witness.pubkey()])?,
+        ])?;
+
+        // When
+        let fee = estimate_fee(trx.message(), &FeeStructure::default());
+
+        // Then
+        assert!(!trx.message().accounts()[1].is_writable());
+        assert_eq!(fee, 2 * FEE_PER_SIGNATURE);
+
+        Ok(())
+    }
+
+    #[test]
+    fn split_fees_add_up() {
+        // Given
+        let mut rng = ChaCha20Rng::seed_from_u64(685);
+
+        for _ in 0..10_000_u16 {
+            let fees = FeeStructure {
+                burn_percent: rng.gen_range(0..=120),
+                ..FeeStructure::default()
+            };
+            let fee = if rng.gen_bool(0.1) {
+                u64::MAX - rng.gen_range(0..1_000)
+            } else {
+                rng.gen_range(0..1_000_000)
+            };
+
+            // When
+            let split = fees.split(fee);
+
+            // Then
+            assert_eq!(split.burned + split.producer, fee);
+            let percent = u128::from(fees.burn_percent.min(100));
+            assert!(u128::from(split.burned) * 100 <= u128::from(fee) * percent);
+            assert!(u128::from(split.burned + 1) * 100 > u128::from(fee) * percent);
+        }
+    }
+
+    #[test]
+    fn congestion_fee_follows_its_curve() -> TestResult {
+        // Given
+        let payer = Keypair::generate();
+        let mut trx = Transaction::new(0);
+        trx.add(&[system::instruction::transfer(
+            payer.pubkey(),
+            Keypair::generate().pubkey(),
+            10,
+        )?])?;
+        let model = CongestionFees::default();
+
+        // When
+        let curve =
+            [-1.0, 0.0, 1.0, 2.0, 3.0, 4.0, 8.0].map(|pressure| model.fee(trx.message(), pressure));
+
+        // Then
+        assert_eq!(
+            curve,
+            [5_000, 5_000, 16_250, 27_500, 38_750, 50_000, 50_000]
+        );
+        assert_eq!(
+            FeeStructure::default().fee(trx.message(), 8.0),
+            FEE_PER_SIGNATURE
+        );
+
+        Ok(())
+    }
+}
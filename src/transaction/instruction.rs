@@ -3,7 +3,7 @@
 // Creation date: Saturday 08 February 2025
 // Author: Vincent Berthier <vincent.berthier@posteo.org>
 // -----
-// Last modified: Sunday 09 February 2025 @ 16:15:10
+// Last modified: Thursday 15 October 2026 @ 18:18:00
 // Modified by: Vincent Berthier
 // -----
 // Copyright (c) 2025 <Vincent Berthier>
@@ -28,7 +28,11 @@
 
 use borsh::{BorshDeserialize, BorshSerialize};
 
-use crate::{account::AccountMeta, crypto::Pubkey};
+use crate::{
+    account::AccountMeta,
+    crypto::Pubkey,
+    layout::{DescribeLayout, Extent, Field, Size},
+};
 
 /// An instruction compiled and ready to be executed on the blockchain.
 #[derive(Clone, Debug, Default, BorshSerialize, BorshDeserialize)]
@@ -93,6 +97,15 @@ impl Instruction {
         }
     }
 
+    /// Create an instruction from an already serialized payload.
+    pub(super) fn from_raw(program_id: Pubkey, accounts: Vec<AccountMeta>, data: Vec<u8>) -> Self {
+        Self {
+            program_id,
+            accounts,
+            data,
+        }
+    }
+
     /// Get the instruction's payload
     #[mutants::skip]
     #[must_use]
@@ -129,3 +142,25 @@ impl CompiledInstruction {
         }
     }
 }
+
+impl DescribeLayout for CompiledInstruction {
+    const NAME: &'static str = "CompiledInstruction";
+    const DESCRIPTION: &'static str =
+        "An instruction of a message, referring to its accounts by their index in the message.";
+
+    fn fields() -> Vec<Field> {
+        vec![
+            Field::new("program_account_id", "u8", Size::Fixed(1)),
+            Field::new("data", "Vec<u8>", Size::items(1)),
+            Field::new("accounts", "Vec<u8>", Size::items(1)),
+        ]
+    }
+
+    fn extents(&self) -> Vec<Extent> {
+        vec![
+            Extent::Fixed,
+            Extent::Count(self.data.len()),
+            Extent::Count(self.accounts.len()),
+        ]
+    }
+}
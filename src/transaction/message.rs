@@ -3,7 +3,7 @@
 // Creation date: Saturday 08 February 2025
 // Author: Vincent Berthier <vincent.berthier@posteo.org>
 // -----
-// Last modified: Thursday 13 February 2025 @ 10:01:24
+// Last modified: Thursday 15 October 2026 @ 20:31:20
 // Modified by: Vincent Berthier
 // -----
 // Copyright (c) 2025 <Vincent Berthier>
@@ -29,49 +29,198 @@
 #![expect(clippy::cast_possible_truncation)]
 
 use borsh::{BorshDeserialize, BorshSerialize};
-use tracing::{debug, instrument, trace};
+use sha2::{Digest as _, Sha256};
+use tracing::{debug, instrument, warn};
 
-use crate::{account::AccountMeta, crypto::Pubkey};
+use crate::{
+    account::{find_or_add, AccountMeta, AccountPrivileges, Privilege},
+    crypto::{Pubkey, DIGEST_LENGTH},
+    layout::{DescribeLayout, Extent, Field, Size},
+    program::{
+        memo::MEMO_PROGRAM,
+        system::{transfer_amount, SYSTEM_PROGRAM},
+        Slot,
+    },
+    validator::BlockHash,
+};
 
 use super::{
     instruction::{CompiledInstruction, Instruction},
-    Result,
+    Error, Result, MAX_INSTRUCTIONS_PER_TRANSACTION,
 };
 
+/// An account of an instruction, with the flags it has in the whole message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ResolvedAccountMeta {
+    /// The position of the account in the message's accounts.
+    pub index: usize,
+    /// The public key of the account.
+    pub key: Pubkey,
+    /// The privileges of the account in the message.
+    pub privileges: AccountPrivileges,
+}
+
+/// The fields of a message a signing device with a small screen can display.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DisplayFields {
+    /// The account paying for the transaction.
+    pub payer: Option<Pubkey>,
+    /// The prisms transferred out of the payer, when only plain transfers (and memos) are invoked.
+    pub outgoing: Option<u64>,
+    /// The programs invoked, in their order of first invocation.
+    pub programs: Vec<Pubkey>,
+}
+
+/// The compiled instructions of a transaction, along with the accounts they reference.
+///
+/// Its serialization is what the signers of the transaction sign.
 #[non_exhaustive]
 #[derive(Clone, Debug, BorshSerialize, BorshDeserialize)]
 pub struct Message {
     /// Slot at which the transaction was created
-    slot: u64,
+    slot: Slot,
+    /// The position of the transaction among those of its payer, if they must be executed in order.
+    sequence: Option<u64>,
+    /// The last slot at which the transaction may be executed, if any.
+    deadline: Option<Slot>,
+    /// The chain id (genesis hash) of the network the transaction is meant for, if any.
+    chain: Option<BlockHash>,
     /// The instruction of a transaction.
     pub instructions: Vec<CompiledInstruction>,
     /// List of accounts referenced by the transaction's instructions.
     pub accounts: Vec<AccountMeta>,
+    /// Whether the message was signed, and can't be changed anymore.
+    #[borsh(skip)]
+    frozen: bool,
 }
 
 impl Message {
-    pub const fn new(slot: u64) -> Self {
+    /// Create an empty message.
+    ///
+    /// # Parameters
+    /// * `slot` - the slot at which (or after which) the transaction was created,
+    #[must_use]
+    pub fn new<S>(slot: S) -> Self
+    where
+        S: Into<Slot>,
+    {
         Self {
-            slot,
+            slot: slot.into(),
+            sequence: None,
+            deadline: None,
+            chain: None,
             instructions: Vec::new(),
             accounts: Vec::new(),
+            frozen: false,
+        }
+    }
+
+    /// The slot at which the transaction was created.
+    #[must_use]
+    pub const fn slot(&self) -> Slot {
+        self.slot
+    }
+
+    pub(super) const fn set_slot(&mut self, slot: Slot) {
+        self.slot = slot;
+    }
+
+    /// The sequence number of the transaction, if it must be executed
+    /// right after the previous one of its payer.
+    #[must_use]
+    pub const fn sequence(&self) -> Option<u64> {
+        self.sequence
+    }
+
+    pub(super) fn set_sequence(&mut self, sequence: u64) -> Result<()> {
+        self.check_unfrozen()?;
+        self.sequence = Some(sequence);
+
+        Ok(())
+    }
+
+    /// The last slot at which the transaction may be executed, if it has a deadline.
+    #[must_use]
+    pub const fn deadline(&self) -> Option<Slot> {
+        self.deadline
+    }
+
+    pub(super) fn set_deadline(&mut self, deadline: Slot) -> Result<()> {
+        self.check_unfrozen()?;
+        self.deadline = Some(deadline);
+
+        Ok(())
+    }
+
+    /// The chain id of the network the transaction is meant for, if it's bound to one.
+    #[must_use]
+    pub const fn chain(&self) -> Option<BlockHash> {
+        self.chain
+    }
+
+    pub(super) fn set_chain(&mut self, chain: BlockHash) -> Result<()> {
+        self.check_unfrozen()?;
+        self.chain = Some(chain);
+
+        Ok(())
+    }
+
+    /// Forbids any further change to the message, done when it's first signed.
+    ///
+    /// The signatures, the signing digest and the bytes of the message all rely on
+    /// them never changing afterwards.
+    pub const fn freeze(&mut self) {
+        self.frozen = true;
+    }
+
+    /// Whether the message can't be changed anymore.
+    #[must_use]
+    pub const fn is_frozen(&self) -> bool {
+        self.frozen
+    }
+
+    /// Allows changing the message again, once its signatures were dropped.
+    pub(super) const fn unfreeze(&mut self) {
+        self.frozen = false;
+    }
+
+    fn check_unfrozen(&self) -> Result<()> {
+        if self.frozen {
+            warn!("tried to change a frozen message");
+            return Err(Error::MessageFrozen);
         }
+
+        Ok(())
     }
 
-    #[instrument(skip(self))]
-    pub fn get_payer(&self) -> Option<Pubkey> {
-        debug!("getting transaction payer account");
+    /// The account paying for the transaction: the first signing account,
+    /// which is writable unless every signer is read-only.
+    pub fn payer(&self) -> Option<&Pubkey> {
         self.accounts
             .iter()
             .find(|acc| acc.is_signing())
-            .map(|acc| *acc.key())
+            .map(AccountMeta::key)
     }
 
+    /// Compiles an instruction and appends it to the message.
+    ///
+    /// The accounts of the message are kept in their canonical order: the writable signers
+    /// (the payer first), the read-only signers, the other writable accounts, the read-only
+    /// ones and finally the programs, each in the order the instructions first reference them.
+    /// The same instructions thus always compile to the same bytes, whichever instruction
+    /// grants an account its privileges.
+    ///
+    /// # Errors
+    /// If the message is frozen, or if an account of the instruction is already in the
+    /// message with an incompatible type.
     #[instrument(skip_all)]
     pub fn add_instruction(&mut self, instruction: &Instruction) -> Result<()> {
         debug!("adding instruction to the message");
+        self.check_unfrozen()?;
         let compiled = self.compile_instruction(instruction)?;
         self.instructions.push(compiled);
+        self.canonicalize();
+        self.mark_fee_payer();
 
         Ok(())
     }
@@ -94,52 +243,348 @@ impl Message {
         ))
     }
 
-    #[instrument(skip_all)]
     fn find_or_add_account(&mut self, account: &AccountMeta) -> Result<u8> {
-        if let Some(idx) = self.find_account(account.key()) {
-            trace!("account was found in position {idx} of the transaction accounts");
-            self.accounts[idx as usize].merge(account)?;
-            return Ok(idx);
+        Ok(find_or_add(&mut self.accounts, account)? as u8)
+    }
+
+    /// Get the positions of the accounts in their canonical order.
+    fn canonical_order(&self) -> Vec<u8> {
+        let mut order = Vec::with_capacity(self.accounts.len());
+        for instruction in &self.instructions {
+            for &id in instruction
+                .accounts
+                .iter()
+                .chain([&instruction.program_account_id])
+            {
+                if !order.contains(&id) {
+                    order.push(id);
+                }
+            }
         }
+        order.sort_by_key(|&id| self.accounts[id as usize].rank());
 
-        trace!("account wasn’t found in the transaction accounts");
-        let idx = self.accounts.len() as u8;
-        self.accounts.push(*account);
-        Ok(idx)
+        order
     }
 
-    #[instrument(skip_all, fields(?account))]
-    fn find_account(&mut self, account: &Pubkey) -> Option<u8> {
-        debug!("looking for account in transaction accounts");
-        self.accounts
-            .iter()
-            .position(|acc| acc.key() == account)
-            .map(|idx| idx as u8)
+    /// Sorts the accounts in their canonical order, updating the references of the instructions.
+    fn canonicalize(&mut self) {
+        let order = self.canonical_order();
+        let mut positions = vec![0; self.accounts.len()];
+        for (position, &id) in order.iter().enumerate() {
+            positions[id as usize] = position as u8;
+        }
+        self.accounts = order.iter().map(|&id| self.accounts[id as usize]).collect();
+        for instruction in &mut self.instructions {
+            for id in &mut instruction.accounts {
+                *id = positions[*id as usize];
+            }
+            instruction.program_account_id = positions[instruction.program_account_id as usize];
+        }
+    }
+
+    /// Whether the accounts are in their canonical order.
+    fn is_canonical(&self) -> bool {
+        self.canonical_order()
+            .into_iter()
+            .eq(0..self.accounts.len() as u8)
+    }
+
+    /// Gives the fee payer privilege to the payer, and to no other account.
+    fn mark_fee_payer(&mut self) {
+        let payer = self.accounts.iter().position(AccountMeta::is_signing);
+        for (i, meta) in self.accounts.iter_mut().enumerate() {
+            meta.set_fee_payer(Some(i) == payer);
+        }
+    }
+
+    /// Decodes a message received as bytes, such as one compiled by a coordinator.
+    ///
+    /// The message is only accepted if encoding it again gives back the exact same bytes,
+    /// so signing it signs the bytes received.
+    ///
+    /// # Parameters
+    /// * `bytes` - The serialized message.
+    ///
+    /// # Errors
+    /// If the bytes aren't the canonical encoding of a message, if the message is not valid,
+    /// if it holds the same account twice or if an instruction references an account the
+    /// message doesn't hold (or a program through an account that is not one).
+    #[instrument(skip_all, fields(n = bytes.len()))]
+    pub fn try_from_bytes(bytes: &[u8]) -> Result<Self> {
+        debug!("decoding a message");
+        let message = borsh::from_slice::<Self>(bytes).map_err(|err| {
+            warn!("the message can't be decoded: {err}");
+            Error::MalformedMessage
+        })?;
+        if message.to_vec() != bytes {
+            warn!("the message isn't canonically encoded");
+            return Err(Error::MalformedMessage);
+        }
+        message.sanitize()?;
+
+        Ok(message)
+    }
+
+    fn sanitize(&self) -> Result<()> {
+        if self.instructions.len() > MAX_INSTRUCTIONS_PER_TRANSACTION {
+            warn!(
+                n = self.instructions.len(),
+                "too many instructions in the message"
+            );
+            return Err(Error::TooManyInstructions {
+                max: MAX_INSTRUCTIONS_PER_TRANSACTION,
+            });
+        }
+        if !self.is_valid() {
+            warn!("the message is not valid");
+            return Err(Error::InvalidMessage);
+        }
+        if self.accounts.iter().enumerate().any(|(i, meta)| {
+            self.accounts[..i]
+                .iter()
+                .any(|other| other.key() == meta.key())
+        }) {
+            warn!("the message holds the same account twice");
+            return Err(Error::InvalidMessage);
+        }
+        for instruction in &self.instructions {
+            for &id in &instruction.accounts {
+                self.account(id)?;
+            }
+            if !self.account(instruction.program_account_id)?.is_program() {
+                warn!("an instruction invokes an account that isn't a program");
+                return Err(Error::InvalidMessage);
+            }
+        }
+        if !self.is_canonical() {
+            warn!("the accounts of the message aren't in their canonical order");
+            return Err(Error::InvalidMessage);
+        }
+
+        Ok(())
+    }
+
+    /// Removes a privilege from an account, for every instruction referencing it.
+    pub(super) fn restrict(&mut self, key: &Pubkey, privilege: Privilege) -> Result<()> {
+        self.check_unfrozen()?;
+        if let Some(meta) = self.accounts.iter_mut().find(|meta| meta.key() == key) {
+            *meta = meta.demote(privilege)?;
+        }
+        self.canonicalize();
+        self.mark_fee_payer();
+
+        Ok(())
     }
 
+    /// Serializes the message, as it is signed.
     #[expect(clippy::unwrap_used)]
+    #[must_use]
     pub fn to_vec(&self) -> Vec<u8> {
         borsh::to_vec(&self).unwrap()
     }
 
+    /// The digest of the exact bytes signed by the signers of the message.
+    #[must_use]
+    pub fn signing_digest(&self) -> [u8; DIGEST_LENGTH] {
+        Sha256::digest(self.to_vec()).into()
+    }
+
+    /// Extracts the fields a signing device can display.
+    #[must_use]
+    pub fn display_fields(&self) -> DisplayFields {
+        let payer = self.payer().copied();
+        let mut programs = Vec::new();
+        let mut outgoing = Some(0_u64);
+        for instruction in &self.instructions {
+            let program = *self.accounts[instruction.program_account_id as usize].key();
+            if !programs.contains(&program) {
+                programs.push(program);
+            }
+            let source = instruction
+                .accounts
+                .first()
+                .map(|&id| *self.accounts[id as usize].key());
+            outgoing = match (
+                program == SYSTEM_PROGRAM,
+                transfer_amount(&instruction.data),
+            ) {
+                (true, Some(amount)) if source == payer => {
+                    outgoing.and_then(|total| total.checked_add(amount))
+                }
+                (true, Some(_)) => outgoing,
+                _ if program == MEMO_PROGRAM => outgoing,
+                _ => None,
+            };
+        }
+
+        DisplayFields {
+            payer,
+            outgoing,
+            programs,
+        }
+    }
+
+    /// Checks that the message has instructions (at most [`MAX_INSTRUCTIONS_PER_TRANSACTION`])
+    /// and accounts, that no program is writable, and that the privileges of the accounts are
+    /// consistent (the payer alone paying the fees, from a writable account).
+    #[must_use]
     pub fn is_valid(&self) -> bool {
-        !self.instructions.is_empty() && !self.accounts.is_empty()
+        let payer = self.accounts.iter().position(AccountMeta::is_signing);
+        !self.instructions.is_empty()
+            && self.instructions.len() <= MAX_INSTRUCTIONS_PER_TRANSACTION
+            && !self.accounts.is_empty()
+            && self.accounts.iter().enumerate().all(|(i, meta)| {
+                let privileges = meta.privileges();
+                privileges.is_consistent()
+                    && !(privileges.is_executable() && privileges.is_writable())
+                    && privileges.is_fee_payer() == (Some(i) == payer)
+                    && (!privileges.is_fee_payer() || privileges.is_writable())
+            })
     }
 
+    /// Get the accounts referenced by the message's instructions.
     #[expect(clippy::missing_const_for_fn, reason = "false positive")]
+    #[must_use]
     pub fn accounts(&self) -> &[AccountMeta] {
         &self.accounts
     }
+
+    /// Get the accounts of an instruction, in the order the instruction references them.
+    ///
+    /// The flags are those of the message: an account made writable or signing by another
+    /// instruction is writable or signing for every instruction.
+    ///
+    /// # Parameters
+    /// * `index` - The position of the instruction in the message.
+    ///
+    /// # Errors
+    /// If there's no such instruction, or if it references an account the message doesn't hold.
+    pub fn instruction_accounts(&self, index: usize) -> Result<Vec<ResolvedAccountMeta>> {
+        self.instruction(index)?
+            .accounts
+            .iter()
+            .map(|&id| {
+                let meta = self.account(id)?;
+                Ok(ResolvedAccountMeta {
+                    index: id as usize,
+                    key: *meta.key(),
+                    privileges: meta.privileges(),
+                })
+            })
+            .collect()
+    }
+
+    /// Get the public key of the program executing an instruction.
+    ///
+    /// # Parameters
+    /// * `index` - The position of the instruction in the message.
+    ///
+    /// # Errors
+    /// If there's no such instruction, or if its program isn't in the message's accounts.
+    pub fn instruction_program_id(&self, index: usize) -> Result<&Pubkey> {
+        let instruction = self.instruction(index)?;
+        Ok(self.account(instruction.program_account_id)?.key())
+    }
+
+    /// Rebuilds the instructions the message was compiled from.
+    ///
+    /// The accounts have the flags they have in the whole message.
+    ///
+    /// # Errors
+    /// If an instruction references an account the message doesn't hold.
+    pub(super) fn decompile(&self) -> Result<Vec<Instruction>> {
+        self.instructions
+            .iter()
+            .map(|instruction| {
+                let accounts = instruction
+                    .accounts
+                    .iter()
+                    .map(|&id| {
+                        let mut meta = *self.account(id)?;
+                        meta.set_fee_payer(false);
+                        Ok(meta)
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(Instruction::from_raw(
+                    *self.account(instruction.program_account_id)?.key(),
+                    accounts,
+                    instruction.data.clone(),
+                ))
+            })
+            .collect()
+    }
+
+    fn instruction(&self, index: usize) -> Result<&CompiledInstruction> {
+        self.instructions
+            .get(index)
+            .ok_or(Error::InstructionOutOfBounds {
+                index,
+                count: self.instructions.len(),
+            })
+    }
+
+    fn account(&self, id: u8) -> Result<&AccountMeta> {
+        self.accounts
+            .get(id as usize)
+            .ok_or(Error::AccountOutOfBounds {
+                index: id as usize,
+                count: self.accounts.len(),
+            })
+    }
+}
+
+impl DescribeLayout for Message {
+    const NAME: &'static str = "Message";
+    const DESCRIPTION: &'static str =
+        "The compiled instructions of a transaction, as signed by its signers.";
+
+    fn fields() -> Vec<Field> {
+        vec![
+            Field::new("slot", "Slot", Size::Fixed(8)),
+            Field::new("sequence", "Option<u64>", Size::Optional(8)),
+            Field::new("deadline", "Option<Slot>", Size::Optional(8)),
+            Field::new("chain", "Option<BlockHash>", Size::Optional(64)),
+            Field::new(
+                "instructions",
+                "Vec<CompiledInstruction>",
+                Size::variable_items(),
+            ),
+            Field::new("accounts", "Vec<AccountMeta>", Size::items(33)),
+        ]
+    }
+
+    fn extents(&self) -> Vec<Extent> {
+        vec![
+            Extent::Fixed,
+            Extent::Present(self.sequence.is_some()),
+            Extent::Present(self.deadline.is_some()),
+            Extent::Present(self.chain.is_some()),
+            Extent::Items(
+                self.instructions
+                    .iter()
+                    .map(DescribeLayout::described_len)
+                    .collect(),
+            ),
+            Extent::Count(self.accounts.len()),
+        ]
+    }
 }
 
 #[cfg(test)]
 #[cfg_attr(coverage_nightly, coverage(off))]
 mod tests {
 
+    use std::assert_matches::assert_matches;
+    use std::collections::{HashMap, HashSet};
+
+    use ed25519_dalek::PUBLIC_KEY_LENGTH;
+    use rand::{Rng as _, SeedableRng as _};
+    use rand_chacha::ChaCha20Rng;
     use test_log::test;
 
-    use crate::account::Writable;
+    use crate::account::{normalize, Writable};
     use crate::crypto::Keypair;
+    use crate::program::{memo, stake, system};
 
     use super::*;
     type TestResult = core::result::Result<(), Box<dyn core::error::Error>>;
@@ -163,4 +608,518 @@ mod tests {
         assert!(!with_instruction.is_valid());
         Ok(())
     }
+
+    #[test]
+    fn signing_digest_covers_signed_bytes() -> TestResult {
+        // Given
+        let key = Keypair::generate();
+        let mut message = Message::new(0);
+        message.add_instruction(&system::instruction::transfer(
+            key.pubkey(),
+            Keypair::generate().pubkey(),
+            10,
+        )?)?;
+        let digest = message.signing_digest();
+        let signed = message.to_vec();
+
+        // When
+        message.add_instruction(&memo::instruction::memo("changed", &[key.pubkey()])?)?;
+
+        // Then
+        assert_eq!(digest.as_slice(), Sha256::digest(signed).as_slice());
+        assert_ne!(message.signing_digest(), digest);
+
+        Ok(())
+    }
+
+    #[test]
+    fn display_outgoing_transfers() -> TestResult {
+        // Given
+        let payer = Keypair::generate().pubkey();
+        let other = Keypair::generate().pubkey();
+        let mut message = Message::new(0);
+        message.add_instruction(&system::instruction::transfer(payer, other, 10)?)?;
+        message.add_instruction(&memo::instruction::memo("paying", &[payer])?)?;
+        message.add_instruction(&system::instruction::transfer(payer, other, 5)?)?;
+        message.add_instruction(&system::instruction::transfer(other, payer, 1)?)?;
+
+        // When
+        let fields = message.display_fields();
+
+        // Then
+        assert_eq!(
+            fields,
+            DisplayFields {
+                payer: Some(payer),
+                outgoing: Some(15),
+                programs: vec![SYSTEM_PROGRAM, MEMO_PROGRAM],
+            }
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_outgoing_with_other_programs() -> TestResult {
+        // Given
+        let payer = Keypair::generate().pubkey();
+        let mut message = Message::new(0);
+        message.add_instruction(&system::instruction::transfer(
+            payer,
+            Keypair::generate().pubkey(),
+            10,
+        )?)?;
+        message.add_instruction(&stake::instruction::deactivate(
+            stake::stake_address(&payer, 0)?,
+            payer,
+            1,
+        )?)?;
+
+        // When
+        let fields = message.display_fields();
+
+        // Then
+        assert_eq!(fields.outgoing, None);
+        assert_eq!(fields.programs.len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn writable_program_account_is_not_valid() -> TestResult {
+        // Given
+        let payer = Keypair::generate().pubkey();
+        let mut message = Message::new(0);
+        message.add_instruction(&system::instruction::transfer(
+            payer,
+            Keypair::generate().pubkey(),
+            10,
+        )?)?;
+        let program = message
+            .accounts
+            .iter()
+            .position(AccountMeta::is_program)
+            .ok_or("no program account")?;
+        // crafted on the wire: the last byte of a meta holds its privileges
+        let mut bytes = borsh::to_vec(&message.accounts[program])?;
+        *bytes.last_mut().ok_or("empty meta")? |= AccountPrivileges::WRITABLE.bits();
+        let valid = message.is_valid();
+
+        // When
+        message.accounts[program] = borsh::from_slice(&bytes)?;
+
+        // Then
+        assert!(valid);
+        assert!(message.accounts[program].is_writable());
+        assert!(!message.is_valid());
+
+        Ok(())
+    }
+
+    #[test]
+    fn only_the_payer_pays_the_fees() -> TestResult {
+        // Given
+        let first = Keypair::generate().pubkey();
+        let second = Keypair::generate().pubkey();
+        let mut message = Message::new(0);
+        message.add_instruction(&system::instruction::transfer(first, second, 1)?)?;
+        message.add_instruction(&memo::instruction::memo("two signers", &[first, second])?)?;
+        let paying = message
+            .accounts()
+            .iter()
+            .map(|meta| meta.privileges().is_fee_payer())
+            .collect::<Vec<_>>();
+        let mut forged = message.clone();
+        forged.accounts[1].set_fee_payer(true);
+
+        // When
+        message.restrict(&first, Privilege::Signing)?;
+        let res = Message::try_from_bytes(&forged.to_vec());
+
+        // Then
+        assert_eq!(paying, vec![true, false, false, false]);
+        // the new payer comes first, the demoted signer after it
+        assert_eq!(message.accounts()[0].key(), &second);
+        assert!(message.accounts()[0].privileges().is_fee_payer());
+        assert_eq!(message.accounts()[1].key(), &first);
+        assert!(!message.accounts()[1].privileges().is_fee_payer());
+        assert!(message.is_valid());
+        assert_matches!(res, Err(Error::InvalidMessage));
+        assert_eq!(
+            borsh::to_vec(&message.accounts()[0])?.len(),
+            PUBLIC_KEY_LENGTH + 1
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn read_only_signers_never_pay_the_fees() -> TestResult {
+        // Given
+        let payer = Keypair::generate().pubkey();
+        let witness = Keypair::generate().pubkey();
+        let mut witnessed = Message::new(0);
+        let mut unpaid = Message::new(0);
+
+        // When
+        witnessed.add_instruction(&memo::instruction::memo("witnessed", &[witness])?)?;
+        witnessed.add_instruction(&system::instruction::transfer(
+            payer,
+            Keypair::generate().pubkey(),
+            10,
+        )?)?;
+        unpaid.add_instruction(&memo::instruction::memo("unpaid", &[witness])?)?;
+
+        // Then
+        assert_eq!(witnessed.payer(), Some(&payer));
+        assert_eq!(witnessed.accounts()[1].key(), &witness);
+        assert!(witnessed.accounts()[1].is_signing() && !witnessed.accounts()[1].is_writable());
+        assert!(!witnessed.accounts()[1].privileges().is_fee_payer());
+        assert!(witnessed.is_valid());
+        assert_eq!(unpaid.payer(), Some(&witness));
+        assert!(!unpaid.is_valid());
+
+        Ok(())
+    }
+
+    #[test]
+    fn frozen_message_refuses_changes() -> TestResult {
+        // Given
+        let payer = Keypair::generate().pubkey();
+        let instruction = system::instruction::transfer(payer, Keypair::generate().pubkey(), 10)?;
+        let mut message = Message::new(3);
+        message.add_instruction(&instruction)?;
+        let bytes = message.to_vec();
+
+        // When
+        message.freeze();
+        let results = [
+            message.add_instruction(&instruction),
+            message.set_sequence(1),
+            message.set_deadline(Slot::new(5)),
+            message.restrict(&payer, Privilege::Writable),
+        ];
+
+        // Then
+        for res in results {
+            assert_matches!(res, Err(Error::MessageFrozen));
+        }
+        assert_eq!(message.to_vec(), bytes);
+        assert!(!Message::try_from_bytes(&bytes)?.is_frozen());
+
+        Ok(())
+    }
+
+    #[test]
+    fn decoded_message_keeps_its_bytes() -> TestResult {
+        // Given
+        let payer = Keypair::generate().pubkey();
+        let mut message = Message::new(3);
+        message.set_sequence(2)?;
+        message.add_instruction(&system::instruction::transfer(
+            payer,
+            Keypair::generate().pubkey(),
+            10,
+        )?)?;
+        let bytes = message.to_vec();
+
+        // When
+        let decoded = Message::try_from_bytes(&bytes)?;
+
+        // Then
+        assert_eq!(decoded.to_vec(), bytes);
+        assert_eq!(decoded.payer(), Some(&payer));
+        assert_eq!(decoded.sequence(), Some(2));
+
+        Ok(())
+    }
+
+    #[test]
+    fn malformed_messages_are_rejected() -> TestResult {
+        // Given
+        let payer = Keypair::generate().pubkey();
+        let mut message = Message::new(0);
+        message.add_instruction(&system::instruction::transfer(
+            payer,
+            Keypair::generate().pubkey(),
+            10,
+        )?)?;
+        let mut trailing = message.to_vec();
+        trailing.push(0);
+        let truncated = &message.to_vec()[..10];
+        let mut out_of_bounds = message.clone();
+        out_of_bounds.instructions[0].accounts.push(42);
+        let mut not_a_program = message.clone();
+        not_a_program.instructions[0].program_account_id = 0;
+        let mut duplicated = message.clone();
+        duplicated.accounts.push(duplicated.accounts[0]);
+        let mut too_many = message.clone();
+        too_many.instructions =
+            vec![message.instructions[0].clone(); MAX_INSTRUCTIONS_PER_TRANSACTION + 1];
+
+        // When
+        let results = [
+            Message::try_from_bytes(&trailing),
+            Message::try_from_bytes(truncated),
+            Message::try_from_bytes(&Message::new(0).to_vec()),
+            Message::try_from_bytes(&out_of_bounds.to_vec()),
+            Message::try_from_bytes(&not_a_program.to_vec()),
+            Message::try_from_bytes(&duplicated.to_vec()),
+            Message::try_from_bytes(&too_many.to_vec()),
+        ];
+
+        // Then
+        assert_matches!(
+            results,
+            [
+                Err(Error::MalformedMessage),
+                Err(Error::MalformedMessage),
+                Err(Error::InvalidMessage),
+                Err(Error::AccountOutOfBounds { index: 42, .. }),
+                Err(Error::InvalidMessage),
+                Err(Error::InvalidMessage),
+                Err(Error::TooManyInstructions {
+                    max: MAX_INSTRUCTIONS_PER_TRANSACTION
+                }),
+            ]
+        );
+
+        Ok(())
+    }
+
+    /// The program of an instruction, and its accounts with their signing and writable flags.
+    type Layout = (Pubkey, Vec<(Pubkey, bool, bool)>);
+
+    fn compile_layout(
+        layout: &[Layout],
+    ) -> core::result::Result<Message, Box<dyn core::error::Error>> {
+        let mut message = Message::new(0);
+        for (program, accounts) in layout {
+            let mut metas = Vec::new();
+            for &(key, signing, writable) in accounts {
+                let writable = if writable {
+                    Writable::Yes
+                } else {
+                    Writable::No
+                };
+                metas.push(if signing {
+                    AccountMeta::signing(key, writable)?
+                } else {
+                    AccountMeta::wallet(key, writable)?
+                });
+            }
+            message.add_instruction(&Instruction::new(*program, metas, &()))?;
+        }
+
+        Ok(message)
+    }
+
+    #[test]
+    fn compiled_bytes_do_not_depend_on_where_privileges_are_granted() -> TestResult {
+        // Given
+        let mut rng = ChaCha20Rng::seed_from_u64(725);
+        let keys = (0..5_u8)
+            .map(|_| Keypair::generate().pubkey())
+            .collect::<Vec<_>>();
+        let programs = [system::SYSTEM_PROGRAM, memo::MEMO_PROGRAM];
+
+        for _ in 0..64_u8 {
+            let mut layout = Vec::new();
+            for _ in 0..rng.gen_range(1..4_u8) {
+                let program = programs[rng.gen_range(0..programs.len())];
+                let accounts = (0..rng.gen_range(1..5_u8))
+                    .map(|_| {
+                        (
+                            keys[rng.gen_range(0..keys.len())],
+                            rng.gen_bool(0.3),
+                            rng.gen_bool(0.5),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                layout.push((program, accounts));
+            }
+            // the payer signs and pays from a writable account
+            layout[0].1[0] = (keys[0], true, true);
+            // every account gets all its privileges from its last occurrence instead
+            let mut granted = HashMap::new();
+            for &(key, signing, writable) in layout.iter().flat_map(|(_, accounts)| accounts) {
+                let entry = granted.entry(key).or_insert((false, false));
+                *entry = (entry.0 || signing, entry.1 || writable);
+            }
+            let mut moved = layout.clone();
+            let mut last = HashSet::new();
+            for (key, signing, writable) in moved
+                .iter_mut()
+                .rev()
+                .flat_map(|(_, accounts)| accounts.iter_mut().rev())
+            {
+                let privileges = if last.insert(*key) {
+                    granted[key]
+                } else {
+                    (false, false)
+                };
+                *signing = privileges.0;
+                *writable = privileges.1;
+            }
+            // When
+            let message = compile_layout(&layout)?;
+            let other = compile_layout(&moved)?;
+
+            // Then
+            assert_eq!(message.to_vec(), other.to_vec());
+            let ranks = message
+                .accounts()
+                .iter()
+                .map(AccountMeta::rank)
+                .collect::<Vec<_>>();
+            assert!(ranks.is_sorted(), "{ranks:?}");
+            Message::try_from_bytes(&message.to_vec())?;
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn accounts_out_of_canonical_order_are_refused() -> TestResult {
+        // Given
+        let payer = Keypair::generate().pubkey();
+        let mut message = Message::new(0);
+        message.add_instruction(&system::instruction::transfer(
+            payer,
+            Keypair::generate().pubkey(),
+            10,
+        )?)?;
+        let mut swapped = message.clone();
+        // the recipient and the program swap places, the instruction still references them
+        swapped.accounts.swap(1, 2);
+        let instruction = &mut swapped.instructions[0];
+        instruction.accounts[1] = 2;
+        instruction.program_account_id = 1;
+
+        // When
+        let res = Message::try_from_bytes(&swapped.to_vec());
+
+        // Then
+        Message::try_from_bytes(&message.to_vec())?;
+        assert_matches!(res, Err(Error::InvalidMessage));
+
+        Ok(())
+    }
+
+    #[test]
+    fn compiled_accounts_match_normalized_metas() -> TestResult {
+        // Given
+        let mut rng = ChaCha20Rng::seed_from_u64(682);
+        let keys = (0..5_u8)
+            .map(|_| Keypair::generate().pubkey())
+            .collect::<Vec<_>>();
+        let programs = [
+            system::SYSTEM_PROGRAM,
+            memo::MEMO_PROGRAM,
+            stake::STAKE_PROGRAM,
+        ];
+
+        for _ in 0..64_u8 {
+            let mut instructions = Vec::new();
+            for _ in 0..rng.gen_range(1..4_u8) {
+                let mut metas = Vec::new();
+                for _ in 0..rng.gen_range(1..5_u8) {
+                    let key = keys[rng.gen_range(0..keys.len())];
+                    let writable = if rng.gen_bool(0.5) {
+                        Writable::Yes
+                    } else {
+                        Writable::No
+                    };
+                    metas.push(if rng.gen_bool(0.3) {
+                        AccountMeta::signing(key, writable)?
+                    } else {
+                        AccountMeta::wallet(key, writable)?
+                    });
+                }
+                instructions.push(Instruction::new(
+                    programs[rng.gen_range(0..programs.len())],
+                    metas,
+                    &(),
+                ));
+            }
+
+            // When
+            let mut message = Message::new(0);
+            let mut flattened = Vec::new();
+            for instruction in &instructions {
+                message.add_instruction(instruction)?;
+                flattened.extend_from_slice(instruction.accounts());
+                flattened.push(AccountMeta::program(*instruction.program())?);
+            }
+            let normalized = normalize(&flattened)?;
+
+            // Then
+            let summary = |metas: &[AccountMeta]| {
+                metas
+                    .iter()
+                    .map(|meta| (*meta.key(), meta.is_signing(), meta.is_writable()))
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(summary(message.accounts()), summary(&normalized));
+            assert_eq!(
+                message.payer(),
+                normalized
+                    .iter()
+                    .find(|meta| meta.is_signing())
+                    .map(AccountMeta::key)
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn instruction_accounts_reflect_merged_flags() -> TestResult {
+        // Given
+        let from = Keypair::generate().pubkey();
+        let to = Keypair::generate().pubkey();
+        let mut message = Message::new(0);
+        message.add_instruction(&memo::instruction::memo("hello", &[to])?)?;
+        message.add_instruction(&system::instruction::transfer(from, to, 10)?)?;
+
+        // When
+        let accounts = message.instruction_accounts(0)?;
+        let program = message.instruction_program_id(0)?;
+
+        // Then
+        assert_eq!(program, &memo::MEMO_PROGRAM);
+        let to_meta = accounts
+            .iter()
+            .find(|meta| meta.key == to)
+            .ok_or("missing account")?;
+        assert!(to_meta.privileges.is_writable());
+        assert_eq!(message.accounts()[to_meta.index].key(), &to);
+        assert_eq!(message.instruction_program_id(1)?, &system::SYSTEM_PROGRAM);
+
+        Ok(())
+    }
+
+    #[test]
+    fn out_of_bounds_instruction_is_an_error() -> TestResult {
+        // Given
+        let key = Keypair::generate().pubkey();
+        let mut message = Message::new(0);
+        message.add_instruction(&memo::instruction::memo("hello", &[key])?)?;
+        message
+            .instructions
+            .push(CompiledInstruction::new(0, Vec::new(), vec![42]));
+
+        // When
+        let missing = message.instruction_accounts(2);
+        let dangling = message.instruction_accounts(1);
+
+        // Then
+        assert_matches!(
+            missing,
+            Err(Error::InstructionOutOfBounds { index: 2, count: 2 })
+        );
+        assert_matches!(dangling, Err(Error::AccountOutOfBounds { index: 42, .. }));
+
+        Ok(())
+    }
 }
@@ -3,7 +3,7 @@
 // Creation date: Saturday 08 February 2025
 // Author: Vincent Berthier <vincent.berthier@posteo.org>
 // -----
-// Last modified: Saturday 08 February 2025 @ 02:40:27
+// Last modified: Thursday 15 October 2026 @ 17:23:26
 // Modified by: Vincent Berthier
 // -----
 // Copyright (c) 2025 <Vincent Berthier>
@@ -26,13 +26,27 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
+mod bundle;
 mod error;
+mod fee;
 mod instruction;
 mod message;
+mod template;
 mod transaction;
 
+pub use bundle::Bundle;
 pub use error::Error;
 type Result<T> = core::result::Result<T, Error>;
 
+pub use fee::{
+    estimate_fee, estimate_size, CongestionFees, FeeModel, FeeSplit, FeeStructure,
+    FEE_PER_SIGNATURE,
+};
 pub use instruction::{CompiledInstruction, Instruction};
-pub use transaction::Transaction;
+pub use message::{DisplayFields, Message, ResolvedAccountMeta};
+pub use template::{
+    AnyPlaceholder, Placeholder, PlaceholderKind, PlaceholderValue, TransactionTemplate,
+};
+pub use transaction::{
+    SignerAccess, Transaction, TransactionSummary, MAX_INSTRUCTIONS_PER_TRANSACTION,
+};
@@ -0,0 +1,570 @@
+// File: src/transaction/template.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 17:23:26
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Transactions of the same shape, compiled once and instantiated with different values.
+
+use tracing::{debug, instrument, trace};
+
+use crate::{
+    crypto::{Keypair, Pubkey},
+    program::Slot,
+};
+
+use super::{Error, Instruction, Message, Result, Transaction};
+
+/// A value bound to a placeholder.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlaceholderValue {
+    /// The key of a wallet or of a signer.
+    Pubkey(Pubkey),
+    /// An amount, or any other integer of the payloads.
+    U64(u64),
+}
+
+impl PlaceholderValue {
+    /// The bytes of the value, as they are serialized in a payload.
+    #[expect(clippy::unwrap_used, reason = "keys and integers always serialize")]
+    fn to_bytes(self) -> Vec<u8> {
+        match self {
+            Self::Pubkey(key) => borsh::to_vec(&key),
+            Self::U64(value) => borsh::to_vec(&value),
+        }
+        .unwrap()
+    }
+
+    const fn has_type_of(self, other: Self) -> bool {
+        matches!(
+            (self, other),
+            (Self::Pubkey(_), Self::Pubkey(_)) | (Self::U64(_), Self::U64(_))
+        )
+    }
+}
+
+impl From<Pubkey> for PlaceholderValue {
+    fn from(key: Pubkey) -> Self {
+        Self::Pubkey(key)
+    }
+}
+
+impl From<u64> for PlaceholderValue {
+    fn from(value: u64) -> Self {
+        Self::U64(value)
+    }
+}
+
+/// A type a placeholder can stand for.
+pub trait PlaceholderKind: Copy + Into<PlaceholderValue> {
+    /// Get a random value, standing for the placeholder while the template is built.
+    fn stand_in() -> Self;
+}
+
+impl PlaceholderKind for Pubkey {
+    fn stand_in() -> Self {
+        Keypair::generate().pubkey()
+    }
+}
+
+impl PlaceholderKind for u64 {
+    fn stand_in() -> Self {
+        rand::random()
+    }
+}
+
+/// A value of a template given when it's instantiated.
+///
+/// The instructions of the template are built with its [stand-in](Self::value), which is
+/// found back in the accounts and the payloads they compile to. A key placeholder can only
+/// stand for a wallet or a signer.
+#[derive(Clone, Copy, Debug)]
+pub struct Placeholder<T> {
+    name: &'static str,
+    stand_in: T,
+}
+
+impl<T> Placeholder<T>
+where
+    T: PlaceholderKind,
+{
+    /// Creates a placeholder.
+    ///
+    /// # Parameters
+    /// * `name` - The name the placeholder is bound by.
+    #[must_use]
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            stand_in: T::stand_in(),
+        }
+    }
+
+    /// Get the value to build the instructions of the template with.
+    #[must_use]
+    pub const fn value(&self) -> T {
+        self.stand_in
+    }
+}
+
+/// A placeholder of any type, as a template declares it.
+#[derive(Clone, Copy, Debug)]
+pub struct AnyPlaceholder {
+    name: &'static str,
+    stand_in: PlaceholderValue,
+}
+
+impl<T> From<Placeholder<T>> for AnyPlaceholder
+where
+    T: PlaceholderKind,
+{
+    fn from(placeholder: Placeholder<T>) -> Self {
+        Self {
+            name: placeholder.name,
+            stand_in: placeholder.stand_in.into(),
+        }
+    }
+}
+
+/// Where the stand-in of a placeholder is in the compiled message.
+#[derive(Clone, Debug)]
+struct Site {
+    name: &'static str,
+    stand_in: PlaceholderValue,
+    /// The positions of the accounts it's the key of.
+    accounts: Vec<usize>,
+    /// The instructions whose payload holds it, with its offset in the payload.
+    payloads: Vec<(usize, usize)>,
+}
+
+/// A transaction whose instructions only differ by some values (such as a recipient and
+/// an amount), compiled once.
+///
+/// Instantiating it only patches the accounts and payload bytes of its placeholders, and
+/// gives the same transaction as building it from scratch with the values. When a key
+/// bound to a placeholder is already an account of the message, the accounts are merged
+/// so the instructions are compiled again.
+///
+/// # Example
+/// ```rust
+/// # use bifrost::{
+/// #     Error,
+/// #     crypto::{Keypair, Pubkey},
+/// #     program::system,
+/// #     transaction::{Placeholder, Transaction, TransactionTemplate},
+/// # };
+/// let payer = Keypair::generate();
+/// let recipient = Placeholder::<Pubkey>::new("recipient");
+/// let amount = Placeholder::<u64>::new("amount");
+/// let template = TransactionTemplate::new(
+///     &[system::instruction::transfer(payer.pubkey(), recipient.value(), amount.value())?],
+///     &[recipient.into(), amount.into()],
+/// )?;
+///
+/// let key = Keypair::generate().pubkey();
+/// let trx = template.instantiate(&[("recipient", key.into()), ("amount", 500.into())], 3)?;
+/// let mut expected = Transaction::new(3);
+/// expected.add(&[system::instruction::transfer(payer.pubkey(), key, 500)?])?;
+/// assert_eq!(trx.message().to_vec(), expected.message().to_vec());
+/// # Ok::<(), Error>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct TransactionTemplate {
+    /// The instructions, built with the stand-ins of the placeholders.
+    instructions: Vec<Instruction>,
+    /// The message the instructions compile to.
+    message: Message,
+    sites: Vec<Site>,
+}
+
+impl TransactionTemplate {
+    /// Compiles the instructions of a template.
+    ///
+    /// # Parameters
+    /// * `instructions` - The instructions, built with the stand-ins of the placeholders,
+    /// * `placeholders` - The placeholders of the template.
+    ///
+    /// # Errors
+    /// If the instructions can't be compiled together, or if a placeholder is declared
+    /// twice or isn't used by the instructions.
+    #[instrument(skip_all, fields(n = instructions.len()))]
+    pub fn new(instructions: &[Instruction], placeholders: &[AnyPlaceholder]) -> Result<Self> {
+        debug!("compiling a transaction template");
+        let mut transaction = Transaction::new(0);
+        transaction.add(instructions)?;
+        let message = transaction.message().clone();
+        let mut sites = Vec::with_capacity(placeholders.len());
+        for (i, placeholder) in placeholders.iter().enumerate() {
+            let name = placeholder.name;
+            if placeholders[..i].iter().any(|other| other.name == name) {
+                return Err(Error::DuplicatePlaceholder { name: name.into() });
+            }
+            let accounts = match placeholder.stand_in {
+                PlaceholderValue::Pubkey(key) => message
+                    .accounts
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, meta)| *meta.key() == key)
+                    .map(|(position, _)| position)
+                    .collect(),
+                PlaceholderValue::U64(_) => Vec::new(),
+            };
+            let bytes = placeholder.stand_in.to_bytes();
+            let payloads = message
+                .instructions
+                .iter()
+                .enumerate()
+                .flat_map(|(index, instruction)| {
+                    occurrences(&instruction.data, &bytes).map(move |offset| (index, offset))
+                })
+                .collect::<Vec<_>>();
+            if accounts.is_empty() && payloads.is_empty() {
+                return Err(Error::UnusedPlaceholder { name: name.into() });
+            }
+            sites.push(Site {
+                name,
+                stand_in: placeholder.stand_in,
+                accounts,
+                payloads,
+            });
+        }
+
+        Ok(Self {
+            instructions: instructions.to_vec(),
+            message,
+            sites,
+        })
+    }
+
+    /// Creates an unsigned transaction from the template.
+    ///
+    /// # Parameters
+    /// * `bindings` - The value of each placeholder, by name,
+    /// * `slot` - The slot at which (or after which) the transaction is created.
+    ///
+    /// # Errors
+    /// If a placeholder isn't bound (or is bound twice, or to a value of the wrong type),
+    /// if a value is given for a placeholder the template doesn't have, or if a key
+    /// doesn't suit the account it stands for.
+    #[instrument(skip_all)]
+    pub fn instantiate<S>(
+        &self,
+        bindings: &[(&str, PlaceholderValue)],
+        slot: S,
+    ) -> Result<Transaction>
+    where
+        S: Into<Slot>,
+    {
+        let values = self.bind(bindings)?;
+        if self.merges_accounts(&values) {
+            trace!("a bound key is already in the message, compiling it again");
+            return self.compile(&values, slot);
+        }
+        let mut message = self.message.clone();
+        message.set_slot(slot.into());
+        for (site, value) in self.sites.iter().zip(&values) {
+            if let PlaceholderValue::Pubkey(key) = *value {
+                for &position in &site.accounts {
+                    message.accounts[position] = message.accounts[position].with_key(key)?;
+                }
+            }
+            let bytes = value.to_bytes();
+            for &(index, offset) in &site.payloads {
+                message.instructions[index].data[offset..offset + bytes.len()]
+                    .copy_from_slice(&bytes);
+            }
+        }
+
+        Ok(Transaction::from_message(message))
+    }
+
+    /// Get the value bound to each placeholder, in the order they were declared.
+    fn bind(&self, bindings: &[(&str, PlaceholderValue)]) -> Result<Vec<PlaceholderValue>> {
+        for (i, (name, _)) in bindings.iter().enumerate() {
+            if bindings[..i].iter().any(|(other, _)| other == name) {
+                return Err(Error::DuplicatePlaceholder {
+                    name: (*name).into(),
+                });
+            }
+            if !self.sites.iter().any(|site| site.name == *name) {
+                return Err(Error::UnknownPlaceholder {
+                    name: (*name).into(),
+                });
+            }
+        }
+
+        self.sites
+            .iter()
+            .map(|site| {
+                let &(_, value) = bindings
+                    .iter()
+                    .find(|(name, _)| *name == site.name)
+                    .ok_or_else(|| Error::UnboundPlaceholder {
+                        name: site.name.into(),
+                    })?;
+                if !value.has_type_of(site.stand_in) {
+                    return Err(Error::WrongPlaceholderType {
+                        name: site.name.into(),
+                    });
+                }
+                Ok(value)
+            })
+            .collect()
+    }
+
+    /// Whether a bound key is the key of another account of the message, which the
+    /// compilation would merge.
+    fn merges_accounts(&self, values: &[PlaceholderValue]) -> bool {
+        let keys = self
+            .sites
+            .iter()
+            .zip(values)
+            .filter(|(site, _)| !site.accounts.is_empty())
+            .filter_map(|(_, value)| match value {
+                PlaceholderValue::Pubkey(key) => Some(*key),
+                PlaceholderValue::U64(_) => None,
+            })
+            .collect::<Vec<_>>();
+        let patched = self
+            .sites
+            .iter()
+            .flat_map(|site| site.accounts.iter().copied())
+            .collect::<Vec<_>>();
+
+        keys.iter().enumerate().any(|(i, key)| {
+            keys[..i].contains(key)
+                || self
+                    .message
+                    .accounts
+                    .iter()
+                    .enumerate()
+                    .any(|(position, meta)| meta.key() == key && !patched.contains(&position))
+        })
+    }
+
+    /// Builds the transaction from scratch, with the instructions holding the bound values.
+    fn compile<S>(&self, values: &[PlaceholderValue], slot: S) -> Result<Transaction>
+    where
+        S: Into<Slot>,
+    {
+        let instructions =
+            self.instructions
+                .iter()
+                .map(|instruction| {
+                    let accounts = instruction
+                        .accounts()
+                        .iter()
+                        .map(|meta| {
+                            let bound = self.sites.iter().zip(values).find_map(|(site, value)| {
+                                match (site.stand_in, *value) {
+                                    (
+                                        PlaceholderValue::Pubkey(stand_in),
+                                        PlaceholderValue::Pubkey(key),
+                                    ) if stand_in == *meta.key() => Some(key),
+                                    _ => None,
+                                }
+                            });
+                            bound.map_or(Ok(*meta), |key| meta.with_key(key))
+                        })
+                        .collect::<core::result::Result<Vec<_>, _>>()?;
+                    let mut data = instruction.data().to_vec();
+                    for (site, value) in self.sites.iter().zip(values) {
+                        let (stand_in, bytes) = (site.stand_in.to_bytes(), value.to_bytes());
+                        for offset in occurrences(instruction.data(), &stand_in) {
+                            data[offset..offset + bytes.len()].copy_from_slice(&bytes);
+                        }
+                    }
+                    Ok(Instruction::from_raw(
+                        *instruction.program(),
+                        accounts,
+                        data,
+                    ))
+                })
+                .collect::<Result<Vec<_>>>()?;
+        let mut transaction = Transaction::new(slot);
+        transaction.add(&instructions)?;
+
+        Ok(transaction)
+    }
+}
+
+/// Get the offsets at which some bytes appear in a payload.
+fn occurrences<'a>(data: &'a [u8], bytes: &'a [u8]) -> impl Iterator<Item = usize> + 'a {
+    data.windows(bytes.len())
+        .enumerate()
+        .filter(move |(_, window)| *window == bytes)
+        .map(|(offset, _)| offset)
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use std::assert_matches::assert_matches;
+
+    use test_log::test;
+
+    use crate::program::{memo, system};
+
+    use super::*;
+    type TestResult = core::result::Result<(), Box<dyn core::error::Error>>;
+
+    struct Payment {
+        payer: Keypair,
+        template: TransactionTemplate,
+    }
+
+    fn payment() -> core::result::Result<Payment, Box<dyn core::error::Error>> {
+        let payer = Keypair::generate();
+        let recipient = Placeholder::<Pubkey>::new("recipient");
+        let amount = Placeholder::<u64>::new("amount");
+        let template = TransactionTemplate::new(
+            &[
+                system::instruction::transfer(payer.pubkey(), recipient.value(), amount.value())?,
+                memo::instruction::memo("invoice", &[payer.pubkey()])?,
+            ],
+            &[recipient.into(), amount.into()],
+        )?;
+
+        Ok(Payment { payer, template })
+    }
+
+    fn from_scratch(
+        payer: &Keypair,
+        recipient: Pubkey,
+        amount: u64,
+    ) -> core::result::Result<Transaction, Box<dyn core::error::Error>> {
+        let mut trx = Transaction::new(7);
+        trx.add(&[
+            system::instruction::transfer(payer.pubkey(), recipient, amount)?,
+            memo::instruction::memo("invoice", &[payer.pubkey()])?,
+        ])?;
+        Ok(trx)
+    }
+
+    #[test]
+    fn instantiated_transaction_matches_one_built_from_scratch() -> TestResult {
+        // Given
+        let payment = payment()?;
+        let recipients = [Keypair::generate().pubkey(), Keypair::generate().pubkey()];
+
+        // When
+        let transactions = recipients
+            .iter()
+            .zip([500_u64, 12])
+            .map(|(&recipient, amount)| {
+                payment.template.instantiate(
+                    &[("amount", amount.into()), ("recipient", recipient.into())],
+                    7,
+                )
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let mut signed = transactions[0].clone();
+        signed.sign(&payment.payer)?;
+
+        // Then
+        for (trx, (&recipient, amount)) in transactions.iter().zip(recipients.iter().zip([500, 12]))
+        {
+            let expected = from_scratch(&payment.payer, recipient, amount)?;
+            assert_eq!(trx.message().to_vec(), expected.message().to_vec());
+        }
+        assert!(signed.is_valid());
+
+        Ok(())
+    }
+
+    #[test]
+    fn key_already_in_the_message_is_compiled_again() -> TestResult {
+        // Given
+        let payment = payment()?;
+        let payer = payment.payer.pubkey();
+
+        // When
+        let to_self = payment
+            .template
+            .instantiate(&[("recipient", payer.into()), ("amount", 10_u64.into())], 7)?;
+
+        // Then
+        let expected = from_scratch(&payment.payer, payer, 10)?;
+        assert_eq!(to_self.message().to_vec(), expected.message().to_vec());
+        assert_eq!(to_self.message().accounts.len(), 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn every_placeholder_must_be_bound_once() -> TestResult {
+        // Given
+        let payment = payment()?;
+        let key = PlaceholderValue::from(Keypair::generate().pubkey());
+        let amount = PlaceholderValue::from(10_u64);
+
+        // When
+        let unbound = payment.template.instantiate(&[("recipient", key)], 0);
+        let unknown = payment.template.instantiate(
+            &[("recipient", key), ("amount", amount), ("fee", amount)],
+            0,
+        );
+        let twice = payment.template.instantiate(
+            &[("recipient", key), ("amount", amount), ("amount", amount)],
+            0,
+        );
+        let mistyped = payment
+            .template
+            .instantiate(&[("recipient", amount), ("amount", amount)], 0);
+
+        // Then
+        assert_matches!(unbound, Err(Error::UnboundPlaceholder { name }) if name == "amount");
+        assert_matches!(unknown, Err(Error::UnknownPlaceholder { name }) if name == "fee");
+        assert_matches!(twice, Err(Error::DuplicatePlaceholder { name }) if name == "amount");
+        assert_matches!(
+            mistyped,
+            Err(Error::WrongPlaceholderType { name }) if name == "recipient"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn unused_placeholder_is_refused() -> TestResult {
+        // Given
+        let payer = Keypair::generate().pubkey();
+        let recipient = Placeholder::<Pubkey>::new("recipient");
+
+        // When
+        let res = TransactionTemplate::new(
+            &[system::instruction::transfer(
+                payer,
+                Keypair::generate().pubkey(),
+                10,
+            )?],
+            &[recipient.into()],
+        );
+
+        // Then
+        assert_matches!(res, Err(Error::UnusedPlaceholder { name }) if name == "recipient");
+
+        Ok(())
+    }
+}
@@ -3,7 +3,7 @@
 // Creation date: Saturday 08 February 2025
 // Author: Vincent Berthier <vincent.berthier@posteo.org>
 // -----
-// Last modified: Thursday 13 February 2025 @ 10:04:57
+// Last modified: Thursday 15 October 2026 @ 18:18:00
 // Modified by: Vincent Berthier
 // -----
 // Copyright (c) 2025 <Vincent Berthier>
@@ -29,9 +29,56 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 use tracing::{debug, instrument, trace, warn};
 
-use crate::crypto::{Keypair, Pubkey, Signature};
+use crate::{
+    account::Privilege,
+    crypto::{Keypair, Pubkey, Signature},
+    layout::{DescribeLayout, Extent, Field, Size},
+    program::{schema::SchemaRegistry, Slot},
+    validator::BlockHash,
+};
 
-use super::{instruction::Instruction, message::Message, Error, Result};
+use super::{
+    instruction::Instruction,
+    message::{DisplayFields, Message},
+    Error, Result,
+};
+
+/// Maximum number of instructions in a single transaction.
+pub const MAX_INSTRUCTIONS_PER_TRANSACTION: usize = 64;
+
+/// What a transaction can do to the account of one of its signers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SignerAccess {
+    /// The signature only authorizes the transaction: nothing of the signer's can change.
+    ReadOnly,
+    /// The transaction can modify the signer's account (its prisms or its data).
+    Writable,
+}
+
+/// What a wallet shows before signing a transaction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransactionSummary {
+    /// The fields a signing device can display.
+    pub fields: DisplayFields,
+    /// The signers of the transaction, the payer first, with what the transaction can do to them.
+    pub signers: Vec<(Pubkey, SignerAccess)>,
+}
+
+impl TransactionSummary {
+    /// Get what the transaction can do to the account of a signer.
+    ///
+    /// # Parameters
+    /// * `signer` - The public key of the signer.
+    ///
+    /// # Returns
+    /// The access of the signer, or `None` if it doesn't sign the transaction.
+    #[must_use]
+    pub fn access(&self, signer: &Pubkey) -> Option<SignerAccess> {
+        self.signers
+            .iter()
+            .find_map(|(key, access)| (key == signer).then_some(*access))
+    }
+}
 
 /// A transaction to execute (or executed) on the Bifrost blockchain.
 #[non_exhaustive]
@@ -49,23 +96,177 @@ impl Transaction {
     /// # Parameters
     /// * `slot` - the slot at which (or after which) the transaction was created,
     #[must_use]
-    pub const fn new(slot: u64) -> Self {
+    pub fn new<S>(slot: S) -> Self
+    where
+        S: Into<Slot>,
+    {
         Self {
             signatures: Vec::new(),
             message: Message::new(slot),
         }
     }
 
+    /// Wraps a message compiled elsewhere in an unsigned transaction.
+    ///
+    /// The message is kept as is: its accounts aren't reordered, so the bytes signed are
+    /// those the message was received as.
+    ///
+    /// # Parameters
+    /// * `message` - The compiled message, usually from [`Message::try_from_bytes`].
+    ///
+    /// # Example
+    /// ```rust
+    /// # use bifrost::{Error, crypto::Keypair, program::system, transaction::{Message, Transaction}};
+    /// # let payer = Keypair::generate();
+    /// # let mut original = Transaction::new(0);
+    /// # original.add(&[system::instruction::transfer(payer.pubkey(), Keypair::generate().pubkey(), 10)?])?;
+    /// # let bytes = original.message().to_vec();
+    /// let mut trx = Transaction::from_message(Message::try_from_bytes(&bytes)?);
+    /// trx.sign(&payer)?;
+    /// assert!(trx.is_valid());
+    /// # Ok::<(), Error>(())
+    /// ```
+    #[must_use]
+    pub const fn from_message(message: Message) -> Self {
+        Self {
+            signatures: Vec::new(),
+            message,
+        }
+    }
+
+    /// Rebuilds the transaction for a newer slot, when it expired before being executed.
+    ///
+    /// The instructions are compiled again, keeping their accounts and payloads (and the
+    /// sequence number and deadline, if any). The new transaction isn't signed.
+    ///
+    /// # Parameters
+    /// * `slot` - The slot at which the transaction is refreshed.
+    ///
+    /// # Errors
+    /// If the message is malformed (an instruction referencing a missing account).
+    ///
+    /// # Example
+    /// ```rust
+    /// # use bifrost::{Error, crypto::Keypair, program::{system, Slot}, transaction::Transaction};
+    /// # let payer = Keypair::generate();
+    /// let mut trx = Transaction::new(0);
+    /// trx.add(&[system::instruction::transfer(payer.pubkey(), Keypair::generate().pubkey(), 10)?])?;
+    /// trx.sign(&payer)?;
+    /// let mut refreshed = trx.refresh(10)?;
+    /// refreshed.sign(&payer)?;
+    /// assert_eq!(refreshed.message().slot(), Slot::new(10));
+    /// # Ok::<(), Error>(())
+    /// ```
+    #[instrument(skip(self, slot))]
+    pub fn refresh<S>(&self, slot: S) -> Result<Self>
+    where
+        S: Into<Slot>,
+    {
+        debug!("refreshing transaction");
+        let mut refreshed = Self::new(slot);
+        if let Some(sequence) = self.message.sequence() {
+            refreshed.message.set_sequence(sequence)?;
+        }
+        if let Some(deadline) = self.message.deadline() {
+            refreshed.message.set_deadline(deadline)?;
+        }
+        if let Some(chain) = self.message.chain() {
+            refreshed.message.set_chain(chain)?;
+        }
+        refreshed.add(&self.message.decompile()?)?;
+
+        Ok(refreshed)
+    }
+
+    /// Requires the transaction to be executed right after the previous one of its payer.
+    ///
+    /// The validator only executes the transaction once the one with the previous sequence
+    /// number (paid by the same account) was executed successfully. Sequences start at 1.
+    ///
+    /// # Parameters
+    /// * `sequence` - The sequence number of the transaction.
+    ///
+    /// # Errors
+    /// If the transaction is already signed.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use bifrost::{Error, transaction::Transaction};
+    /// let trx = Transaction::new(0).with_sequence(1)?;
+    /// assert_eq!(trx.message().sequence(), Some(1));
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn with_sequence(mut self, sequence: u64) -> Result<Self> {
+        self.check_unfrozen()?;
+        self.message.set_sequence(sequence)?;
+        Ok(self)
+    }
+
+    /// Sets the last slot at which the transaction may be executed.
+    ///
+    /// The deadline is part of the signed message. If the validator only gets to the
+    /// transaction after that slot, it fails without executing its instructions.
+    ///
+    /// # Parameters
+    /// * `deadline` - The last slot at which the transaction may be executed.
+    ///
+    /// # Errors
+    /// If the transaction is already signed.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use bifrost::{Error, program::Slot, transaction::Transaction};
+    /// let trx = Transaction::new(0).with_deadline(10)?;
+    /// assert_eq!(trx.message().deadline(), Some(Slot::new(10)));
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn with_deadline<S>(mut self, deadline: S) -> Result<Self>
+    where
+        S: Into<Slot>,
+    {
+        self.check_unfrozen()?;
+        self.message.set_deadline(deadline.into())?;
+        Ok(self)
+    }
+
+    /// Binds the transaction to a network, so that it can't be replayed on another one.
+    ///
+    /// The chain id is part of the signed message. A validator started from another
+    /// genesis refuses the transaction.
+    ///
+    /// # Parameters
+    /// * `chain` - The chain id of the network, the hash of its genesis.
+    ///
+    /// # Errors
+    /// If the transaction is already signed.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use bifrost::{Error, transaction::Transaction, validator::{Genesis, GenesisConfig}};
+    /// let genesis = Genesis::from_allocations("", &GenesisConfig::default())?;
+    /// let trx = Transaction::new(0).with_chain(genesis.hash())?;
+    /// assert_eq!(trx.message().chain(), Some(genesis.hash()));
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn with_chain(mut self, chain: BlockHash) -> Result<Self> {
+        self.check_unfrozen()?;
+        self.message.set_chain(chain)?;
+        Ok(self)
+    }
+
     /// Add instructions to the transaction.
     ///
-    /// Note that it will clear any signatures if any.
+    /// Once the transaction is signed, its message is frozen: use
+    /// [`Transaction::into_unfrozen`] to add instructions to it anyway.
     ///
     /// # Parameters
     /// * `instructions` - list of instructions to add to the transaction,
     ///
     /// # Errors
-    /// Errors only happen if the same public key points to two different types
-    /// of accounts (such as one is a wallet, the other a program).
+    /// If the transaction is already signed, if the same public key points to two
+    /// different types of accounts (such as one is a wallet, the other a program), or
+    /// if the transaction would hold more than [`MAX_INSTRUCTIONS_PER_TRANSACTION`]
+    /// instructions.
     ///
     /// # Example
     /// ```rust
@@ -88,8 +289,13 @@ impl Transaction {
             n = instructions.len(),
             "adding instructions to the transaction"
         );
-        trace!("resetting signatures");
-        self.signatures.clear();
+        self.check_unfrozen()?;
+        if self.message.instructions.len() + instructions.len() > MAX_INSTRUCTIONS_PER_TRANSACTION {
+            warn!("too many instructions for a single transaction");
+            return Err(Error::TooManyInstructions {
+                max: MAX_INSTRUCTIONS_PER_TRANSACTION,
+            });
+        }
         for instr in instructions {
             trace!("adding transaction");
             self.message.add_instruction(instr)?;
@@ -98,10 +304,48 @@ impl Transaction {
         Ok(())
     }
 
+    /// Removes a privilege from an account, in every instruction already added.
+    ///
+    /// Useful to review instructions provided by a third party before signing them:
+    /// the compiled message only gives the account the remaining privileges.
+    ///
+    /// # Parameters
+    /// * `key` - The public key of the account,
+    /// * `privilege` - The privilege removed.
+    ///
+    /// # Errors
+    /// If the transaction is already signed, if the account is the payer, which must sign
+    /// and be writable to pay the fees, or if the account can't lose the privilege (such
+    /// as a program that never signs).
+    ///
+    /// # Example
+    /// ```rust
+    /// # use bifrost::{Error, crypto::Keypair, account::Privilege, program::system, transaction::Transaction};
+    /// # let payer = Keypair::generate();
+    /// let receiver = Keypair::generate().pubkey();
+    /// let mut trx = Transaction::new(0);
+    /// trx.add(&[system::instruction::transfer(payer.pubkey(), receiver, 10)?])?;
+    /// trx.restrict(&receiver, Privilege::Writable)?;
+    /// trx.sign(&payer)?;
+    /// # Ok::<(), Error>(())
+    /// ```
+    #[instrument(skip(self))]
+    pub fn restrict(&mut self, key: &Pubkey, privilege: Privilege) -> Result<()> {
+        debug!("restricting an account of the transaction");
+        self.check_unfrozen()?;
+        if self.message.payer() == Some(key) {
+            warn!("the payer's privileges can't be restricted");
+            return Err(Error::RestrictedPayer { key: *key });
+        }
+        self.message.restrict(key, privilege)
+    }
+
     /// Sign a transaction.
     ///
     /// The payer's signature will always be used as the one
     /// used to designate the transaction in the future.
+    /// Signing again with the same key replaces its previous signature.
+    /// The first signature freezes the message, see [`Message::freeze`].
     ///
     /// # Parameters
     /// * `key` - the `keypair` of the signer,
@@ -122,21 +366,56 @@ impl Transaction {
     /// trx.sign(&keypair)?;
     /// # Ok::<(), Error>(())
     /// ```
-    #[expect(
-        clippy::unwrap_used,
-        clippy::unwrap_in_result,
-        reason = "if we can sign, there’s a payer"
-    )]
     #[instrument(skip_all, fields(?key))]
     pub fn sign(&mut self, key: &Keypair) -> Result<()> {
         debug!("signing transaction");
+        let Some(payer) = self.message.payer().copied() else {
+            warn!("the transaction has no signing account");
+            return Err(Error::NoSignersOnTransaction);
+        };
         let signature = self.get_signature(key)?;
 
-        if key.pubkey() == self.message.get_payer().unwrap() {
+        trace!("dropping any previous signature of the key");
+        let message = self.message.to_vec();
+        self.signatures
+            .retain(|previous| previous.verify(&key.pubkey(), &message).is_err());
+        if key.pubkey() == payer {
             self.signatures.insert(0, signature);
         } else {
             self.signatures.push(signature);
         }
+        self.message.freeze();
+
+        Ok(())
+    }
+
+    /// Drops the signatures so the transaction can be changed again.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use bifrost::{Error, crypto::Keypair, program::system, transaction::Transaction};
+    /// # let payer = Keypair::generate();
+    /// let mut trx = Transaction::new(0);
+    /// trx.add(&[system::instruction::transfer(payer.pubkey(), Keypair::generate().pubkey(), 10)?])?;
+    /// trx.sign(&payer)?;
+    /// let mut trx = trx.into_unfrozen().with_sequence(1)?;
+    /// trx.sign(&payer)?;
+    /// assert!(trx.is_valid());
+    /// # Ok::<(), Error>(())
+    /// ```
+    #[must_use]
+    pub fn into_unfrozen(mut self) -> Self {
+        self.signatures.clear();
+        self.message.unfreeze();
+        self
+    }
+
+    /// Checks the transaction can still be changed: it isn't signed.
+    fn check_unfrozen(&self) -> Result<()> {
+        if self.message.is_frozen() || !self.signatures.is_empty() {
+            warn!("tried to change a signed transaction");
+            return Err(Error::MessageFrozen);
+        }
 
         Ok(())
     }
@@ -157,10 +436,19 @@ impl Transaction {
         self.message.is_valid() && self.check_signed().is_ok()
     }
 
+    /// Checks that the message is valid and every signer signed it, without
+    /// verifying the signatures themselves.
+    ///
+    /// Only meant for the transactions the validator signed itself.
+    pub(crate) fn has_all_signatures(&self) -> bool {
+        let signers = self.get_signers();
+        self.message.is_valid() && !signers.is_empty() && signers.len() == self.signatures.len()
+    }
+
     /// Get the overall signature of the transaction (if it exists).
     ///
     /// If there are multiple signers, this will always be the one
-    /// associated with the payer (*i.e.* the first referenced signing account).
+    /// associated with the payer (*i.e.* the first referenced writable signing account).
     ///
     /// # Returns
     /// The transaction's signature if it exists
@@ -170,6 +458,89 @@ impl Transaction {
         self.signatures.first()
     }
 
+    /// Describes each instruction of the transaction in a human readable form.
+    ///
+    /// # Parameters
+    /// * `registry` - The decoders of the programs' instructions.
+    ///
+    /// # Returns
+    /// The program and description of each instruction, in their order of execution.
+    #[must_use]
+    pub fn explain(&self, registry: &SchemaRegistry) -> Vec<(Pubkey, String)> {
+        self.message
+            .instructions
+            .iter()
+            .map(|instruction| {
+                let program =
+                    *self.message.accounts()[instruction.program_account_id as usize].key();
+                let description = registry.decode(&program, &instruction.data);
+                (program, description)
+            })
+            .collect()
+    }
+
+    /// Summarizes the transaction for the wallets about to sign it.
+    ///
+    /// Unlike the other signers, the read-only ones only authorize the transaction:
+    /// their accounts can't be modified by it.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use bifrost::{
+    /// #     Error,
+    /// #     crypto::Keypair,
+    /// #     program::{memo, system},
+    /// #     transaction::{SignerAccess, Transaction},
+    /// # };
+    /// let payer = Keypair::generate().pubkey();
+    /// let witness = Keypair::generate().pubkey();
+    /// let mut trx = Transaction::new(0);
+    /// trx.add(&[
+    ///     system::instruction::transfer(payer, Keypair::generate().pubkey(), 10)?,
+    ///     memo::instruction::memo("witnessed", &[witness])?,
+    /// ])?;
+    /// let summary = trx.summary();
+    /// assert_eq!(summary.access(&payer), Some(SignerAccess::Writable));
+    /// assert_eq!(summary.access(&witness), Some(SignerAccess::ReadOnly));
+    /// # Ok::<(), Error>(())
+    /// ```
+    #[must_use]
+    pub fn summary(&self) -> TransactionSummary {
+        let signers = self
+            .message
+            .accounts()
+            .iter()
+            .filter(|meta| meta.is_signing())
+            .map(|meta| {
+                let access = if meta.is_writable() {
+                    SignerAccess::Writable
+                } else {
+                    SignerAccess::ReadOnly
+                };
+                (*meta.key(), access)
+            })
+            .collect();
+
+        TransactionSummary {
+            fields: self.message.display_fields(),
+            signers,
+        }
+    }
+
+    /// Get the account paying for the transaction
+    /// (*i.e.* the first referenced writable signing account).
+    #[must_use]
+    pub fn payer(&self) -> Option<&Pubkey> {
+        self.message.payer()
+    }
+
+    /// Get every signature of the transaction, the payer's first.
+    #[expect(clippy::missing_const_for_fn, reason = "false positive")]
+    #[must_use]
+    pub fn signatures(&self) -> &[Signature] {
+        &self.signatures
+    }
+
     #[instrument(skip_all)]
     fn check_signed(&self) -> Result<()> {
         debug!("checking transaction signatures");
@@ -180,6 +551,16 @@ impl Transaction {
             return Err(Error::NoSignersOnTransaction);
         }
 
+        if self
+            .signatures
+            .iter()
+            .enumerate()
+            .any(|(i, signature)| self.signatures[..i].contains(signature))
+        {
+            warn!("the same signature appears twice");
+            return Err(Error::DuplicateSignature);
+        }
+
         if signers.len() != self.signatures.len() {
             warn!("wrong number of signatures on the transaction");
             return Err(Error::WrongNumberOfSignatures {
@@ -222,6 +603,25 @@ impl Transaction {
     }
 }
 
+impl DescribeLayout for Transaction {
+    const NAME: &'static str = "Transaction";
+    const DESCRIPTION: &'static str = "A signed transaction, as submitted to the validator.";
+
+    fn fields() -> Vec<Field> {
+        vec![
+            Field::new("signatures", "Vec<Signature>", Size::items(64)),
+            Field::new("message", "Message", Size::Variable),
+        ]
+    }
+
+    fn extents(&self) -> Vec<Extent> {
+        vec![
+            Extent::Count(self.signatures.len()),
+            Extent::Len(self.message.described_len()),
+        ]
+    }
+}
+
 #[cfg(test)]
 #[cfg_attr(coverage_nightly, coverage(off))]
 mod tests {
@@ -277,10 +677,17 @@ mod tests {
 
         // When
         trx.sign(&keypair)?;
-        trx.add(&[instruction])?;
+        let res = trx.add(&[instruction.clone()]);
+        let mut rebuilt = trx.clone().into_unfrozen();
+        rebuilt.add(&[instruction])?;
 
         // Then
-        assert!(!trx.is_valid());
+        assert_matches!(res, Err(super::super::Error::MessageFrozen));
+        assert!(trx.is_valid());
+        assert!(!rebuilt.message().is_frozen());
+        assert!(!rebuilt.is_valid());
+        rebuilt.sign(&keypair)?;
+        assert!(rebuilt.is_valid());
 
         Ok(())
     }
@@ -423,6 +830,28 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn deadline_is_covered_by_the_signature() -> TestResult {
+        // Given
+        let keypair = Keypair::generate();
+        let instruction =
+            get_instruction(vec![AccountMeta::signing(keypair.pubkey(), Writable::Yes)?]);
+        let mut trx = Transaction::new(0);
+        trx.add(&[instruction])?;
+        trx.sign(&keypair)?;
+
+        // When
+        let mut delayed = trx.clone().into_unfrozen().with_deadline(5)?;
+        let unsigned = delayed.signatures().len();
+        delayed.sign(&keypair)?;
+
+        // Then
+        assert_eq!(unsigned, 0);
+        assert!(delayed.is_valid());
+        assert_ne!(delayed.signatures, trx.signatures);
+        Ok(())
+    }
+
     #[test]
     fn trx_signature_is_first_signers() -> TestResult {
         // Given
@@ -445,4 +874,298 @@ mod tests {
         assert_matches!(signature, Some(sig) if *sig == expected);
         Ok(())
     }
+
+    #[test]
+    fn reject_too_many_instructions() -> TestResult {
+        // Given
+        let keypair = Keypair::generate();
+        let mut trx = Transaction::new(0);
+        let instruction =
+            get_instruction(vec![AccountMeta::signing(keypair.pubkey(), Writable::Yes)?]);
+        trx.add(&vec![
+            instruction.clone();
+            MAX_INSTRUCTIONS_PER_TRANSACTION - 1
+        ])?;
+
+        // When
+        trx.add(&[instruction.clone()])?;
+        let res = trx.add(&[instruction]);
+
+        // Then
+        assert_eq!(
+            trx.message.instructions.len(),
+            MAX_INSTRUCTIONS_PER_TRANSACTION
+        );
+        assert_matches!(
+            res,
+            Err(super::super::Error::TooManyInstructions {
+                max: MAX_INSTRUCTIONS_PER_TRANSACTION
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn signing_twice_replaces_signature() -> TestResult {
+        // Given
+        let payer = Keypair::generate();
+        let signer = Keypair::generate();
+        let mut trx = Transaction::new(0);
+        let instruction = get_instruction(vec![
+            AccountMeta::signing(payer.pubkey(), Writable::Yes)?,
+            AccountMeta::signing(signer.pubkey(), Writable::No)?,
+        ]);
+        trx.add(&[instruction])?;
+        trx.sign(&payer)?;
+        trx.sign(&signer)?;
+
+        // When
+        trx.sign(&signer)?;
+        trx.sign(&payer)?;
+
+        // Then
+        assert_eq!(trx.signatures.len(), 2);
+        assert_eq!(trx.signature(), Some(&payer.sign(trx.message.to_vec())));
+        assert!(trx.is_valid());
+
+        Ok(())
+    }
+
+    #[test]
+    fn resigning_after_message_change() -> TestResult {
+        // Given
+        let keypair = Keypair::generate();
+        let mut trx = Transaction::new(0);
+        let instruction =
+            get_instruction(vec![AccountMeta::signing(keypair.pubkey(), Writable::Yes)?]);
+        trx.add(&[instruction.clone()])?;
+        trx.sign(&keypair)?;
+        let old = *trx.signature().ok_or("the transaction is not signed")?;
+        let mut trx = trx.into_unfrozen();
+        trx.add(&[instruction])?;
+
+        // When
+        trx.sign(&keypair)?;
+
+        // Then
+        assert_eq!(trx.signatures.len(), 1);
+        assert_ne!(trx.signature(), Some(&old));
+        assert!(trx.is_valid());
+
+        Ok(())
+    }
+
+    #[test]
+    fn reject_duplicate_signatures() -> TestResult {
+        // Given
+        let keypair = Keypair::generate();
+        let mut trx = Transaction::new(0);
+        let instruction =
+            get_instruction(vec![AccountMeta::signing(keypair.pubkey(), Writable::Yes)?]);
+        trx.add(&[instruction])?;
+        trx.sign(&keypair)?;
+        let mut bytes = borsh::to_vec(&trx)?;
+        // the signatures are serialized first: a length followed by the signatures
+        bytes[0] = 2;
+        bytes.splice(4..4, trx.signatures[0].as_ref().to_vec());
+        let received = borsh::from_slice::<Transaction>(&bytes)?;
+
+        // When
+        let res = received.check_signed();
+
+        // Then
+        assert!(!received.is_valid());
+        assert_matches!(res, Err(super::super::Error::DuplicateSignature));
+
+        Ok(())
+    }
+
+    #[test]
+    fn cannot_sign_without_signing_accounts() -> TestResult {
+        // Given
+        let keypair = Keypair::generate();
+        let mut trx = Transaction::new(0);
+        let instruction =
+            get_instruction(vec![AccountMeta::wallet(keypair.pubkey(), Writable::Yes)?]);
+        trx.add(&[instruction])?;
+
+        // When
+        let res = trx.sign(&keypair);
+
+        // Then
+        assert_eq!(trx.payer(), None);
+        assert_matches!(res, Err(super::super::Error::NoSignersOnTransaction));
+
+        Ok(())
+    }
+
+    #[test]
+    fn payer_is_first_signer() -> TestResult {
+        // Given
+        let payer = Keypair::generate().pubkey();
+        let signer = Keypair::generate().pubkey();
+        let mut trx = Transaction::new(0);
+
+        // When
+        trx.add(&[get_instruction(vec![
+            AccountMeta::wallet(Keypair::generate().pubkey(), Writable::Yes)?,
+            AccountMeta::signing(payer, Writable::Yes)?,
+            AccountMeta::signing(signer, Writable::No)?,
+        ])])?;
+
+        // Then
+        assert_eq!(trx.payer(), Some(&payer));
+
+        Ok(())
+    }
+
+    #[test]
+    fn message_signed_out_of_band_matches_original_bytes() -> TestResult {
+        // Given
+        let payer = Keypair::generate();
+        let cosigner = Keypair::generate();
+        let instructions = [
+            get_instruction(vec![
+                AccountMeta::wallet(Keypair::generate().pubkey(), Writable::Yes)?,
+                AccountMeta::signing(payer.pubkey(), Writable::Yes)?,
+            ]),
+            get_instruction(vec![AccountMeta::signing(cosigner.pubkey(), Writable::No)?]),
+        ];
+        let mut plain = Transaction::new(7);
+        plain.add(&instructions)?;
+        let mut sequenced = Transaction::new(7).with_sequence(3)?;
+        sequenced.add(&instructions)?;
+
+        for coordinator in [plain, sequenced] {
+            let bytes = coordinator.message().to_vec();
+
+            // When
+            let mut trx = Transaction::from_message(Message::try_from_bytes(&bytes)?);
+            trx.sign(&cosigner)?;
+            trx.sign(&payer)?;
+
+            // Then
+            assert!(trx.is_valid());
+            assert_eq!(trx.message().to_vec(), bytes);
+            assert_eq!(trx.message().sequence(), coordinator.message().sequence());
+            trx.signature()
+                .ok_or("unsigned")?
+                .verify(&payer.pubkey(), &bytes)?;
+            trx.signatures()[1].verify(&cosigner.pubkey(), &bytes)?;
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn restricted_accounts_lose_privileges() -> TestResult {
+        // Given
+        let payer = Keypair::generate();
+        let cosigner = Keypair::generate();
+        let receiver = Keypair::generate().pubkey();
+        let mut trx = Transaction::new(0);
+        trx.add(&[get_instruction(vec![
+            AccountMeta::signing(payer.pubkey(), Writable::Yes)?,
+            AccountMeta::signing(cosigner.pubkey(), Writable::Yes)?,
+            AccountMeta::wallet(receiver, Writable::Yes)?,
+        ])])?;
+        trx.sign(&cosigner)?;
+
+        // When
+        let mut trx = trx.into_unfrozen();
+        trx.restrict(&cosigner.pubkey(), Privilege::Signing)?;
+        trx.restrict(&receiver, Privilege::Writable)?;
+        let payer_restricted = trx.restrict(&payer.pubkey(), Privilege::Writable);
+        trx.sign(&payer)?;
+
+        // Then
+        let accounts = trx.message().instruction_accounts(0)?;
+        assert!(accounts[0].privileges.is_signer() && accounts[0].privileges.is_writable());
+        assert!(!accounts[1].privileges.is_signer() && accounts[1].privileges.is_writable());
+        assert!(!accounts[2].privileges.is_signer() && !accounts[2].privileges.is_writable());
+        assert!(trx.is_valid());
+        assert_eq!(trx.signatures().len(), 1);
+        assert_matches!(
+            payer_restricted,
+            Err(super::super::Error::RestrictedPayer { key }) if key == payer.pubkey()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn refreshed_transaction_only_needs_signing() -> TestResult {
+        // Given
+        let payer = Keypair::generate();
+        let cosigner = Keypair::generate();
+        let mut trx = Transaction::new(3).with_sequence(4)?.with_deadline(12)?;
+        trx.add(&[
+            get_instruction(vec![
+                AccountMeta::wallet(payer.pubkey(), Writable::No)?,
+                AccountMeta::signing(cosigner.pubkey(), Writable::No)?,
+            ]),
+            Instruction::new(
+                PROGRAM,
+                vec![AccountMeta::signing(payer.pubkey(), Writable::Yes)?],
+                &42_u64,
+            ),
+        ])?;
+        trx.sign(&payer)?;
+        trx.sign(&cosigner)?;
+
+        // When
+        let mut refreshed = trx.refresh(10)?;
+        let unsigned = refreshed.signatures().len();
+        refreshed.sign(&payer)?;
+        refreshed.sign(&cosigner)?;
+
+        // Then
+        assert_eq!(unsigned, 0);
+        assert!(refreshed.is_valid());
+        assert_eq!(refreshed.message().slot(), Slot::new(10));
+        assert_eq!(refreshed.message().sequence(), Some(4));
+        assert_eq!(refreshed.message().deadline(), Some(Slot::new(12)));
+        assert_ne!(refreshed.signature(), trx.signature());
+        for index in 0..2 {
+            assert_eq!(
+                refreshed.message().instruction_accounts(index)?,
+                trx.message().instruction_accounts(index)?
+            );
+            assert_eq!(
+                refreshed.message().instructions[index].data,
+                trx.message().instructions[index].data
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn summary_tells_read_only_signers_apart() -> TestResult {
+        // Given
+        let payer = Keypair::generate().pubkey();
+        let cosigner = Keypair::generate().pubkey();
+        let mut trx = Transaction::new(0);
+        trx.add(&[
+            get_instruction(vec![AccountMeta::signing(cosigner, Writable::No)?]),
+            get_instruction(vec![AccountMeta::signing(payer, Writable::Yes)?]),
+        ])?;
+
+        // When
+        let summary = trx.summary();
+
+        // Then
+        assert_eq!(
+            summary.signers,
+            vec![
+                (payer, SignerAccess::Writable),
+                (cosigner, SignerAccess::ReadOnly)
+            ]
+        );
+        assert_eq!(summary.fields, trx.message().display_fields());
+        assert_eq!(summary.access(&cosigner), Some(SignerAccess::ReadOnly));
+        assert_eq!(summary.access(&Keypair::generate().pubkey()), None);
+
+        Ok(())
+    }
 }
@@ -0,0 +1,166 @@
+// File: src/validator/admission.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 14:34:46
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! The rules deciding whether the validator takes a transaction in.
+//!
+//! The policies of [`ValidatorConfig::admission`](super::ValidatorConfig::admission)
+//! are evaluated in order once the signatures of a transaction were verified,
+//! before it's scheduled. The first one that doesn't accept it decides its fate.
+
+use std::{collections::HashSet, fmt::Debug};
+
+use crate::{crypto::Pubkey, transaction::Transaction};
+
+/// What the validator knows when a transaction is submitted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AdmissionContext {
+    /// The slot the validator is at.
+    pub slot: u64,
+}
+
+/// The outcome of an admission policy.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AdmissionDecision {
+    /// The transaction can be scheduled.
+    Accept,
+    /// The transaction is dropped, for the given reason.
+    Reject(&'static str),
+    /// The transaction is evaluated again once the validator reaches the slot.
+    Defer(u64),
+}
+
+/// A rule deciding whether a transaction is taken in.
+pub trait AdmissionPolicy: Debug + Send + Sync {
+    /// Decides what to do with a transaction.
+    ///
+    /// # Parameters
+    /// * `transaction` - The transaction, whose signatures are valid,
+    /// * `context` - The state of the validator.
+    fn admit(&self, transaction: &Transaction, context: &AdmissionContext) -> AdmissionDecision;
+}
+
+/// Only admits the transactions paid by some accounts.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PayerAllowList(pub HashSet<Pubkey>);
+
+impl AdmissionPolicy for PayerAllowList {
+    fn admit(&self, transaction: &Transaction, _context: &AdmissionContext) -> AdmissionDecision {
+        match transaction.payer() {
+            Some(payer) if self.0.contains(payer) => AdmissionDecision::Accept,
+            _ => AdmissionDecision::Reject("the payer isn't allowed"),
+        }
+    }
+}
+
+/// Rejects the transactions invoking some programs.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProgramDenyList(pub HashSet<Pubkey>);
+
+impl AdmissionPolicy for ProgramDenyList {
+    fn admit(&self, transaction: &Transaction, _context: &AdmissionContext) -> AdmissionDecision {
+        let message = transaction.message();
+        let denied = (0..message.instructions.len()).any(|index| {
+            message
+                .instruction_program_id(index)
+                .is_ok_and(|program| self.0.contains(program))
+        });
+        if denied {
+            AdmissionDecision::Reject("a denied program is invoked")
+        } else {
+            AdmissionDecision::Accept
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use test_log::test;
+
+    use crate::crypto::Keypair;
+    use crate::program::{memo, system};
+
+    use super::*;
+    type TestResult = core::result::Result<(), Box<dyn core::error::Error>>;
+
+    const CONTEXT: AdmissionContext = AdmissionContext { slot: 1 };
+
+    fn transfer(payer: &Keypair) -> Result<Transaction, Box<dyn core::error::Error>> {
+        let mut trx = Transaction::new(0);
+        trx.add(&[system::instruction::transfer(
+            payer.pubkey(),
+            Keypair::generate().pubkey(),
+            10,
+        )?])?;
+        trx.sign(payer)?;
+        Ok(trx)
+    }
+
+    #[test]
+    fn allow_list_admits_known_payers() -> TestResult {
+        // Given
+        let allowed = Keypair::generate();
+        let policy = PayerAllowList(HashSet::from([allowed.pubkey()]));
+
+        // When
+        let known = policy.admit(&transfer(&allowed)?, &CONTEXT);
+        let unknown = policy.admit(&transfer(&Keypair::generate())?, &CONTEXT);
+
+        // Then
+        assert_eq!(known, AdmissionDecision::Accept);
+        assert_eq!(
+            unknown,
+            AdmissionDecision::Reject("the payer isn't allowed")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn deny_list_rejects_programs() -> TestResult {
+        // Given
+        let payer = Keypair::generate();
+        let policy = ProgramDenyList(HashSet::from([memo::MEMO_PROGRAM]));
+        let mut with_memo = Transaction::new(0);
+        with_memo.add(&[memo::instruction::memo("hello", &[payer.pubkey()])?])?;
+        with_memo.sign(&payer)?;
+
+        // When
+        let denied = policy.admit(&with_memo, &CONTEXT);
+        let allowed = policy.admit(&transfer(&payer)?, &CONTEXT);
+
+        // Then
+        assert_eq!(
+            denied,
+            AdmissionDecision::Reject("a denied program is invoked")
+        );
+        assert_eq!(allowed, AdmissionDecision::Accept);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,298 @@
+// File: src/validator/audit.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 18:18:00
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Signed checkpoints of the ledger, for auditors.
+//!
+//! Every few slots, the validator summarizes the ledger (the hash of the slot's block,
+//! the state root and the supply) in a checkpoint it signs. Each checkpoint holds the
+//! hash of the previous one: an auditor holding only the checkpoints can check they
+//! form an unbroken chain, then replay the transactions between two of them to check
+//! the state root they lead to.
+
+use std::{fmt::Debug, sync::Arc};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use sha2::{Digest as _, Sha512};
+use tracing::{debug, instrument, warn};
+
+use super::{BlockHash, Error, Result};
+use crate::{
+    crypto::{Keypair, Pubkey, Signature},
+    layout::{DescribeLayout, Extent, Field, Size},
+};
+
+/// How often the validator produces a checkpoint, and the key signing them.
+#[derive(Clone)]
+pub struct AuditConfig {
+    /// The number of slots between two checkpoints.
+    pub interval: u64,
+    /// The key signing the checkpoints.
+    pub signer: Arc<Keypair>,
+}
+
+impl AuditConfig {
+    /// Whether a checkpoint is produced at the end of a slot.
+    ///
+    /// # Parameters
+    /// * `slot` - The slot ending.
+    #[must_use]
+    pub const fn is_due(&self, slot: u64) -> bool {
+        self.interval > 0 && slot % self.interval == 0
+    }
+}
+
+impl Debug for AuditConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AuditConfig")
+            .field("interval", &self.interval)
+            .field("signer", &self.signer.pubkey())
+            .finish()
+    }
+}
+
+/// A signed summary of the ledger at the end of a slot.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct AuditCheckpoint {
+    /// The slot summarized.
+    pub slot: u64,
+    /// The hash of the slot's block.
+    pub block_hash: BlockHash,
+    /// The root of the accounts' state at the end of the slot.
+    pub state_root: BlockHash,
+    /// The total of prisms held by the accounts.
+    pub supply: u64,
+    /// The hash of the previous checkpoint (the default hash for the first one).
+    pub previous: BlockHash,
+    /// The signature of the checkpoint's hash.
+    pub signature: Signature,
+}
+
+impl AuditCheckpoint {
+    /// Produces and signs a checkpoint, following the previous one.
+    ///
+    /// # Parameters
+    /// * `slot` - The slot summarized,
+    /// * `block_hash` - The hash of the slot's block,
+    /// * `state_root` - The root of the accounts' state,
+    /// * `supply` - The total of prisms held by the accounts,
+    /// * `previous` - The last checkpoint, if any,
+    /// * `signer` - The key signing the checkpoint.
+    #[instrument(skip_all, fields(slot))]
+    pub fn new(
+        slot: u64,
+        block_hash: BlockHash,
+        state_root: BlockHash,
+        supply: u64,
+        previous: Option<&Self>,
+        signer: &Keypair,
+    ) -> Self {
+        debug!("producing an audit checkpoint");
+        let previous = previous.map(Self::hash).unwrap_or_default();
+        let hash = digest(slot, &block_hash, &state_root, supply, &previous);
+
+        Self {
+            slot,
+            block_hash,
+            state_root,
+            supply,
+            previous,
+            signature: signer.sign(hash),
+        }
+    }
+
+    /// The hash of the checkpoint, covering everything but its signature.
+    #[must_use]
+    pub fn hash(&self) -> BlockHash {
+        digest(
+            self.slot,
+            &self.block_hash,
+            &self.state_root,
+            self.supply,
+            &self.previous,
+        )
+    }
+
+    /// Checks that the checkpoint was signed by the expected key.
+    ///
+    /// # Parameters
+    /// * `signer` - The key that should have signed the checkpoint.
+    ///
+    /// # Errors
+    /// If the signature doesn't match the checkpoint's content.
+    pub fn verify(&self, signer: &Pubkey) -> Result<()> {
+        self.signature.verify(signer, self.hash()).map_err(|err| {
+            warn!(
+                slot = self.slot,
+                "the checkpoint isn't signed by '{signer}': {err}"
+            );
+            Error::InvalidCheckpointSignature { slot: self.slot }
+        })
+    }
+}
+
+/// Hashes the content of a checkpoint.
+#[expect(clippy::little_endian_bytes, clippy::unwrap_used)]
+fn digest(
+    slot: u64,
+    block_hash: &BlockHash,
+    state_root: &BlockHash,
+    supply: u64,
+    previous: &BlockHash,
+) -> BlockHash {
+    let mut hasher = Sha512::new();
+    hasher.update(slot.to_le_bytes());
+    hasher.update(block_hash);
+    hasher.update(state_root);
+    hasher.update(supply.to_le_bytes());
+    hasher.update(previous);
+
+    BlockHash::from_bytes(&hasher.finalize()).unwrap()
+}
+
+/// Checks that checkpoints form an unbroken chain, all signed by the same key.
+///
+/// # Parameters
+/// * `checkpoints` - The checkpoints, from the first one,
+/// * `signer` - The key that should have signed them.
+///
+/// # Errors
+/// If a checkpoint isn't properly signed, or doesn't follow the previous one.
+#[instrument(skip_all, fields(n = checkpoints.len()))]
+pub fn verify_audit_trail(checkpoints: &[AuditCheckpoint], signer: &Pubkey) -> Result<()> {
+    debug!("verifying an audit trail");
+    let mut previous: Option<&AuditCheckpoint> = None;
+    for checkpoint in checkpoints {
+        checkpoint.verify(signer)?;
+        let linked = previous.map_or_else(
+            || checkpoint.previous == BlockHash::default(),
+            |previous| checkpoint.previous == previous.hash() && checkpoint.slot > previous.slot,
+        );
+        if !linked {
+            warn!(
+                slot = checkpoint.slot,
+                "the checkpoint doesn't follow the previous one"
+            );
+            return Err(Error::BrokenAuditTrail {
+                slot: checkpoint.slot,
+            });
+        }
+        previous = Some(checkpoint);
+    }
+
+    Ok(())
+}
+
+impl DescribeLayout for AuditCheckpoint {
+    const NAME: &'static str = "AuditCheckpoint";
+    const DESCRIPTION: &'static str = "A signed summary of the ledger at the end of a slot.";
+
+    fn fields() -> Vec<Field> {
+        vec![
+            Field::new("slot", "u64", Size::Fixed(8)),
+            Field::new("block_hash", "BlockHash", Size::Fixed(64)),
+            Field::new("state_root", "BlockHash", Size::Fixed(64)),
+            Field::new("supply", "u64", Size::Fixed(8)),
+            Field::new("previous", "BlockHash", Size::Fixed(64)),
+            Field::new("signature", "Signature", Size::Fixed(64)),
+        ]
+    }
+
+    fn extents(&self) -> Vec<Extent> {
+        vec![Extent::Fixed; 6]
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use std::assert_matches::assert_matches;
+
+    use test_log::test;
+
+    use super::*;
+    type TestResult = core::result::Result<(), Box<dyn core::error::Error>>;
+
+    fn trail(signer: &Keypair, slots: &[u64]) -> Result<Vec<AuditCheckpoint>> {
+        let mut checkpoints: Vec<AuditCheckpoint> = Vec::new();
+        for &slot in slots {
+            let root = BlockHash::from_bytes(&[u8::try_from(slot).unwrap_or_default(); 64])?;
+            let checkpoint =
+                AuditCheckpoint::new(slot, root, root, 1_000, checkpoints.last(), signer);
+            checkpoints.push(checkpoint);
+        }
+
+        Ok(checkpoints)
+    }
+
+    #[test]
+    fn altered_checkpoints_break_the_trail() -> TestResult {
+        // Given
+        let signer = Keypair::generate();
+        let checkpoints = trail(&signer, &[2, 4, 6, 8])?;
+        let mut altered = checkpoints.clone();
+        altered[1].supply += 1;
+        let mut removed = checkpoints.clone();
+        removed.remove(2);
+        let forged = trail(&Keypair::generate(), &[2, 4, 6, 8])?;
+
+        // When
+        let valid = verify_audit_trail(&checkpoints, &signer.pubkey());
+        let altered = verify_audit_trail(&altered, &signer.pubkey());
+        let removed = verify_audit_trail(&removed, &signer.pubkey());
+        let forged = verify_audit_trail(&forged, &signer.pubkey());
+
+        // Then
+        assert_matches!(valid, Ok(()));
+        assert_matches!(altered, Err(Error::InvalidCheckpointSignature { slot: 4 }));
+        assert_matches!(removed, Err(Error::BrokenAuditTrail { slot: 8 }));
+        assert_matches!(forged, Err(Error::InvalidCheckpointSignature { slot: 2 }));
+
+        Ok(())
+    }
+
+    #[test]
+    fn checkpoints_are_due_every_interval() {
+        // Given
+        let config = AuditConfig {
+            interval: 3,
+            signer: Arc::new(Keypair::generate()),
+        };
+        let never = AuditConfig {
+            interval: 0,
+            ..config.clone()
+        };
+
+        // When
+        let due = (1..=9)
+            .filter(|&slot| config.is_due(slot))
+            .collect::<Vec<_>>();
+
+        // Then
+        assert_eq!(due, vec![3, 6, 9]);
+        assert!(!never.is_due(3));
+    }
+}
@@ -3,7 +3,7 @@
 // Creation date: Sunday 16 February 2025
 // Author: Vincent Berthier <vincent.berthier@posteo.org>
 // -----
-// Last modified: Sunday 16 February 2025 @ 01:20:00
+// Last modified: Thursday 15 October 2026 @ 20:51:54
 // Modified by: Vincent Berthier
 // -----
 // Copyright (c) 2025 <Vincent Berthier>
@@ -29,11 +29,14 @@
 use std::fmt::Debug;
 
 use sha2::{Digest as _, Sha512};
-use tracing::{debug, instrument};
+use tracing::{debug, instrument, warn};
 
-use crate::crypto::Signature;
+use crate::{
+    crypto::{Keypair, Signature},
+    layout::{DescribeLayout, Extent, Field, Size},
+};
 
-use super::blockhash::BlockHash;
+use super::{blockhash::BlockHash, Error, IdentityHistory, Result};
 
 pub const GENESIS_BLOCK: &str =
     "4n1FyWzYPeGUndCLBAaWVMKZ5gCv1EJvgKwTrLSpnz8uJQ7E3zdhTXaFg4UaiLP9aPK5dmccZK2qKfZjYgc16kzd";
@@ -43,7 +46,12 @@ pub struct Block {
     pub hash: BlockHash,
     pub parent: BlockHash,
     pub slot: u64,
+    pub state_root: BlockHash,
     pub transactions: Vec<Signature>,
+    /// The signature of the block's hash by the leader of its slot.
+    pub leader_signature: Option<Signature>,
+    /// The estimated unix timestamp of the slot, once the block is sealed.
+    pub block_time: Option<i64>,
 }
 
 impl Block {
@@ -53,16 +61,19 @@ impl Block {
             hash: BlockHash::default(),
             parent: GENESIS_BLOCK.parse().unwrap(),
             slot: 1,
+            state_root: BlockHash::default(),
             transactions: Vec::new(),
+            leader_signature: None,
+            block_time: None,
         }
     }
 
-    fn add_transaction(&mut self, sig: Signature) {
+    pub(super) fn add_transaction(&mut self, sig: Signature) {
         self.transactions.push(sig);
     }
 
     #[instrument(skip_all, fields(slot = self.slot))]
-    fn finalize(&mut self) -> Self {
+    pub(super) fn finalize(&mut self) -> Self {
         debug!("finalizing block");
 
         let hash = self.get_hash();
@@ -70,22 +81,75 @@ impl Block {
         let res = self.clone();
         self.slot += 1;
         self.transactions.clear();
+        self.leader_signature = None;
+        self.block_time = None;
         self.parent = hash;
 
         res
     }
 
-    #[expect(clippy::little_endian_bytes, clippy::unwrap_used)]
+    #[expect(clippy::unwrap_used)]
     #[instrument(skip_all, fields(slot = self.slot, parent = ?self.parent, sigs = self.transactions.len()))]
     pub fn get_hash(&self) -> BlockHash {
         debug!("getting block hash");
         let mut hasher = Sha512::new();
-        hasher.update(self.parent);
-        hasher.update(self.slot.to_le_bytes());
-        self.transactions.iter().for_each(|sig| hasher.update(sig));
+        hasher.update(self.preimage());
 
         BlockHash::from_bytes(&hasher.finalize()).unwrap()
     }
+
+    /// The bytes hashed to get the hash of the block.
+    #[expect(clippy::little_endian_bytes)]
+    pub(crate) fn preimage(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(self.parent.as_ref());
+        bytes.extend_from_slice(&self.slot.to_le_bytes());
+        bytes.extend_from_slice(self.state_root.as_ref());
+        self.transactions
+            .iter()
+            .for_each(|sig| bytes.extend_from_slice(sig.as_ref()));
+
+        bytes
+    }
+}
+
+impl Block {
+    /// Signs the block as the leader of its slot.
+    ///
+    /// # Parameters
+    /// * `key` - The identity of the validator producing the block.
+    pub fn sign(&mut self, key: &Keypair) {
+        self.leader_signature = Some(key.sign(self.hash));
+    }
+
+    /// Checks that the block was signed by the identity its producer had during its slot.
+    ///
+    /// # Parameters
+    /// * `identities` - The identities of the producer over time.
+    ///
+    /// # Errors
+    /// If the hash doesn't match the block's content, if no identity covers the slot,
+    /// or if the block isn't signed by it.
+    #[instrument(skip_all, fields(slot = self.slot))]
+    pub fn verify(&self, identities: &IdentityHistory) -> Result<()> {
+        debug!("verifying block");
+        if self.hash != self.get_hash() {
+            warn!("the block's hash doesn't match its content");
+            return Err(Error::BlockHashMismatch { slot: self.slot });
+        }
+        let Some(identity) = identities.identity_at(self.slot) else {
+            warn!("no identity is expected for the slot");
+            return Err(Error::InvalidBlockSignature { slot: self.slot });
+        };
+        let Some(signature) = &self.leader_signature else {
+            warn!("the block isn't signed");
+            return Err(Error::InvalidBlockSignature { slot: self.slot });
+        };
+        signature.verify(identity, self.hash).map_err(|err| {
+            warn!("the block isn't signed by '{identity}': {err}");
+            Error::InvalidBlockSignature { slot: self.slot }
+        })
+    }
 }
 
 impl PartialEq for Block {
@@ -94,10 +158,43 @@ impl PartialEq for Block {
     }
 }
 
+impl DescribeLayout for Block {
+    const NAME: &'static str = "Block";
+    const DESCRIPTION: &'static str =
+        "The header of a block, as hashed to get its hash (signed by the leader of its slot).";
+
+    fn fields() -> Vec<Field> {
+        vec![
+            Field::new("parent", "BlockHash", Size::Fixed(64)),
+            Field::new("slot", "u64", Size::Fixed(8)),
+            Field::new("state_root", "BlockHash", Size::Fixed(64)),
+            Field::new(
+                "transactions",
+                "[Signature]",
+                Size::Sequence {
+                    prefix: 0,
+                    item: Some(64),
+                },
+            ),
+        ]
+    }
+
+    fn extents(&self) -> Vec<Extent> {
+        vec![
+            Extent::Fixed,
+            Extent::Fixed,
+            Extent::Fixed,
+            Extent::Count(self.transactions.len()),
+        ]
+    }
+}
+
 #[cfg(test)]
 #[cfg_attr(coverage_nightly, coverage(off))]
 mod tests {
 
+    use std::assert_matches::assert_matches;
+
     use test_log::test;
 
     use super::*;
@@ -110,7 +207,10 @@ mod tests {
             hash: BlockHash::default(),
             parent: GENESIS_BLOCK.parse().unwrap(),
             slot: 0,
+            state_root: BlockHash::default(),
             transactions: Vec::new(),
+            leader_signature: None,
+            block_time: None,
         };
 
         for slot in 1..=10 {
@@ -160,4 +260,81 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn state_root_changes_hash() -> TestResult {
+        // Given
+        let mut block1 = Block::genesis();
+        let mut block2 = Block::genesis();
+
+        // When
+        block2.state_root = BlockHash::from_bytes(&[1; 64])?;
+
+        // Then
+        assert_ne!(block1.finalize(), block2.finalize());
+
+        Ok(())
+    }
+
+    #[test]
+    fn blocks_verify_across_identity_rotation() -> TestResult {
+        // Given
+        let old = Keypair::generate();
+        let new = Keypair::generate();
+        let mut identities = IdentityHistory::new(old.pubkey());
+        let boundary = identities.rotate(new.pubkey(), 5);
+        let mut block = Block::genesis();
+        block.slot = boundary.get() - 1;
+        let mut before = block.finalize();
+        let mut after = block.finalize();
+
+        // When
+        before.sign(&old);
+        after.sign(&new);
+        let mut before_wrong_era = before.clone();
+        before_wrong_era.sign(&new);
+        let mut after_wrong_era = after.clone();
+        after_wrong_era.sign(&old);
+
+        // Then
+        assert_eq!(after.slot, boundary.get());
+        before.verify(&identities)?;
+        after.verify(&identities)?;
+        assert_matches!(
+            before_wrong_era.verify(&identities),
+            Err(Error::InvalidBlockSignature { slot }) if slot == boundary.get() - 1
+        );
+        assert_matches!(
+            after_wrong_era.verify(&identities),
+            Err(Error::InvalidBlockSignature { slot }) if slot == boundary.get()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn tampered_or_unsigned_blocks_are_rejected() -> TestResult {
+        // Given
+        let key = Keypair::generate();
+        let identities = IdentityHistory::new(key.pubkey());
+        let mut block = Block::genesis();
+        let unsigned = block.finalize();
+        let mut tampered = block.finalize();
+        tampered.sign(&key);
+
+        // When
+        tampered.state_root = BlockHash::from_bytes(&[1; 64])?;
+
+        // Then
+        assert_matches!(
+            unsigned.verify(&identities),
+            Err(Error::InvalidBlockSignature { slot: 1 })
+        );
+        assert_matches!(
+            tampered.verify(&identities),
+            Err(Error::BlockHashMismatch { slot: 2 })
+        );
+
+        Ok(())
+    }
 }
@@ -3,7 +3,7 @@
 // Creation date: Sunday 16 February 2025
 // Author: Vincent Berthier <vincent.berthier@posteo.org>
 // -----
-// Last modified: Sunday 16 February 2025 @ 01:13:25
+// Last modified: Thursday 15 October 2026 @ 15:41:02
 // Modified by: Vincent Berthier
 // -----
 // Copyright (c) 2025 <Vincent Berthier>
@@ -28,13 +28,21 @@
 
 use std::{fmt::Debug, str::FromStr};
 
+use borsh::{BorshDeserialize, BorshSerialize};
+
 use super::{Error, Result};
 
 /// The type of a block hash.
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+///
+/// The hash of a network's [`Genesis`](super::Genesis) is also its chain id.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, BorshSerialize, BorshDeserialize)]
 pub struct BlockHash([u8; 64]);
 
 impl BlockHash {
+    /// Builds a block hash from its bytes.
+    ///
+    /// # Errors
+    /// If there aren't exactly 64 bytes.
     pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
         let bytes = bytes
             .to_vec()
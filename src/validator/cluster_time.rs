@@ -0,0 +1,192 @@
+// File: src/validator/cluster_time.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 20:51:54
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::collections::BTreeMap;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use tracing::{debug, instrument, trace, warn};
+
+/// The number of recent slots whose estimated time is kept.
+pub const BLOCK_TIME_HISTORY: usize = 8_192;
+
+/// The cluster's estimate of the wall-clock time, built from the validators' timestamps.
+#[derive(Clone, Debug, Default, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct ClusterClock {
+    /// The estimated unix timestamp of the recent slots.
+    block_times: BTreeMap<u64, i64>,
+}
+
+impl ClusterClock {
+    /// Starts a clock without any estimate.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            block_times: BTreeMap::new(),
+        }
+    }
+
+    /// Records the time of a slot from the timestamps reported by the validators.
+    ///
+    /// The estimate is the median of the timestamps, clamped so it never goes
+    /// back in time nor moves forward by more than the maximum step since
+    /// the previous slot. Only the [`BLOCK_TIME_HISTORY`] most recent slots are kept.
+    ///
+    /// # Parameters
+    /// * `slot` - The slot whose time is estimated,
+    /// * `timestamps` - The unix timestamps reported by the validators for the slot,
+    /// * `max_step` - The maximum number of seconds the estimate may move forward in a single slot.
+    ///
+    /// # Returns
+    /// The estimated time of the slot, if one could be made.
+    #[instrument(skip(self, timestamps))]
+    pub fn record_slot(&mut self, slot: u64, timestamps: &[i64], max_step: i64) -> Option<i64> {
+        debug!(n = timestamps.len(), "estimating cluster time");
+        let previous = self
+            .block_times
+            .range(..slot)
+            .next_back()
+            .map(|(_, &time)| time);
+        let time = if let Some(median) = median(timestamps) {
+            match previous {
+                Some(previous) if median < previous => {
+                    warn!(median, previous, "cluster time would go backwards");
+                    previous
+                }
+                Some(previous) if median.saturating_sub(previous) > max_step => {
+                    warn!(median, previous, "cluster time jumps too far ahead");
+                    previous.saturating_add(max_step)
+                }
+                _ => median,
+            }
+        } else {
+            trace!("no timestamp reported, keeping the previous estimate");
+            previous?
+        };
+        self.block_times.insert(slot, time);
+        while self.block_times.len() > BLOCK_TIME_HISTORY {
+            self.block_times.pop_first();
+        }
+
+        Some(time)
+    }
+
+    /// Get the estimated unix timestamp of a recent slot.
+    #[must_use]
+    pub fn block_time(&self, slot: u64) -> Option<i64> {
+        self.block_times.get(&slot).copied()
+    }
+}
+
+/// The median of the timestamps (the lower one for an even count).
+fn median(timestamps: &[i64]) -> Option<i64> {
+    let mut sorted = timestamps.to_vec();
+    sorted.sort_unstable();
+    sorted.get(sorted.len().checked_sub(1)? >> 1).copied()
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+
+    const START: i64 = 1_700_000_000;
+    const MAX_STEP: i64 = 10;
+
+    #[test]
+    fn skewed_validators_follow_median() {
+        // Given
+        let mut clock = ClusterClock::new();
+        let skews = [-300, -2, 0, 1, 3_600];
+
+        // When
+        let times = (1..=5_i64)
+            .map(|slot| {
+                let timestamps = skews
+                    .iter()
+                    .map(|skew| START + slot + skew)
+                    .collect::<Vec<_>>();
+                clock.record_slot(slot.unsigned_abs(), &timestamps, MAX_STEP)
+            })
+            .collect::<Vec<_>>();
+
+        // Then
+        assert_eq!(
+            times,
+            (1..=5).map(|slot| Some(START + slot)).collect::<Vec<_>>()
+        );
+        assert_eq!(clock.block_time(3), Some(START + 3));
+        assert_eq!(clock.block_time(6), None);
+    }
+
+    #[test]
+    fn time_never_goes_backwards() {
+        // Given
+        let mut clock = ClusterClock::new();
+        clock.record_slot(1, &[START], MAX_STEP);
+
+        // When
+        let time = clock.record_slot(2, &[START - 5, START - 4], MAX_STEP);
+
+        // Then
+        assert_eq!(time, Some(START));
+    }
+
+    #[test]
+    fn time_jumps_are_clamped() {
+        // Given
+        let mut clock = ClusterClock::new();
+        clock.record_slot(1, &[START], MAX_STEP);
+
+        // When
+        let jump = clock.record_slot(2, &[START + 100], MAX_STEP);
+        let silent = clock.record_slot(3, &[], MAX_STEP);
+
+        // Then
+        assert_eq!(jump, Some(START + 10));
+        assert_eq!(silent, Some(START + 10));
+    }
+
+    #[test]
+    fn only_recent_slots_are_kept() {
+        // Given
+        let mut clock = ClusterClock::new();
+        let slots = BLOCK_TIME_HISTORY as u64 + 2;
+
+        // When
+        for slot in 1..=slots {
+            clock.record_slot(slot, &[START], MAX_STEP);
+        }
+
+        // Then
+        assert_eq!(clock.block_time(2), None);
+        assert_eq!(clock.block_time(3), Some(START));
+        assert_eq!(clock.block_time(slots), Some(START));
+    }
+}
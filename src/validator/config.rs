@@ -0,0 +1,201 @@
+// File: src/validator/config.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 20:51:54
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::{sync::Arc, time::Duration};
+
+use tracing::warn;
+
+use crate::{
+    crypto::Pubkey,
+    transaction::{FeeModel, FeeStructure, Message, MAX_INSTRUCTIONS_PER_TRANSACTION},
+};
+
+use super::{
+    admission::AdmissionPolicy, transaction_queue::DEFAULT_IDEMPOTENCY_WINDOW, AuditConfig,
+    BlockHash, Error, MemoryBudget, Result, RewardsConfig,
+};
+
+/// How the processor orders the pending transactions when building a batch.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum QueuePolicy {
+    /// Transactions are processed in the order they were received.
+    #[default]
+    Fifo,
+    /// Transactions are picked round-robin across payers, keeping each payer's order.
+    FairByPayer,
+}
+
+/// Configuration of the validator.
+#[derive(Clone, Debug)]
+pub struct ValidatorConfig {
+    /// How pending transactions are ordered within a batch.
+    pub queue_policy: QueuePolicy,
+    /// Maximum number of transactions executed in a single batch (a block).
+    pub batch_size: usize,
+    /// Maximum serialized size of the transactions executed in a single batch.
+    pub max_block_bytes: usize,
+    /// Maximum number of instructions a transaction may hold to be executed
+    /// (never more than [`MAX_INSTRUCTIONS_PER_TRANSACTION`], higher values are clamped).
+    pub max_instructions: usize,
+    /// Whether the balance changes of the accounts are recorded (costs disk space).
+    pub balance_history: bool,
+    /// The parameters of the fee charged to the transactions, and how it's shared.
+    pub fees: FeeStructure,
+    /// A custom computation of the fee, replacing the parameters of `fees`
+    /// (which still decide how the fee is shared).
+    pub fee_model: Option<Arc<dyn FeeModel>>,
+    /// The identity of the validator, paid the producer's part of the fees
+    /// (the whole fees are burned without one).
+    pub identity: Option<Pubkey>,
+    /// How long a transaction whose sequence number is ahead of its payer's
+    /// waits for the missing ones before failing.
+    pub sequence_timeout: Duration,
+    /// How long a slot lasts when nothing is executed in it but transactions are
+    /// deferred to a later slot.
+    pub slot_duration: Duration,
+    /// How far the estimated time of the cluster may move forward in a single slot.
+    pub max_time_step: Duration,
+    /// Whether the fee is still charged to the transactions whose deadline passed
+    /// before they were executed (their instructions are never executed).
+    pub charge_missed_deadlines: bool,
+    /// The policies deciding which transactions are taken in, evaluated in order.
+    pub admission: Vec<Arc<dyn AdmissionPolicy>>,
+    /// Whether the time each transaction takes to go through the validator is recorded.
+    pub latency_tracking: bool,
+    /// The chain id of the network (the hash of its genesis): the transactions must be
+    /// bound to it. Any transaction is accepted without one.
+    pub chain: Option<BlockHash>,
+    /// How often a signed checkpoint of the ledger is produced for the auditors, if ever.
+    pub audit: Option<AuditConfig>,
+    /// How long the idempotency key of a submission is remembered: a payer reusing it
+    /// within that time gets the original submission back instead of a new one.
+    pub idempotency_window: Duration,
+    /// The memory the validator may use, and when it starts shedding load.
+    pub memory: MemoryBudget,
+    /// How the validators are rewarded at the end of each epoch, if they are.
+    pub rewards: Option<RewardsConfig>,
+}
+
+impl Default for ValidatorConfig {
+    fn default() -> Self {
+        Self {
+            queue_policy: QueuePolicy::default(),
+            batch_size: 64,
+            max_block_bytes: 1024 * 1024,
+            max_instructions: MAX_INSTRUCTIONS_PER_TRANSACTION,
+            balance_history: false,
+            fees: FeeStructure::default(),
+            fee_model: None,
+            identity: None,
+            sequence_timeout: Duration::from_secs(2),
+            slot_duration: Duration::from_millis(400),
+            max_time_step: Duration::from_secs(60),
+            charge_missed_deadlines: true,
+            admission: Vec::new(),
+            latency_tracking: false,
+            chain: None,
+            audit: None,
+            idempotency_window: DEFAULT_IDEMPOTENCY_WINDOW,
+            memory: MemoryBudget::default(),
+            rewards: None,
+        }
+    }
+}
+
+impl ValidatorConfig {
+    /// The maximum number of instructions of a transaction, clamped to
+    /// [`MAX_INSTRUCTIONS_PER_TRANSACTION`].
+    #[must_use]
+    pub fn max_instructions(&self) -> usize {
+        self.max_instructions.min(MAX_INSTRUCTIONS_PER_TRANSACTION)
+    }
+
+    /// Checks that a new configuration only changes the parameters that can change
+    /// while the validator runs.
+    ///
+    /// The chain id, the queue policy, the balance history, the audit checkpoints
+    /// and the rewards are fixed; everything else (batch sizes, limits, fees, admission policies,
+    /// timeouts, the idempotency window, the memory budget and latency tracking)
+    /// can be reloaded.
+    ///
+    /// # Parameters
+    /// * `new` - The configuration replacing this one.
+    ///
+    /// # Errors
+    /// If fixed parameters differ, listing all of them.
+    pub fn check_reload(&self, new: &Self) -> Result<()> {
+        let audit = |config: &Self| {
+            config
+                .audit
+                .as_ref()
+                .map(|audit| (audit.interval, audit.signer.pubkey()))
+        };
+        let rewards = |config: &Self| {
+            config.rewards.as_ref().map(|rewards| {
+                (
+                    rewards.inflation,
+                    rewards.block_points,
+                    rewards.vote_points,
+                    rewards.signer.pubkey(),
+                )
+            })
+        };
+        let fields = [
+            ("chain", self.chain == new.chain),
+            ("queue_policy", self.queue_policy == new.queue_policy),
+            (
+                "balance_history",
+                self.balance_history == new.balance_history,
+            ),
+            ("audit", audit(self) == audit(new)),
+            ("rewards", rewards(self) == rewards(new)),
+        ]
+        .into_iter()
+        .filter_map(|(field, same)| (!same).then_some(field))
+        .collect::<Vec<_>>();
+        if !fields.is_empty() {
+            warn!(?fields, "fixed parameters can't be reloaded");
+            return Err(Error::ImmutableConfig { fields });
+        }
+
+        Ok(())
+    }
+
+    /// Computes the fee charged to the transaction of a message.
+    ///
+    /// # Parameters
+    /// * `message` - The message of the transaction,
+    /// * `queue_pressure` - The number of transactions waiting to be executed, in batches.
+    #[must_use]
+    pub fn fee(&self, message: &Message, queue_pressure: f64) -> u64 {
+        self.fee_model.as_ref().map_or_else(
+            || self.fees.fee(message, queue_pressure),
+            |model| model.fee(message, queue_pressure),
+        )
+    }
+}
@@ -3,7 +3,7 @@
 // Creation date: Saturday 08 February 2025
 // Author: Vincent Berthier <vincent.berthier@posteo.org>
 // -----
-// Last modified: Sunday 16 February 2025 @ 00:10:14
+// Last modified: Thursday 15 October 2026 @ 17:15:59
 // Modified by: Vincent Berthier
 // -----
 // Copyright (c) 2025 <Vincent Berthier>
@@ -28,34 +28,197 @@
 
 use derive_more::derive::{Display, From};
 
+use super::BlockHash;
+use crate::crypto::Pubkey;
+
 /// Errors of the validator module.
 #[derive(Debug, Display, From)]
 #[display("within the validator: {_variant}")]
 pub enum Error {
+    /// The allocation file couldn't be read.
+    #[display("could not read the allocation file {path:?}: {reason}")]
+    AllocationFile {
+        /// The path of the file.
+        path: std::path::PathBuf,
+        /// Why it couldn't be read.
+        reason: std::io::Error,
+    },
+    /// The total amount of prisms of a transaction's accounts changed by more than its fee.
+    #[display("prisms total has changed by {delta} after fees")]
+    BalanceInvariantViolation {
+        /// The change of the total, fees excluded.
+        delta: i128,
+    },
+    /// The hash of a block doesn't match its content.
+    #[display("the hash of the block of slot {slot} doesn't match its content")]
+    BlockHashMismatch {
+        /// The slot of the block.
+        slot: u64,
+    },
+    /// A checkpoint doesn't follow the previous one of an audit trail.
+    #[display("the audit checkpoint of slot {slot} doesn't follow the previous one")]
+    BrokenAuditTrail {
+        /// The slot of the checkpoint.
+        slot: u64,
+    },
+    /// A program that isn't built in failed with its own error code.
+    #[display(
+        "instruction {instruction} failed with the custom error {code} of program '{program}'"
+    )]
+    CustomProgramError {
+        /// The position of the instruction in the transaction.
+        instruction: usize,
+        /// The program executing the instruction.
+        program: Pubkey,
+        /// The program's own error code.
+        code: u32,
+    },
+    /// The transaction reached the executor after its deadline.
+    #[display("the transaction's deadline (slot {deadline}) passed, the current slot is {slot}")]
+    DeadlineExceeded {
+        /// The last slot the transaction could be executed at.
+        deadline: u64,
+        /// The slot the transaction would have been executed at.
+        slot: u64,
+    },
+    /// An account is listed more than once in an allocation file.
+    #[display("'{key}' is allocated again on line {line}")]
+    DuplicateAllocation {
+        /// The duplicated account.
+        key: Pubkey,
+        /// The line of the duplicate.
+        line: usize,
+    },
+    /// A reloaded configuration changes parameters that are fixed while the validator runs.
+    #[display("these parameters can't change while the validator runs: {}", fields.join(", "))]
+    ImmutableConfig {
+        /// The parameters that were changed.
+        fields: Vec<&'static str>,
+    },
+    /// The public intake already received transactions.
+    #[display("the public transaction intake is already open")]
+    IntakeAlreadyOpen,
+    /// The validator doesn't accept new transactions for now.
+    #[display("the transaction intake is paused")]
+    IntakePaused,
+    /// A block isn't signed by the identity its producer had during its slot.
+    #[display("the block of slot {slot} isn't signed by the expected identity")]
+    InvalidBlockSignature {
+        /// The slot of the block.
+        slot: u64,
+    },
+    /// A transaction of a bundle has invalid signatures.
+    #[display("the signatures of transaction {index} of the bundle are invalid")]
+    InvalidBundleMember {
+        /// The position of the transaction in the bundle.
+        index: usize,
+    },
+    /// An audit checkpoint isn't signed by the expected key.
+    #[display("the audit checkpoint of slot {slot} isn't signed by the expected key")]
+    InvalidCheckpointSignature {
+        /// The slot of the checkpoint.
+        slot: u64,
+    },
+    /// A duration, amount or number of slots couldn't be parsed.
+    #[display("'{token}' is invalid at offset {offset}: {reason}")]
+    InvalidQuantity {
+        /// The text that was parsed.
+        token: String,
+        /// The position of the offending character, in bytes.
+        offset: usize,
+        /// What's wrong with it.
+        reason: &'static str,
+    },
     /// The transaction's signatures are missing or do not match the expectation.
     #[display("the transaction’s signatures are invalid")]
     InvalidTransactionSignatures,
-    /// The total amount of prisms has changed while it's not supposed to.
-    #[display("prisms total has changed")]
-    PrismTotalChanged,
+    /// A line of an allocation file couldn't be parsed.
+    #[display("line {line} of the allocation file is malformed: {reason}")]
+    MalformedAllocation {
+        /// The line number.
+        line: usize,
+        /// What's wrong with it.
+        reason: &'static str,
+    },
+    /// The validator is short of memory, and refuses new transactions.
+    #[display("the {component:?} memory is over its limit of {limit} bytes")]
+    MemoryPressure {
+        /// The component whose limit was reached.
+        component: super::MemoryComponent,
+        /// The limit, in bytes.
+        limit: usize,
+    },
+    /// A subsystem didn't behave as expected during the self-test.
+    #[display("the self-test of the {subsystem:?} subsystem failed")]
+    SelfTestFailed {
+        /// The subsystem that failed.
+        subsystem: super::Subsystem,
+    },
+    /// The transaction's sequence number doesn't follow the last one executed for its payer.
+    #[display("expected the sequence number {expected}, got {got}")]
+    SequenceGap {
+        /// The sequence number expected for the payer.
+        expected: u64,
+        /// The sequence number of the transaction.
+        got: u64,
+    },
     /// Error while sending a message to a thread
     #[display("could not send a '{kind}' message")]
     SendMessage {
         /// The kind of message that failed.
         kind: &'static str,
     },
+    /// The allocations exceed the maximum supply.
+    #[display("{total} prisms are allocated, more than the maximum supply of {max}")]
+    SupplyExceeded {
+        /// The total allocated.
+        total: u128,
+        /// The maximum supply.
+        max: u64,
+    },
+    /// The transaction holds more instructions than the validator accepts.
+    #[display("the validator doesn't accept more than {max} instructions per transaction")]
+    TooManyInstructions {
+        /// The maximum number of instructions.
+        max: usize,
+    },
+    /// A program was invoked more times than it allows in a single transaction.
+    #[display("'{program}' can't be invoked more than {max} times per transaction")]
+    TooManyInvocations {
+        /// The program invoked.
+        program: Pubkey,
+        /// The maximum number of invocations.
+        max: usize,
+    },
     /// When the lock on the vault could not be obtained.
     #[display("the lock on the vault could not be obtained")]
     VaultLock,
+    /// A transaction was signed for another network than the validator's.
+    #[display("the transaction is meant for chain {got:?}, not {expected:?}")]
+    WrongChain {
+        /// The chain id of the validator.
+        expected: Box<BlockHash>,
+        /// The chain id the transaction was signed for, if any.
+        got: Option<Box<BlockHash>>,
+    },
     /// When byte array doesn't have the right size for a block hash
     #[display("the given hash is not compatible with a block hash")]
     WrongHashLength,
+    /// An error occurred while operating on an account.
+    #[from]
+    Account(crate::account::Error),
+    /// An error occurred in a cryptographic operation.
+    #[from]
+    Crypto(crate::crypto::Error),
     /// An error occurred in the vault
     #[from]
     Io(crate::io::Error),
     /// An error occurred while running a program.
     #[from]
     Program(crate::program::Error),
+    /// An error occurred while reading a transaction.
+    #[from]
+    Transaction(crate::transaction::Error),
     /// When a string is not a valid `bs58` encoding of a block hash
     #[from]
     HashParse(bs58::decode::Error),
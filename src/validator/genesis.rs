@@ -0,0 +1,386 @@
+// File: src/validator/genesis.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 15:15:37
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! The initial balances of a network.
+//!
+//! An allocation file lists the accounts funded at genesis, one per line:
+//! `pubkey,prisms` or `pubkey,prisms,delegation` (the validator the stake is
+//! delegated to). Blank lines and lines starting with `#` are ignored.
+
+use std::{collections::BTreeMap, path::Path};
+
+use sha2::{Digest as _, Sha512};
+use tracing::{debug, instrument, trace, warn};
+
+use super::{blockhash::BlockHash, units::parse_prisms, Error, Result};
+use crate::crypto::Pubkey;
+
+/// What to do with an account listed more than once in an allocation file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    /// The file is rejected.
+    #[default]
+    Reject,
+    /// The amounts are summed (the delegations must agree).
+    Sum,
+}
+
+/// How an allocation file is validated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GenesisConfig {
+    /// The maximum total of prisms allocated at genesis.
+    pub max_supply: u64,
+    /// What to do with the accounts listed more than once.
+    pub duplicates: DuplicatePolicy,
+}
+
+impl Default for GenesisConfig {
+    fn default() -> Self {
+        Self {
+            max_supply: u64::MAX,
+            duplicates: DuplicatePolicy::default(),
+        }
+    }
+}
+
+/// The initial balance of an account.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Allocation {
+    /// The prisms of the account.
+    pub prisms: u64,
+    /// The validator the account's stake is delegated to, if any.
+    pub delegation: Option<Pubkey>,
+}
+
+/// The initial state of a network.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Genesis {
+    /// The initial balances, by account.
+    allocations: BTreeMap<Pubkey, Allocation>,
+}
+
+impl Genesis {
+    /// Reads and validates an allocation file.
+    ///
+    /// # Parameters
+    /// * `path` - The path of the allocation file,
+    /// * `config` - How the allocations are validated.
+    ///
+    /// # Errors
+    /// If the file can't be read, or if its content isn't valid (see [`Self::from_allocations`]).
+    #[instrument(skip(config))]
+    pub async fn from_allocation_file<P: AsRef<Path> + std::fmt::Debug>(
+        path: P,
+        config: &GenesisConfig,
+    ) -> Result<Self> {
+        debug!("reading allocation file");
+        let content = tokio::fs::read_to_string(path.as_ref())
+            .await
+            .map_err(|reason| Error::AllocationFile {
+                path: path.as_ref().to_path_buf(),
+                reason,
+            })?;
+
+        Self::from_allocations(&content, config)
+    }
+
+    /// Validates the content of an allocation file.
+    ///
+    /// # Parameters
+    /// * `content` - The allocations, one per line,
+    /// * `config` - How the allocations are validated.
+    ///
+    /// # Errors
+    /// If a line is malformed, if an account is listed twice and the duplicates
+    /// are rejected (or their delegations differ), or if the total exceeds the
+    /// maximum supply.
+    #[instrument(skip_all)]
+    pub fn from_allocations(content: &str, config: &GenesisConfig) -> Result<Self> {
+        debug!("validating allocations");
+        let mut allocations = BTreeMap::new();
+        let mut total = 0_u128;
+        for (index, row) in content.lines().enumerate() {
+            let line = index + 1;
+            let row = row.trim();
+            if row.is_empty() || row.starts_with('#') {
+                trace!(line, "skipping line");
+                continue;
+            }
+            let (key, allocation) = parse_row(row, line)?;
+            total += u128::from(allocation.prisms);
+            match allocations.get_mut(&key) {
+                None => {
+                    allocations.insert(key, allocation);
+                }
+                Some(existing) => {
+                    merge(existing, allocation, config.duplicates)
+                        .ok_or(Error::DuplicateAllocation { key, line })?;
+                }
+            }
+        }
+        if total > u128::from(config.max_supply) {
+            warn!(
+                total,
+                max = config.max_supply,
+                "the allocations exceed the supply"
+            );
+            return Err(Error::SupplyExceeded {
+                total,
+                max: config.max_supply,
+            });
+        }
+
+        Ok(Self { allocations })
+    }
+
+    /// The initial balances, by account.
+    #[must_use]
+    pub const fn allocations(&self) -> &BTreeMap<Pubkey, Allocation> {
+        &self.allocations
+    }
+
+    /// The total of prisms allocated.
+    #[must_use]
+    pub fn total(&self) -> u64 {
+        self.allocations
+            .values()
+            .map(|allocation| allocation.prisms)
+            .sum()
+    }
+
+    /// The hash of the allocations, which doesn't depend on their order in the file.
+    #[expect(clippy::little_endian_bytes, clippy::unwrap_used)]
+    #[must_use]
+    pub fn hash(&self) -> BlockHash {
+        let mut hasher = Sha512::new();
+        for (key, allocation) in &self.allocations {
+            hasher.update(key);
+            hasher.update(allocation.prisms.to_le_bytes());
+            match allocation.delegation {
+                Some(delegation) => {
+                    hasher.update([1]);
+                    hasher.update(delegation);
+                }
+                None => hasher.update([0]),
+            }
+        }
+
+        BlockHash::from_bytes(&hasher.finalize()).unwrap()
+    }
+}
+
+/// Parses a line of an allocation file.
+fn parse_row(row: &str, line: usize) -> Result<(Pubkey, Allocation)> {
+    let fields = row.split(',').map(str::trim).collect::<Vec<_>>();
+    let malformed = |reason| Error::MalformedAllocation { line, reason };
+    let (key, prisms, delegation) = match fields.as_slice() {
+        [key, prisms] => (key, prisms, None),
+        [key, prisms, delegation] => (key, prisms, Some(delegation)),
+        _ => return Err(malformed("expected 'pubkey,prisms[,delegation]'")),
+    };
+    let key = key.parse().map_err(|_err| malformed("invalid pubkey"))?;
+    let prisms = parse_prisms(prisms).map_err(|err| match err {
+        Error::InvalidQuantity { reason, .. } => malformed(reason),
+        err => err,
+    })?;
+    let delegation = delegation
+        .map(|delegation| delegation.parse())
+        .transpose()
+        .map_err(|_err| malformed("invalid delegation"))?;
+
+    Ok((key, Allocation { prisms, delegation }))
+}
+
+/// Merges a duplicated allocation into the existing one, if the policy allows it.
+fn merge(existing: &mut Allocation, duplicate: Allocation, policy: DuplicatePolicy) -> Option<()> {
+    if policy == DuplicatePolicy::Reject || existing.delegation != duplicate.delegation {
+        warn!("duplicated allocation");
+        return None;
+    }
+    existing.prisms = existing.prisms.checked_add(duplicate.prisms)?;
+
+    Some(())
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use std::assert_matches::assert_matches;
+
+    use test_log::test;
+
+    use crate::crypto::Keypair;
+
+    use super::*;
+    type TestResult = core::result::Result<(), Box<dyn core::error::Error>>;
+
+    fn rows(keys: &[Pubkey]) -> Vec<String> {
+        keys.iter()
+            .enumerate()
+            .map(|(amount, key)| format!("{key},{}", amount + 1))
+            .collect()
+    }
+
+    #[test]
+    fn hash_does_not_depend_on_order() -> TestResult {
+        // Given
+        let keys = (0..5_u8)
+            .map(|_| Keypair::generate().pubkey())
+            .collect::<Vec<_>>();
+        let mut lines = rows(&keys);
+        lines.push(format!("{},10,{}", Keypair::generate().pubkey(), keys[0]));
+        let content = lines.join("\n");
+        lines.reverse();
+        let reversed = format!("# reversed\n\n{}", lines.join("\n"));
+
+        // When
+        let genesis = Genesis::from_allocations(&content, &GenesisConfig::default())?;
+        let other = Genesis::from_allocations(&reversed, &GenesisConfig::default())?;
+
+        // Then
+        assert_eq!(genesis.allocations().len(), 6);
+        assert_eq!(genesis.total(), 25);
+        assert_eq!(genesis, other);
+        assert_eq!(genesis.hash(), other.hash());
+
+        Ok(())
+    }
+
+    #[test]
+    fn amounts_can_be_grouped() -> TestResult {
+        // Given
+        let key = Keypair::generate().pubkey();
+        let content = format!("{key},1_000_000");
+
+        // When
+        let genesis = Genesis::from_allocations(&content, &GenesisConfig::default())?;
+        let res = Genesis::from_allocations(&format!("{key},1__000"), &GenesisConfig::default());
+
+        // Then
+        assert_eq!(genesis.total(), 1_000_000);
+        assert_matches!(
+            res,
+            Err(Error::MalformedAllocation {
+                line: 1,
+                reason: "misplaced digit separator"
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn malformed_rows_are_reported() {
+        // Given
+        let key = Keypair::generate().pubkey();
+        let contents = [
+            format!("{key},10\n{key}"),
+            format!("# header\n{key},ten"),
+            format!("{key},-1"),
+            "H1LS9EF2cPrmmM828buVJSvvbztLc9buJPHM,10".to_owned(),
+            format!("{key},10,not a key"),
+        ];
+
+        for (content, expected) in contents.iter().zip([2, 2, 1, 1, 1]) {
+            // When
+            let res = Genesis::from_allocations(content, &GenesisConfig::default());
+
+            // Then
+            assert_matches!(res, Err(Error::MalformedAllocation { line, .. }) if line == expected);
+        }
+    }
+
+    #[test]
+    fn duplicates_are_rejected_or_summed() -> TestResult {
+        // Given
+        let key = Keypair::generate().pubkey();
+        let validator = Keypair::generate().pubkey();
+        let content = format!("{key},10\n{key},5");
+        let conflicting = format!("{key},10,{validator}\n{key},5");
+        let summing = GenesisConfig {
+            duplicates: DuplicatePolicy::Sum,
+            ..GenesisConfig::default()
+        };
+
+        // When
+        let rejected = Genesis::from_allocations(&content, &GenesisConfig::default());
+        let summed = Genesis::from_allocations(&content, &summing)?;
+        let conflict = Genesis::from_allocations(&conflicting, &summing);
+
+        // Then
+        assert_matches!(rejected, Err(Error::DuplicateAllocation { key: dup, line: 2 }) if dup == key);
+        assert_eq!(summed.allocations()[&key].prisms, 15);
+        assert_matches!(conflict, Err(Error::DuplicateAllocation { line: 2, .. }));
+
+        Ok(())
+    }
+
+    #[test]
+    fn total_is_capped_by_supply() {
+        // Given
+        let keys = (0..3_u8)
+            .map(|_| Keypair::generate().pubkey())
+            .collect::<Vec<_>>();
+        let content = rows(&keys).join("\n");
+        let overflowing = format!("{},{}\n{},{}", keys[0], u64::MAX, keys[1], u64::MAX);
+        let capped = GenesisConfig {
+            max_supply: 5,
+            ..GenesisConfig::default()
+        };
+
+        // When
+        let exceeded = Genesis::from_allocations(&content, &capped);
+        let overflow = Genesis::from_allocations(&overflowing, &GenesisConfig::default());
+        let at_cap = Genesis::from_allocations(
+            &content,
+            &GenesisConfig {
+                max_supply: 6,
+                ..capped
+            },
+        );
+
+        // Then
+        assert_matches!(exceeded, Err(Error::SupplyExceeded { total: 6, max: 5 }));
+        assert_matches!(overflow, Err(Error::SupplyExceeded { .. }));
+        assert_matches!(at_cap, Ok(genesis) if genesis.total() == 6);
+    }
+
+    #[test(tokio::test)]
+    async fn missing_file_is_reported() -> TestResult {
+        // Given
+        const PATH: &str = "/tmp/bifrost/genesis-1/allocations.csv";
+
+        // When
+        let res = Genesis::from_allocation_file(PATH, &GenesisConfig::default()).await;
+
+        // Then
+        assert_matches!(res, Err(Error::AllocationFile { .. }));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,152 @@
+// File: src/validator/identity.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 14:48:44
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::collections::BTreeMap;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use tracing::{debug, instrument};
+
+use crate::{
+    crypto::Pubkey,
+    program::{Slot, SLOTS_PER_EPOCH},
+};
+
+/// The successive identities of the validator, each valid from an epoch boundary.
+///
+/// Rotating the identity only takes effect at the next epoch, so the blocks already
+/// produced (and those of the current epoch) keep being checked against the previous key.
+#[derive(Clone, Debug, Default, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct IdentityHistory {
+    /// The identity of each era, by the first slot of the era.
+    eras: BTreeMap<u64, Pubkey>,
+}
+
+impl IdentityHistory {
+    /// Starts the history with the identity the validator was created with.
+    ///
+    /// # Parameters
+    /// * `identity` - The first identity of the validator.
+    #[must_use]
+    pub fn new(identity: Pubkey) -> Self {
+        Self {
+            eras: BTreeMap::from([(0, identity)]),
+        }
+    }
+
+    /// Schedules a new identity from the next epoch.
+    ///
+    /// Rotating twice within the same epoch replaces the pending identity.
+    ///
+    /// # Parameters
+    /// * `identity` - The new identity of the validator,
+    /// * `current_slot` - The slot the validator is in.
+    ///
+    /// # Returns
+    /// The first slot signed with the new identity.
+    #[instrument(skip(self, current_slot))]
+    pub fn rotate<S>(&mut self, identity: Pubkey, current_slot: S) -> Slot
+    where
+        S: Into<Slot>,
+    {
+        let boundary = current_slot
+            .into()
+            .epoch(SLOTS_PER_EPOCH)
+            .next()
+            .first_slot(SLOTS_PER_EPOCH);
+        debug!(%boundary, "rotating the validator identity");
+        self.eras.split_off(&boundary.get());
+        self.eras.insert(boundary.get(), identity);
+
+        boundary
+    }
+
+    /// Get the identity expected to sign a slot.
+    ///
+    /// # Parameters
+    /// * `slot` - The slot signed.
+    #[must_use]
+    pub fn identity_at<S>(&self, slot: S) -> Option<&Pubkey>
+    where
+        S: Into<Slot>,
+    {
+        self.eras
+            .range(..=slot.into().get())
+            .next_back()
+            .map(|(_, identity)| identity)
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use test_log::test;
+
+    use crate::{crypto::Keypair, program::Epoch};
+
+    use super::*;
+
+    #[test]
+    fn rotation_takes_effect_at_next_epoch() {
+        // Given
+        let old = Keypair::generate().pubkey();
+        let new = Keypair::generate().pubkey();
+        let mut history = IdentityHistory::new(old);
+
+        // When
+        let boundary = history.rotate(new, SLOTS_PER_EPOCH + 12);
+
+        // Then
+        assert_eq!(boundary, Epoch::new(2).first_slot(SLOTS_PER_EPOCH));
+        assert_eq!(history.identity_at(SLOTS_PER_EPOCH + 13), Some(&old));
+        assert_eq!(history.identity_at(boundary.saturating_sub(1)), Some(&old));
+        assert_eq!(history.identity_at(boundary), Some(&new));
+        assert_eq!(history.identity_at(u64::MAX), Some(&new));
+    }
+
+    #[test]
+    fn pending_rotation_is_replaced() {
+        // Given
+        let old = Keypair::generate().pubkey();
+        let replaced = Keypair::generate().pubkey();
+        let new = Keypair::generate().pubkey();
+        let mut history = IdentityHistory::new(old);
+        history.rotate(replaced, 10);
+
+        // When
+        let boundary = history.rotate(new, 20);
+
+        // Then
+        assert_eq!(history.identity_at(boundary), Some(&new));
+        assert_eq!(history.identity_at(10), Some(&old));
+        assert_eq!(history, {
+            let mut expected = IdentityHistory::new(old);
+            expected.rotate(new, 0);
+            expected
+        });
+    }
+}
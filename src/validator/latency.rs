@@ -0,0 +1,243 @@
+// File: src/validator/latency.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 15:08:58
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use tokio::time::{Duration, Instant};
+
+/// The number of buckets of a latency histogram.
+pub const LATENCY_BUCKETS: usize = 32;
+
+/// A step of a transaction's way through the validator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Stage {
+    /// The transaction was handed to the validator.
+    Received,
+    /// Its signatures were verified.
+    Sanitized,
+    /// It was picked in a batch.
+    Scheduled,
+    /// Its execution started.
+    ExecutionStart,
+    /// Its instructions were executed.
+    ExecutionEnd,
+    /// Its accounts were saved in the vault.
+    Committed,
+    /// Its status was sent to the client.
+    Notified,
+}
+
+impl Stage {
+    /// The number of stages.
+    pub const COUNT: usize = 7;
+    /// All the stages, in the order a transaction goes through them.
+    pub const ALL: [Self; Self::COUNT] = [
+        Self::Received,
+        Self::Sanitized,
+        Self::Scheduled,
+        Self::ExecutionStart,
+        Self::ExecutionEnd,
+        Self::Committed,
+        Self::Notified,
+    ];
+
+    const fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// When a transaction reached each stage, on a monotonic clock.
+///
+/// The stages a transaction never reached (e.g. a failed one is never committed) are missing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Timings {
+    stamps: [Option<Instant>; Stage::COUNT],
+}
+
+impl Timings {
+    /// Starts the timings of a transaction that was just received.
+    #[must_use]
+    pub fn received() -> Self {
+        let mut timings = Self::default();
+        timings.record(Stage::Received);
+        timings
+    }
+
+    /// Records that the transaction reached a stage now.
+    pub fn record(&mut self, stage: Stage) {
+        self.stamps[stage.index()] = Some(Instant::now());
+    }
+
+    /// Get when the transaction reached a stage, if it did.
+    #[must_use]
+    pub const fn at(&self, stage: Stage) -> Option<Instant> {
+        self.stamps[stage.index()]
+    }
+
+    /// Get the time it took to go from one stage to another, if the transaction reached both.
+    #[must_use]
+    pub fn between(&self, from: Stage, to: Stage) -> Option<Duration> {
+        Some(self.at(to)?.saturating_duration_since(self.at(from)?))
+    }
+
+    /// Get the time it took to reach a stage from the previous one the transaction reached.
+    #[must_use]
+    pub fn spent(&self, stage: Stage) -> Option<Duration> {
+        let reached = self.at(stage)?;
+        let previous = self.stamps[..stage.index()]
+            .iter()
+            .rev()
+            .find_map(|&at| at)?;
+        Some(reached.saturating_duration_since(previous))
+    }
+
+    /// Whether the stages the transaction reached were reached in order.
+    #[must_use]
+    pub fn is_monotonic(&self) -> bool {
+        self.stamps.iter().flatten().is_sorted()
+    }
+}
+
+/// The distribution of durations, in buckets of powers of two microseconds.
+///
+/// The first bucket counts the durations under a microsecond, the bucket `i`
+/// the ones in [2^(i-1), 2^i) µs, and the last one everything above.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    buckets: [u64; LATENCY_BUCKETS],
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self {
+            buckets: [0; LATENCY_BUCKETS],
+        }
+    }
+}
+
+impl LatencyHistogram {
+    /// Counts a duration.
+    pub fn record(&mut self, duration: Duration) {
+        let bucket = &mut self.buckets[Self::bucket(duration)];
+        *bucket = bucket.saturating_add(1);
+    }
+
+    /// Get the number of durations counted.
+    #[must_use]
+    pub fn count(&self) -> u64 {
+        self.buckets.iter().sum()
+    }
+
+    /// Get the number of durations counted in each bucket.
+    #[must_use]
+    pub const fn buckets(&self) -> &[u64; LATENCY_BUCKETS] {
+        &self.buckets
+    }
+
+    fn bucket(duration: Duration) -> usize {
+        let micros = duration.as_micros();
+        let bits = u128::BITS.saturating_sub(micros.leading_zeros());
+        usize::try_from(bits).map_or(LATENCY_BUCKETS - 1, |bits| bits.min(LATENCY_BUCKETS - 1))
+    }
+}
+
+/// The time spent by the executed transactions to reach each stage from the previous one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LatencyStats {
+    stages: [LatencyHistogram; Stage::COUNT],
+}
+
+impl LatencyStats {
+    /// Adds the timings of a transaction.
+    pub fn record(&mut self, timings: &Timings) {
+        for stage in Stage::ALL {
+            if let Some(spent) = timings.spent(stage) {
+                self.stages[stage.index()].record(spent);
+            }
+        }
+    }
+
+    /// Get the distribution of the time spent to reach a stage
+    /// (always empty for [`Stage::Received`]).
+    #[must_use]
+    pub const fn histogram(&self, stage: Stage) -> &LatencyHistogram {
+        &self.stages[stage.index()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+
+    #[test]
+    fn durations_fall_in_power_of_two_buckets() {
+        // Given
+        let mut histogram = LatencyHistogram::default();
+
+        // When
+        histogram.record(Duration::from_nanos(500));
+        histogram.record(Duration::from_micros(1));
+        histogram.record(Duration::from_micros(3));
+        histogram.record(Duration::from_micros(4));
+        histogram.record(Duration::from_secs(1_000_000));
+
+        // Then
+        let buckets = histogram.buckets();
+        assert_eq!(buckets[0], 1);
+        assert_eq!(buckets[1], 1);
+        assert_eq!(buckets[2], 1);
+        assert_eq!(buckets[3], 1);
+        assert_eq!(buckets[LATENCY_BUCKETS - 1], 1);
+        assert_eq!(histogram.count(), 5);
+    }
+
+    #[test]
+    fn missing_stages_are_skipped() {
+        // Given
+        let mut timings = Timings::received();
+        timings.record(Stage::ExecutionStart);
+        timings.record(Stage::Notified);
+
+        // When
+        let mut stats = LatencyStats::default();
+        stats.record(&timings);
+
+        // Then
+        assert!(timings.is_monotonic());
+        assert_eq!(timings.spent(Stage::Committed), None);
+        assert_eq!(
+            timings.spent(Stage::Notified),
+            timings.between(Stage::ExecutionStart, Stage::Notified)
+        );
+        assert_eq!(stats.histogram(Stage::Received).count(), 0);
+        assert_eq!(stats.histogram(Stage::Scheduled).count(), 0);
+        assert_eq!(stats.histogram(Stage::ExecutionStart).count(), 1);
+        assert_eq!(stats.histogram(Stage::Notified).count(), 1);
+        assert!(timings.at(Stage::Sanitized).is_none());
+    }
+}
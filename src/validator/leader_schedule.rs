@@ -0,0 +1,231 @@
+// File: src/validator/leader_schedule.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 20:31:20
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::collections::BTreeMap;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use sha2::{Digest as _, Sha512};
+use tracing::{debug, instrument};
+
+use crate::{account::Stake, crypto::Pubkey, program::Epoch};
+
+/// Number of consecutive slots given to the same leader.
+pub const NUM_CONSECUTIVE_LEADER_SLOTS: u64 = 4;
+
+/// The validators producing the blocks of an epoch, weighted by their active stake.
+#[derive(Clone, Debug, Default, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct LeaderSchedule {
+    /// The epoch of the schedule.
+    epoch: Epoch,
+    /// The active stake of each validator, snapshotted at the start of the epoch.
+    stakes: BTreeMap<Pubkey, u64>,
+    /// The total active stake.
+    total: u64,
+}
+
+impl LeaderSchedule {
+    /// Creates the leader schedule of an epoch from the stake accounts.
+    ///
+    /// # Parameters
+    /// * `epoch` - The epoch of the schedule,
+    /// * `stakes` - The stake accounts.
+    #[must_use]
+    #[instrument(skip(epoch, stakes))]
+    pub fn new<'a, E, I>(epoch: E, stakes: I) -> Self
+    where
+        E: Into<Epoch>,
+        I: IntoIterator<Item = &'a Stake>,
+    {
+        let epoch = epoch.into();
+        debug!("computing leader schedule");
+        let mut totals = BTreeMap::new();
+        for stake in stakes {
+            let active = stake.active(epoch);
+            if let (Some(validator), true) = (stake.validator, active > 0) {
+                let staked: &mut u64 = totals.entry(validator).or_default();
+                *staked = staked.saturating_add(active);
+            }
+        }
+        let total = totals
+            .values()
+            .fold(0_u64, |sum, stake| sum.saturating_add(*stake));
+
+        Self {
+            epoch,
+            stakes: totals,
+            total,
+        }
+    }
+
+    /// Get the active stake of a validator during the epoch.
+    ///
+    /// # Parameters
+    /// * `validator` - The identity of the validator.
+    #[must_use]
+    pub fn stake_of(&self, validator: &Pubkey) -> u64 {
+        self.stakes.get(validator).copied().unwrap_or_default()
+    }
+
+    /// Get the number of validators with active stake during the epoch.
+    #[must_use]
+    pub fn validators(&self) -> usize {
+        self.stakes.len()
+    }
+
+    /// Get the epoch of the schedule.
+    #[must_use]
+    pub const fn epoch(&self) -> Epoch {
+        self.epoch
+    }
+
+    /// Get the stake needed for a vote to be confirmed (more than two thirds of the total).
+    #[expect(clippy::integer_division)]
+    #[must_use]
+    pub const fn confirmation_threshold(&self) -> u64 {
+        // split so that doubling the total can't overflow
+        self.total / 3 * 2 + self.total % 3 * 2 / 3 + 1
+    }
+
+    /// Get the leader of a slot of the epoch.
+    ///
+    /// # Parameters
+    /// * `slot` - The slot, relative to the start of the epoch.
+    ///
+    /// # Returns
+    /// The identity of the leader, or `None` if no stake is active.
+    #[expect(clippy::integer_division, clippy::little_endian_bytes)]
+    #[must_use]
+    pub fn leader(&self, slot: u64) -> Option<Pubkey> {
+        if self.total == 0 {
+            return None;
+        }
+        let mut hasher = Sha512::new();
+        hasher.update(self.epoch.get().to_le_bytes());
+        hasher.update((slot / NUM_CONSECUTIVE_LEADER_SLOTS).to_le_bytes());
+        let mut seed = [0; 8];
+        seed.copy_from_slice(&hasher.finalize()[..8]);
+
+        let mut target = u64::from_le_bytes(seed) % self.total;
+        for (validator, stake) in &self.stakes {
+            if target < *stake {
+                return Some(*validator);
+            }
+            target -= stake;
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+
+    use test_log::test;
+
+    use crate::crypto::Keypair;
+
+    use super::*;
+    fn delegated(amount: u64, validator: Pubkey) -> Stake {
+        let mut stake = Stake::new(Keypair::generate().pubkey(), 0, amount);
+        stake.delegate(validator, 0);
+        stake
+    }
+
+    fn slots_led(schedule: &LeaderSchedule, validator: &Pubkey) -> usize {
+        (0..4_000)
+            .filter(|slot| schedule.leader(*slot).as_ref() == Some(validator))
+            .count()
+    }
+
+    #[test]
+    fn stake_counts_from_next_epoch() {
+        // Given
+        let validator = Keypair::generate().pubkey();
+        let mut stake = Stake::new(Keypair::generate().pubkey(), 0, 1_000);
+
+        // When
+        stake.delegate(validator, 1);
+        let current = LeaderSchedule::new(1, [&stake]);
+        let next = LeaderSchedule::new(2, [&stake]);
+
+        // Then
+        assert_eq!(current.leader(0), None);
+        assert_eq!(next.leader(0), Some(validator));
+        assert_eq!(next.stake_of(&validator), 1_000);
+        assert_eq!(next.confirmation_threshold(), 667);
+    }
+
+    #[test]
+    fn schedule_shifts_with_stake() {
+        // Given
+        let validator1 = Keypair::generate().pubkey();
+        let validator2 = Keypair::generate().pubkey();
+        let mut moving = delegated(8_000, validator1);
+        let stakes = [delegated(1_000, validator1), delegated(1_000, validator2)];
+        let before = LeaderSchedule::new(1, stakes.iter().chain([&moving]));
+
+        // When
+        moving.delegate(validator2, 1);
+        let after = LeaderSchedule::new(2, stakes.iter().chain([&moving]));
+
+        // Then
+        assert!(slots_led(&before, &validator1) > 3 * slots_led(&before, &validator2));
+        assert!(slots_led(&after, &validator2) > 3 * slots_led(&after, &validator1));
+    }
+
+    #[test]
+    fn threshold_of_a_huge_stake_does_not_overflow() {
+        // Given
+        let validator = Keypair::generate().pubkey();
+        let stakes = [
+            delegated(u64::MAX, validator),
+            delegated(1_000, Keypair::generate().pubkey()),
+        ];
+
+        // When
+        let schedule = LeaderSchedule::new(1, &stakes);
+
+        // Then
+        assert_eq!(schedule.stake_of(&validator), u64::MAX);
+        assert_eq!(schedule.confirmation_threshold(), u64::MAX / 3 * 2 + 1);
+    }
+
+    #[test]
+    fn threshold_is_above_two_thirds() {
+        for (total, threshold) in [(0, 1), (1, 1), (2, 2), (3, 3), (4, 3), (5, 4), (1_000, 667)] {
+            let stakes = [delegated(total, Keypair::generate().pubkey())];
+            let schedule = LeaderSchedule::new(1, &stakes);
+            assert_eq!(
+                schedule.confirmation_threshold(),
+                threshold,
+                "wrong threshold for {total}"
+            );
+        }
+    }
+}
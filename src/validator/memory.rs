@@ -0,0 +1,307 @@
+// File: src/validator/memory.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 17:15:59
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! The accounting of the memory the validator uses, and the order it sheds load in.
+//!
+//! Each component has its own ceiling, and the validator as a whole has a global cap.
+//! When the memory used approaches the global cap, the validator:
+//! 1. refuses the new registrations with [`Error::MemoryPressure`] once less than the
+//!    [`headroom`](MemoryBudget::headroom) is left,
+//! 2. shrinks the account cache so that the queued and in-flight transactions never
+//!    take the validator over the cap.
+
+use crate::io::DEFAULT_CACHE_CAPACITY;
+
+use super::{Error, Result};
+
+/// A part of the validator whose memory is accounted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MemoryComponent {
+    /// The transactions sent that weren't scheduled yet.
+    Queue,
+    /// The transactions scheduled in a batch that weren't executed yet.
+    InFlight,
+    /// The accounts kept in memory to avoid reading them from the disk.
+    AccountCache,
+    /// All the components together.
+    Total,
+}
+
+/// The memory the validator may use, in bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MemoryBudget {
+    /// Maximum serialized size of the transactions waiting to be scheduled.
+    pub queue: usize,
+    /// Maximum serialized size of the transactions of a batch, lowering `max_block_bytes`.
+    pub in_flight: usize,
+    /// Maximum memory used by the account cache.
+    pub account_cache: usize,
+    /// Maximum memory used by all the components together.
+    pub global: usize,
+    /// The memory kept free under the global cap: new transactions are refused
+    /// once less is left.
+    pub headroom: usize,
+}
+
+impl Default for MemoryBudget {
+    fn default() -> Self {
+        Self {
+            queue: 64 * 1024 * 1024,
+            in_flight: 4 * 1024 * 1024,
+            account_cache: DEFAULT_CACHE_CAPACITY,
+            global: 256 * 1024 * 1024,
+            headroom: 16 * 1024 * 1024,
+        }
+    }
+}
+
+/// The memory used by each component, in bytes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoryUsage {
+    /// The serialized size of the transactions waiting to be scheduled.
+    pub queue: usize,
+    /// The serialized size of the transactions scheduled that weren't executed yet.
+    pub in_flight: usize,
+    /// The memory used by the account cache, when the processor last measured it.
+    pub account_cache: usize,
+}
+
+impl MemoryUsage {
+    /// The memory used by all the components together.
+    #[must_use]
+    pub const fn total(&self) -> usize {
+        self.queue
+            .saturating_add(self.in_flight)
+            .saturating_add(self.account_cache)
+    }
+}
+
+/// Keeps track of the memory used by the validator against its budget.
+#[derive(Debug, Default)]
+pub struct MemoryTracker {
+    budget: MemoryBudget,
+    usage: MemoryUsage,
+}
+
+impl MemoryTracker {
+    /// Changes the budget, the memory already used is kept.
+    pub const fn set_budget(&mut self, budget: MemoryBudget) {
+        self.budget = budget;
+    }
+
+    /// Get the memory used by each component.
+    pub const fn usage(&self) -> MemoryUsage {
+        self.usage
+    }
+
+    /// Accounts for a transaction sent to the queue, unless it doesn't fit the budget.
+    ///
+    /// # Parameters
+    /// * `bytes` - The serialized size of the transaction.
+    ///
+    /// # Errors
+    /// If the queue would go over its ceiling, or if less than the headroom would be
+    /// left under the global cap.
+    pub const fn reserve(&mut self, bytes: usize) -> Result<()> {
+        let queue = self.usage.queue.saturating_add(bytes);
+        if queue > self.budget.queue {
+            return Err(Error::MemoryPressure {
+                component: MemoryComponent::Queue,
+                limit: self.budget.queue,
+            });
+        }
+        let limit = self.budget.global.saturating_sub(self.budget.headroom);
+        if self.usage.total().saturating_add(bytes) > limit {
+            return Err(Error::MemoryPressure {
+                component: MemoryComponent::Total,
+                limit,
+            });
+        }
+        self.usage.queue = queue;
+
+        Ok(())
+    }
+
+    /// Moves a queued transaction to the in-flight ones, once it's scheduled in a batch.
+    pub const fn schedule(&mut self, bytes: usize) {
+        self.usage.queue = self.usage.queue.saturating_sub(bytes);
+        self.usage.in_flight = self.usage.in_flight.saturating_add(bytes);
+    }
+
+    /// Forgets a transaction once it was executed or rejected.
+    ///
+    /// # Parameters
+    /// * `bytes` - The serialized size of the transaction,
+    /// * `scheduled` - Whether it was scheduled in a batch.
+    pub const fn release(&mut self, bytes: usize, scheduled: bool) {
+        if scheduled {
+            self.usage.in_flight = self.usage.in_flight.saturating_sub(bytes);
+        } else {
+            self.usage.queue = self.usage.queue.saturating_sub(bytes);
+        }
+    }
+
+    /// Records the memory used by the account cache.
+    pub const fn set_account_cache(&mut self, bytes: usize) {
+        self.usage.account_cache = bytes;
+    }
+
+    /// The capacity left to the account cache: its ceiling, shrunk so that the queued
+    /// and in-flight transactions don't take the validator over the global cap.
+    pub const fn cache_capacity(&self) -> usize {
+        let left = self
+            .budget
+            .global
+            .saturating_sub(self.usage.queue)
+            .saturating_sub(self.usage.in_flight);
+        if left < self.budget.account_cache {
+            left
+        } else {
+            self.budget.account_cache
+        }
+    }
+
+    /// The maximum serialized size of the next batch.
+    ///
+    /// # Parameters
+    /// * `max_block_bytes` - The maximum size of a block.
+    pub fn batch_bytes(&self, max_block_bytes: usize) -> usize {
+        max_block_bytes.min(self.budget.in_flight)
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use std::assert_matches::assert_matches;
+
+    use test_log::test;
+
+    use super::*;
+    type TestResult = core::result::Result<(), Box<dyn core::error::Error>>;
+
+    const BUDGET: MemoryBudget = MemoryBudget {
+        queue: 1_000,
+        in_flight: 300,
+        account_cache: 800,
+        global: 2_000,
+        headroom: 200,
+    };
+
+    fn tracker() -> MemoryTracker {
+        let mut tracker = MemoryTracker::default();
+        tracker.set_budget(BUDGET);
+        tracker
+    }
+
+    #[test]
+    fn queue_is_refused_at_its_ceiling() {
+        // Given
+        let mut tracker = tracker();
+        let admitted = (0..10_u8).map(|_| tracker.reserve(100)).collect::<Vec<_>>();
+
+        // When
+        let refused = tracker.reserve(100);
+        tracker.release(100, false);
+        let after_release = tracker.reserve(100);
+
+        // Then
+        assert!(admitted.iter().all(Result::is_ok));
+        assert_matches!(
+            refused,
+            Err(Error::MemoryPressure {
+                component: MemoryComponent::Queue,
+                limit: 1_000
+            })
+        );
+        assert_matches!(after_release, Ok(()));
+        assert_eq!(tracker.usage().queue, 1_000);
+    }
+
+    #[test]
+    fn registrations_are_refused_before_the_global_cap() -> TestResult {
+        // Given
+        let mut tracker = tracker();
+        tracker.set_account_cache(800);
+        for _ in 0..9_u8 {
+            tracker.reserve(100)?;
+        }
+        tracker.schedule(300);
+
+        // When
+        let refused = tracker.reserve(200);
+        let admitted = tracker.reserve(100);
+
+        // Then
+        assert_matches!(
+            refused,
+            Err(Error::MemoryPressure {
+                component: MemoryComponent::Total,
+                limit: 1_800
+            })
+        );
+        assert_matches!(admitted, Ok(()));
+        assert_eq!(
+            tracker.usage(),
+            MemoryUsage {
+                queue: 700,
+                in_flight: 300,
+                account_cache: 800,
+            }
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn account_cache_shrinks_under_the_global_cap() -> TestResult {
+        // Given
+        let mut tracker = tracker();
+        let unloaded = tracker.cache_capacity();
+        for _ in 0..10_u8 {
+            tracker.reserve(100)?;
+        }
+        tracker.schedule(300);
+        for _ in 0..3_u8 {
+            tracker.reserve(100)?;
+        }
+
+        // When
+        let loaded = tracker.cache_capacity();
+        tracker.release(300, true);
+        let executed = tracker.cache_capacity();
+
+        // Then
+        assert_eq!(unloaded, 800);
+        assert_eq!(loaded, 2_000 - 1_000 - 300);
+        assert_eq!(executed, 800);
+        assert_eq!(tracker.batch_bytes(1_000), 300);
+
+        Ok(())
+    }
+}
@@ -3,7 +3,7 @@
 // Creation date: Saturday 08 February 2025
 // Author: Vincent Berthier <vincent.berthier@posteo.org>
 // -----
-// Last modified: Sunday 16 February 2025 @ 01:11:53
+// Last modified: Thursday 15 October 2026 @ 20:53:04
 // Modified by: Vincent Berthier
 // -----
 // Copyright (c) 2025 <Vincent Berthier>
@@ -26,11 +26,46 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
+mod admission;
+mod audit;
 mod block;
 mod blockhash;
+mod cluster_time;
+mod config;
 mod error;
+mod genesis;
+mod identity;
+mod latency;
+mod leader_schedule;
+mod memory;
+mod pipeline;
 mod processor;
+mod rewards;
+mod self_test;
+mod slot_clock;
 mod transaction_queue;
+mod units;
 
+pub use admission::{
+    AdmissionContext, AdmissionDecision, AdmissionPolicy, PayerAllowList, ProgramDenyList,
+};
+pub use audit::{verify_audit_trail, AuditCheckpoint, AuditConfig};
+pub(crate) use block::Block;
+pub use blockhash::BlockHash;
+pub use cluster_time::{ClusterClock, BLOCK_TIME_HISTORY};
+pub use config::{QueuePolicy, ValidatorConfig};
 pub use error::Error;
+pub use genesis::{Allocation, DuplicatePolicy, Genesis, GenesisConfig};
+pub use identity::IdentityHistory;
+pub use leader_schedule::{LeaderSchedule, NUM_CONSECUTIVE_LEADER_SLOTS};
+pub use memory::{MemoryBudget, MemoryComponent, MemoryUsage};
+pub use pipeline::{Pipeline, PipelineBuilder, Scheduler};
+#[cfg(any(test, feature = "test-utils"))]
+pub(crate) use processor::{check_balance, check_invocations, execute_instruction, total_prisms};
+pub use processor::{register_transaction, start_processor, ValidatorHandle};
+pub use rewards::{EpochActivity, EpochRewards, RewardsConfig, ValidatorActivity, ValidatorReward};
+pub use self_test::{self_test, SelfTestReport, Subsystem, SubsystemCheck};
+pub use slot_clock::{SlotClock, SlotTick, SystemClock, TimeSource, DRIFT_HISTORY};
+pub use transaction_queue::{AdmittedTransaction, BlockCap, Status};
+pub use units::{parse_duration, parse_prisms, parse_slots};
 type Result<T> = core::result::Result<T, Error>;
@@ -0,0 +1,187 @@
+// File: src/validator/pipeline.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 20:49:38
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! The swappable stages of the transaction processor.
+//!
+//! Transactions go through the processor in stages: they're sanitized when
+//! registered, filtered by the admission policies, scheduled in batches (the blocks),
+//! executed, committed to the vault and their status is notified. The
+//! scheduling and the policies can be replaced by custom implementations
+//! through a [`PipelineBuilder`]; its default assembly is the regular processor,
+//! started by [`start_processor`](super::start_processor).
+//!
+//! The stages aren't separate tasks: the processor's loop calls them in turn for each
+//! transaction, so a custom stage must not block.
+
+use std::sync::Arc;
+
+use tracing::{debug, instrument, trace};
+
+use super::{
+    admission::{AdmissionContext, AdmissionDecision, AdmissionPolicy},
+    transaction_queue::{AdmittedTransaction, BlockCap, PendingTransactions},
+    Result, ValidatorConfig,
+};
+use crate::transaction::Transaction;
+
+/// The scheduling stage: orders the pending transactions and groups them in batches.
+pub trait Scheduler: Send + Sync {
+    /// Adds a transaction to the pending ones.
+    fn push(&mut self, transaction: AdmittedTransaction);
+
+    /// Whether no transaction is pending.
+    fn is_empty(&self) -> bool;
+
+    /// Takes the next batch of transactions to execute.
+    ///
+    /// # Parameters
+    /// * `size` - The maximum number of transactions in the batch,
+    /// * `max_bytes` - The maximum serialized size of the transactions of the batch.
+    ///
+    /// # Returns
+    /// The batch, and the cap it hit if transactions were left pending.
+    fn next_batch(
+        &mut self,
+        size: usize,
+        max_bytes: usize,
+    ) -> (Vec<AdmittedTransaction>, Option<BlockCap>);
+}
+
+/// The stages used by a processor.
+pub struct Pipeline {
+    /// The configuration of the validator.
+    pub(super) config: ValidatorConfig,
+    /// The scheduling stage.
+    pub(super) scheduler: Box<dyn Scheduler>,
+    /// The admission policies a transaction must pass, in order.
+    pub(super) policies: Vec<Arc<dyn AdmissionPolicy>>,
+}
+
+impl Pipeline {
+    /// Evaluates the admission policies of the pipeline, in order.
+    ///
+    /// # Returns
+    /// The decision of the first policy that didn't accept the transaction.
+    #[instrument(skip_all)]
+    pub(super) fn admit(
+        &self,
+        transaction: &Transaction,
+        context: &AdmissionContext,
+    ) -> AdmissionDecision {
+        trace!("checking the admission policies");
+        self.policies
+            .iter()
+            .map(|policy| policy.admit(transaction, context))
+            .find(|decision| *decision != AdmissionDecision::Accept)
+            .unwrap_or(AdmissionDecision::Accept)
+    }
+
+    /// Replaces the configuration, keeping the scheduler and its pending transactions.
+    ///
+    /// The admission policies of the new configuration replace those of the old one,
+    /// the policies added to the builder are kept after them.
+    ///
+    /// # Errors
+    /// If the new configuration changes fixed parameters (see [`ValidatorConfig::check_reload`]).
+    pub(super) fn reload(&mut self, config: ValidatorConfig) -> Result<()> {
+        self.config.check_reload(&config)?;
+        debug!("reloading the pipeline's configuration");
+        let added = self.policies.split_off(self.config.admission.len());
+        self.policies = config.admission.clone();
+        self.policies.extend(added);
+        self.config = config;
+
+        Ok(())
+    }
+}
+
+/// Assembles the stages of a processor.
+///
+/// Without changes, it builds the default processor: the scheduler follows
+/// the queue policy of the configuration, and only the admission policies of
+/// the configuration are checked.
+pub struct PipelineBuilder {
+    /// The configuration of the validator.
+    config: ValidatorConfig,
+    /// The scheduler replacing the default one, if any.
+    scheduler: Option<Box<dyn Scheduler>>,
+    /// The admission policies a transaction must pass.
+    policies: Vec<Arc<dyn AdmissionPolicy>>,
+}
+
+impl PipelineBuilder {
+    /// Starts a pipeline with the default stages.
+    ///
+    /// # Parameters
+    /// * `config` - The configuration of the validator.
+    #[must_use]
+    pub fn new(config: ValidatorConfig) -> Self {
+        Self {
+            policies: config.admission.clone(),
+            config,
+            scheduler: None,
+        }
+    }
+
+    /// Replaces the scheduling stage.
+    ///
+    /// # Parameters
+    /// * `scheduler` - The scheduler ordering and batching the pending transactions.
+    #[must_use]
+    pub fn with_scheduler(mut self, scheduler: Box<dyn Scheduler>) -> Self {
+        self.scheduler = Some(scheduler);
+        self
+    }
+
+    /// Adds an admission policy checked after the ones already added
+    /// (those of the configuration come first).
+    ///
+    /// # Parameters
+    /// * `policy` - The policy the transactions must pass to be scheduled.
+    #[must_use]
+    pub fn with_policy(mut self, policy: Arc<dyn AdmissionPolicy>) -> Self {
+        self.policies.push(policy);
+        self
+    }
+
+    /// Builds the pipeline, using the default stages for the ones that weren't replaced.
+    #[must_use]
+    pub fn build(self) -> Pipeline {
+        debug!(policies = self.policies.len(), "building the pipeline");
+        let scheduler = self.scheduler.unwrap_or_else(|| {
+            trace!("using the default scheduler");
+            Box::new(PendingTransactions::new(self.config.queue_policy))
+        });
+
+        Pipeline {
+            config: self.config,
+            scheduler,
+            policies: self.policies,
+        }
+    }
+}
@@ -3,7 +3,7 @@
 // Creation date: Saturday 08 February 2025
 // Author: Vincent Berthier <vincent.berthier@posteo.org>
 // -----
-// Last modified: Saturday 15 February 2025 @ 23:30:27
+// Last modified: Thursday 15 October 2026 @ 20:53:04
 // Modified by: Vincent Berthier
 // -----
 // Copyright (c) 2025 <Vincent Berthier>
@@ -26,199 +26,1482 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use std::sync::Arc;
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    sync::Arc,
+    time::Duration,
+};
 
 use tokio::{
     select,
     sync::{
         mpsc::{channel, Receiver as TReceiver, Sender as TSender},
-        oneshot::Receiver as OReceiver,
+        oneshot::{channel as oneshot, Receiver as OReceiver, Sender as OSender},
         RwLock,
     },
+    task::JoinHandle,
+    time::{sleep_until, Instant},
 };
 use tracing::{debug, info, instrument, trace, warn};
 
-use super::{transaction_queue::Status, Error, Result};
+use super::{
+    admission::{AdmissionContext, AdmissionDecision},
+    block::Block,
+    latency::{LatencyStats, Stage, Timings},
+    pipeline::Pipeline,
+    transaction_queue::{
+        AdmittedTransaction, BlockCapStats, BundleStatus, IdempotencyKey, PendingSummary,
+        QueuedBundle, QueuedTransaction, SchedulingState, SequenceBuffer, Status, TransactionQueue,
+    },
+    AuditCheckpoint, BlockHash, EpochRewards, Error, LeaderSchedule, MemoryUsage, Result,
+    RewardsConfig, SlotClock, SystemClock, TimeSource as _, ValidatorConfig,
+};
 use crate::{
-    account::{AccountMeta, TransactionAccount, Wallet},
-    crypto::Pubkey,
+    account::{AccountMeta, Error as AccountError, TransactionAccount, TransactionContext, Wallet},
+    crypto::{Pubkey, Signature},
     io::Vault,
-    program::dispatcher::dispatch,
-    transaction::{CompiledInstruction, Transaction},
+    program::{
+        dispatcher::{dispatch, max_invocations},
+        Context, Epoch, Slot, SLOTS_PER_EPOCH,
+    },
+    transaction::{estimate_size, Bundle, Transaction},
     validator::transaction_queue::TRANSACTION_QUEUE,
 };
 
-const TRANSACTION_FEE: u64 = 5_000;
-const CURRENT_SLOT: u64 = 1;
+/// The slot of the first batch executed by the processor, each batch then takes one slot.
+const FIRST_SLOT: u64 = 1;
+
+/// How far back the system clock may go before the processor reports it.
+const MAX_CLOCK_REGRESSION: Duration = Duration::from_secs(1);
+
+/// A configuration to reload, and where to tell whether it was applied.
+type Reload = (ValidatorConfig, OSender<Result<()>>);
+
+/// Controls a running processor.
+#[derive(Debug)]
+pub struct ValidatorHandle {
+    /// Stops the processor after its current batch, discarding the transactions still waiting.
+    stop: OSender<()>,
+    /// The configurations to reload.
+    reloads: TSender<Reload>,
+}
+
+impl ValidatorHandle {
+    /// Applies a new configuration without restarting the processor.
+    ///
+    /// The pending transactions are kept, and the new parameters apply from the
+    /// next batch on.
+    ///
+    /// # Parameters
+    /// * `new` - The configuration replacing the current one.
+    ///
+    /// # Errors
+    /// If the new configuration changes parameters fixed while the validator runs
+    /// (nothing is applied then), or if the processor stopped.
+    #[instrument(skip_all)]
+    pub async fn reload_config(&self, new: ValidatorConfig) -> Result<()> {
+        debug!("requesting a configuration reload");
+        let (tx, rx) = oneshot();
+        self.reloads
+            .send((new, tx))
+            .await
+            .map_err(|_err| Error::SendMessage { kind: "reload" })?;
+
+        rx.await
+            .map_err(|_err| Error::SendMessage { kind: "reload" })?
+    }
+
+    /// Whether the system clock went backwards by more than the allowed regression:
+    /// the timestamps of the blocks are frozen until it catches up.
+    #[must_use]
+    pub fn is_clock_regressed(&self) -> bool {
+        TRANSACTION_QUEUE.is_clock_regressed()
+    }
+
+    /// Stops the processor after its current batch.
+    ///
+    /// The transactions still pending, deferred to a later slot or held for a missing
+    /// sequence number are discarded: their status channels are closed without a final status.
+    pub fn stop(self) {
+        if self.stop.send(()).is_err() {
+            warn!("the processor already stopped");
+        }
+    }
+}
+
+/// Starts a processor in its own task.
+///
+/// Only one processor should run at a time: they all take their transactions from
+/// the same queue, filled by [`register_transaction`].
+///
+/// # Parameters
+/// * `vault` - The vault the transactions are executed against,
+/// * `pipeline` - The stages of the processor (see [`PipelineBuilder`](super::PipelineBuilder)).
+///
+/// # Returns
+/// The handle controlling the processor, and the task running it.
+#[must_use]
+pub fn start_processor(
+    vault: Arc<RwLock<Vault>>,
+    pipeline: Pipeline,
+) -> (ValidatorHandle, JoinHandle<()>) {
+    let (stop, stop_control) = oneshot();
+    let (reloads, reload_control) = channel(1);
+    let handle = tokio::spawn(processor(vault, pipeline, stop_control, reload_control));
+
+    (ValidatorHandle { stop, reloads }, handle)
+}
+
+/// Sanitizes a transaction submitted by a client and queues it for the running processor.
+///
+/// # Returns
+/// The receiver of the successive statuses of the transaction.
+///
+/// # Errors
+/// If the transaction isn't properly signed, targets another chain, or can't be queued.
+pub async fn register_transaction(trx: Transaction) -> Result<TReceiver<Status>> {
+    register_in(&TRANSACTION_QUEUE, trx).await
+}
 
+/// Sanitizes a transaction submitted by a client and adds it to a queue.
 #[instrument(skip_all)]
-async fn register_transaction(trx: Transaction) -> Result<TReceiver<Status>> {
+async fn register_in(queue: &TransactionQueue, trx: Transaction) -> Result<TReceiver<Status>> {
     debug!("registering new transaction");
+    let mut timings = Timings::received();
     if !trx.is_valid() {
         warn!("cannot add an invalid transaction (signature issue)");
         return Err(Error::InvalidTransactionSignatures);
     }
+    check_chain(queue.chain(), &trx)?;
+    timings.record(Stage::Sanitized);
+    queue.open_intake();
+    enqueue(queue, trx, timings).await
+}
+
+/// What registering a transaction under an idempotency key gave.
+#[derive(Debug)]
+enum Submission {
+    /// The transaction was queued, its status is sent on the receiver.
+    Queued(TReceiver<Status>),
+    /// The payer already used the key within the idempotency window:
+    /// the signature of the transaction first submitted with it.
+    Replayed(Signature),
+}
+
+/// Registers a transaction under an idempotency key chosen by the client, so its retries
+/// give back the original submission instead of being queued again.
+///
+/// The key is recorded before the transaction is sanitized, so a retry racing the first
+/// attempt can't be queued too. It's forgotten if the transaction is refused.
+#[instrument(skip(trx))]
+async fn register_with_key(trx: Transaction, key: IdempotencyKey) -> Result<Submission> {
+    debug!("registering transaction with an idempotency key");
+    let (Some(&payer), Some(&id)) = (trx.payer(), trx.signature()) else {
+        warn!("cannot add an unsigned transaction");
+        return Err(Error::InvalidTransactionSignatures);
+    };
+    if let Err(original) = TRANSACTION_QUEUE.claim_key(payer, key, id) {
+        debug!(?original, "the submission was already made");
+        return Ok(Submission::Replayed(original));
+    }
+
+    register_transaction(trx)
+        .await
+        .map(Submission::Queued)
+        .inspect_err(|_| TRANSACTION_QUEUE.release_key(payer, key, &id))
+}
+
+/// Registers a transaction produced by the validator itself, skipping the
+/// verification of its signatures.
+///
+/// The signatures are still verified when debug assertions are enabled,
+/// to catch the transactions the validator mis-signed.
+#[instrument(skip_all)]
+async fn register_trusted(trx: Transaction) -> Result<TReceiver<Status>> {
+    debug!("registering trusted transaction");
+    let mut timings = Timings::received();
+    if !trx.has_all_signatures() || (cfg!(debug_assertions) && !trx.is_valid()) {
+        warn!("the validator produced an invalid transaction");
+        return Err(Error::InvalidTransactionSignatures);
+    }
+    check_chain(TRANSACTION_QUEUE.chain(), &trx)?;
+    timings.record(Stage::Sanitized);
+
+    enqueue(&TRANSACTION_QUEUE, trx, timings).await
+}
+
+/// Adds a sanitized transaction to the queue, unless the intake is paused or the
+/// validator is short of memory.
+async fn enqueue(
+    queue: &TransactionQueue,
+    trx: Transaction,
+    timings: Timings,
+) -> Result<TReceiver<Status>> {
+    if queue.is_paused() {
+        warn!("transaction intake is paused");
+        return Err(Error::IntakePaused);
+    }
+    queue
+        .reserve_memory(estimate_size(trx.message()))
+        .inspect_err(|err| warn!("transaction refused: {err}"))?;
 
     trace!("adding transaction");
     let (tx, rx) = channel(5);
     #[expect(clippy::unwrap_used, reason = "channel was just created, can’t fail")]
     tx.send(Status::Pending).await.unwrap();
-    TRANSACTION_QUEUE.send(trx, tx).await;
+    queue.send(trx, tx, timings).await;
+
+    Ok(rx)
+}
+
+/// Registers a bundle of transactions, executed together in the same block or not at all.
+#[instrument(skip_all, fields(n = bundle.len()))]
+async fn register_bundle(bundle: Bundle) -> Result<TReceiver<BundleStatus>> {
+    debug!("registering new bundle");
+    let mut timings = Timings::received();
+    if let Some(index) = bundle.transactions().iter().position(|trx| !trx.is_valid()) {
+        warn!(index, "cannot add a bundle with an invalid transaction");
+        return Err(Error::InvalidBundleMember { index });
+    }
+    let chain = TRANSACTION_QUEUE.chain();
+    for trx in bundle.transactions() {
+        check_chain(chain, trx)?;
+    }
+    timings.record(Stage::Sanitized);
+    TRANSACTION_QUEUE.open_intake();
+    if TRANSACTION_QUEUE.is_paused() {
+        warn!("transaction intake is paused");
+        return Err(Error::IntakePaused);
+    }
+    TRANSACTION_QUEUE
+        .reserve_memory(bundle.size())
+        .inspect_err(|err| warn!("bundle refused: {err}"))?;
+
+    trace!("adding bundle");
+    let (tx, rx) = channel(5);
+    #[expect(clippy::unwrap_used, reason = "channel was just created, can’t fail")]
+    tx.send(BundleStatus::Pending).await.unwrap();
+    TRANSACTION_QUEUE.send_bundle(bundle, tx, timings).await;
 
     Ok(rx)
 }
 
+/// Checks that a transaction is bound to the validator's chain, if it has one.
+fn check_chain(expected: Option<BlockHash>, trx: &Transaction) -> Result<()> {
+    let Some(expected) = expected else {
+        return Ok(());
+    };
+    let got = trx.message().chain();
+    if got != Some(expected) {
+        warn!(
+            ?expected,
+            ?got,
+            "the transaction is meant for another chain"
+        );
+        return Err(Error::WrongChain {
+            expected: Box::new(expected),
+            got: got.map(Box::new),
+        });
+    }
+
+    Ok(())
+}
+
+/// Stops accepting new transactions, the ones already queued are still executed.
+fn pause_intake() {
+    TRANSACTION_QUEUE.pause_intake();
+}
+
+/// Accepts new transactions again.
+fn resume_intake() {
+    TRANSACTION_QUEUE.resume_intake();
+}
+
+/// How many blocks were capped, leaving transactions for the next ones.
+fn block_caps() -> BlockCapStats {
+    TRANSACTION_QUEUE.cap_stats()
+}
+
+/// Get the memory used by each component of the validator, for monitoring.
+fn memory_usage() -> MemoryUsage {
+    TRANSACTION_QUEUE.memory_usage()
+}
+
+/// Lists the transactions sent to the processor that weren't executed yet, for monitoring.
+fn pending() -> Vec<PendingSummary> {
+    TRANSACTION_QUEUE.snapshot()
+}
+
+/// Get when a recently executed transaction reached each stage, if the latency is tracked.
+fn timings(id: &Signature) -> Option<Timings> {
+    TRANSACTION_QUEUE.timings(id)
+}
+
+/// Get the distribution of the time the executed transactions spent reaching each stage.
+fn latencies() -> LatencyStats {
+    TRANSACTION_QUEUE.latency_stats()
+}
+
+/// Stops accepting new transactions and waits until the queued ones are executed.
+async fn drain() {
+    TRANSACTION_QUEUE.drain().await;
+}
+
+/// The outcome of a bulk import.
+#[derive(Debug, Default)]
+pub struct BulkReport {
+    /// The number of transactions skipped because a previous import applied them.
+    pub resumed_from: u64,
+    /// The number of transactions executed successfully.
+    pub applied: u64,
+    /// The index (from the start of the import) and error of the transactions that failed.
+    pub failures: Vec<(u64, Error)>,
+}
+
+/// Applies a list of transactions before the public intake opens, for migrations.
+///
+/// The transactions go through the regular runtime in batches, each batch being a slot.
+/// The signatures of a batch are verified in parallel. The progress is saved with the
+/// vault after each batch, so an interrupted import can be resumed by applying the
+/// same list again: the transactions already applied are skipped.
+///
+/// # Parameters
+/// * `vault` - The vault the transactions are applied to,
+/// * `config` - The configuration of the validator (the batch size in particular),
+/// * `transactions` - The transactions, always in the same order.
+///
+/// # Errors
+/// If the public intake already received transactions, or if the vault can't be saved.
+#[instrument(skip_all)]
+async fn bulk_apply<I>(
+    vault: &RwLock<Vault>,
+    config: &ValidatorConfig,
+    transactions: I,
+) -> Result<BulkReport>
+where
+    I: IntoIterator<Item = Transaction>,
+{
+    debug!("applying transactions in bulk");
+    if TRANSACTION_QUEUE.is_open() {
+        warn!("the public intake is already open");
+        return Err(Error::IntakeAlreadyOpen);
+    }
+    let resumed_from = vault.read().await.bulk_progress();
+    let mut report = BulkReport {
+        resumed_from,
+        ..BulkReport::default()
+    };
+    let mut transactions = transactions
+        .into_iter()
+        .skip(resumed_from.try_into().unwrap_or(usize::MAX));
+    let mut index = resumed_from;
+    let mut ledger = Block::genesis();
+    loop {
+        let batch = transactions
+            .by_ref()
+            .take(config.batch_size.max(1))
+            .collect::<Vec<_>>();
+        if batch.is_empty() {
+            break;
+        }
+        #[expect(clippy::integer_division, reason = "the slot of the batch")]
+        let slot = FIRST_SLOT.saturating_add(index / config.batch_size.max(1) as u64);
+        trace!(slot, n = batch.len(), "applying batch");
+        let verified = verify_signatures(&batch);
+        for (trx, valid) in batch.into_iter().zip(verified) {
+            if let Some(sig) = trx.signature() {
+                ledger.add_transaction(*sig);
+            }
+            let res = if valid {
+                // nothing is queued while importing
+                let fee = config.fee(trx.message(), 0.0);
+                execute_transaction_inner(vault, config, trx, fee, slot).await
+            } else {
+                Err(Error::InvalidTransactionSignatures)
+            };
+            match res {
+                Ok(()) => report.applied += 1,
+                Err(err) => {
+                    debug!(index, "bulk transaction failed: {err}");
+                    report.failures.push((index, err));
+                }
+            }
+            index += 1;
+        }
+        close_slot(vault, config, &mut ledger, slot, SystemClock.wall_time()).await?;
+        let mut vault = vault.write().await;
+        vault.set_bulk_progress(index);
+        vault.save().await?;
+        drop(vault);
+    }
+
+    Ok(report)
+}
+
+/// Applies a new configuration to a running processor, if its fixed parameters didn't change.
+fn reload(
+    pipeline: &mut Pipeline,
+    held: &mut SequenceBuffer,
+    clock: &mut SlotClock<SystemClock>,
+    config: ValidatorConfig,
+    answer: OSender<Result<()>>,
+) {
+    info!("reloading the configuration");
+    let sequence_timeout = config.sequence_timeout;
+    let slot_duration = config.slot_duration;
+    let latency_tracking = config.latency_tracking;
+    let idempotency_window = config.idempotency_window;
+    let memory = config.memory;
+    let res = pipeline.reload(config).inspect(|()| {
+        held.set_timeout(sequence_timeout);
+        clock.set_slot_duration(slot_duration);
+        TRANSACTION_QUEUE.set_latency_tracking(latency_tracking);
+        TRANSACTION_QUEUE.set_idempotency_window(idempotency_window);
+        TRANSACTION_QUEUE.set_memory_budget(memory);
+    });
+    if answer.send(res).is_err() {
+        warn!("nobody waits for the reload anymore");
+    }
+}
+
+/// Seals the block of a slot, after paying the rewards of the epoch if it ends
+/// (then snapshotting the stakes for the next one), records its estimated time,
+/// and produces an audit checkpoint when one is due.
+///
+/// The time of the block is estimated from the timestamp of the slot, in milliseconds
+/// since the unix epoch.
+///
+/// Only the incremental state of the vault is read, so closing a slot never waits
+/// for the accounts to be hashed again.
+async fn close_slot(
+    vault: &RwLock<Vault>,
+    config: &ValidatorConfig,
+    ledger: &mut Block,
+    slot: u64,
+    timestamp: i64,
+) -> Result<()> {
+    if let Some(rewards) = config.rewards.as_ref() {
+        reward_validators(vault, config.identity, rewards, ledger, slot).await?;
+    }
+    let epoch = Slot::new(slot).epoch(SLOTS_PER_EPOCH);
+    if epoch.last_slot(SLOTS_PER_EPOCH) == Slot::new(slot) {
+        snapshot_stakes(vault, epoch.next()).await?;
+    }
+    ledger.slot = slot;
+    // a single validator produces the blocks: its own clock is the only one reporting
+    let timestamp = timestamp.div_euclid(1_000);
+    let max_step = i64::try_from(config.max_time_step.as_secs()).unwrap_or(i64::MAX);
+    {
+        let mut vault = vault.write().await;
+        ledger.block_time = vault.record_block_time(slot, &[timestamp], max_step);
+        ledger.state_root = BlockHash::from_bytes(&vault.state_root())?;
+    }
+    let block = ledger.finalize();
+    let Some(audit) = config.audit.as_ref().filter(|audit| audit.is_due(slot)) else {
+        return Ok(());
+    };
+    trace!(slot, "producing an audit checkpoint");
+    let mut vault = vault.write().await;
+    let checkpoint = AuditCheckpoint::new(
+        slot,
+        block.hash,
+        block.state_root,
+        vault.supply(),
+        vault.audit_trail().last(),
+        &audit.signer,
+    );
+    vault.record_checkpoint(checkpoint);
+    drop(vault);
+
+    Ok(())
+}
+
+/// Snapshots the stake of the validators as the leader schedule of an epoch.
+///
+/// # Parameters
+/// * `vault` - The vault holding the stake accounts,
+/// * `epoch` - The epoch starting.
+async fn snapshot_stakes(vault: &RwLock<Vault>, epoch: Epoch) -> Result<()> {
+    let mut vault = vault.write().await;
+    let schedule = vault.snapshot_stakes(epoch).await?;
+    info!(
+        epoch = epoch.get(),
+        validators = schedule.validators(),
+        "stakes snapshotted for the leader schedule"
+    );
+    drop(vault);
+
+    Ok(())
+}
+
+/// Counts the block of the slot for its producer (and as missed by its scheduled
+/// leader if it's another validator) and, at the end of an epoch, pays the validators
+/// their rewards.
+async fn reward_validators(
+    vault: &RwLock<Vault>,
+    identity: Option<Pubkey>,
+    config: &RewardsConfig,
+    ledger: &mut Block,
+    slot: u64,
+) -> Result<()> {
+    let epoch = Slot::new(slot).epoch(SLOTS_PER_EPOCH);
+    let index = Slot::new(slot).index_in_epoch(SLOTS_PER_EPOCH);
+    let mut guard = vault.write().await;
+    let leader = guard
+        .leader_schedule()
+        .filter(|schedule| schedule.epoch() == epoch)
+        .and_then(|schedule| schedule.leader(index));
+    if let Some(leader) = leader.filter(|leader| Some(*leader) != identity) {
+        trace!(%leader, "the scheduled leader missed the slot");
+        guard.epoch_activity_mut().record_slot(leader, false);
+    }
+    if let Some(identity) = identity {
+        guard.epoch_activity_mut().record_slot(identity, true);
+    }
+    drop(guard);
+    if epoch.last_slot(SLOTS_PER_EPOCH) != Slot::new(slot) {
+        return Ok(());
+    }
+
+    pay_rewards(vault, config, ledger, epoch, slot).await
+}
+
+/// Pays the rewards of an epoch, and records them.
+///
+/// Each reward is minted on the account of the signer and transferred to the
+/// validator by a single transaction added to the block of the slot: the balance
+/// changes of both accounts are recorded under its signature. A reward that can't
+/// be paid is left undistributed, without stopping the payment of the others.
+#[expect(clippy::unwrap_used)]
+#[instrument(skip(vault, config, ledger))]
+async fn pay_rewards(
+    vault: &RwLock<Vault>,
+    config: &RewardsConfig,
+    ledger: &mut Block,
+    epoch: Epoch,
+    slot: u64,
+) -> Result<()> {
+    debug!("paying the rewards of the epoch");
+    let activity = vault.read().await.epoch_activity().clone();
+    let mut report = EpochRewards::compute(epoch, config, &activity);
+    let transactions = report.transactions(&config.signer, slot)?;
+    let paid = report.rewards.iter().filter(|reward| reward.prisms > 0);
+    for (reward, trx) in paid.zip(&transactions) {
+        let signature = *trx.signature().unwrap();
+        if let Err(err) = execute_internal(vault, trx, reward.prisms, slot).await {
+            warn!(validator = %reward.validator, "could not pay the reward: {err}");
+            report.undistributed = report.undistributed.saturating_add(reward.prisms);
+            continue;
+        }
+        ledger.add_transaction(signature);
+        report.transactions.push(signature);
+    }
+    info!(
+        distributed = report.distributed(),
+        validators = report.rewards.len(),
+        "rewards of the epoch paid"
+    );
+    vault.write().await.record_epoch_rewards(report);
+
+    Ok(())
+}
+
+/// Records the memory used by the account cache, and resizes it to what the budget
+/// leaves it: it's shrunk when the pending transactions need its memory to keep the
+/// validator under its global cap, and grows back once they're executed.
+///
+/// # Parameters
+/// * `vault` - The vault holding the cache,
+/// * `applied` - The capacity last given to the cache, if any.
+async fn fit_account_cache(vault: &RwLock<Vault>, applied: &mut Option<usize>) {
+    let size = vault.read().await.cache_size();
+    let capacity = TRANSACTION_QUEUE.account_cache(size);
+    if *applied != Some(capacity) {
+        debug!(size, capacity, "resizing the account cache");
+        vault.write().await.set_cache_capacity(capacity);
+        *applied = Some(capacity);
+    }
+}
+
+/// Verifies the signatures of transactions, spreading them over the available cores.
+fn verify_signatures(transactions: &[Transaction]) -> Vec<bool> {
+    let threads = std::thread::available_parallelism().map_or(1, usize::from);
+    let chunk = transactions.len().div_ceil(threads).max(1);
+    std::thread::scope(|scope| {
+        transactions
+            .chunks(chunk)
+            .map(|chunk| {
+                scope.spawn(|| chunk.iter().map(Transaction::is_valid).collect::<Vec<_>>())
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap_or_default())
+            .collect()
+    })
+}
+
 #[mutants::skip]
 #[instrument(skip_all)]
-async fn processor(vault: Arc<RwLock<Vault>>, stop_control: OReceiver<()>) {
+async fn processor(
+    vault: Arc<RwLock<Vault>>,
+    pipeline: Pipeline,
+    stop_control: OReceiver<()>,
+    reloads: TReceiver<Reload>,
+) {
     let mut stop_control = stop_control;
+    let mut reloads = reloads;
+    let mut pipeline = pipeline;
     let queue = TRANSACTION_QUEUE.get_receiver();
+    let bundle_queue = TRANSACTION_QUEUE.get_bundle_receiver();
+    let mut bundles = VecDeque::new();
+    let mut held = SequenceBuffer::new(pipeline.config.sequence_timeout);
+    let mut deferred = BTreeMap::new();
+    let mut slot = FIRST_SLOT;
+    let mut clock = SlotClock::new(
+        SystemClock,
+        pipeline.config.slot_duration,
+        MAX_CLOCK_REGRESSION,
+    );
+    let mut slot_start = clock.tick();
+    let mut ledger = Block::genesis();
+    let mut cache_capacity = None;
+    TRANSACTION_QUEUE.set_latency_tracking(pipeline.config.latency_tracking);
+    TRANSACTION_QUEUE.set_chain(pipeline.config.chain);
+    TRANSACTION_QUEUE.set_idempotency_window(pipeline.config.idempotency_window);
+    TRANSACTION_QUEUE.set_memory_budget(pipeline.config.memory);
+    if pipeline.config.balance_history {
+        vault.write().await.enable_balance_history().await;
+    }
+    let epoch = Slot::new(slot).epoch(SLOTS_PER_EPOCH);
+    let snapshotted = vault
+        .read()
+        .await
+        .leader_schedule()
+        .map(LeaderSchedule::epoch);
+    if snapshotted != Some(epoch) {
+        if let Err(err) = snapshot_stakes(&vault, epoch).await {
+            warn!("could not snapshot the stakes: {err}");
+        }
+    }
     loop {
-        trace!("waiting for notification");
-        select! {
-            Ok(()) = &mut stop_control => {
-                info!("stop control called, ending processor thread");
-                break;
+        TRANSACTION_QUEUE.set_slot(slot);
+        let waiting = pipeline.scheduler.is_empty() && bundles.is_empty();
+        if !waiting && stop_control.try_recv().is_ok() {
+            info!("stop control called, ending processor thread");
+            break;
+        }
+        let later = deferred.split_off(&slot.saturating_add(1));
+        for queued in std::mem::replace(&mut deferred, later)
+            .into_values()
+            .flatten()
+        {
+            trace!("evaluating a deferred transaction again");
+            admit(&mut pipeline, &mut deferred, queued, slot).await;
+        }
+        if waiting {
+            trace!("waiting for notification");
+            let deadline = held.next_deadline();
+            let slot_end = Instant::from_std(clock.slot_end(slot_start.slot));
+            select! {
+                Ok(()) = &mut stop_control => {
+                    info!("stop control called, ending processor thread");
+                    break;
+                }
+                Ok(queued) = queue.recv() => {
+                    trace!("transaction received");
+                    admit(&mut pipeline, &mut deferred, queued, slot).await;
+                }
+                Ok(bundle) = bundle_queue.recv() => {
+                    trace!("bundle received");
+                    bundles.push_back(bundle);
+                }
+                Some((config, answer)) = reloads.recv() => {
+                    reload(&mut pipeline, &mut held, &mut clock, config, answer);
+                }
+                () = sleep_until(deadline.unwrap_or_else(Instant::now)), if deadline.is_some() => {
+                    trace!("a held transaction expired");
+                }
+                () = sleep_until(slot_end), if !deferred.is_empty() => {
+                    trace!("the slot elapsed with deferred transactions");
+                }
+                else => {
+                    warn!("something weird happened here…");
+                }
+            }
+        }
+
+        while let Ok(queued) = queue.try_recv() {
+            admit(&mut pipeline, &mut deferred, queued, slot).await;
+        }
+        bundles.extend(std::iter::from_fn(|| bundle_queue.try_recv().ok()));
+        while let Ok((config, answer)) = reloads.try_recv() {
+            reload(&mut pipeline, &mut held, &mut clock, config, answer);
+        }
+        // a bundle fills its block on its own
+        let bundled = if let Some(bundle) = bundles.pop_front() {
+            for trx in bundle.0.transactions() {
+                if let Some(sig) = trx.signature() {
+                    ledger.add_transaction(*sig);
+                }
             }
-            Ok((trx, tx_status)) = queue.recv() => {
-                trace!("transaction received");
-                execute_transaction(&vault, trx, tx_status).await;
+            let executed = execute_bundle(&vault, &pipeline, bundle, slot).await;
+            TRANSACTION_QUEUE.done();
+            match executed {
+                Ok(executed) => executed,
+                Err(err) => {
+                    warn!("the vault is left inconsistent, stopping the processor: {err}");
+                    break;
+                }
             }
-            else => {
-                warn!("something weird happened here…");
+        } else {
+            false
+        };
+        let (batch, cap) = if bundled {
+            (Vec::new(), None)
+        } else {
+            pipeline.scheduler.next_batch(
+                pipeline.config.batch_size,
+                TRANSACTION_QUEUE.batch_bytes(pipeline.config.max_block_bytes),
+            )
+        };
+        if let Some(cap) = cap {
+            debug!(?cap, "block capped, rolling the pending transactions over");
+            TRANSACTION_QUEUE.record_cap(cap);
+        }
+        let executed = bundled || !batch.is_empty();
+        for sig in batch.iter().filter_map(|queued| queued.0.signature()) {
+            TRANSACTION_QUEUE.schedule(sig);
+            ledger.add_transaction(*sig);
+        }
+        for queued in batch {
+            execute_in_sequence(&vault, &pipeline, &mut held, queued, slot).await;
+        }
+        for (trx, tx_status, fee) in held.expired(Instant::now()) {
+            debug!("held transaction expired");
+            execute_transaction(&vault, &pipeline, trx, tx_status, fee, slot).await;
+            TRANSACTION_QUEUE.done();
+        }
+        fit_account_cache(&vault, &mut cache_capacity).await;
+        let tick = clock.tick();
+        TRANSACTION_QUEUE.set_clock_regressed(clock.is_regressed());
+        let elapsed = tick.slot > slot_start.slot;
+        if executed || (elapsed && !deferred.is_empty()) {
+            let closed = close_slot(&vault, &pipeline.config, &mut ledger, slot, tick.timestamp);
+            if let Err(err) = closed.await {
+                warn!(slot, "could not close the slot: {err}");
             }
+            slot = slot.saturating_add(1);
+            slot_start = tick;
+        } else {
+            trace!("empty batch, staying on the same slot");
         }
     }
     debug!("processor thread exited");
 }
 
-#[expect(clippy::unwrap_used, reason = "the receivers cannot have been dropped")]
-async fn execute_transaction(vault: &RwLock<Vault>, trx: Transaction, tx_status: TSender<Status>) {
+/// Schedules a transaction if the admission policies accept it.
+///
+/// Rejected transactions are notified right away, deferred ones are kept
+/// aside until the processor reaches their slot.
+#[expect(clippy::unwrap_used, reason = "only signed transactions are queued")]
+#[instrument(skip_all)]
+async fn admit(
+    pipeline: &mut Pipeline,
+    deferred: &mut BTreeMap<u64, Vec<QueuedTransaction>>,
+    queued: QueuedTransaction,
+    slot: u64,
+) {
+    let (trx, tx_status) = queued;
+    let sig = *trx.signature().unwrap();
+    let fee = pipeline.config.fee(
+        trx.message(),
+        TRANSACTION_QUEUE.pressure(pipeline.config.batch_size),
+    );
+    TRANSACTION_QUEUE.set_fee(&sig, fee);
+    match pipeline.admit(&trx, &AdmissionContext { slot }) {
+        AdmissionDecision::Accept => {
+            TRANSACTION_QUEUE.set_state(&sig, SchedulingState::Waiting);
+            pipeline.scheduler.push((trx, tx_status, fee));
+        }
+        AdmissionDecision::Reject(reason) => {
+            warn!(reason, "transaction rejected by an admission policy");
+            notify(&tx_status, Status::Rejected(reason)).await;
+            TRANSACTION_QUEUE.untrack(&sig);
+            TRANSACTION_QUEUE.done();
+        }
+        AdmissionDecision::Defer(until) => {
+            debug!(until, "transaction deferred");
+            TRANSACTION_QUEUE.set_state(&sig, SchedulingState::Deferred(until));
+            deferred.entry(until).or_default().push((trx, tx_status));
+        }
+    }
+}
+
+/// Executes the transactions of a bundle in order, if they can all be admitted.
+///
+/// # Returns
+/// Whether the bundle was admitted, taking the block.
+///
+/// # Errors
+/// If a failed bundle couldn't be undone: its first transactions stay committed, the
+/// vault can't be trusted anymore.
+#[instrument(skip_all, fields(n = queued.0.len()))]
+async fn execute_bundle(
+    vault: &RwLock<Vault>,
+    pipeline: &Pipeline,
+    queued: QueuedBundle,
+    slot: u64,
+) -> Result<bool> {
+    let (bundle, tx_status) = queued;
+    let ids = bundle
+        .transactions()
+        .iter()
+        .filter_map(|trx| trx.signature().copied())
+        .collect::<Vec<_>>();
+    let status = match admit_bundle(pipeline, &bundle, slot) {
+        Ok(()) => {
+            for id in &ids {
+                TRANSACTION_QUEUE.set_state(id, SchedulingState::Running);
+                TRANSACTION_QUEUE.schedule(id);
+            }
+            match execute_bundle_inner(vault, &pipeline.config, bundle, slot).await? {
+                None => BundleStatus::Succeeded,
+                Some(index) => BundleStatus::Failed { index },
+            }
+        }
+        Err(reason) => {
+            warn!(reason, "bundle rejected");
+            BundleStatus::Rejected(reason)
+        }
+    };
+    let recorded = ids
+        .iter()
+        .map(|id| (*id, TRANSACTION_QUEUE.untrack(id)))
+        .collect::<Vec<_>>();
+    notify(&tx_status, status).await;
+    if matches!(status, BundleStatus::Rejected(_)) {
+        return Ok(false);
+    }
+    for (id, timings) in recorded {
+        if let Some(mut timings) = timings {
+            timings.record(Stage::Notified);
+            TRANSACTION_QUEUE.record_timings(id, timings);
+        }
+    }
+
+    Ok(true)
+}
+
+/// Checks a bundle can fit in a block, and that the admission policies accept
+/// all its transactions.
+///
+/// # Returns
+/// Why the bundle is rejected, if it is.
+fn admit_bundle(
+    pipeline: &Pipeline,
+    bundle: &Bundle,
+    slot: u64,
+) -> core::result::Result<(), &'static str> {
+    if bundle.len() > pipeline.config.batch_size || bundle.size() > pipeline.config.max_block_bytes
+    {
+        return Err("the bundle can never fit in a block");
+    }
+    for trx in bundle.transactions() {
+        match pipeline.admit(trx, &AdmissionContext { slot }) {
+            AdmissionDecision::Accept => {}
+            AdmissionDecision::Reject(reason) => return Err(reason),
+            AdmissionDecision::Defer(_) => {
+                return Err("a transaction of the bundle can't be executed yet")
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Executes the transactions of a bundle in order, undoing all of them if one fails.
+///
+/// # Returns
+/// The position of the transaction that failed, if one did.
+///
+/// # Errors
+/// If the transactions executed before the one that failed couldn't be undone.
+#[expect(clippy::unwrap_used)]
+async fn execute_bundle_inner(
+    vault: &RwLock<Vault>,
+    config: &ValidatorConfig,
+    bundle: Bundle,
+    slot: u64,
+) -> Result<Option<usize>> {
+    let mut keys = bundle
+        .transactions()
+        .iter()
+        .flat_map(|trx| trx.message().accounts().iter().map(|meta| *meta.key()))
+        .chain(config.identity)
+        .collect::<Vec<_>>();
+    keys.sort();
+    keys.dedup();
+    let checkpoint = match vault.read().await.checkpoint(&keys).await {
+        Ok(checkpoint) => checkpoint,
+        Err(err) => {
+            warn!("could not record the state of the bundle's accounts: {err}");
+            return Ok(Some(0));
+        }
+    };
+
+    let mut executed = Vec::new();
+    for (index, trx) in bundle.into_transactions().into_iter().enumerate() {
+        let sig = *trx.signature().unwrap();
+        TRANSACTION_QUEUE.stamp(&sig, Stage::ExecutionStart);
+        executed.push(sig);
+        let fee = config.fee(trx.message(), TRANSACTION_QUEUE.pressure(config.batch_size));
+        if let Err(err) = execute_transaction_inner(vault, config, trx, fee, slot).await {
+            warn!(
+                index,
+                "transaction {sig:?} of the bundle failed: {err}, undoing the bundle"
+            );
+            vault
+                .write()
+                .await
+                .rollback(checkpoint, slot, &executed)
+                .await
+                .inspect_err(|rollback_err| warn!("could not undo the bundle: {rollback_err}"))?;
+            return Ok(Some(index));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Executes a transaction, unless its sequence number is ahead of its payer's,
+/// in which case it's held until the previous ones are executed.
+///
+/// The held transactions that become next in line are executed afterwards.
+#[instrument(skip_all)]
+async fn execute_in_sequence(
+    vault: &RwLock<Vault>,
+    pipeline: &Pipeline,
+    held: &mut SequenceBuffer,
+    queued: AdmittedTransaction,
+    slot: u64,
+) {
+    let (trx, tx_status, fee) = queued;
+    let (Some(sequence), Some(&payer)) = (trx.message().sequence(), trx.payer()) else {
+        execute_transaction(vault, pipeline, trx, tx_status, fee, slot).await;
+        TRANSACTION_QUEUE.done();
+        return;
+    };
+
+    if sequence > expected_sequence(vault, &payer).await {
+        trace!(sequence, "transaction is ahead of its payer, holding it");
+        let sig = trx.signature().copied();
+        let Err((trx, tx_status, fee)) = held.hold(payer, sequence, (trx, tx_status, fee)) else {
+            if let Some(sig) = sig {
+                TRANSACTION_QUEUE.set_state(&sig, SchedulingState::OutOfSequence(sequence));
+            }
+            return;
+        };
+        execute_transaction(vault, pipeline, trx, tx_status, fee, slot).await;
+        TRANSACTION_QUEUE.done();
+        return;
+    }
+
+    execute_transaction(vault, pipeline, trx, tx_status, fee, slot).await;
+    TRANSACTION_QUEUE.done();
+    while let Some((next, next_status, next_fee)) =
+        held.take(&payer, expected_sequence(vault, &payer).await)
+    {
+        trace!("executing the next held transaction");
+        execute_transaction(vault, pipeline, next, next_status, next_fee, slot).await;
+        TRANSACTION_QUEUE.done();
+    }
+}
+
+/// The sequence number expected for the next transaction of a payer.
+async fn expected_sequence(vault: &RwLock<Vault>, payer: &Pubkey) -> u64 {
+    vault
+        .read()
+        .await
+        .last_sequence(payer)
+        .map_or(1, |last| last.saturating_add(1))
+}
+
+#[expect(clippy::unwrap_used, reason = "only signed transactions are queued")]
+async fn execute_transaction(
+    vault: &RwLock<Vault>,
+    pipeline: &Pipeline,
+    trx: Transaction,
+    tx_status: TSender<Status>,
+    fee: u64,
+    slot: u64,
+) {
     let sig = *trx.signature().unwrap();
-    match execute_transaction_inner(vault, trx).await {
-        Ok(()) => tx_status.send(Status::Succeeded).await.unwrap(),
+    TRANSACTION_QUEUE.set_state(&sig, SchedulingState::Running);
+    TRANSACTION_QUEUE.stamp(&sig, Stage::ExecutionStart);
+    let res = execute_transaction_inner(vault, &pipeline.config, trx, fee, slot).await;
+    let timings = TRANSACTION_QUEUE.untrack(&sig);
+    let status = match res {
+        Ok(()) => Status::Succeeded,
+        Err(Error::CustomProgramError {
+            instruction, code, ..
+        }) => {
+            warn!(
+                instruction,
+                code, "transaction {sig:?} failed with a custom error"
+            );
+            Status::CustomError { instruction, code }
+        }
         Err(err) => {
             warn!("transaction {sig:?} failed to run: {err}");
-            tx_status.send(Status::Failed).await.unwrap();
+            Status::Failed
         }
+    };
+    notify(&tx_status, status).await;
+    if let Some(mut timings) = timings {
+        timings.record(Stage::Notified);
+        TRANSACTION_QUEUE.record_timings(sig, timings);
+    }
+}
+
+/// Sends a status to the client, unless it stopped listening (after a timeout
+/// for instance, its retry then being given the signature of the original submission).
+async fn notify<T>(tx_status: &TSender<T>, status: T) {
+    if tx_status.send(status).await.is_err() {
+        trace!("the client stopped listening for the status");
     }
 }
 
 #[expect(clippy::unwrap_used)]
 #[instrument(skip_all, fields(sig = ?trx.signature().unwrap()))]
-async fn execute_transaction_inner(vault: &RwLock<Vault>, trx: Transaction) -> Result<()> {
+async fn execute_transaction_inner(
+    vault: &RwLock<Vault>,
+    config: &ValidatorConfig,
+    trx: Transaction,
+    fee: u64,
+    slot: u64,
+) -> Result<()> {
     debug!("executing transaction");
+    check_chain(config.chain, &trx)?;
+    check_invocations(config, &trx)?;
     let metas = trx.message().accounts();
-    let payer = trx.message().get_payer().unwrap();
+    let payer = *trx.payer().unwrap();
+    if let Some(sequence) = trx.message().sequence() {
+        let expected = expected_sequence(vault, &payer).await;
+        if sequence != expected {
+            warn!(expected, sequence, "transaction is out of sequence");
+            return Err(Error::SequenceGap {
+                expected,
+                got: sequence,
+            });
+        }
+    }
     let mut accounts = get_transaction_accounts(vault, metas).await?;
-    let mut mut_accounts = accounts.iter_mut().collect::<Vec<_>>();
-
     let payer_id = metas.iter().position(|meta| *meta.key() == payer).unwrap();
-    mut_accounts[payer_id].prisms -= TRANSACTION_FEE;
-    let total_prisms = mut_accounts
-        .iter()
-        .fold(0, |acc, account| acc + account.prisms);
+    if let Some(deadline) = trx.message().deadline().map(Slot::get) {
+        if slot > deadline {
+            warn!(deadline, slot, "the transaction's deadline passed");
+            if config.charge_missed_deadlines {
+                charge_fee(
+                    vault,
+                    config,
+                    &trx,
+                    accounts.swap_remove(payer_id),
+                    fee,
+                    slot,
+                )
+                .await?;
+            } else {
+                trace!("missed deadlines aren't charged");
+            }
+            return Err(Error::DeadlineExceeded { deadline, slot });
+        }
+    }
 
     {
         trace!("preparing accounts");
-        let trx_accounts = mut_accounts
-            .iter_mut()
-            .enumerate()
-            .map(|(i, account)| TransactionAccount::new(&metas[i], account))
-            .collect::<Vec<_>>();
+        let trx_context = TransactionContext::new(
+            accounts
+                .iter_mut()
+                .enumerate()
+                .map(|(i, account)| TransactionAccount::new(&metas[i], account))
+                .collect(),
+        );
+        let total_before = total_prisms(trx_context.accounts());
+        trx_context.checked_debit(payer_id, fee)?;
+        trx_context.commit();
 
         trace!("looping through instructions");
-        for instruction in &trx.message().instructions {
-            let program = metas[instruction.program_account_id as usize].key();
-            execute_instruction(program, instruction, &trx_accounts)?;
+        let context = Context::new(slot).with_fee(payer, fee);
+        for (index, instruction) in trx.message().instructions.iter().enumerate() {
+            if let Err(err) = execute_instruction(
+                &trx,
+                index,
+                &context,
+                &instruction.data,
+                trx_context.accounts(),
+            ) {
+                trx_context.rollback();
+                return Err(err);
+            }
         }
-    }
-    let new_total_prisms = accounts.iter().fold(0, |acc, account| acc + account.prisms);
-    if total_prisms != new_total_prisms {
-        warn!("there was a change in the total of prisms: ignoring transaction");
-        return Err(Error::PrismTotalChanged);
-    }
-
-    save_accounts(vault, metas, accounts).await?;
-
-    Ok(())
-}
 
-#[instrument(skip_all)]
-fn execute_instruction(
-    program: &Pubkey,
-    instruction: &CompiledInstruction,
-    accounts: &[TransactionAccount],
-) -> Result<()> {
-    debug!("executing instruction");
-    let mut instr_accounts = Vec::new();
-    for i in &instruction.accounts {
-        instr_accounts.push(accounts[*i as usize].clone());
+        if let Err(err) = check_balance(trx_context.accounts(), total_before, -i128::from(fee)) {
+            trx_context.rollback();
+            return Err(err);
+        }
+        trx_context.commit();
     }
+    TRANSACTION_QUEUE.stamp(trx.signature().unwrap(), Stage::ExecutionEnd);
 
-    dispatch(program, &instr_accounts, &instruction.data)?;
+    commit_transaction(
+        vault,
+        config,
+        metas,
+        accounts,
+        fee,
+        trx.message().sequence().map(|sequence| (payer, sequence)),
+        *trx.signature().unwrap(),
+        slot,
+    )
+    .await?;
+    TRANSACTION_QUEUE.stamp(trx.signature().unwrap(), Stage::Committed);
 
     Ok(())
 }
 
-#[instrument(skip_all)]
-#[expect(clippy::significant_drop_tightening)]
-async fn get_transaction_accounts(
+/// Only charges the fee of a transaction to its payer, without executing its instructions.
+#[expect(clippy::unwrap_used)]
+async fn charge_fee(
     vault: &RwLock<Vault>,
-    metas: &[AccountMeta],
-) -> Result<Vec<Wallet>> {
-    debug!("getting the instruction’s account from the disk, creating them if necessary");
-    let vault = vault.read().await;
-    let mut res = Vec::new();
-    for meta in metas {
-        let account = vault.get(meta.key()).await?;
-        res.push(account);
+    config: &ValidatorConfig,
+    trx: &Transaction,
+    mut payer: Wallet,
+    fee: u64,
+    slot: u64,
+) -> Result<()> {
+    debug!(fee, "charging the fee only");
+    let meta = *trx
+        .message()
+        .accounts()
+        .iter()
+        .find(|meta| meta.key() == trx.payer().unwrap())
+        .unwrap();
+    {
+        let trx_context = TransactionContext::new(vec![TransactionAccount::new(&meta, &mut payer)]);
+        trx_context.checked_debit(0, fee)?;
+        trx_context.commit();
     }
 
-    Ok(res)
+    commit_transaction(
+        vault,
+        config,
+        &[meta],
+        vec![payer],
+        fee,
+        None,
+        *trx.signature().unwrap(),
+        slot,
+    )
+    .await
 }
 
+/// Saves the accounts changed by a transaction, distributes its fee and advances the
+/// sequence of its payer under a single lock of the vault.
+///
+/// If any of it fails, the accounts are restored: the payer is never debited of a fee
+/// that wasn't burned nor paid, and a transaction is never saved without its sequence.
+#[expect(
+    clippy::too_many_arguments,
+    reason = "everything a transaction commits at once"
+)]
 #[instrument(skip_all)]
-#[expect(clippy::significant_drop_tightening)]
-async fn save_accounts(
+async fn commit_transaction(
     vault: &RwLock<Vault>,
+    config: &ValidatorConfig,
     metas: &[AccountMeta],
     accounts: Vec<Wallet>,
+    fee: u64,
+    sequence: Option<(Pubkey, u64)>,
+    signature: Signature,
+    slot: u64,
 ) -> Result<()> {
-    debug!("saving accounts on the disk");
+    debug!("committing the transaction");
+    let keys = metas
+        .iter()
+        .map(|meta| *meta.key())
+        .chain(config.identity)
+        .collect::<Vec<_>>();
     let mut vault = vault.write().await;
-    for (meta, account) in metas.iter().zip(accounts.iter()) {
-        if !meta.is_writable() {
-            continue;
-        }
-        vault
-            .save_account(*meta.key(), account, CURRENT_SLOT)
-            .await?;
+    let checkpoint = vault.checkpoint(&keys).await?;
+    let res = match save_accounts(&mut vault, metas, accounts, signature, slot).await {
+        Ok(()) => distribute_fee(&mut vault, config, fee, signature, slot).await,
+        Err(err) => Err(err),
+    };
+    if let Err(err) = res {
+        warn!("could not commit the transaction: {err}, undoing it");
+        vault.rollback(checkpoint, slot, &[signature]).await?;
+        return Err(err);
     }
+    if let Some((payer, sequence)) = sequence {
+        vault.set_sequence(payer, sequence);
+    }
+    drop(vault);
 
     Ok(())
 }
 
-#[cfg(test)]
-#[cfg_attr(coverage_nightly, coverage(off))]
-mod tests {
-    #![expect(clippy::shadow_unrelated)]
+/// Burns a part of the fee, and pays the rest to the validator.
+#[instrument(skip(vault, config, signature))]
+async fn distribute_fee(
+    vault: &mut Vault,
+    config: &ValidatorConfig,
+    fee: u64,
+    signature: Signature,
+    slot: u64,
+) -> Result<()> {
+    debug!("distributing the fee");
+    let split = config.fees.split(fee);
+    let Some(identity) = config.identity else {
+        trace!("no validator identity, burning the whole fee");
+        vault.burn(fee);
+        return Ok(());
+    };
+
+    vault.burn(split.burned);
+    if split.producer > 0 {
+        let mut account = vault.get(&identity).await?;
+        let before = account.prisms;
+        account.prisms = account.prisms.saturating_add(split.producer);
+        vault.save_account(identity, &account, slot).await?;
+        vault.record_balance(identity, slot, signature, before, account.prisms);
+    }
+
+    Ok(())
+}
+
+/// Checks the transaction doesn't invoke programs more than allowed.
+pub(crate) fn check_invocations(config: &ValidatorConfig, trx: &Transaction) -> Result<()> {
+    let instructions = &trx.message().instructions;
+    let max_instructions = config.max_instructions();
+    if instructions.len() > max_instructions {
+        warn!(
+            n = instructions.len(),
+            "too many instructions in the transaction"
+        );
+        return Err(Error::TooManyInstructions {
+            max: max_instructions,
+        });
+    }
+
+    let mut invocations = HashMap::new();
+    for index in 0..instructions.len() {
+        let program = *trx.message().instruction_program_id(index)?;
+        let count = invocations.entry(program).or_insert(0_usize);
+        *count += 1;
+        if let Some(max) = max_invocations(&program) {
+            if *count > max {
+                warn!(%program, "program invoked too many times");
+                return Err(Error::TooManyInvocations { program, max });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Sums the prisms of the accounts, without risking an overflow.
+pub(crate) fn total_prisms(accounts: &[TransactionAccount]) -> i128 {
+    accounts
+        .iter()
+        .map(|account| i128::from(account.prisms()))
+        .sum()
+}
+
+/// Checks the total of prisms of the accounts changed by exactly the expected amount.
+///
+/// # Parameters
+/// * `accounts` - The accounts of the transaction, once executed,
+/// * `total_before` - The total of their prisms before the execution,
+/// * `expected` - The change of the total the execution may make (the fee it burns, the
+///   prisms it mints…).
+pub(crate) fn check_balance(
+    accounts: &[TransactionAccount],
+    total_before: i128,
+    expected: i128,
+) -> Result<()> {
+    let delta = total_prisms(accounts) - total_before - expected;
+    if delta != 0 {
+        warn!(delta, "the total of prisms changed: ignoring transaction");
+        return Err(Error::BalanceInvariantViolation { delta });
+    }
+
+    Ok(())
+}
+
+#[instrument(skip_all, fields(index))]
+pub(crate) fn execute_instruction(
+    trx: &Transaction,
+    index: usize,
+    context: &Context,
+    data: &[u8],
+    accounts: &[TransactionAccount],
+) -> Result<()> {
+    debug!("executing instruction");
+    let message = trx.message();
+    let program = message.instruction_program_id(index)?;
+    let mut instr_accounts = Vec::new();
+    for meta in message.instruction_accounts(index)? {
+        let account = &accounts[meta.index];
+        if account.is_closed() && meta.privileges.is_writable() {
+            warn!(key = %account.key, "closed account used as writable");
+            return Err(AccountError::AccountClosed { key: account.key }.into());
+        }
+        instr_accounts.push(account.clone());
+    }
+
+    dispatch(program, context, &instr_accounts, data).map_err(|err| {
+        err.custom_code().map_or_else(
+            || err.into(),
+            |code| Error::CustomProgramError {
+                instruction: index,
+                program: *program,
+                code,
+            },
+        )
+    })
+}
+
+/// Executes a transaction generated by the validator: it's free, and its
+/// instructions run like those of any other transaction.
+///
+/// The `minted` prisms are created on its payer beforehand, and the total of
+/// prisms of its accounts must have grown by exactly that much once it's executed:
+/// nothing is saved otherwise.
+#[expect(clippy::unwrap_used)]
+#[instrument(skip_all, fields(sig = ?trx.signature().unwrap()))]
+async fn execute_internal(
+    vault: &RwLock<Vault>,
+    trx: &Transaction,
+    minted: u64,
+    slot: u64,
+) -> Result<()> {
+    debug!(minted, "executing internal transaction");
+    let metas = trx.message().accounts();
+    let payer = *trx.payer().unwrap();
+    let mut accounts = get_transaction_accounts(vault, metas).await?;
+    let payer_id = metas.iter().position(|meta| *meta.key() == payer).unwrap();
+    {
+        let trx_context = TransactionContext::new(
+            accounts
+                .iter_mut()
+                .enumerate()
+                .map(|(i, account)| TransactionAccount::new(&metas[i], account))
+                .collect(),
+        );
+        let total_before = total_prisms(trx_context.accounts());
+        trx_context.checked_credit(payer_id, minted)?;
+        let context = Context::new(slot);
+        for (index, instruction) in trx.message().instructions.iter().enumerate() {
+            if let Err(err) = execute_instruction(
+                trx,
+                index,
+                &context,
+                &instruction.data,
+                trx_context.accounts(),
+            ) {
+                trx_context.rollback();
+                return Err(err);
+            }
+        }
+
+        if let Err(err) = check_balance(trx_context.accounts(), total_before, i128::from(minted)) {
+            trx_context.rollback();
+            return Err(err);
+        }
+        trx_context.commit();
+    }
+
+    let mut vault = vault.write().await;
+    save_accounts(&mut vault, metas, accounts, *trx.signature().unwrap(), slot).await
+}
+
+#[instrument(skip_all)]
+#[expect(clippy::significant_drop_tightening)]
+async fn get_transaction_accounts(
+    vault: &RwLock<Vault>,
+    metas: &[AccountMeta],
+) -> Result<Vec<Wallet>> {
+    debug!("getting the instruction’s account from the disk, creating them if necessary");
+    let vault = vault.read().await;
+    let mut res = Vec::new();
+    for meta in metas {
+        let account = vault.get(meta.key()).await?;
+        res.push(account);
+    }
+
+    Ok(res)
+}
+
+#[instrument(skip_all)]
+async fn save_accounts(
+    vault: &mut Vault,
+    metas: &[AccountMeta],
+    accounts: Vec<Wallet>,
+    signature: Signature,
+    slot: u64,
+) -> Result<()> {
+    debug!("saving accounts on the disk");
+    for (meta, account) in metas.iter().zip(accounts.iter()) {
+        if !meta.is_writable() {
+            continue;
+        }
+        let before = vault.get(meta.key()).await?.prisms;
+        if *account == Wallet::default() {
+            vault.remove_account(meta.key()).await?;
+        } else {
+            vault.save_account(*meta.key(), account, slot).await?;
+        }
+        vault.record_balance(*meta.key(), slot, signature, before, account.prisms);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    #![expect(clippy::shadow_unrelated)]
+
+    extern crate test as libtest;
 
     use std::assert_matches::assert_matches;
-    use std::fs::remove_dir_all;
-    use std::path::PathBuf;
+    use std::collections::HashSet;
+    use std::fs::{read, read_dir, remove_dir_all};
+    use std::path::{Path, PathBuf};
+    use std::time::Duration;
 
     use ed25519_dalek::PUBLIC_KEY_LENGTH;
+    use rand::{Rng as _, SeedableRng as _};
+    use rand_chacha::ChaCha20Rng;
     use test_log::test;
     use tokio::sync::oneshot::{channel, Sender as OSender};
     use tokio::task::JoinHandle;
     use tracing::info;
 
-    use crate::account::{AccountMeta, Wallet, Writable};
+    use crate::account::{AccountMeta, Stake, Wallet, Writable};
     use crate::crypto::{Keypair, Pubkey};
     use crate::io::set_vault_path;
-    use crate::program::{system, testing_dummy};
-    use crate::transaction::{Instruction, Transaction};
+    use crate::program::{memo, stake, system, testing_dummy, Epoch};
+    use crate::transaction::{
+        estimate_fee, FeeModel, FeeStructure, Instruction, Message, Transaction, FEE_PER_SIGNATURE,
+    };
+    use crate::validator::admission::{AdmissionPolicy, PayerAllowList};
+    use crate::validator::pipeline::{PipelineBuilder, Scheduler};
+    use crate::validator::transaction_queue::BlockCap;
+    use crate::validator::{
+        verify_audit_trail, AuditConfig, Genesis, GenesisConfig, MemoryBudget, MemoryComponent,
+        QueuePolicy,
+    };
 
     use super::super::Error;
     use super::*;
@@ -226,13 +1509,15 @@ mod tests {
     type Result<T> = core::result::Result<T, Box<dyn core::error::Error>>;
 
     pub const PROGRAM: Pubkey = Pubkey::from_bytes(&[2; PUBLIC_KEY_LENGTH]);
+    /// The fee paid by the transactions signed by a single key.
+    const TRANSACTION_FEE: u64 = FEE_PER_SIGNATURE;
 
     async fn reset_vault<P>(path: P) -> Result<Vault>
     where
         P: Into<PathBuf>,
     {
         let path = path.into();
-        set_vault_path(&path);
+        set_vault_path(&path)?;
         if path.exists() {
             remove_dir_all(path)?;
         }
@@ -277,11 +1562,54 @@ mod tests {
     }
 
     fn launch_transaction_processor(vault: Arc<RwLock<Vault>>) -> (OSender<()>, JoinHandle<()>) {
+        launch_processor_with(vault, ValidatorConfig::default())
+    }
+
+    fn launch_processor_with(
+        vault: Arc<RwLock<Vault>>,
+        config: ValidatorConfig,
+    ) -> (OSender<()>, JoinHandle<()>) {
+        launch_pipeline(vault, PipelineBuilder::new(config).build())
+    }
+
+    fn launch_pipeline(
+        vault: Arc<RwLock<Vault>>,
+        pipeline: Pipeline,
+    ) -> (OSender<()>, JoinHandle<()>) {
         let (tx, rx) = channel();
-        let handle = tokio::spawn(async { processor(vault, rx).await });
+        let (_reloads, reload_control) = tokio::sync::mpsc::channel(1);
+        let handle =
+            tokio::spawn(async move { processor(vault, pipeline, rx, reload_control).await });
         (tx, handle)
     }
 
+    /// Executes a transaction, charged the fee it's admitted with when nothing else is queued.
+    async fn execute_admitted(
+        vault: &RwLock<Vault>,
+        config: &ValidatorConfig,
+        trx: Transaction,
+        slot: u64,
+    ) -> super::Result<()> {
+        let fee = config.fee(trx.message(), 0.0);
+        execute_transaction_inner(vault, config, trx, fee, slot).await
+    }
+
+    async fn wait_for_statuses<T>(receivers: &mut [TReceiver<T>]) -> Vec<T>
+    where
+        T: Default,
+    {
+        let mut statuses = Vec::new();
+        for rx in receivers {
+            let mut status = T::default();
+            while let Some(new_status) = rx.recv().await {
+                status = new_status;
+            }
+            statuses.push(status);
+        }
+
+        statuses
+    }
+
     #[test(tokio::test)]
     async fn accepts_valid_transactions_only() -> TestResult {
         // Given
@@ -297,6 +1625,62 @@ mod tests {
         Ok(())
     }
 
+    /// Measures the intake alone, on a queue of its own so no processor takes the transactions.
+    #[bench]
+    #[expect(clippy::unwrap_used)]
+    fn register_transaction_throughput(b: &mut libtest::Bencher) {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let queue = TransactionQueue::new();
+        let receiver = queue.get_receiver();
+        let trx = create_signed_transaction().unwrap();
+        b.iter(|| {
+            runtime.block_on(register_in(&queue, trx.clone())).unwrap();
+            let (trx, _status) = receiver.try_recv().unwrap();
+            queue.untrack(trx.signature().unwrap());
+            queue.done();
+        });
+    }
+
+    #[test(tokio::test)]
+    async fn trusted_transactions_must_be_signed() -> TestResult {
+        // Given
+        let trx = create_unsigned_transaction()?;
+
+        // When
+        let res = register_trusted(trx).await;
+
+        // Then
+        assert_matches!(res, Err(Error::InvalidTransactionSignatures));
+
+        Ok(())
+    }
+
+    #[cfg(debug_assertions)]
+    #[test(tokio::test)]
+    async fn mis_signed_trusted_transaction_is_caught_in_debug() -> TestResult {
+        // Given
+        let key = Keypair::generate();
+        let mut trx = Transaction::new(0);
+        trx.add(&[system::instruction::transfer(
+            key.pubkey(),
+            Keypair::generate().pubkey(),
+            10,
+        )?])?;
+        trx.sign(&key)?;
+        // changes the slot of the message, right after the only signature
+        let mut bytes = borsh::to_vec(&trx)?;
+        bytes[4 + 64] ^= 1;
+        let mis_signed: Transaction = borsh::from_slice(&bytes)?;
+
+        // When
+        let res = register_trusted(mis_signed).await;
+
+        // Then
+        assert_matches!(res, Err(Error::InvalidTransactionSignatures));
+
+        Ok(())
+    }
+
     #[test(tokio::test)]
     async fn run_system_transfer_transaction() -> TestResult {
         // Given
@@ -307,7 +1691,7 @@ mod tests {
 
         let key1 = Keypair::generate();
         let key2 = Keypair::generate().pubkey();
-        let wallet1_before = Wallet { prisms: AMOUNT };
+        let wallet1_before = Wallet::new(AMOUNT);
 
         vault
             .save_account(key1.pubkey(), &wallet1_before, 0)
@@ -333,6 +1717,7 @@ mod tests {
         stop_control.send(()).unwrap();
         handle.await?;
         vault.write().await.save().await?;
+        drop(vault);
 
         // Then
         let vault = Vault::load_or_create().await?;
@@ -345,6 +1730,29 @@ mod tests {
         Ok(())
     }
 
+    #[test(tokio::test)]
+    async fn clients_no_longer_listening_dont_stop_the_processor() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/validator-45";
+        let vault = Arc::new(RwLock::new(reset_vault(VAULT).await?));
+        let (stop_control, handle) = launch_transaction_processor(Arc::clone(&vault));
+
+        // When
+        drop(register_transaction(create_signed_transaction()?).await?);
+        let statuses =
+            wait_for_statuses(&mut [register_transaction(create_signed_transaction()?).await?])
+                .await;
+
+        // Then
+        assert_ne!(statuses, [Status::Pending]);
+        assert!(!handle.is_finished(), "the processor stopped");
+        #[expect(clippy::unwrap_used)]
+        stop_control.send(()).unwrap();
+        handle.await?;
+
+        Ok(())
+    }
+
     #[test(tokio::test)]
     async fn fail_system_transfer_transaction() -> TestResult {
         // Given
@@ -355,7 +1763,7 @@ mod tests {
 
         let key1 = Keypair::generate();
         let key2 = Keypair::generate().pubkey();
-        let wallet1_before = Wallet { prisms: AMOUNT };
+        let wallet1_before = Wallet::new(AMOUNT);
 
         vault
             .save_account(key1.pubkey(), &wallet1_before, 0)
@@ -396,7 +1804,7 @@ mod tests {
 
         let key1 = Keypair::generate();
         let key2 = Keypair::generate().pubkey();
-        let wallet1_before = Wallet { prisms: AMOUNT };
+        let wallet1_before = Wallet::new(AMOUNT);
 
         vault
             .save_account(key1.pubkey(), &wallet1_before, 0)
@@ -426,4 +1834,2294 @@ mod tests {
 
         Ok(())
     }
+
+    #[test(tokio::test)]
+    async fn revoke_before_queued_delegated_transfer() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/validator-6";
+        const AMOUNT: u64 = 1_000_000;
+
+        let mut vault = reset_vault(VAULT).await?;
+
+        let owner = Keypair::generate();
+        let delegate = Keypair::generate();
+        let payer = Keypair::generate();
+        let receiver = Keypair::generate().pubkey();
+        vault
+            .save_account(owner.pubkey(), &Wallet::new(AMOUNT), 0)
+            .await?;
+        vault
+            .save_account(delegate.pubkey(), &Wallet::new(AMOUNT), 0)
+            .await?;
+        vault
+            .save_account(payer.pubkey(), &Wallet::new(AMOUNT), 0)
+            .await?;
+        vault.save().await?;
+        let vault = Arc::new(RwLock::new(vault));
+
+        let mut approve = Transaction::new(0);
+        approve.add(&[system::instruction::approve(
+            owner.pubkey(),
+            delegate.pubkey(),
+            500_000,
+            false,
+        )?])?;
+        approve.sign(&owner)?;
+        let mut revoke = Transaction::new(0);
+        revoke.add(&[system::instruction::revoke(owner.pubkey())?])?;
+        revoke.sign(&owner)?;
+        // the delegate only signs, the fees are paid by another account
+        let mut spend = Transaction::new(0);
+        spend.add(&[
+            system::instruction::transfer(payer.pubkey(), receiver, 10)?,
+            system::instruction::delegated_transfer(
+                owner.pubkey(),
+                delegate.pubkey(),
+                receiver,
+                100_000,
+            )?,
+        ])?;
+        spend.sign(&payer)?;
+        spend.sign(&delegate)?;
+
+        // When
+        let mut rx_approve = register_transaction(approve).await?;
+        let mut rx_revoke = register_transaction(revoke).await?;
+        let mut rx_spend = register_transaction(spend).await?;
+        let (stop_control, handle) = launch_transaction_processor(Arc::clone(&vault));
+        let mut statuses = Vec::new();
+        for rx in [&mut rx_approve, &mut rx_revoke, &mut rx_spend] {
+            let mut status = Status::Pending;
+            while let Some(new_status) = rx.recv().await {
+                status = new_status;
+            }
+            statuses.push(status);
+        }
+        #[expect(clippy::unwrap_used)]
+        stop_control.send(()).unwrap();
+        handle.await?;
+
+        // Then
+        assert_eq!(
+            statuses,
+            vec![Status::Succeeded, Status::Succeeded, Status::Failed]
+        );
+        let owner_after = vault.read().await.get(&owner.pubkey()).await?;
+        let receiver_after = vault.read().await.get(&receiver).await?;
+        assert_eq!(owner_after.prisms, AMOUNT - 2 * TRANSACTION_FEE);
+        assert_eq!(receiver_after.prisms, 0);
+
+        Ok(())
+    }
+
+    fn read_vault_files(path: &Path) -> Result<Vec<(PathBuf, Vec<u8>)>> {
+        let mut files = Vec::new();
+        for entry in read_dir(path)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                files.extend(read_vault_files(&path)?);
+            } else {
+                files.push((path.clone(), read(path)?));
+            }
+        }
+        files.sort();
+
+        Ok(files)
+    }
+
+    #[test(tokio::test)]
+    async fn failed_transaction_leaves_vault_untouched() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/validator-7";
+        const AMOUNT: u64 = 1_000_000;
+
+        let mut vault = reset_vault(VAULT).await?;
+
+        let key1 = Keypair::generate();
+        let key2 = Keypair::generate().pubkey();
+        let key3 = Keypair::generate().pubkey();
+        vault
+            .save_account(key1.pubkey(), &Wallet::new(AMOUNT), 0)
+            .await?;
+        vault.save().await?;
+        let vault = Arc::new(RwLock::new(vault));
+        let before = read_vault_files(Path::new(VAULT))?;
+
+        let mut trx = Transaction::new(0);
+        trx.add(&[
+            system::instruction::transfer(key1.pubkey(), key2, 500_000)?,
+            system::instruction::transfer(key1.pubkey(), key3, 500_000)?,
+        ])?;
+        trx.sign(&key1)?;
+
+        // When
+        let mut status = Status::Pending;
+        let mut rx = register_transaction(trx).await?;
+        let (stop_control, handle) = launch_transaction_processor(Arc::clone(&vault));
+        while let Some(new_status) = rx.recv().await {
+            status = new_status;
+        }
+        #[expect(clippy::unwrap_used)]
+        stop_control.send(()).unwrap();
+        handle.await?;
+
+        // Then
+        assert_eq!(status, Status::Failed);
+        assert_eq!(read_vault_files(Path::new(VAULT))?, before);
+        assert_eq!(vault.read().await.get(&key1.pubkey()).await?.prisms, AMOUNT);
+
+        Ok(())
+    }
+
+    async fn run_transactions(
+        vault: &Arc<RwLock<Vault>>,
+        transactions: Vec<Transaction>,
+    ) -> Result<Vec<Status>> {
+        let mut receivers = Vec::new();
+        for trx in transactions {
+            receivers.push(register_transaction(trx).await?);
+        }
+        let (stop_control, handle) = launch_transaction_processor(Arc::clone(vault));
+        let mut statuses = Vec::new();
+        for rx in &mut receivers {
+            let mut status = Status::Pending;
+            while let Some(new_status) = rx.recv().await {
+                status = new_status;
+            }
+            statuses.push(status);
+        }
+        #[expect(clippy::unwrap_used)]
+        stop_control.send(()).unwrap();
+        handle.await?;
+
+        Ok(statuses)
+    }
+
+    #[test(tokio::test)]
+    async fn closed_account_starts_from_scratch() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/validator-8";
+        const AMOUNT: u64 = 1_000_000;
+
+        let mut vault = reset_vault(VAULT).await?;
+        let owner = Keypair::generate();
+        let funder = Keypair::generate();
+        let beneficiary = Keypair::generate().pubkey();
+        vault
+            .save_account(owner.pubkey(), &Wallet::new(AMOUNT), 0)
+            .await?;
+        vault
+            .save_account(funder.pubkey(), &Wallet::new(AMOUNT), 0)
+            .await?;
+        vault.save().await?;
+        let vault = Arc::new(RwLock::new(vault));
+
+        let mut approve = Transaction::new(0);
+        approve.add(&[system::instruction::approve(
+            owner.pubkey(),
+            beneficiary,
+            1_000,
+            false,
+        )?])?;
+        approve.sign(&owner)?;
+        let mut close = Transaction::new(0);
+        close.add(&[system::instruction::close_account(
+            owner.pubkey(),
+            beneficiary,
+        )?])?;
+        close.sign(&owner)?;
+        let mut fund = Transaction::new(0);
+        fund.add(&[system::instruction::transfer(
+            funder.pubkey(),
+            owner.pubkey(),
+            10_000,
+        )?])?;
+        fund.sign(&funder)?;
+
+        // When
+        let statuses = run_transactions(&vault, vec![approve, close, fund]).await?;
+
+        // Then
+        assert_eq!(statuses, vec![Status::Succeeded; 3]);
+        let owner_after = vault.read().await.get(&owner.pubkey()).await?;
+        let beneficiary_after = vault.read().await.get(&beneficiary).await?;
+        assert_eq!(owner_after, Wallet::new(10_000));
+        assert_eq!(beneficiary_after.prisms, AMOUNT - 2 * TRANSACTION_FEE);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn closed_account_cannot_be_written_again() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/validator-9";
+        const AMOUNT: u64 = 1_000_000;
+
+        let mut vault = reset_vault(VAULT).await?;
+        let owner = Keypair::generate();
+        let beneficiary = Keypair::generate().pubkey();
+        vault
+            .save_account(owner.pubkey(), &Wallet::new(AMOUNT), 0)
+            .await?;
+        vault.save().await?;
+        let vault = Arc::new(RwLock::new(vault));
+
+        let mut trx = Transaction::new(0);
+        trx.add(&[
+            system::instruction::close_account(owner.pubkey(), beneficiary)?,
+            system::instruction::transfer(owner.pubkey(), beneficiary, 0)?,
+        ])?;
+        trx.sign(&owner)?;
+
+        // When
+        let statuses = run_transactions(&vault, vec![trx]).await?;
+
+        // Then
+        assert_eq!(statuses, vec![Status::Failed]);
+        let owner_after = vault.read().await.get(&owner.pubkey()).await?;
+        assert_eq!(owner_after.prisms, AMOUNT);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn programs_see_the_fee_paid() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/validator-10";
+        const AMOUNT: u64 = 1_000_000;
+
+        let mut vault = reset_vault(VAULT).await?;
+        let payer = Keypair::generate();
+        let sender = Keypair::generate();
+        let receiver = Keypair::generate().pubkey();
+        vault
+            .save_account(payer.pubkey(), &Wallet::new(AMOUNT), 0)
+            .await?;
+        vault
+            .save_account(sender.pubkey(), &Wallet::new(AMOUNT), 0)
+            .await?;
+        vault.save().await?;
+        let vault = Arc::new(RwLock::new(vault));
+
+        let mut trx = Transaction::new(0);
+        trx.add(&[
+            testing_dummy::instruction::check_fee(payer.pubkey(), 2 * TRANSACTION_FEE)?,
+            system::instruction::transfer(sender.pubkey(), receiver, 500_000)?,
+            testing_dummy::instruction::check_fee(payer.pubkey(), 2 * TRANSACTION_FEE)?,
+        ])?;
+        trx.sign(&payer)?;
+        trx.sign(&sender)?;
+        let mut wrong_fee = Transaction::new(0);
+        wrong_fee.add(&[testing_dummy::instruction::check_fee(
+            payer.pubkey(),
+            TRANSACTION_FEE + 1,
+        )?])?;
+        wrong_fee.sign(&payer)?;
+
+        // When
+        let statuses = run_transactions(&vault, vec![trx, wrong_fee]).await?;
+
+        // Then
+        assert_eq!(statuses, vec![Status::Succeeded, Status::Failed]);
+        let payer_after = vault.read().await.get(&payer.pubkey()).await?;
+        let sender_after = vault.read().await.get(&sender.pubkey()).await?;
+        // the first transaction has two signatures, the failed one isn't charged
+        assert_eq!(payer_after.prisms, AMOUNT - 2 * TRANSACTION_FEE);
+        assert_eq!(sender_after.prisms, AMOUNT - 500_000);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn conjured_prisms_are_not_committed() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/validator-11";
+        const AMOUNT: u64 = 1_000_000;
+
+        let mut vault = reset_vault(VAULT).await?;
+        let key = Keypair::generate();
+        vault
+            .save_account(key.pubkey(), &Wallet::new(AMOUNT), 0)
+            .await?;
+        vault.save().await?;
+        let vault = RwLock::new(vault);
+
+        let mut trx = Transaction::new(0);
+        trx.add(&[testing_dummy::instruction::mint_prisms(
+            key.pubkey(),
+            500_000,
+        )?])?;
+        trx.sign(&key)?;
+
+        // When
+        let res = execute_admitted(&vault, &ValidatorConfig::default(), trx, FIRST_SLOT).await;
+
+        // Then
+        assert_matches!(
+            res,
+            Err(Error::BalanceInvariantViolation { delta: 500_000 })
+        );
+        let wallet_after = vault.read().await.get(&key.pubkey()).await?;
+        assert_eq!(wallet_after.prisms, AMOUNT);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn instructions_per_transaction_are_capped() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/validator-12";
+        let mut vault = reset_vault(VAULT).await?;
+        let key = Keypair::generate();
+        let receiver = Keypair::generate().pubkey();
+        vault
+            .save_account(key.pubkey(), &Wallet::new(1_000_000), 0)
+            .await?;
+        vault.save().await?;
+        let vault = RwLock::new(vault);
+        let config = ValidatorConfig {
+            max_instructions: 2,
+            ..ValidatorConfig::default()
+        };
+        let transfer = system::instruction::transfer(key.pubkey(), receiver, 1)?;
+        let mut at_limit = Transaction::new(0);
+        at_limit.add(&[
+            transfer.clone(),
+            memo::instruction::memo("first", &[key.pubkey()])?,
+        ])?;
+        at_limit.sign(&key)?;
+        let mut over_limit = Transaction::new(0);
+        over_limit.add(&[
+            transfer,
+            memo::instruction::memo("first", &[key.pubkey()])?,
+            memo::instruction::memo("second", &[key.pubkey()])?,
+        ])?;
+        over_limit.sign(&key)?;
+
+        // When
+        execute_admitted(&vault, &config, at_limit, FIRST_SLOT).await?;
+        let res = execute_admitted(&vault, &config, over_limit, FIRST_SLOT).await;
+
+        // Then
+        assert_matches!(res, Err(Error::TooManyInstructions { max: 2 }));
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn program_invocations_are_capped() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/validator-13";
+        let mut vault = reset_vault(VAULT).await?;
+        let key = Keypair::generate();
+        let receiver = Keypair::generate().pubkey();
+        vault
+            .save_account(key.pubkey(), &Wallet::new(1_000_000), 0)
+            .await?;
+        vault.save().await?;
+        let vault = RwLock::new(vault);
+        let mut instructions = vec![system::instruction::transfer(key.pubkey(), receiver, 1)?];
+        for _ in 0..memo::MAX_INVOCATIONS {
+            instructions.push(memo::instruction::memo("memo", &[key.pubkey()])?);
+        }
+        let mut at_limit = Transaction::new(0);
+        at_limit.add(&instructions)?;
+        at_limit.sign(&key)?;
+        instructions.push(memo::instruction::memo("memo", &[key.pubkey()])?);
+        let mut over_limit = Transaction::new(0);
+        over_limit.add(&instructions)?;
+        over_limit.sign(&key)?;
+
+        // When
+        execute_admitted(&vault, &ValidatorConfig::default(), at_limit, FIRST_SLOT).await?;
+        let res =
+            execute_admitted(&vault, &ValidatorConfig::default(), over_limit, FIRST_SLOT).await;
+
+        // Then
+        assert_matches!(
+            res,
+            Err(Error::TooManyInvocations { program, max: memo::MAX_INVOCATIONS })
+                if program == memo::MEMO_PROGRAM
+        );
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn paused_intake_rejects_transactions() -> TestResult {
+        // Given
+        let trx = create_signed_transaction()?;
+        pause_intake();
+
+        // When
+        let paused = register_transaction(trx.clone()).await;
+        resume_intake();
+        let resumed = register_transaction(trx).await;
+
+        // Then
+        assert_matches!(paused, Err(Error::IntakePaused));
+        assert_matches!(resumed, Ok(_));
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn drain_completes_queued_transactions() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/validator-14";
+        let mut vault = reset_vault(VAULT).await?;
+        let key = Keypair::generate();
+        let receiver = Keypair::generate().pubkey();
+        vault
+            .save_account(key.pubkey(), &Wallet::new(1_000_000), 0)
+            .await?;
+        vault.save().await?;
+        let vault = Arc::new(RwLock::new(vault));
+        let mut receivers = Vec::new();
+        for amount in 1..=10 {
+            let mut trx = Transaction::new(0);
+            trx.add(&[system::instruction::transfer(
+                key.pubkey(),
+                receiver,
+                amount,
+            )?])?;
+            trx.sign(&key)?;
+            receivers.push(register_transaction(trx).await?);
+        }
+        let (stop_control, handle) = launch_transaction_processor(Arc::clone(&vault));
+
+        // When
+        drain().await;
+        let res = register_transaction(create_signed_transaction()?).await;
+
+        // Then
+        for mut rx in receivers {
+            let mut status = Status::Pending;
+            while let Ok(new_status) = rx.try_recv() {
+                status = new_status;
+            }
+            assert_eq!(status, Status::Succeeded);
+        }
+        assert_matches!(res, Err(Error::IntakePaused));
+        assert_eq!(vault.read().await.get(&receiver).await?.prisms, 55);
+        #[expect(clippy::unwrap_used)]
+        stop_control.send(()).unwrap();
+        handle.await?;
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn charged_fee_matches_estimate() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/validator-15";
+        const AMOUNT: u64 = 1_000_000;
+        let mut vault = reset_vault(VAULT).await?;
+        let payer = Keypair::generate();
+        let receiver = Keypair::generate().pubkey();
+        vault
+            .save_account(payer.pubkey(), &Wallet::new(AMOUNT), 0)
+            .await?;
+        vault.save().await?;
+        let vault = RwLock::new(vault);
+        let config = ValidatorConfig {
+            fees: FeeStructure {
+                base_fee: 1_000,
+                per_signature: 300,
+                per_byte: 2,
+                ..FeeStructure::default()
+            },
+            ..ValidatorConfig::default()
+        };
+        let mut rng = ChaCha20Rng::seed_from_u64(679);
+
+        for _ in 0..20_u8 {
+            let signers = (0..rng.gen_range(0..4_u8))
+                .map(|_| Keypair::generate())
+                .collect::<Vec<_>>();
+            let amount = rng.gen_range(0..100);
+            let mut instructions = vec![system::instruction::transfer(
+                payer.pubkey(),
+                receiver,
+                amount,
+            )?];
+            for i in 0..rng.gen_range(1..=memo::MAX_INVOCATIONS) {
+                let keys = signers
+                    .iter()
+                    .map(Keypair::pubkey)
+                    .chain([payer.pubkey()])
+                    .collect::<Vec<_>>();
+                instructions.push(memo::instruction::memo(&"m".repeat(i * 10), &keys)?);
+            }
+            let mut trx = Transaction::new(0);
+            trx.add(&instructions)?;
+            for signer in signers.iter().chain([&payer]) {
+                trx.sign(signer)?;
+            }
+            let estimate = estimate_fee(trx.message(), &config.fees);
+            let before = vault.read().await.get(&payer.pubkey()).await?.prisms;
+
+            // When
+            execute_admitted(&vault, &config, trx, FIRST_SLOT).await?;
+
+            // Then
+            let after = vault.read().await.get(&payer.pubkey()).await?.prisms;
+            assert_eq!(before - after - amount, estimate);
+        }
+
+        Ok(())
+    }
+
+    #[test(tokio::test(flavor = "multi_thread", worker_threads = 4))]
+    async fn concurrent_registrations_are_all_processed() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/validator-16";
+        const TASKS: u64 = 16;
+        const PER_TASK: u64 = 8;
+        let vault = Arc::new(RwLock::new(reset_vault(VAULT).await?));
+        let (stop_control, handle) = launch_transaction_processor(Arc::clone(&vault));
+
+        // When
+        let mut tasks = Vec::new();
+        for task in 0..TASKS {
+            let transactions = (0..PER_TASK)
+                .map(|_| create_signed_transaction())
+                .collect::<Result<Vec<_>>>()?;
+            tasks.push(tokio::spawn(async move {
+                let mut receivers = Vec::new();
+                for (i, trx) in (0..).zip(transactions) {
+                    if (task + i) % 3 == 0 {
+                        tokio::task::yield_now().await;
+                    }
+                    receivers.push(register_transaction(trx).await?);
+                }
+                super::Result::Ok(receivers)
+            }));
+        }
+        let mut processed = 0_u64;
+        for task in tasks {
+            for mut rx in task.await?? {
+                let mut status = Status::Pending;
+                while let Some(new_status) =
+                    tokio::time::timeout(Duration::from_secs(5), rx.recv()).await?
+                {
+                    status = new_status;
+                }
+                if status != Status::Pending {
+                    processed += 1;
+                }
+            }
+        }
+        #[expect(clippy::unwrap_used)]
+        stop_control.send(()).unwrap();
+        handle.await?;
+
+        // Then
+        assert_eq!(processed, TASKS * PER_TASK);
+
+        Ok(())
+    }
+
+    #[test(tokio::test(flavor = "multi_thread", worker_threads = 4))]
+    async fn concurrent_retries_are_queued_once() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/validator-38";
+        const RETRIES: usize = 16;
+        const AMOUNT: u64 = 1_000_000;
+        const KEY: IdempotencyKey = 0x00C0_FFEE;
+        let mut vault = reset_vault(VAULT).await?;
+        let payer = Keypair::generate();
+        vault
+            .save_account(payer.pubkey(), &Wallet::new(AMOUNT), 0)
+            .await?;
+        vault.save().await?;
+        let vault = Arc::new(RwLock::new(vault));
+        let mut trx = Transaction::new(0);
+        trx.add(&[system::instruction::transfer(
+            payer.pubkey(),
+            Keypair::generate().pubkey(),
+            10,
+        )?])?;
+        trx.sign(&payer)?;
+        let id = *trx.signature().ok_or("unsigned")?;
+        let start = Arc::new(tokio::sync::Barrier::new(RETRIES));
+
+        // When
+        let mut tasks = Vec::new();
+        for _ in 0..RETRIES {
+            let (trx, start) = (trx.clone(), Arc::clone(&start));
+            tasks.push(tokio::spawn(async move {
+                start.wait().await;
+                register_with_key(trx, KEY).await
+            }));
+        }
+        let mut queued = Vec::new();
+        let mut replayed = Vec::new();
+        for task in tasks {
+            match task.await?? {
+                Submission::Queued(rx) => queued.push(rx),
+                Submission::Replayed(original) => replayed.push(original),
+            }
+        }
+        let waiting = pending().len();
+        let (stop_control, handle) = launch_transaction_processor(Arc::clone(&vault));
+        let statuses = wait_for_statuses(&mut queued).await;
+        #[expect(clippy::unwrap_used)]
+        stop_control.send(()).unwrap();
+        handle.await?;
+
+        // Then
+        assert_eq!(waiting, 1);
+        assert_eq!(statuses, vec![Status::Succeeded]);
+        assert_eq!(replayed, vec![id; RETRIES - 1]);
+        assert_eq!(
+            vault.read().await.get(&payer.pubkey()).await?.prisms,
+            AMOUNT - 10 - TRANSACTION_FEE
+        );
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn fees_are_split_between_burn_and_producer() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/validator-17";
+        const AMOUNT: u64 = 1_000_000;
+        let mut vault = reset_vault(VAULT).await?;
+        let payer = Keypair::generate();
+        let receiver = Keypair::generate().pubkey();
+        let identity = Keypair::generate().pubkey();
+        vault
+            .save_account(payer.pubkey(), &Wallet::new(AMOUNT), 0)
+            .await?;
+        vault.save().await?;
+        let vault = RwLock::new(vault);
+        let config = ValidatorConfig {
+            fees: FeeStructure {
+                base_fee: 1_001,
+                burn_percent: 40,
+                ..FeeStructure::default()
+            },
+            identity: Some(identity),
+            ..ValidatorConfig::default()
+        };
+        let mut rng = ChaCha20Rng::seed_from_u64(685);
+        let mut fees = 0;
+        let mut expected_burn = 0;
+
+        // When
+        for _ in 0..20_u8 {
+            let mut trx = Transaction::new(0);
+            trx.add(&[system::instruction::transfer(
+                payer.pubkey(),
+                receiver,
+                rng.gen_range(0..1_000),
+            )?])?;
+            trx.sign(&payer)?;
+            let fee = estimate_fee(trx.message(), &config.fees);
+            fees += fee;
+            expected_burn += config.fees.split(fee).burned;
+            execute_admitted(&vault, &config, trx, FIRST_SLOT).await?;
+        }
+
+        // Then
+        let vault = vault.read().await;
+        let burned = vault.burned();
+        let mut supply = burned;
+        for key in [payer.pubkey(), receiver, identity] {
+            supply += vault.get(&key).await?.prisms;
+        }
+        assert_eq!(supply, AMOUNT);
+        assert_eq!(burned, expected_burn);
+        assert_eq!(vault.get(&identity).await?.prisms, fees - burned);
+        drop(vault);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn sequenced_transactions_run_in_order() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/validator-18";
+        const AMOUNT: u64 = 1_000_000;
+        let mut vault = reset_vault(VAULT).await?;
+        let payer = Keypair::generate();
+        let receiver = Keypair::generate().pubkey();
+        vault
+            .save_account(payer.pubkey(), &Wallet::new(AMOUNT), 0)
+            .await?;
+        vault.save().await?;
+        let vault = Arc::new(RwLock::new(vault));
+        let config = ValidatorConfig {
+            balance_history: true,
+            ..ValidatorConfig::default()
+        };
+        let mut receivers = Vec::new();
+        for sequence in [2, 3, 1] {
+            let mut trx = Transaction::new(0).with_sequence(sequence)?;
+            trx.add(&[system::instruction::transfer(
+                payer.pubkey(),
+                receiver,
+                sequence,
+            )?])?;
+            trx.sign(&payer)?;
+            receivers.push(register_transaction(trx).await?);
+        }
+
+        // When
+        let (stop_control, handle) = launch_processor_with(Arc::clone(&vault), config);
+        let statuses = wait_for_statuses(&mut receivers).await;
+        #[expect(clippy::unwrap_used)]
+        stop_control.send(()).unwrap();
+        handle.await?;
+
+        // Then
+        assert_eq!(statuses, vec![Status::Succeeded; 3]);
+        let vault = vault.read().await;
+        let deltas = vault
+            .get_balance_history(&receiver, 0, u64::MAX, 0, 10)
+            .iter()
+            .map(|change| change.delta)
+            .collect::<Vec<_>>();
+        assert_eq!(deltas, vec![1, 2, 3]);
+        assert_eq!(vault.last_sequence(&payer.pubkey()), Some(3));
+        drop(vault);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn held_transaction_fails_after_timeout() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/validator-19";
+        const TIMEOUT: Duration = Duration::from_millis(100);
+        let vault = Arc::new(RwLock::new(reset_vault(VAULT).await?));
+        let config = ValidatorConfig {
+            sequence_timeout: TIMEOUT,
+            ..ValidatorConfig::default()
+        };
+        let payer = Keypair::generate();
+        let mut trx = Transaction::new(0).with_sequence(2)?;
+        trx.add(&[system::instruction::transfer(
+            payer.pubkey(),
+            Keypair::generate().pubkey(),
+            10,
+        )?])?;
+        trx.sign(&payer)?;
+        let start = Instant::now();
+
+        // When
+        let mut receivers = vec![register_transaction(trx).await?];
+        let (stop_control, handle) = launch_processor_with(Arc::clone(&vault), config);
+        let statuses = wait_for_statuses(&mut receivers).await;
+        #[expect(clippy::unwrap_used)]
+        stop_control.send(()).unwrap();
+        handle.await?;
+
+        // Then
+        assert_eq!(statuses, vec![Status::Failed]);
+        assert!(start.elapsed() >= TIMEOUT);
+        assert_eq!(vault.read().await.last_sequence(&payer.pubkey()), None);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn capped_blocks_roll_over_in_order() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/validator-20";
+        const AMOUNT: u64 = 1_000_000;
+        let mut vault = reset_vault(VAULT).await?;
+        let payer = Keypair::generate();
+        let receiver = Keypair::generate().pubkey();
+        vault
+            .save_account(payer.pubkey(), &Wallet::new(AMOUNT), 0)
+            .await?;
+        vault.save().await?;
+        let vault = Arc::new(RwLock::new(vault));
+        let config = ValidatorConfig {
+            batch_size: 3,
+            balance_history: true,
+            ..ValidatorConfig::default()
+        };
+        let mut receivers = Vec::new();
+        for amount in 1..=10 {
+            let mut trx = Transaction::new(0);
+            trx.add(&[system::instruction::transfer(
+                payer.pubkey(),
+                receiver,
+                amount,
+            )?])?;
+            trx.sign(&payer)?;
+            receivers.push(register_transaction(trx).await?);
+        }
+
+        // When
+        let (stop_control, handle) = launch_processor_with(Arc::clone(&vault), config);
+        let statuses = wait_for_statuses(&mut receivers).await;
+        #[expect(clippy::unwrap_used)]
+        stop_control.send(()).unwrap();
+        handle.await?;
+
+        // Then
+        assert_eq!(statuses, vec![Status::Succeeded; 10]);
+        let vault = vault.read().await;
+        let deltas = vault
+            .get_balance_history(&receiver, 0, u64::MAX, 0, 20)
+            .iter()
+            .map(|change| change.delta)
+            .collect::<Vec<_>>();
+        assert_eq!(deltas, (1..=10).collect::<Vec<_>>());
+        drop(vault);
+        assert!(block_caps().transactions >= 3);
+
+        Ok(())
+    }
+
+    /// A fee model charging the same fee to every transaction.
+    #[derive(Debug)]
+    struct FlatFee(u64);
+
+    impl FeeModel for FlatFee {
+        fn fee(&self, _message: &Message, _queue_pressure: f64) -> u64 {
+            self.0
+        }
+    }
+
+    #[test(tokio::test)]
+    async fn custom_fee_model_is_charged() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/validator-21";
+        const AMOUNT: u64 = 1_000_000;
+        const FEE: u64 = 1_234;
+        let mut vault = reset_vault(VAULT).await?;
+        let payer = Keypair::generate();
+        let receiver = Keypair::generate().pubkey();
+        vault
+            .save_account(payer.pubkey(), &Wallet::new(AMOUNT), 0)
+            .await?;
+        vault.save().await?;
+        let vault = RwLock::new(vault);
+        let model: Arc<dyn FeeModel> = Arc::new(FlatFee(FEE));
+        let config = ValidatorConfig {
+            fee_model: Some(Arc::clone(&model)),
+            ..ValidatorConfig::default()
+        };
+        let mut trx = Transaction::new(0);
+        trx.add(&[system::instruction::transfer(payer.pubkey(), receiver, 10)?])?;
+        trx.sign(&payer)?;
+        let estimate = model.fee(trx.message(), 0.0);
+
+        // When
+        execute_admitted(&vault, &config, trx, FIRST_SLOT).await?;
+
+        // Then
+        let vault = vault.read().await;
+        assert_eq!(estimate, FEE);
+        assert_eq!(vault.get(&payer.pubkey()).await?.prisms, AMOUNT - 10 - FEE);
+        assert_eq!(vault.burned(), FEE);
+        drop(vault);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn fee_decided_at_admission_is_charged() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/validator-46";
+        const AMOUNT: u64 = 1_000_000;
+        const FEE: u64 = 1_234;
+        let mut vault = reset_vault(VAULT).await?;
+        let payer = Keypair::generate();
+        vault
+            .save_account(payer.pubkey(), &Wallet::new(AMOUNT), 0)
+            .await?;
+        let vault = RwLock::new(vault);
+        let mut pipeline = PipelineBuilder::new(ValidatorConfig {
+            fee_model: Some(Arc::new(FlatFee(FEE))),
+            ..ValidatorConfig::default()
+        })
+        .build();
+        let mut trx = Transaction::new(0);
+        trx.add(&[system::instruction::transfer(
+            payer.pubkey(),
+            Keypair::generate().pubkey(),
+            10,
+        )?])?;
+        trx.sign(&payer)?;
+        let (tx, _rx) = tokio::sync::mpsc::channel(5);
+
+        // When
+        admit(&mut pipeline, &mut BTreeMap::new(), (trx, tx), FIRST_SLOT).await;
+        pipeline.config.fee_model = Some(Arc::new(FlatFee(FEE * 2)));
+        let (batch, _) = pipeline.scheduler.next_batch(1, usize::MAX);
+        let (trx, _, fee) = batch.into_iter().next().ok_or("nothing admitted")?;
+        execute_transaction_inner(&vault, &pipeline.config, trx, fee, FIRST_SLOT).await?;
+
+        // Then
+        let vault = vault.read().await;
+        assert_eq!(fee, FEE);
+        assert_eq!(vault.get(&payer.pubkey()).await?.prisms, AMOUNT - 10 - FEE);
+        drop(vault);
+
+        Ok(())
+    }
+
+    const DEADLINE_AMOUNT: u64 = 1_000_000;
+    const DEADLINE_FEE: u64 = 1_000;
+
+    /// Queues three transfers behind blocks of a single transaction, the second one
+    /// only valid during the first slot, and returns their statuses along with the
+    /// final balances of the payer and the receiver.
+    async fn run_past_deadline(
+        path: &str,
+        charge_missed_deadlines: bool,
+    ) -> Result<(Vec<Status>, u64, u64)> {
+        let mut vault = reset_vault(path).await?;
+        let payer = Keypair::generate();
+        let receiver = Keypair::generate().pubkey();
+        vault
+            .save_account(payer.pubkey(), &Wallet::new(DEADLINE_AMOUNT), 0)
+            .await?;
+        vault.save().await?;
+        let vault = Arc::new(RwLock::new(vault));
+        let config = ValidatorConfig {
+            batch_size: 1,
+            fee_model: Some(Arc::new(FlatFee(DEADLINE_FEE))),
+            charge_missed_deadlines,
+            ..ValidatorConfig::default()
+        };
+        let mut receivers = Vec::new();
+        for (amount, deadline) in [(1, None), (2, Some(FIRST_SLOT)), (4, Some(FIRST_SLOT + 2))] {
+            let mut trx = Transaction::new(0);
+            if let Some(deadline) = deadline {
+                trx = trx.with_deadline(deadline)?;
+            }
+            trx.add(&[system::instruction::transfer(
+                payer.pubkey(),
+                receiver,
+                amount,
+            )?])?;
+            trx.sign(&payer)?;
+            receivers.push(register_transaction(trx).await?);
+        }
+
+        let (stop_control, handle) = launch_processor_with(Arc::clone(&vault), config);
+        let statuses = wait_for_statuses(&mut receivers).await;
+        #[expect(clippy::unwrap_used)]
+        stop_control.send(()).unwrap();
+        handle.await?;
+
+        let vault = vault.read().await;
+        let payer_prisms = vault.get(&payer.pubkey()).await?.prisms;
+        let receiver_prisms = vault.get(&receiver).await?.prisms;
+        drop(vault);
+        Ok((statuses, payer_prisms, receiver_prisms))
+    }
+
+    #[test(tokio::test)]
+    async fn deadline_passes_behind_full_block() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/validator-22";
+
+        // When
+        let (statuses, payer, receiver) = run_past_deadline(VAULT, true).await?;
+
+        // Then
+        assert_eq!(
+            statuses,
+            vec![Status::Succeeded, Status::Failed, Status::Succeeded]
+        );
+        assert_eq!(receiver, 5);
+        assert_eq!(payer, DEADLINE_AMOUNT - 5 - 3 * DEADLINE_FEE);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn missed_deadline_can_be_free() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/validator-23";
+
+        // When
+        let (statuses, payer, receiver) = run_past_deadline(VAULT, false).await?;
+
+        // Then
+        assert_eq!(
+            statuses,
+            vec![Status::Succeeded, Status::Failed, Status::Succeeded]
+        );
+        assert_eq!(receiver, 5);
+        assert_eq!(payer, DEADLINE_AMOUNT - 5 - 2 * DEADLINE_FEE);
+
+        Ok(())
+    }
+
+    /// A scheduler executing the most recent transactions first, one per batch.
+    #[derive(Default)]
+    struct Lifo(Vec<AdmittedTransaction>);
+
+    impl Scheduler for Lifo {
+        fn push(&mut self, transaction: AdmittedTransaction) {
+            self.0.push(transaction);
+        }
+
+        fn is_empty(&self) -> bool {
+            self.0.is_empty()
+        }
+
+        fn next_batch(
+            &mut self,
+            _size: usize,
+            _max_bytes: usize,
+        ) -> (Vec<AdmittedTransaction>, Option<BlockCap>) {
+            let batch = self.0.pop().into_iter().collect();
+            let cap = (!self.0.is_empty()).then_some(BlockCap::Transactions);
+            (batch, cap)
+        }
+    }
+
+    /// A policy deferring every transaction until a slot.
+    #[derive(Debug)]
+    struct OpensAt(u64);
+
+    impl AdmissionPolicy for OpensAt {
+        fn admit(
+            &self,
+            _transaction: &Transaction,
+            context: &AdmissionContext,
+        ) -> AdmissionDecision {
+            if context.slot < self.0 {
+                AdmissionDecision::Defer(self.0)
+            } else {
+                AdmissionDecision::Accept
+            }
+        }
+    }
+
+    #[test(tokio::test)]
+    async fn custom_scheduler_orders_execution() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/validator-24";
+        const AMOUNT: u64 = 1_000_000;
+        let mut vault = reset_vault(VAULT).await?;
+        let payer = Keypair::generate();
+        let receiver = Keypair::generate().pubkey();
+        vault
+            .save_account(payer.pubkey(), &Wallet::new(AMOUNT), 0)
+            .await?;
+        vault.save().await?;
+        let vault = Arc::new(RwLock::new(vault));
+        let config = ValidatorConfig {
+            balance_history: true,
+            ..ValidatorConfig::default()
+        };
+        let mut receivers = Vec::new();
+        for amount in 1..=4 {
+            let mut trx = Transaction::new(0);
+            trx.add(&[system::instruction::transfer(
+                payer.pubkey(),
+                receiver,
+                amount,
+            )?])?;
+            trx.sign(&payer)?;
+            receivers.push(register_transaction(trx).await?);
+        }
+        let pipeline = PipelineBuilder::new(config)
+            .with_scheduler(Box::new(Lifo::default()))
+            .build();
+
+        // When
+        let (stop_control, handle) = launch_pipeline(Arc::clone(&vault), pipeline);
+        let statuses = wait_for_statuses(&mut receivers).await;
+        #[expect(clippy::unwrap_used)]
+        stop_control.send(()).unwrap();
+        handle.await?;
+
+        // Then
+        assert_eq!(statuses, vec![Status::Succeeded; 4]);
+        let vault = vault.read().await;
+        let deltas = vault
+            .get_balance_history(&receiver, 0, u64::MAX, 0, 10)
+            .iter()
+            .map(|change| change.delta)
+            .collect::<Vec<_>>();
+        assert_eq!(deltas, vec![4, 3, 2, 1]);
+        drop(vault);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn policy_rejects_before_execution() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/validator-25";
+        const AMOUNT: u64 = 1_000_000;
+        let mut vault = reset_vault(VAULT).await?;
+        let denied = Keypair::generate();
+        let allowed = Keypair::generate();
+        let receiver = Keypair::generate().pubkey();
+        for payer in [&denied, &allowed] {
+            vault
+                .save_account(payer.pubkey(), &Wallet::new(AMOUNT), 0)
+                .await?;
+        }
+        vault.save().await?;
+        let vault = Arc::new(RwLock::new(vault));
+        let mut receivers = Vec::new();
+        for payer in [&denied, &allowed] {
+            let mut trx = Transaction::new(0);
+            trx.add(&[system::instruction::transfer(payer.pubkey(), receiver, 10)?])?;
+            trx.sign(payer)?;
+            receivers.push(register_transaction(trx).await?);
+        }
+        let pipeline = PipelineBuilder::new(ValidatorConfig::default())
+            .with_policy(Arc::new(PayerAllowList(HashSet::from([allowed.pubkey()]))))
+            .build();
+
+        // When
+        let (stop_control, handle) = launch_pipeline(Arc::clone(&vault), pipeline);
+        let statuses = wait_for_statuses(&mut receivers).await;
+        #[expect(clippy::unwrap_used)]
+        stop_control.send(()).unwrap();
+        handle.await?;
+
+        // Then
+        assert_eq!(
+            statuses,
+            vec![
+                Status::Rejected("the payer isn't allowed"),
+                Status::Succeeded
+            ]
+        );
+        let vault = vault.read().await;
+        assert_eq!(vault.get(&denied.pubkey()).await?.prisms, AMOUNT);
+        assert_eq!(vault.get(&receiver).await?.prisms, 10);
+        drop(vault);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn deferred_transaction_waits_for_its_slot() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/validator-26";
+        const AMOUNT: u64 = 1_000_000;
+        const OPENING: u64 = FIRST_SLOT + 3;
+        const SLOT: Duration = Duration::from_millis(50);
+        let mut vault = reset_vault(VAULT).await?;
+        let payer = Keypair::generate();
+        let receiver = Keypair::generate().pubkey();
+        vault
+            .save_account(payer.pubkey(), &Wallet::new(AMOUNT), 0)
+            .await?;
+        vault.save().await?;
+        let vault = Arc::new(RwLock::new(vault));
+        let config = ValidatorConfig {
+            balance_history: true,
+            admission: vec![Arc::new(OpensAt(OPENING))],
+            slot_duration: SLOT,
+            ..ValidatorConfig::default()
+        };
+        let mut trx = Transaction::new(0);
+        trx.add(&[system::instruction::transfer(payer.pubkey(), receiver, 10)?])?;
+        trx.sign(&payer)?;
+        let mut receivers = vec![register_transaction(trx).await?];
+
+        // When
+        let start = Instant::now();
+        let (stop_control, handle) = launch_processor_with(Arc::clone(&vault), config);
+        let statuses = wait_for_statuses(&mut receivers).await;
+        let waited = start.elapsed();
+        #[expect(clippy::unwrap_used)]
+        stop_control.send(()).unwrap();
+        handle.await?;
+
+        // Then
+        assert_eq!(statuses, vec![Status::Succeeded]);
+        let vault = vault.read().await;
+        let slots = vault
+            .get_balance_history(&receiver, 0, u64::MAX, 0, 10)
+            .iter()
+            .map(|change| change.slot)
+            .collect::<Vec<_>>();
+        assert_eq!(slots, vec![OPENING]);
+        assert!(
+            waited >= SLOT * 3,
+            "the empty slots were closed too fast ({waited:?})"
+        );
+        drop(vault);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn pending_transactions_report_their_blocking_account() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/validator-29";
+        let mut vault = reset_vault(VAULT).await?;
+        let payer = Keypair::generate();
+        vault
+            .save_account(payer.pubkey(), &Wallet::new(1_000_000), 0)
+            .await?;
+        vault.save().await?;
+        let vault = Arc::new(RwLock::new(vault));
+        let mut ids = Vec::new();
+        let mut receivers = Vec::new();
+        for _ in 0..2_u8 {
+            let mut trx = Transaction::new(0);
+            trx.add(&[system::instruction::transfer(
+                payer.pubkey(),
+                Keypair::generate().pubkey(),
+                10,
+            )?])?;
+            trx.sign(&payer)?;
+            ids.extend(trx.signature().copied());
+            receivers.push(register_transaction(trx).await?);
+        }
+        // the first transaction can't load its accounts until the lock is released
+        let lock = vault.write().await;
+
+        // When
+        let (stop_control, handle) = launch_transaction_processor(Arc::clone(&vault));
+        let snapshot = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let snapshot = pending();
+                if snapshot
+                    .first()
+                    .is_some_and(|first| first.state == SchedulingState::Running)
+                {
+                    return snapshot;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await?;
+        drop(lock);
+        let statuses = wait_for_statuses(&mut receivers).await;
+        #[expect(clippy::unwrap_used)]
+        stop_control.send(()).unwrap();
+        handle.await?;
+
+        // Then
+        let states = snapshot
+            .iter()
+            .map(|summary| (summary.id, summary.payer, summary.state))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            states,
+            vec![
+                (ids[0], payer.pubkey(), SchedulingState::Running),
+                (
+                    ids[1],
+                    payer.pubkey(),
+                    SchedulingState::BlockedOn(payer.pubkey())
+                ),
+            ]
+        );
+        assert!(snapshot.iter().all(|summary| summary.fee.is_some()));
+        assert_eq!(statuses, vec![Status::Succeeded; 2]);
+        assert!(pending().is_empty());
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn latency_stages_are_recorded_in_order() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/validator-30";
+        const AMOUNT: u64 = 1_000_000;
+        let mut vault = reset_vault(VAULT).await?;
+        let payer = Keypair::generate();
+        vault
+            .save_account(payer.pubkey(), &Wallet::new(AMOUNT), 0)
+            .await?;
+        vault.save().await?;
+        let vault = Arc::new(RwLock::new(vault));
+        let config = ValidatorConfig {
+            latency_tracking: true,
+            ..ValidatorConfig::default()
+        };
+        let (stop_control, handle) = launch_processor_with(Arc::clone(&vault), config);
+        // lets the processor turn the tracking on
+        tokio::task::yield_now().await;
+        let before = latencies();
+
+        // When
+        let mut ids = Vec::new();
+        let mut receivers = Vec::new();
+        for amount in [10, 20, 2 * AMOUNT, 30] {
+            let mut trx = Transaction::new(0);
+            trx.add(&[system::instruction::transfer(
+                payer.pubkey(),
+                Keypair::generate().pubkey(),
+                amount,
+            )?])?;
+            trx.sign(&payer)?;
+            ids.extend(trx.signature().copied());
+            receivers.push(register_transaction(trx).await?);
+        }
+        let statuses = wait_for_statuses(&mut receivers).await;
+        #[expect(clippy::unwrap_used)]
+        stop_control.send(()).unwrap();
+        handle.await?;
+
+        // Then
+        assert_eq!(
+            statuses,
+            vec![
+                Status::Succeeded,
+                Status::Succeeded,
+                Status::Failed,
+                Status::Succeeded
+            ]
+        );
+        let timings = ids
+            .iter()
+            .map(|id| timings(id).ok_or("missing timings"))
+            .collect::<core::result::Result<Vec<_>, _>>()?;
+        assert!(timings.iter().all(Timings::is_monotonic));
+        for (status, timings) in statuses.iter().zip(&timings) {
+            let committed = *status == Status::Succeeded;
+            for stage in Stage::ALL {
+                let expected =
+                    committed || !matches!(stage, Stage::ExecutionEnd | Stage::Committed);
+                assert_eq!(timings.at(stage).is_some(), expected, "{stage:?}");
+            }
+        }
+        let mut expected = before;
+        for timings in &timings {
+            expected.record(timings);
+        }
+        assert_eq!(latencies(), expected);
+
+        Ok(())
+    }
+
+    fn bundle_of(payer: &Keypair, transfers: &[(Pubkey, u64)]) -> Result<Bundle> {
+        let mut transactions = Vec::new();
+        for &(receiver, amount) in transfers {
+            let mut trx = Transaction::new(0);
+            trx.add(&[system::instruction::transfer(
+                payer.pubkey(),
+                receiver,
+                amount,
+            )?])?;
+            trx.sign(payer)?;
+            transactions.push(trx);
+        }
+
+        Ok(Bundle::new(transactions)?)
+    }
+
+    #[test(tokio::test)]
+    async fn failed_bundle_undoes_every_transaction() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/validator-31";
+        const AMOUNT: u64 = 1_000_000;
+        let mut vault = reset_vault(VAULT).await?;
+        let payer = Keypair::generate();
+        let identity = Keypair::generate().pubkey();
+        let receivers = [
+            Keypair::generate().pubkey(),
+            Keypair::generate().pubkey(),
+            Keypair::generate().pubkey(),
+        ];
+        vault
+            .save_account(payer.pubkey(), &Wallet::new(AMOUNT), 0)
+            .await?;
+        vault.save().await?;
+        let root = vault.state_root();
+        let vault = Arc::new(RwLock::new(vault));
+        let config = ValidatorConfig {
+            balance_history: true,
+            identity: Some(identity),
+            ..ValidatorConfig::default()
+        };
+        let bundle = bundle_of(
+            &payer,
+            &[
+                (receivers[0], 10),
+                (receivers[1], 20),
+                (receivers[2], 2 * AMOUNT),
+            ],
+        )?;
+
+        // When
+        let (stop_control, handle) = launch_processor_with(Arc::clone(&vault), config);
+        let statuses = wait_for_statuses(&mut [register_bundle(bundle).await?]).await;
+        #[expect(clippy::unwrap_used)]
+        stop_control.send(()).unwrap();
+        handle.await?;
+
+        // Then
+        assert_eq!(statuses, vec![BundleStatus::Failed { index: 2 }]);
+        let vault = vault.read().await;
+        assert_eq!(vault.get(&payer.pubkey()).await?.prisms, AMOUNT);
+        for key in receivers.iter().chain([&identity]) {
+            assert_eq!(vault.get(key).await?.prisms, 0);
+            assert!(vault
+                .get_balance_history(key, 0, u64::MAX, 0, 10)
+                .is_empty());
+        }
+        assert_eq!(vault.burned(), 0);
+        assert_eq!(vault.state_root(), root);
+        drop(vault);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn bundles_run_in_a_single_block() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/validator-32";
+        const AMOUNT: u64 = 1_000_000;
+        let mut vault = reset_vault(VAULT).await?;
+        let payer = Keypair::generate();
+        let receivers = [Keypair::generate().pubkey(), Keypair::generate().pubkey()];
+        vault
+            .save_account(payer.pubkey(), &Wallet::new(AMOUNT), 0)
+            .await?;
+        vault.save().await?;
+        let vault = Arc::new(RwLock::new(vault));
+        let config = ValidatorConfig {
+            batch_size: 2,
+            balance_history: true,
+            ..ValidatorConfig::default()
+        };
+        let oversized = bundle_of(
+            &payer,
+            &[(receivers[0], 1), (receivers[0], 2), (receivers[0], 3)],
+        )?;
+        let bundle = bundle_of(&payer, &[(receivers[0], 10), (receivers[1], 20)])?;
+        let mut trx = Transaction::new(0);
+        trx.add(&[system::instruction::transfer(
+            payer.pubkey(),
+            receivers[1],
+            30,
+        )?])?;
+        trx.sign(&payer)?;
+
+        // When
+        let (stop_control, handle) = launch_processor_with(Arc::clone(&vault), config);
+        let bundle_statuses = wait_for_statuses(&mut [
+            register_bundle(oversized).await?,
+            register_bundle(bundle).await?,
+        ])
+        .await;
+        let statuses = wait_for_statuses(&mut [register_transaction(trx).await?]).await;
+        #[expect(clippy::unwrap_used)]
+        stop_control.send(()).unwrap();
+        handle.await?;
+
+        // Then
+        assert_eq!(
+            bundle_statuses,
+            vec![
+                BundleStatus::Rejected("the bundle can never fit in a block"),
+                BundleStatus::Succeeded
+            ]
+        );
+        assert_eq!(statuses, vec![Status::Succeeded]);
+        let vault = vault.read().await;
+        let first = vault.get_balance_history(&receivers[0], 0, u64::MAX, 0, 10);
+        let second = vault.get_balance_history(&receivers[1], 0, u64::MAX, 0, 10);
+        assert_eq!(first.len(), 1);
+        assert_eq!(second.len(), 2);
+        assert_eq!(first[0].slot, second[0].slot);
+        assert!(second[1].slot > second[0].slot);
+        assert_eq!(vault.get(&receivers[1]).await?.prisms, 50);
+        drop(vault);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn custom_program_errors_reach_the_status() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/validator-33";
+        const CODE: u32 = 42;
+        let mut vault = reset_vault(VAULT).await?;
+        let payer = Keypair::generate();
+        vault
+            .save_account(payer.pubkey(), &Wallet::new(1_000_000), 0)
+            .await?;
+        vault.save().await?;
+        let vault = Arc::new(RwLock::new(vault));
+        let mut trx = Transaction::new(0);
+        trx.add(&[
+            memo::instruction::memo("before", &[payer.pubkey()])?,
+            testing_dummy::instruction::fail(payer.pubkey(), CODE)?,
+        ])?;
+        trx.sign(&payer)?;
+
+        // When
+        let (stop_control, handle) = launch_transaction_processor(Arc::clone(&vault));
+        let statuses = wait_for_statuses(&mut [register_transaction(trx).await?]).await;
+        #[expect(clippy::unwrap_used)]
+        stop_control.send(()).unwrap();
+        handle.await?;
+
+        // Then
+        assert_eq!(
+            statuses,
+            vec![Status::CustomError {
+                instruction: 1,
+                code: CODE
+            }]
+        );
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn transactions_signed_for_another_chain_are_refused() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/validator-34";
+        let payer = Keypair::generate();
+        let receiver = Keypair::generate().pubkey();
+        let testnet = Genesis::from_allocations(
+            &format!("{},1_000_000", payer.pubkey()),
+            &GenesisConfig::default(),
+        )?;
+        let mainnet = Genesis::from_allocations(
+            &format!("{},2_000_000", payer.pubkey()),
+            &GenesisConfig::default(),
+        )?;
+        let mut vault = reset_vault(VAULT).await?;
+        vault
+            .save_account(payer.pubkey(), &Wallet::new(1_000_000), 0)
+            .await?;
+        let vault = RwLock::new(vault);
+        let config = ValidatorConfig {
+            chain: Some(mainnet.hash()),
+            ..ValidatorConfig::default()
+        };
+        let transfer = |chain: Option<BlockHash>| -> Result<Transaction> {
+            let mut trx = Transaction::new(0);
+            if let Some(chain) = chain {
+                trx = trx.with_chain(chain)?;
+            }
+            trx.add(&[system::instruction::transfer(payer.pubkey(), receiver, 10)?])?;
+            trx.sign(&payer)?;
+            Ok(trx)
+        };
+
+        // When
+        let replayed = execute_admitted(&vault, &config, transfer(Some(testnet.hash()))?, 1).await;
+        let unbound = execute_admitted(&vault, &config, transfer(None)?, 1).await;
+        let bound = execute_admitted(&vault, &config, transfer(Some(mainnet.hash()))?, 1).await;
+
+        // Then
+        assert_matches!(
+            replayed,
+            Err(Error::WrongChain { expected, got: Some(got) })
+                if *expected == mainnet.hash() && *got == testnet.hash()
+        );
+        assert_matches!(unbound, Err(Error::WrongChain { got: None, .. }));
+        assert_matches!(bound, Ok(()));
+        assert_eq!(vault.read().await.get(&receiver).await?.prisms, 10);
+
+        Ok(())
+    }
+
+    /// Rewinds the vault to a checkpoint, optionally tampers with an account, and
+    /// replays the transactions after it, returning the state root reached.
+    async fn replay_from(
+        vault: &RwLock<Vault>,
+        start: crate::io::Checkpoint,
+        applied: u64,
+        tamper: Option<Pubkey>,
+        transactions: &[Transaction],
+    ) -> Result<BlockHash> {
+        let mut guard = vault.write().await;
+        guard.rollback(start, applied, &[]).await?;
+        if let Some(key) = tamper {
+            let account = guard.get(&key).await?;
+            guard
+                .save_account(key, &Wallet::new(account.prisms + 1), applied)
+                .await?;
+        }
+        guard.set_bulk_progress(applied);
+        drop(guard);
+        let config = ValidatorConfig {
+            batch_size: 1,
+            ..ValidatorConfig::default()
+        };
+        bulk_apply(vault, &config, transactions.to_vec()).await?;
+
+        Ok(BlockHash::from_bytes(&vault.read().await.state_root())?)
+    }
+
+    #[test(tokio::test)]
+    async fn tampered_history_is_caught_between_checkpoints() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/validator-35";
+        let auditor = Arc::new(Keypair::generate());
+        let payer = Keypair::generate();
+        let receiver = Keypair::generate().pubkey();
+        let mut vault = reset_vault(VAULT).await?;
+        vault
+            .save_account(payer.pubkey(), &Wallet::new(1_000_000), 0)
+            .await?;
+        let vault = RwLock::new(vault);
+        let config = ValidatorConfig {
+            batch_size: 1,
+            audit: Some(AuditConfig {
+                interval: 2,
+                signer: Arc::clone(&auditor),
+            }),
+            ..ValidatorConfig::default()
+        };
+        let transactions = (1..=4)
+            .map(|amount| {
+                let mut trx = Transaction::new(0);
+                trx.add(&[system::instruction::transfer(
+                    payer.pubkey(),
+                    receiver,
+                    amount,
+                )?])?;
+                trx.sign(&payer)?;
+                Ok(trx)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        bulk_apply(&vault, &config, transactions[..2].to_vec()).await?;
+        let start = vault
+            .read()
+            .await
+            .checkpoint(&[payer.pubkey(), receiver])
+            .await?;
+        bulk_apply(&vault, &config, transactions.clone()).await?;
+        let trail = vault.read().await.audit_trail().to_vec();
+
+        // When
+        let honest = replay_from(&vault, start.clone(), 2, None, &transactions).await?;
+        let tampered = replay_from(&vault, start, 2, Some(receiver), &transactions).await?;
+
+        // Then
+        verify_audit_trail(&trail, &auditor.pubkey())?;
+        assert_eq!(
+            trail
+                .iter()
+                .map(|checkpoint| checkpoint.slot)
+                .collect::<Vec<_>>(),
+            vec![2, 4]
+        );
+        assert_eq!(trail[1].supply, 1_000_000 - 4 * TRANSACTION_FEE);
+        assert_eq!(honest, trail[1].state_root);
+        assert_ne!(tampered, trail[1].state_root);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn reloaded_batch_size_applies_to_queued_transactions() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/validator-36";
+        let mut vault = reset_vault(VAULT).await?;
+        let payer = Keypair::generate();
+        let receiver = Keypair::generate().pubkey();
+        vault
+            .save_account(payer.pubkey(), &Wallet::new(1_000_000), 0)
+            .await?;
+        vault.save().await?;
+        let vault = Arc::new(RwLock::new(vault));
+        let config = ValidatorConfig {
+            batch_size: 1,
+            balance_history: true,
+            ..ValidatorConfig::default()
+        };
+        let transfer = |amount| -> Result<Transaction> {
+            let mut trx = Transaction::new(0);
+            trx.add(&[system::instruction::transfer(
+                payer.pubkey(),
+                receiver,
+                amount,
+            )?])?;
+            trx.sign(&payer)?;
+            Ok(trx)
+        };
+        let (validator, handle) = start_processor(
+            Arc::clone(&vault),
+            PipelineBuilder::new(config.clone()).build(),
+        );
+        let mut before = Vec::new();
+        for amount in 1..=3 {
+            before.push(register_transaction(transfer(amount)?).await?);
+        }
+        let before = wait_for_statuses(&mut before).await;
+
+        // When
+        // unconstrained: the processor must not run before the reload is requested
+        let mut queued = tokio::task::unconstrained(async {
+            let mut queued = Vec::new();
+            for amount in 4..=6 {
+                queued.push(register_transaction(transfer(amount)?).await?);
+            }
+            validator
+                .reload_config(ValidatorConfig {
+                    batch_size: 3,
+                    ..config.clone()
+                })
+                .await?;
+            Ok::<_, Box<dyn core::error::Error>>(queued)
+        })
+        .await?;
+        let refused = validator
+            .reload_config(ValidatorConfig {
+                batch_size: 3,
+                queue_policy: QueuePolicy::FairByPayer,
+                chain: Some(BlockHash::default()),
+                ..config
+            })
+            .await;
+        let after = wait_for_statuses(&mut queued).await;
+        validator.stop();
+        handle.await?;
+
+        // Then
+        assert_matches!(
+            refused,
+            Err(Error::ImmutableConfig { fields }) if fields == ["chain", "queue_policy"]
+        );
+        assert_eq!(before, vec![Status::Succeeded; 3]);
+        assert_eq!(after, vec![Status::Succeeded; 3]);
+        let vault = vault.read().await;
+        let slots = vault
+            .get_balance_history(&receiver, 0, u64::MAX, 0, 10)
+            .iter()
+            .map(|change| change.slot)
+            .collect::<Vec<_>>();
+        drop(vault);
+        assert_eq!(slots.len(), 6);
+        assert!(slots[0] < slots[1] && slots[1] < slots[2]);
+        assert!(slots[3] > slots[2]);
+        assert_eq!(slots[3..], [slots[3]; 3]);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn read_only_signers_are_charged_but_never_modified() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/validator-37";
+        const AMOUNT: u64 = 1_000_000;
+        let mut vault = reset_vault(VAULT).await?;
+        let payer = Keypair::generate();
+        let witness = Keypair::generate();
+        let receiver = Keypair::generate().pubkey();
+        let witnessed = Wallet {
+            prisms: 500,
+            data: vec![1, 2, 3].into(),
+        };
+        vault
+            .save_account(payer.pubkey(), &Wallet::new(AMOUNT), 0)
+            .await?;
+        vault.save_account(witness.pubkey(), &witnessed, 0).await?;
+        vault.save().await?;
+        let vault = Arc::new(RwLock::new(vault));
+        let signed = |instruction: Instruction| -> Result<Transaction> {
+            let mut trx = Transaction::new(0);
+            trx.add(&[
+                system::instruction::transfer(payer.pubkey(), receiver, 10)?,
+                instruction,
+            ])?;
+            trx.sign(&payer)?;
+            trx.sign(&witness)?;
+            Ok(trx)
+        };
+        let memo = signed(memo::instruction::memo("witnessed", &[witness.pubkey()])?)?;
+        let tamper = signed(testing_dummy::instruction::tamper(vec![
+            AccountMeta::signing(witness.pubkey(), Writable::No)?,
+        ]))?;
+
+        // When
+        let (stop_control, handle) = launch_transaction_processor(Arc::clone(&vault));
+        let statuses = wait_for_statuses(&mut [
+            register_transaction(memo).await?,
+            register_transaction(tamper).await?,
+        ])
+        .await;
+        #[expect(clippy::unwrap_used)]
+        stop_control.send(()).unwrap();
+        handle.await?;
+
+        // Then
+        assert_eq!(statuses, vec![Status::Succeeded, Status::Failed]);
+        let vault = vault.read().await;
+        assert_eq!(
+            vault.get(&payer.pubkey()).await?.prisms,
+            AMOUNT - 10 - 2 * TRANSACTION_FEE
+        );
+        assert_eq!(vault.get(&witness.pubkey()).await?, witnessed);
+        drop(vault);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn programs_see_the_slot_of_their_block() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/validator-39";
+        let mut vault = reset_vault(VAULT).await?;
+        let payers = (0..4_u8).map(|_| Keypair::generate()).collect::<Vec<_>>();
+        for payer in &payers {
+            vault
+                .save_account(payer.pubkey(), &Wallet::new(1_000_000), 0)
+                .await?;
+        }
+        vault.save().await?;
+        let vault = Arc::new(RwLock::new(vault));
+        let config = ValidatorConfig {
+            batch_size: 3,
+            ..ValidatorConfig::default()
+        };
+        let record = |payer: &Keypair| -> Result<Transaction> {
+            let mut trx = Transaction::new(0);
+            trx.add(&[testing_dummy::instruction::record_clock(payer.pubkey())?])?;
+            trx.sign(payer)?;
+            Ok(trx)
+        };
+        let mut first_block = Vec::new();
+        for payer in &payers[..3] {
+            first_block.push(register_transaction(record(payer)?).await?);
+        }
+
+        // When
+        let (stop_control, handle) = launch_processor_with(Arc::clone(&vault), config);
+        let mut statuses = wait_for_statuses(&mut first_block).await;
+        statuses.extend(
+            wait_for_statuses(&mut [register_transaction(record(&payers[3])?).await?]).await,
+        );
+        #[expect(clippy::unwrap_used)]
+        stop_control.send(()).unwrap();
+        handle.await?;
+
+        // Then
+        assert_eq!(statuses, vec![Status::Succeeded; 4]);
+        let vault = vault.read().await;
+        let mut seen = Vec::new();
+        for payer in &payers {
+            let data = vault.get(&payer.pubkey()).await?.data;
+            seen.push(borsh::from_slice::<(Slot, Epoch)>(&data)?);
+        }
+        drop(vault);
+        let first = seen[0];
+        assert_eq!(seen[..3], [first; 3]);
+        assert_eq!(seen[3].0.get(), first.0.get() + 1);
+        assert_eq!(seen[3].1, first.1);
+
+        Ok(())
+    }
+
+    fn transfers(payer: &Keypair, count: u8) -> Result<Vec<Transaction>> {
+        (0..count)
+            .map(|_| {
+                let mut trx = Transaction::new(0);
+                trx.add(&[system::instruction::transfer(
+                    payer.pubkey(),
+                    Keypair::generate().pubkey(),
+                    10,
+                )?])?;
+                trx.sign(payer)?;
+                Ok(trx)
+            })
+            .collect()
+    }
+
+    #[test(tokio::test)]
+    async fn registrations_are_shed_at_the_queue_ceiling() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/validator-40";
+        let mut vault = reset_vault(VAULT).await?;
+        let payer = Keypair::generate();
+        vault
+            .save_account(payer.pubkey(), &Wallet::new(1_000_000), 0)
+            .await?;
+        vault.save().await?;
+        let vault = Arc::new(RwLock::new(vault));
+        let transfers = transfers(&payer, 4)?;
+        let size = estimate_size(transfers[0].message());
+        let config = ValidatorConfig {
+            memory: MemoryBudget {
+                queue: 3 * size,
+                ..MemoryBudget::default()
+            },
+            ..ValidatorConfig::default()
+        };
+        TRANSACTION_QUEUE.set_memory_budget(config.memory);
+        let mut queued = Vec::new();
+        for trx in &transfers[..3] {
+            queued.push(register_transaction(trx.clone()).await?);
+        }
+
+        // When
+        let shed = register_transaction(transfers[3].clone()).await;
+        let full = memory_usage();
+        let (stop_control, handle) = launch_processor_with(Arc::clone(&vault), config);
+        let statuses = wait_for_statuses(&mut queued).await;
+        let retried =
+            wait_for_statuses(&mut [register_transaction(transfers[3].clone()).await?]).await;
+        #[expect(clippy::unwrap_used)]
+        stop_control.send(()).unwrap();
+        handle.await?;
+
+        // Then
+        assert_matches!(
+            shed,
+            Err(Error::MemoryPressure {
+                component: MemoryComponent::Queue,
+                limit
+            }) if limit == 3 * size
+        );
+        assert_eq!(full.queue, 3 * size);
+        assert_eq!(statuses, vec![Status::Succeeded; 3]);
+        assert_eq!(retried, vec![Status::Succeeded]);
+        let drained = memory_usage();
+        assert_eq!((drained.queue, drained.in_flight), (0, 0));
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn account_cache_shrinks_under_the_global_cap() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/validator-41";
+        let mut vault = reset_vault(VAULT).await?;
+        let payer = Keypair::generate();
+        // the first accounts are the least recently used ones
+        let mut keys = (0..32_u8)
+            .map(|_| Keypair::generate().pubkey())
+            .collect::<Vec<_>>();
+        keys.push(payer.pubkey());
+        for key in &keys {
+            vault.save_account(*key, &Wallet::new(1_000_000), 0).await?;
+        }
+        vault.save().await?;
+        vault.preload(&keys).await?;
+        let cached = vault.cache_size();
+        let vault = Arc::new(RwLock::new(vault));
+        let transfers = transfers(&payer, 3)?;
+        let config = ValidatorConfig {
+            batch_size: 1,
+            memory: MemoryBudget {
+                account_cache: 2 * cached,
+                global: cached,
+                headroom: 0,
+                ..MemoryBudget::default()
+            },
+            ..ValidatorConfig::default()
+        };
+        TRANSACTION_QUEUE.set_memory_budget(config.memory);
+        let mut queued = Vec::new();
+        for trx in transfers {
+            queued.push(register_transaction(trx).await?);
+        }
+
+        // When
+        let (stop_control, handle) = launch_processor_with(Arc::clone(&vault), config);
+        let statuses = wait_for_statuses(&mut queued).await;
+        #[expect(clippy::unwrap_used)]
+        stop_control.send(()).unwrap();
+        handle.await?;
+        let vault = vault.read().await;
+        let before = vault.cache_stats();
+        vault.get(&keys[0]).await?;
+        let after = vault.cache_stats();
+        drop(vault);
+
+        // Then
+        assert_eq!(statuses, vec![Status::Succeeded; 3]);
+        assert_eq!(after.misses, before.misses + 1);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn bulk_apply_is_refused_once_intake_opened() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/validator-27";
+        let vault = RwLock::new(reset_vault(VAULT).await?);
+        register_transaction(create_signed_transaction()?).await?;
+
+        // When
+        let res = bulk_apply(&vault, &ValidatorConfig::default(), Vec::new()).await;
+
+        // Then
+        assert_matches!(res, Err(Error::IntakeAlreadyOpen));
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn interrupted_bulk_apply_resumes() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/validator-28";
+        const AMOUNT: u64 = 1_000_000;
+        const MIS_SIGNED: usize = 4;
+        let mut vault = reset_vault(VAULT).await?;
+        let payer = Keypair::generate();
+        let receiver = Keypair::generate().pubkey();
+        vault
+            .save_account(payer.pubkey(), &Wallet::new(AMOUNT), 0)
+            .await?;
+        let vault = RwLock::new(vault);
+        let config = ValidatorConfig {
+            batch_size: 3,
+            ..ValidatorConfig::default()
+        };
+        let mut transactions = Vec::new();
+        for amount in 1..=10 {
+            let mut trx = Transaction::new(0);
+            trx.add(&[system::instruction::transfer(
+                payer.pubkey(),
+                receiver,
+                amount,
+            )?])?;
+            trx.sign(&payer)?;
+            transactions.push(trx);
+        }
+        // changes the slot of the message, right after the only signature
+        let mut bytes = borsh::to_vec(&transactions[MIS_SIGNED])?;
+        bytes[4 + 64] ^= 1;
+        transactions[MIS_SIGNED] = borsh::from_slice(&bytes)?;
+
+        // When
+        let interrupted =
+            bulk_apply(&vault, &config, transactions.clone().into_iter().take(5)).await?;
+        let resumed = bulk_apply(&vault, &config, transactions).await?;
+
+        // Then
+        assert_eq!((interrupted.resumed_from, interrupted.applied), (0, 4));
+        assert_matches!(
+            interrupted.failures.as_slice(),
+            [(4, Error::InvalidTransactionSignatures)]
+        );
+        assert_eq!((resumed.resumed_from, resumed.applied), (5, 5));
+        assert!(resumed.failures.is_empty());
+        let vault = vault.read().await;
+        assert_eq!(vault.get(&receiver).await?.prisms, 55 - 5);
+        assert_eq!(vault.bulk_progress(), 10);
+        drop(vault);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn validators_are_rewarded_at_the_end_of_the_epoch() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/validator-42";
+        let mut vault = reset_vault(VAULT).await?;
+        vault.enable_balance_history().await;
+        let signer = Arc::new(Keypair::generate());
+        let identity = Keypair::generate().pubkey();
+        let busy = Keypair::generate().pubkey();
+        let absent = Keypair::generate().pubkey();
+        // the identity produces 3 blocks below, for 6 points against 14
+        for _ in 0..6_u8 {
+            vault.epoch_activity_mut().record_slot(busy, true);
+        }
+        for _ in 0..2_u8 {
+            vault.epoch_activity_mut().record_vote(busy);
+        }
+        for _ in 0..4_u8 {
+            vault.epoch_activity_mut().record_slot(absent, false);
+        }
+        let supply = vault.supply();
+        let vault = RwLock::new(vault);
+        let config = ValidatorConfig {
+            identity: Some(identity),
+            rewards: Some(RewardsConfig {
+                inflation: 1_001,
+                block_points: 2,
+                vote_points: 1,
+                signer: Arc::clone(&signer),
+            }),
+            ..ValidatorConfig::default()
+        };
+        let mut ledger = Block::genesis();
+        let last = SLOTS_PER_EPOCH - 1;
+
+        // When
+        for slot in [10, 11] {
+            close_slot(&vault, &config, &mut ledger, slot, SystemClock.wall_time()).await?;
+        }
+        let before = vault.read().await.get_epoch_rewards(0).cloned();
+        close_slot(&vault, &config, &mut ledger, last, SystemClock.wall_time()).await?;
+        close_slot(
+            &vault,
+            &config,
+            &mut ledger,
+            last + 1,
+            SystemClock.wall_time(),
+        )
+        .await?;
+
+        // Then
+        let vault = vault.read().await;
+        let rewards = vault.get_epoch_rewards(0).ok_or("no rewards")?;
+        assert_eq!(before, None);
+        assert_eq!(rewards.reward_of(&identity), 300);
+        assert_eq!(rewards.reward_of(&busy), 700);
+        assert_eq!(rewards.reward_of(&absent), 0);
+        assert_eq!(rewards.undistributed, 1);
+        assert_eq!(rewards.transactions.len(), 2);
+        assert_eq!(vault.get(&identity).await?.prisms, 300);
+        assert_eq!(vault.get(&busy).await?.prisms, 700);
+        assert_eq!(vault.get(&absent).await?.prisms, 0);
+        assert_eq!(vault.get(&signer.pubkey()).await?.prisms, 0);
+        assert_eq!(vault.supply(), supply + 1_000);
+        for key in [identity, busy] {
+            let history = vault.get_balance_history(&key, 0, last, 0, 10);
+            assert_eq!(history.len(), 1);
+            assert!(rewards.transactions.contains(&history[0].signature));
+        }
+        assert_eq!(vault.epoch_activity().activity(&identity).produced, 1);
+        assert_eq!(vault.epoch_activity().activity(&busy).produced, 0);
+        drop(vault);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn stakes_are_snapshotted_at_the_start_of_each_epoch() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/validator-44";
+        let mut vault = reset_vault(VAULT).await?;
+        let identity = Keypair::generate().pubkey();
+        let validator = Keypair::generate().pubkey();
+        let staker = Keypair::generate().pubkey();
+        let mut stake = Stake::new(staker, 0, 5_000);
+        stake.delegate(validator, 0);
+        let mut account = Wallet::new(5_000);
+        account.data = borsh::to_vec(&stake)?.into();
+        vault
+            .save_account(stake::stake_address(&staker, 0)?, &account, 0)
+            .await?;
+        // a copy of the stake at another address wasn't written by the stake program
+        vault
+            .save_account(Keypair::generate().pubkey(), &account, 0)
+            .await?;
+        let vault = RwLock::new(vault);
+        let config = ValidatorConfig {
+            identity: Some(identity),
+            rewards: Some(RewardsConfig {
+                inflation: 0,
+                block_points: 1,
+                vote_points: 1,
+                signer: Arc::new(Keypair::generate()),
+            }),
+            ..ValidatorConfig::default()
+        };
+        let mut ledger = Block::genesis();
+
+        // When
+        close_slot(
+            &vault,
+            &config,
+            &mut ledger,
+            SLOTS_PER_EPOCH - 1,
+            SystemClock.wall_time(),
+        )
+        .await?;
+        close_slot(
+            &vault,
+            &config,
+            &mut ledger,
+            SLOTS_PER_EPOCH,
+            SystemClock.wall_time(),
+        )
+        .await?;
+
+        // Then
+        let vault = vault.read().await;
+        let schedule = vault.leader_schedule().ok_or("no leader schedule")?;
+        assert_eq!(schedule.epoch(), Epoch::new(1));
+        assert_eq!(schedule.validators(), 1);
+        assert_eq!(schedule.stake_of(&validator), 5_000);
+        assert_eq!(vault.epoch_activity().activity(&validator).missed, 1);
+        assert_eq!(vault.epoch_activity().activity(&identity).produced, 1);
+        drop(vault);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn failed_rewards_mint_nothing() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/validator-43";
+        const SIGNER_PRISMS: u64 = u64::MAX - 500;
+        let mut vault = reset_vault(VAULT).await?;
+        let signer = Arc::new(Keypair::generate());
+        let small = Keypair::generate().pubkey();
+        let large = Keypair::generate().pubkey();
+        vault
+            .save_account(signer.pubkey(), &Wallet::new(SIGNER_PRISMS), 0)
+            .await?;
+        for _ in 0..3_u8 {
+            vault.epoch_activity_mut().record_slot(small, true);
+        }
+        for _ in 0..7_u8 {
+            vault.epoch_activity_mut().record_slot(large, true);
+        }
+        let supply = vault.supply();
+        let vault = RwLock::new(vault);
+        let config = ValidatorConfig {
+            rewards: Some(RewardsConfig {
+                inflation: 1_000,
+                block_points: 1,
+                vote_points: 1,
+                signer: Arc::clone(&signer),
+            }),
+            ..ValidatorConfig::default()
+        };
+        let mut ledger = Block::genesis();
+
+        // When
+        close_slot(
+            &vault,
+            &config,
+            &mut ledger,
+            SLOTS_PER_EPOCH - 1,
+            SystemClock.wall_time(),
+        )
+        .await?;
+
+        // Then
+        let vault = vault.read().await;
+        let rewards = vault.get_epoch_rewards(0).ok_or("no rewards")?;
+        assert_eq!(rewards.transactions.len(), 1);
+        assert_eq!(rewards.undistributed, 700);
+        assert_eq!(vault.get(&small).await?.prisms, 300);
+        assert_eq!(vault.get(&large).await?.prisms, 0);
+        assert_eq!(vault.get(&signer.pubkey()).await?.prisms, SIGNER_PRISMS);
+        assert_eq!(vault.supply(), supply + 300);
+        drop(vault);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn closed_slots_record_their_block_time() -> TestResult {
+        // Given
+        let vault = RwLock::new(reset_vault("/tmp/bifrost/validator-47").await?);
+        let config = ValidatorConfig::default();
+        let mut ledger = Block::genesis();
+        let now = SystemClock.wall_time().div_euclid(1_000);
+
+        // When
+        close_slot(&vault, &config, &mut ledger, 1, SystemClock.wall_time()).await?;
+        close_slot(&vault, &config, &mut ledger, 2, SystemClock.wall_time()).await?;
+
+        // Then
+        let vault = vault.read().await;
+        let first = vault.get_block_time(1).ok_or("no block time")?;
+        let second = vault.get_block_time(2).ok_or("no block time")?;
+        assert!(first >= now);
+        assert!(second >= first);
+        assert_eq!(vault.get_block_time(3), None);
+        drop(vault);
+
+        Ok(())
+    }
 }
@@ -0,0 +1,339 @@
+// File: src/validator/rewards.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 20:22:52
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Rewards of the validators, at the end of each epoch.
+//!
+//! During an epoch, the validator counts the blocks each leader produced (or missed)
+//! and the votes each validator landed. At the end of the epoch, a pool of prisms is
+//! minted and shared between the validators in proportion of the points they earned.
+//! The computation only uses integers and iterates over the validators in the order
+//! of their keys, so every replica gets the same result: the prisms left by the
+//! rounding aren't minted.
+//!
+//! The pool is minted on the account of the rewards' signer, then paid with transfers
+//! it signs, executed like any other transaction: the balance history of every
+//! account explains its rewards.
+
+use std::{collections::BTreeMap, fmt::Debug, sync::Arc};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use tracing::{debug, instrument};
+
+use crate::{
+    crypto::{Keypair, Pubkey, Signature},
+    program::{system, Epoch, Slot},
+    transaction::Transaction,
+};
+
+use super::Result;
+
+/// How much the validators are rewarded at the end of each epoch, and the key paying them.
+#[derive(Clone)]
+pub struct RewardsConfig {
+    /// The number of prisms minted at the end of each epoch, for the validators.
+    pub inflation: u64,
+    /// The points earned for each block produced.
+    pub block_points: u64,
+    /// The points earned for each vote landed.
+    pub vote_points: u64,
+    /// The key signing the reward transactions.
+    pub signer: Arc<Keypair>,
+}
+
+impl Debug for RewardsConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RewardsConfig")
+            .field("inflation", &self.inflation)
+            .field("block_points", &self.block_points)
+            .field("vote_points", &self.vote_points)
+            .field("signer", &self.signer.pubkey())
+            .finish()
+    }
+}
+
+/// What a validator did during an epoch.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct ValidatorActivity {
+    /// The number of blocks it produced as the leader of a slot.
+    pub produced: u64,
+    /// The number of slots it was the leader of, but didn't produce a block for.
+    pub missed: u64,
+    /// The number of its votes that landed.
+    pub votes: u64,
+}
+
+/// What the validators did during an epoch, by identity.
+#[derive(Clone, Debug, Default, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct EpochActivity {
+    /// The activity of each validator.
+    validators: BTreeMap<Pubkey, ValidatorActivity>,
+}
+
+impl EpochActivity {
+    /// Records a slot of the epoch.
+    ///
+    /// # Parameters
+    /// * `leader` - The identity of the leader of the slot,
+    /// * `produced` - Whether it produced the block of the slot.
+    pub fn record_slot(&mut self, leader: Pubkey, produced: bool) {
+        let activity = self.validators.entry(leader).or_default();
+        if produced {
+            activity.produced = activity.produced.saturating_add(1);
+        } else {
+            activity.missed = activity.missed.saturating_add(1);
+        }
+    }
+
+    /// Records a vote that landed.
+    ///
+    /// # Parameters
+    /// * `voter` - The identity of the validator that voted.
+    pub fn record_vote(&mut self, voter: Pubkey) {
+        let activity = self.validators.entry(voter).or_default();
+        activity.votes = activity.votes.saturating_add(1);
+    }
+
+    /// Get the activity of a validator.
+    ///
+    /// # Parameters
+    /// * `validator` - The identity of the validator.
+    #[must_use]
+    pub fn activity(&self, validator: &Pubkey) -> ValidatorActivity {
+        self.validators.get(validator).copied().unwrap_or_default()
+    }
+}
+
+/// The reward of a validator for an epoch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct ValidatorReward {
+    /// The identity of the validator.
+    pub validator: Pubkey,
+    /// What it did during the epoch.
+    pub activity: ValidatorActivity,
+    /// The number of prisms it earned.
+    pub prisms: u64,
+}
+
+/// The rewards paid at the end of an epoch.
+#[derive(Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct EpochRewards {
+    /// The epoch rewarded.
+    pub epoch: Epoch,
+    /// The number of prisms to share.
+    pub pool: u64,
+    /// The reward of each validator active during the epoch, in the order of their identities.
+    pub rewards: Vec<ValidatorReward>,
+    /// The prisms of the pool left by the rounding, by an epoch without activity or
+    /// by the rewards that couldn't be paid, which aren't minted.
+    pub undistributed: u64,
+    /// The signatures of the transactions paying the rewards.
+    pub transactions: Vec<Signature>,
+}
+
+impl EpochRewards {
+    /// Shares the pool of an epoch between its validators.
+    ///
+    /// # Parameters
+    /// * `epoch` - The epoch rewarded,
+    /// * `config` - The size of the pool and the points earned by each action,
+    /// * `activity` - What the validators did during the epoch.
+    #[instrument(skip(config, activity))]
+    #[must_use]
+    pub fn compute(epoch: Epoch, config: &RewardsConfig, activity: &EpochActivity) -> Self {
+        debug!("computing the rewards of the epoch");
+        let points = |validator: &ValidatorActivity| {
+            u128::from(validator.produced) * u128::from(config.block_points)
+                + u128::from(validator.votes) * u128::from(config.vote_points)
+        };
+        let total = activity.validators.values().map(points).sum::<u128>();
+        let rewards = activity
+            .validators
+            .iter()
+            .map(|(validator, done)| {
+                let prisms = u128::from(config.inflation)
+                    .saturating_mul(points(done))
+                    .checked_div(total)
+                    .unwrap_or_default();
+                ValidatorReward {
+                    validator: *validator,
+                    activity: *done,
+                    prisms: u64::try_from(prisms).unwrap_or_default(),
+                }
+            })
+            .collect::<Vec<_>>();
+        let distributed = rewards.iter().map(|reward| reward.prisms).sum::<u64>();
+
+        Self {
+            epoch,
+            pool: config.inflation,
+            rewards,
+            undistributed: config.inflation.saturating_sub(distributed),
+            transactions: Vec::new(),
+        }
+    }
+
+    /// Get the number of prisms paid to the validators.
+    #[must_use]
+    pub const fn distributed(&self) -> u64 {
+        self.pool.saturating_sub(self.undistributed)
+    }
+
+    /// Get the reward of a validator.
+    ///
+    /// # Parameters
+    /// * `validator` - The identity of the validator.
+    ///
+    /// # Returns
+    /// The number of prisms it earned (zero if it wasn't active).
+    #[must_use]
+    pub fn reward_of(&self, validator: &Pubkey) -> u64 {
+        self.rewards
+            .iter()
+            .find(|reward| reward.validator == *validator)
+            .map_or(0, |reward| reward.prisms)
+    }
+
+    /// Builds the transactions paying the rewards, from the account of the signer.
+    ///
+    /// Validators without reward don't get a transaction.
+    ///
+    /// # Parameters
+    /// * `signer` - The key paying the rewards,
+    /// * `slot` - The slot at which the rewards are paid.
+    ///
+    /// # Errors
+    /// If a validator's identity isn't on the `ed25519` curve.
+    pub fn transactions<S>(&self, signer: &Keypair, slot: S) -> Result<Vec<Transaction>>
+    where
+        S: Into<Slot> + Copy,
+    {
+        self.rewards
+            .iter()
+            .filter(|reward| reward.prisms > 0)
+            .map(|reward| {
+                let mut trx = Transaction::new(slot);
+                trx.add(&[system::instruction::transfer(
+                    signer.pubkey(),
+                    reward.validator,
+                    reward.prisms,
+                )?])?;
+                trx.sign(signer)?;
+                Ok(trx)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+
+    fn config(inflation: u64) -> RewardsConfig {
+        RewardsConfig {
+            inflation,
+            block_points: 3,
+            vote_points: 1,
+            signer: Arc::new(Keypair::generate()),
+        }
+    }
+
+    #[test]
+    fn rewards_follow_the_points_earned() {
+        // Given
+        let validators = [
+            Keypair::generate().pubkey(),
+            Keypair::generate().pubkey(),
+            Keypair::generate().pubkey(),
+        ];
+        let mut activity = EpochActivity::default();
+        let mut reversed = EpochActivity::default();
+        // 6 blocks and 2 votes, 2 blocks and 4 votes, 3 slots missed
+        let slots = [
+            (0, true),
+            (0, true),
+            (1, true),
+            (2, false),
+            (0, true),
+            (2, false),
+            (0, true),
+            (1, true),
+            (0, true),
+            (2, false),
+            (0, true),
+        ];
+        let votes = [0, 1, 1, 0, 1, 1];
+        for (leader, produced) in slots {
+            activity.record_slot(validators[leader], produced);
+        }
+        for voter in votes {
+            activity.record_vote(validators[voter]);
+        }
+        for (leader, produced) in slots.into_iter().rev() {
+            reversed.record_slot(validators[leader], produced);
+        }
+        for voter in votes.into_iter().rev() {
+            reversed.record_vote(validators[voter]);
+        }
+
+        // When
+        let rewards = EpochRewards::compute(Epoch::new(3), &config(1_000), &activity);
+        let replayed = EpochRewards::compute(Epoch::new(3), &config(1_000), &reversed);
+
+        // Then
+        // 20 and 10 points out of 30
+        assert_eq!(rewards.reward_of(&validators[0]), 666);
+        assert_eq!(rewards.reward_of(&validators[1]), 333);
+        assert_eq!(rewards.reward_of(&validators[2]), 0);
+        assert_eq!(activity.activity(&validators[2]).missed, 3);
+        assert_eq!(rewards.undistributed, 1);
+        assert_eq!(rewards.distributed(), 999);
+        assert_eq!(rewards.rewards.len(), 3);
+        assert_eq!(rewards, replayed);
+    }
+
+    #[test]
+    fn nothing_is_distributed_without_activity() {
+        // Given
+        let mut activity = EpochActivity::default();
+        let absent = Keypair::generate().pubkey();
+        activity.record_slot(absent, false);
+
+        // When
+        let rewards = EpochRewards::compute(Epoch::new(1), &config(1_000), &activity);
+        let empty = EpochRewards::compute(Epoch::new(1), &config(1_000), &EpochActivity::default());
+
+        // Then
+        assert_eq!(rewards.reward_of(&absent), 0);
+        assert_eq!(rewards.undistributed, 1_000);
+        assert!(empty.rewards.is_empty());
+        assert_eq!(empty.distributed(), 0);
+    }
+}
@@ -0,0 +1,260 @@
+// File: src/validator/self_test.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 20:53:45
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::time::{Duration, Instant};
+
+use tokio::fs::{create_dir_all, read, remove_dir_all};
+use tracing::{debug, info, instrument, warn};
+
+use crate::{
+    account::Wallet,
+    crypto::Keypair,
+    io::{self, get_vault_path, FileWrite, WritePool},
+};
+
+use super::{Error, Result};
+
+/// The message signed by the cryptography probe.
+const PROBE_MESSAGE: &[u8] = b"bifrost self-test";
+/// The balance of the account written by the storage probe.
+const PROBE_PRISMS: u64 = 42;
+/// The folder of the vault where the storage probe writes, removed afterwards.
+const PROBE_FOLDER: &str = "self-test";
+
+/// A part of the validator checked before serving transactions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Subsystem {
+    /// Generating keys, signing and verifying signatures.
+    Crypto,
+    /// Writing, reading and removing files in the folder of the vault.
+    Storage,
+}
+
+/// The outcome of the check of a subsystem.
+#[derive(Debug)]
+pub struct SubsystemCheck {
+    /// The subsystem checked.
+    pub subsystem: Subsystem,
+    /// How long the check took.
+    pub duration: Duration,
+    /// Why the check failed, if it did.
+    pub error: Option<Error>,
+}
+
+/// The outcome of the self-test of the validator.
+#[derive(Debug)]
+pub struct SelfTestReport {
+    /// The checks of every subsystem, in the order they ran.
+    pub checks: Vec<SubsystemCheck>,
+}
+
+impl SelfTestReport {
+    /// Whether every subsystem passed its check.
+    #[must_use]
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|check| check.error.is_none())
+    }
+
+    /// Get the check of a subsystem.
+    #[must_use]
+    pub fn get(&self, subsystem: Subsystem) -> Option<&SubsystemCheck> {
+        self.checks
+            .iter()
+            .find(|check| check.subsystem == subsystem)
+    }
+}
+
+/// Checks that the subsystems of the validator work before serving transactions.
+///
+/// The storage probe writes an account in a scratch folder of the vault, the way the vault
+/// saves its files, reads it back from the disk, then removes the folder. The vault itself
+/// isn't opened: the validator may already hold it.
+///
+/// # Example
+/// ```rust
+/// # use bifrost::validator::self_test;
+/// # async fn start() {
+/// let report = self_test().await;
+/// if !report.passed() {
+///     // refuse to start
+/// }
+/// # }
+/// ```
+#[instrument]
+pub async fn self_test() -> SelfTestReport {
+    info!("running the validator self-test");
+    let crypto_start = Instant::now();
+    let crypto = SubsystemCheck {
+        subsystem: Subsystem::Crypto,
+        error: check_crypto().err(),
+        duration: crypto_start.elapsed(),
+    };
+    let storage_start = Instant::now();
+    let storage = SubsystemCheck {
+        subsystem: Subsystem::Storage,
+        error: check_storage().await.err(),
+        duration: storage_start.elapsed(),
+    };
+    let checks = vec![crypto, storage];
+
+    for check in &checks {
+        match &check.error {
+            None => {
+                debug!(subsystem = ?check.subsystem, duration = ?check.duration, "check passed");
+            }
+            Some(err) => warn!(subsystem = ?check.subsystem, "check failed: {err}"),
+        }
+    }
+
+    SelfTestReport { checks }
+}
+
+#[instrument]
+fn check_crypto() -> Result<()> {
+    debug!("checking cryptography");
+    let key = Keypair::generate();
+    let signature = key.sign(PROBE_MESSAGE);
+    signature.verify(&key.pubkey(), PROBE_MESSAGE)?;
+    if signature.verify(&key.pubkey(), b"another message").is_ok() {
+        return Err(Error::SelfTestFailed {
+            subsystem: Subsystem::Crypto,
+        });
+    }
+
+    Ok(())
+}
+
+#[instrument]
+async fn check_storage() -> Result<()> {
+    debug!("checking storage");
+    let folder = get_vault_path()?.join(PROBE_FOLDER);
+    let path = folder.join("probe");
+    let probe = Wallet::new(PROBE_PRISMS);
+
+    create_dir_all(&folder).await.map_err(io::Error::from)?;
+    let pool = WritePool::new(1, folder.join("commit"));
+    pool.commit(vec![FileWrite::new(&path, &probe)]).await?;
+    let data = read(&path).await.map_err(io::Error::from)?;
+    remove_dir_all(&folder).await.map_err(io::Error::from)?;
+    if borsh::from_slice::<Wallet>(&data).ok() != Some(probe) {
+        warn!("the probe account read doesn't match the one written");
+        return Err(Error::SelfTestFailed {
+            subsystem: Subsystem::Storage,
+        });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use std::assert_matches::assert_matches;
+    use std::fs::{remove_dir_all, write};
+    use std::path::Path;
+
+    use test_log::test;
+
+    use crate::io::{set_vault_path, Vault};
+
+    use super::*;
+    type TestResult = core::result::Result<(), Box<dyn core::error::Error>>;
+
+    #[test(tokio::test)]
+    async fn healthy_validator_passes() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/self-test-1";
+        if Path::new(VAULT).exists() {
+            remove_dir_all(VAULT)?;
+        }
+        set_vault_path(VAULT)?;
+
+        // When
+        let report = self_test().await;
+
+        // Then
+        assert!(report.passed(), "{report:?}");
+        assert_eq!(report.checks.len(), 2);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn broken_storage_is_caught() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/self-test-2";
+        if Path::new(VAULT).is_dir() {
+            remove_dir_all(VAULT)?;
+        }
+        std::fs::create_dir_all("/tmp/bifrost")?;
+        write(VAULT, b"not a folder")?;
+        set_vault_path(VAULT)?;
+
+        // When
+        let report = self_test().await;
+
+        // Then
+        assert!(!report.passed());
+        assert_matches!(
+            report.get(Subsystem::Crypto),
+            Some(SubsystemCheck { error: None, .. })
+        );
+        assert_matches!(
+            report.get(Subsystem::Storage),
+            Some(SubsystemCheck {
+                error: Some(Error::Io(io::Error::FileSystem(_))),
+                ..
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn opened_vault_is_not_touched() -> TestResult {
+        // Given
+        const VAULT: &str = "/tmp/bifrost/self-test-3";
+        if Path::new(VAULT).exists() {
+            remove_dir_all(VAULT)?;
+        }
+        set_vault_path(VAULT)?;
+        let vault = Vault::load_or_create().await?;
+        let root = vault.state_root();
+
+        // When
+        let report = self_test().await;
+
+        // Then
+        assert!(report.passed(), "{report:?}");
+        assert_eq!(vault.state_root(), root);
+        assert!(!Path::new(VAULT).join(PROBE_FOLDER).exists());
+        drop(vault);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,428 @@
+// File: src/validator/slot_clock.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 20:53:04
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use tracing::{debug, instrument, trace, warn};
+
+/// The number of recent slots whose wall-clock drift is kept.
+pub const DRIFT_HISTORY: usize = 8_192;
+
+/// Where the slot clock reads the time from.
+pub trait TimeSource {
+    /// The current monotonic instant, which never goes backwards.
+    fn now(&self) -> Instant;
+    /// The current wall-clock time, as milliseconds since the unix epoch.
+    fn wall_time(&self) -> i64;
+}
+
+/// The time of the host.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl TimeSource for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn wall_time(&self) -> i64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| {
+                i64::try_from(elapsed.as_millis()).unwrap_or(i64::MAX)
+            })
+    }
+}
+
+/// A slot produced by the slot clock.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SlotTick {
+    /// The slot the clock is in.
+    pub slot: u64,
+    /// The timestamp of the slot, in milliseconds since the unix epoch.
+    pub timestamp: i64,
+    /// How far ahead (or behind if negative) the wall clock is from the monotonic clock, in milliseconds.
+    pub drift: i64,
+}
+
+/// Produces the slots from a monotonic clock.
+///
+/// The slots only depend on the time elapsed since the clock started, so a host clock jumping
+/// backwards can't make a slot go back nor stall. The wall clock is only used for the timestamps,
+/// which are frozen while it is behind the last timestamp given.
+#[derive(Clone, Debug)]
+pub struct SlotClock<T: TimeSource> {
+    source: T,
+    /// The duration of a slot.
+    slot_duration: Duration,
+    /// How far back the wall clock may go before it is reported.
+    max_regression: Duration,
+    /// When the clock started.
+    start: Instant,
+    /// The wall-clock time when the clock started.
+    start_wall: i64,
+    /// When the current slot duration took effect.
+    origin: Instant,
+    /// The slot the clock was in when the current slot duration took effect.
+    origin_slot: u64,
+    /// The last timestamp given.
+    last_timestamp: i64,
+    /// Whether the wall clock is currently behind by more than the allowed regression.
+    regressed: bool,
+    /// The wall-clock drift of the recent slots.
+    drifts: BTreeMap<u64, i64>,
+}
+
+impl<T: TimeSource> SlotClock<T> {
+    /// Starts a slot clock.
+    ///
+    /// # Parameters
+    /// * `source` - Where the time is read from,
+    /// * `slot_duration` - The duration of a slot,
+    /// * `max_regression` - How far back the wall clock may go before it is reported.
+    pub fn new(source: T, slot_duration: Duration, max_regression: Duration) -> Self {
+        let start = source.now();
+        let start_wall = source.wall_time();
+        Self {
+            source,
+            slot_duration,
+            max_regression,
+            start,
+            start_wall,
+            origin: start,
+            origin_slot: 0,
+            last_timestamp: start_wall,
+            regressed: false,
+            drifts: BTreeMap::new(),
+        }
+    }
+
+    /// Reads the current slot and its timestamp.
+    ///
+    /// The wall-clock drift is recorded for the slot (only the [`DRIFT_HISTORY`] most recent
+    /// ones are kept). If the wall clock went back by more than the allowed regression,
+    /// a warning is logged and the clock is reported as regressed until the wall clock catches up.
+    #[instrument(skip(self))]
+    pub fn tick(&mut self) -> SlotTick {
+        let now = self.source.now();
+        let slot = self.slot_at(now);
+        let elapsed = now.saturating_duration_since(self.start);
+        let elapsed_ms = i64::try_from(elapsed.as_millis()).unwrap_or(i64::MAX);
+        let wall = self.source.wall_time();
+        let drift = wall.saturating_sub(self.start_wall.saturating_add(elapsed_ms));
+        self.drifts.insert(slot, drift);
+        while self.drifts.len() > DRIFT_HISTORY {
+            self.drifts.pop_first();
+        }
+
+        let regression = self.last_timestamp.saturating_sub(wall);
+        let max_regression = i64::try_from(self.max_regression.as_millis()).unwrap_or(i64::MAX);
+        if regression > max_regression {
+            if !self.regressed {
+                warn!(
+                    slot,
+                    wall,
+                    last_timestamp = self.last_timestamp,
+                    regression,
+                    "the system clock went backwards, freezing the timestamps"
+                );
+            }
+            self.regressed = true;
+        } else if regression <= 0 {
+            if self.regressed {
+                debug!(slot, "the system clock caught up");
+            }
+            self.regressed = false;
+            self.last_timestamp = wall;
+        } else {
+            trace!(slot, regression, "the system clock is slightly behind");
+        }
+
+        SlotTick {
+            slot,
+            timestamp: self.last_timestamp,
+            drift,
+        }
+    }
+
+    /// When a slot ends.
+    ///
+    /// # Parameters
+    /// * `slot` - A slot produced by the clock.
+    pub fn slot_end(&self, slot: u64) -> Instant {
+        let slots = slot.saturating_add(1).saturating_sub(self.origin_slot);
+        let offset = self
+            .slot_duration
+            .saturating_mul(u32::try_from(slots).unwrap_or(u32::MAX));
+
+        self.origin.checked_add(offset).unwrap_or(self.origin)
+    }
+
+    /// Changes the duration of the slots, from the current one which starts over.
+    ///
+    /// # Parameters
+    /// * `slot_duration` - The new duration of a slot.
+    pub fn set_slot_duration(&mut self, slot_duration: Duration) {
+        if slot_duration == self.slot_duration {
+            return;
+        }
+        let now = self.source.now();
+        debug!(?slot_duration, "changing the duration of the slots");
+        self.origin_slot = self.slot_at(now);
+        self.origin = now;
+        self.slot_duration = slot_duration;
+    }
+
+    /// The slot the clock is in at an instant.
+    #[expect(
+        clippy::integer_division,
+        reason = "a slot is only reached once complete"
+    )]
+    fn slot_at(&self, now: Instant) -> u64 {
+        let elapsed = now.saturating_duration_since(self.origin);
+        let slots = u64::try_from(elapsed.as_nanos() / self.slot_duration.as_nanos().max(1))
+            .unwrap_or(u64::MAX);
+
+        self.origin_slot.saturating_add(slots)
+    }
+
+    /// Get the wall-clock drift recorded for a recent slot, in milliseconds.
+    pub fn drift(&self, slot: u64) -> Option<i64> {
+        self.drifts.get(&slot).copied()
+    }
+
+    /// Whether the wall clock is behind the timestamps by more than the allowed regression.
+    pub const fn is_regressed(&self) -> bool {
+        self.regressed
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use std::cell::Cell;
+
+    use test_log::test;
+
+    use super::*;
+
+    const START: i64 = 1_700_000_000_000;
+    const SLOT: Duration = Duration::from_millis(400);
+    const MAX_REGRESSION: Duration = Duration::from_secs(1);
+
+    /// A time source moved by hand.
+    struct MockTime {
+        start: Instant,
+        elapsed: Cell<Duration>,
+        wall: Cell<i64>,
+    }
+
+    impl MockTime {
+        fn new() -> Self {
+            Self {
+                start: Instant::now(),
+                elapsed: Cell::new(Duration::ZERO),
+                wall: Cell::new(START),
+            }
+        }
+
+        /// Moves both clocks forward.
+        fn advance(&self, duration: Duration) {
+            self.elapsed.set(self.elapsed.get() + duration);
+            self.wall
+                .set(self.wall.get() + i64::try_from(duration.as_millis()).unwrap_or(0));
+        }
+
+        /// Moves the wall clock only.
+        fn jump(&self, millis: i64) {
+            self.wall.set(self.wall.get() + millis);
+        }
+    }
+
+    impl TimeSource for &MockTime {
+        fn now(&self) -> Instant {
+            self.start + self.elapsed.get()
+        }
+
+        fn wall_time(&self) -> i64 {
+            self.wall.get()
+        }
+    }
+
+    #[test]
+    fn slots_follow_elapsed_time() {
+        // Given
+        let time = MockTime::new();
+        let mut clock = SlotClock::new(&time, SLOT, MAX_REGRESSION);
+
+        // When
+        let first = clock.tick();
+        time.advance(SLOT * 3);
+        let fourth = clock.tick();
+
+        // Then
+        assert_eq!(
+            first,
+            SlotTick {
+                slot: 0,
+                timestamp: START,
+                drift: 0
+            }
+        );
+        assert_eq!(
+            fourth,
+            SlotTick {
+                slot: 3,
+                timestamp: START + 1_200,
+                drift: 0
+            }
+        );
+        assert!(!clock.is_regressed());
+    }
+
+    #[test]
+    fn drift_is_recorded_per_slot() {
+        // Given
+        let time = MockTime::new();
+        let mut clock = SlotClock::new(&time, SLOT, MAX_REGRESSION);
+
+        // When
+        time.advance(SLOT);
+        time.jump(150);
+        clock.tick();
+        time.advance(SLOT);
+        time.jump(-200);
+        clock.tick();
+
+        // Then
+        assert_eq!(clock.drift(1), Some(150));
+        assert_eq!(clock.drift(2), Some(-50));
+        assert_eq!(clock.drift(3), None);
+        assert!(!clock.is_regressed());
+    }
+
+    #[test]
+    fn small_regressions_never_go_backwards() {
+        // Given
+        let time = MockTime::new();
+        let mut clock = SlotClock::new(&time, SLOT, MAX_REGRESSION);
+        time.advance(SLOT);
+        let before = clock.tick();
+
+        // When
+        time.advance(SLOT);
+        time.jump(-500);
+        let after = clock.tick();
+
+        // Then
+        assert_eq!(after.slot, 2);
+        assert_eq!(after.timestamp, before.timestamp);
+        assert!(!clock.is_regressed());
+    }
+
+    #[test]
+    fn large_regressions_freeze_timestamps() {
+        // Given
+        let time = MockTime::new();
+        let mut clock = SlotClock::new(&time, SLOT, MAX_REGRESSION);
+        time.advance(SLOT);
+        let before = clock.tick();
+
+        // When
+        time.advance(SLOT);
+        time.jump(-60_000);
+        let frozen = clock.tick();
+        time.advance(SLOT);
+        let still_frozen = clock.tick();
+
+        // Then
+        assert_eq!((frozen.slot, still_frozen.slot), (2, 3));
+        assert_eq!(frozen.timestamp, before.timestamp);
+        assert_eq!(still_frozen.timestamp, before.timestamp);
+        assert_eq!(clock.drift(2), Some(-60_000));
+        assert!(clock.is_regressed());
+    }
+
+    #[test]
+    fn clock_recovers_once_wall_time_catches_up() {
+        // Given
+        let time = MockTime::new();
+        let mut clock = SlotClock::new(&time, SLOT, MAX_REGRESSION);
+        time.advance(SLOT);
+        clock.tick();
+        time.jump(-5_000);
+        clock.tick();
+
+        // When
+        time.jump(5_400);
+        let recovered = clock.tick();
+
+        // Then
+        assert_eq!(recovered.timestamp, START + 800);
+        assert!(!clock.is_regressed());
+    }
+
+    #[test]
+    fn slot_end_follows_the_slot_duration() {
+        // Given
+        let time = MockTime::new();
+        let mut clock = SlotClock::new(&time, SLOT, MAX_REGRESSION);
+        time.advance(SLOT * 2);
+        let before = clock.tick();
+
+        // When
+        clock.set_slot_duration(SLOT * 2);
+        let restarted = clock.tick();
+        time.advance(SLOT * 3);
+        let after = clock.tick();
+
+        // Then
+        assert_eq!((before.slot, restarted.slot, after.slot), (2, 2, 3));
+        assert_eq!(clock.slot_end(2), time.start + SLOT * 4);
+        assert_eq!(clock.slot_end(3), time.start + SLOT * 6);
+    }
+
+    #[test]
+    fn only_recent_drifts_are_kept() {
+        // Given
+        let time = MockTime::new();
+        let mut clock = SlotClock::new(&time, SLOT, MAX_REGRESSION);
+
+        // When
+        for _ in 0..=DRIFT_HISTORY {
+            clock.tick();
+            time.advance(SLOT);
+        }
+
+        // Then
+        assert_eq!(clock.drift(0), None);
+        assert_eq!(clock.drift(1), Some(0));
+        assert_eq!(clock.drift(DRIFT_HISTORY as u64), Some(0));
+    }
+}
@@ -3,7 +3,7 @@
 // Creation date: Saturday 15 February 2025
 // Author: Vincent Berthier <vincent.berthier@posteo.org>
 // -----
-// Last modified: Saturday 15 February 2025 @ 23:29:55
+// Last modified: Thursday 15 October 2026 @ 20:53:04
 // Modified by: Vincent Berthier
 // -----
 // Copyright (c) 2025 <Vincent Berthier>
@@ -26,39 +26,257 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use std::sync::{Arc, LazyLock};
+use std::collections::{hash_map::Entry, BTreeMap, HashMap, VecDeque};
+use std::sync::{
+    atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+    Arc, LazyLock, Mutex, MutexGuard,
+};
 
 use async_channel::{unbounded, Receiver, Sender};
-use tokio::sync::mpsc::Sender as TSender;
+use tokio::{
+    sync::{mpsc::Sender as TSender, Notify},
+    time::{Duration, Instant},
+};
+use tracing::{debug, instrument, trace};
 
-use crate::transaction::Transaction;
+use crate::{
+    account::AccountPrivileges,
+    crypto::{Pubkey, Signature},
+    transaction::{estimate_size, Bundle, Transaction},
+};
+
+use super::{
+    latency::{LatencyStats, Stage, Timings},
+    memory::MemoryTracker,
+    pipeline::Scheduler,
+    BlockHash, MemoryBudget, MemoryUsage, QueuePolicy, Result,
+};
 
 pub static TRANSACTION_QUEUE: LazyLock<TransactionQueue> = LazyLock::new(TransactionQueue::new);
 
+/// Where a transaction stands.
 #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
 pub enum Status {
+    /// A program failed with its own error code, executing the given instruction.
+    CustomError {
+        /// The position of the instruction in the transaction.
+        instruction: usize,
+        /// The program's own error code.
+        code: u32,
+    },
+    /// The execution failed.
     Failed,
+    /// Waiting to be executed.
     #[default]
     Pending,
+    /// Not admitted by a policy, for the given reason.
+    Rejected(&'static str),
+    /// Being executed.
     Running,
+    /// Executed and committed to the vault.
+    Succeeded,
+}
+
+/// A transaction received by the processor, and where to send its status.
+pub type QueuedTransaction = (Transaction, TSender<Status>);
+/// A transaction admitted by the processor, with the fee decided then: the fee it's charged.
+pub type AdmittedTransaction = (Transaction, TSender<Status>, u64);
+
+/// Where a bundle of transactions stands.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum BundleStatus {
+    /// The transaction at the given position failed, none of them was committed.
+    Failed {
+        /// The position of the transaction in the bundle.
+        index: usize,
+    },
+    #[default]
+    Pending,
+    /// Not admitted, for the given reason.
+    Rejected(&'static str),
+    /// All the transactions were executed.
     Succeeded,
 }
 
+pub type QueuedBundle = (Bundle, TSender<BundleStatus>);
+
+/// The limit that stopped a batch while transactions were still pending.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlockCap {
+    /// The batch holds the maximum number of transactions.
+    Transactions,
+    /// The next transaction would make the batch too big.
+    Bytes,
+}
+
+/// How many batches were stopped by each cap.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BlockCapStats {
+    /// The number of batches capped by their number of transactions.
+    pub transactions: u64,
+    /// The number of batches capped by their size.
+    pub bytes: u64,
+}
+
+/// Where a pending transaction stands in the processor.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SchedulingState {
+    /// Waiting for its turn.
+    #[default]
+    Waiting,
+    /// Deferred by an admission policy until the given slot.
+    Deferred(u64),
+    /// Held until the previous transactions of its payer are executed, with its sequence number.
+    OutOfSequence(u64),
+    /// Waiting for the running transaction to release an account.
+    BlockedOn(Pubkey),
+    /// Being executed.
+    Running,
+}
+
+/// A transaction sent to the processor that wasn't executed yet, for monitoring.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PendingSummary {
+    /// The first signature of the transaction.
+    pub id: Signature,
+    /// The account paying for the transaction.
+    pub payer: Pubkey,
+    /// The fee estimated when the processor received the transaction, if it did.
+    pub fee: Option<u64>,
+    /// The number of slots since the transaction was sent.
+    pub age: u64,
+    /// The number of accounts referenced by the transaction.
+    pub accounts: usize,
+    /// Where the transaction stands.
+    pub state: SchedulingState,
+}
+
+/// What the queue keeps of a transaction until it's executed.
+#[derive(Clone)]
+struct Tracked {
+    /// The order in which the transaction was sent.
+    arrival: u64,
+    payer: Pubkey,
+    /// The slot during which the transaction was sent.
+    sent: u64,
+    fee: Option<u64>,
+    /// The accounts of the transaction, with their privileges.
+    accounts: Vec<(Pubkey, AccountPrivileges)>,
+    state: SchedulingState,
+    /// When the transaction reached each stage, if the latency is tracked.
+    timings: Option<Timings>,
+    /// The serialized size of the transaction.
+    size: usize,
+    /// Whether the transaction was scheduled in a batch.
+    scheduled: bool,
+}
+
+impl Tracked {
+    /// Get the first account the transaction can't use while another one runs.
+    fn conflict(&self, running: &Self) -> Option<Pubkey> {
+        self.accounts.iter().find_map(|&(key, privileges)| {
+            running
+                .accounts
+                .iter()
+                .any(|&(other, other_privileges)| {
+                    other == key && (privileges.is_writable() || other_privileges.is_writable())
+                })
+                .then_some(key)
+        })
+    }
+}
+
+/// The timings of the executed transactions, and their aggregate.
+#[derive(Default)]
+struct LatencyLog {
+    /// The timings of the last executed transactions, oldest first.
+    recent: VecDeque<(Signature, Timings)>,
+    stats: LatencyStats,
+}
+
+/// Maximum number of executed transactions whose timings are kept.
+pub const MAX_RECENT_TIMINGS: usize = 1024;
+
+/// Maximum number of transactions held for a single payer.
+pub const MAX_HELD_PER_PAYER: usize = 16;
+
+/// The key a client gives a submission, so that its retries aren't queued again.
+pub type IdempotencyKey = u128;
+
+/// How long an idempotency key is remembered by default.
+pub const DEFAULT_IDEMPOTENCY_WINDOW: Duration = Duration::from_secs(120);
+
 pub struct TransactionQueue {
-    sender: Arc<Sender<(Transaction, TSender<Status>)>>,
-    receiver: Arc<Receiver<(Transaction, TSender<Status>)>>,
+    sender: Arc<Sender<QueuedTransaction>>,
+    receiver: Arc<Receiver<QueuedTransaction>>,
+    bundle_sender: Arc<Sender<QueuedBundle>>,
+    bundle_receiver: Arc<Receiver<QueuedBundle>>,
+    /// Whether new transactions are refused.
+    paused: AtomicBool,
+    /// Whether a transaction was ever registered through the public intake.
+    opened: AtomicBool,
+    /// The number of transactions sent that weren't executed yet.
+    outstanding: AtomicUsize,
+    /// Notified when the last outstanding transaction was executed.
+    drained: Notify,
+    /// The number of batches capped by their number of transactions.
+    transactions_caps: AtomicU64,
+    /// The number of batches capped by their size.
+    bytes_caps: AtomicU64,
+    /// The slot the processor is in.
+    slot: AtomicU64,
+    /// Whether the system clock of the processor went backwards.
+    clock_regressed: AtomicBool,
+    /// The number of transactions ever sent, to keep them in order.
+    arrivals: AtomicU64,
+    /// The transactions sent that weren't executed yet.
+    tracked: Mutex<HashMap<Signature, Tracked>>,
+    /// Whether the timings of the transactions are recorded.
+    latency_tracking: AtomicBool,
+    latency: Mutex<LatencyLog>,
+    /// The chain id the transactions must be bound to, if any.
+    chain: Mutex<Option<BlockHash>>,
+    /// The idempotency keys of the recent submissions.
+    idempotency: Mutex<IdempotencyKeys>,
+    /// The memory used by the validator, against its budget.
+    memory: Mutex<MemoryTracker>,
 }
 
 impl TransactionQueue {
-    fn new() -> Self {
+    pub(super) fn new() -> Self {
         let (tx, rx) = unbounded();
+        let (bundle_tx, bundle_rx) = unbounded();
         Self {
             sender: Arc::new(tx),
             receiver: Arc::new(rx),
+            bundle_sender: Arc::new(bundle_tx),
+            bundle_receiver: Arc::new(bundle_rx),
+            paused: AtomicBool::new(false),
+            opened: AtomicBool::new(false),
+            outstanding: AtomicUsize::new(0),
+            drained: Notify::new(),
+            transactions_caps: AtomicU64::new(0),
+            bytes_caps: AtomicU64::new(0),
+            slot: AtomicU64::new(0),
+            clock_regressed: AtomicBool::new(false),
+            arrivals: AtomicU64::new(0),
+            tracked: Mutex::new(HashMap::new()),
+            latency_tracking: AtomicBool::new(false),
+            latency: Mutex::new(LatencyLog::default()),
+            chain: Mutex::new(None),
+            idempotency: Mutex::new(IdempotencyKeys::new(DEFAULT_IDEMPOTENCY_WINDOW)),
+            memory: Mutex::new(MemoryTracker::default()),
         }
     }
 
-    pub async fn send(&self, transaction: Transaction, status_tx: TSender<Status>) {
+    pub async fn send(
+        &self,
+        transaction: Transaction,
+        status_tx: TSender<Status>,
+        timings: Timings,
+    ) {
+        self.outstanding.fetch_add(1, Ordering::SeqCst);
+        self.track(&transaction, timings);
         #[expect(
             clippy::unwrap_used,
             reason = "can only fail if the validator is terminated"
@@ -66,7 +284,929 @@ impl TransactionQueue {
         self.sender.send((transaction, status_tx)).await.unwrap();
     }
 
-    pub fn get_receiver(&self) -> Arc<Receiver<(Transaction, TSender<Status>)>> {
+    pub fn get_receiver(&self) -> Arc<Receiver<QueuedTransaction>> {
         Arc::clone(&self.receiver)
     }
+
+    /// Sends a bundle to the processor, it counts as a single outstanding transaction.
+    pub async fn send_bundle(
+        &self,
+        bundle: Bundle,
+        status_tx: TSender<BundleStatus>,
+        timings: Timings,
+    ) {
+        self.outstanding.fetch_add(1, Ordering::SeqCst);
+        for transaction in bundle.transactions() {
+            self.track(transaction, timings);
+        }
+        #[expect(
+            clippy::unwrap_used,
+            reason = "can only fail if the validator is terminated"
+        )]
+        self.bundle_sender.send((bundle, status_tx)).await.unwrap();
+    }
+
+    pub fn get_bundle_receiver(&self) -> Arc<Receiver<QueuedBundle>> {
+        Arc::clone(&self.bundle_receiver)
+    }
+
+    /// Marks a transaction received from the queue as executed.
+    pub fn done(&self) {
+        if self.outstanding.fetch_sub(1, Ordering::SeqCst) == 1 {
+            trace!("no more outstanding transactions");
+            self.drained.notify_waiters();
+        }
+    }
+
+    #[instrument(skip(self))]
+    pub fn pause_intake(&self) {
+        debug!("pausing transaction intake");
+        self.paused.store(true, Ordering::SeqCst);
+    }
+
+    #[instrument(skip(self))]
+    pub fn resume_intake(&self) {
+        debug!("resuming transaction intake");
+        self.paused.store(false, Ordering::SeqCst);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
+    /// Records that the public intake received a transaction.
+    pub fn open_intake(&self) {
+        if !self.opened.swap(true, Ordering::SeqCst) {
+            debug!("the public intake is open");
+        }
+    }
+
+    /// Whether the public intake ever received a transaction.
+    pub fn is_open(&self) -> bool {
+        self.opened.load(Ordering::SeqCst)
+    }
+
+    /// Records whether the system clock of the processor is behind its timestamps.
+    pub fn set_clock_regressed(&self, regressed: bool) {
+        self.clock_regressed.store(regressed, Ordering::SeqCst);
+    }
+
+    /// Whether the system clock of the processor is behind its timestamps.
+    pub fn is_clock_regressed(&self) -> bool {
+        self.clock_regressed.load(Ordering::SeqCst)
+    }
+
+    /// Get the number of transactions waiting to be executed (besides the one running), in batches.
+    ///
+    /// # Parameters
+    /// * `batch_size` - The maximum number of transactions in a batch.
+    #[expect(
+        clippy::cast_precision_loss,
+        reason = "the pressure doesn't need to be exact"
+    )]
+    pub fn pressure(&self, batch_size: usize) -> f64 {
+        let waiting = self.outstanding.load(Ordering::SeqCst).saturating_sub(1);
+        waiting as f64 / batch_size.max(1) as f64
+    }
+
+    /// Counts a batch stopped by a cap.
+    pub fn record_cap(&self, cap: BlockCap) {
+        match cap {
+            BlockCap::Transactions => self.transactions_caps.fetch_add(1, Ordering::Relaxed),
+            BlockCap::Bytes => self.bytes_caps.fetch_add(1, Ordering::Relaxed),
+        };
+    }
+
+    /// Get how many batches were stopped by each cap.
+    pub fn cap_stats(&self) -> BlockCapStats {
+        BlockCapStats {
+            transactions: self.transactions_caps.load(Ordering::Relaxed),
+            bytes: self.bytes_caps.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Records the slot the processor is in.
+    pub fn set_slot(&self, slot: u64) {
+        self.slot.store(slot, Ordering::Relaxed);
+    }
+
+    fn lock_tracked(&self) -> MutexGuard<'_, HashMap<Signature, Tracked>> {
+        #[expect(clippy::unwrap_used, reason = "nothing panics while it's locked")]
+        self.tracked.lock().unwrap()
+    }
+
+    /// Keeps what's needed to monitor a transaction until it's executed.
+    fn track(&self, transaction: &Transaction, timings: Timings) {
+        let (Some(&id), Some(&payer)) = (transaction.signature(), transaction.payer()) else {
+            return;
+        };
+        let tracked = Tracked {
+            arrival: self.arrivals.fetch_add(1, Ordering::Relaxed),
+            payer,
+            sent: self.slot.load(Ordering::Relaxed),
+            fee: None,
+            accounts: transaction
+                .message()
+                .accounts
+                .iter()
+                .map(|meta| (*meta.key(), meta.privileges()))
+                .collect(),
+            state: SchedulingState::Waiting,
+            timings: self
+                .latency_tracking
+                .load(Ordering::Relaxed)
+                .then_some(timings),
+            size: estimate_size(transaction.message()),
+            scheduled: false,
+        };
+        self.lock_tracked().insert(id, tracked);
+    }
+
+    /// Records the fee estimated for a transaction.
+    pub fn set_fee(&self, id: &Signature, fee: u64) {
+        if let Some(tracked) = self.lock_tracked().get_mut(id) {
+            tracked.fee = Some(fee);
+        }
+    }
+
+    /// Records where a transaction stands.
+    pub fn set_state(&self, id: &Signature, state: SchedulingState) {
+        if let Some(tracked) = self.lock_tracked().get_mut(id) {
+            tracked.state = state;
+        }
+    }
+
+    /// Records that a transaction reached a stage, if the latency is tracked.
+    pub fn stamp(&self, id: &Signature, stage: Stage) {
+        if let Some(timings) = self
+            .lock_tracked()
+            .get_mut(id)
+            .and_then(|tracked| tracked.timings.as_mut())
+        {
+            timings.record(stage);
+        }
+    }
+
+    /// Marks a transaction as scheduled in a batch, its memory is now in flight.
+    pub fn schedule(&self, id: &Signature) {
+        let Some(size) = self
+            .lock_tracked()
+            .get_mut(id)
+            .filter(|tracked| !tracked.scheduled)
+            .map(|tracked| {
+                tracked.scheduled = true;
+                if let Some(timings) = tracked.timings.as_mut() {
+                    timings.record(Stage::Scheduled);
+                }
+                tracked.size
+            })
+        else {
+            return;
+        };
+        self.lock_memory().schedule(size);
+    }
+
+    /// Stops monitoring a transaction, once it was executed or rejected, releasing its memory.
+    ///
+    /// Returns its timings so far, if the latency is tracked.
+    pub fn untrack(&self, id: &Signature) -> Option<Timings> {
+        let tracked = self.lock_tracked().remove(id)?;
+        self.lock_memory().release(tracked.size, tracked.scheduled);
+        tracked.timings
+    }
+
+    /// Sets whether the time the transactions take to go through the validator is recorded.
+    pub fn set_latency_tracking(&self, enabled: bool) {
+        self.latency_tracking.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Sets the chain id the transactions must be bound to.
+    pub fn set_chain(&self, chain: Option<BlockHash>) {
+        *self.lock_chain() = chain;
+    }
+
+    /// The chain id the transactions must be bound to, if any.
+    pub fn chain(&self) -> Option<BlockHash> {
+        *self.lock_chain()
+    }
+
+    fn lock_chain(&self) -> MutexGuard<'_, Option<BlockHash>> {
+        #[expect(clippy::unwrap_used, reason = "nothing panics while it's locked")]
+        self.chain.lock().unwrap()
+    }
+
+    /// Sets how long the idempotency keys are remembered.
+    pub fn set_idempotency_window(&self, window: Duration) {
+        self.lock_idempotency().set_window(window);
+    }
+
+    /// Records the idempotency key of a submission, unless the payer already used it.
+    ///
+    /// # Errors
+    /// Gives the signature of the transaction first submitted with the key.
+    pub fn claim_key(
+        &self,
+        payer: Pubkey,
+        key: IdempotencyKey,
+        id: Signature,
+    ) -> core::result::Result<(), Signature> {
+        self.lock_idempotency()
+            .claim(payer, key, id, Instant::now())
+    }
+
+    /// Forgets the idempotency key of a submission that was refused.
+    pub fn release_key(&self, payer: Pubkey, key: IdempotencyKey, id: &Signature) {
+        self.lock_idempotency().release(payer, key, id);
+    }
+
+    fn lock_idempotency(&self) -> MutexGuard<'_, IdempotencyKeys> {
+        #[expect(clippy::unwrap_used, reason = "nothing panics while it's locked")]
+        self.idempotency.lock().unwrap()
+    }
+
+    fn lock_memory(&self) -> MutexGuard<'_, MemoryTracker> {
+        #[expect(clippy::unwrap_used, reason = "nothing panics while it's locked")]
+        self.memory.lock().unwrap()
+    }
+
+    /// Sets the memory the validator may use.
+    pub fn set_memory_budget(&self, budget: MemoryBudget) {
+        self.lock_memory().set_budget(budget);
+    }
+
+    /// Accounts for the memory of transactions about to be sent, unless the validator
+    /// is short of it.
+    ///
+    /// # Parameters
+    /// * `bytes` - The serialized size of the transactions.
+    ///
+    /// # Errors
+    /// If the queue is full, or if the validator is close to its global cap.
+    pub fn reserve_memory(&self, bytes: usize) -> Result<()> {
+        self.lock_memory().reserve(bytes)
+    }
+
+    /// Records the memory used by the account cache, and gives the capacity it's left with.
+    pub fn account_cache(&self, bytes: usize) -> usize {
+        let mut memory = self.lock_memory();
+        memory.set_account_cache(bytes);
+        memory.cache_capacity()
+    }
+
+    /// The maximum serialized size of the next batch.
+    ///
+    /// # Parameters
+    /// * `max_block_bytes` - The maximum size of a block.
+    pub fn batch_bytes(&self, max_block_bytes: usize) -> usize {
+        self.lock_memory().batch_bytes(max_block_bytes)
+    }
+
+    /// Get the memory used by each component of the validator.
+    pub fn memory_usage(&self) -> MemoryUsage {
+        self.lock_memory().usage()
+    }
+
+    fn lock_latency(&self) -> MutexGuard<'_, LatencyLog> {
+        #[expect(clippy::unwrap_used, reason = "nothing panics while it's locked")]
+        self.latency.lock().unwrap()
+    }
+
+    /// Keeps the timings of an executed transaction, and adds them to the aggregate.
+    pub fn record_timings(&self, id: Signature, timings: Timings) {
+        let mut log = self.lock_latency();
+        log.stats.record(&timings);
+        if log.recent.len() == MAX_RECENT_TIMINGS {
+            log.recent.pop_front();
+        }
+        log.recent.push_back((id, timings));
+    }
+
+    /// Get the timings of a recently executed transaction, if they were recorded.
+    pub fn timings(&self, id: &Signature) -> Option<Timings> {
+        self.lock_latency()
+            .recent
+            .iter()
+            .rev()
+            .find_map(|(other, timings)| (other == id).then_some(*timings))
+    }
+
+    /// Get the distribution of the time the executed transactions spent reaching each stage.
+    pub fn latency_stats(&self) -> LatencyStats {
+        self.lock_latency().stats
+    }
+
+    /// Lists the transactions sent that weren't executed yet, in their order of arrival.
+    ///
+    /// Only their metadata is copied while the queue is locked, the summaries are built
+    /// afterwards. The transactions waiting for an account the running one uses are
+    /// reported as blocked on it.
+    pub fn snapshot(&self) -> Vec<PendingSummary> {
+        let slot = self.slot.load(Ordering::Relaxed);
+        let mut tracked = self
+            .lock_tracked()
+            .iter()
+            .map(|(id, tracked)| (*id, tracked.clone()))
+            .collect::<Vec<_>>();
+        tracked.sort_by_key(|(_, tracked)| tracked.arrival);
+        let running = tracked
+            .iter()
+            .filter(|(_, tracked)| tracked.state == SchedulingState::Running)
+            .map(|(_, tracked)| tracked.clone())
+            .collect::<Vec<_>>();
+
+        tracked
+            .into_iter()
+            .map(|(id, tracked)| {
+                let blocked_on = running
+                    .iter()
+                    .find_map(|running| tracked.conflict(running))
+                    .filter(|_| tracked.state == SchedulingState::Waiting);
+                PendingSummary {
+                    id,
+                    payer: tracked.payer,
+                    fee: tracked.fee,
+                    age: slot.saturating_sub(tracked.sent),
+                    accounts: tracked.accounts.len(),
+                    state: blocked_on.map_or(tracked.state, SchedulingState::BlockedOn),
+                }
+            })
+            .collect()
+    }
+
+    /// Pauses the intake and waits until every transaction already sent was executed.
+    #[instrument(skip(self))]
+    pub async fn drain(&self) {
+        self.pause_intake();
+        loop {
+            // created before checking the count so a notification can't be missed
+            let drained = self.drained.notified();
+            if self.outstanding.load(Ordering::SeqCst) == 0 {
+                debug!("transaction queue drained");
+                return;
+            }
+            drained.await;
+        }
+    }
+}
+
+/// Transactions received by the processor that weren't executed yet.
+pub enum PendingTransactions {
+    /// All transactions in their order of arrival.
+    Fifo(VecDeque<AdmittedTransaction>),
+    /// The transactions grouped by payer.
+    FairByPayer {
+        /// The payers having pending transactions, in the order they'll be served.
+        payers: VecDeque<Pubkey>,
+        /// The pending transactions of each payer, in their order of arrival.
+        queues: HashMap<Pubkey, VecDeque<AdmittedTransaction>>,
+    },
+}
+
+impl PendingTransactions {
+    pub fn new(policy: QueuePolicy) -> Self {
+        match policy {
+            QueuePolicy::Fifo => Self::Fifo(VecDeque::new()),
+            QueuePolicy::FairByPayer => Self::FairByPayer {
+                payers: VecDeque::new(),
+                queues: HashMap::new(),
+            },
+        }
+    }
+}
+
+impl Scheduler for PendingTransactions {
+    fn is_empty(&self) -> bool {
+        match self {
+            Self::Fifo(queue) => queue.is_empty(),
+            Self::FairByPayer { payers, .. } => payers.is_empty(),
+        }
+    }
+
+    #[expect(
+        clippy::unwrap_used,
+        reason = "queued transactions are valid, so they have a payer"
+    )]
+    #[instrument(skip_all)]
+    fn push(&mut self, transaction: AdmittedTransaction) {
+        trace!("adding transaction to the pending ones");
+        match self {
+            Self::Fifo(queue) => queue.push_back(transaction),
+            Self::FairByPayer { payers, queues } => {
+                let payer = *transaction.0.payer().unwrap();
+                let queue = queues.entry(payer).or_default();
+                if queue.is_empty() {
+                    payers.push_back(payer);
+                }
+                queue.push_back(transaction);
+            }
+        }
+    }
+
+    /// Takes the next batch of transactions to execute: the transactions of the next block.
+    ///
+    /// With the `FairByPayer` policy, the payers are served round-robin, starting
+    /// where the previous batch stopped. When only one payer has pending
+    /// transactions, this is the same as FIFO.
+    ///
+    /// The transactions that don't fit in the batch stay pending, in the same order,
+    /// for the next one. A transaction bigger than the byte limit still gets a batch
+    /// of its own so it can't block the queue.
+    ///
+    /// # Parameters
+    /// * `size` - The maximum number of transactions in the batch,
+    /// * `max_bytes` - The maximum serialized size of the transactions of the batch.
+    ///
+    /// # Returns
+    /// The batch, and the cap it hit if transactions were left pending.
+    #[instrument(skip(self))]
+    fn next_batch(
+        &mut self,
+        size: usize,
+        max_bytes: usize,
+    ) -> (Vec<AdmittedTransaction>, Option<BlockCap>) {
+        debug!("building next batch of transactions");
+        let mut batch = Vec::with_capacity(size);
+        let mut bytes = 0_usize;
+        let mut fits = |taken: &[AdmittedTransaction], trx: &Transaction| {
+            let trx_bytes = estimate_size(trx.message());
+            if !taken.is_empty() && bytes.saturating_add(trx_bytes) > max_bytes {
+                return false;
+            }
+            bytes = bytes.saturating_add(trx_bytes);
+            true
+        };
+        let mut cap = None;
+        match self {
+            Self::Fifo(queue) => {
+                while batch.len() < size {
+                    let Some((trx, ..)) = queue.front() else {
+                        break;
+                    };
+                    if !fits(&batch, trx) {
+                        cap = Some(BlockCap::Bytes);
+                        break;
+                    }
+                    batch.extend(queue.pop_front());
+                }
+            }
+            Self::FairByPayer { payers, queues } => {
+                while batch.len() < size {
+                    let Some(&payer) = payers.front() else {
+                        break;
+                    };
+                    let Some(queue) = queues.get_mut(&payer) else {
+                        payers.pop_front();
+                        continue;
+                    };
+                    if queue.front().is_some_and(|(trx, ..)| !fits(&batch, trx)) {
+                        // the payer stays first in line for the next batch
+                        cap = Some(BlockCap::Bytes);
+                        break;
+                    }
+                    payers.pop_front();
+                    batch.extend(queue.pop_front());
+                    if queue.is_empty() {
+                        queues.remove(&payer);
+                    } else {
+                        payers.push_back(payer);
+                    }
+                }
+            }
+        }
+        if cap.is_none() && batch.len() == size && !self.is_empty() {
+            cap = Some(BlockCap::Transactions);
+        }
+        if let Some(cap) = cap {
+            trace!(?cap, "the batch is full");
+        }
+
+        (batch, cap)
+    }
+}
+
+/// Transactions whose sequence number is ahead of their payer's,
+/// held until the missing ones are executed.
+pub struct SequenceBuffer {
+    /// How long a transaction can be held.
+    timeout: Duration,
+    /// The held transactions of each payer by sequence number, with their deadline.
+    held: HashMap<Pubkey, BTreeMap<u64, (AdmittedTransaction, Instant)>>,
+}
+
+impl SequenceBuffer {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            held: HashMap::new(),
+        }
+    }
+
+    /// Changes how long the transactions held from now on wait for their turn.
+    pub const fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Holds a transaction until its turn comes.
+    ///
+    /// # Errors
+    /// Gives the transaction back if too many are already held for the payer,
+    /// or if one with the same sequence number is.
+    #[instrument(skip(self, transaction))]
+    pub fn hold(
+        &mut self,
+        payer: Pubkey,
+        sequence: u64,
+        transaction: AdmittedTransaction,
+    ) -> core::result::Result<(), AdmittedTransaction> {
+        debug!("holding transaction");
+        let held = self.held.entry(payer).or_default();
+        if held.len() >= MAX_HELD_PER_PAYER || held.contains_key(&sequence) {
+            trace!("cannot hold the transaction");
+            return Err(transaction);
+        }
+        held.insert(sequence, (transaction, Instant::now() + self.timeout));
+
+        Ok(())
+    }
+
+    /// Takes the held transaction of a payer with the given sequence number.
+    pub fn take(&mut self, payer: &Pubkey, sequence: u64) -> Option<AdmittedTransaction> {
+        let held = self.held.get_mut(payer)?;
+        let transaction = held.remove(&sequence).map(|(transaction, _)| transaction);
+        if held.is_empty() {
+            self.held.remove(payer);
+        }
+        transaction
+    }
+
+    /// When the next held transaction expires.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.held
+            .values()
+            .flat_map(BTreeMap::values)
+            .map(|(_, deadline)| *deadline)
+            .min()
+    }
+
+    /// Takes the transactions held for longer than the timeout.
+    pub fn expired(&mut self, now: Instant) -> Vec<AdmittedTransaction> {
+        let mut expired = Vec::new();
+        self.held.retain(|_, held| {
+            let sequences = held
+                .iter()
+                .filter(|(_, (_, deadline))| *deadline <= now)
+                .map(|(&sequence, _)| sequence)
+                .collect::<Vec<_>>();
+            for sequence in sequences {
+                if let Some((transaction, _)) = held.remove(&sequence) {
+                    expired.push(transaction);
+                }
+            }
+            !held.is_empty()
+        });
+
+        expired
+    }
+}
+
+/// The idempotency keys of the recent submissions, scoped by payer.
+pub struct IdempotencyKeys {
+    /// How long a key is remembered.
+    window: Duration,
+    /// The transaction first submitted with each key, and when.
+    keys: HashMap<(Pubkey, IdempotencyKey), (Signature, Instant)>,
+}
+
+impl IdempotencyKeys {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            keys: HashMap::new(),
+        }
+    }
+
+    /// Changes how long the keys are remembered.
+    pub const fn set_window(&mut self, window: Duration) {
+        self.window = window;
+    }
+
+    /// Records a key on its first sight, forgetting those older than the window.
+    ///
+    /// # Errors
+    /// Gives the signature of the transaction first submitted with the key,
+    /// if the payer used it within the window.
+    #[instrument(skip(self, id, now))]
+    pub fn claim(
+        &mut self,
+        payer: Pubkey,
+        key: IdempotencyKey,
+        id: Signature,
+        now: Instant,
+    ) -> core::result::Result<(), Signature> {
+        let window = self.window;
+        self.keys
+            .retain(|_, (_, seen)| now.saturating_duration_since(*seen) < window);
+        match self.keys.entry((payer, key)) {
+            Entry::Occupied(entry) => {
+                trace!("the key was already used");
+                Err(entry.get().0)
+            }
+            Entry::Vacant(entry) => {
+                entry.insert((id, now));
+                Ok(())
+            }
+        }
+    }
+
+    /// Forgets a key, if it's still held by the given transaction.
+    pub fn release(&mut self, payer: Pubkey, key: IdempotencyKey, id: &Signature) {
+        if self
+            .keys
+            .get(&(payer, key))
+            .is_some_and(|(held, _)| held == id)
+        {
+            self.keys.remove(&(payer, key));
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use std::assert_matches::assert_matches;
+
+    use test_log::test;
+    use tokio::sync::mpsc::channel;
+
+    use crate::account::{AccountMeta, Writable};
+    use crate::crypto::Keypair;
+    use crate::transaction::Instruction;
+
+    use super::*;
+    type TestResult = core::result::Result<(), Box<dyn core::error::Error>>;
+    type Result<T> = core::result::Result<T, Box<dyn core::error::Error>>;
+
+    const PROGRAM: Pubkey = Pubkey::from_bytes(&[2; 32]);
+
+    fn queued_transaction(payer: Pubkey, id: u8) -> Result<AdmittedTransaction> {
+        let mut trx = Transaction::new(0);
+        trx.add(&[Instruction::new(
+            PROGRAM,
+            vec![AccountMeta::signing(payer, Writable::Yes)?],
+            &id,
+        )])?;
+        let (tx, _rx) = channel(1);
+
+        Ok((trx, tx, 0))
+    }
+
+    fn queued(payer: Pubkey, count: u8) -> Result<PendingTransactions> {
+        let mut pending = PendingTransactions::new(QueuePolicy::Fifo);
+        for id in 0..count {
+            pending.push(queued_transaction(payer, id)?);
+        }
+
+        Ok(pending)
+    }
+
+    fn flood(policy: QueuePolicy) -> Result<(PendingTransactions, Pubkey)> {
+        let flooder = Keypair::generate().pubkey();
+        let payer = Keypair::generate().pubkey();
+        let mut pending = PendingTransactions::new(policy);
+        for _ in 0..1_000_u16 {
+            pending.push(queued_transaction(flooder, 0)?);
+        }
+        pending.push(queued_transaction(payer, 0)?);
+
+        Ok((pending, payer))
+    }
+
+    fn batch_of(pending: &mut PendingTransactions, payer: &Pubkey) -> Option<usize> {
+        let mut batch = 0;
+        while !pending.is_empty() {
+            if pending
+                .next_batch(64, usize::MAX)
+                .0
+                .iter()
+                .any(|(trx, ..)| trx.payer() == Some(payer))
+            {
+                return Some(batch);
+            }
+            batch += 1;
+        }
+
+        None
+    }
+
+    #[test]
+    fn fair_policy_serves_small_payer_early() -> TestResult {
+        // Given
+        let (mut pending, payer) = flood(QueuePolicy::FairByPayer)?;
+
+        // When
+        let batch = batch_of(&mut pending, &payer);
+
+        // Then
+        assert!(batch.is_some_and(|batch| batch < 2), "{batch:?}");
+
+        Ok(())
+    }
+
+    #[test]
+    fn fifo_policy_serves_in_arrival_order() -> TestResult {
+        // Given
+        let (mut pending, payer) = flood(QueuePolicy::Fifo)?;
+
+        // When
+        let batch = batch_of(&mut pending, &payer);
+
+        // Then
+        assert_eq!(batch, Some(15));
+
+        Ok(())
+    }
+
+    #[test]
+    fn fair_policy_keeps_payer_order() -> TestResult {
+        // Given
+        let payer1 = Keypair::generate().pubkey();
+        let payer2 = Keypair::generate().pubkey();
+        let mut pending = PendingTransactions::new(QueuePolicy::FairByPayer);
+        for (id, payer) in [payer1, payer1, payer1, payer2].into_iter().enumerate() {
+            pending.push(queued_transaction(payer, u8::try_from(id)?)?);
+        }
+
+        // When
+        let (batch, _) = pending.next_batch(3, usize::MAX);
+        let (rest, _) = pending.next_batch(3, usize::MAX);
+
+        // Then
+        let order = batch
+            .iter()
+            .chain(rest.iter())
+            .map(|(trx, ..)| trx.message().instructions[0].data.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(order, vec![vec![0], vec![3], vec![1], vec![2]]);
+        assert!(pending.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn capped_batches_roll_over_in_order() -> TestResult {
+        // Given
+        let payer1 = Keypair::generate().pubkey();
+        let payer2 = Keypair::generate().pubkey();
+        let mut fifo = PendingTransactions::new(QueuePolicy::Fifo);
+        let mut fair = PendingTransactions::new(QueuePolicy::FairByPayer);
+        for (id, payer) in [payer1, payer1, payer1, payer2, payer2]
+            .into_iter()
+            .enumerate()
+        {
+            fifo.push(queued_transaction(payer, u8::try_from(id)?)?);
+            fair.push(queued_transaction(payer, u8::try_from(id)?)?);
+        }
+        let (sample, ..) = queued_transaction(payer1, 0)?;
+        let max_bytes = 2 * estimate_size(sample.message());
+
+        // When
+        let mut batches = Vec::new();
+        for pending in [&mut fifo, &mut fair] {
+            let mut order = Vec::new();
+            while !pending.is_empty() {
+                let (batch, cap) = pending.next_batch(3, max_bytes);
+                order.push((
+                    batch
+                        .iter()
+                        .map(|(trx, ..)| trx.message().instructions[0].data[0])
+                        .collect::<Vec<_>>(),
+                    cap,
+                ));
+            }
+            batches.push(order);
+        }
+        let (by_count, count_cap) = queued(payer1, 5)?.next_batch(3, usize::MAX);
+
+        // Then
+        assert_eq!(
+            batches,
+            vec![
+                vec![
+                    (vec![0, 1], Some(BlockCap::Bytes)),
+                    (vec![2, 3], Some(BlockCap::Bytes)),
+                    (vec![4], None),
+                ],
+                vec![
+                    (vec![0, 3], Some(BlockCap::Bytes)),
+                    (vec![1, 4], Some(BlockCap::Bytes)),
+                    (vec![2], None),
+                ],
+            ]
+        );
+        assert_eq!(
+            (by_count.len(), count_cap),
+            (3, Some(BlockCap::Transactions))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn sequence_buffer_is_bounded_and_expires() -> TestResult {
+        // Given
+        let payer = Keypair::generate().pubkey();
+        let mut buffer = SequenceBuffer::new(Duration::from_secs(1));
+        for sequence in 0..MAX_HELD_PER_PAYER as u64 {
+            let held = buffer.hold(payer, sequence + 2, queued_transaction(payer, 0)?);
+            assert_matches!(held, Ok(()));
+        }
+
+        // When
+        let overflow = buffer.hold(payer, 100, queued_transaction(payer, 0)?);
+        let taken = buffer.take(&payer, 2);
+        let early = buffer.expired(Instant::now());
+        let late = buffer.expired(Instant::now() + Duration::from_secs(2));
+
+        // Then
+        assert_matches!(overflow, Err(_));
+        assert!(taken.is_some());
+        assert!(early.is_empty());
+        assert_eq!(late.len(), MAX_HELD_PER_PAYER - 1);
+        assert_eq!(buffer.next_deadline(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn read_only_signers_only_take_a_read_lock() -> TestResult {
+        // Given
+        let witness = Keypair::generate().pubkey();
+        let tracked = |writable| -> Result<Tracked> {
+            let mut trx = Transaction::new(0);
+            trx.add(&[Instruction::new(
+                PROGRAM,
+                vec![
+                    AccountMeta::signing(Keypair::generate().pubkey(), Writable::Yes)?,
+                    AccountMeta::signing(witness, writable)?,
+                ],
+                &0_u8,
+            )])?;
+            Ok(Tracked {
+                arrival: 0,
+                payer: *trx.payer().ok_or("no payer")?,
+                sent: 0,
+                fee: None,
+                accounts: trx
+                    .message()
+                    .accounts()
+                    .iter()
+                    .map(|meta| (*meta.key(), meta.privileges()))
+                    .collect(),
+                state: SchedulingState::Waiting,
+                timings: None,
+                size: estimate_size(trx.message()),
+                scheduled: false,
+            })
+        };
+        let running = tracked(Writable::No)?;
+
+        // When
+        let reading = tracked(Writable::No)?.conflict(&running);
+        let writing = tracked(Writable::Yes)?.conflict(&running);
+
+        // Then
+        assert_eq!(reading, None);
+        assert_eq!(writing, Some(witness));
+
+        Ok(())
+    }
+
+    #[test]
+    fn idempotency_keys_are_scoped_by_payer_and_expire() {
+        // Given
+        const KEY: IdempotencyKey = 7;
+        let signer = Keypair::generate();
+        let (payer, other) = (signer.pubkey(), Keypair::generate().pubkey());
+        let ids = (0..4_u8).map(|i| signer.sign([i])).collect::<Vec<_>>();
+        let mut keys = IdempotencyKeys::new(Duration::from_secs(10));
+        let start = Instant::now();
+
+        // When
+        let first = keys.claim(payer, KEY, ids[0], start);
+        let replay = keys.claim(payer, KEY, ids[1], start + Duration::from_secs(9));
+        let other_payer = keys.claim(other, KEY, ids[2], start + Duration::from_secs(9));
+        let expired = keys.claim(payer, KEY, ids[3], start + Duration::from_secs(10));
+        keys.release(payer, KEY, &ids[0]);
+        let kept = keys.claim(payer, KEY, ids[0], start + Duration::from_secs(11));
+        keys.release(payer, KEY, &ids[3]);
+        let released = keys.claim(payer, KEY, ids[0], start + Duration::from_secs(11));
+
+        // Then
+        assert_eq!(first, Ok(()));
+        assert_eq!(replay, Err(ids[0]));
+        assert_eq!(other_payer, Ok(()));
+        assert_eq!(expired, Ok(()));
+        assert_eq!(kept, Err(ids[3]));
+        assert_eq!(released, Ok(()));
+    }
 }
@@ -0,0 +1,303 @@
+// File: src/validator/units.rs
+// Project: Bifrost
+// Creation date: Thursday 15 October 2026
+// Author: Vincent Berthier <vincent.berthier@posteo.org>
+// -----
+// Last modified: Thursday 15 October 2026 @ 15:15:37
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2026 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::time::Duration;
+
+use tracing::warn;
+
+use super::{Error, Result};
+
+/// The units a duration can be given in, with their length in nanoseconds.
+const DURATION_UNITS: [(&str, u64); 7] = [
+    ("ns", 1),
+    ("us", 1_000),
+    ("µs", 1_000),
+    ("ms", 1_000_000),
+    ("s", 1_000_000_000),
+    ("min", 60_000_000_000),
+    ("h", 3_600_000_000_000),
+];
+
+/// Builds the error pointing at the offending part of a token.
+fn invalid(token: &str, offset: usize, reason: &'static str) -> Error {
+    warn!(token, offset, reason, "invalid quantity");
+    Error::InvalidQuantity {
+        token: token.to_owned(),
+        offset,
+        reason,
+    }
+}
+
+/// Parses the leading digits of a token, which may be grouped with underscores.
+///
+/// # Returns
+/// The number, and the offset of the first character after it.
+fn parse_integer(token: &str) -> Result<(u64, usize)> {
+    let mut value = 0_u64;
+    let mut end = 0;
+    let mut previous_underscore = false;
+    for (offset, c) in token.char_indices() {
+        match c {
+            '0'..='9' => {
+                value = value
+                    .checked_mul(10)
+                    .and_then(|value| value.checked_add(u64::from(c) - u64::from('0')))
+                    .ok_or_else(|| invalid(token, 0, "the number is too large"))?;
+                previous_underscore = false;
+            }
+            '_' if offset > 0 && !previous_underscore => previous_underscore = true,
+            '_' => return Err(invalid(token, offset, "misplaced digit separator")),
+            _ => break,
+        }
+        end = offset + c.len_utf8();
+    }
+    if end == 0 {
+        return Err(invalid(token, 0, "expected a number"));
+    }
+    if previous_underscore {
+        return Err(invalid(token, end - 1, "misplaced digit separator"));
+    }
+
+    Ok((value, end))
+}
+
+/// Parses a duration made of a number and its unit: `ns`, `us` (or `µs`), `ms`, `s`, `min` or `h`.
+///
+/// # Parameters
+/// * `token` - The text to parse, e.g. `400ms`.
+///
+/// # Errors
+/// If the number or its unit is missing, invalid or ambiguous, or if the duration is too long.
+///
+/// # Example
+/// ```
+/// # use std::time::Duration;
+/// # use bifrost::validator::{parse_duration, Error};
+/// assert_eq!(parse_duration("400ms")?, Duration::from_millis(400));
+/// assert_eq!(parse_duration("5min")?, Duration::from_secs(300));
+/// assert!(parse_duration("400").is_err());
+/// # Ok::<(), Error>(())
+/// ```
+pub fn parse_duration(token: &str) -> Result<Duration> {
+    let (value, end) = parse_integer(token)?;
+    let unit = token.get(end..).unwrap_or_default();
+    if unit.is_empty() {
+        return Err(invalid(
+            token,
+            end,
+            "missing unit (ns, us, ms, s, min or h)",
+        ));
+    }
+    if unit == "m" {
+        return Err(invalid(token, end, "ambiguous unit, use 'ms' or 'min'"));
+    }
+    let (_, nanos) = DURATION_UNITS
+        .iter()
+        .find(|(name, _)| *name == unit)
+        .ok_or_else(|| invalid(token, end, "unknown unit (ns, us, ms, s, min or h)"))?;
+    let nanos = value
+        .checked_mul(*nanos)
+        .ok_or_else(|| invalid(token, 0, "the duration is too long"))?;
+
+    Ok(Duration::from_nanos(nanos))
+}
+
+/// Parses an amount of prisms, whose digits may be grouped with underscores.
+///
+/// # Parameters
+/// * `token` - The text to parse, e.g. `1_000_000`.
+///
+/// # Errors
+/// If the amount isn't a whole number of prisms, or doesn't fit in a `u64`.
+///
+/// # Example
+/// ```
+/// # use bifrost::validator::{parse_prisms, Error};
+/// assert_eq!(parse_prisms("1_000_000")?, 1_000_000);
+/// assert!(parse_prisms("1__000").is_err());
+/// # Ok::<(), Error>(())
+/// ```
+pub fn parse_prisms(token: &str) -> Result<u64> {
+    let (value, end) = parse_integer(token)?;
+    if end < token.len() {
+        return Err(invalid(token, end, "expected a whole number of prisms"));
+    }
+
+    Ok(value)
+}
+
+/// Parses a number of slots, given either as such or as an approximate duration
+/// prefixed by `~`, converted with the duration of a slot (rounded to the nearest slot).
+///
+/// # Parameters
+/// * `token` - The text to parse, e.g. `1_500` or `~2h`,
+/// * `slot_duration` - The duration of a slot.
+///
+/// # Errors
+/// If the number or the duration is invalid, or if the duration of a slot is zero.
+///
+/// # Example
+/// ```
+/// # use std::time::Duration;
+/// # use bifrost::validator::{parse_slots, Error};
+/// let slot = Duration::from_millis(400);
+/// assert_eq!(parse_slots("1_500", slot)?, 1_500);
+/// assert_eq!(parse_slots("~2h", slot)?, 18_000);
+/// # Ok::<(), Error>(())
+/// ```
+pub fn parse_slots(token: &str, slot_duration: Duration) -> Result<u64> {
+    let Some(duration) = token.strip_prefix('~') else {
+        let (value, end) = parse_integer(token)?;
+        if end < token.len() {
+            return Err(invalid(
+                token,
+                end,
+                "expected a number of slots, prefix durations with '~'",
+            ));
+        }
+        return Ok(value);
+    };
+    if slot_duration.is_zero() {
+        return Err(invalid(token, 0, "the duration of a slot is zero"));
+    }
+    let duration = parse_duration(duration).map_err(|err| match err {
+        Error::InvalidQuantity { offset, reason, .. } => invalid(token, offset + 1, reason),
+        err => err,
+    })?;
+    let slot = slot_duration.as_nanos();
+    #[expect(clippy::integer_division, reason = "rounded to the nearest slot")]
+    let slots = duration.as_nanos().saturating_add(slot / 2) / slot;
+
+    u64::try_from(slots).map_err(|_err| invalid(token, 1, "too many slots"))
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use std::assert_matches::assert_matches;
+
+    use test_log::test;
+
+    use super::*;
+
+    type TestResult = core::result::Result<(), Box<dyn core::error::Error>>;
+
+    #[test]
+    fn durations_need_an_unambiguous_unit() -> TestResult {
+        // Given
+        let valid = [
+            ("250ns", Duration::from_nanos(250)),
+            ("3µs", Duration::from_micros(3)),
+            ("400ms", Duration::from_millis(400)),
+            ("2s", Duration::from_secs(2)),
+            ("5min", Duration::from_secs(300)),
+            ("1_000h", Duration::from_secs(3_600_000)),
+        ];
+        let invalid = [
+            ("400", 3, "missing unit (ns, us, ms, s, min or h)"),
+            ("5m", 1, "ambiguous unit, use 'ms' or 'min'"),
+            ("2sec", 1, "unknown unit (ns, us, ms, s, min or h)"),
+            ("ms", 0, "expected a number"),
+            ("1.5s", 1, "unknown unit (ns, us, ms, s, min or h)"),
+            ("_1s", 0, "misplaced digit separator"),
+            ("9_999_999h", 0, "the duration is too long"),
+        ];
+
+        // When / Then
+        for (token, expected) in valid {
+            assert_eq!(parse_duration(token)?, expected, "{token}");
+        }
+        for (token, expected_offset, expected_reason) in invalid {
+            assert_matches!(
+                parse_duration(token),
+                Err(Error::InvalidQuantity { offset, reason, .. })
+                    if offset == expected_offset && reason == expected_reason,
+                "{token}"
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn amounts_are_whole_prisms() -> TestResult {
+        // Given
+        let invalid = [
+            ("1__000", 2, "misplaced digit separator"),
+            ("1_000_", 5, "misplaced digit separator"),
+            ("1.5", 1, "expected a whole number of prisms"),
+            ("10k", 2, "expected a whole number of prisms"),
+            ("-1", 0, "expected a number"),
+            ("18_446_744_073_709_551_616", 0, "the number is too large"),
+        ];
+
+        // When / Then
+        assert_eq!(parse_prisms("1_000_000")?, 1_000_000);
+        assert_eq!(parse_prisms("18_446_744_073_709_551_615")?, u64::MAX);
+        for (token, expected_offset, expected_reason) in invalid {
+            assert_matches!(
+                parse_prisms(token),
+                Err(Error::InvalidQuantity { offset, reason, .. })
+                    if offset == expected_offset && reason == expected_reason,
+                "{token}"
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn approximate_durations_are_converted_to_slots() -> TestResult {
+        // Given
+        let slot = Duration::from_millis(400);
+
+        // When / Then
+        assert_eq!(parse_slots("1_500", slot)?, 1_500);
+        assert_eq!(parse_slots("~2h", slot)?, 18_000);
+        assert_eq!(parse_slots("~1s", slot)?, 3);
+        assert_eq!(parse_slots("~100ms", slot)?, 0);
+        assert_matches!(
+            parse_slots("2h", slot),
+            Err(Error::InvalidQuantity { offset: 1, .. })
+        );
+        assert_matches!(
+            parse_slots("~2m", slot),
+            Err(Error::InvalidQuantity {
+                offset: 2,
+                reason: "ambiguous unit, use 'ms' or 'min'",
+                ..
+            })
+        );
+        assert_matches!(
+            parse_slots("~2h", Duration::ZERO),
+            Err(Error::InvalidQuantity { offset: 0, .. })
+        );
+
+        Ok(())
+    }
+}